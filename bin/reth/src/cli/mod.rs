@@ -14,7 +14,7 @@ use reth_chainspec::ChainSpec;
 use reth_cli_commands::{
     config_cmd, db, dump_genesis, import, init_cmd, init_state,
     node::{self, NoArgs},
-    p2p, prune, recover, stage,
+    p2p, prune, recover, stage, txpool,
 };
 use reth_cli_runner::CliRunner;
 use reth_db::DatabaseEnv;
@@ -172,6 +172,7 @@ impl<Ext: clap::Args + fmt::Debug> Cli<Ext> {
             Commands::Debug(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
             Commands::Recover(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
             Commands::Prune(command) => runner.run_until_ctrl_c(command.execute()),
+            Commands::Txpool(command) => runner.run_until_ctrl_c(command.execute()),
         }
     }
 
@@ -235,6 +236,9 @@ pub enum Commands<Ext: clap::Args + fmt::Debug = NoArgs> {
     /// Prune according to the configuration without any limits
     #[command(name = "prune")]
     Prune(prune::PruneCommand),
+    /// Transaction pool utilities
+    #[command(name = "txpool")]
+    Txpool(txpool::Command),
 }
 
 #[cfg(test)]
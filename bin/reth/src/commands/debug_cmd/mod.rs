@@ -7,6 +7,7 @@ mod build_block;
 mod execution;
 mod in_memory_merkle;
 mod merkle;
+mod parallel_execution;
 mod replay_engine;
 
 /// `reth debug` command
@@ -29,6 +30,8 @@ pub enum Subcommands {
     BuildBlock(build_block::Command),
     /// Debug engine API by replaying stored messages.
     ReplayEngine(replay_engine::Command),
+    /// Debug parallel block execution by comparing it against serial execution.
+    ParallelExecution(parallel_execution::Command),
 }
 
 impl Command {
@@ -40,6 +43,7 @@ impl Command {
             Subcommands::InMemoryMerkle(command) => command.execute(ctx).await,
             Subcommands::BuildBlock(command) => command.execute(ctx).await,
             Subcommands::ReplayEngine(command) => command.execute(ctx).await,
+            Subcommands::ParallelExecution(command) => command.execute(ctx).await,
         }
     }
 }
@@ -0,0 +1,104 @@
+//! Command for comparing parallel block execution against serial execution.
+
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_cli_runner::CliContext;
+use reth_evm::execute::{BlockExecutorProvider, Executor};
+use reth_primitives::BlockNumber;
+use reth_provider::{
+    BlockReader, ChainSpecProvider, HeaderProvider, ProviderError, TransactionVariant,
+};
+use reth_revm::database::StateProviderDatabase;
+use tracing::*;
+
+use crate::macros::block_executor;
+
+/// `reth debug parallel-execution` command
+/// This debug routine executes a range of historical blocks both serially and in parallel and
+/// compares the outputs. The node must have the state history of the range available.
+#[derive(Debug, Parser)]
+pub struct Command {
+    #[command(flatten)]
+    env: EnvironmentArgs,
+
+    /// The first block of the range.
+    #[arg(long)]
+    from: BlockNumber,
+
+    /// The last block of the range.
+    #[arg(long)]
+    to: BlockNumber,
+}
+
+impl Command {
+    /// Execute `debug parallel-execution` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        eyre::ensure!(self.from > 0, "the genesis block can't be executed");
+        eyre::ensure!(self.from <= self.to, "the range must not be empty");
+
+        let Environment { provider_factory, .. } = self.env.init(AccessRights::RO)?;
+        let executor_provider = block_executor!(provider_factory.chain_spec());
+        let provider = provider_factory.provider()?;
+
+        let mut serial_time = Duration::ZERO;
+        let mut parallel_time = Duration::ZERO;
+        let mut diverged = Vec::new();
+        for block_number in self.from..=self.to {
+            let block = provider
+                .block_with_senders(block_number.into(), TransactionVariant::NoHash)?
+                .ok_or_else(|| eyre::eyre!("block {block_number} not found"))?;
+            let total_difficulty = provider
+                .header_td_by_number(block_number)?
+                .ok_or(ProviderError::TotalDifficultyNotFound(block_number))?;
+            let state_provider = provider_factory.history_by_block_number(block_number - 1)?;
+
+            let started_at = Instant::now();
+            let mut serial = executor_provider
+                .executor(StateProviderDatabase::new(&state_provider))
+                .execute((&block, total_difficulty).into())?;
+            serial_time += started_at.elapsed();
+
+            let started_at = Instant::now();
+            let mut parallel = executor_provider.execute_parallel(
+                StateProviderDatabase::new(&state_provider),
+                (&block, total_difficulty).into(),
+            )?;
+            parallel_time += started_at.elapsed();
+
+            // the accounts in the reverts of a block are ordered by hash map iteration
+            serial.state.reverts.sort();
+            parallel.state.reverts.sort();
+
+            if serial == parallel {
+                debug!(target: "reth::cli", block_number, "Parallel execution matches");
+            } else {
+                warn!(
+                    target: "reth::cli",
+                    block_number,
+                    receipts = serial.receipts == parallel.receipts,
+                    requests = serial.requests == parallel.requests,
+                    gas_used = serial.gas_used == parallel.gas_used,
+                    state = serial.state == parallel.state,
+                    "Parallel execution diverged"
+                );
+                diverged.push(block_number);
+            }
+        }
+
+        info!(
+            target: "reth::cli",
+            blocks = self.to - self.from + 1,
+            ?serial_time,
+            ?parallel_time,
+            diverged = diverged.len(),
+            "Compared parallel execution against serial execution"
+        );
+        if !diverged.is_empty() {
+            eyre::bail!("Parallel execution diverged at blocks {diverged:?}")
+        }
+
+        Ok(())
+    }
+}
@@ -1,39 +1,62 @@
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use clap::Parser;
 use eyre::Context;
+use futures::StreamExt;
 use reth_basic_payload_builder::{BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig};
-use reth_beacon_consensus::{hooks::EngineHooks, BeaconConsensusEngine, EthBeaconConsensus};
+use reth_beacon_consensus::{
+    hooks::EngineHooks, BeaconConsensusEngine, BeaconConsensusEngineHandle, EthBeaconConsensus,
+};
 use reth_blockchain_tree::{
     BlockchainTree, BlockchainTreeConfig, ShareableBlockchainTree, TreeExternals,
 };
+use reth_chainspec::ChainSpec;
 use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
 use reth_cli_runner::CliContext;
 use reth_cli_util::get_secret_key;
 use reth_config::Config;
 use reth_consensus::Consensus;
 use reth_db::DatabaseEnv;
-use reth_engine_util::engine_store::{EngineMessageStore, StoredEngineApiMessage};
+use reth_engine_service::service::{ChainEvent, EngineService};
+use reth_engine_tree::tree::TreeConfig;
+use reth_engine_util::engine_store::{
+    EngineMessageStore, StoredEngineApiMessage, StoredEngineApiResponse,
+};
 use reth_fs_util as fs;
 use reth_network::{BlockDownloaderProvider, NetworkHandle};
 use reth_network_api::NetworkInfo;
 use reth_payload_builder::{PayloadBuilderHandle, PayloadBuilderService};
 use reth_provider::{
-    providers::BlockchainProvider, CanonStateSubscriptions, ChainSpecProvider, ProviderFactory,
+    providers::{BlockchainProvider, BlockchainProvider2},
+    BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider, ProviderFactory,
+    StateProviderFactory,
 };
-use reth_prune::PruneModes;
+use reth_prune::{PruneModes, PrunerBuilder};
 use reth_stages::Pipeline;
 use reth_static_file::StaticFileProducer;
 use reth_tasks::TaskExecutor;
+use reth_tokio_util::EventSender;
 use reth_transaction_pool::noop::NoopTransactionPool;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc::unbounded_channel, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::*;
 
 use crate::{args::NetworkArgs, macros::block_executor};
 
+#[cfg(not(feature = "optimism"))]
+type ReplayEngineTypes = reth_node_ethereum::EthEngineTypes;
+
+#[cfg(feature = "optimism")]
+type ReplayEngineTypes = reth_node_optimism::OptimismEngineTypes;
+
 /// `reth debug replay-engine` command
 /// This script will read stored engine API messages and replay them by the timestamp.
-/// It does not require
+/// If the node's responses were recorded along with the messages, any divergence from them is
+/// reported.
 #[derive(Debug, Parser)]
 pub struct Command {
     #[command(flatten)]
@@ -49,6 +72,15 @@ pub struct Command {
     /// The number of milliseconds between Engine API messages.
     #[arg(long = "interval", default_value_t = 1_000)]
     interval: u64,
+
+    /// Replay the messages into the experimental engine tree instead of the legacy consensus
+    /// engine.
+    #[arg(long = "engine.experimental", default_value = "false")]
+    experimental: bool,
+
+    /// The path to write the per-message latency and divergence report to, as JSON.
+    #[arg(long = "report", value_name = "PATH")]
+    report: Option<PathBuf>,
 }
 
 impl Command {
@@ -73,13 +105,57 @@ impl Command {
         Ok(network)
     }
 
-    /// Execute `debug replay-engine` command
-    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
-        let Environment { provider_factory, config, data_dir } = self.env.init(AccessRights::RW)?;
+    /// Spawns the payload builder service on top of the given provider.
+    fn spawn_payload_builder<Provider>(
+        task_executor: &TaskExecutor,
+        provider: Provider,
+        chain_spec: Arc<ChainSpec>,
+    ) -> PayloadBuilderHandle<ReplayEngineTypes>
+    where
+        Provider: StateProviderFactory
+            + BlockReaderIdExt
+            + CanonStateSubscriptions
+            + Clone
+            + Unpin
+            + 'static,
+    {
+        #[cfg(not(feature = "optimism"))]
+        let payload_builder = reth_ethereum_payload_builder::EthereumPayloadBuilder::default();
 
-        let consensus: Arc<dyn Consensus> =
-            Arc::new(EthBeaconConsensus::new(provider_factory.chain_spec()));
+        // Optimism's payload builder is implemented on the OptimismPayloadBuilder type.
+        #[cfg(feature = "optimism")]
+        let payload_builder = reth_node_optimism::OptimismPayloadBuilder::new(
+            reth_node_optimism::OptimismEvmConfig::default(),
+        );
 
+        let canonical_state_stream = provider.canonical_state_stream();
+        let payload_generator = BasicPayloadJobGenerator::with_builder(
+            provider,
+            NoopTransactionPool::default(),
+            task_executor.clone(),
+            BasicPayloadJobGeneratorConfig::default(),
+            chain_spec,
+            payload_builder,
+        );
+
+        let (payload_service, payload_builder) =
+            PayloadBuilderService::new(payload_generator, canonical_state_stream);
+
+        task_executor.spawn_critical("payload builder service", payload_service);
+
+        payload_builder
+    }
+
+    /// Spawns the legacy [`BeaconConsensusEngine`].
+    async fn spawn_beacon_consensus_engine(
+        ctx: &CliContext,
+        provider_factory: ProviderFactory<Arc<DatabaseEnv>>,
+        consensus: Arc<dyn Consensus>,
+        network: NetworkHandle,
+    ) -> eyre::Result<(
+        BeaconConsensusEngineHandle<ReplayEngineTypes>,
+        oneshot::Receiver<eyre::Result<()>>,
+    )> {
         let executor = block_executor!(provider_factory.chain_spec());
 
         // Configure blockchain tree
@@ -95,52 +171,12 @@ impl Command {
         // Set up the blockchain provider
         let blockchain_db = BlockchainProvider::new(provider_factory.clone(), blockchain_tree)?;
 
-        // Set up network
-        let network_secret_path =
-            self.network.p2p_secret_key.clone().unwrap_or_else(|| data_dir.p2p_secret());
-        let network = self
-            .build_network(
-                &config,
-                ctx.task_executor.clone(),
-                provider_factory.clone(),
-                network_secret_path,
-                data_dir.known_peers(),
-            )
-            .await?;
-
-        // Set up payload builder
-        #[cfg(not(feature = "optimism"))]
-        let payload_builder = reth_ethereum_payload_builder::EthereumPayloadBuilder::default();
-
-        // Optimism's payload builder is implemented on the OptimismPayloadBuilder type.
-        #[cfg(feature = "optimism")]
-        let payload_builder = reth_node_optimism::OptimismPayloadBuilder::new(
-            reth_node_optimism::OptimismEvmConfig::default(),
-        );
-
-        let payload_generator = BasicPayloadJobGenerator::with_builder(
+        let payload_builder = Self::spawn_payload_builder(
+            &ctx.task_executor,
             blockchain_db.clone(),
-            NoopTransactionPool::default(),
-            ctx.task_executor.clone(),
-            BasicPayloadJobGeneratorConfig::default(),
             provider_factory.chain_spec(),
-            payload_builder,
         );
 
-        #[cfg(feature = "optimism")]
-        let (payload_service, payload_builder): (
-            _,
-            PayloadBuilderHandle<reth_node_optimism::OptimismEngineTypes>,
-        ) = PayloadBuilderService::new(payload_generator, blockchain_db.canonical_state_stream());
-
-        #[cfg(not(feature = "optimism"))]
-        let (payload_service, payload_builder): (
-            _,
-            PayloadBuilderHandle<reth_node_ethereum::EthEngineTypes>,
-        ) = PayloadBuilderService::new(payload_generator, blockchain_db.canonical_state_stream());
-
-        ctx.task_executor.spawn_critical("payload builder service", payload_service);
-
         // Configure the consensus engine
         let network_client = network.fetch_client().await?;
         let (beacon_consensus_engine, beacon_engine_handle) = BeaconConsensusEngine::new(
@@ -149,7 +185,7 @@ impl Command {
                 provider_factory.clone(),
                 StaticFileProducer::new(provider_factory.clone(), PruneModes::none()),
             ),
-            blockchain_db.clone(),
+            blockchain_db,
             Box::new(ctx.task_executor.clone()),
             Box::new(network),
             None,
@@ -165,38 +201,201 @@ impl Command {
         info!(target: "reth::cli", "Starting consensus engine");
         ctx.task_executor.spawn_critical_blocking("consensus engine", async move {
             let res = beacon_consensus_engine.await;
+            let _ = tx.send(res.map_err(Into::into));
+        });
+
+        Ok((beacon_engine_handle, rx))
+    }
+
+    /// Spawns the [`EngineService`] that drives the experimental engine tree.
+    async fn spawn_engine_tree(
+        ctx: &CliContext,
+        provider_factory: ProviderFactory<Arc<DatabaseEnv>>,
+        consensus: Arc<dyn Consensus>,
+        network: NetworkHandle,
+    ) -> eyre::Result<(
+        BeaconConsensusEngineHandle<ReplayEngineTypes>,
+        oneshot::Receiver<eyre::Result<()>>,
+    )> {
+        let executor = block_executor!(provider_factory.chain_spec());
+
+        // Set up the blockchain provider
+        let blockchain_db = BlockchainProvider2::new(provider_factory.clone())?;
+
+        let payload_builder = Self::spawn_payload_builder(
+            &ctx.task_executor,
+            blockchain_db.clone(),
+            provider_factory.chain_spec(),
+        );
+
+        // Configure the engine tree
+        let network_client = network.fetch_client().await?;
+        let (to_engine, from_handle) = unbounded_channel();
+        let pruner = PrunerBuilder::default().build_with_provider_factory(provider_factory.clone());
+        let mut engine_service = EngineService::new(
+            consensus,
+            executor,
+            provider_factory.chain_spec(),
+            network_client,
+            Box::pin(UnboundedReceiverStream::new(from_handle)),
+            Pipeline::builder().build(
+                provider_factory.clone(),
+                StaticFileProducer::new(provider_factory.clone(), PruneModes::none()),
+            ),
+            Box::new(ctx.task_executor.clone()),
+            provider_factory,
+            blockchain_db,
+            pruner,
+            payload_builder,
+            TreeConfig::default(),
+        );
+        let beacon_engine_handle =
+            BeaconConsensusEngineHandle::new(to_engine, EventSender::default());
+        info!(target: "reth::cli", "Engine tree initialized");
+
+        // Run engine tree to completion
+        let (tx, rx) = oneshot::channel();
+        info!(target: "reth::cli", "Starting engine tree");
+        ctx.task_executor.spawn_critical("engine tree", async move {
+            let mut res = Ok(());
+            while let Some(event) = engine_service.next().await {
+                debug!(target: "reth::cli", ?event, "Engine tree event");
+                if matches!(event, ChainEvent::FatalError) {
+                    res = Err(eyre::eyre!("Fatal error in engine tree"));
+                    break
+                }
+            }
             let _ = tx.send(res);
         });
 
+        Ok((beacon_engine_handle, rx))
+    }
+
+    /// Execute `debug replay-engine` command
+    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+        let Environment { provider_factory, config, data_dir } = self.env.init(AccessRights::RW)?;
+
+        let consensus: Arc<dyn Consensus> =
+            Arc::new(EthBeaconConsensus::new(provider_factory.chain_spec()));
+
+        // Set up network
+        let network_secret_path =
+            self.network.p2p_secret_key.clone().unwrap_or_else(|| data_dir.p2p_secret());
+        let network = self
+            .build_network(
+                &config,
+                ctx.task_executor.clone(),
+                provider_factory.clone(),
+                network_secret_path,
+                data_dir.known_peers(),
+            )
+            .await?;
+
+        let (beacon_engine_handle, rx) = if self.experimental {
+            Self::spawn_engine_tree(&ctx, provider_factory, consensus, network).await?
+        } else {
+            Self::spawn_beacon_consensus_engine(&ctx, provider_factory, consensus, network).await?
+        };
+
+        let mut report = Vec::new();
+        let mut replay_latencies = Vec::new();
+        let mut diverged = 0;
         let engine_api_store = EngineMessageStore::new(self.engine_api_store.clone());
         for filepath in engine_api_store.engine_messages_iter()? {
             let contents =
                 fs::read(&filepath).wrap_err(format!("failed to read: {}", filepath.display()))?;
             let message = serde_json::from_slice(&contents)
                 .wrap_err(format!("failed to parse: {}", filepath.display()))?;
+            let recorded = engine_api_store
+                .response(&filepath)
+                .wrap_err(format!("failed to read response to: {}", filepath.display()))?;
             debug!(target: "reth::cli", filepath = %filepath.display(), ?message, "Forwarding Engine API message");
-            match message {
+            let started_at = Instant::now();
+            let (method, response) = match message {
                 StoredEngineApiMessage::ForkchoiceUpdated { state, payload_attrs } => {
                     let response =
-                        beacon_engine_handle.fork_choice_updated(state, payload_attrs).await?;
+                        beacon_engine_handle.fork_choice_updated(state, payload_attrs).await;
                     debug!(target: "reth::cli", ?response, "Received for forkchoice updated");
+                    (
+                        "forkchoiceUpdated",
+                        StoredEngineApiResponse::forkchoice_updated(
+                            started_at.elapsed(),
+                            &response,
+                        ),
+                    )
                 }
                 StoredEngineApiMessage::NewPayload { payload, cancun_fields } => {
-                    let response = beacon_engine_handle.new_payload(payload, cancun_fields).await?;
+                    let response = beacon_engine_handle.new_payload(payload, cancun_fields).await;
                     debug!(target: "reth::cli", ?response, "Received for new payload");
+                    (
+                        "newPayload",
+                        StoredEngineApiResponse::new_payload(started_at.elapsed(), &response),
+                    )
                 }
             };
 
+            let divergence = recorded
+                .as_ref()
+                .map(|recorded| recorded.divergence(&response))
+                .unwrap_or_default();
+            if !divergence.is_empty() {
+                diverged += 1;
+                for divergence in &divergence {
+                    warn!(target: "reth::cli", filepath = %filepath.display(), %divergence, "Engine API response diverged");
+                }
+            }
+            info!(
+                target: "reth::cli",
+                filepath = %filepath.display(),
+                method,
+                latency = ?response.latency,
+                recorded_latency = ?recorded.as_ref().map(|recorded| recorded.latency),
+                "Replayed Engine API message"
+            );
+
+            replay_latencies.push(response.latency);
+            report.push(serde_json::json!({
+                "file": filepath.file_name().map(|name| name.to_string_lossy()),
+                "method": method,
+                "latencyMs": response.latency.as_secs_f64() * 1000.,
+                "recordedLatencyMs": recorded.as_ref().map(|recorded| recorded.latency.as_secs_f64() * 1000.),
+                "status": response.status.as_ref().map(|status| status.status.as_str()).map_err(Clone::clone),
+                "divergence": divergence.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            }));
+
             // Pause before next message
             tokio::time::sleep(Duration::from_millis(self.interval)).await;
         }
 
-        info!(target: "reth::cli", "Finished replaying engine API messages");
+        replay_latencies.sort_unstable();
+        let percentile = |p: usize| {
+            replay_latencies
+                .get(
+                    (replay_latencies.len() * p / 100)
+                        .min(replay_latencies.len().saturating_sub(1)),
+                )
+                .copied()
+                .unwrap_or_default()
+        };
+        info!(
+            target: "reth::cli",
+            messages = replay_latencies.len(),
+            diverged,
+            p50 = ?percentile(50),
+            p99 = ?percentile(99),
+            max = ?replay_latencies.last().copied().unwrap_or_default(),
+            "Finished replaying engine API messages"
+        );
+
+        if let Some(path) = &self.report {
+            fs::write(path, serde_json::to_vec_pretty(&report)?)?;
+            info!(target: "reth::cli", path = %path.display(), "Wrote replay report");
+        }
 
         match rx.await? {
-            Ok(()) => info!("Beacon consensus engine exited successfully"),
+            Ok(()) => info!("Consensus engine exited successfully"),
             Err(error) => {
-                error!(target: "reth::cli", %error, "Beacon consensus engine exited with an error")
+                error!(target: "reth::cli", %error, "Consensus engine exited with an error")
             }
         };
 
@@ -18,12 +18,18 @@ pub struct EngineArgs {
     /// Enable the engine2 experimental features on reth binary
     #[arg(long = "engine.experimental", default_value = "false")]
     pub experimental: bool,
+
+    /// Execute the transactions of a block in parallel, falling back to serial execution for
+    /// transactions that conflict with previous ones. Requires `--engine.experimental`.
+    #[arg(long = "engine.parallel-execution", default_value = "false")]
+    pub parallel_execution: bool,
 }
 
 #[cfg(not(feature = "optimism"))]
 fn main() {
     use clap::Parser;
     use reth::cli::Cli;
+    use reth_engine_tree::tree::TreeConfig;
     use reth_node_builder::EngineNodeLauncher;
     use reth_node_ethereum::{node::EthereumAddOns, EthereumNode};
     use reth_provider::providers::BlockchainProvider2;
@@ -47,6 +53,10 @@ fn main() {
                         let launcher = EngineNodeLauncher::new(
                             builder.task_executor().clone(),
                             builder.config().datadir(),
+                        )
+                        .with_tree_config(
+                            TreeConfig::default()
+                                .with_parallel_execution(engine_args.parallel_execution),
                         );
                         builder.launch_with(launcher)
                     })
@@ -11,7 +11,7 @@ use self::error::CanonicalError;
 use crate::error::InsertBlockError;
 use reth_primitives::{
     BlockHash, BlockNumHash, BlockNumber, Receipt, SealedBlock, SealedBlockWithSenders,
-    SealedHeader,
+    SealedHeader, TxHash,
 };
 use reth_storage_errors::provider::{ProviderError, ProviderResult};
 use std::collections::BTreeMap;
@@ -214,6 +214,15 @@ pub enum BlockStatus2 {
         /// The lowest ancestor block that is not connected to the canonical chain.
         missing_ancestor: BlockNumHash,
     },
+    /// The block is valid, but omits a transaction of the inclusion list (FOCIL) of its parent
+    /// that could have been appended to it.
+    ///
+    /// This is not a consensus failure, the block is kept and the consensus layer decides how to
+    /// treat it.
+    InclusionListUnsatisfied {
+        /// The hash of the first omitted inclusion list transaction.
+        missing: TxHash,
+    },
 }
 
 /// How a payload was inserted if it was valid.
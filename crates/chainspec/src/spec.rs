@@ -750,6 +750,14 @@ impl From<Genesis> for ChainSpec {
             (EthereumHardfork::Shanghai.boxed(), genesis.config.shanghai_time),
             (EthereumHardfork::Cancun.boxed(), genesis.config.cancun_time),
             (EthereumHardfork::Prague.boxed(), genesis.config.prague_time),
+            (
+                EthereumHardfork::Focil.boxed(),
+                genesis
+                    .config
+                    .extra_fields
+                    .get_deserialized::<u64>("focilTime")
+                    .and_then(Result::ok),
+            ),
             #[cfg(feature = "optimism")]
             (OptimismHardfork::Regolith.boxed(), genesis_info.regolith_time),
             #[cfg(feature = "optimism")]
@@ -974,6 +982,13 @@ impl ChainSpecBuilder {
         self
     }
 
+    /// Enable FOCIL inclusion lists at genesis.
+    pub fn focil_activated(mut self) -> Self {
+        self = self.prague_activated();
+        self.hardforks.insert(EthereumHardfork::Focil, ForkCondition::Timestamp(0));
+        self
+    }
+
     /// Enable Bedrock at genesis
     #[cfg(feature = "optimism")]
     pub fn bedrock_activated(mut self) -> Self {
@@ -2450,6 +2465,17 @@ Post-merge hard forks (timestamp based):
         assert_eq!(genesis.config.prague_time, Some(4662));
     }
 
+    #[test]
+    fn test_parse_focil_genesis() {
+        let s = r#"{"config":{"chainId":1337,"shanghaiTime":0,"cancunTime":0,"pragueTime":0,"focilTime":4662},"alloc":{}}"#;
+        let genesis: Genesis = serde_json::from_str(s).unwrap();
+        let chainspec = ChainSpec::from(genesis);
+
+        assert_eq!(chainspec.fork(EthereumHardfork::Focil), ForkCondition::Timestamp(4662));
+        assert!(!chainspec.is_focil_active_at_timestamp(4661));
+        assert!(chainspec.is_focil_active_at_timestamp(4662));
+    }
+
     #[test]
     fn test_parse_cancun_genesis_all_formats() {
         let s = r#"{"config":{"ethash":{},"chainId":1337,"homesteadBlock":0,"eip150Block":0,"eip155Block":0,"eip158Block":0,"byzantiumBlock":0,"constantinopleBlock":0,"petersburgBlock":0,"istanbulBlock":0,"berlinBlock":0,"londonBlock":0,"terminalTotalDifficulty":0,"terminalTotalDifficultyPassed":true,"shanghaiTime":0,"cancunTime":4661},"nonce":"0x0","timestamp":"0x0","extraData":"0x","gasLimit":"0x4c4b40","difficulty":"0x1","mixHash":"0x0000000000000000000000000000000000000000000000000000000000000000","coinbase":"0x0000000000000000000000000000000000000000","alloc":{"658bdf435d810c91414ec09147daa6db62406379":{"balance":"0x487a9a304539440000"},"aa00000000000000000000000000000000000000":{"code":"0x6042","storage":{"0x0000000000000000000000000000000000000000000000000000000000000000":"0x0000000000000000000000000000000000000000000000000000000000000000","0x0100000000000000000000000000000000000000000000000000000000000000":"0x0100000000000000000000000000000000000000000000000000000000000000","0x0200000000000000000000000000000000000000000000000000000000000000":"0x0200000000000000000000000000000000000000000000000000000000000000","0x0300000000000000000000000000000000000000000000000000000000000000":"0x0000000000000000000000000000000000000000000000000000000000000303"},"balance":"0x1","nonce":"0x1"},"bb00000000000000000000000000000000000000":{"code":"0x600154600354","storage":{"0x0000000000000000000000000000000000000000000000000000000000000000":"0x0000000000000000000000000000000000000000000000000000000000000000","0x0100000000000000000000000000000000000000000000000000000000000000":"0x0100000000000000000000000000000000000000000000000000000000000000","0x0200000000000000000000000000000000000000000000000000000000000000":"0x0200000000000000000000000000000000000000000000000000000000000000","0x0300000000000000000000000000000000000000000000000000000000000000":"0x0000000000000000000000000000000000000000000000000000000000000303"},"balance":"0x2","nonce":"0x1"}},"number":"0x0","gasUsed":"0x0","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000000","baseFeePerGas":"0x3b9aca00"}"#;
@@ -22,3 +22,4 @@ pub mod recover;
 pub mod stage;
 #[cfg(feature = "dev")]
 pub mod test_vectors;
+pub mod txpool;
@@ -0,0 +1,28 @@
+//! `reth txpool` command.
+
+use clap::{Parser, Subcommand};
+
+mod replay;
+
+/// `reth txpool` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    #[command(subcommand)]
+    command: Subcommands,
+}
+
+/// `reth txpool` subcommands
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Replay a transaction pool recording with a different pool configuration.
+    Replay(replay::Command),
+}
+
+impl Command {
+    /// Execute `txpool` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            Subcommands::Replay(command) => command.execute().await,
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Command that replays a transaction pool recording.
+
+use clap::{Parser, ValueEnum};
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use reth_node_core::{args::TxPoolArgs, cli::config::RethTransactionPoolConfig};
+use reth_transaction_pool::{
+    record::PoolRecorder,
+    replay::{replay_pool, replay_records, ReplayOrdering, ReplayReport},
+    PoolConfig,
+};
+use std::path::PathBuf;
+use tracing::info;
+
+/// Replays a recording made with `reth node --txpool.record` into a pool with the default
+/// configuration and into a pool with the given configuration and ordering, and compares the
+/// value of the blocks built from both pools and the number of evicted transactions.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the recording.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment)]
+    path: PathBuf,
+
+    /// Show the value of every replayed block.
+    #[arg(long, verbatim_doc_comment)]
+    blocks: bool,
+
+    /// The ordering of the pool to compare against the default ordering by coinbase tip.
+    #[arg(long, value_enum, default_value_t = Ordering::CoinbaseTip, verbatim_doc_comment)]
+    ordering: Ordering,
+
+    /// The configuration of the pool to compare against the default configuration.
+    #[command(flatten)]
+    txpool: TxPoolArgs,
+}
+
+impl Command {
+    /// Execute `txpool replay` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let records = PoolRecorder::load(&self.path)?;
+        info!(target: "reth::cli", records = records.len(), path = ?self.path, "Replaying transaction pool recording");
+
+        let baseline_pool = replay_pool(ReplayOrdering::CoinbaseTip, PoolConfig::default());
+        let baseline = replay_records(&baseline_pool, records.clone()).await;
+
+        let candidate_pool =
+            replay_pool(ReplayOrdering::from(self.ordering), self.txpool.pool_config());
+        let candidate = replay_records(&candidate_pool, records).await;
+
+        if self.blocks {
+            println!("{}", blocks_table(&baseline, &candidate));
+        }
+        println!("{}", summary_table(&baseline, &candidate));
+
+        Ok(())
+    }
+}
+
+/// The ordering of the replayed pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Ordering {
+    /// Order by coinbase tip, like the node.
+    CoinbaseTip,
+    /// Order by max fee per gas.
+    MaxFee,
+    /// Order by arrival in the pool.
+    Fifo,
+}
+
+impl From<Ordering> for ReplayOrdering {
+    fn from(ordering: Ordering) -> Self {
+        match ordering {
+            Ordering::CoinbaseTip => Self::CoinbaseTip,
+            Ordering::MaxFee => Self::MaxFee,
+            Ordering::Fifo => Self::Fifo,
+        }
+    }
+}
+
+fn summary_table(baseline: &ReplayReport, candidate: &ReplayReport) -> ComfyTable {
+    let mut table = ComfyTable::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header(["", "Default config", "Given config", "Difference"]);
+
+    let rows = [
+        ("Inserted transactions", baseline.inserted as i128, candidate.inserted as i128),
+        ("Rejected transactions", baseline.rejected as i128, candidate.rejected as i128),
+        ("Discarded transactions", baseline.discarded as i128, candidate.discarded as i128),
+        ("Built blocks", baseline.blocks.len() as i128, candidate.blocks.len() as i128),
+        (
+            "Total block value (wei)",
+            baseline.total_value() as i128,
+            candidate.total_value() as i128,
+        ),
+    ];
+    for (name, baseline, candidate) in rows {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(name))
+            .add_cell(Cell::new(baseline))
+            .add_cell(Cell::new(candidate))
+            .add_cell(Cell::new(candidate - baseline));
+        table.add_row(row);
+    }
+    table
+}
+
+fn blocks_table(baseline: &ReplayReport, candidate: &ReplayReport) -> ComfyTable {
+    let mut table = ComfyTable::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header([
+        "Block",
+        "Transactions (default)",
+        "Transactions (given)",
+        "Value (default)",
+        "Value (given)",
+        "Difference",
+    ]);
+
+    // both replays build a block for every recorded canonical update
+    for (baseline, candidate) in baseline.blocks.iter().zip(&candidate.blocks) {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(baseline.number))
+            .add_cell(Cell::new(baseline.transactions))
+            .add_cell(Cell::new(candidate.transactions))
+            .add_cell(Cell::new(baseline.value))
+            .add_cell(Cell::new(candidate.value))
+            .add_cell(Cell::new(candidate.value as i128 - baseline.value as i128));
+        table.add_row(row);
+    }
+    table
+}
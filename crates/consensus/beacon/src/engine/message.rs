@@ -85,6 +85,15 @@ impl OnForkChoiceUpdated {
         }
     }
 
+    /// Creates a new instance of `OnForkChoiceUpdated` from an already resolved forkchoice update
+    /// result.
+    pub fn with_result(
+        forkchoice_status: ForkchoiceStatus,
+        result: ForkChoiceUpdateResult,
+    ) -> Self {
+        Self { forkchoice_status, fut: Either::Left(futures::future::ready(result)) }
+    }
+
     /// If the forkchoice update was successful and no payload attributes were provided, this method
     pub const fn updated_with_pending_payload_id(
         payload_status: PayloadStatus,
@@ -23,6 +23,8 @@ pub struct TreeConfig {
     max_invalid_header_cache_length: u32,
     /// Maximum number of blocks to execute sequentially in a batch.
     max_execute_block_batch_size: usize,
+    /// Whether to execute the transactions of a block in parallel.
+    parallel_execution: bool,
 }
 
 impl Default for TreeConfig {
@@ -33,6 +35,7 @@ impl Default for TreeConfig {
             block_buffer_limit: DEFAULT_BLOCK_BUFFER_LIMIT,
             max_invalid_header_cache_length: DEFAULT_MAX_INVALID_HEADER_CACHE_LENGTH,
             max_execute_block_batch_size: DEFAULT_MAX_EXECUTE_BLOCK_BATCH_SIZE,
+            parallel_execution: false,
         }
     }
 }
@@ -52,6 +55,7 @@ impl TreeConfig {
             block_buffer_limit,
             max_invalid_header_cache_length,
             max_execute_block_batch_size,
+            parallel_execution: false,
         }
     }
 
@@ -80,6 +84,11 @@ impl TreeConfig {
         self.max_execute_block_batch_size
     }
 
+    /// Return whether the transactions of a block are executed in parallel.
+    pub const fn parallel_execution(&self) -> bool {
+        self.parallel_execution
+    }
+
     /// Setter for persistence threshold.
     pub const fn with_persistence_threshold(mut self, persistence_threshold: u64) -> Self {
         self.persistence_threshold = persistence_threshold;
@@ -118,4 +127,10 @@ impl TreeConfig {
         self.max_execute_block_batch_size = max_execute_block_batch_size;
         self
     }
+
+    /// Setter for parallel execution of the transactions of a block.
+    pub const fn with_parallel_execution(mut self, parallel_execution: bool) -> Self {
+        self.parallel_execution = parallel_execution;
+        self
+    }
 }
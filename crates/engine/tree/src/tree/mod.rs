@@ -18,13 +18,13 @@ use reth_chain_state::{
 use reth_consensus::{Consensus, PostExecutionInput};
 use reth_engine_primitives::EngineTypes;
 use reth_errors::{ConsensusError, ProviderResult};
-use reth_evm::execute::{BlockExecutorProvider, Executor};
+use reth_evm::execute::{BlockExecutionError, BlockExecutorProvider, Executor};
 use reth_payload_builder::PayloadBuilderHandle;
 use reth_payload_primitives::{PayloadAttributes, PayloadBuilderAttributes};
 use reth_payload_validator::ExecutionPayloadValidator;
 use reth_primitives::{
-    Block, BlockNumHash, BlockNumber, GotExpected, Header, SealedBlock, SealedBlockWithSenders,
-    SealedHeader, B256, U256,
+    Block, BlockNumHash, BlockNumber, BlockWithSenders, GotExpected, Header, SealedBlock,
+    SealedBlockWithSenders, SealedHeader, TxHash, B256, U256,
 };
 use reth_provider::{
     BlockReader, ExecutionOutcome, ProviderError, StateProviderBox, StateProviderFactory,
@@ -83,6 +83,9 @@ pub struct TreeState {
     parent_to_child: HashMap<B256, HashSet<B256>>,
     /// Currently tracked canonical head of the chain.
     current_canonical_head: BlockNumHash,
+    /// The first omitted inclusion list transaction of executed blocks that don't satisfy the
+    /// inclusion list of their parent.
+    unsatisfied_inclusion_lists: HashMap<B256, TxHash>,
 }
 
 impl TreeState {
@@ -93,6 +96,7 @@ impl TreeState {
             blocks_by_number: BTreeMap::new(),
             current_canonical_head,
             parent_to_child: HashMap::new(),
+            unsatisfied_inclusion_lists: HashMap::new(),
         }
     }
 
@@ -188,6 +192,7 @@ impl TreeState {
                 for block in blocks {
                     let block_hash = block.block.hash();
                     self.blocks_by_hash.remove(&block_hash);
+                    self.unsatisfied_inclusion_lists.remove(&block_hash);
 
                     if let Some(parent_children) =
                         self.parent_to_child.get_mut(&block.block.parent_hash)
@@ -678,6 +683,19 @@ where
                             // not known to be invalid, but we don't know anything else
                             PayloadStatusEnum::Syncing
                         }
+                        InsertPayloadOk2::Inserted(BlockStatus2::InclusionListUnsatisfied {
+                            missing,
+                        }) => {
+                            latest_valid_hash = Some(parent_hash);
+                            self.try_connect_buffered_blocks(num_hash);
+                            inclusion_list_unsatisfied(missing)
+                        }
+                        InsertPayloadOk2::AlreadySeen(BlockStatus2::InclusionListUnsatisfied {
+                            missing,
+                        }) => {
+                            latest_valid_hash = Some(parent_hash);
+                            inclusion_list_unsatisfied(missing)
+                        }
                     };
 
                     PayloadStatus::new(status, latest_valid_hash)
@@ -1403,7 +1421,12 @@ where
                 Ok(res) => {
                     debug!(target: "engine", child =?child_num_hash, ?res, "connected buffered block");
                     if self.is_sync_target_head(child_num_hash.hash) &&
-                        matches!(res, InsertPayloadOk2::Inserted(BlockStatus2::Valid))
+                        matches!(
+                            res,
+                            InsertPayloadOk2::Inserted(
+                                BlockStatus2::Valid | BlockStatus2::InclusionListUnsatisfied { .. }
+                            )
+                        )
                     {
                         self.make_canonical(child_num_hash.hash);
                     }
@@ -1634,7 +1657,9 @@ where
 
         // try to append the block
         match self.insert_block(block) {
-            Ok(InsertPayloadOk2::Inserted(BlockStatus2::Valid)) => {
+            Ok(InsertPayloadOk2::Inserted(
+                BlockStatus2::Valid | BlockStatus2::InclusionListUnsatisfied { .. },
+            )) => {
                 if self.is_sync_target_head(block_num_hash.hash) {
                     trace!(target: "engine", "appended downloaded sync target block");
                     // we just inserted the current sync target block, we can try to make it
@@ -1690,7 +1715,12 @@ where
         block: SealedBlockWithSenders,
     ) -> Result<InsertPayloadOk2, InsertBlockErrorKindTwo> {
         if self.block_by_hash(block.hash())?.is_some() {
-            return Ok(InsertPayloadOk2::AlreadySeen(BlockStatus2::Valid))
+            let status = match self.state.tree_state.unsatisfied_inclusion_lists.get(&block.hash())
+            {
+                Some(missing) => BlockStatus2::InclusionListUnsatisfied { missing: *missing },
+                None => BlockStatus2::Valid,
+            };
+            return Ok(InsertPayloadOk2::AlreadySeen(status))
         }
 
         let start = Instant::now();
@@ -1727,15 +1757,18 @@ where
             return Err(e.into())
         }
 
-        let executor = self.executor_provider.executor(StateProviderDatabase::new(&state_provider));
-
         let block_number = block.number;
         let block_hash = block.hash();
         let sealed_block = Arc::new(block.block.clone());
         let block = block.unseal();
 
         let exec_time = Instant::now();
-        let output = executor.execute((&block, U256::MAX).into())?;
+        let db = StateProviderDatabase::new(&state_provider);
+        let output = if self.config.parallel_execution() {
+            self.executor_provider.execute_parallel(db, (&block, U256::MAX).into())?
+        } else {
+            self.executor_provider.executor(db).execute((&block, U256::MAX).into())?
+        };
         debug!(target: "engine", elapsed=?exec_time.elapsed(), ?block_number, "Executed block");
 
         self.consensus.validate_block_post_execution(
@@ -1765,6 +1798,17 @@ where
             trie: Arc::new(trie_output),
         };
 
+        // blocks that omit valid transactions of their parent's inclusion list are still valid,
+        // but reported separately
+        let status = match self.unsatisfied_inclusion_list_tx(&executed, state_provider)? {
+            Some(missing) => {
+                debug!(target: "engine", ?block_number, %missing, "Block omits inclusion list transaction");
+                self.state.tree_state.unsatisfied_inclusion_lists.insert(block_hash, missing);
+                BlockStatus2::InclusionListUnsatisfied { missing }
+            }
+            None => BlockStatus2::Valid,
+        };
+
         if self.state.tree_state.canonical_block_hash() == executed.block().parent_hash {
             debug!(target: "engine", pending = ?executed.block().num_hash() ,"updating pending block");
             // if the parent is the canonical head, we can insert the block as the pending block
@@ -1782,7 +1826,64 @@ where
         };
         self.emit_event(EngineApiEvent::BeaconConsensus(engine_event));
 
-        Ok(InsertPayloadOk2::Inserted(BlockStatus2::Valid))
+        Ok(InsertPayloadOk2::Inserted(status))
+    }
+
+    /// Returns the first transaction of the parent's inclusion list (FOCIL) that the executed
+    /// block omits although it could have been appended to it.
+    ///
+    /// Every omitted transaction is executed on top of the post state of the block with the gas
+    /// the block has left, so it only counts as includable if it passes the full transaction
+    /// validation of the EVM.
+    fn unsatisfied_inclusion_list_tx(
+        &self,
+        executed: &ExecutedBlock,
+        state_provider: StateProviderBox,
+    ) -> Result<Option<TxHash>, InsertBlockErrorKindTwo> {
+        let block = executed.block();
+        let Some(inclusion_list) = self.payload_builder.inclusion_lists().get(&block.parent_hash)
+        else {
+            return Ok(None)
+        };
+        let mut omitted = inclusion_list
+            .iter()
+            .filter(|tx| !block.body.iter().any(|included| included.hash == tx.hash))
+            .peekable();
+        if omitted.peek().is_none() {
+            return Ok(None)
+        }
+
+        let gas_left = block.gas_limit.saturating_sub(block.gas_used);
+        let post_state = MemoryOverlayStateProvider::new(vec![executed.clone()], state_provider);
+        for tx in omitted {
+            // blob transactions can't be part of an inclusion list
+            if tx.is_eip4844() || tx.gas_limit() > gas_left {
+                continue
+            }
+
+            let candidate = BlockWithSenders {
+                block: Block {
+                    header: Header { gas_limit: gas_left, gas_used: 0, ..block.header().clone() },
+                    body: vec![tx.clone().into_signed()],
+                    ommers: Vec::new(),
+                    withdrawals: None,
+                    requests: None,
+                },
+                senders: vec![tx.signer()],
+            };
+            match self
+                .executor_provider
+                .executor(StateProviderDatabase::new(&post_state))
+                .execute((&candidate, U256::MAX).into())
+            {
+                Ok(_) => return Ok(Some(tx.hash)),
+                Err(err @ BlockExecutionError::Internal(_)) => return Err(err.into()),
+                // the transaction is invalid on top of the block
+                Err(_) => {}
+            }
+        }
+
+        Ok(None)
     }
 
     /// Handles an error that occurred while inserting a block.
@@ -2004,6 +2105,22 @@ pub struct PersistenceState {
     last_persisted_block_number: u64,
 }
 
+/// The EIP-7805 status of blocks that don't satisfy the inclusion list of their parent.
+const INCLUSION_LIST_UNSATISFIED: &str = "INCLUSION_LIST_UNSATISFIED";
+
+/// Returns the payload status of a block that doesn't satisfy the inclusion list of its parent.
+///
+/// EIP-7805 reports this as `INCLUSION_LIST_UNSATISFIED`, which [`PayloadStatusEnum`] can't
+/// express yet. The status is reported as invalid with a dedicated validation error instead, but
+/// unlike invalid blocks the block is kept in the tree and not cached as an invalid header.
+fn inclusion_list_unsatisfied(missing: TxHash) -> PayloadStatusEnum {
+    PayloadStatusEnum::Invalid {
+        validation_error: format!(
+            "{INCLUSION_LIST_UNSATISFIED}: block omits inclusion list transaction {missing}"
+        ),
+    }
+}
+
 impl PersistenceState {
     /// Determines if there is a persistence task in progress by checking if the
     /// receiver is set.
@@ -2035,7 +2152,10 @@ mod tests {
     use reth_chainspec::{ChainSpec, HOLESKY, MAINNET};
     use reth_ethereum_engine_primitives::EthEngineTypes;
     use reth_evm::test_utils::MockExecutorProvider;
-    use reth_primitives::Bytes;
+    use reth_primitives::{
+        Address, Bytes, Signature, Transaction, TransactionSigned, TransactionSignedEcRecovered,
+        TxEip1559,
+    };
     use reth_provider::test_utils::MockEthProvider;
     use reth_rpc_types_compat::engine::{block_to_payload_v1, payload::block_to_payload_v3};
     use reth_trie::updates::TrieUpdates;
@@ -2129,6 +2249,7 @@ mod tests {
                 blocks_by_number,
                 current_canonical_head: blocks.last().unwrap().block().num_hash(),
                 parent_to_child,
+                unsatisfied_inclusion_lists: HashMap::new(),
             };
 
             let last_executed_block = blocks.last().unwrap().clone();
@@ -2520,6 +2641,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_inclusion_list_unsatisfied() {
+        let chain_spec = MAINNET.clone();
+        let mut test_harness = TestHarness::new(chain_spec);
+
+        let base_chain: Vec<_> = test_harness.block_builder.get_executed_blocks(0..1).collect();
+        test_harness = test_harness.with_blocks(base_chain.clone());
+        let block = test_harness.block_builder.create_fork(base_chain[0].block(), 1).remove(0);
+
+        // an inclusion list transaction the block omits, which the mocked executor accepts on top
+        // of the block
+        let tx = TransactionSignedEcRecovered::from_signed_transaction(
+            TransactionSigned::from_transaction_and_signature(
+                Transaction::Eip1559(TxEip1559 { gas_limit: 21_000, ..Default::default() }),
+                Signature::default(),
+            ),
+            Address::random(),
+        );
+        test_harness
+            .tree
+            .payload_builder
+            .inclusion_lists()
+            .insert(block.parent_hash, vec![tx.clone()]);
+        test_harness.extend_execution_outcome([ExecutionOutcome::default()]);
+
+        let status = BlockStatus2::InclusionListUnsatisfied { missing: tx.hash };
+        assert_eq!(
+            test_harness.insert_block(block.clone()).unwrap(),
+            InsertPayloadOk2::Inserted(status)
+        );
+
+        // the block is kept and not cached as invalid
+        assert!(test_harness.tree.state.tree_state.block_by_hash(block.hash()).is_some());
+        assert!(test_harness.tree.state.invalid_headers.get(&block.hash()).is_none());
+
+        // the status is reported again for known blocks
+        assert_eq!(
+            test_harness.tree.insert_block(block).unwrap(),
+            InsertPayloadOk2::AlreadySeen(status)
+        );
+    }
+
     #[tokio::test]
     async fn test_holesky_payload() {
         let s = include_str!("../../test-data/holesky/1.rlp");
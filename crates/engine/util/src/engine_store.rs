@@ -1,21 +1,29 @@
 //! Stores engine API messages to disk for later inspection and replay.
 
-use futures::{Stream, StreamExt};
-use reth_beacon_consensus::BeaconEngineMessage;
+use futures::{future::BoxFuture, Stream, StreamExt};
+use reth_beacon_consensus::{
+    BeaconEngineMessage, BeaconForkChoiceUpdateError, BeaconOnNewPayloadError, OnForkChoiceUpdated,
+};
 use reth_engine_primitives::EngineTypes;
+use reth_errors::RethResult;
 use reth_fs_util as fs;
+use reth_primitives::B256;
 use reth_rpc_types::{
-    engine::{CancunPayloadFields, ForkchoiceState},
+    engine::{CancunPayloadFields, ForkchoiceState, ForkchoiceUpdated, PayloadId, PayloadStatus},
     ExecutionPayload,
 };
+use reth_tasks::TaskSpawner;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
-    path::PathBuf,
+    fmt,
+    path::{Path, PathBuf},
     pin::Pin,
-    task::{ready, Context, Poll},
-    time::SystemTime,
+    task::{Context, Poll},
+    time::{Duration, Instant, SystemTime},
 };
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::*;
 
 /// A message from the engine API that has been stored to disk.
@@ -38,8 +46,138 @@ pub enum StoredEngineApiMessage<Attributes> {
     },
 }
 
+/// The node's response to a stored engine API message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredEngineApiResponse {
+    /// The time it took the node to respond to the message.
+    pub latency: Duration,
+    /// The returned payload status, or the error message if the call failed.
+    pub status: Result<PayloadStatus, String>,
+    /// The payload id returned by an `engine_forkchoiceUpdated` call, if any.
+    pub payload_id: Option<PayloadId>,
+}
+
+impl StoredEngineApiResponse {
+    /// Creates the response record of an `engine_newPayload` call.
+    pub fn new_payload(
+        latency: Duration,
+        result: &Result<PayloadStatus, BeaconOnNewPayloadError>,
+    ) -> Self {
+        Self {
+            latency,
+            status: result.as_ref().map_err(ToString::to_string).cloned(),
+            payload_id: None,
+        }
+    }
+
+    /// Creates the response record of an `engine_forkchoiceUpdated` call.
+    pub fn forkchoice_updated(
+        latency: Duration,
+        result: &Result<ForkchoiceUpdated, BeaconForkChoiceUpdateError>,
+    ) -> Self {
+        match result {
+            Ok(updated) => Self {
+                latency,
+                status: Ok(updated.payload_status.clone()),
+                payload_id: updated.payload_id,
+            },
+            Err(error) => Self { latency, status: Err(error.to_string()), payload_id: None },
+        }
+    }
+
+    /// Compares this recorded response with the response to the replayed message and returns all
+    /// the ways in which they differ.
+    ///
+    /// Latencies, validation error messages and the messages of failed calls are not considered.
+    pub fn divergence(&self, replayed: &Self) -> Vec<EngineApiResponseDivergence> {
+        let mut divergence = Vec::new();
+        match (&self.status, &replayed.status) {
+            (Ok(recorded), Ok(replayed)) => {
+                if recorded.status.as_str() != replayed.status.as_str() {
+                    divergence.push(EngineApiResponseDivergence::Status {
+                        recorded: recorded.status.as_str().to_string(),
+                        replayed: replayed.status.as_str().to_string(),
+                    });
+                }
+                if recorded.latest_valid_hash != replayed.latest_valid_hash {
+                    divergence.push(EngineApiResponseDivergence::LatestValidHash {
+                        recorded: recorded.latest_valid_hash,
+                        replayed: replayed.latest_valid_hash,
+                    });
+                }
+            }
+            // error messages may contain details that differ between runs
+            (Err(_), Err(_)) => {}
+            (recorded, replayed) => {
+                let describe = |status: &Result<PayloadStatus, String>| match status {
+                    Ok(status) => status.status.as_str().to_string(),
+                    Err(error) => format!("error: {error}"),
+                };
+                divergence.push(EngineApiResponseDivergence::Status {
+                    recorded: describe(recorded),
+                    replayed: describe(replayed),
+                });
+            }
+        }
+        if self.payload_id != replayed.payload_id {
+            divergence.push(EngineApiResponseDivergence::PayloadId {
+                recorded: self.payload_id,
+                replayed: replayed.payload_id,
+            });
+        }
+        divergence
+    }
+}
+
+/// A difference between a recorded [`StoredEngineApiResponse`] and the response to the same
+/// message on replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineApiResponseDivergence {
+    /// The payload status or the error differs.
+    Status {
+        /// The recorded status.
+        recorded: String,
+        /// The status returned on replay.
+        replayed: String,
+    },
+    /// The latest valid hash of the payload status differs.
+    LatestValidHash {
+        /// The recorded latest valid hash.
+        recorded: Option<B256>,
+        /// The latest valid hash returned on replay.
+        replayed: Option<B256>,
+    },
+    /// The payload id differs.
+    PayloadId {
+        /// The recorded payload id.
+        recorded: Option<PayloadId>,
+        /// The payload id returned on replay.
+        replayed: Option<PayloadId>,
+    },
+}
+
+impl fmt::Display for EngineApiResponseDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { recorded, replayed } => {
+                write!(f, "status: recorded {recorded}, replayed {replayed}")
+            }
+            Self::LatestValidHash { recorded, replayed } => {
+                write!(f, "latest valid hash: recorded {recorded:?}, replayed {replayed:?}")
+            }
+            Self::PayloadId { recorded, replayed } => {
+                write!(f, "payload id: recorded {recorded:?}, replayed {replayed:?}")
+            }
+        }
+    }
+}
+
 /// This can read and write engine API messages in a specific directory.
-#[derive(Debug)]
+///
+/// The node's responses are stored in the `responses` subdirectory, under the same file name as
+/// the message they belong to.
+#[derive(Debug, Clone)]
 pub struct EngineMessageStore {
     /// The path to the directory that stores the engine API messages.
     path: PathBuf,
@@ -53,6 +191,28 @@ impl EngineMessageStore {
         Self { path }
     }
 
+    /// Returns the path the given [`BeaconEngineMessage`] is stored at, if it is stored at all.
+    pub fn message_path<Engine>(
+        &self,
+        msg: &BeaconEngineMessage<Engine>,
+        received_at: SystemTime,
+    ) -> Option<PathBuf>
+    where
+        Engine: EngineTypes,
+    {
+        let timestamp = received_at.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis();
+        let filename = match msg {
+            BeaconEngineMessage::ForkchoiceUpdated { state, .. } => {
+                format!("{}-fcu-{}.json", timestamp, state.head_block_hash)
+            }
+            BeaconEngineMessage::NewPayload { payload, .. } => {
+                format!("{}-new_payload-{}.json", timestamp, payload.block_hash())
+            }
+            BeaconEngineMessage::TransitionConfigurationExchanged => return None,
+        };
+        Some(self.path.join(filename))
+    }
+
     /// Stores the received [`BeaconEngineMessage`] to disk, appending the `received_at` time to the
     /// path.
     pub fn on_message<Engine>(
@@ -64,12 +224,11 @@ impl EngineMessageStore {
         Engine: EngineTypes,
     {
         fs::create_dir_all(&self.path)?; // ensure that store path had been created
-        let timestamp = received_at.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis();
+        let Some(path) = self.message_path(msg, received_at) else { return Ok(()) };
         match msg {
             BeaconEngineMessage::ForkchoiceUpdated { state, payload_attrs, tx: _tx } => {
-                let filename = format!("{}-fcu-{}.json", timestamp, state.head_block_hash);
                 fs::write(
-                    self.path.join(filename),
+                    path,
                     serde_json::to_vec(&StoredEngineApiMessage::ForkchoiceUpdated {
                         state: *state,
                         payload_attrs: payload_attrs.clone(),
@@ -77,9 +236,8 @@ impl EngineMessageStore {
                 )?;
             }
             BeaconEngineMessage::NewPayload { payload, cancun_fields, tx: _tx } => {
-                let filename = format!("{}-new_payload-{}.json", timestamp, payload.block_hash());
                 fs::write(
-                    self.path.join(filename),
+                    path,
                     serde_json::to_vec(
                         &StoredEngineApiMessage::<Engine::PayloadAttributes>::NewPayload {
                             payload: payload.clone(),
@@ -94,6 +252,33 @@ impl EngineMessageStore {
         Ok(())
     }
 
+    /// Returns the path the response to the message stored at `message_path` is stored at.
+    fn response_path(&self, message_path: &Path) -> Option<PathBuf> {
+        Some(self.path.join("responses").join(message_path.file_name()?))
+    }
+
+    /// Stores the node's response to the message stored at `message_path`.
+    pub fn on_response(
+        &self,
+        message_path: &Path,
+        response: &StoredEngineApiResponse,
+    ) -> eyre::Result<()> {
+        let path = self
+            .response_path(message_path)
+            .ok_or_else(|| eyre::eyre!("invalid message path: {}", message_path.display()))?;
+        fs::create_dir_all(self.path.join("responses"))?;
+        fs::write(path, serde_json::to_vec(response)?)?;
+        Ok(())
+    }
+
+    /// Reads the recorded response to the message stored at `message_path`, if there is one.
+    pub fn response(&self, message_path: &Path) -> eyre::Result<Option<StoredEngineApiResponse>> {
+        let Some(path) = self.response_path(message_path).filter(|path| path.exists()) else {
+            return Ok(None)
+        };
+        Ok(Some(serde_json::from_slice(&fs::read(path)?)?))
+    }
+
     /// Finds and iterates through any stored engine API message files, ordered by timestamp.
     pub fn engine_messages_iter(&self) -> eyre::Result<impl Iterator<Item = PathBuf>> {
         let mut filenames_by_ts = BTreeMap::<u64, Vec<PathBuf>>::default();
@@ -107,6 +292,8 @@ impl EngineMessageStore {
                 } else {
                     tracing::warn!(target: "engine::store", %filename, "Could not parse timestamp from filename")
                 }
+            } else if entry.path().is_dir() {
+                tracing::debug!(target: "engine::store", ?filename, "Skipping directory");
             } else {
                 tracing::warn!(target: "engine::store", ?filename, "Skipping non json file");
             }
@@ -115,40 +302,235 @@ impl EngineMessageStore {
     }
 }
 
-/// A wrapper stream that stores Engine API messages in
+/// A wrapper stream that stores Engine API messages and the node's responses to them in
 /// the specified directory.
+///
+/// The messages are stored by a task that forwards them to this stream as soon as they arrive, so
+/// that the recorded latencies include the time a message waits until the engine processes it.
 #[derive(Debug)]
-#[pin_project::pin_project]
-pub struct EngineStoreStream<S> {
-    /// Inner message stream.
-    #[pin]
-    stream: S,
-    /// Engine message store.
-    store: EngineMessageStore,
+pub struct EngineStoreStream<Engine: EngineTypes> {
+    /// Messages forwarded by the store task.
+    messages: UnboundedReceiverStream<BeaconEngineMessage<Engine>>,
+}
+
+impl<Engine: EngineTypes> EngineStoreStream<Engine> {
+    /// Create new engine store stream wrapper and spawns the task that stores the messages of
+    /// the given stream.
+    pub fn new<S>(stream: S, path: PathBuf, executor: Box<dyn TaskSpawner>) -> Self
+    where
+        S: Stream<Item = BeaconEngineMessage<Engine>> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let store = EngineMessageStore::new(path);
+        executor.spawn(Box::pin(store_messages(stream, store, tx, executor.clone())));
+        Self { messages: UnboundedReceiverStream::new(rx) }
+    }
 }
 
-impl<S> EngineStoreStream<S> {
-    /// Create new engine store stream wrapper.
-    pub const fn new(stream: S, path: PathBuf) -> Self {
-        Self { stream, store: EngineMessageStore::new(path) }
+impl<Engine: EngineTypes> Stream for EngineStoreStream<Engine> {
+    type Item = BeaconEngineMessage<Engine>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.messages.poll_next_unpin(cx)
     }
 }
 
-impl<S, Engine> Stream for EngineStoreStream<S>
-where
+/// Stores the messages of the stream and forwards them to the engine until either the stream
+/// ends or the engine is dropped.
+///
+/// The responses are recorded by separate tasks, before they are forwarded to the original
+/// senders.
+async fn store_messages<S, Engine>(
+    stream: S,
+    store: EngineMessageStore,
+    messages: mpsc::UnboundedSender<BeaconEngineMessage<Engine>>,
+    executor: Box<dyn TaskSpawner>,
+) where
     S: Stream<Item = BeaconEngineMessage<Engine>>,
     Engine: EngineTypes,
 {
-    type Item = S::Item;
-
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let mut this = self.project();
-        let next = ready!(this.stream.poll_next_unpin(cx));
-        if let Some(msg) = &next {
-            if let Err(error) = this.store.on_message(msg, SystemTime::now()) {
-                error!(target: "engine::stream::store", ?msg, %error, "Error handling Engine API message");
+    let mut stream = std::pin::pin!(stream);
+    while let Some(mut msg) = stream.next().await {
+        let received_at = SystemTime::now();
+        if let Err(error) = store.on_message(&msg, received_at) {
+            error!(target: "engine::stream::store", ?msg, %error, "Error handling Engine API message");
+        } else if let Some(path) = store.message_path(&msg, received_at) {
+            if let Some(pending) = record_response(store.clone(), path, &mut msg) {
+                executor.spawn(pending);
             }
         }
-        Poll::Ready(next)
+        if messages.send(msg).is_err() {
+            // the engine is gone
+            return
+        }
+    }
+}
+
+/// Replaces the response sender of the message with one that records the response before
+/// forwarding it to the original sender.
+fn record_response<Engine: EngineTypes>(
+    store: EngineMessageStore,
+    path: PathBuf,
+    msg: &mut BeaconEngineMessage<Engine>,
+) -> Option<BoxFuture<'static, ()>> {
+    let received_at = Instant::now();
+    match msg {
+        BeaconEngineMessage::NewPayload { tx, .. } => {
+            let (new_tx, rx) = oneshot::channel();
+            let tx = std::mem::replace(tx, new_tx);
+            Some(Box::pin(async move {
+                let Ok(result) = rx.await else { return };
+                let response = StoredEngineApiResponse::new_payload(received_at.elapsed(), &result);
+                store_response(&store, &path, &response);
+                let _ = tx.send(result);
+            }))
+        }
+        BeaconEngineMessage::ForkchoiceUpdated { tx, .. } => {
+            let (new_tx, rx) = oneshot::channel::<RethResult<OnForkChoiceUpdated>>();
+            let tx = std::mem::replace(tx, new_tx);
+            Some(Box::pin(async move {
+                let Ok(result) = rx.await else { return };
+                let on_updated = match result {
+                    Ok(on_updated) => on_updated,
+                    Err(error) => {
+                        let response = StoredEngineApiResponse {
+                            latency: received_at.elapsed(),
+                            status: Err(error.to_string()),
+                            payload_id: None,
+                        };
+                        store_response(&store, &path, &response);
+                        let _ = tx.send(Err(error));
+                        return
+                    }
+                };
+                let forkchoice_status = on_updated.forkchoice_status();
+                let result = on_updated.await;
+                let response = StoredEngineApiResponse::forkchoice_updated(
+                    received_at.elapsed(),
+                    &result.clone().map_err(BeaconForkChoiceUpdateError::from),
+                );
+                store_response(&store, &path, &response);
+                let _ = tx.send(Ok(OnForkChoiceUpdated::with_result(forkchoice_status, result)));
+            }))
+        }
+        BeaconEngineMessage::TransitionConfigurationExchanged => None,
+    }
+}
+
+/// Stores the response, logging any error.
+fn store_response(store: &EngineMessageStore, path: &Path, response: &StoredEngineApiResponse) {
+    if let Err(error) = store.on_response(path, response) {
+        error!(target: "engine::stream::store", ?response, %error, "Error storing Engine API response");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_ethereum_engine_primitives::EthEngineTypes;
+    use reth_rpc_types::engine::{PayloadAttributes, PayloadStatusEnum};
+    use reth_tasks::TokioTaskExecutor;
+
+    fn response(status: Result<PayloadStatus, String>) -> StoredEngineApiResponse {
+        StoredEngineApiResponse { latency: Duration::from_millis(10), status, payload_id: None }
+    }
+
+    #[test]
+    fn divergence() {
+        let hash = B256::random();
+        let valid = response(Ok(PayloadStatus::new(PayloadStatusEnum::Valid, Some(hash))));
+        let invalid = |validation_error: &str| {
+            response(Ok(PayloadStatus::new(
+                PayloadStatusEnum::Invalid { validation_error: validation_error.to_string() },
+                Some(hash),
+            )))
+        };
+
+        // latencies are not compared
+        let slow = StoredEngineApiResponse { latency: Duration::from_secs(1), ..valid.clone() };
+        assert!(valid.divergence(&slow).is_empty());
+
+        // neither are validation or error messages
+        assert!(invalid("bad state root").divergence(&invalid("bad receipts root")).is_empty());
+        assert!(response(Err("database error".to_string()))
+            .divergence(&response(Err("provider error".to_string())))
+            .is_empty());
+
+        assert_eq!(
+            valid.divergence(&invalid("bad state root")),
+            vec![EngineApiResponseDivergence::Status {
+                recorded: "VALID".to_string(),
+                replayed: "INVALID".to_string(),
+            }]
+        );
+        assert_eq!(
+            valid.divergence(&response(Err("database error".to_string()))),
+            vec![EngineApiResponseDivergence::Status {
+                recorded: "VALID".to_string(),
+                replayed: "error: database error".to_string(),
+            }]
+        );
+
+        let other_hash = StoredEngineApiResponse {
+            status: Ok(PayloadStatus::new(PayloadStatusEnum::Valid, None)),
+            payload_id: Some(PayloadId::new([1; 8])),
+            ..valid
+        };
+        assert_eq!(
+            valid.divergence(&other_hash),
+            vec![
+                EngineApiResponseDivergence::LatestValidHash {
+                    recorded: Some(hash),
+                    replayed: None
+                },
+                EngineApiResponseDivergence::PayloadId {
+                    recorded: None,
+                    replayed: Some(PayloadId::new([1; 8])),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut stream = EngineStoreStream::new(
+            UnboundedReceiverStream::new(rx),
+            dir.path().to_path_buf(),
+            Box::new(TokioTaskExecutor::default()),
+        );
+
+        let state = ForkchoiceState { head_block_hash: B256::random(), ..Default::default() };
+        let (response_tx, response_rx) = oneshot::channel();
+        tx.send(BeaconEngineMessage::<EthEngineTypes>::ForkchoiceUpdated {
+            state,
+            payload_attrs: None,
+            tx: response_tx,
+        })
+        .unwrap();
+
+        // respond like the engine
+        let Some(BeaconEngineMessage::ForkchoiceUpdated { tx, .. }) = stream.next().await else {
+            panic!("expected forkchoice update")
+        };
+        let status = PayloadStatus::new(PayloadStatusEnum::Valid, Some(state.head_block_hash));
+        tx.send(Ok(OnForkChoiceUpdated::valid(status.clone()))).unwrap();
+        let updated = response_rx.await.unwrap().unwrap().await.unwrap();
+        assert_eq!(updated.payload_status, status);
+
+        let store = EngineMessageStore::new(dir.path().to_path_buf());
+        let paths = store.engine_messages_iter().unwrap().collect::<Vec<_>>();
+        assert_eq!(paths.len(), 1);
+        let message: StoredEngineApiMessage<PayloadAttributes> =
+            serde_json::from_slice(&fs::read(&paths[0]).unwrap()).unwrap();
+        assert!(matches!(
+            message,
+            StoredEngineApiMessage::ForkchoiceUpdated { state: stored, payload_attrs: None } if stored == state
+        ));
+
+        let recorded = store.response(&paths[0]).unwrap().expect("response is recorded");
+        assert_eq!(recorded.status, Ok(status));
+        assert_eq!(recorded.payload_id, None);
     }
 }
@@ -4,6 +4,7 @@ use futures::Stream;
 use reth_beacon_consensus::BeaconEngineMessage;
 use reth_engine_primitives::EngineTypes;
 use reth_payload_validator::ExecutionPayloadValidator;
+use reth_tasks::TaskSpawner;
 use std::path::PathBuf;
 use tokio_util::either::Either;
 
@@ -71,11 +72,17 @@ pub trait EngineMessageStreamExt<Engine: EngineTypes>:
     }
 
     /// Stores engine messages at the specified location.
-    fn store_messages(self, path: PathBuf) -> EngineStoreStream<Self>
+    ///
+    /// The messages are stored by a task spawned on the given executor.
+    fn store_messages(
+        self,
+        path: PathBuf,
+        executor: Box<dyn TaskSpawner>,
+    ) -> EngineStoreStream<Engine>
     where
-        Self: Sized,
+        Self: Sized + Send + 'static,
     {
-        EngineStoreStream::new(self, path)
+        EngineStoreStream::new(self, path, executor)
     }
 
     /// If the path is [Some], returns the stream that stores engine messages at the specified
@@ -83,12 +90,13 @@ pub trait EngineMessageStreamExt<Engine: EngineTypes>:
     fn maybe_store_messages(
         self,
         maybe_path: Option<PathBuf>,
-    ) -> Either<EngineStoreStream<Self>, Self>
+        executor: Box<dyn TaskSpawner>,
+    ) -> Either<EngineStoreStream<Engine>, Self>
     where
-        Self: Sized,
+        Self: Sized + Send + 'static,
     {
         if let Some(path) = maybe_path {
-            Either::Left(self.store_messages(path))
+            Either::Left(self.store_messages(path, executor))
         } else {
             Either::Right(self)
         }
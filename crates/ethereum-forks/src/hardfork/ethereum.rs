@@ -51,6 +51,10 @@ hardfork!(
         Cancun,
         /// Prague: <https://github.com/ethereum/execution-specs/blob/master/network-upgrades/mainnet-upgrades/prague.md>
         Prague,
+        /// Fork-choice enforced inclusion lists (FOCIL): <https://eips.ethereum.org/EIPS/eip-7805>.
+        ///
+        /// Experimental, not scheduled on any public network.
+        Focil,
     }
 );
 
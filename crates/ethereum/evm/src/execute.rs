@@ -34,6 +34,9 @@ use revm_primitives::{
     BlockEnv, CfgEnvWithHandlerCfg, EVMError, EnvWithHandlerCfg, ResultAndState,
 };
 
+#[cfg(feature = "std")]
+mod parallel;
+
 #[cfg(not(feature = "std"))]
 use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
 #[cfg(feature = "std")]
@@ -105,6 +108,18 @@ where
         let executor = self.eth_executor(db);
         EthBatchExecutor { executor, batch_record: BlockBatchRecord::default() }
     }
+
+    #[cfg(feature = "std")]
+    fn execute_parallel<DB>(
+        &self,
+        db: DB,
+        input: BlockExecutionInput<'_, BlockWithSenders>,
+    ) -> Result<BlockExecutionOutput<Receipt>, BlockExecutionError>
+    where
+        DB: revm_primitives::db::DatabaseRef<Error: Into<ProviderError> + Display> + Send + Sync,
+    {
+        self.eth_executor(reth_revm::db::WrapDatabaseRef(db)).execute_parallel(input)
+    }
 }
 
 /// Helper type for the output of executing a block.
@@ -147,14 +162,30 @@ where
         DB: Database,
         DB::Error: Into<ProviderError> + Display,
     {
-        // apply pre execution changes
+        self.apply_pre_execution_changes(block, &mut evm)?;
+        let (receipts, gas_used) = self.execute_transactions(block, &mut evm, |_, _| Ok(None))?;
+        let requests = self.apply_post_execution_changes(block, &receipts, &mut evm)?;
+
+        Ok(EthExecuteOutput { receipts, requests, gas_used })
+    }
+
+    /// Applies the pre-execution changes that require an [EVM](Evm).
+    fn apply_pre_execution_changes<Ext, DB>(
+        &self,
+        block: &BlockWithSenders,
+        evm: &mut Evm<'_, Ext, &mut State<DB>>,
+    ) -> Result<(), BlockExecutionError>
+    where
+        DB: Database,
+        DB::Error: Into<ProviderError> + Display,
+    {
         apply_beacon_root_contract_call(
             &self.evm_config,
             &self.chain_spec,
             block.timestamp,
             block.number,
             block.parent_beacon_block_root,
-            &mut evm,
+            evm,
         )?;
         apply_blockhashes_update(
             evm.db_mut(),
@@ -164,10 +195,28 @@ where
             block.parent_hash,
         )?;
 
-        // execute transactions
+        Ok(())
+    }
+
+    /// Executes the transactions in the block and returns their receipts and the total gas used.
+    ///
+    /// For every transaction, `precomputed` is called with the index of the transaction and the
+    /// state before the transaction. If it returns a result, that result is committed instead of
+    /// executing the transaction.
+    fn execute_transactions<Ext, DB, F>(
+        &self,
+        block: &BlockWithSenders,
+        evm: &mut Evm<'_, Ext, &mut State<DB>>,
+        mut precomputed: F,
+    ) -> Result<(Vec<Receipt>, u64), BlockExecutionError>
+    where
+        DB: Database,
+        DB::Error: Into<ProviderError> + Display,
+        F: FnMut(usize, &mut State<DB>) -> Result<Option<ResultAndState>, BlockExecutionError>,
+    {
         let mut cumulative_gas_used = 0;
         let mut receipts = Vec::with_capacity(block.body.len());
-        for (sender, transaction) in block.transactions_with_sender() {
+        for (index, (sender, transaction)) in block.transactions_with_sender().enumerate() {
             // The sum of the transaction’s gas limit, Tg, and the gas utilized in this block prior,
             // must be no greater than the block’s gasLimit.
             let block_available_gas = block.header.gas_limit - cumulative_gas_used;
@@ -179,23 +228,28 @@ where
                 .into())
             }
 
-            self.evm_config.fill_tx_env(evm.tx_mut(), transaction, *sender);
-
-            // Execute transaction.
-            let ResultAndState { result, state } = evm.transact().map_err(move |err| {
-                let new_err = match err {
-                    EVMError::Transaction(e) => EVMError::Transaction(e),
-                    EVMError::Header(e) => EVMError::Header(e),
-                    EVMError::Database(e) => EVMError::Database(e.into()),
-                    EVMError::Custom(e) => EVMError::Custom(e),
-                    EVMError::Precompile(e) => EVMError::Precompile(e),
-                };
-                // Ensure hash is calculated for error log, if not already done
-                BlockValidationError::EVM {
-                    hash: transaction.recalculate_hash(),
-                    error: Box::new(new_err),
+            let ResultAndState { result, state } = match precomputed(index, evm.db_mut())? {
+                Some(result) => result,
+                None => {
+                    self.evm_config.fill_tx_env(evm.tx_mut(), transaction, *sender);
+
+                    // Execute transaction.
+                    evm.transact().map_err(move |err| {
+                        let new_err = match err {
+                            EVMError::Transaction(e) => EVMError::Transaction(e),
+                            EVMError::Header(e) => EVMError::Header(e),
+                            EVMError::Database(e) => EVMError::Database(e.into()),
+                            EVMError::Custom(e) => EVMError::Custom(e),
+                            EVMError::Precompile(e) => EVMError::Precompile(e),
+                        };
+                        // Ensure hash is calculated for error log, if not already done
+                        BlockValidationError::EVM {
+                            hash: transaction.recalculate_hash(),
+                            error: Box::new(new_err),
+                        }
+                    })?
                 }
-            })?;
+            };
             evm.db_mut().commit(state);
 
             // append gas used
@@ -217,25 +271,40 @@ where
             );
         }
 
+        Ok((receipts, cumulative_gas_used))
+    }
+
+    /// Applies the post-execution changes that require an [EVM](Evm) and returns the list of
+    /// EIP-7685 [requests](Request).
+    fn apply_post_execution_changes<Ext, DB>(
+        &self,
+        block: &BlockWithSenders,
+        receipts: &[Receipt],
+        evm: &mut Evm<'_, Ext, &mut State<DB>>,
+    ) -> Result<Vec<Request>, BlockExecutionError>
+    where
+        DB: Database,
+        DB::Error: Into<ProviderError> + Display,
+    {
         let requests = if self.chain_spec.is_prague_active_at_timestamp(block.timestamp) {
             // Collect all EIP-6110 deposits
             let deposit_requests =
-                crate::eip6110::parse_deposits_from_receipts(&self.chain_spec, &receipts)?;
+                crate::eip6110::parse_deposits_from_receipts(&self.chain_spec, receipts)?;
 
             // Collect all EIP-7685 requests
             let withdrawal_requests =
-                apply_withdrawal_requests_contract_call(&self.evm_config, &mut evm)?;
+                apply_withdrawal_requests_contract_call(&self.evm_config, evm)?;
 
             // Collect all EIP-7251 requests
             let consolidation_requests =
-                apply_consolidation_requests_contract_call(&self.evm_config, &mut evm)?;
+                apply_consolidation_requests_contract_call(&self.evm_config, evm)?;
 
             [deposit_requests, withdrawal_requests, consolidation_requests].concat()
         } else {
             vec![]
         };
 
-        Ok(EthExecuteOutput { receipts, requests, gas_used: cumulative_gas_used })
+        Ok(requests)
     }
 }
 
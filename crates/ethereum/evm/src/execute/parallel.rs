@@ -0,0 +1,304 @@
+//! Speculative parallel execution of the transactions of a block.
+
+use super::{EthBlockExecutor, EthEvmExecutor, EthExecuteOutput};
+use core::fmt::Display;
+use rayon::prelude::*;
+use reth_evm::{
+    execute::{BlockExecutionError, BlockExecutionInput, BlockExecutionOutput, ProviderError},
+    read_set::TrackedExecution,
+    ConfigureEvm,
+};
+use reth_primitives::{Address, BlockWithSenders, Receipt, B256, U256};
+use reth_revm::{
+    db::{states::bundle_state::BundleRetention, WrapDatabaseRef},
+    Evm, State,
+};
+use revm_primitives::{db::DatabaseRef, AccountInfo, Bytecode, EnvWithHandlerCfg};
+
+impl<EvmConfig> EthEvmExecutor<EvmConfig>
+where
+    EvmConfig: ConfigureEvm,
+{
+    /// Executes the transactions in the block in parallel and returns their receipts and the total
+    /// gas used.
+    ///
+    /// Every transaction is first executed speculatively on top of the state before the block's
+    /// transactions, recording the state it reads. The results are then committed in order. If the
+    /// state a transaction read was changed by a previous transaction of the block, its result is
+    /// discarded and the transaction is executed again on top of the current state, so the outcome
+    /// is identical to executing the transactions serially.
+    fn execute_transactions_parallel<Ext, DB>(
+        &self,
+        block: &BlockWithSenders,
+        evm: &mut Evm<'_, Ext, &mut State<WrapDatabaseRef<DB>>>,
+    ) -> Result<(Vec<Receipt>, u64), BlockExecutionError>
+    where
+        DB: DatabaseRef<Error: Into<ProviderError> + Display> + Sync,
+    {
+        let env = EnvWithHandlerCfg::new(evm.context.evm.env.clone(), evm.handler.cfg);
+        let snapshot = StateSnapshot(&**evm.db());
+        let mut executions: Vec<Option<TrackedExecution>> = block
+            .body
+            .par_iter()
+            .zip(block.senders.par_iter())
+            .map(|(transaction, sender)| {
+                let mut env = env.clone();
+                self.evm_config.fill_tx_env(&mut env.tx, transaction, *sender);
+                // failed transactions are executed again serially, which surfaces the error if
+                // it wasn't caused by a previous transaction of the block
+                TrackedExecution::transact(&self.evm_config, &mut WrapDatabaseRef(&snapshot), env)
+                    .ok()
+            })
+            .collect();
+
+        self.execute_transactions(block, evm, |index, state| {
+            let Some(execution) = executions[index].take() else { return Ok(None) };
+            execution.into_result_on(state).map_err(|err| {
+                let err: ProviderError = err.into();
+                err.into()
+            })
+        })
+    }
+}
+
+impl<EvmConfig, DB> EthBlockExecutor<EvmConfig, WrapDatabaseRef<DB>>
+where
+    EvmConfig: ConfigureEvm,
+    DB: DatabaseRef<Error: Into<ProviderError> + Display> + Sync,
+{
+    /// Executes the block like [`Executor::execute`](reth_evm::execute::Executor::execute), but
+    /// executes the transactions of the block in parallel.
+    ///
+    /// See [`EthEvmExecutor::execute_transactions_parallel`].
+    pub(crate) fn execute_parallel(
+        mut self,
+        input: BlockExecutionInput<'_, BlockWithSenders>,
+    ) -> Result<BlockExecutionOutput<Receipt>, BlockExecutionError> {
+        let BlockExecutionInput { block, total_difficulty } = input;
+
+        // 1. prepare state on new block
+        self.on_new_block(&block.header);
+
+        // 2. configure the evm and execute
+        let env = self.evm_env_for_block(&block.header, total_difficulty);
+        let EthExecuteOutput { receipts, requests, gas_used } = {
+            let mut evm = self.executor.evm_config.evm_with_env(&mut self.state, env);
+            self.executor.apply_pre_execution_changes(block, &mut evm)?;
+            let (receipts, gas_used) =
+                self.executor.execute_transactions_parallel(block, &mut evm)?;
+            let requests =
+                self.executor.apply_post_execution_changes(block, &receipts, &mut evm)?;
+            EthExecuteOutput { receipts, requests, gas_used }
+        };
+
+        // 3. apply post execution changes
+        self.post_execution(block, total_difficulty)?;
+
+        // NOTE: we need to merge keep the reverts for the bundle retention
+        self.state.merge_transitions(BundleRetention::Reverts);
+
+        Ok(BlockExecutionOutput { state: self.state.take_bundle(), receipts, requests, gas_used })
+    }
+}
+
+/// A read-only view of a [State] that transactions can be executed on concurrently.
+///
+/// Values that aren't cached by the state are read from the underlying database.
+#[derive(Debug)]
+struct StateSnapshot<'a, DB: DatabaseRef>(&'a State<WrapDatabaseRef<DB>>);
+
+impl<DB: DatabaseRef> DatabaseRef for StateSnapshot<'_, DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.0.cache.accounts.get(&address) {
+            Some(account) => Ok(account.account_info()),
+            None => self.0.database.0.basic_ref(address),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.0.cache.contracts.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.0.database.0.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let Some(account) = self.0.cache.accounts.get(&address) else {
+            return self.0.database.0.storage_ref(address, index)
+        };
+        let Some(plain_account) = &account.account else { return Ok(U256::ZERO) };
+        match plain_account.storage.get(&index) {
+            Some(value) => Ok(*value),
+            // if the account was destroyed or newly created, its storage isn't in the database
+            None if account.status.is_storage_known() => Ok(U256::ZERO),
+            None => self.0.database.0.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        match self.0.block_hashes.get(&number) {
+            Some(hash) => Ok(*hash),
+            None => self.0.database.0.block_hash_ref(number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{execute::EthExecutorProvider, EthEvmConfig};
+    use reth_chainspec::{ChainSpecBuilder, MAINNET};
+    use reth_evm::execute::{BlockExecutorProvider, Executor};
+    use reth_primitives::{
+        constants::ETH_TO_WEI, keccak256, public_key_to_address, Account, Address, Block,
+        BlockWithSenders, Bytes, Header, Transaction, TransactionSigned, TxKind, TxLegacy, U256,
+    };
+    use reth_revm::{database::StateProviderDatabase, test_utils::StateProviderTest};
+    use reth_testing_utils::generators::{self, sign_tx_with_key_pair};
+    use secp256k1::{Keypair, Secp256k1};
+    use std::{collections::HashMap, sync::Arc};
+
+    const BASE_FEE: u64 = 7;
+
+    /// `sstore(0, add(sload(0), 1))`
+    const COUNTER_CODE: [u8; 10] = [0x60, 0x00, 0x54, 0x60, 0x01, 0x01, 0x60, 0x00, 0x55, 0x00];
+
+    const COUNTER: Address = Address::repeat_byte(0xc0);
+
+    const BENEFICIARY: Address = Address::repeat_byte(0xbe);
+
+    /// Test state with funded senders and a counter contract.
+    struct TestState {
+        db: StateProviderTest,
+        senders: Vec<Keypair>,
+    }
+
+    impl TestState {
+        fn new(senders: usize) -> Self {
+            let mut db = StateProviderTest::default();
+            let secp = Secp256k1::new();
+            let senders = (0..senders)
+                .map(|_| {
+                    let key_pair = Keypair::new(&secp, &mut generators::rng());
+                    db.insert_account(
+                        public_key_to_address(key_pair.public_key()),
+                        Account { nonce: 0, balance: U256::from(ETH_TO_WEI), bytecode_hash: None },
+                        None,
+                        HashMap::new(),
+                    );
+                    key_pair
+                })
+                .collect();
+
+            let code = Bytes::from_static(&COUNTER_CODE);
+            db.insert_account(
+                COUNTER,
+                Account { nonce: 1, balance: U256::ZERO, bytecode_hash: Some(keccak256(&code)) },
+                Some(code),
+                HashMap::from([(Default::default(), U256::from(1))]),
+            );
+
+            Self { db, senders }
+        }
+
+        fn transfer(
+            &self,
+            sender: usize,
+            nonce: u64,
+            to: Address,
+            value: u64,
+        ) -> TransactionSigned {
+            sign_tx_with_key_pair(
+                self.senders[sender],
+                Transaction::Legacy(TxLegacy {
+                    chain_id: Some(MAINNET.chain.id()),
+                    nonce,
+                    gas_price: BASE_FEE as u128 + 1,
+                    gas_limit: 100_000,
+                    to: TxKind::Call(to),
+                    value: U256::from(value),
+                    input: Default::default(),
+                }),
+            )
+        }
+
+        fn sender(&self, sender: usize) -> Address {
+            public_key_to_address(self.senders[sender].public_key())
+        }
+
+        /// Executes the block serially and in parallel and asserts that the outputs are identical.
+        fn assert_parallel_execution(&self, body: Vec<TransactionSigned>) {
+            let chain_spec =
+                Arc::new(ChainSpecBuilder::from(&*MAINNET).shanghai_activated().build());
+            let provider = EthExecutorProvider::new(chain_spec, EthEvmConfig::default());
+
+            let header = Header {
+                number: 1,
+                beneficiary: BENEFICIARY,
+                gas_limit: 30_000_000,
+                base_fee_per_gas: Some(BASE_FEE),
+                ..Default::default()
+            };
+            let senders = body.iter().map(|tx| tx.recover_signer().unwrap()).collect();
+            let block = BlockWithSenders {
+                block: Block { header, body, ommers: vec![], withdrawals: None, requests: None },
+                senders,
+            };
+
+            let mut serial = provider
+                .executor(StateProviderDatabase::new(&self.db))
+                .execute((&block, U256::ZERO).into())
+                .unwrap();
+            let mut parallel = provider
+                .execute_parallel(StateProviderDatabase::new(&self.db), (&block, U256::ZERO).into())
+                .unwrap();
+
+            // the accounts in the reverts of a block are ordered by hash map iteration
+            serial.state.reverts.sort();
+            parallel.state.reverts.sort();
+            assert_eq!(serial, parallel);
+        }
+    }
+
+    #[test]
+    fn independent_transfers() {
+        let state = TestState::new(4);
+        let body =
+            (0..4).map(|i| state.transfer(i, 0, Address::with_last_byte(i as u8), 1)).collect();
+        state.assert_parallel_execution(body);
+    }
+
+    #[test]
+    fn same_sender() {
+        let state = TestState::new(1);
+        let body = (0..4).map(|nonce| state.transfer(0, nonce, Address::random(), 1)).collect();
+        state.assert_parallel_execution(body);
+    }
+
+    #[test]
+    fn shared_recipient() {
+        let state = TestState::new(4);
+        let recipient = state.sender(0);
+        let body = (0..4).map(|i| state.transfer(i, 0, recipient, 1)).collect();
+        state.assert_parallel_execution(body);
+    }
+
+    #[test]
+    fn shared_storage() {
+        let state = TestState::new(3);
+        let body = (0..3).map(|i| state.transfer(i, 0, COUNTER, 0)).collect();
+        state.assert_parallel_execution(body);
+    }
+
+    #[test]
+    fn beneficiary_transfer() {
+        let state = TestState::new(3);
+        let body = vec![
+            state.transfer(0, 0, Address::random(), 1),
+            state.transfer(1, 0, BENEFICIARY, (ETH_TO_WEI / 2) as u64),
+            state.transfer(2, 0, Address::random(), 1),
+        ];
+        state.assert_parallel_execution(body);
+    }
+}
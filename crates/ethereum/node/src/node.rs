@@ -3,12 +3,18 @@
 use std::sync::Arc;
 
 use reth_auto_seal_consensus::AutoSealConsensus;
-use reth_basic_payload_builder::{BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig};
+use reth_basic_payload_builder::{
+    BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig, BoxedPayloadBuilder,
+    DatabaseParallelStateRoot, MultiStrategyPayloadJobGenerator, PayloadStrategy,
+};
 use reth_beacon_consensus::EthBeaconConsensus;
 use reth_chainspec::ChainSpec;
 use reth_ethereum_engine_primitives::{
     EthBuiltPayload, EthPayloadAttributes, EthPayloadBuilderAttributes,
 };
+#[cfg(unix)]
+use reth_ethereum_payload_builder::ExternalTransactionSelector;
+use reth_ethereum_payload_builder::GreedyTransactionSelector;
 use reth_evm_ethereum::execute::EthExecutorProvider;
 use reth_network::NetworkHandle;
 use reth_node_api::{FullNodeComponents, NodeAddOns};
@@ -18,15 +24,19 @@ use reth_node_builder::{
         PayloadServiceBuilder, PoolBuilder,
     },
     node::{FullNodeTypes, NodeTypes},
-    BuilderContext, ConfigureEvm, Node, PayloadBuilderConfig, PayloadTypes,
+    BuilderContext, ConfigureEvm, Node, PayloadBuilderConfig, PayloadBuilderStrategy, PayloadTypes,
 };
 use reth_payload_builder::{PayloadBuilderHandle, PayloadBuilderService};
-use reth_provider::CanonStateSubscriptions;
+use reth_payload_relay::{
+    BeaconChainConfig, BuilderSigner, RelayBuilderHandle, RelayBuilderService, RelayClient,
+    RelayPayloadBuilder,
+};
+use reth_provider::{CanonStateSubscriptions, StateProviderFactory};
 use reth_rpc::EthApi;
 use reth_tracing::tracing::{debug, info};
 use reth_transaction_pool::{
-    blobstore::DiskFileBlobStore, EthTransactionPool, TransactionPool,
-    TransactionValidationTaskExecutor,
+    blobstore::{OpenDiskFileBlobStore, SegmentedBlobStore, SegmentedBlobStoreConfig},
+    EthTransactionPool, TransactionPool, TransactionValidationTaskExecutor,
 };
 
 use crate::{EthEngineTypes, EthEvmConfig};
@@ -136,22 +146,32 @@ impl<Node> PoolBuilder<Node> for EthereumPoolBuilder
 where
     Node: FullNodeTypes,
 {
-    type Pool = EthTransactionPool<Node::Provider, DiskFileBlobStore>;
+    type Pool = EthTransactionPool<Node::Provider, SegmentedBlobStore>;
 
     async fn build_pool(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::Pool> {
         let data_dir = ctx.config().datadir();
         let pool_config = ctx.pool_config();
-        let blob_store = DiskFileBlobStore::open(data_dir.blobstore(), Default::default())?;
-        let validator = TransactionValidationTaskExecutor::eth_builder(ctx.chain_spec())
-            .with_head_timestamp(ctx.head().timestamp)
-            .kzg_settings(ctx.kzg_settings()?)
-            .with_local_transactions_config(pool_config.local_transactions_config.clone())
-            .with_additional_tasks(ctx.config().txpool.additional_validation_tasks)
-            .build_with_tasks(
-                ctx.provider().clone(),
-                ctx.task_executor().clone(),
-                blob_store.clone(),
-            );
+        let mut blob_store_config = SegmentedBlobStoreConfig::default();
+        if ctx.config().txpool.journal {
+            // the journal relies on the blob store to keep the sidecars of blob transactions
+            blob_store_config = blob_store_config.with_open(OpenDiskFileBlobStore::ReIndex);
+        }
+        let blob_store = SegmentedBlobStore::open(data_dir.blobstore(), blob_store_config)?;
+        let mut validator_builder =
+            TransactionValidationTaskExecutor::eth_builder(ctx.chain_spec())
+                .with_head_timestamp(ctx.head().timestamp)
+                .kzg_settings(ctx.kzg_settings()?)
+                .with_local_transactions_config(pool_config.local_transactions_config.clone())
+                .with_additional_tasks(ctx.config().txpool.additional_validation_tasks)
+                .with_external_tasks(ctx.config().txpool.external_validation_tasks);
+        if let Some(simulation) = ctx.config().txpool.simulation_config() {
+            validator_builder = validator_builder.with_simulation(simulation);
+        }
+        let validator = validator_builder.build_with_tasks(
+            ctx.provider().clone(),
+            ctx.task_executor().clone(),
+            blob_store.clone(),
+        );
 
         let transaction_pool =
             reth_transaction_pool::Pool::eth_pool(validator, blob_store, pool_config);
@@ -177,6 +197,42 @@ where
                 },
             );
 
+            if ctx.config().txpool.journal {
+                let journal_config =
+                    reth_transaction_pool::journal::TransactionJournalConfig::with_journal(
+                        data_dir.txpool_journal(),
+                    );
+
+                ctx.task_executor().spawn_critical_with_graceful_shutdown_signal(
+                    "transaction pool journal task",
+                    |shutdown| {
+                        reth_transaction_pool::journal::journal_transactions_task(
+                            shutdown,
+                            pool.clone(),
+                            journal_config,
+                        )
+                    },
+                );
+            }
+
+            if let Some(recording_path) = ctx.config().txpool.record.clone() {
+                let client = ctx.provider().clone();
+                let chain_events = ctx.provider().canonical_state_stream();
+                ctx.task_executor().spawn_critical_with_graceful_shutdown_signal(
+                    "transaction pool recorder task",
+                    |shutdown| {
+                        reth_transaction_pool::record::record_pool_task(
+                            shutdown,
+                            client,
+                            pool.clone(),
+                            chain_events,
+                            recording_path,
+                        )
+                    },
+                );
+                info!(target: "reth::cli", "Recording transaction pool");
+            }
+
             // spawn the maintenance task
             ctx.task_executor().spawn_critical(
                 "txpool maintenance task",
@@ -229,21 +285,61 @@ where
         let payload_builder =
             reth_ethereum_payload_builder::EthereumPayloadBuilder::new(self.evm_config);
         let conf = ctx.payload_builder_config();
+        let relay = spawn_relay_builder(ctx, &conf)?;
+        let strategies = match conf.strategies() {
+            // payloads submitted to relays are built with the default strategy
+            [] if relay.is_some() => &[PayloadBuilderStrategy::BundleFirst][..],
+            strategies => strategies,
+        };
 
         let payload_job_config = BasicPayloadJobGeneratorConfig::default()
             .interval(conf.interval())
             .deadline(conf.deadline())
-            .max_payload_tasks(conf.max_payload_tasks())
-            .extradata(conf.extradata_bytes());
+            // every strategy builds in its own task
+            .max_payload_tasks(conf.max_payload_tasks().max(strategies.len()))
+            .extradata(conf.extradata_bytes())
+            .simulation_cache(conf.simulation_cache());
+        let parallel_state_root =
+            Arc::new(DatabaseParallelStateRoot::<Node::DB, _>::new(ctx.provider().clone()));
+
+        if strategies.is_empty() {
+            let payload_generator = BasicPayloadJobGenerator::with_builder(
+                ctx.provider().clone(),
+                pool,
+                ctx.task_executor().clone(),
+                payload_job_config,
+                ctx.chain_spec(),
+                payload_builder,
+            )
+            .with_parallel_state_root(parallel_state_root);
+            let (payload_service, payload_builder) = PayloadBuilderService::new(
+                payload_generator,
+                ctx.provider().canonical_state_stream(),
+            );
+
+            ctx.task_executor()
+                .spawn_critical("payload builder service", Box::pin(payload_service));
+
+            return Ok(payload_builder)
+        }
 
-        let payload_generator = BasicPayloadJobGenerator::with_builder(
+        info!(target: "reth::cli", ?strategies, "Building payloads with competing strategies");
+        let strategies = strategies
+            .iter()
+            .map(|strategy| {
+                payload_strategy(*strategy, &conf, payload_builder.clone(), relay.clone())
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        let payload_generator = MultiStrategyPayloadJobGenerator::new(
             ctx.provider().clone(),
             pool,
             ctx.task_executor().clone(),
             payload_job_config,
             ctx.chain_spec(),
-            payload_builder,
-        );
+            strategies,
+        )
+        .with_parallel_state_root(parallel_state_root);
         let (payload_service, payload_builder) =
             PayloadBuilderService::new(payload_generator, ctx.provider().canonical_state_stream());
 
@@ -253,6 +349,75 @@ where
     }
 }
 
+/// Spawns the [`RelayBuilderService`] if relays are configured, and returns its handle.
+fn spawn_relay_builder<Node: FullNodeTypes>(
+    ctx: &BuilderContext<Node>,
+    conf: &impl PayloadBuilderConfig,
+) -> eyre::Result<Option<RelayBuilderHandle>> {
+    if conf.relays().is_empty() {
+        return Ok(None)
+    }
+
+    let chain = ctx.chain_spec().chain;
+    let beacon = BeaconChainConfig::for_chain(chain)
+        .ok_or_else(|| eyre::eyre!("submitting payloads to relays is not supported on {chain}"))?;
+    let secret_key = conf
+        .relay_secret_key()
+        .ok_or_else(|| eyre::eyre!("submitting payloads to relays requires a BLS secret key"))?;
+    let signer = BuilderSigner::from_file(secret_key, beacon.genesis_fork_version)?;
+    info!(target: "reth::cli", relays = ?conf.relays(), builder_pubkey = %signer.public_key(), "Submitting payloads to relays");
+
+    let relays = conf.relays().iter().cloned().map(RelayClient::new).collect();
+    let (relay_service, relay) = RelayBuilderService::new(relays, signer, ctx.chain_spec(), beacon);
+    ctx.task_executor().spawn_critical("relay builder service", Box::pin(relay_service.run()));
+
+    Ok(Some(relay))
+}
+
+/// Returns the [`PayloadStrategy`] that builds payloads with the given builder and the
+/// transaction selection of the given strategy, and submits them to the relays if enabled.
+fn payload_strategy<Pool, Client, Evm>(
+    strategy: PayloadBuilderStrategy,
+    conf: &impl PayloadBuilderConfig,
+    payload_builder: reth_ethereum_payload_builder::EthereumPayloadBuilder<Evm>,
+    relay: Option<RelayBuilderHandle>,
+) -> eyre::Result<
+    PayloadStrategy<
+        BoxedPayloadBuilder<Pool, Client, EthPayloadBuilderAttributes, EthBuiltPayload>,
+    >,
+>
+where
+    Pool: TransactionPool + 'static,
+    Client: StateProviderFactory + 'static,
+    Evm: ConfigureEvm,
+{
+    let builder = match strategy {
+        PayloadBuilderStrategy::Greedy => BoxedPayloadBuilder::new(
+            payload_builder.with_selector(GreedyTransactionSelector::new()),
+        ),
+        PayloadBuilderStrategy::BundleFirst => BoxedPayloadBuilder::new(payload_builder),
+        #[cfg(unix)]
+        PayloadBuilderStrategy::External => {
+            let ipc_path = conf.external_builder_ipc().ok_or_else(|| {
+                eyre::eyre!("the external payload strategy requires --builder.external-ipc")
+            })?;
+            let selector =
+                ExternalTransactionSelector::new(ipc_path, conf.external_builder_timeout());
+            BoxedPayloadBuilder::new(payload_builder.with_selector(selector))
+        }
+        #[cfg(not(unix))]
+        PayloadBuilderStrategy::External => {
+            let _ = conf;
+            eyre::bail!("the external payload strategy is only supported on unix")
+        }
+    };
+    let builder = match relay {
+        Some(relay) => BoxedPayloadBuilder::new(RelayPayloadBuilder::new(builder, relay)),
+        None => builder,
+    };
+    Ok(PayloadStrategy::new(strategy.name(), builder))
+}
+
 /// A basic ethereum payload service.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct EthereumNetworkBuilder {
@@ -0,0 +1,203 @@
+//! Transaction selection by an external builder over IPC.
+
+use crate::selector::{DefaultTransactionSelector, TransactionSelector};
+use reth_payload_builder::EthPayloadBuilderAttributes;
+use reth_primitives::{Bytes, TransactionSigned, TransactionSignedEcRecovered};
+use serde_json::{json, Value};
+use std::{
+    io::{self, Write},
+    os::unix::net::UnixStream,
+    path::PathBuf,
+    time::Duration,
+};
+use tracing::{trace, warn};
+
+/// The JSON-RPC method the external builder is asked for the transactions of a payload with.
+pub const EXTERNAL_BUILDER_METHOD: &str = "builder_getTransactions";
+
+/// A [`TransactionSelector`] that includes the transactions chosen by an external builder.
+///
+/// For every build attempt, the builder listening on the IPC socket is sent a
+/// [`builder_getTransactions`](EXTERNAL_BUILDER_METHOD) JSON-RPC request with the attributes of
+/// the payload, and responds with the EIP-2718 encoded transactions of the payload, in order.
+/// The transactions are executed like an inclusion list, so invalid transactions are skipped and
+/// blob transactions must be in the pool. The remaining block space is filled with the bundles and
+/// transactions of the pool, like the [`DefaultTransactionSelector`] does.
+///
+/// If the builder can't be reached or responds with an error, the payload only contains
+/// transactions of the pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalTransactionSelector {
+    /// The path of the IPC socket of the external builder.
+    ipc_path: PathBuf,
+    /// How long to wait for the external builder to respond.
+    timeout: Duration,
+    /// The limits of the payload.
+    limits: DefaultTransactionSelector,
+}
+
+impl ExternalTransactionSelector {
+    /// Creates a selector that asks the external builder listening on the given IPC socket.
+    pub fn new(ipc_path: impl Into<PathBuf>, timeout: Duration) -> Self {
+        Self { ipc_path: ipc_path.into(), timeout, limits: DefaultTransactionSelector::new() }
+    }
+
+    /// Caps the blob gas of the payload.
+    pub const fn with_max_blob_gas(mut self, max_blob_gas: u64) -> Self {
+        self.limits = self.limits.with_max_blob_gas(max_blob_gas);
+        self
+    }
+
+    /// Requests the transactions of the payload with the given attributes from the external
+    /// builder.
+    fn request_transactions(
+        &self,
+        attributes: &EthPayloadBuilderAttributes,
+    ) -> io::Result<Vec<TransactionSignedEcRecovered>> {
+        let mut stream = UnixStream::connect(&self.ipc_path)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": EXTERNAL_BUILDER_METHOD,
+            "params": [{
+                "payloadId": attributes.id,
+                "parentHash": attributes.parent,
+                "timestamp": format!("{:#x}", attributes.timestamp),
+                "suggestedFeeRecipient": attributes.suggested_fee_recipient,
+                "prevRandao": attributes.prev_randao,
+                "parentBeaconBlockRoot": attributes.parent_beacon_block_root,
+            }],
+        });
+        serde_json::to_writer(&mut stream, &request)?;
+        stream.write_all(b"\n")?;
+
+        let mut response = serde_json::Deserializer::from_reader(&mut stream).into_iter::<Value>();
+        let mut response =
+            response.next().ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))??;
+        if let Some(error) = response.get("error") {
+            return Err(io::Error::other(format!("external builder responded with {error}")))
+        }
+
+        let transactions: Vec<Bytes> = serde_json::from_value(
+            response.get_mut("result").map(Value::take).unwrap_or_default(),
+        )?;
+        transactions
+            .into_iter()
+            .map(|raw| {
+                TransactionSigned::decode_enveloped(&mut raw.as_ref())
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+                    .into_ecrecovered()
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "invalid transaction signature")
+                    })
+            })
+            .collect()
+    }
+}
+
+impl TransactionSelector for ExternalTransactionSelector {
+    fn inclusion_list(
+        &self,
+        attributes: &EthPayloadBuilderAttributes,
+    ) -> Vec<TransactionSignedEcRecovered> {
+        match self.request_transactions(attributes) {
+            Ok(transactions) => {
+                trace!(target: "payload_builder", id=%attributes.id, transactions = transactions.len(), "received transactions of external builder");
+                transactions
+            }
+            Err(err) => {
+                warn!(target: "payload_builder", id=%attributes.id, %err, ipc_path = %self.ipc_path.display(), "failed to request transactions of external builder");
+                Vec::new()
+            }
+        }
+    }
+
+    fn max_blob_gas(&self) -> u64 {
+        self.limits.max_blob_gas()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::B256;
+    use reth_rpc_types::engine::PayloadAttributes;
+    use reth_testing_utils::generators::{self, random_signed_tx};
+    use std::{io::BufRead, os::unix::net::UnixListener, thread};
+
+    fn attributes() -> EthPayloadBuilderAttributes {
+        EthPayloadBuilderAttributes::new(
+            B256::with_last_byte(1),
+            PayloadAttributes {
+                timestamp: 2,
+                prev_randao: B256::ZERO,
+                suggested_fee_recipient: Default::default(),
+                withdrawals: Some(Vec::new()),
+                parent_beacon_block_root: None,
+            },
+        )
+    }
+
+    /// Serves a single request on a new IPC socket with the given response and returns the path
+    /// of the socket and the received request.
+    fn serve(name: &str, response: Value) -> (PathBuf, thread::JoinHandle<Value>) {
+        let path = std::env::temp_dir().join(format!("reth-{name}-{}.ipc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            io::BufReader::new(&mut stream).read_line(&mut line).unwrap();
+            let request: Value = serde_json::from_str(&line).unwrap();
+            let mut response = response;
+            response["jsonrpc"] = "2.0".into();
+            response["id"] = request["id"].clone();
+            serde_json::to_writer(&mut stream, &response).unwrap();
+            request
+        });
+        (path, handle)
+    }
+
+    #[test]
+    fn includes_transactions_of_external_builder() {
+        let mut rng = generators::rng();
+        let tx = random_signed_tx(&mut rng);
+        let (path, server) =
+            serve("external-builder", json!({ "result": [tx.envelope_encoded()] }));
+
+        let attributes = attributes();
+        let selector = ExternalTransactionSelector::new(&path, Duration::from_secs(1));
+        let transactions = selector.inclusion_list(&attributes);
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].hash, tx.hash);
+
+        let request = server.join().unwrap();
+        assert_eq!(request["method"], EXTERNAL_BUILDER_METHOD);
+        assert_eq!(request["params"][0]["parentHash"], json!(attributes.parent));
+        assert_eq!(request["params"][0]["timestamp"], "0x2");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn includes_nothing_on_error() {
+        let (path, server) = serve(
+            "external-builder-error",
+            json!({ "error": { "code": -32000, "message": "no payload" } }),
+        );
+
+        let selector = ExternalTransactionSelector::new(&path, Duration::from_secs(1));
+        assert!(selector.inclusion_list(&attributes()).is_empty());
+        server.join().unwrap();
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn includes_nothing_without_builder() {
+        let path = std::env::temp_dir().join("reth-external-builder-missing.ipc");
+        let selector = ExternalTransactionSelector::new(path, Duration::from_secs(1));
+        assert!(selector.inclusion_list(&attributes()).is_empty());
+    }
+}
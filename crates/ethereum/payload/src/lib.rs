@@ -9,6 +9,7 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 #![allow(clippy::useless_let_if_seq)]
 
+use alloy_eips::eip7002::WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS;
 use reth_basic_payload_builder::{
     commit_withdrawals, is_better_payload, BuildArguments, BuildOutcome, PayloadBuilder,
     PayloadConfig, WithdrawalsOutcome,
@@ -23,7 +24,7 @@ use reth_evm::{
 use reth_evm_ethereum::{eip6110::parse_deposits_from_receipts, EthEvmConfig};
 use reth_execution_types::ExecutionOutcome;
 use reth_payload_builder::{
-    error::PayloadBuilderError, EthBuiltPayload, EthPayloadBuilderAttributes,
+    error::PayloadBuilderError, EthBuiltPayload, EthPayloadBuilderAttributes, SimulationCache,
 };
 use reth_primitives::{
     constants::{
@@ -31,30 +32,60 @@ use reth_primitives::{
     },
     eip4844::calculate_excess_blob_gas,
     proofs::{self, calculate_requests_root},
-    Block, EthereumHardforks, Header, IntoRecoveredTransaction, Receipt, EMPTY_OMMER_ROOT_HASH,
-    U256,
+    Address, Block, EthereumHardforks, Header, IntoRecoveredTransaction, Receipt,
+    TransactionSignedEcRecovered, TxHash, EMPTY_OMMER_ROOT_HASH, U256,
 };
 use reth_provider::StateProviderFactory;
 use reth_revm::{database::StateProviderDatabase, state_change::apply_blockhashes_update};
-use reth_transaction_pool::{BestTransactionsAttributes, TransactionPool};
+use reth_transaction_pool::{
+    BestTransactionsAttributes, BundleOrTransaction, TransactionPool, ValidPoolTransaction,
+};
+use reth_trie::HashedPostState;
 use revm::{
     db::states::bundle_state::BundleRetention,
     primitives::{EVMError, EnvWithHandlerCfg, InvalidTransaction, ResultAndState},
-    DatabaseCommit, State,
+    Database, DatabaseCommit, State,
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
 };
 use tracing::{debug, trace, warn};
 
+mod overlay;
+use overlay::BundleOverlay;
+
+mod selector;
+pub use selector::{
+    DefaultTransactionSelector, GreedyTransactionSelector, SelectionState, TransactionDecision,
+    TransactionSelector,
+};
+
+#[cfg(unix)]
+mod external;
+#[cfg(unix)]
+pub use external::{ExternalTransactionSelector, EXTERNAL_BUILDER_METHOD};
+
 /// Ethereum payload builder
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct EthereumPayloadBuilder<EvmConfig = EthEvmConfig> {
+pub struct EthereumPayloadBuilder<EvmConfig = EthEvmConfig, Selector = DefaultTransactionSelector> {
     /// The type responsible for creating the evm.
     evm_config: EvmConfig,
+    /// The type responsible for selecting the transactions of the payload.
+    selector: Selector,
 }
 
 impl<EvmConfig> EthereumPayloadBuilder<EvmConfig> {
     /// `EthereumPayloadBuilder` constructor.
     pub const fn new(evm_config: EvmConfig) -> Self {
-        Self { evm_config }
+        Self { evm_config, selector: DefaultTransactionSelector::new() }
+    }
+}
+
+impl<EvmConfig, Selector> EthereumPayloadBuilder<EvmConfig, Selector> {
+    /// Configures the [`TransactionSelector`] that decides which transactions are included.
+    pub fn with_selector<S>(self, selector: S) -> EthereumPayloadBuilder<EvmConfig, S> {
+        EthereumPayloadBuilder { evm_config: self.evm_config, selector }
     }
 }
 
@@ -65,9 +96,11 @@ impl Default for EthereumPayloadBuilder {
 }
 
 // Default implementation of [PayloadBuilder] for unit type
-impl<EvmConfig, Pool, Client> PayloadBuilder<Pool, Client> for EthereumPayloadBuilder<EvmConfig>
+impl<EvmConfig, Selector, Pool, Client> PayloadBuilder<Pool, Client>
+    for EthereumPayloadBuilder<EvmConfig, Selector>
 where
     EvmConfig: ConfigureEvm,
+    Selector: TransactionSelector + Clone,
     Client: StateProviderFactory,
     Pool: TransactionPool,
 {
@@ -78,7 +111,7 @@ where
         &self,
         args: BuildArguments<Pool, Client, EthPayloadBuilderAttributes, EthBuiltPayload>,
     ) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError> {
-        default_ethereum_payload_builder(self.evm_config.clone(), args)
+        default_ethereum_payload_builder(self.evm_config.clone(), &self.selector, args)
     }
 
     fn build_empty_payload(
@@ -246,18 +279,21 @@ where
     }
 }
 
-/// Constructs an Ethereum transaction payload using the best transactions from the pool.
+/// Constructs an Ethereum transaction payload using the transactions chosen by the given
+/// [`TransactionSelector`].
 ///
 /// Given build arguments including an Ethereum client, transaction pool,
 /// and configuration, this function creates a transaction payload. Returns
 /// a result indicating success with the payload or an error in case of failure.
 #[inline]
-pub fn default_ethereum_payload_builder<EvmConfig, Pool, Client>(
+pub fn default_ethereum_payload_builder<EvmConfig, Selector, Pool, Client>(
     evm_config: EvmConfig,
+    selector: &Selector,
     args: BuildArguments<Pool, Client, EthPayloadBuilderAttributes, EthBuiltPayload>,
 ) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError>
 where
     EvmConfig: ConfigureEvm,
+    Selector: TransactionSelector,
     Client: StateProviderFactory,
     Pool: TransactionPool,
 {
@@ -267,7 +303,12 @@ where
     let state = StateProviderDatabase::new(state_provider);
     let mut db =
         State::builder().with_database_ref(cached_reads.as_db(state)).with_bundle_update().build();
+    // computes the state root in the background while transactions are executed
+    let state_root_task =
+        config.state_root.as_ref().map(|state_root| state_root.spawn(config.parent_block.hash()));
     let extra_data = config.extra_data();
+    let focil_inclusion_list = config.inclusion_list();
+    let simulation_cache = config.simulation_cache.clone();
     let PayloadConfig {
         initialized_block_env,
         initialized_cfg,
@@ -279,22 +320,44 @@ where
 
     debug!(target: "payload_builder", id=%attributes.id, parent_hash = ?parent_block.hash(), parent_number = parent_block.number, "building new payload");
     let mut cumulative_gas_used = 0;
-    let mut sum_blob_gas_used = 0;
     let block_gas_limit: u64 =
         initialized_block_env.gas_limit.try_into().unwrap_or(chain_spec.max_gas_limit);
     let base_fee = initialized_block_env.basefee.to::<u64>();
 
     let mut executed_txs = Vec::new();
 
-    let mut best_txs = pool.best_transactions_with_attributes(BestTransactionsAttributes::new(
-        base_fee,
-        initialized_block_env.get_blob_gasprice().map(|gasprice| gasprice as u64),
-    ));
-
     let mut total_fees = U256::ZERO;
 
     let block_number = initialized_block_env.number.to::<u64>();
 
+    // the FOCIL inclusion list is enforced by consensus, so its transactions are tried first
+    let mut inclusion_list = focil_inclusion_list
+        .filter(|_| chain_spec.is_focil_active_at_timestamp(attributes.timestamp))
+        .map(|list| list.to_vec())
+        .unwrap_or_default()
+        .into_iter()
+        .chain(selector.inclusion_list(&attributes));
+    let mut best_txs = selector.best_transactions(
+        &pool,
+        BestTransactionsAttributes::new(
+            base_fee,
+            initialized_block_env.get_blob_gasprice().map(|gasprice| gasprice as u64),
+        ),
+        block_number,
+    );
+    // hashes of the transactions included via bundles or the inclusion list
+    let mut included_txs = HashSet::new();
+    // transactions whose simulation predicts that they revert or use far less gas than their limit
+    // are tried after all other pool transactions, together with their descendants
+    let mut deprioritized_txs: VecDeque<Arc<ValidPoolTransaction<Pool::Transaction>>> =
+        VecDeque::new();
+    let mut deprioritized_senders = HashSet::new();
+    // senders of pool transactions the selector skipped together with their descendants
+    let mut skipped_senders = HashSet::new();
+    let mut selection = SelectionState::default();
+    let max_transactions_per_sender = selector.max_transactions_per_sender();
+    let max_blob_gas = selector.max_blob_gas().min(MAX_DATA_GAS_PER_BLOCK);
+
     // apply eip-4788 pre block contract call
     pre_block_beacon_root_contract_call(
         &mut db,
@@ -325,14 +388,205 @@ where
     )
     .map_err(|err| PayloadBuilderError::Internal(err.into()))?;
 
+    if let Some(task) = &state_root_task {
+        task.on_state_changed(&db);
+    }
+
     let mut receipts = Vec::new();
-    while let Some(pool_tx) = best_txs.next() {
+    loop {
+        // transactions of the inclusion list are tried before any pool transaction
+        let (pool_tx, tx) = if let Some(tx) = inclusion_list.next() {
+            (None, tx)
+        } else {
+            match best_txs.next() {
+                None => {
+                    let Some(pool_tx) = deprioritized_txs.pop_front() else { break };
+                    let tx = pool_tx.to_recovered_transaction();
+                    (Some(pool_tx), tx)
+                }
+                Some(BundleOrTransaction::Transaction(pool_tx)) => {
+                    if pool_tx.should_deprioritize() ||
+                        deprioritized_senders.contains(&pool_tx.sender())
+                    {
+                        trace!(target: "payload_builder", tx=?pool_tx.hash(), "deferring deprioritized transaction");
+                        deprioritized_senders.insert(pool_tx.sender());
+                        deprioritized_txs.push_back(pool_tx);
+                        continue
+                    }
+                    let tx = pool_tx.to_recovered_transaction();
+                    (Some(pool_tx), tx)
+                }
+                Some(BundleOrTransaction::Bundle(bundle)) => {
+                    // ensure we still have capacity for all transactions of the bundle
+                    if cumulative_gas_used + bundle.gas_limit() > block_gas_limit {
+                        trace!(target: "payload_builder", bundle=?bundle.hash(), "skipping bundle that exceeds the remaining block gas");
+                        continue
+                    }
+
+                    // check if the job was cancelled, if so we can exit early
+                    if cancel.is_cancelled() {
+                        return Ok(BuildOutcome::Cancelled)
+                    }
+
+                    // the bundle is executed atomically, so its changes are kept in an overlay that
+                    // is discarded if any of its transactions fails
+                    let mut overlay = BundleOverlay::new(&mut db);
+                    let mut bundle_gas_used = 0;
+                    let mut bundle_fees = U256::ZERO;
+                    let mut bundle_receipts = Vec::with_capacity(bundle.transactions().len());
+                    let mut bundle_txs = Vec::with_capacity(bundle.transactions().len());
+                    let mut bundle_accounts = HashSet::new();
+                    let mut bundle_blob_gas = 0;
+                    let mut bundle_senders = HashMap::<Address, usize>::new();
+                    let mut stop = false;
+                    for bundle_tx in bundle.transactions() {
+                        let tx = bundle_tx.to_recovered_transaction();
+
+                        // bundles are subject to the same blob gas budget and per-sender cap as
+                        // pool transactions
+                        let tx_blob_gas = blob_gas(&tx);
+                        if selection.blob_gas_used() + bundle_blob_gas + tx_blob_gas > max_blob_gas
+                        {
+                            trace!(target: "payload_builder", ?tx, bundle=?bundle.hash(), "skipping bundle that exceeds the max data gas per block");
+                            break
+                        }
+                        let sender_txs = bundle_senders.entry(tx.signer()).or_default();
+                        *sender_txs += 1;
+                        if max_transactions_per_sender.is_some_and(|max| {
+                            selection.transactions_by_sender(&tx.signer()) + *sender_txs > max
+                        }) {
+                            trace!(target: "payload_builder", ?tx, bundle=?bundle.hash(), "skipping bundle whose sender exceeds the max transactions per sender");
+                            break
+                        }
+
+                        match selector.pre_transaction(&tx, &selection) {
+                            TransactionDecision::Include => {}
+                            TransactionDecision::Stop => {
+                                stop = true;
+                                break
+                            }
+                            TransactionDecision::Skip |
+                            TransactionDecision::SkipWithDescendants => {
+                                trace!(target: "payload_builder", ?tx, bundle=?bundle.hash(), "skipping bundle with rejected transaction");
+                                break
+                            }
+                        }
+
+                        let env = EnvWithHandlerCfg::new_with_cfg_env(
+                            initialized_cfg.clone(),
+                            initialized_block_env.clone(),
+                            evm_config.tx_env(&tx),
+                        );
+                        let ResultAndState { result, state } = match transact(
+                            &evm_config,
+                            simulation_cache.as_ref(),
+                            &mut overlay,
+                            env,
+                            tx.hash,
+                        ) {
+                            Ok(res) => res,
+                            Err(EVMError::Transaction(err)) => {
+                                trace!(target: "payload_builder", %err, ?tx, bundle=?bundle.hash(), "skipping bundle with invalid transaction");
+                                break
+                            }
+                            Err(err) => {
+                                // this is an error that we should treat as fatal for this attempt
+                                return Err(PayloadBuilderError::EvmExecutionError(err))
+                            }
+                        };
+
+                        if !result.is_success() && !bundle.can_revert(&tx.hash) {
+                            trace!(target: "payload_builder", ?tx, bundle=?bundle.hash(), "skipping bundle with reverted transaction");
+                            break
+                        }
+                        bundle_accounts.extend(state.keys().copied());
+                        overlay.commit(state);
+
+                        let gas_used = result.gas_used();
+                        bundle_gas_used += gas_used;
+                        bundle_blob_gas += tx_blob_gas;
+
+                        #[allow(clippy::needless_update)] // side-effect of optimism fields
+                        bundle_receipts.push(Some(Receipt {
+                            tx_type: tx.tx_type(),
+                            success: result.is_success(),
+                            cumulative_gas_used: cumulative_gas_used + bundle_gas_used,
+                            logs: result.logs().to_vec(),
+                            ..Default::default()
+                        }));
+
+                        let miner_fee = tx
+                            .effective_tip_per_gas(Some(base_fee))
+                            .expect("fee is always valid; execution succeeded");
+                        bundle_fees += U256::from(miner_fee) * U256::from(gas_used);
+
+                        bundle_txs.push((tx, result));
+                    }
+
+                    if bundle_txs.len() != bundle.transactions().len() {
+                        // not all transactions were executed successfully, discard the bundle
+                        if stop {
+                            break
+                        }
+                        continue
+                    }
+
+                    overlay.commit_to();
+                    if let Some(task) = &state_root_task {
+                        task.on_accounts_changed(&db, bundle_accounts);
+                    }
+                    cumulative_gas_used += bundle_gas_used;
+                    total_fees += bundle_fees;
+                    receipts.extend(bundle_receipts);
+                    for (tx, result) in &bundle_txs {
+                        selection.record(tx.signer(), result.gas_used(), blob_gas(tx));
+                        selector.post_transaction(tx, result, &selection);
+                    }
+                    included_txs.extend(bundle_txs.iter().map(|(tx, _)| tx.hash));
+                    executed_txs.extend(bundle_txs.into_iter().map(|(tx, _)| tx.into_signed()));
+                    if bundle_blob_gas > 0 && selection.blob_gas_used() == max_blob_gas {
+                        best_txs.skip_blobs();
+                    }
+                    continue
+                }
+            }
+        };
+
+        if included_txs.contains(&tx.hash) {
+            // already included via a bundle or the inclusion list
+            continue
+        }
+
+        // the pool yields the transactions of a sender in nonce order, so all later transactions
+        // of a skipped sender are its descendants
+        if pool_tx.is_some() && skipped_senders.contains(&tx.signer()) {
+            trace!(target: "payload_builder", ?tx, "skipping descendant of transaction rejected by selector");
+            continue
+        }
+
+        // the pool only checks conditionals against the earliest possible next block, so they're
+        // re-checked against the block that's actually built
+        if let Some(pool_tx) = &pool_tx {
+            if pool_tx
+                .conditional
+                .as_ref()
+                .is_some_and(|c| !c.check_block(block_number, attributes.timestamp).is_met())
+            {
+                trace!(target: "payload_builder", ?tx, "skipping transaction whose conditional the payload doesn't meet");
+                best_txs.mark_invalid(pool_tx);
+                skipped_senders.insert(pool_tx.sender());
+                continue
+            }
+        }
+
         // ensure we still have capacity for this transaction
-        if cumulative_gas_used + pool_tx.gas_limit() > block_gas_limit {
+        if cumulative_gas_used + tx.gas_limit() > block_gas_limit {
             // we can't fit this transaction into the block, so we need to mark it as invalid
             // which also removes all dependent transaction from the iterator before we can
             // continue
-            best_txs.mark_invalid(&pool_tx);
+            if let Some(pool_tx) = &pool_tx {
+                best_txs.mark_invalid(pool_tx);
+            }
             continue
         }
 
@@ -341,34 +595,61 @@ where
             return Ok(BuildOutcome::Cancelled)
         }
 
-        // convert tx to a signed transaction
-        let tx = pool_tx.to_recovered_transaction();
-
         // There's only limited amount of blob space available per block, so we need to check if
         // the EIP-4844 can still fit in the block
-        if let Some(blob_tx) = tx.transaction.as_eip4844() {
-            let tx_blob_gas = blob_tx.blob_gas();
-            if sum_blob_gas_used + tx_blob_gas > MAX_DATA_GAS_PER_BLOCK {
-                // we can't fit this _blob_ transaction into the block, so we mark it as
-                // invalid, which removes its dependent transactions from
-                // the iterator. This is similar to the gas limit condition
-                // for regular transactions above.
-                trace!(target: "payload_builder", tx=?tx.hash, ?sum_blob_gas_used, ?tx_blob_gas, "skipping blob transaction because it would exceed the max data gas per block");
-                best_txs.mark_invalid(&pool_tx);
+        let tx_blob_gas = blob_gas(&tx);
+        if selection.blob_gas_used() + tx_blob_gas > max_blob_gas {
+            // we can't fit this _blob_ transaction into the block, so we mark it as
+            // invalid, which removes its dependent transactions from
+            // the iterator. This is similar to the gas limit condition
+            // for regular transactions above.
+            trace!(target: "payload_builder", tx=?tx.hash, sum_blob_gas_used=?selection.blob_gas_used(), ?tx_blob_gas, "skipping blob transaction because it would exceed the max data gas per block");
+            if let Some(pool_tx) = &pool_tx {
+                best_txs.mark_invalid(pool_tx);
+            }
+            continue
+        }
+
+        // pool transactions of senders that reached their cap are skipped together with their
+        // descendants, which are from the same sender
+        if let (Some(pool_tx), Some(max)) = (&pool_tx, max_transactions_per_sender) {
+            if selection.transactions_by_sender(&tx.signer()) >= max {
+                trace!(target: "payload_builder", tx=?tx.hash, sender=?tx.signer(), "skipping transaction because its sender reached the max transactions per sender");
+                best_txs.mark_invalid(pool_tx);
                 continue
             }
         }
 
+        match selector.pre_transaction(&tx, &selection) {
+            TransactionDecision::Include => {}
+            TransactionDecision::Skip => {
+                trace!(target: "payload_builder", ?tx, "skipping transaction rejected by selector");
+                continue
+            }
+            TransactionDecision::SkipWithDescendants => {
+                trace!(target: "payload_builder", ?tx, "skipping transaction and its descendants rejected by selector");
+                if let Some(pool_tx) = &pool_tx {
+                    best_txs.mark_invalid(pool_tx);
+                    skipped_senders.insert(pool_tx.sender());
+                }
+                continue
+            }
+            TransactionDecision::Stop => break,
+        }
+
         let env = EnvWithHandlerCfg::new_with_cfg_env(
             initialized_cfg.clone(),
             initialized_block_env.clone(),
             evm_config.tx_env(&tx),
         );
 
-        // Configure the environment for the block.
-        let mut evm = evm_config.evm_with_env(&mut db, env);
-
-        let ResultAndState { result, state } = match evm.transact() {
+        let ResultAndState { result, state } = match transact(
+            &evm_config,
+            simulation_cache.as_ref(),
+            &mut db,
+            env,
+            tx.hash,
+        ) {
             Ok(res) => res,
             Err(err) => {
                 match err {
@@ -376,11 +657,13 @@ where
                         if matches!(err, InvalidTransaction::NonceTooLow { .. }) {
                             // if the nonce is too low, we can skip this transaction
                             trace!(target: "payload_builder", %err, ?tx, "skipping nonce too low transaction");
-                        } else {
+                        } else if let Some(pool_tx) = &pool_tx {
                             // if the transaction is invalid, we can skip it and all of its
                             // descendants
                             trace!(target: "payload_builder", %err, ?tx, "skipping invalid transaction and its descendants");
-                            best_txs.mark_invalid(&pool_tx);
+                            best_txs.mark_invalid(pool_tx);
+                        } else {
+                            trace!(target: "payload_builder", %err, ?tx, "skipping invalid inclusion list transaction");
                         }
 
                         continue
@@ -392,20 +675,10 @@ where
                 }
             }
         };
-        // drop evm so db is released.
-        drop(evm);
         // commit changes
-        db.commit(state);
-
-        // add to the total blob gas used if the transaction successfully executed
-        if let Some(blob_tx) = tx.transaction.as_eip4844() {
-            let tx_blob_gas = blob_tx.blob_gas();
-            sum_blob_gas_used += tx_blob_gas;
-
-            // if we've reached the max data gas per block, we can skip blob txs entirely
-            if sum_blob_gas_used == MAX_DATA_GAS_PER_BLOCK {
-                best_txs.skip_blobs();
-            }
+        match &state_root_task {
+            Some(task) => task.commit(&mut db, state),
+            None => db.commit(state),
         }
 
         let gas_used = result.gas_used();
@@ -413,6 +686,15 @@ where
         // add gas used by the transaction to cumulative gas used, before creating the receipt
         cumulative_gas_used += gas_used;
 
+        // add to the total blob gas used if the transaction successfully executed
+        selection.record(tx.signer(), gas_used, tx_blob_gas);
+        selector.post_transaction(&tx, &result, &selection);
+
+        // if we've reached the max data gas per block, we can skip blob txs entirely
+        if tx_blob_gas > 0 && selection.blob_gas_used() == max_blob_gas {
+            best_txs.skip_blobs();
+        }
+
         // Push transaction changeset and calculate header bloom filter for receipt.
         #[allow(clippy::needless_update)] // side-effect of optimism fields
         receipts.push(Some(Receipt {
@@ -430,6 +712,9 @@ where
         total_fees += U256::from(miner_fee) * U256::from(gas_used);
 
         // append transaction to the list of executed transactions
+        if pool_tx.is_none() {
+            included_txs.insert(tx.hash);
+        }
         executed_txs.push(tx.into_signed());
     }
 
@@ -463,6 +748,18 @@ where
     let WithdrawalsOutcome { withdrawals_root, withdrawals } =
         commit_withdrawals(&mut db, &chain_spec, attributes.timestamp, attributes.withdrawals)?;
 
+    // stream the changes of the withdrawal requests contract call and the withdrawals
+    if let Some(task) = &state_root_task {
+        task.on_accounts_changed(
+            &db,
+            requests
+                .is_some()
+                .then_some(WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS)
+                .into_iter()
+                .chain(withdrawals.iter().flatten().map(|withdrawal| withdrawal.address)),
+        );
+    }
+
     // merge all transitions into bundle state, this would apply the withdrawal balance changes
     // and 4788 contract call
     db.merge_transitions(BundleRetention::PlainState);
@@ -477,10 +774,14 @@ where
         execution_outcome.receipts_root_slow(block_number).expect("Number is in range");
     let logs_bloom = execution_outcome.block_logs_bloom(block_number).expect("Number is in range");
 
-    // calculate the state root
-    let state_root = {
+    // calculate the state root, reusing the background computation if it covered the final state
+    let hashed_state = HashedPostState::from_bundle_state(&execution_outcome.state().state);
+    let (state_root, _) = {
         let state_provider = db.database.0.inner.borrow_mut();
-        state_provider.db.state_root(execution_outcome.state())?
+        match state_root_task {
+            Some(task) => task.finish(&state_provider.db.0, hashed_state)?,
+            None => state_provider.db.0.hashed_state_root_with_updates(hashed_state)?,
+        }
     };
 
     // create the block header
@@ -508,7 +809,7 @@ where
             Some(calculate_excess_blob_gas(0, 0))
         };
 
-        blob_gas_used = Some(sum_blob_gas_used);
+        blob_gas_used = Some(selection.blob_gas_used());
     }
 
     let header = Header {
@@ -548,3 +849,483 @@ where
 
     Ok(BuildOutcome::Better { payload, cached_reads })
 }
+
+/// Executes the transaction in the given environment.
+///
+/// If the simulation cache is enabled, the result of a previous execution is reused if the state
+/// the transaction read is unchanged.
+fn transact<EvmConfig, DB>(
+    evm_config: &EvmConfig,
+    simulation_cache: Option<&SimulationCache>,
+    db: &mut DB,
+    env: EnvWithHandlerCfg,
+    tx_hash: TxHash,
+) -> Result<ResultAndState, EVMError<DB::Error>>
+where
+    EvmConfig: ConfigureEvm,
+    DB: Database,
+{
+    match simulation_cache {
+        Some(cache) => cache.transact(evm_config, db, env, tx_hash),
+        None => evm_config.evm_with_env(db, env).transact(),
+    }
+}
+
+/// Returns the blob gas used by the transaction, zero for non-blob transactions.
+fn blob_gas(tx: &TransactionSignedEcRecovered) -> u64 {
+    tx.transaction.as_eip4844().map(|blob_tx| blob_tx.blob_gas()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_eips::eip4788::{BEACON_ROOTS_ADDRESS, BEACON_ROOTS_CODE};
+    use reth_basic_payload_builder::{
+        Cancelled, IncrementalStateRootSpawner, ParallelStateRootProvider,
+    };
+    use reth_chainspec::ChainSpecBuilder;
+    use reth_payload_builder::PayloadId;
+    use reth_primitives::{
+        constants::eip4844::DATA_GAS_PER_BLOB, BlobTransactionSidecar, SealedBlock, Withdrawal,
+        Withdrawals, B256,
+    };
+    use reth_provider::{
+        test_utils::{ExtendedAccount, MockEthProvider},
+        ProviderResult,
+    };
+    use reth_tasks::TokioTaskExecutor;
+    use reth_transaction_pool::{
+        blobstore::InMemoryBlobStore,
+        conditional::TransactionConditional,
+        test_utils::{MockOrdering, MockTransaction, TestPool, TestPoolBuilder},
+        validate::{SimulationStatus, TransactionSimulation, ValidTransaction},
+        NewBundle, Pool, PoolTransaction, TransactionOrigin, TransactionValidationOutcome,
+        TransactionValidator,
+    };
+    use reth_trie::updates::TrieUpdates;
+    use std::{sync::Mutex, thread::ThreadId};
+
+    /// Selector that applies fixed decisions and records the transactions it was asked about.
+    #[derive(Debug, Default)]
+    struct TestSelector {
+        decisions: HashMap<TxHash, TransactionDecision>,
+        inclusion_list: Vec<TransactionSignedEcRecovered>,
+        seen: Mutex<Vec<TxHash>>,
+    }
+
+    impl TransactionSelector for TestSelector {
+        fn inclusion_list(
+            &self,
+            _attributes: &EthPayloadBuilderAttributes,
+        ) -> Vec<TransactionSignedEcRecovered> {
+            self.inclusion_list.clone()
+        }
+
+        fn pre_transaction(
+            &self,
+            tx: &TransactionSignedEcRecovered,
+            _selection: &SelectionState,
+        ) -> TransactionDecision {
+            self.seen.lock().unwrap().push(tx.hash);
+            self.decisions.get(&tx.hash).copied().unwrap_or(TransactionDecision::Include)
+        }
+    }
+
+    /// Computes a distinct fake root on every call and records the threads it was called on.
+    #[derive(Debug, Default)]
+    struct RecordingStateRoot {
+        threads: Mutex<Vec<ThreadId>>,
+    }
+
+    impl ParallelStateRootProvider for RecordingStateRoot {
+        fn parallel_state_root_with_updates(
+            &self,
+            _parent_hash: B256,
+            _hashed_state: HashedPostState,
+        ) -> ProviderResult<(B256, TrieUpdates)> {
+            let mut threads = self.threads.lock().unwrap();
+            threads.push(std::thread::current().id());
+            Ok((B256::with_last_byte(threads.len() as u8), TrieUpdates::default()))
+        }
+    }
+
+    /// Validator that reports a reverting simulation for the transactions with the given hashes.
+    #[derive(Debug, Clone, Default)]
+    struct SimulatingValidator {
+        reverting: Arc<Mutex<HashSet<TxHash>>>,
+    }
+
+    impl TransactionValidator for SimulatingValidator {
+        type Transaction = MockTransaction;
+
+        async fn validate_transaction(
+            &self,
+            _origin: TransactionOrigin,
+            transaction: Self::Transaction,
+        ) -> TransactionValidationOutcome<Self::Transaction> {
+            let simulation =
+                self.reverting.lock().unwrap().contains(transaction.hash()).then(|| {
+                    TransactionSimulation {
+                        status: SimulationStatus::Revert,
+                        gas_used: transaction.gas_limit(),
+                        gas_limit: transaction.gas_limit(),
+                        block_number: 1,
+                        low_gas_usage: false,
+                    }
+                });
+            TransactionValidationOutcome::Valid {
+                balance: U256::MAX,
+                state_nonce: 0,
+                transaction: ValidTransaction::Valid(transaction),
+                propagate: false,
+                authorities: None,
+                simulation,
+            }
+        }
+    }
+
+    /// Returns a funded transaction of a new sender, ordered by the given priority fee.
+    fn transaction(client: &MockEthProvider, priority_fee: u128) -> MockTransaction {
+        let tx = MockTransaction::eip1559()
+            .with_gas_limit(21_000)
+            .with_max_fee(1_000)
+            .with_priority_fee(priority_fee);
+        fund(client, &tx);
+        tx
+    }
+
+    /// Returns a funded blob transaction with a single blob.
+    fn blob_transaction(client: &MockEthProvider, priority_fee: u128) -> MockTransaction {
+        let sidecar = BlobTransactionSidecar {
+            blobs: vec![Default::default()],
+            commitments: vec![Default::default()],
+            proofs: vec![Default::default()],
+        };
+        let tx = MockTransaction::eip4844_with_sidecar(sidecar)
+            .with_gas_limit(21_000)
+            .with_max_fee(1_000)
+            .with_priority_fee(priority_fee);
+        fund(client, &tx);
+        tx
+    }
+
+    fn fund(client: &MockEthProvider, tx: &MockTransaction) {
+        client.add_account(tx.sender(), ExtendedAccount::new(0, U256::from(u64::MAX)));
+    }
+
+    /// Builds a payload on top of an empty Cancun block and returns the hashes of its
+    /// transactions.
+    fn build<P, S>(pool: P, client: MockEthProvider, selector: &S) -> Vec<TxHash>
+    where
+        P: TransactionPool,
+        S: TransactionSelector,
+    {
+        let block = build_block(pool, client, selector, payload_config(Default::default()));
+        block.body.iter().map(|tx| tx.hash).collect()
+    }
+
+    /// Returns the config of a payload with the given withdrawals on top of an empty Cancun block.
+    fn payload_config(withdrawals: Withdrawals) -> PayloadConfig<EthPayloadBuilderAttributes> {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().cancun_activated().build());
+        let parent = Header {
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(0),
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(B256::ZERO),
+            ..Default::default()
+        }
+        .seal_slow();
+        let parent = Arc::new(SealedBlock { header: parent, ..Default::default() });
+        let attributes = EthPayloadBuilderAttributes {
+            id: PayloadId::new([0; 8]),
+            parent: parent.hash(),
+            timestamp: parent.timestamp + 12,
+            suggested_fee_recipient: Default::default(),
+            prev_randao: B256::ZERO,
+            withdrawals,
+            parent_beacon_block_root: Some(B256::with_last_byte(1)),
+        };
+        PayloadConfig::new(parent, Default::default(), attributes, chain_spec)
+    }
+
+    /// Builds the payload of the given config and returns its block.
+    fn build_block<P, S>(
+        pool: P,
+        client: MockEthProvider,
+        selector: &S,
+        config: PayloadConfig<EthPayloadBuilderAttributes>,
+    ) -> SealedBlock
+    where
+        P: TransactionPool,
+        S: TransactionSelector,
+    {
+        let args = BuildArguments::new(
+            client,
+            pool,
+            Default::default(),
+            config,
+            Cancelled::default(),
+            None,
+        );
+
+        match default_ethereum_payload_builder(EthEvmConfig::default(), selector, args).unwrap() {
+            BuildOutcome::Better { payload, .. } => payload.block().clone(),
+            _ => panic!("expected a payload"),
+        }
+    }
+
+    async fn add(pool: &TestPool, txs: &[MockTransaction]) {
+        for tx in txs {
+            pool.add_transaction(TransactionOrigin::External, tx.clone()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn selector_decisions() {
+        let client = MockEthProvider::default();
+        let first = transaction(&client, 3);
+        let second = transaction(&client, 2);
+        let second_next = second.next();
+        let third = transaction(&client, 1);
+        let txs = [first.clone(), second.clone(), second_next.clone(), third.clone()];
+
+        // skipping a transaction still tries its descendant, which fails with a nonce gap
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, &txs).await;
+        let selector = TestSelector {
+            decisions: HashMap::from([(*second.hash(), TransactionDecision::Skip)]),
+            ..Default::default()
+        };
+        assert_eq!(build(pool, client.clone(), &selector), vec![*first.hash(), *third.hash()]);
+        assert!(selector.seen.lock().unwrap().contains(second_next.hash()));
+
+        // skipping with descendants never considers the descendant
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, &txs).await;
+        let selector = TestSelector {
+            decisions: HashMap::from([(*second.hash(), TransactionDecision::SkipWithDescendants)]),
+            ..Default::default()
+        };
+        assert_eq!(build(pool, client.clone(), &selector), vec![*first.hash(), *third.hash()]);
+        assert!(!selector.seen.lock().unwrap().contains(second_next.hash()));
+
+        // stopping ends the payload
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, &txs).await;
+        let selector = TestSelector {
+            decisions: HashMap::from([(*second.hash(), TransactionDecision::Stop)]),
+            ..Default::default()
+        };
+        assert_eq!(build(pool, client, &selector), vec![*first.hash()]);
+        assert!(!selector.seen.lock().unwrap().contains(third.hash()));
+    }
+
+    #[tokio::test]
+    async fn conditionals_checked_against_payload() {
+        let client = MockEthProvider::default();
+        let expired = transaction(&client, 3);
+        let expired_next = expired.next();
+        let met = transaction(&client, 2);
+        let too_early = transaction(&client, 1);
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, &[expired_next]).await;
+
+        // the pool accepts a maximum timestamp after the earliest possible next block, but the
+        // payload is built 12 seconds after its parent
+        for (tx, conditional) in [
+            (&expired, TransactionConditional { timestamp_max: Some(6), ..Default::default() }),
+            (&met, TransactionConditional { timestamp_max: Some(12), ..Default::default() }),
+            (
+                &too_early,
+                TransactionConditional { block_number_min: Some(2), ..Default::default() },
+            ),
+        ] {
+            pool.add_conditional_transaction(TransactionOrigin::External, tx.clone(), conditional)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(build(pool, client, &DefaultTransactionSelector::new()), vec![*met.hash()]);
+    }
+
+    #[tokio::test]
+    async fn max_transactions_per_sender() {
+        let client = MockEthProvider::default();
+        let first = transaction(&client, 2);
+        let other = transaction(&client, 1);
+        let second = first.next();
+        let third = second.next();
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, &[first.clone(), second.clone(), third, other.clone()]).await;
+
+        let selector = DefaultTransactionSelector::new().with_max_transactions_per_sender(2);
+        assert_eq!(
+            build(pool, client, &selector),
+            vec![*first.hash(), *second.hash(), *other.hash()]
+        );
+    }
+
+    #[tokio::test]
+    async fn max_blob_gas() {
+        let client = MockEthProvider::default();
+        let blob = blob_transaction(&client, 3);
+        let other_blob = blob_transaction(&client, 2);
+        let tx = transaction(&client, 1);
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, &[blob.clone(), other_blob, tx.clone()]).await;
+
+        let selector = DefaultTransactionSelector::new().with_max_blob_gas(DATA_GAS_PER_BLOB);
+        assert_eq!(build(pool, client, &selector), vec![*blob.hash(), *tx.hash()]);
+    }
+
+    #[tokio::test]
+    async fn bundles_respect_limits() {
+        let client = MockEthProvider::default();
+        let bundle_tx = transaction(&client, 1);
+        let bundle_tx_next = bundle_tx.next();
+        let tx = transaction(&client, 2);
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, std::slice::from_ref(&tx)).await;
+        pool.add_bundle(
+            TransactionOrigin::External,
+            NewBundle {
+                transactions: vec![bundle_tx.clone(), bundle_tx_next.clone()],
+                reverting_tx_hashes: Vec::new(),
+                block_number: 1,
+            },
+        )
+        .await
+        .unwrap();
+
+        // the bundle exceeds the per-sender cap and is skipped entirely
+        let selector = DefaultTransactionSelector::new().with_max_transactions_per_sender(1);
+        assert_eq!(build(pool.clone(), client.clone(), &selector), vec![*tx.hash()]);
+
+        // without the cap, the bundle is included before the pool transactions
+        assert_eq!(
+            build(pool, client, &DefaultTransactionSelector::new()),
+            vec![*bundle_tx.hash(), *bundle_tx_next.hash(), *tx.hash()]
+        );
+    }
+
+    #[tokio::test]
+    async fn greedy_selector_ignores_bundles() {
+        let client = MockEthProvider::default();
+        let bundle_tx = transaction(&client, 1);
+        let tx = transaction(&client, 2);
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, std::slice::from_ref(&tx)).await;
+        pool.add_bundle(
+            TransactionOrigin::External,
+            NewBundle {
+                transactions: vec![bundle_tx.clone()],
+                reverting_tx_hashes: Vec::new(),
+                block_number: 1,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            build(pool.clone(), client.clone(), &DefaultTransactionSelector::new()),
+            vec![*bundle_tx.hash(), *tx.hash()]
+        );
+        assert_eq!(build(pool, client, &GreedyTransactionSelector::new()), vec![*tx.hash()]);
+    }
+
+    #[tokio::test]
+    async fn failed_bundle_is_discarded() {
+        let client = MockEthProvider::default();
+        let first = transaction(&client, 1);
+        // the second transaction of the bundle has a nonce gap and fails
+        let gapped = transaction(&client, 1).with_nonce(5);
+        let tx = first.clone().with_hash(B256::random());
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, std::slice::from_ref(&tx)).await;
+        pool.add_bundle(
+            TransactionOrigin::External,
+            NewBundle {
+                transactions: vec![first, gapped],
+                reverting_tx_hashes: Vec::new(),
+                block_number: 1,
+            },
+        )
+        .await
+        .unwrap();
+
+        // the nonce increment of the bundle's first transaction is not applied, so the pool
+        // transaction with the same nonce is valid
+        assert_eq!(build(pool, client, &DefaultTransactionSelector::new()), vec![*tx.hash()]);
+    }
+
+    #[tokio::test]
+    async fn inclusion_list_first_and_deduplicated() {
+        let client = MockEthProvider::default();
+        let best = transaction(&client, 2);
+        let listed = transaction(&client, 1);
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, &[best.clone(), listed.clone()]).await;
+
+        let selector =
+            TestSelector { inclusion_list: vec![listed.clone().into()], ..Default::default() };
+        assert_eq!(build(pool, client, &selector), vec![*listed.hash(), *best.hash()]);
+    }
+
+    #[tokio::test]
+    async fn deprioritized_transactions_last() {
+        let client = MockEthProvider::default();
+        let reverting = transaction(&client, 3);
+        let reverting_next = reverting.next();
+        let tx = transaction(&client, 1);
+
+        let validator = SimulatingValidator::default();
+        validator.reverting.lock().unwrap().insert(*reverting.hash());
+        let pool = Pool::new(
+            validator,
+            MockOrdering::default(),
+            InMemoryBlobStore::default(),
+            Default::default(),
+        );
+        for tx in [reverting.clone(), reverting_next.clone(), tx.clone()] {
+            pool.add_transaction(TransactionOrigin::External, tx).await.unwrap();
+        }
+
+        // the descendant of the deprioritized transaction is deferred as well
+        assert_eq!(
+            build(pool, client, &DefaultTransactionSelector::new()),
+            vec![*tx.hash(), *reverting.hash(), *reverting_next.hash()]
+        );
+    }
+
+    #[tokio::test]
+    async fn state_root_computed_in_background() {
+        let client = MockEthProvider::default();
+        client.add_account(
+            BEACON_ROOTS_ADDRESS,
+            ExtendedAccount::new(1, U256::ZERO).with_bytecode(BEACON_ROOTS_CODE.clone()),
+        );
+        let tx = transaction(&client, 1);
+        let pool: TestPool = TestPoolBuilder::default().into();
+        add(&pool, std::slice::from_ref(&tx)).await;
+
+        let state_root = Arc::new(RecordingStateRoot::default());
+        let withdrawals = Withdrawals::new(vec![
+            Withdrawal { index: 0, validator_index: 0, address: tx.sender(), amount: 1 },
+            Withdrawal { index: 1, validator_index: 1, address: Address::random(), amount: 2 },
+        ]);
+        let config =
+            payload_config(withdrawals).with_state_root(Some(IncrementalStateRootSpawner::new(
+                Box::new(TokioTaskExecutor::default()),
+                state_root.clone(),
+            )));
+        let block = build_block(pool, client, &DefaultTransactionSelector::new(), config);
+        assert_eq!(block.body.len(), 1);
+
+        // the beacon root contract call and the withdrawals were streamed to the worker, so its
+        // last root is used instead of computing the root once more after the payload was built
+        let threads = state_root.threads.lock().unwrap();
+        assert!(!threads.contains(&std::thread::current().id()));
+        assert_eq!(block.state_root, B256::with_last_byte(threads.len() as u8));
+    }
+}
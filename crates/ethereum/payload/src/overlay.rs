@@ -0,0 +1,96 @@
+//! Execution of bundles on top of the payload's state without committing to it.
+
+use reth_primitives::{Address, B256, U256};
+use revm::{
+    primitives::{AccountInfo, Bytecode, EvmState},
+    Database, DatabaseCommit,
+};
+
+/// A [`Database`] that keeps the state changes committed to it on top of the wrapped database.
+///
+/// The transactions of a bundle are executed against the overlay, so that a bundle that fails
+/// can be discarded by dropping the overlay, without copying the state of the payload up front.
+/// The changes of a successful bundle are applied to the wrapped database in execution order
+/// with [`BundleOverlay::commit_to`].
+#[derive(Debug)]
+pub(crate) struct BundleOverlay<'a, DB> {
+    /// The database the changes are kept on top of.
+    db: &'a mut DB,
+    /// The changes of the executed transactions, in execution order.
+    changes: Vec<EvmState>,
+}
+
+impl<'a, DB> BundleOverlay<'a, DB> {
+    /// Creates an overlay without changes.
+    pub(crate) fn new(db: &'a mut DB) -> Self {
+        Self { db, changes: Vec::new() }
+    }
+}
+
+impl<DB: DatabaseCommit> BundleOverlay<'_, DB> {
+    /// Commits all changes to the wrapped database.
+    pub(crate) fn commit_to(self) {
+        for state in self.changes {
+            self.db.commit(state);
+        }
+    }
+}
+
+impl<DB: Database> Database for BundleOverlay<'_, DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        for state in self.changes.iter().rev() {
+            if let Some(account) = state.get(&address) {
+                // destroyed accounts, including empty accounts that were touched (EIP-161), no
+                // longer exist once the change is committed
+                if account.is_selfdestructed() ||
+                    (account.is_empty() &&
+                        (account.is_touched() || account.is_loaded_as_not_existing()))
+                {
+                    return Ok(None)
+                }
+                return Ok(Some(account.info.clone()))
+            }
+        }
+        self.db.basic(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        for state in self.changes.iter().rev() {
+            let code = state
+                .values()
+                .filter(|account| account.info.code_hash == code_hash)
+                .find_map(|account| account.info.code.clone());
+            if let Some(code) = code {
+                return Ok(code)
+            }
+        }
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        for state in self.changes.iter().rev() {
+            if let Some(account) = state.get(&address) {
+                if let Some(slot) = account.storage.get(&index) {
+                    return Ok(slot.present_value)
+                }
+                // the storage of destroyed and newly created accounts starts out empty
+                if account.is_selfdestructed() || account.is_created() {
+                    return Ok(U256::ZERO)
+                }
+            }
+        }
+        self.db.storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash(number)
+    }
+}
+
+impl<DB> DatabaseCommit for BundleOverlay<'_, DB> {
+    fn commit(&mut self, changes: EvmState) {
+        self.changes.push(changes);
+    }
+}
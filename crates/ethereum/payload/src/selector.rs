@@ -0,0 +1,236 @@
+//! Pluggable transaction selection for the Ethereum payload builder.
+
+use reth_payload_builder::EthPayloadBuilderAttributes;
+use reth_primitives::{
+    constants::eip4844::MAX_DATA_GAS_PER_BLOCK, Address, TransactionSignedEcRecovered,
+};
+use reth_transaction_pool::{
+    BestBundlesAndTransactions, BestTransactionsAttributes, TransactionPool,
+};
+use revm::primitives::ExecutionResult;
+use std::collections::HashMap;
+
+/// Decides which transactions the Ethereum payload builder includes in a payload, and in which
+/// order.
+///
+/// The builder consults the selector at every step of the transaction loop: it first executes
+/// the [inclusion list](TransactionSelector::inclusion_list), then pulls candidates from
+/// [`TransactionSelector::best_transactions`] and asks
+/// [`TransactionSelector::pre_transaction`] before executing each of them. Gas limit, per-sender
+/// and blob gas limits are enforced by the builder according to the selector's configuration.
+///
+/// All hooks have defaults that reproduce the behaviour of the stock builder, so implementations
+/// only need to override what they want to change, e.g. compliance filtering or custom ordering.
+pub trait TransactionSelector: Send + Sync {
+    /// Returns the bundles and transactions the builder should try to include, in order.
+    ///
+    /// Defaults to the bundles targeting the block followed by the best transactions of the pool.
+    fn best_transactions<Pool: TransactionPool>(
+        &self,
+        pool: &Pool,
+        attributes: BestTransactionsAttributes,
+        block_number: u64,
+    ) -> BestBundlesAndTransactions<Pool::Transaction> {
+        pool.best_bundles_and_transactions(attributes, block_number)
+    }
+
+    /// Returns the transactions that must be included, if valid, before any transaction of the
+    /// pool.
+    ///
+    /// Transactions of the inclusion list that fail to execute are skipped. Blob transactions
+    /// must also be in the pool, since the sidecars of the payload are fetched from it.
+    fn inclusion_list(
+        &self,
+        _attributes: &EthPayloadBuilderAttributes,
+    ) -> Vec<TransactionSignedEcRecovered> {
+        Vec::new()
+    }
+
+    /// Returns the maximum number of transactions a single sender can have in the payload.
+    ///
+    /// The cap applies to pool transactions and bundles, a bundle that would exceed it is skipped
+    /// entirely. Transactions of the inclusion list are not capped, but count towards it.
+    fn max_transactions_per_sender(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the blob gas the payload is allowed to use.
+    ///
+    /// This can only lower the protocol limit of [`MAX_DATA_GAS_PER_BLOCK`]. The budget applies to
+    /// all transactions, a bundle that would exceed it is skipped entirely.
+    fn max_blob_gas(&self) -> u64 {
+        MAX_DATA_GAS_PER_BLOCK
+    }
+
+    /// Called before a transaction is executed, after the builder's own limits were checked.
+    ///
+    /// For bundles this is called for every transaction of the bundle, and any decision other
+    /// than [`TransactionDecision::Include`] discards the whole bundle.
+    fn pre_transaction(
+        &self,
+        _tx: &TransactionSignedEcRecovered,
+        _selection: &SelectionState,
+    ) -> TransactionDecision {
+        TransactionDecision::Include
+    }
+
+    /// Called after a transaction was executed and committed to the payload.
+    fn post_transaction(
+        &self,
+        _tx: &TransactionSignedEcRecovered,
+        _result: &ExecutionResult,
+        _selection: &SelectionState,
+    ) {
+    }
+}
+
+/// The outcome of [`TransactionSelector::pre_transaction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionDecision {
+    /// Execute the transaction and include it if valid.
+    Include,
+    /// Skip this transaction only.
+    Skip,
+    /// Skip this transaction and all pool transactions that depend on it.
+    SkipWithDescendants,
+    /// Stop adding transactions to the payload.
+    Stop,
+}
+
+/// The transactions selected for the payload so far.
+#[derive(Debug, Clone, Default)]
+pub struct SelectionState {
+    /// Gas used by the included transactions.
+    gas_used: u64,
+    /// Blob gas used by the included transactions.
+    blob_gas_used: u64,
+    /// Number of included transactions per sender.
+    senders: HashMap<Address, usize>,
+}
+
+impl SelectionState {
+    /// Returns the gas used by the included transactions.
+    pub const fn gas_used(&self) -> u64 {
+        self.gas_used
+    }
+
+    /// Returns the blob gas used by the included transactions.
+    pub const fn blob_gas_used(&self) -> u64 {
+        self.blob_gas_used
+    }
+
+    /// Returns the number of included transactions.
+    pub fn transactions(&self) -> usize {
+        self.senders.values().sum()
+    }
+
+    /// Returns the number of included transactions of the given sender.
+    pub fn transactions_by_sender(&self, sender: &Address) -> usize {
+        self.senders.get(sender).copied().unwrap_or_default()
+    }
+
+    /// Records an included transaction.
+    pub(crate) fn record(&mut self, sender: Address, gas_used: u64, blob_gas_used: u64) {
+        self.gas_used += gas_used;
+        self.blob_gas_used += blob_gas_used;
+        *self.senders.entry(sender).or_default() += 1;
+    }
+}
+
+/// The default [`TransactionSelector`].
+///
+/// Selects the bundles targeting the block first and then the best transactions of the pool,
+/// optionally capping the number of transactions per sender and the blob gas of the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultTransactionSelector {
+    /// Maximum number of transactions per sender.
+    max_transactions_per_sender: Option<usize>,
+    /// Maximum blob gas of the payload.
+    max_blob_gas: u64,
+}
+
+impl DefaultTransactionSelector {
+    /// Creates a selector without any limits beyond the protocol ones.
+    pub const fn new() -> Self {
+        Self { max_transactions_per_sender: None, max_blob_gas: MAX_DATA_GAS_PER_BLOCK }
+    }
+
+    /// Caps the number of transactions a single sender can have in the payload.
+    pub const fn with_max_transactions_per_sender(mut self, max: usize) -> Self {
+        self.max_transactions_per_sender = Some(max);
+        self
+    }
+
+    /// Caps the blob gas of the payload.
+    pub const fn with_max_blob_gas(mut self, max_blob_gas: u64) -> Self {
+        self.max_blob_gas = max_blob_gas;
+        self
+    }
+}
+
+impl Default for DefaultTransactionSelector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionSelector for DefaultTransactionSelector {
+    fn max_transactions_per_sender(&self) -> Option<usize> {
+        self.max_transactions_per_sender
+    }
+
+    fn max_blob_gas(&self) -> u64 {
+        self.max_blob_gas
+    }
+}
+
+/// A [`TransactionSelector`] that greedily fills the payload with the pool transactions that pay
+/// the highest tip and ignores bundles.
+///
+/// Unlike the bundle-first [`DefaultTransactionSelector`], this never gives up block space to a
+/// bundle whose transactions pay less than the best transactions of the pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GreedyTransactionSelector {
+    /// The limits of the payload.
+    limits: DefaultTransactionSelector,
+}
+
+impl GreedyTransactionSelector {
+    /// Creates a selector without any limits beyond the protocol ones.
+    pub const fn new() -> Self {
+        Self { limits: DefaultTransactionSelector::new() }
+    }
+
+    /// Caps the number of transactions a single sender can have in the payload.
+    pub const fn with_max_transactions_per_sender(mut self, max: usize) -> Self {
+        self.limits = self.limits.with_max_transactions_per_sender(max);
+        self
+    }
+
+    /// Caps the blob gas of the payload.
+    pub const fn with_max_blob_gas(mut self, max_blob_gas: u64) -> Self {
+        self.limits = self.limits.with_max_blob_gas(max_blob_gas);
+        self
+    }
+}
+
+impl TransactionSelector for GreedyTransactionSelector {
+    fn best_transactions<Pool: TransactionPool>(
+        &self,
+        pool: &Pool,
+        attributes: BestTransactionsAttributes,
+        _block_number: u64,
+    ) -> BestBundlesAndTransactions<Pool::Transaction> {
+        BestBundlesAndTransactions::transactions_only(
+            pool.best_transactions_with_attributes(attributes),
+        )
+    }
+
+    fn max_transactions_per_sender(&self) -> Option<usize> {
+        self.limits.max_transactions_per_sender
+    }
+
+    fn max_blob_gas(&self) -> u64 {
+        self.limits.max_blob_gas
+    }
+}
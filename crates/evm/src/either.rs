@@ -8,7 +8,7 @@ use reth_execution_types::{BlockExecutionInput, BlockExecutionOutput, ExecutionO
 use reth_primitives::{BlockNumber, BlockWithSenders, Receipt};
 use reth_prune_types::PruneModes;
 use reth_storage_errors::provider::ProviderError;
-use revm_primitives::db::Database;
+use revm_primitives::db::{Database, DatabaseRef};
 
 // re-export Either
 pub use futures_util::future::Either;
@@ -43,6 +43,20 @@ where
             Self::Right(b) => Either::Right(b.batch_executor(db)),
         }
     }
+
+    fn execute_parallel<DB>(
+        &self,
+        db: DB,
+        input: BlockExecutionInput<'_, BlockWithSenders>,
+    ) -> Result<BlockExecutionOutput<Receipt>, BlockExecutionError>
+    where
+        DB: DatabaseRef<Error: Into<ProviderError> + Display> + Send + Sync,
+    {
+        match self {
+            Self::Left(a) => a.execute_parallel(db, input),
+            Self::Right(b) => b.execute_parallel(db, input),
+        }
+    }
 }
 
 impl<A, B, DB> Executor<DB> for Either<A, B>
@@ -9,7 +9,8 @@ use core::fmt::Display;
 
 use reth_primitives::{BlockNumber, BlockWithSenders, Receipt};
 use reth_prune_types::PruneModes;
-use revm_primitives::db::Database;
+use revm::db::WrapDatabaseRef;
+use revm_primitives::db::{Database, DatabaseRef};
 
 /// A general purpose executor trait that executes an input (e.g. block) and produces an output
 /// (e.g. state changes and receipts).
@@ -136,6 +137,23 @@ pub trait BlockExecutorProvider: Send + Sync + Clone + Unpin + 'static {
     fn batch_executor<DB>(&self, db: DB) -> Self::BatchExecutor<DB>
     where
         DB: Database<Error: Into<ProviderError> + Display>;
+
+    /// Executes a single block like the [`Executor`] of [`BlockExecutorProvider::executor`], but
+    /// executes the block's transactions in parallel if supported.
+    ///
+    /// The output must be identical to the output of executing the block serially.
+    ///
+    /// By default, the block is executed serially.
+    fn execute_parallel<DB>(
+        &self,
+        db: DB,
+        input: BlockExecutionInput<'_, BlockWithSenders>,
+    ) -> Result<BlockExecutionOutput<Receipt>, BlockExecutionError>
+    where
+        DB: DatabaseRef<Error: Into<ProviderError> + Display> + Send + Sync,
+    {
+        self.executor(WrapDatabaseRef(db)).execute(input)
+    }
 }
 
 #[cfg(test)]
@@ -27,6 +27,7 @@ pub mod either;
 pub mod execute;
 pub mod noop;
 pub mod provider;
+pub mod read_set;
 pub mod system_calls;
 
 #[cfg(any(test, feature = "test-utils"))]
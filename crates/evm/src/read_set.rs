@@ -0,0 +1,269 @@
+//! Execution of transactions that records the state they read, so their results can be reused on
+//! top of a different state as long as the state they read is unchanged.
+
+use crate::ConfigureEvm;
+use reth_primitives::{Address, B256, U256};
+use revm::{
+    interpreter::{opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter},
+    Database, EvmContext, Inspector,
+};
+use revm_primitives::{
+    AccountInfo, Bytecode, EVMError, EnvWithHandlerCfg, HashMap, ResultAndState,
+};
+
+/// The result of a transaction along with the state it read during execution.
+///
+/// Every transaction credits the block's beneficiary, so the beneficiary is only part of the read
+/// set if the transaction observed it, e.g. via `BALANCE` or by calling it. Otherwise the result is
+/// valid on top of any beneficiary state, see [`TrackedExecution::result_on`].
+#[derive(Debug, Clone)]
+pub struct TrackedExecution {
+    /// The state the transaction read.
+    reads: ReadSet,
+    /// The block's beneficiary.
+    coinbase: Address,
+    /// The beneficiary before execution, if the transaction didn't observe it.
+    beneficiary: Option<AccountInfo>,
+    /// The execution result.
+    result: ResultAndState,
+}
+
+impl TrackedExecution {
+    /// Executes the transaction of the given environment and records the state it reads.
+    pub fn transact<EvmConfig, DB>(
+        evm_config: &EvmConfig,
+        db: &mut DB,
+        env: EnvWithHandlerCfg,
+    ) -> Result<Self, EVMError<DB::Error>>
+    where
+        EvmConfig: ConfigureEvm,
+        DB: Database,
+    {
+        let coinbase = env.block.coinbase;
+        let mut recorder = ReadRecorder::new(db);
+        let mut observer = BeneficiaryObserver { beneficiary: coinbase, observed: false };
+        let result =
+            evm_config.evm_with_env_and_inspector(&mut recorder, env, &mut observer).transact()?;
+
+        let mut reads = recorder.into_reads();
+        let mut beneficiary = None;
+        if !observer.observed && result.state.contains_key(&coinbase) {
+            // the beneficiary is only credited
+            beneficiary = Some(reads.accounts.remove(&coinbase).flatten().unwrap_or_default());
+        }
+
+        Ok(Self { reads, coinbase, beneficiary, result })
+    }
+
+    /// Returns the state the transaction read, excluding the beneficiary if it was only credited.
+    pub const fn reads(&self) -> &ReadSet {
+        &self.reads
+    }
+
+    /// Returns the execution result.
+    pub const fn result(&self) -> &ResultAndState {
+        &self.result
+    }
+
+    /// Returns the result of executing the transaction on top of the given database, if the state
+    /// the transaction read is unchanged.
+    ///
+    /// If the transaction didn't observe the beneficiary, its credit is applied on top of the
+    /// beneficiary's current state.
+    pub fn result_on<DB: Database>(
+        &self,
+        db: &mut DB,
+    ) -> Result<Option<ResultAndState>, DB::Error> {
+        if !self.reads.is_valid(db)? {
+            return Ok(None)
+        }
+        let mut result = self.result.clone();
+        credit_beneficiary(db, self.coinbase, self.beneficiary.as_ref(), &mut result)?;
+        Ok(Some(result))
+    }
+
+    /// Same as [`TrackedExecution::result_on`], but consumes the execution.
+    pub fn into_result_on<DB: Database>(
+        self,
+        db: &mut DB,
+    ) -> Result<Option<ResultAndState>, DB::Error> {
+        if !self.reads.is_valid(db)? {
+            return Ok(None)
+        }
+        let mut result = self.result;
+        credit_beneficiary(db, self.coinbase, self.beneficiary.as_ref(), &mut result)?;
+        Ok(Some(result))
+    }
+}
+
+/// Applies the beneficiary's credit of the result on top of its current state.
+///
+/// The beneficiary is only part of the result state if it was touched, and whether it's removed
+/// as an empty account only depends on its resulting state, so the credit can be moved to any
+/// beneficiary state.
+fn credit_beneficiary<DB: Database>(
+    db: &mut DB,
+    coinbase: Address,
+    before: Option<&AccountInfo>,
+    result: &mut ResultAndState,
+) -> Result<(), DB::Error> {
+    let Some(before) = before else { return Ok(()) };
+    let current = db.basic(coinbase)?.unwrap_or_default();
+    if let Some(account) = result.state.get_mut(&coinbase) {
+        let credit = account.info.balance.saturating_sub(before.balance);
+        account.info = AccountInfo { balance: current.balance.saturating_add(credit), ..current };
+    }
+    Ok(())
+}
+
+/// The state read by a transaction.
+#[derive(Debug, Clone, Default)]
+pub struct ReadSet {
+    accounts: HashMap<Address, Option<AccountInfo>>,
+    storage: HashMap<Address, HashMap<U256, U256>>,
+    block_hashes: HashMap<u64, B256>,
+}
+
+impl ReadSet {
+    /// Returns the addresses of all accounts that were read, including those only read via their
+    /// storage.
+    pub fn addresses(&self) -> impl Iterator<Item = Address> + '_ {
+        self.accounts.keys().chain(self.storage.keys()).copied()
+    }
+
+    /// Returns `true` if the database still holds the values that were read.
+    ///
+    /// Accounts are read before their storage, so this can be used with databases that require
+    /// accounts to be loaded first, like [`State`](revm::db::State).
+    pub fn is_valid<DB: Database>(&self, db: &mut DB) -> Result<bool, DB::Error> {
+        for (address, info) in &self.accounts {
+            if db.basic(*address)? != *info {
+                return Ok(false)
+            }
+        }
+        for (address, slots) in &self.storage {
+            for (slot, value) in slots {
+                if db.storage(*address, *slot)? != *value {
+                    return Ok(false)
+                }
+            }
+        }
+        for (number, hash) in &self.block_hashes {
+            if db.block_hash(*number)? != *hash {
+                return Ok(false)
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns `true` if any value read from the given account differs from its new state.
+    pub fn is_changed(
+        &self,
+        address: Address,
+        info: Option<&AccountInfo>,
+        destroyed: bool,
+        mut storage: impl Iterator<Item = (U256, U256)>,
+    ) -> bool {
+        if self.accounts.get(&address).is_some_and(|read| read.as_ref() != info) {
+            return true
+        }
+        let Some(slots) = self.storage.get(&address) else { return false };
+        destroyed ||
+            storage.any(|(slot, value)| slots.get(&slot).is_some_and(|read| *read != value))
+    }
+}
+
+/// A [Database] that records the state read from the underlying database.
+#[derive(Debug)]
+pub struct ReadRecorder<'a, DB> {
+    db: &'a mut DB,
+    reads: ReadSet,
+}
+
+impl<'a, DB> ReadRecorder<'a, DB> {
+    /// Creates a new recorder on top of the given database.
+    pub fn new(db: &'a mut DB) -> Self {
+        Self { db, reads: ReadSet::default() }
+    }
+
+    /// Returns the state that was read.
+    pub fn into_reads(self) -> ReadSet {
+        self.reads
+    }
+}
+
+impl<DB: Database> Database for ReadRecorder<'_, DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        self.reads.accounts.entry(address).or_insert_with(|| info.clone());
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        // code is addressed by its hash, so it never changes
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.reads.storage.entry(address).or_default().entry(index).or_insert(value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.reads.block_hashes.entry(number).or_insert(hash);
+        Ok(hash)
+    }
+}
+
+/// An [Inspector] that detects whether a transaction observes the block's beneficiary account,
+/// beyond being credited with the transaction fees.
+#[derive(Debug)]
+struct BeneficiaryObserver {
+    beneficiary: Address,
+    observed: bool,
+}
+
+impl<DB: Database> Inspector<DB> for BeneficiaryObserver {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        match interp.current_opcode() {
+            opcode::BALANCE | opcode::EXTCODESIZE | opcode::EXTCODECOPY | opcode::EXTCODEHASH => {
+                if let Ok(word) = interp.stack().peek(0) {
+                    self.observed |=
+                        Address::from_word(B256::from(word.to_be_bytes())) == self.beneficiary;
+                }
+            }
+            opcode::SELFBALANCE => {
+                self.observed |= interp.contract.target_address == self.beneficiary;
+            }
+            _ => {}
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        // the gas of value transfers depends on whether the target is empty
+        self.observed |= [inputs.caller, inputs.target_address, inputs.bytecode_address]
+            .contains(&self.beneficiary);
+        None
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.observed |= inputs.caller == self.beneficiary;
+        None
+    }
+
+    fn selfdestruct(&mut self, _contract: Address, target: Address, _value: U256) {
+        self.observed |= target == self.beneficiary;
+    }
+}
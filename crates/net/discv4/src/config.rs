@@ -6,7 +6,7 @@
 use alloy_primitives::bytes::Bytes;
 use alloy_rlp::Encodable;
 use reth_net_banlist::BanList;
-use reth_net_nat::{NatResolver, ResolveNatInterval};
+use reth_net_nat::{NatResolver, PortMappingRequest, ResolveNatInterval};
 use reth_network_peers::NodeRecord;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -66,6 +66,11 @@ pub struct Discv4Config {
     /// If configured and a `external_ip_resolver` is configured, try to resolve the external ip
     /// using this interval.
     pub resolve_external_ip_interval: Option<Duration>,
+    /// Ports to map on the NAT gateway, if the `external_ip_resolver` supports port mapping.
+    ///
+    /// Mappings of the discovery UDP port and the `RLPx` TCP port are announced in the local
+    /// node record.
+    pub nat_port_mappings: Vec<PortMappingRequest>,
     /// The duration after which we consider a bond expired.
     pub bond_expiration: Duration,
 }
@@ -103,7 +108,10 @@ impl Discv4Config {
     pub fn resolve_external_ip_interval(&self) -> Option<ResolveNatInterval> {
         let resolver = self.external_ip_resolver?;
         let interval = self.resolve_external_ip_interval?;
-        Some(ResolveNatInterval::interval(resolver, interval))
+        Some(
+            ResolveNatInterval::interval(resolver, interval)
+                .with_port_mappings(self.nat_port_mappings.iter().copied()),
+        )
     }
 }
 
@@ -138,6 +146,7 @@ impl Default for Discv4Config {
             external_ip_resolver: Some(Default::default()),
             // By default retry public IP using a 5min interval
             resolve_external_ip_interval: Some(Duration::from_secs(60 * 5)),
+            nat_port_mappings: Default::default(),
         }
     }
 }
@@ -307,6 +316,23 @@ impl Discv4ConfigBuilder {
         self
     }
 
+    /// Adds a port to map on the NAT gateway.
+    ///
+    /// This only has an effect if the configured external IP resolver supports port mapping.
+    pub fn add_nat_port_mapping(&mut self, request: PortMappingRequest) -> &mut Self {
+        self.config.nat_port_mappings.push(request);
+        self
+    }
+
+    /// Adds multiple ports to map on the NAT gateway.
+    pub fn add_nat_port_mappings(
+        &mut self,
+        requests: impl IntoIterator<Item = PortMappingRequest>,
+    ) -> &mut Self {
+        self.config.nat_port_mappings.extend(requests);
+        self
+    }
+
     /// Returns the configured [`Discv4Config`]
     pub fn build(&self) -> Discv4Config {
         self.config.clone()
@@ -47,7 +47,7 @@ use std::{
     cell::RefCell,
     collections::{btree_map, hash_map::Entry, BTreeMap, HashMap, VecDeque},
     fmt,
-    future::poll_fn,
+    future::{poll_fn, Future},
     io,
     net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4},
     pin::Pin,
@@ -83,9 +83,11 @@ pub use reth_network_peers::NodeRecord;
 pub mod test_utils;
 
 use crate::table::PongTable;
-use reth_net_nat::ResolveNatInterval;
 /// reexport to get public ip.
-pub use reth_net_nat::{external_ip, NatResolver};
+pub use reth_net_nat::{
+    external_ip, NatResolver, NatUpdate, PortMappingProtocol, PortMappingRequest,
+    ResolveNatInterval,
+};
 
 /// The default address for discv4 via UDP
 ///
@@ -410,6 +412,19 @@ impl Discv4 {
         Ok(rx.await?)
     }
 
+    /// Deletes all port mappings on the NAT gateway, see
+    /// [`Discv4ConfigBuilder::add_nat_port_mapping`].
+    ///
+    /// The mappings are not requested again afterwards, this is intended to be called on shutdown.
+    pub async fn unmap_nat_ports(&self) -> Result<(), Discv4Error> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(Discv4Command::UnmapNatPorts(tx))?;
+        if let Some(unmap) = rx.await? {
+            unmap.await
+        }
+        Ok(())
+    }
+
     /// Terminates the spawned [`Discv4Service`].
     pub fn terminate(&self) {
         self.send_to_service(Discv4Command::Terminated);
@@ -428,6 +443,12 @@ pub struct Discv4Service {
     local_eip_868_enr: Enr<SecretKey>,
     /// Local ENR of the server.
     local_node_record: NodeRecord,
+    /// The local TCP port of the server, this can differ from the port announced in the
+    /// [`NodeRecord`] if the port is mapped on a NAT gateway.
+    local_tcp_port: u16,
+    /// The most recent external address and port mappings resolved by the
+    /// [`ResolveNatInterval`].
+    nat_update: NatUpdate,
     /// Keeps track of the node record of the local node.
     shared_node_record: Arc<Mutex<NodeRecord>>,
     /// The secret key used to sign payloads
@@ -565,7 +586,9 @@ impl Discv4Service {
         Self {
             local_address,
             local_eip_868_enr,
+            local_tcp_port: local_node_record.tcp_port,
             local_node_record,
+            nat_update: Default::default(),
             shared_node_record,
             _socket: socket,
             kbuckets,
@@ -625,6 +648,52 @@ impl Discv4Service {
         }
     }
 
+    /// Sets the given ports as the node's external ports in the node record announced in
+    /// discovery
+    pub fn set_external_ports(&mut self, udp_port: u16, tcp_port: u16) {
+        if self.local_node_record.udp_port != udp_port ||
+            self.local_node_record.tcp_port != tcp_port
+        {
+            debug!(target: "discv4", udp_port, tcp_port, "Updating external ports");
+            self.local_node_record.udp_port = udp_port;
+            self.local_node_record.tcp_port = tcp_port;
+            if self.local_node_record.address.is_ipv4() {
+                let _ = self.local_eip_868_enr.set_udp4(udp_port, &self.secret_key);
+                let _ = self.local_eip_868_enr.set_tcp4(tcp_port, &self.secret_key);
+            } else {
+                let _ = self.local_eip_868_enr.set_udp6(udp_port, &self.secret_key);
+                let _ = self.local_eip_868_enr.set_tcp6(tcp_port, &self.secret_key);
+            }
+            let mut lock = self.shared_node_record.lock();
+            *lock = self.local_node_record;
+            debug!(target: "discv4", enr=?self.local_eip_868_enr, "Updated local ENR");
+        }
+    }
+
+    /// Applies the resolved external address and port mappings to the local node record.
+    ///
+    /// Listeners are notified with [`DiscoveryUpdate::ExternalAddr`] if either changed.
+    fn on_nat_update(&mut self, update: NatUpdate) {
+        // keep the last known address if resolving failed this time
+        let update =
+            NatUpdate { external_ip: update.external_ip.or(self.nat_update.external_ip), ..update };
+        if update == self.nat_update {
+            return
+        }
+
+        if let Some(ip) = update.external_ip {
+            self.set_external_ip_addr(ip);
+        }
+        let udp_port = self.local_address.port();
+        let udp_port = update.mapped_port(PortMappingProtocol::Udp, udp_port).unwrap_or(udp_port);
+        let tcp_port = self.local_tcp_port;
+        let tcp_port = update.mapped_port(PortMappingProtocol::Tcp, tcp_port).unwrap_or(tcp_port);
+        self.set_external_ports(udp_port, tcp_port);
+
+        self.nat_update = update.clone();
+        self.notify(DiscoveryUpdate::ExternalAddr(update));
+    }
+
     /// Returns the [`PeerId`] that identifies this node
     pub const fn local_peer_id(&self) -> &PeerId {
         &self.local_node_record.id
@@ -1601,10 +1670,10 @@ impl Discv4Service {
                 self.re_ping_oldest();
             }
 
-            if let Some(Poll::Ready(Some(ip))) =
-                self.resolve_external_ip_interval.as_mut().map(|r| r.poll_tick(cx))
+            if let Some(Poll::Ready(update)) =
+                self.resolve_external_ip_interval.as_mut().map(|r| r.poll_update(cx))
             {
-                self.set_external_ip_addr(ip);
+                self.on_nat_update(update);
             }
 
             // drain all incoming `Discv4` commands, this channel can never close
@@ -1624,6 +1693,13 @@ impl Discv4Service {
                         let rx = self.update_stream();
                         let _ = tx.send(rx);
                     }
+                    Discv4Command::UnmapNatPorts(tx) => {
+                        let unmap = self
+                            .resolve_external_ip_interval
+                            .as_ref()
+                            .map(|interval| Box::pin(interval.unmap_ports()) as _);
+                        let _ = tx.send(unmap);
+                    }
                     Discv4Command::BanPeer(node_id) => self.ban_node(node_id),
                     Discv4Command::Remove(node_id) => {
                         self.remove_node(node_id);
@@ -1938,6 +2014,7 @@ enum Discv4Command {
     Lookup { node_id: Option<PeerId>, tx: Option<NodeRecordSender> },
     SetLookupInterval(Duration),
     Updates(OneshotSender<ReceiverStream<DiscoveryUpdate>>),
+    UnmapNatPorts(OneshotSender<Option<Pin<Box<dyn Future<Output = ()> + Send>>>>),
     Terminated,
 }
 
@@ -2276,6 +2353,8 @@ pub enum DiscoveryUpdate {
     EnrForkId(NodeRecord, ForkId),
     /// Node that was removed from the table
     Removed(PeerId),
+    /// The external address or the port mappings of the local node changed.
+    ExternalAddr(NatUpdate),
     /// A series of updates
     Batch(Vec<DiscoveryUpdate>),
 }
@@ -14,7 +14,12 @@ use reth_ethereum_forks::{EnrForkIdEntry, ForkId};
 use reth_network_peers::NodeRecord;
 use tracing::warn;
 
-use crate::{enr::discv4_id_to_multiaddr_id, filter::MustNotIncludeKeys, NetworkStackId};
+use crate::{
+    enr::discv4_id_to_multiaddr_id,
+    filter::{EnrFilter, MustNotIncludeKeys},
+    topic::Topic,
+    NetworkStackId,
+};
 
 /// The default address for discv5 via UDP is IPv4.
 ///
@@ -77,6 +82,13 @@ pub struct ConfigBuilder {
     /// Custom filter rules to apply to a discovered peer in order to determine if it should be
     /// passed up to rlpx or dropped.
     discovered_peer_filter: Option<MustNotIncludeKeys>,
+    /// Additional filter rules on the kv-pairs of a discovered peer's node record.
+    enr_filter: Option<EnrFilter>,
+    /// Topics to advertise in local node record.
+    advertised_topics: Vec<Topic>,
+    /// Topics to look up. If set, only discovered peers that advertise any of these topics are
+    /// passed up to rlpx.
+    lookup_topics: Vec<Topic>,
 }
 
 impl ConfigBuilder {
@@ -92,6 +104,9 @@ impl ConfigBuilder {
             bootstrap_lookup_interval,
             bootstrap_lookup_countdown,
             discovered_peer_filter,
+            enr_filter,
+            advertised_topics,
+            lookup_topics,
         } = discv5_config;
 
         Self {
@@ -104,6 +119,9 @@ impl ConfigBuilder {
             bootstrap_lookup_interval: Some(bootstrap_lookup_interval),
             bootstrap_lookup_countdown: Some(bootstrap_lookup_countdown),
             discovered_peer_filter: Some(discovered_peer_filter),
+            enr_filter,
+            advertised_topics,
+            lookup_topics,
         }
     }
 
@@ -206,6 +224,28 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets filter rules on the kv-pairs of a discovered peer's node record, to determine whether
+    /// or not it should be passed to rlpx. Applies in addition to the disallowed keys, see
+    /// [`must_not_include_keys`](Self::must_not_include_keys).
+    pub fn enr_filter(mut self, filter: EnrFilter) -> Self {
+        self.enr_filter = Some(filter);
+        self
+    }
+
+    /// Adds topics to advertise in the local [`Enr`](discv5::enr::Enr), so that peers looking up
+    /// any of these topics discover this node.
+    pub fn advertise_topics(mut self, topics: impl IntoIterator<Item = Topic>) -> Self {
+        self.advertised_topics.extend(topics);
+        self
+    }
+
+    /// Adds topics to look up. Lookup queries then search for peers that advertise any of the
+    /// topics, and discovered peers that don't advertise any of the topics are not passed to rlpx.
+    pub fn lookup_topics(mut self, topics: impl IntoIterator<Item = Topic>) -> Self {
+        self.lookup_topics.extend(topics);
+        self
+    }
+
     /// Returns a new [`Config`].
     pub fn build(self) -> Config {
         let Self {
@@ -218,6 +258,9 @@ impl ConfigBuilder {
             bootstrap_lookup_interval,
             bootstrap_lookup_countdown,
             discovered_peer_filter,
+            enr_filter,
+            advertised_topics,
+            lookup_topics,
         } = self;
 
         let mut discv5_config = discv5_config
@@ -247,6 +290,9 @@ impl ConfigBuilder {
             bootstrap_lookup_interval,
             bootstrap_lookup_countdown,
             discovered_peer_filter,
+            enr_filter,
+            advertised_topics,
+            lookup_topics,
         }
     }
 }
@@ -280,6 +326,13 @@ pub struct Config {
     /// Custom filter rules to apply to a discovered peer in order to determine if it should be
     /// passed up to rlpx or dropped.
     pub(super) discovered_peer_filter: MustNotIncludeKeys,
+    /// Additional filter rules on the kv-pairs of a discovered peer's node record.
+    pub(super) enr_filter: Option<EnrFilter>,
+    /// Topics to advertise in local node record.
+    pub(super) advertised_topics: Vec<Topic>,
+    /// Topics to look up. If set, only discovered peers that advertise any of these topics are
+    /// passed up to rlpx.
+    pub(super) lookup_topics: Vec<Topic>,
 }
 
 impl Config {
@@ -296,6 +349,9 @@ impl Config {
             bootstrap_lookup_interval: None,
             bootstrap_lookup_countdown: None,
             discovered_peer_filter: None,
+            enr_filter: None,
+            advertised_topics: Vec::new(),
+            lookup_topics: Vec::new(),
         }
     }
 
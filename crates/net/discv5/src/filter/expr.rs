@@ -0,0 +1,415 @@
+//! A small expression language to filter discovered peers by the kv-pairs of their node records.
+//!
+//! Grammar:
+//!
+//! ```text
+//! expr    := and ( "||" and )*
+//! and     := unary ( "&&" unary )*
+//! unary   := "!" unary | primary
+//! primary := "(" expr ")"
+//!          | "has" "(" key ")"
+//!          | "topic" "(" name ")"
+//!          | key ( "==" | "!=" ) value
+//! key     := word | string
+//! name    := word | string
+//! value   := string | "0x" hex | decimal
+//! ```
+//!
+//! A `word` consists of ASCII alphanumerics and `_`, `-` or `.`, a `string` is enclosed in double
+//! quotes. Values are compared with the rlp encoded value of the kv-pair, strings and hex values
+//! are encoded as rlp strings and decimal values as rlp integers.
+//!
+//! For example, `has(opstack) && !has(eth2) && (topic(sequencers) || role == "sequencer")`.
+
+use std::{fmt, iter::Peekable, str::FromStr};
+
+use alloy_primitives::{hex, Bytes};
+
+use super::FilterOutcome;
+use crate::topic::Topic;
+
+/// A literal compared with the value of a kv-pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnrValue {
+    /// A utf-8 string, e.g. `"sequencer"`.
+    Str(String),
+    /// Raw bytes, e.g. `0x0a`.
+    Bytes(Bytes),
+    /// An unsigned integer, e.g. `10`.
+    Uint(u64),
+}
+
+impl EnrValue {
+    /// Returns the rlp encoding of the value.
+    pub fn rlp(&self) -> Vec<u8> {
+        match self {
+            Self::Str(s) => alloy_rlp::encode(s.as_bytes()),
+            Self::Bytes(bytes) => alloy_rlp::encode(bytes),
+            Self::Uint(n) => alloy_rlp::encode(n),
+        }
+    }
+}
+
+impl fmt::Display for EnrValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Str(s) => write!(f, "{s:?}"),
+            Self::Bytes(bytes) => write!(f, "{bytes}"),
+            Self::Uint(n) => write!(f, "{n}"),
+        }
+    }
+}
+
+/// Filter rules on the kv-pairs of a discovered [`Enr`](discv5::Enr).
+///
+/// Can be parsed from a string, see the [module](self) level docs for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnrFilter {
+    /// Node record must contain a kv-pair with the key.
+    Has(String),
+    /// Node record must contain a kv-pair with the key and value.
+    Eq(String, EnrValue),
+    /// Node record must advertise the [`Topic`].
+    Topic(Topic),
+    /// Inverts the inner filter.
+    Not(Box<Self>),
+    /// All inner filters must match.
+    All(Vec<Self>),
+    /// At least one inner filter must match.
+    Any(Vec<Self>),
+}
+
+impl EnrFilter {
+    /// Returns `true` if the [`Enr`](discv5::Enr) matches the filter.
+    pub fn matches<K: discv5::enr::EnrKey>(&self, enr: &discv5::enr::Enr<K>) -> bool {
+        match self {
+            Self::Has(key) => enr.get_raw_rlp(key).is_some(),
+            Self::Eq(key, value) => enr.get_raw_rlp(key) == Some(value.rlp().as_slice()),
+            Self::Topic(topic) => topic.is_advertised_by(enr),
+            Self::Not(filter) => !filter.matches(enr),
+            Self::All(filters) => filters.iter().all(|filter| filter.matches(enr)),
+            Self::Any(filters) => filters.iter().any(|filter| filter.matches(enr)),
+        }
+    }
+
+    /// Returns [`FilterOutcome::Ok`] if the [`Enr`](discv5::Enr) matches the filter.
+    pub fn filter(&self, enr: &discv5::Enr) -> FilterOutcome {
+        if self.matches(enr) {
+            return FilterOutcome::Ok
+        }
+        FilterOutcome::Ignore { reason: format!("enr doesn't match filter `{self}`") }
+    }
+}
+
+impl fmt::Display for EnrFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn fmt_nested(filter: &EnrFilter, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match filter {
+                EnrFilter::All(_) | EnrFilter::Any(_) => write!(f, "({filter})"),
+                _ => filter.fmt(f),
+            }
+        }
+
+        fn fmt_list(filters: &[EnrFilter], op: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for (i, filter) in filters.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {op} ")?;
+                }
+                fmt_nested(filter, f)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Self::Has(key) => write!(f, "has({key:?})"),
+            Self::Eq(key, value) => write!(f, "{key:?} == {value}"),
+            Self::Topic(topic) => write!(f, "topic({:?})", topic.name()),
+            Self::Not(filter) => {
+                f.write_str("!")?;
+                fmt_nested(filter, f)
+            }
+            Self::All(filters) => fmt_list(filters, "&&", f),
+            Self::Any(filters) => fmt_list(filters, "||", f),
+        }
+    }
+}
+
+impl FromStr for EnrFilter {
+    type Err = EnrFilterParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { tokens: tokenize(s)?.into_iter().peekable() };
+        let filter = parser.parse_or()?;
+        if let Some(token) = parser.tokens.next() {
+            return Err(EnrFilterParseError::UnexpectedToken(token.to_string()))
+        }
+        Ok(filter)
+    }
+}
+
+/// Errors parsing an [`EnrFilter`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum EnrFilterParseError {
+    /// Input contains a character that is not part of the syntax.
+    #[error("unexpected character '{0}'")]
+    UnexpectedChar(char),
+    /// A string literal is missing its closing quote.
+    #[error("unterminated string")]
+    UnterminatedString,
+    /// Input ended while more tokens were expected.
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+    /// A token is not valid at its position.
+    #[error("unexpected token `{0}`")]
+    UnexpectedToken(String),
+    /// A value literal is malformed.
+    #[error("invalid value `{0}`, expected a string, hex or decimal literal")]
+    InvalidValue(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Str(String),
+    LParen,
+    RParen,
+    Not,
+    And,
+    Or,
+    Eq,
+    NotEq,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Word(word) => f.write_str(word),
+            Self::Str(s) => write!(f, "{s:?}"),
+            Self::LParen => f.write_str("("),
+            Self::RParen => f.write_str(")"),
+            Self::Not => f.write_str("!"),
+            Self::And => f.write_str("&&"),
+            Self::Or => f.write_str("||"),
+            Self::Eq => f.write_str("=="),
+            Self::NotEq => f.write_str("!="),
+        }
+    }
+}
+
+const fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, EnrFilterParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '!' if chars.next_if_eq(&'=').is_some() => Token::NotEq,
+            '!' => Token::Not,
+            '=' if chars.next_if_eq(&'=').is_some() => Token::Eq,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => s.push(c),
+                        None => return Err(EnrFilterParseError::UnterminatedString),
+                    }
+                }
+                Token::Str(s)
+            }
+            c if is_word_char(c) => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|c| is_word_char(*c)) {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+            c => return Err(EnrFilterParseError::UnexpectedChar(c)),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<I: Iterator<Item = Token>> {
+    tokens: Peekable<I>,
+}
+
+impl<I: Iterator<Item = Token>> Parser<I> {
+    fn next(&mut self) -> Result<Token, EnrFilterParseError> {
+        self.tokens.next().ok_or(EnrFilterParseError::UnexpectedEnd)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), EnrFilterParseError> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(EnrFilterParseError::UnexpectedToken(token.to_string())),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<EnrFilter, EnrFilterParseError> {
+        let mut filters = vec![self.parse_and()?];
+        while self.tokens.next_if_eq(&Token::Or).is_some() {
+            filters.push(self.parse_and()?);
+        }
+        Ok(if filters.len() == 1 { filters.remove(0) } else { EnrFilter::Any(filters) })
+    }
+
+    fn parse_and(&mut self) -> Result<EnrFilter, EnrFilterParseError> {
+        let mut filters = vec![self.parse_unary()?];
+        while self.tokens.next_if_eq(&Token::And).is_some() {
+            filters.push(self.parse_unary()?);
+        }
+        Ok(if filters.len() == 1 { filters.remove(0) } else { EnrFilter::All(filters) })
+    }
+
+    fn parse_unary(&mut self) -> Result<EnrFilter, EnrFilterParseError> {
+        if self.tokens.next_if_eq(&Token::Not).is_some() {
+            return Ok(EnrFilter::Not(Box::new(self.parse_unary()?)))
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<EnrFilter, EnrFilterParseError> {
+        let key = match self.next()? {
+            Token::LParen => {
+                let filter = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                return Ok(filter)
+            }
+            Token::Word(word) if self.tokens.peek() == Some(&Token::LParen) => {
+                self.expect(Token::LParen)?;
+                let arg = self.parse_name()?;
+                self.expect(Token::RParen)?;
+                return match word.as_str() {
+                    "has" => Ok(EnrFilter::Has(arg)),
+                    "topic" => Ok(EnrFilter::Topic(Topic::new(arg))),
+                    _ => Err(EnrFilterParseError::UnexpectedToken(word)),
+                }
+            }
+            Token::Word(key) | Token::Str(key) => key,
+            token => return Err(EnrFilterParseError::UnexpectedToken(token.to_string())),
+        };
+
+        let negate = match self.next()? {
+            Token::Eq => false,
+            Token::NotEq => true,
+            token => return Err(EnrFilterParseError::UnexpectedToken(token.to_string())),
+        };
+        let filter = EnrFilter::Eq(key, self.parse_value()?);
+
+        Ok(if negate { EnrFilter::Not(Box::new(filter)) } else { filter })
+    }
+
+    fn parse_name(&mut self) -> Result<String, EnrFilterParseError> {
+        match self.next()? {
+            Token::Word(name) | Token::Str(name) => Ok(name),
+            token => Err(EnrFilterParseError::UnexpectedToken(token.to_string())),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<EnrValue, EnrFilterParseError> {
+        match self.next()? {
+            Token::Str(s) => Ok(EnrValue::Str(s)),
+            Token::Word(word) if word.starts_with("0x") => hex::decode(&word)
+                .map(|bytes| EnrValue::Bytes(bytes.into()))
+                .map_err(|_| EnrFilterParseError::InvalidValue(word)),
+            Token::Word(word) => word
+                .parse()
+                .map(EnrValue::Uint)
+                .map_err(|_| EnrFilterParseError::InvalidValue(word)),
+            token => Err(EnrFilterParseError::UnexpectedToken(token.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use discv5::enr::{CombinedKey, Enr};
+
+    use super::*;
+    use crate::{topic::TOPICS_ENR_KEY, NetworkStackId};
+
+    #[test]
+    fn parse_enr_filter() {
+        let filter: EnrFilter =
+            "has(opstack) && !has(\"eth2\") && (topic(sequencers) || role == \"sequencer\")"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            filter,
+            EnrFilter::All(vec![
+                EnrFilter::Has("opstack".to_string()),
+                EnrFilter::Not(Box::new(EnrFilter::Has("eth2".to_string()))),
+                EnrFilter::Any(vec![
+                    EnrFilter::Topic(Topic::new("sequencers")),
+                    EnrFilter::Eq("role".to_string(), EnrValue::Str("sequencer".to_string())),
+                ]),
+            ])
+        );
+
+        // display output can be parsed again
+        assert_eq!(filter.to_string().parse::<EnrFilter>().unwrap(), filter);
+
+        assert_eq!(
+            "version != 0x01".parse::<EnrFilter>().unwrap(),
+            EnrFilter::Not(Box::new(EnrFilter::Eq(
+                "version".to_string(),
+                EnrValue::Bytes(Bytes::from_static(&[1]))
+            )))
+        );
+
+        assert_eq!("has(eth".parse::<EnrFilter>(), Err(EnrFilterParseError::UnexpectedEnd));
+        assert_eq!(
+            "eth == foo".parse::<EnrFilter>(),
+            Err(EnrFilterParseError::InvalidValue("foo".to_string()))
+        );
+        assert_eq!("eth = 1".parse::<EnrFilter>(), Err(EnrFilterParseError::UnexpectedChar('=')));
+        assert_eq!(
+            "has(eth) has(eth2)".parse::<EnrFilter>(),
+            Err(EnrFilterParseError::UnexpectedToken("has".to_string()))
+        );
+    }
+
+    #[test]
+    fn enr_filter_matches() {
+        let sequencers = Topic::new("sequencers");
+
+        let sk = CombinedKey::generate_secp256k1();
+        let enr = Enr::builder()
+            .add_value_rlp(NetworkStackId::OPSTACK, alloy_rlp::encode(10u64).into())
+            .add_value_rlp("role", alloy_rlp::encode(&b"sequencer"[..]).into())
+            .add_value_rlp(TOPICS_ENR_KEY, alloy_rlp::encode(vec![sequencers.id()]).into())
+            .build(&sk)
+            .unwrap();
+
+        for (filter, expected) in [
+            ("has(opstack)", true),
+            ("has(eth)", false),
+            ("opstack == 10", true),
+            ("opstack == 11", false),
+            ("role == \"sequencer\"", true),
+            ("role == 0x73657175656e636572", true),
+            ("role != \"sequencer\"", false),
+            ("topic(sequencers)", true),
+            ("topic(builders)", false),
+            ("has(eth) || topic(sequencers)", true),
+            ("has(opstack) && !topic(sequencers)", false),
+        ] {
+            let filter: EnrFilter = filter.parse().unwrap();
+            assert_eq!(filter.matches(&enr), expected, "{filter}");
+        }
+
+        let filter: EnrFilter = "has(eth)".parse().unwrap();
+        assert!(matches!(filter.filter(&enr), FilterOutcome::Ignore { .. }));
+    }
+}
@@ -5,6 +5,10 @@ use std::collections::HashSet;
 use derive_more::Constructor;
 use itertools::Itertools;
 
+mod expr;
+
+pub use expr::{EnrFilter, EnrFilterParseError, EnrValue};
+
 /// Outcome of applying filtering rules on node record.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FilterOutcome {
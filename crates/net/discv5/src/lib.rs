@@ -35,6 +35,7 @@ pub mod error;
 pub mod filter;
 pub mod metrics;
 pub mod network_stack_id;
+pub mod topic;
 
 pub use discv5::{self, IpMode};
 
@@ -45,8 +46,9 @@ pub use config::{
 };
 pub use enr::enr_to_discv4_id;
 pub use error::Error;
-pub use filter::{FilterOutcome, MustNotIncludeKeys};
+pub use filter::{EnrFilter, FilterOutcome, MustNotIncludeKeys};
 pub use network_stack_id::NetworkStackId;
+pub use topic::{Topic, TopicId};
 
 use metrics::{DiscoveredPeersMetrics, Discv5Metrics};
 
@@ -62,6 +64,12 @@ pub const MAX_KBUCKET_INDEX: usize = 255;
 /// Default is 0th index.
 pub const DEFAULT_MIN_TARGET_KBUCKET_INDEX: usize = 0;
 
+/// Number of peers advertising a looked up topic, that a periodic lookup query searches for before
+/// it completes.
+///
+/// Default is 16, the size of a kbucket.
+pub const DEFAULT_TOPIC_LOOKUP_PEERS: usize = 16;
+
 /// Transparent wrapper around [`discv5::Discv5`].
 #[derive(Clone)]
 pub struct Discv5 {
@@ -73,6 +81,10 @@ pub struct Discv5 {
     fork_key: Option<&'static [u8]>,
     /// Filter applied to a discovered peers before passing it up to app.
     discovered_peer_filter: MustNotIncludeKeys,
+    /// Additional filter rules on the kv-pairs of a discovered peer's node record.
+    enr_filter: Option<EnrFilter>,
+    /// Topics looked up, discovered peers must advertise one of these if any are set.
+    lookup_topics: Arc<[Topic]>,
     /// Metrics for underlying [`discv5::Discv5`] node and filtered discovered peers.
     metrics: Discv5Metrics,
 }
@@ -122,6 +134,18 @@ impl Discv5 {
         self.set_eip868_in_local_enr(key, buf.into())
     }
 
+    /// Sets the external UDP or TCP socket of the node in the local [`Enr`], e.g. the address
+    /// and port mapped on a NAT gateway.
+    pub fn update_local_enr_socket(&self, socket: SocketAddr, is_tcp: bool) {
+        if self.discv5.update_local_enr_socket(socket, is_tcp) {
+            debug!(target: "discv5",
+                %socket,
+                is_tcp,
+                "updated socket in local enr"
+            );
+        }
+    }
+
     /// Adds the peer and id to the ban list.
     ///
     /// This will prevent any future inclusion in the table
@@ -180,6 +204,8 @@ impl Discv5 {
             bootstrap_lookup_interval,
             bootstrap_lookup_countdown,
             discovered_peer_filter,
+            enr_filter,
+            lookup_topics,
             ..
         } = discv5_config;
 
@@ -202,6 +228,7 @@ impl Discv5 {
         bootstrap(bootstrap_nodes, &discv5).await?;
 
         let metrics = Discv5Metrics::default();
+        let lookup_topics: Arc<[Topic]> = lookup_topics.into();
 
         //
         // 4. start bg kbuckets maintenance
@@ -210,12 +237,21 @@ impl Discv5 {
             lookup_interval,
             bootstrap_lookup_interval,
             bootstrap_lookup_countdown,
+            lookup_topics.clone(),
             metrics.clone(),
             discv5.clone(),
         );
 
         Ok((
-            Self { discv5, rlpx_ip_mode, fork_key, discovered_peer_filter, metrics },
+            Self {
+                discv5,
+                rlpx_ip_mode,
+                fork_key,
+                discovered_peer_filter,
+                enr_filter,
+                lookup_topics,
+                metrics,
+            },
             discv5_updates,
             bc_enr,
         ))
@@ -357,7 +393,43 @@ impl Discv5 {
     /// Applies filtering rules on an ENR. Returns [`Ok`](FilterOutcome::Ok) if peer should be
     /// passed up to app, and [`Ignore`](FilterOutcome::Ignore) if peer should instead be dropped.
     pub fn filter_discovered_peer(&self, enr: &discv5::Enr) -> FilterOutcome {
-        self.discovered_peer_filter.filter(enr)
+        let outcome = self.discovered_peer_filter.filter(enr);
+        if !outcome.is_ok() {
+            return outcome
+        }
+        if let Some(filter) = &self.enr_filter {
+            let outcome = filter.filter(enr);
+            if !outcome.is_ok() {
+                return outcome
+            }
+        }
+        if !self.lookup_topics.is_empty() && !topic::advertises_any(enr, &self.lookup_topics) {
+            return FilterOutcome::Ignore {
+                reason: format!("one of topics {} required", self.lookup_topics.iter().format(",")),
+            }
+        }
+        FilterOutcome::Ok
+    }
+
+    /// Runs a lookup query for peers that advertise the given [`Topic`]. Returns up to
+    /// `target_peer_no` node records of such peers.
+    ///
+    /// Peers found by the query are inserted into the kbuckets, and passed up to the app via
+    /// [`discv5::Event`]s like any other discovered peer.
+    pub async fn find_topic_peers(
+        &self,
+        topic: &Topic,
+        target_peer_no: usize,
+    ) -> Result<Vec<discv5::Enr>, Error> {
+        let topic = topic.clone();
+        self.discv5
+            .find_node_predicate(
+                discv5::enr::NodeId::random(),
+                Box::new(move |enr| topic.is_advertised_by(enr)),
+                target_peer_no,
+            )
+            .await
+            .map_err(Error::LookupFailed)
     }
 
     /// Returns the [`ForkId`] of the given [`Enr`](discv5::Enr) w.r.t. the local node's network
@@ -424,7 +496,8 @@ pub fn build_local_enr(
 ) -> (Enr<SecretKey>, NodeRecord, Option<&'static [u8]>, IpMode) {
     let mut builder = discv5::enr::Enr::builder();
 
-    let Config { discv5_config, fork, tcp_socket, other_enr_kv_pairs, .. } = config;
+    let Config { discv5_config, fork, tcp_socket, other_enr_kv_pairs, advertised_topics, .. } =
+        config;
 
     let socket = match discv5_config.listen_config {
         ListenConfig::Ipv4 { ip, port } => {
@@ -474,6 +547,12 @@ pub fn build_local_enr(
         builder.add_value_rlp(key, value.clone().into());
     }
 
+    // advertise topics
+    if !advertised_topics.is_empty() {
+        let topics = advertised_topics.iter().map(Topic::id).collect::<Vec<_>>();
+        builder.add_value_rlp(topic::TOPICS_ENR_KEY, alloy_rlp::encode(topics).into());
+    }
+
     // enr v4 not to get confused with discv4, independent versioning enr and
     // discovery
     let enr = builder.build(sk).expect("should build enr v4");
@@ -526,6 +605,7 @@ pub fn spawn_populate_kbuckets_bg(
     lookup_interval: u64,
     bootstrap_lookup_interval: u64,
     bootstrap_lookup_countdown: u64,
+    lookup_topics: Arc<[Topic]>,
     metrics: Discv5Metrics,
     discv5: Arc<discv5::Discv5>,
 ) {
@@ -550,7 +630,7 @@ pub fn spawn_populate_kbuckets_bg(
                     "starting bootstrap boost lookup query"
                 );
 
-                lookup(target, &discv5, &metrics).await;
+                lookup(target, &lookup_topics, &discv5, &metrics).await;
 
                 tokio::time::sleep(pulse_lookup_interval).await;
             }
@@ -567,7 +647,7 @@ pub fn spawn_populate_kbuckets_bg(
                     "starting periodic lookup query"
                 );
 
-                lookup(target, &discv5, &metrics).await;
+                lookup(target, &lookup_topics, &discv5, &metrics).await;
 
                 if kbucket_index > DEFAULT_MIN_TARGET_KBUCKET_INDEX {
                     // try to populate bucket one step closer
@@ -615,8 +695,11 @@ pub fn get_lookup_target(
 }
 
 /// Runs a [`discv5::Discv5`] lookup query.
+///
+/// If any topics are given, the query searches for peers that advertise one of the topics.
 pub async fn lookup(
     target: discv5::enr::NodeId,
+    topics: &[Topic],
     discv5: &discv5::Discv5,
     metrics: &DiscoveredPeersMetrics,
 ) {
@@ -625,7 +708,20 @@ pub async fn lookup(
         discv5.with_kbuckets(|kbuckets| kbuckets.read().iter_ref().count()),
     );
 
-    match discv5.find_node(target).await {
+    let result = if topics.is_empty() {
+        discv5.find_node(target).await
+    } else {
+        let topics = topics.to_vec();
+        discv5
+            .find_node_predicate(
+                target,
+                Box::new(move |enr| topic::advertises_any(enr, &topics)),
+                DEFAULT_TOPIC_LOOKUP_PEERS,
+            )
+            .await
+    };
+
+    match result {
         Err(err) => trace!(target: "net::discv5",
             %err,
             "lookup query failed"
@@ -671,6 +767,8 @@ mod test {
             rlpx_ip_mode: IpMode::Ip4,
             fork_key: None,
             discovered_peer_filter: MustNotIncludeKeys::default(),
+            enr_filter: None,
+            lookup_topics: Arc::new([]),
             metrics: Discv5Metrics::default(),
         }
     }
@@ -892,4 +990,43 @@ mod test {
         assert_eq!(fork_id, decoded_fork_id);
         assert_eq!(TCP_PORT, enr.tcp4().unwrap()); // listen config is defaulting to ip mode ipv4
     }
+
+    #[test]
+    fn filter_discovered_peer_by_enr_and_topic() {
+        let sequencers = Topic::new("sequencers");
+
+        let mut discv5 = discv5_noop();
+        discv5.enr_filter = Some("has(opstack)".parse().unwrap());
+        discv5.lookup_topics = Arc::new([sequencers.clone()]);
+
+        // advertises topic in local enr
+        let config = Config::builder((Ipv4Addr::UNSPECIFIED, 30303).into())
+            .add_enr_kv_pair(NetworkStackId::OPSTACK, alloy_rlp::encode(10u64).into())
+            .advertise_topics([sequencers.clone()])
+            .build();
+        let sk = SecretKey::new(&mut thread_rng());
+        let (enr, _, _, _) = build_local_enr(&sk, &config);
+        let EnrCombinedKeyWrapper(enr) = enr.into();
+
+        assert!(sequencers.is_advertised_by(&enr));
+        assert_eq!(discv5.filter_discovered_peer(&enr), FilterOutcome::Ok);
+
+        // missing topic
+        let config = Config::builder((Ipv4Addr::UNSPECIFIED, 30303).into())
+            .add_enr_kv_pair(NetworkStackId::OPSTACK, alloy_rlp::encode(10u64).into())
+            .build();
+        let (enr, _, _, _) = build_local_enr(&sk, &config);
+        let EnrCombinedKeyWrapper(enr) = enr.into();
+
+        assert!(!discv5.filter_discovered_peer(&enr).is_ok());
+
+        // doesn't match enr filter
+        let config = Config::builder((Ipv4Addr::UNSPECIFIED, 30303).into())
+            .advertise_topics([sequencers])
+            .build();
+        let (enr, _, _, _) = build_local_enr(&sk, &config);
+        let EnrCombinedKeyWrapper(enr) = enr.into();
+
+        assert!(!discv5.filter_discovered_peer(&enr).is_ok());
+    }
 }
@@ -0,0 +1,106 @@
+//! Topics advertised in node records, used to discover the peers of app-specific networks.
+//!
+//! A node advertises the topics it is interested in under the [`TOPICS_ENR_KEY`] kv-pair of its
+//! [`Enr`](discv5::Enr). Peers that look up a topic only pass nodes advertising the topic up to
+//! the app, and run lookup queries until enough such nodes are found.
+
+use std::{fmt, str::FromStr};
+
+use alloy_primitives::{keccak256, B64};
+
+/// Key of the ENR kv-pair that lists the topics a node advertises.
+pub const TOPICS_ENR_KEY: &[u8] = b"topics";
+
+/// Identifies a [`Topic`] in a node record.
+///
+/// This is the first 8 bytes of the keccak256 hash of the topic name, to keep node records within
+/// the 300 bytes size limit.
+pub type TopicId = B64;
+
+/// A named topic, e.g. `"sequencers"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Topic {
+    name: String,
+    id: TopicId,
+}
+
+impl Topic {
+    /// Returns a new topic with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        let id = TopicId::from_slice(&keccak256(name.as_bytes())[..8]);
+        Self { name, id }
+    }
+
+    /// Returns the name of the topic.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the [`TopicId`] advertised in node records.
+    pub const fn id(&self) -> TopicId {
+        self.id
+    }
+
+    /// Returns `true` if the [`Enr`](discv5::Enr) advertises the topic.
+    pub fn is_advertised_by<K: discv5::enr::EnrKey>(&self, enr: &discv5::enr::Enr<K>) -> bool {
+        advertised_topics(enr).contains(&self.id)
+    }
+}
+
+impl fmt::Display for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.name.fmt(f)
+    }
+}
+
+impl FromStr for Topic {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s))
+    }
+}
+
+/// Returns the [`TopicId`]s advertised by the [`Enr`](discv5::Enr).
+///
+/// Returns an empty list if the node record doesn't advertise any topics, or if the kv-pair can't
+/// be decoded.
+pub fn advertised_topics<K: discv5::enr::EnrKey>(enr: &discv5::enr::Enr<K>) -> Vec<TopicId> {
+    enr.get_decodable::<Vec<TopicId>>(TOPICS_ENR_KEY).and_then(Result::ok).unwrap_or_default()
+}
+
+/// Returns `true` if the [`Enr`](discv5::Enr) advertises any of the given topics.
+pub fn advertises_any<K: discv5::enr::EnrKey>(enr: &discv5::enr::Enr<K>, topics: &[Topic]) -> bool {
+    let advertised = advertised_topics(enr);
+    topics.iter().any(|topic| advertised.contains(&topic.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use discv5::enr::{CombinedKey, Enr};
+
+    use super::*;
+
+    #[test]
+    fn advertised_topic() {
+        let sequencers = Topic::new("sequencers");
+        let builders = Topic::new("builders");
+
+        let sk = CombinedKey::generate_secp256k1();
+        let enr = Enr::builder()
+            .add_value_rlp(TOPICS_ENR_KEY, alloy_rlp::encode(vec![sequencers.id()]).into())
+            .build(&sk)
+            .unwrap();
+
+        assert_eq!(advertised_topics(&enr), vec![sequencers.id()]);
+        assert!(sequencers.is_advertised_by(&enr));
+        assert!(!builders.is_advertised_by(&enr));
+        assert!(advertises_any(&enr, &[builders.clone(), sequencers]));
+        assert!(!advertises_any(&enr, &[builders]));
+
+        let sk = CombinedKey::generate_secp256k1();
+        let enr = Enr::builder().build(&sk).unwrap();
+        assert!(advertised_topics(&enr).is_empty());
+    }
+}
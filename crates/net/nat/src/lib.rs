@@ -1,4 +1,4 @@
-//! Helpers for resolving the external IP.
+//! Helpers for resolving the external IP and mapping ports on the local gateway.
 //!
 //! ## Feature Flags
 //!
@@ -12,15 +12,27 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+pub mod mapping;
+pub mod natpmp;
+pub mod pcp;
+pub mod upnp;
+
+pub use mapping::{
+    default_gateway, NatUpdate, PortMapper, PortMapping, PortMappingError, PortMappingProtocol,
+    PortMappingRequest, DEFAULT_PORT_MAPPING_LIFETIME,
+};
+
 use std::{
     fmt,
     future::{poll_fn, Future},
     net::{AddrParseError, IpAddr},
     pin::Pin,
     str::FromStr,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
+use tokio::{sync::Mutex, time::Sleep};
 
 #[cfg(feature = "serde")]
 use serde_with::{DeserializeFromStr, SerializeDisplay};
@@ -40,6 +52,14 @@ pub enum NatResolver {
     Any,
     /// Resolve external IP via `UPnP`.
     Upnp,
+    /// Resolve external IP via NAT-PMP, using the given gateway or the default gateway of the
+    /// host.
+    NatPmp(Option<IpAddr>),
+    /// Resolve external IP via PCP, using the given gateway or the default gateway of the host.
+    ///
+    /// PCP can only report the external IP of a port mapping, so without port mappings this
+    /// behaves like [`NatResolver::PublicIp`].
+    Pcp(Option<IpAddr>),
     /// Resolve external IP via a network request.
     PublicIp,
     /// Use the given [`IpAddr`]
@@ -53,6 +73,13 @@ impl NatResolver {
     pub async fn external_addr(self) -> Option<IpAddr> {
         external_addr_with(self).await
     }
+
+    /// Returns true if this resolver can map ports on the local gateway.
+    ///
+    /// See also [`PortMapper`].
+    pub const fn supports_port_mapping(&self) -> bool {
+        matches!(self, Self::Upnp | Self::NatPmp(_) | Self::Pcp(_))
+    }
 }
 
 impl fmt::Display for NatResolver {
@@ -60,6 +87,10 @@ impl fmt::Display for NatResolver {
         match self {
             Self::Any => f.write_str("any"),
             Self::Upnp => f.write_str("upnp"),
+            Self::NatPmp(None) => f.write_str("natpmp"),
+            Self::NatPmp(Some(gateway)) => write!(f, "natpmp:{gateway}"),
+            Self::Pcp(None) => f.write_str("pcp"),
+            Self::Pcp(Some(gateway)) => write!(f, "pcp:{gateway}"),
             Self::PublicIp => f.write_str("publicip"),
             Self::ExternalIp(ip) => write!(f, "extip:{ip}"),
             Self::None => f.write_str("none"),
@@ -85,13 +116,21 @@ impl FromStr for NatResolver {
         let r = match s {
             "any" => Self::Any,
             "upnp" => Self::Upnp,
+            "natpmp" => Self::NatPmp(None),
+            "pcp" => Self::Pcp(None),
             "none" => Self::None,
             "publicip" | "public-ip" => Self::PublicIp,
             s => {
+                if let Some(gateway) = s.strip_prefix("natpmp:") {
+                    return Ok(Self::NatPmp(Some(gateway.parse::<IpAddr>()?)));
+                }
+                if let Some(gateway) = s.strip_prefix("pcp:") {
+                    return Ok(Self::Pcp(Some(gateway.parse::<IpAddr>()?)));
+                }
                 let Some(ip) = s.strip_prefix("extip:") else {
                     return Err(ParseNatResolverError::UnknownVariant(format!(
                         "Unknown Nat Resolver: {s}"
-                    )))
+                    )));
                 };
                 Self::ExternalIp(ip.parse::<IpAddr>()?)
             }
@@ -100,12 +139,24 @@ impl FromStr for NatResolver {
     }
 }
 
+/// The in-flight resolution of a [`ResolveNatInterval`], yields the update and the next time a
+/// port mapping must be renewed.
+type ResolveFuture =
+    Pin<Box<dyn Future<Output = (NatUpdate, Option<tokio::time::Instant>)> + Send>>;
+
 /// With this type you can resolve the external public IP address on an interval basis.
+///
+/// If configured with port mappings, see [`ResolveNatInterval::with_port_mappings`], these are
+/// requested from the gateway on every tick and renewed before their lease expires.
 #[must_use = "Does nothing unless polled"]
 pub struct ResolveNatInterval {
     resolver: NatResolver,
-    future: Option<Pin<Box<dyn Future<Output = Option<IpAddr>> + Send>>>,
+    future: Option<ResolveFuture>,
     interval: tokio::time::Interval,
+    /// Maintains the configured port mappings, if the resolver supports port mapping.
+    port_mapper: Option<Arc<Mutex<PortMapper>>>,
+    /// Fires when the next port mapping must be renewed.
+    renewal: Option<Pin<Box<Sleep>>>,
 }
 
 impl fmt::Debug for ResolveNatInterval {
@@ -114,13 +165,15 @@ impl fmt::Debug for ResolveNatInterval {
             .field("resolver", &self.resolver)
             .field("future", &self.future.as_ref().map(drop))
             .field("interval", &self.interval)
+            .field("port_mapper", &self.port_mapper)
+            .field("renewal", &self.renewal.as_ref().map(|sleep| sleep.deadline()))
             .finish()
     }
 }
 
 impl ResolveNatInterval {
     fn with_interval(resolver: NatResolver, interval: tokio::time::Interval) -> Self {
-        Self { resolver, future: None, interval }
+        Self { resolver, future: None, interval, port_mapper: None, renewal: None }
     }
 
     /// Creates a new [`ResolveNatInterval`] that attempts to resolve the public IP with interval of
@@ -143,6 +196,32 @@ impl ResolveNatInterval {
         Self::with_interval(resolver, interval)
     }
 
+    /// Additionally maintains the given port mappings on the gateway.
+    ///
+    /// This has no effect if the resolver does not support port mapping, see
+    /// [`NatResolver::supports_port_mapping`].
+    pub fn with_port_mappings(
+        mut self,
+        requests: impl IntoIterator<Item = PortMappingRequest>,
+    ) -> Self {
+        self.port_mapper =
+            PortMapper::new(self.resolver, requests).map(|mapper| Arc::new(Mutex::new(mapper)));
+        self
+    }
+
+    /// Returns a future that deletes all port mappings from the gateway.
+    ///
+    /// The mappings are not requested again afterwards, this is intended to be awaited on
+    /// shutdown.
+    pub fn unmap_ports(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mapper = self.port_mapper.clone();
+        async move {
+            if let Some(mapper) = mapper {
+                mapper.lock().await.unmap_all().await;
+            }
+        }
+    }
+
     /// Completes when the next [`IpAddr`] in the interval has been reached.
     pub async fn tick(&mut self) -> Option<IpAddr> {
         poll_fn(|cx| self.poll_tick(cx)).await
@@ -156,13 +235,60 @@ impl ResolveNatInterval {
     ///  * `Poll::Ready(Option<IpAddr>)` if the next [`IpAddr`] has been resolved. This returns
     ///    `None` if the attempt was unsuccessful.
     pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Option<IpAddr>> {
-        if self.interval.poll_tick(cx).is_ready() {
-            self.future = Some(Box::pin(self.resolver.external_addr()));
+        self.poll_update(cx).map(|update| update.external_ip)
+    }
+
+    /// Completes when the next [`NatUpdate`] has been resolved.
+    pub async fn tick_update(&mut self) -> NatUpdate {
+        poll_fn(|cx| self.poll_update(cx)).await
+    }
+
+    /// Polls for the next [`NatUpdate`], which contains the resolved [`IpAddr`] and all active
+    /// port mappings.
+    ///
+    /// An update is resolved on every tick of the interval and whenever a port mapping must be
+    /// renewed.
+    pub fn poll_update(&mut self, cx: &mut Context<'_>) -> Poll<NatUpdate> {
+        let renewal_due =
+            self.renewal.as_mut().is_some_and(|renewal| renewal.as_mut().poll(cx).is_ready());
+        if renewal_due {
+            self.renewal = None;
+        }
+
+        if self.interval.poll_tick(cx).is_ready() || renewal_due {
+            let resolver = self.resolver;
+            self.future = Some(match self.port_mapper.clone() {
+                Some(mapper) => Box::pin(async move {
+                    let mut mapper = mapper.lock().await;
+                    let mut update = mapper.refresh().await;
+                    if update.external_ip.is_none() {
+                        update.external_ip = resolver.external_addr().await;
+                    }
+                    (update, mapper.next_renewal())
+                }),
+                None => Box::pin(async move {
+                    (
+                        NatUpdate {
+                            external_ip: resolver.external_addr().await,
+                            ..Default::default()
+                        },
+                        None,
+                    )
+                }),
+            });
         }
 
         if let Some(mut fut) = self.future.take() {
             match fut.as_mut().poll(cx) {
-                Poll::Ready(ip) => return Poll::Ready(ip),
+                Poll::Ready((update, next_renewal)) => {
+                    if let Some(deadline) = next_renewal {
+                        let mut renewal = Box::pin(tokio::time::sleep_until(deadline));
+                        // register the renewal timer
+                        let _ = renewal.as_mut().poll(cx);
+                        self.renewal = Some(renewal);
+                    }
+                    return Poll::Ready(update);
+                }
                 Poll::Pending => self.future = Some(fut),
             }
         }
@@ -179,7 +305,26 @@ pub async fn external_ip() -> Option<IpAddr> {
 /// Given a [`NatResolver`] attempts to produce an IP address (best effort).
 pub async fn external_addr_with(resolver: NatResolver) -> Option<IpAddr> {
     match resolver {
-        NatResolver::Any | NatResolver::Upnp | NatResolver::PublicIp => resolve_external_ip().await,
+        NatResolver::Any | NatResolver::PublicIp | NatResolver::Pcp(_) => {
+            resolve_external_ip().await
+        }
+        NatResolver::Upnp => {
+            let upnp = async { upnp::UpnpGateway::discover().await?.external_ip().await };
+            match upnp.await {
+                Ok(ip) => Some(ip),
+                Err(_) => resolve_external_ip().await,
+            }
+        }
+        NatResolver::NatPmp(gateway) => {
+            let natpmp = async {
+                let gateway = gateway.or_else(default_gateway)?;
+                natpmp::NatPmpClient::with_gateway_ip(gateway).external_ip().await.ok()
+            };
+            match natpmp.await {
+                Some(ip) => Some(ip),
+                None => resolve_external_ip().await,
+            }
+        }
         NatResolver::ExternalIp(ip) => Some(ip),
         NatResolver::None => None,
     }
@@ -230,10 +375,73 @@ mod tests {
     fn test_from_str() {
         assert_eq!(NatResolver::Any, "any".parse().unwrap());
         assert_eq!(NatResolver::None, "none".parse().unwrap());
+        assert_eq!(NatResolver::NatPmp(None), "natpmp".parse().unwrap());
+        assert_eq!(NatResolver::Pcp(None), "pcp".parse().unwrap());
 
         let ip = NatResolver::ExternalIp(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
         let s = "extip:0.0.0.0";
         assert_eq!(ip, s.parse().unwrap());
         assert_eq!(ip.to_string().as_str(), s);
+
+        let gateway = NatResolver::NatPmp(Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))));
+        let s = "natpmp:192.168.1.1";
+        assert_eq!(gateway, s.parse().unwrap());
+        assert_eq!(gateway.to_string().as_str(), s);
+
+        let gateway = NatResolver::Pcp(Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))));
+        let s = "pcp:192.168.1.1";
+        assert_eq!(gateway, s.parse().unwrap());
+        assert_eq!(gateway.to_string().as_str(), s);
+    }
+
+    #[tokio::test]
+    async fn resolve_interval_with_port_mappings() {
+        use tokio::net::UdpSocket;
+
+        // fake NAT-PMP gateway that grants every mapping for 120s
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let gateway = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 64];
+            loop {
+                let (len, from) = socket.recv_from(&mut buf).await.unwrap();
+                let req = &buf[..len];
+                let mut resp = vec![0, 128 + req[1], 0, 0, 0, 0, 0, 1];
+                if req[1] == 0 {
+                    resp.extend_from_slice(&[1, 2, 3, 4]);
+                } else {
+                    resp.extend_from_slice(&req[4..8]);
+                    resp.extend_from_slice(&120u32.to_be_bytes());
+                }
+                socket.send_to(&resp, from).await.unwrap();
+            }
+        });
+
+        let resolver = NatResolver::NatPmp(Some(gateway.ip()));
+        let mapper = PortMapper::new(
+            resolver,
+            [PortMappingRequest::udp(30303), PortMappingRequest::tcp(30303)],
+        )
+        .unwrap()
+        .with_natpmp_gateway(gateway);
+        let mapper = Arc::new(Mutex::new(mapper));
+
+        let mut interval = ResolveNatInterval::interval(resolver, Duration::from_secs(60 * 60));
+        interval.port_mapper = Some(mapper.clone());
+
+        let update = interval.tick_update().await;
+        assert_eq!(update.external_ip, Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))));
+        assert_eq!(update.mapped_port(PortMappingProtocol::Udp, 30303), Some(30303));
+        assert_eq!(update.mapped_port(PortMappingProtocol::Tcp, 30303), Some(30303));
+
+        // renewed after half the lifetime, long before the next tick of the interval
+        let renewal = mapper.lock().await.next_renewal().unwrap();
+        assert!(renewal <= tokio::time::Instant::now() + Duration::from_secs(60));
+        assert!(interval.renewal.is_some());
+
+        // mappings are deleted on shutdown and not requested again
+        interval.unmap_ports().await;
+        let update = mapper.lock().await.refresh().await;
+        assert!(update.mappings.is_empty());
     }
 }
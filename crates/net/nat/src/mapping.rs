@@ -0,0 +1,480 @@
+//! Port mapping on the local gateway via `UPnP`, NAT-PMP or PCP.
+
+use crate::{natpmp::NatPmpClient, pcp::PcpClient, upnp::UpnpGateway, NatResolver};
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::{net::UdpSocket, time::Instant};
+use tracing::{debug, trace};
+
+/// The default lifetime of a port mapping, as recommended by RFC 6886.
+pub const DEFAULT_PORT_MAPPING_LIFETIME: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// How often a UDP request is sent to the gateway before giving up.
+pub const DEFAULT_REQUEST_ATTEMPTS: usize = 4;
+
+/// The timeout of the first UDP request, doubled on every retransmission.
+const INITIAL_REQUEST_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Lower bound for renewing a mapping, in case the gateway grants very short lifetimes.
+const MIN_RENEWAL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The transport protocol of a port mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PortMappingProtocol {
+    /// Map a TCP port.
+    Tcp,
+    /// Map a UDP port.
+    Udp,
+}
+
+impl fmt::Display for PortMappingProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp => f.write_str("TCP"),
+            Self::Udp => f.write_str("UDP"),
+        }
+    }
+}
+
+/// A port mapping to request from the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortMappingRequest {
+    /// The transport protocol to map.
+    pub protocol: PortMappingProtocol,
+    /// The local port to forward to.
+    pub internal_port: u16,
+    /// The suggested external port, the gateway may assign a different one.
+    pub external_port: u16,
+    /// The requested lifetime of the mapping.
+    pub lifetime: Duration,
+}
+
+impl PortMappingRequest {
+    /// Creates a new request that maps the given port to the same external port.
+    pub const fn new(protocol: PortMappingProtocol, port: u16) -> Self {
+        Self {
+            protocol,
+            internal_port: port,
+            external_port: port,
+            lifetime: DEFAULT_PORT_MAPPING_LIFETIME,
+        }
+    }
+
+    /// Creates a new request for a TCP port.
+    pub const fn tcp(port: u16) -> Self {
+        Self::new(PortMappingProtocol::Tcp, port)
+    }
+
+    /// Creates a new request for a UDP port.
+    pub const fn udp(port: u16) -> Self {
+        Self::new(PortMappingProtocol::Udp, port)
+    }
+
+    /// Sets the suggested external port.
+    pub const fn with_external_port(mut self, external_port: u16) -> Self {
+        self.external_port = external_port;
+        self
+    }
+
+    /// Sets the requested lifetime.
+    pub const fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+}
+
+/// A port mapping granted by the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PortMapping {
+    /// The transport protocol that is mapped.
+    pub protocol: PortMappingProtocol,
+    /// The local port traffic is forwarded to.
+    pub internal_port: u16,
+    /// The port on the gateway's external address.
+    pub external_port: u16,
+    /// How long the gateway keeps the mapping.
+    pub lifetime: Duration,
+}
+
+/// The outcome of resolving the external address and refreshing all port mappings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NatUpdate {
+    /// The resolved external IP address, if any.
+    pub external_ip: Option<IpAddr>,
+    /// All currently active port mappings.
+    pub mappings: Vec<PortMapping>,
+}
+
+impl NatUpdate {
+    /// Returns the external port mapped to the given local port, if any.
+    pub fn mapped_port(&self, protocol: PortMappingProtocol, internal_port: u16) -> Option<u16> {
+        self.mappings
+            .iter()
+            .find(|m| m.protocol == protocol && m.internal_port == internal_port)
+            .map(|m| m.external_port)
+    }
+
+    /// Returns the external socket address mapped to the given local port, if the external IP
+    /// and a mapping for the port are known.
+    pub fn mapped_addr(
+        &self,
+        protocol: PortMappingProtocol,
+        internal_port: u16,
+    ) -> Option<SocketAddr> {
+        Some(SocketAddr::new(self.external_ip?, self.mapped_port(protocol, internal_port)?))
+    }
+}
+
+/// Errors that can occur when talking to the gateway.
+#[derive(Debug, thiserror::Error)]
+pub enum PortMappingError {
+    /// The resolver does not support port mapping.
+    #[error("port mapping is not supported by nat resolver {0}")]
+    Unsupported(NatResolver),
+    /// No gateway could be found.
+    #[error("no gateway found")]
+    NoGateway,
+    /// The gateway did not respond in time.
+    #[error("gateway did not respond")]
+    Timeout,
+    /// The gateway responded with a malformed message.
+    #[error("malformed response from gateway")]
+    MalformedResponse,
+    /// The gateway rejected a NAT-PMP request with the given result code.
+    #[error("NAT-PMP request failed with result code {0}")]
+    NatPmp(u16),
+    /// The gateway rejected a PCP request with the given result code.
+    #[error("PCP request failed with result code {0}")]
+    Pcp(u8),
+    /// A `UPnP` request failed.
+    #[error("UPnP request failed: {0}")]
+    Upnp(String),
+    /// HTTP request to a `UPnP` gateway failed.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// Socket error.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// The gateway a [`PortMapper`] talks to.
+#[derive(Debug)]
+enum Gateway {
+    NatPmp(NatPmpClient),
+    Pcp(PcpClient),
+    Upnp(UpnpGateway),
+}
+
+impl Gateway {
+    /// Finds the gateway for the given resolver.
+    ///
+    /// The PCP nonces of the previous gateway are kept, so that existing mappings can be renewed.
+    async fn discover(
+        resolver: NatResolver,
+        previous: Option<&Self>,
+    ) -> Result<Self, PortMappingError> {
+        match resolver {
+            NatResolver::Upnp => Ok(Self::Upnp(UpnpGateway::discover().await?)),
+            NatResolver::NatPmp(gateway) => {
+                let gateway =
+                    gateway.or_else(default_gateway).ok_or(PortMappingError::NoGateway)?;
+                Ok(Self::NatPmp(NatPmpClient::with_gateway_ip(gateway)))
+            }
+            NatResolver::Pcp(gateway) => {
+                let gateway =
+                    gateway.or_else(default_gateway).ok_or(PortMappingError::NoGateway)?;
+                let mut client = PcpClient::with_gateway_ip(gateway);
+                if let Some(Self::Pcp(previous)) = previous {
+                    client = client.with_nonces(previous.nonces().clone());
+                }
+                Ok(Self::Pcp(client))
+            }
+            resolver => Err(PortMappingError::Unsupported(resolver)),
+        }
+    }
+
+    async fn map_port(
+        &mut self,
+        request: PortMappingRequest,
+    ) -> Result<PortMapping, PortMappingError> {
+        match self {
+            Self::NatPmp(client) => client.map_port(request).await,
+            Self::Pcp(client) => client.map_port(request).await,
+            Self::Upnp(gateway) => gateway.map_port(request).await,
+        }
+    }
+
+    async fn unmap_port(&mut self, mapping: &PortMapping) -> Result<(), PortMappingError> {
+        match self {
+            Self::NatPmp(client) => {
+                client.unmap_port(mapping.protocol, mapping.internal_port).await
+            }
+            Self::Pcp(client) => client.unmap_port(mapping.protocol, mapping.internal_port).await,
+            Self::Upnp(gateway) => {
+                gateway.unmap_port(mapping.protocol, mapping.external_port).await
+            }
+        }
+    }
+
+    async fn external_ip(&self) -> Result<Option<IpAddr>, PortMappingError> {
+        match self {
+            Self::NatPmp(client) => client.external_ip().await.map(Some),
+            Self::Pcp(client) => Ok(client.external_ip()),
+            Self::Upnp(gateway) => gateway.external_ip().await.map(Some),
+        }
+    }
+}
+
+/// A mapping granted by the gateway and when to renew it.
+#[derive(Debug, Clone, Copy)]
+struct ActiveMapping {
+    mapping: PortMapping,
+    renew_at: Instant,
+}
+
+/// Maintains a set of port mappings on the local gateway.
+///
+/// Each call to [`PortMapper::refresh`] requests all mappings that are not active yet or that
+/// reached half of their lifetime, and resolves the gateway's external IP.
+#[derive(Debug)]
+pub struct PortMapper {
+    /// How to find the gateway.
+    resolver: NatResolver,
+    /// The gateway, discovered on first use.
+    gateway: Option<Gateway>,
+    /// Whether the gateway must be discovered again, because a request failed.
+    rediscover: bool,
+    /// All mappings to maintain.
+    requests: Vec<PortMappingRequest>,
+    /// The active mapping for each request.
+    active: Vec<Option<ActiveMapping>>,
+}
+
+impl PortMapper {
+    /// Creates a new instance that maintains the given mappings.
+    ///
+    /// Returns `None` if the resolver does not support port mapping, see
+    /// [`NatResolver::supports_port_mapping`].
+    pub fn new(
+        resolver: NatResolver,
+        requests: impl IntoIterator<Item = PortMappingRequest>,
+    ) -> Option<Self> {
+        if !resolver.supports_port_mapping() {
+            return None;
+        }
+        let requests = requests.into_iter().collect::<Vec<_>>();
+        let active = vec![None; requests.len()];
+        Some(Self { resolver, gateway: None, rediscover: false, requests, active })
+    }
+
+    /// Uses the NAT-PMP server at the given address instead of discovering the gateway.
+    #[cfg(test)]
+    pub(crate) fn with_natpmp_gateway(mut self, gateway: SocketAddr) -> Self {
+        self.gateway = Some(Gateway::NatPmp(NatPmpClient::new(gateway)));
+        self
+    }
+
+    /// Returns all currently active mappings.
+    pub fn mappings(&self) -> impl Iterator<Item = &PortMapping> + '_ {
+        self.active.iter().flatten().map(|active| &active.mapping)
+    }
+
+    /// Returns the earliest time at which an active mapping must be renewed.
+    pub fn next_renewal(&self) -> Option<Instant> {
+        self.active.iter().flatten().map(|active| active.renew_at).min()
+    }
+
+    /// Requests all mappings that are due and resolves the external IP of the gateway.
+    pub async fn refresh(&mut self) -> NatUpdate {
+        if self.gateway.is_none() || self.rediscover {
+            match Gateway::discover(self.resolver, self.gateway.as_ref()).await {
+                Ok(gateway) => {
+                    debug!(target: "net::nat", ?gateway, "discovered gateway");
+                    self.gateway = Some(gateway);
+                    self.rediscover = false;
+                }
+                Err(err) => {
+                    debug!(target: "net::nat", %err, resolver=%self.resolver, "failed to discover gateway");
+                    self.active.iter_mut().for_each(|active| *active = None);
+                    return NatUpdate::default();
+                }
+            }
+        }
+        let gateway = self.gateway.as_mut().expect("gateway is set");
+
+        let now = Instant::now();
+        let mut failed = false;
+        for (request, active) in self.requests.iter().zip(self.active.iter_mut()) {
+            if active.is_some_and(|active| active.renew_at > now) {
+                continue;
+            }
+            // suggest the previously assigned port so that a renewal keeps the mapping
+            let request = match active {
+                Some(active) => request.with_external_port(active.mapping.external_port),
+                None => *request,
+            };
+            match gateway.map_port(request).await {
+                Ok(mapping) => {
+                    trace!(target: "net::nat", ?mapping, "mapped port");
+                    let renew_at = now + (mapping.lifetime / 2).max(MIN_RENEWAL_INTERVAL);
+                    *active = Some(ActiveMapping { mapping, renew_at });
+                }
+                Err(err) => {
+                    debug!(target: "net::nat", %err, ?request, "failed to map port");
+                    *active = None;
+                    failed = true;
+                }
+            }
+        }
+
+        let external_ip = match gateway.external_ip().await {
+            Ok(ip) => ip,
+            Err(err) => {
+                debug!(target: "net::nat", %err, "failed to resolve external ip via gateway");
+                failed = true;
+                None
+            }
+        };
+
+        if failed {
+            // the gateway may have changed, rediscover it on the next attempt
+            self.rediscover = true;
+        }
+
+        NatUpdate { external_ip, mappings: self.mappings().copied().collect() }
+    }
+
+    /// Deletes all active mappings from the gateway and stops maintaining them.
+    ///
+    /// This should be called on shutdown, gateways otherwise keep the mappings until their lease
+    /// expires.
+    pub async fn unmap_all(&mut self) {
+        self.requests.clear();
+        let active = std::mem::take(&mut self.active);
+        let Some(gateway) = self.gateway.as_mut() else { return };
+        for mapping in active.into_iter().flatten().map(|active| active.mapping) {
+            match gateway.unmap_port(&mapping).await {
+                Ok(()) => trace!(target: "net::nat", ?mapping, "unmapped port"),
+                Err(err) => debug!(target: "net::nat", %err, ?mapping, "failed to unmap port"),
+            }
+        }
+    }
+}
+
+/// Returns the default IPv4 gateway of this host, if it can be determined.
+///
+/// This is only supported on Linux.
+pub fn default_gateway() -> Option<IpAddr> {
+    #[cfg(target_os = "linux")]
+    {
+        let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+        parse_proc_net_route(&routes)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Returns the gateway of the default route in the `/proc/net/route` table.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_net_route(routes: &str) -> Option<IpAddr> {
+    routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace().skip(1);
+        let destination = fields.next()?;
+        let gateway = u32::from_str_radix(fields.next()?, 16).ok()?;
+        // addresses are printed in host byte order
+        (destination == "00000000" && gateway != 0)
+            .then(|| IpAddr::V4(Ipv4Addr::from(gateway.to_ne_bytes())))
+    })
+}
+
+/// Returns the local address that is used to reach the given remote address.
+pub(crate) async fn local_addr_for(remote: SocketAddr) -> Result<SocketAddr, PortMappingError> {
+    let bind: SocketAddr = if remote.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind).await?;
+    socket.connect(remote).await?;
+    Ok(socket.local_addr()?)
+}
+
+/// Sends the request to the gateway and waits for a response that passes `is_response`.
+///
+/// The request is retransmitted up to `attempts` times, doubling the timeout on each attempt.
+/// Returns the length of the response written to `buf`.
+pub(crate) async fn udp_request(
+    gateway: SocketAddr,
+    request: &[u8],
+    buf: &mut [u8],
+    attempts: usize,
+    is_response: impl Fn(&[u8]) -> bool,
+) -> Result<usize, PortMappingError> {
+    let bind: SocketAddr = if gateway.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind).await?;
+    // only accept datagrams from the gateway
+    socket.connect(gateway).await?;
+
+    let mut timeout = INITIAL_REQUEST_TIMEOUT;
+    for _ in 0..attempts {
+        socket.send(request).await?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            match tokio::time::timeout_at(deadline, socket.recv(buf)).await {
+                Ok(Ok(len)) if is_response(&buf[..len]) => return Ok(len),
+                // ignore unrelated datagrams
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => return Err(err.into()),
+                Err(_) => break,
+            }
+        }
+        timeout *= 2;
+    }
+
+    Err(PortMappingError::Timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_default_route() {
+        let routes =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+                      eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+                      eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+        let expected = u32::from_str_radix("0101A8C0", 16).unwrap().to_ne_bytes();
+        assert_eq!(parse_proc_net_route(routes), Some(IpAddr::V4(Ipv4Addr::from(expected))));
+        assert_eq!(parse_proc_net_route("Iface\tDestination\tGateway\n"), None);
+    }
+
+    #[test]
+    fn mapped_addr() {
+        let update = NatUpdate {
+            external_ip: Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))),
+            mappings: vec![PortMapping {
+                protocol: PortMappingProtocol::Udp,
+                internal_port: 30303,
+                external_port: 40404,
+                lifetime: DEFAULT_PORT_MAPPING_LIFETIME,
+            }],
+        };
+        assert_eq!(
+            update.mapped_addr(PortMappingProtocol::Udp, 30303),
+            Some("1.2.3.4:40404".parse().unwrap())
+        );
+        assert_eq!(update.mapped_addr(PortMappingProtocol::Tcp, 30303), None);
+    }
+}
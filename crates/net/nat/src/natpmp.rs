@@ -0,0 +1,230 @@
+//! NAT-PMP client.
+//!
+//! See also <https://datatracker.ietf.org/doc/html/rfc6886>
+
+use crate::mapping::{
+    udp_request, PortMapping, PortMappingError, PortMappingProtocol, PortMappingRequest,
+    DEFAULT_REQUEST_ATTEMPTS,
+};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+/// The port a NAT-PMP (and PCP) server listens on.
+pub const NAT_PMP_PORT: u16 = 5351;
+
+/// NAT-PMP protocol version.
+const VERSION: u8 = 0;
+
+/// Opcode to request the external address of the gateway.
+const OPCODE_EXTERNAL_ADDRESS: u8 = 0;
+
+/// Opcode to request a UDP mapping.
+const OPCODE_MAP_UDP: u8 = 1;
+
+/// Opcode to request a TCP mapping.
+const OPCODE_MAP_TCP: u8 = 2;
+
+/// Set on the opcode of every response.
+const RESPONSE_FLAG: u8 = 128;
+
+/// Result code of a successful request.
+const RESULT_SUCCESS: u16 = 0;
+
+/// A client that talks NAT-PMP to a single gateway.
+#[derive(Debug, Clone)]
+pub struct NatPmpClient {
+    /// Address of the NAT-PMP server.
+    gateway: SocketAddr,
+    /// How often a request is sent before giving up.
+    attempts: usize,
+}
+
+impl NatPmpClient {
+    /// Creates a new client for the NAT-PMP server at the given address.
+    pub const fn new(gateway: SocketAddr) -> Self {
+        Self { gateway, attempts: DEFAULT_REQUEST_ATTEMPTS }
+    }
+
+    /// Creates a new client for the gateway listening on the default [`NAT_PMP_PORT`].
+    pub const fn with_gateway_ip(gateway: IpAddr) -> Self {
+        Self::new(SocketAddr::new(gateway, NAT_PMP_PORT))
+    }
+
+    /// Sets how often a request is sent before giving up.
+    ///
+    /// The timeout doubles with every attempt, starting at 250ms.
+    pub const fn with_attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Returns the address of the NAT-PMP server.
+    pub const fn gateway(&self) -> SocketAddr {
+        self.gateway
+    }
+
+    /// Requests the external IPv4 address of the gateway.
+    pub async fn external_ip(&self) -> Result<IpAddr, PortMappingError> {
+        let request = [VERSION, OPCODE_EXTERNAL_ADDRESS];
+        let mut buf = [0u8; 16];
+        let len = udp_request(self.gateway, &request, &mut buf, self.attempts, |resp| {
+            is_response_to(resp, OPCODE_EXTERNAL_ADDRESS)
+        })
+        .await?;
+        let resp = &buf[..len];
+        if resp.len() < 12 {
+            return Err(PortMappingError::MalformedResponse);
+        }
+        check_result_code(resp)?;
+
+        Ok(IpAddr::V4(Ipv4Addr::new(resp[8], resp[9], resp[10], resp[11])))
+    }
+
+    /// Requests a new mapping, or renews an existing one.
+    ///
+    /// The returned mapping's external port may differ from the suggested one.
+    pub async fn map_port(
+        &self,
+        request: PortMappingRequest,
+    ) -> Result<PortMapping, PortMappingError> {
+        let opcode = match request.protocol {
+            PortMappingProtocol::Udp => OPCODE_MAP_UDP,
+            PortMappingProtocol::Tcp => OPCODE_MAP_TCP,
+        };
+        let lifetime = u32::try_from(request.lifetime.as_secs()).unwrap_or(u32::MAX);
+
+        let mut req = [0u8; 12];
+        req[0] = VERSION;
+        req[1] = opcode;
+        req[4..6].copy_from_slice(&request.internal_port.to_be_bytes());
+        req[6..8].copy_from_slice(&request.external_port.to_be_bytes());
+        req[8..12].copy_from_slice(&lifetime.to_be_bytes());
+
+        let mut buf = [0u8; 16];
+        let len = udp_request(self.gateway, &req, &mut buf, self.attempts, |resp| {
+            is_response_to(resp, opcode) &&
+                resp.len() >= 10 &&
+                u16::from_be_bytes([resp[8], resp[9]]) == request.internal_port
+        })
+        .await?;
+        let resp = &buf[..len];
+        if resp.len() < 16 {
+            return Err(PortMappingError::MalformedResponse);
+        }
+        check_result_code(resp)?;
+
+        let external_port = u16::from_be_bytes([resp[10], resp[11]]);
+        let lifetime = u32::from_be_bytes([resp[12], resp[13], resp[14], resp[15]]);
+
+        Ok(PortMapping {
+            protocol: request.protocol,
+            internal_port: request.internal_port,
+            external_port,
+            lifetime: Duration::from_secs(lifetime as u64),
+        })
+    }
+
+    /// Removes the mapping for the given internal port.
+    pub async fn unmap_port(
+        &self,
+        protocol: PortMappingProtocol,
+        internal_port: u16,
+    ) -> Result<(), PortMappingError> {
+        // a mapping request with a lifetime of zero deletes the mapping
+        let request = PortMappingRequest::new(protocol, internal_port)
+            .with_external_port(0)
+            .with_lifetime(Duration::ZERO);
+        self.map_port(request).await.map(drop)
+    }
+}
+
+/// Returns true if the datagram is a response to a request with the given opcode.
+const fn is_response_to(resp: &[u8], opcode: u8) -> bool {
+    resp.len() >= 4 && resp[0] == VERSION && resp[1] == RESPONSE_FLAG + opcode
+}
+
+const fn check_result_code(resp: &[u8]) -> Result<(), PortMappingError> {
+    let code = u16::from_be_bytes([resp[2], resp[3]]);
+    if code != RESULT_SUCCESS {
+        return Err(PortMappingError::NatPmp(code));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UdpSocket;
+
+    /// Spawns a fake NAT-PMP gateway that maps every port to `internal + 1000` and reports
+    /// `1.2.3.4` as its external address.
+    async fn spawn_fake_gateway() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 64];
+            loop {
+                let (len, from) = socket.recv_from(&mut buf).await.unwrap();
+                let req = &buf[..len];
+                let resp = match req[1] {
+                    OPCODE_EXTERNAL_ADDRESS => {
+                        let mut resp = vec![VERSION, RESPONSE_FLAG, 0, 0, 0, 0, 0, 1];
+                        resp.extend_from_slice(&[1, 2, 3, 4]);
+                        resp
+                    }
+                    opcode => {
+                        let internal = u16::from_be_bytes([req[4], req[5]]);
+                        let mut resp = vec![VERSION, RESPONSE_FLAG + opcode, 0, 0, 0, 0, 0, 1];
+                        resp.extend_from_slice(&internal.to_be_bytes());
+                        resp.extend_from_slice(&(internal + 1000).to_be_bytes());
+                        resp.extend_from_slice(&req[8..12]);
+                        resp
+                    }
+                };
+                socket.send_to(&resp, from).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn natpmp_fake_gateway() {
+        let gateway = spawn_fake_gateway().await;
+        let client = NatPmpClient::new(gateway);
+
+        let ip = client.external_ip().await.unwrap();
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)));
+
+        let mapping = client
+            .map_port(PortMappingRequest::tcp(30303).with_lifetime(Duration::from_secs(60)))
+            .await
+            .unwrap();
+        assert_eq!(
+            mapping,
+            PortMapping {
+                protocol: PortMappingProtocol::Tcp,
+                internal_port: 30303,
+                external_port: 31303,
+                lifetime: Duration::from_secs(60),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn natpmp_rejected() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let gateway = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 64];
+            let (_, from) = socket.recv_from(&mut buf).await.unwrap();
+            // not authorized
+            let resp = [VERSION, RESPONSE_FLAG, 0, 2, 0, 0, 0, 1, 0, 0, 0, 0];
+            socket.send_to(&resp, from).await.unwrap();
+        });
+
+        let err = NatPmpClient::new(gateway).external_ip().await.unwrap_err();
+        assert!(matches!(err, PortMappingError::NatPmp(2)));
+    }
+}
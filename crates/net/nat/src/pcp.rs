@@ -0,0 +1,302 @@
+//! Port Control Protocol (PCP) client.
+//!
+//! See also <https://datatracker.ietf.org/doc/html/rfc6887>
+
+use crate::{
+    mapping::{
+        local_addr_for, udp_request, PortMapping, PortMappingError, PortMappingProtocol,
+        PortMappingRequest, DEFAULT_REQUEST_ATTEMPTS,
+    },
+    natpmp::NAT_PMP_PORT,
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+/// PCP protocol version.
+const VERSION: u8 = 2;
+
+/// Opcode of the `MAP` request.
+const OPCODE_MAP: u8 = 1;
+
+/// Set on the opcode of every response.
+const RESPONSE_FLAG: u8 = 0x80;
+
+/// Result code of a successful request.
+const RESULT_SUCCESS: u8 = 0;
+
+/// Length of the common request and response header.
+const HEADER_LEN: usize = 24;
+
+/// Length of a `MAP` request or response, including the header.
+const MAP_LEN: usize = HEADER_LEN + 36;
+
+/// IANA protocol number of TCP.
+const PROTOCOL_TCP: u8 = 6;
+
+/// IANA protocol number of UDP.
+const PROTOCOL_UDP: u8 = 17;
+
+/// The nonces of PCP mappings, by protocol and internal port.
+pub type PcpNonces = HashMap<(PortMappingProtocol, u16), [u8; 12]>;
+
+/// A client that talks PCP to a single gateway.
+///
+/// Every mapping is identified by its own nonce, which the server uses to authenticate renewals
+/// and deletions. To renew mappings after switching to a new client, e.g. because the gateway was
+/// rediscovered, the nonces must be carried over with [`PcpClient::with_nonces`].
+#[derive(Debug, Clone)]
+pub struct PcpClient {
+    /// Address of the PCP server.
+    gateway: SocketAddr,
+    /// Nonce of every mapping requested by this client.
+    nonces: PcpNonces,
+    /// How often a request is sent before giving up.
+    attempts: usize,
+    /// External address assigned by the server on the most recent mapping.
+    external_ip: Option<IpAddr>,
+}
+
+impl PcpClient {
+    /// Creates a new client for the PCP server at the given address.
+    pub fn new(gateway: SocketAddr) -> Self {
+        Self {
+            gateway,
+            nonces: Default::default(),
+            attempts: DEFAULT_REQUEST_ATTEMPTS,
+            external_ip: None,
+        }
+    }
+
+    /// Creates a new client for the gateway listening on the default PCP port.
+    pub fn with_gateway_ip(gateway: IpAddr) -> Self {
+        Self::new(SocketAddr::new(gateway, NAT_PMP_PORT))
+    }
+
+    /// Sets how often a request is sent before giving up.
+    ///
+    /// The timeout doubles with every attempt, starting at 250ms.
+    pub const fn with_attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Uses the given nonces for the mappings they were created for.
+    pub fn with_nonces(mut self, nonces: PcpNonces) -> Self {
+        self.nonces = nonces;
+        self
+    }
+
+    /// Returns the nonces of all mappings requested by this client.
+    pub const fn nonces(&self) -> &PcpNonces {
+        &self.nonces
+    }
+
+    /// Returns the address of the PCP server.
+    pub const fn gateway(&self) -> SocketAddr {
+        self.gateway
+    }
+
+    /// Returns the external address assigned by the server on the most recent mapping.
+    ///
+    /// PCP has no dedicated request for the external address, so this is `None` until a mapping
+    /// was established.
+    pub const fn external_ip(&self) -> Option<IpAddr> {
+        self.external_ip
+    }
+
+    /// Requests a new mapping, or renews an existing one.
+    ///
+    /// The returned mapping's external port may differ from the suggested one.
+    pub async fn map_port(
+        &mut self,
+        request: PortMappingRequest,
+    ) -> Result<PortMapping, PortMappingError> {
+        let client_ip = local_addr_for(self.gateway).await?.ip();
+        let nonce = *self
+            .nonces
+            .entry((request.protocol, request.internal_port))
+            .or_insert_with(rand::random);
+        let req = encode_map_request(&request, client_ip, &nonce);
+
+        let mut buf = [0u8; 1100];
+        let len = udp_request(self.gateway, &req, &mut buf, self.attempts, |resp| {
+            resp.len() >= HEADER_LEN &&
+                resp[0] == VERSION &&
+                resp[1] == RESPONSE_FLAG | OPCODE_MAP &&
+                // error responses may not echo the payload
+                (resp[3] != RESULT_SUCCESS || (resp.len() >= MAP_LEN && resp[24..36] == nonce))
+        })
+        .await?;
+        let resp = &buf[..len];
+
+        let result = resp[3];
+        if result != RESULT_SUCCESS {
+            return Err(PortMappingError::Pcp(result));
+        }
+
+        let lifetime = u32::from_be_bytes([resp[4], resp[5], resp[6], resp[7]]);
+        let external_port = u16::from_be_bytes([resp[42], resp[43]]);
+        let mut external_ip = [0u8; 16];
+        external_ip.copy_from_slice(&resp[44..60]);
+        let external_ip = Ipv6Addr::from(external_ip);
+        self.external_ip =
+            Some(external_ip.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(IpAddr::V6(external_ip)));
+
+        Ok(PortMapping {
+            protocol: request.protocol,
+            internal_port: request.internal_port,
+            external_port,
+            lifetime: Duration::from_secs(lifetime as u64),
+        })
+    }
+
+    /// Removes the mapping for the given internal port.
+    pub async fn unmap_port(
+        &mut self,
+        protocol: PortMappingProtocol,
+        internal_port: u16,
+    ) -> Result<(), PortMappingError> {
+        // a mapping request with a lifetime of zero deletes the mapping
+        let request = PortMappingRequest::new(protocol, internal_port)
+            .with_external_port(0)
+            .with_lifetime(Duration::ZERO);
+        self.map_port(request).await?;
+        self.nonces.remove(&(protocol, internal_port));
+        Ok(())
+    }
+}
+
+/// Encodes a `MAP` request.
+fn encode_map_request(
+    request: &PortMappingRequest,
+    client_ip: IpAddr,
+    nonce: &[u8; 12],
+) -> [u8; MAP_LEN] {
+    let lifetime = u32::try_from(request.lifetime.as_secs()).unwrap_or(u32::MAX);
+    let protocol = match request.protocol {
+        PortMappingProtocol::Tcp => PROTOCOL_TCP,
+        PortMappingProtocol::Udp => PROTOCOL_UDP,
+    };
+
+    let mut req = [0u8; MAP_LEN];
+    // header
+    req[0] = VERSION;
+    req[1] = OPCODE_MAP;
+    req[4..8].copy_from_slice(&lifetime.to_be_bytes());
+    req[8..24].copy_from_slice(&to_ipv6(client_ip).octets());
+    // map payload
+    req[24..36].copy_from_slice(nonce);
+    req[36] = protocol;
+    req[40..42].copy_from_slice(&request.internal_port.to_be_bytes());
+    req[42..44].copy_from_slice(&request.external_port.to_be_bytes());
+    // no preference for the external address, this is `::ffff:0.0.0.0` for IPv4 clients
+    let suggested_ip = if client_ip.is_ipv4() {
+        Ipv4Addr::UNSPECIFIED.to_ipv6_mapped()
+    } else {
+        Ipv6Addr::UNSPECIFIED
+    };
+    req[44..60].copy_from_slice(&suggested_ip.octets());
+    req
+}
+
+/// Addresses are always encoded as IPv6, with IPv4 addresses mapped.
+const fn to_ipv6(ip: IpAddr) -> Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UdpSocket;
+
+    /// Spawns a fake PCP gateway that maps every port to `internal + 1000` on `1.2.3.4`.
+    async fn spawn_fake_gateway() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1100];
+            loop {
+                let (len, from) = socket.recv_from(&mut buf).await.unwrap();
+                let req = &buf[..len];
+                assert_eq!(len, MAP_LEN);
+
+                let internal = u16::from_be_bytes([req[40], req[41]]);
+                let mut resp = [0u8; MAP_LEN];
+                resp[0] = VERSION;
+                resp[1] = RESPONSE_FLAG | OPCODE_MAP;
+                resp[4..8].copy_from_slice(&req[4..8]);
+                resp[24..44].copy_from_slice(&req[24..44]);
+                resp[42..44].copy_from_slice(&(internal + 1000).to_be_bytes());
+                resp[44..60].copy_from_slice(&Ipv4Addr::new(1, 2, 3, 4).to_ipv6_mapped().octets());
+                socket.send_to(&resp, from).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn pcp_fake_gateway() {
+        let gateway = spawn_fake_gateway().await;
+        let mut client = PcpClient::new(gateway);
+        assert_eq!(client.external_ip(), None);
+
+        let mapping = client
+            .map_port(PortMappingRequest::udp(30303).with_lifetime(Duration::from_secs(120)))
+            .await
+            .unwrap();
+        assert_eq!(
+            mapping,
+            PortMapping {
+                protocol: PortMappingProtocol::Udp,
+                internal_port: 30303,
+                external_port: 31303,
+                lifetime: Duration::from_secs(120),
+            }
+        );
+        assert_eq!(client.external_ip(), Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))));
+    }
+
+    #[tokio::test]
+    async fn pcp_nonce_per_mapping() {
+        let gateway = spawn_fake_gateway().await;
+        let mut client = PcpClient::new(gateway);
+
+        client.map_port(PortMappingRequest::udp(30303)).await.unwrap();
+        client.map_port(PortMappingRequest::tcp(30303)).await.unwrap();
+        let udp = client.nonces()[&(PortMappingProtocol::Udp, 30303)];
+        assert_ne!(udp, client.nonces()[&(PortMappingProtocol::Tcp, 30303)]);
+
+        // a new client renews the mapping with the same nonce
+        let mut client = PcpClient::new(gateway).with_nonces(client.nonces().clone());
+        client.map_port(PortMappingRequest::udp(30303)).await.unwrap();
+        assert_eq!(client.nonces()[&(PortMappingProtocol::Udp, 30303)], udp);
+
+        client.unmap_port(PortMappingProtocol::Udp, 30303).await.unwrap();
+        assert!(!client.nonces().contains_key(&(PortMappingProtocol::Udp, 30303)));
+    }
+
+    #[test]
+    fn encode_map() {
+        let nonce = [7u8; 12];
+        let req = encode_map_request(
+            &PortMappingRequest::tcp(30303).with_lifetime(Duration::from_secs(7200)),
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)),
+            &nonce,
+        );
+        assert_eq!(req[0], VERSION);
+        assert_eq!(req[1], OPCODE_MAP);
+        assert_eq!(u32::from_be_bytes([req[4], req[5], req[6], req[7]]), 7200);
+        assert_eq!(req[8..24], Ipv4Addr::new(192, 168, 1, 2).to_ipv6_mapped().octets());
+        assert_eq!(req[24..36], nonce);
+        assert_eq!(req[36], PROTOCOL_TCP);
+        assert_eq!(u16::from_be_bytes([req[40], req[41]]), 30303);
+        assert_eq!(u16::from_be_bytes([req[42], req[43]]), 30303);
+        assert_eq!(req[44..60], Ipv4Addr::UNSPECIFIED.to_ipv6_mapped().octets());
+    }
+}
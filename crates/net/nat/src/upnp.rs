@@ -0,0 +1,321 @@
+//! Minimal `UPnP` Internet Gateway Device (IGD) client.
+//!
+//! Discovers the gateway via SSDP and talks SOAP to its `WANIPConnection` (or
+//! `WANPPPConnection`) service.
+//!
+//! See also <https://upnp.org/specs/gw/UPnP-gw-WANIPConnection-v2-Service.pdf>
+
+use crate::mapping::{
+    local_addr_for, PortMapping, PortMappingError, PortMappingProtocol, PortMappingRequest,
+};
+use reqwest::Url;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::net::UdpSocket;
+
+/// Multicast address for SSDP discovery.
+const SSDP_MULTICAST_ADDR: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(239, 255, 255, 250)), 1900);
+
+/// The device type we search for.
+const IGD_SEARCH_TARGET: &str = "urn:schemas-upnp-org:device:InternetGatewayDevice:1";
+
+/// Services that support port mapping, in order of preference.
+const WAN_CONNECTION_SERVICES: &[&str] = &[
+    "urn:schemas-upnp-org:service:WANIPConnection:2",
+    "urn:schemas-upnp-org:service:WANIPConnection:1",
+    "urn:schemas-upnp-org:service:WANPPPConnection:1",
+];
+
+/// How long to wait for the gateway to answer the SSDP search.
+const SSDP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Timeout of HTTP requests to the gateway.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Description of mappings created by reth.
+const MAPPING_DESCRIPTION: &str = "reth";
+
+/// A discovered `UPnP` gateway.
+#[derive(Debug, Clone)]
+pub struct UpnpGateway {
+    /// The control URL of the WAN connection service.
+    control_url: Url,
+    /// The service type of the WAN connection service.
+    service_type: String,
+    /// The local address that the gateway routes to us.
+    local_ip: IpAddr,
+    /// HTTP client used for SOAP requests.
+    client: reqwest::Client,
+}
+
+impl UpnpGateway {
+    /// Searches the local network for an internet gateway device.
+    pub async fn discover() -> Result<Self, PortMappingError> {
+        let location = ssdp_search().await?;
+        Self::from_location(location).await
+    }
+
+    /// Creates a gateway from the URL of its device description.
+    pub async fn from_location(location: Url) -> Result<Self, PortMappingError> {
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .map_err(PortMappingError::Http)?;
+        let description = client.get(location.clone()).send().await?.text().await?;
+        let (service_type, control_url) = find_wan_connection_service(&description)
+            .ok_or_else(|| PortMappingError::Upnp("no WAN connection service".to_string()))?;
+        let control_url = location
+            .join(control_url)
+            .map_err(|err| PortMappingError::Upnp(format!("invalid control URL: {err}")))?;
+
+        let gateway = control_url
+            .socket_addrs(|| Some(80))
+            .ok()
+            .and_then(|addrs| addrs.into_iter().next())
+            .ok_or_else(|| PortMappingError::Upnp("unresolvable control URL".to_string()))?;
+        let local_ip = local_addr_for(gateway).await?.ip();
+
+        Ok(Self { control_url, service_type: service_type.to_string(), local_ip, client })
+    }
+
+    /// Returns the control URL of the WAN connection service.
+    pub const fn control_url(&self) -> &Url {
+        &self.control_url
+    }
+
+    /// Requests the external IP address of the gateway.
+    pub async fn external_ip(&self) -> Result<IpAddr, PortMappingError> {
+        let resp = self.soap_request("GetExternalIPAddress", "").await?;
+        xml_text(&resp, "NewExternalIPAddress")
+            .and_then(|ip| ip.trim().parse().ok())
+            .ok_or(PortMappingError::MalformedResponse)
+    }
+
+    /// Requests a new mapping, or renews an existing one.
+    ///
+    /// Gateways don't assign a different external port, so the request's suggested external port
+    /// is used, which defaults to the internal port.
+    pub async fn map_port(
+        &self,
+        request: PortMappingRequest,
+    ) -> Result<PortMapping, PortMappingError> {
+        let external_port =
+            if request.external_port == 0 { request.internal_port } else { request.external_port };
+        let lifetime = u32::try_from(request.lifetime.as_secs()).unwrap_or(u32::MAX);
+        let args = format!(
+            "<NewRemoteHost></NewRemoteHost>\
+             <NewExternalPort>{external_port}</NewExternalPort>\
+             <NewProtocol>{}</NewProtocol>\
+             <NewInternalPort>{}</NewInternalPort>\
+             <NewInternalClient>{}</NewInternalClient>\
+             <NewEnabled>1</NewEnabled>\
+             <NewPortMappingDescription>{MAPPING_DESCRIPTION}</NewPortMappingDescription>\
+             <NewLeaseDuration>{lifetime}</NewLeaseDuration>",
+            request.protocol, request.internal_port, self.local_ip,
+        );
+        self.soap_request("AddPortMapping", &args).await?;
+
+        Ok(PortMapping {
+            protocol: request.protocol,
+            internal_port: request.internal_port,
+            external_port,
+            lifetime: request.lifetime,
+        })
+    }
+
+    /// Removes the mapping of the given external port.
+    pub async fn unmap_port(
+        &self,
+        protocol: PortMappingProtocol,
+        external_port: u16,
+    ) -> Result<(), PortMappingError> {
+        let args = format!(
+            "<NewRemoteHost></NewRemoteHost>\
+             <NewExternalPort>{external_port}</NewExternalPort>\
+             <NewProtocol>{protocol}</NewProtocol>",
+        );
+        self.soap_request("DeletePortMapping", &args).await.map(drop)
+    }
+
+    /// Sends a SOAP request for the given action and returns the response body.
+    async fn soap_request(&self, action: &str, args: &str) -> Result<String, PortMappingError> {
+        let body = format!(
+            "<?xml version=\"1.0\"?>\
+             <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+             s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+             <s:Body><u:{action} xmlns:u=\"{}\">{args}</u:{action}></s:Body></s:Envelope>",
+            self.service_type
+        );
+        let resp = self
+            .client
+            .post(self.control_url.clone())
+            .header("Content-Type", "text/xml; charset=\"utf-8\"")
+            .header("SOAPAction", format!("\"{}#{action}\"", self.service_type))
+            .body(body)
+            .send()
+            .await?;
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            let reason = xml_text(&text, "errorDescription")
+                .or_else(|| xml_text(&text, "errorCode"))
+                .unwrap_or_else(|| status.as_str());
+            return Err(PortMappingError::Upnp(format!("{action} failed: {reason}")));
+        }
+        Ok(text)
+    }
+}
+
+/// Sends an SSDP search and returns the location of the first gateway that responds.
+async fn ssdp_search() -> Result<Url, PortMappingError> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    let search = format!(
+        "M-SEARCH * HTTP/1.1\r\n\
+         HOST: {SSDP_MULTICAST_ADDR}\r\n\
+         ST: {IGD_SEARCH_TARGET}\r\n\
+         MAN: \"ssdp:discover\"\r\n\
+         MX: 2\r\n\r\n"
+    );
+    socket.send_to(search.as_bytes(), SSDP_MULTICAST_ADDR).await?;
+
+    let mut buf = [0u8; 2048];
+    tokio::time::timeout(SSDP_TIMEOUT, async {
+        loop {
+            let (len, _) = socket.recv_from(&mut buf).await?;
+            let resp = String::from_utf8_lossy(&buf[..len]);
+            if let Some(location) = ssdp_location(&resp) {
+                return Ok(location);
+            }
+        }
+    })
+    .await
+    .map_err(|_| PortMappingError::NoGateway)?
+}
+
+/// Extracts the `LOCATION` header of an SSDP response.
+fn ssdp_location(resp: &str) -> Option<Url> {
+    resp.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case("location").then(|| value.trim().parse().ok())?
+    })
+}
+
+/// Returns the service type and control URL of the preferred WAN connection service in the
+/// device description.
+fn find_wan_connection_service(description: &str) -> Option<(&str, &str)> {
+    let services = description
+        .split("<service>")
+        .skip(1)
+        .filter_map(|service| {
+            let service_type = xml_text(service, "serviceType")?.trim();
+            let control_url = xml_text(service, "controlURL")?.trim();
+            Some((service_type, control_url))
+        })
+        .collect::<Vec<_>>();
+
+    WAN_CONNECTION_SERVICES
+        .iter()
+        .find_map(|wanted| services.iter().find(|(service_type, _)| service_type == wanted))
+        .copied()
+}
+
+/// Returns the text of the first element with the given (unprefixed) name.
+fn xml_text<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{name}>");
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&format!("</{name}>"))?;
+    Some(&xml[start..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    const DESCRIPTION: &str = r#"<?xml version="1.0"?>
+<root xmlns="urn:schemas-upnp-org:device-1-0">
+  <device>
+    <serviceList>
+      <service>
+        <serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType>
+        <controlURL>/ctl/L3F</controlURL>
+      </service>
+      <service>
+        <serviceType>urn:schemas-upnp-org:service:WANIPConnection:1</serviceType>
+        <controlURL>/ctl/IPConn</controlURL>
+      </service>
+    </serviceList>
+  </device>
+</root>"#;
+
+    /// Spawns a fake gateway serving the device description and answering SOAP requests.
+    async fn spawn_fake_gateway() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let len = stream.read(&mut buf).await.unwrap();
+                let req = String::from_utf8_lossy(&buf[..len]).to_string();
+                let body = if req.starts_with("GET /desc.xml") {
+                    DESCRIPTION.to_string()
+                } else if req.contains("#GetExternalIPAddress") {
+                    "<s:Envelope><s:Body><u:GetExternalIPAddressResponse>\
+                     <NewExternalIPAddress>1.2.3.4</NewExternalIPAddress>\
+                     </u:GetExternalIPAddressResponse></s:Body></s:Envelope>"
+                        .to_string()
+                } else if req.contains("#DeletePortMapping") {
+                    assert!(req.contains("<NewExternalPort>30303</NewExternalPort>"));
+                    "<s:Envelope><s:Body><u:DeletePortMappingResponse/></s:Body></s:Envelope>"
+                        .to_string()
+                } else {
+                    assert!(req.contains("#AddPortMapping"));
+                    assert!(req.contains("<NewInternalPort>30303</NewInternalPort>"));
+                    "<s:Envelope><s:Body><u:AddPortMappingResponse/></s:Body></s:Envelope>"
+                        .to_string()
+                };
+                let resp = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(resp.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{addr}/desc.xml").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn upnp_fake_gateway() {
+        let location = spawn_fake_gateway().await;
+        let gateway = UpnpGateway::from_location(location.clone()).await.unwrap();
+        assert_eq!(gateway.control_url(), &location.join("/ctl/IPConn").unwrap());
+
+        let ip = gateway.external_ip().await.unwrap();
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)));
+
+        let mapping = gateway.map_port(PortMappingRequest::udp(30303)).await.unwrap();
+        assert_eq!(mapping.protocol, PortMappingProtocol::Udp);
+        assert_eq!(mapping.external_port, 30303);
+
+        gateway.unmap_port(mapping.protocol, mapping.external_port).await.unwrap();
+    }
+
+    #[test]
+    fn parse_ssdp_location() {
+        let resp = "HTTP/1.1 200 OK\r\n\
+                    CACHE-CONTROL: max-age=120\r\n\
+                    ST: urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\n\
+                    Location: http://192.168.1.1:5000/rootDesc.xml\r\n\r\n";
+        assert_eq!(
+            ssdp_location(resp),
+            Some("http://192.168.1.1:5000/rootDesc.xml".parse().unwrap())
+        );
+    }
+}
@@ -3,8 +3,10 @@
 use std::{collections::HashSet, net::SocketAddr, sync::Arc};
 
 use reth_chainspec::{ChainSpec, MAINNET};
-use reth_discv4::{Discv4Config, Discv4ConfigBuilder, NatResolver, DEFAULT_DISCOVERY_ADDRESS};
-use reth_discv5::NetworkStackId;
+use reth_discv4::{
+    Discv4Config, Discv4ConfigBuilder, NatResolver, PortMappingRequest, DEFAULT_DISCOVERY_ADDRESS,
+};
+use reth_discv5::{EnrFilter, NetworkStackId, Topic};
 use reth_dns_discovery::DnsDiscoveryConfig;
 use reth_eth_wire::{HelloMessage, HelloMessageWithProtocols, Status};
 use reth_network_peers::{mainnet_nodes, pk2id, sepolia_nodes, PeerId, TrustedPeer};
@@ -50,6 +52,9 @@ pub struct NetworkConfig<C> {
     pub discovery_v4_config: Option<Discv4Config>,
     /// How to set up discovery version 5.
     pub discovery_v5_config: Option<reth_discv5::Config>,
+    /// How to resolve the external IP and which ports to map on the NAT gateway for discovery
+    /// version 5, if discovery version 4, which does this otherwise, is disabled.
+    pub discovery_v5_nat: Option<(NatResolver, Vec<PortMappingRequest>)>,
     /// Address to listen for incoming connections
     pub listener_addr: SocketAddr,
     /// How to instantiate peer manager.
@@ -160,6 +165,10 @@ pub struct NetworkConfigBuilder {
     discovery_v4_builder: Option<Discv4ConfigBuilder>,
     /// How to set up discovery version 5.
     discovery_v5_builder: Option<reth_discv5::ConfigBuilder>,
+    /// Filter rules on the node records of peers discovered via discovery version 5.
+    discovery_v5_enr_filter: Option<EnrFilter>,
+    /// Topics to advertise and look up via discovery version 5.
+    discovery_v5_topics: Vec<Topic>,
     /// All boot nodes to start network discovery with.
     boot_nodes: HashSet<TrustedPeer>,
     /// Address to use for discovery
@@ -184,6 +193,10 @@ pub struct NetworkConfigBuilder {
     head: Option<Head>,
     /// Whether tx gossip is disabled
     tx_gossip_disabled: bool,
+    /// How to resolve the external IP, if discovery version 4 is disabled.
+    external_ip_resolver: NatResolver,
+    /// Whether to map the listener and discovery ports on the NAT gateway.
+    nat_port_mapping: bool,
     /// The block importer type
     block_import: Option<Box<dyn BlockImport>>,
     /// How to instantiate transactions manager.
@@ -206,6 +219,8 @@ impl NetworkConfigBuilder {
             dns_discovery_config: Some(Default::default()),
             discovery_v4_builder: Some(Default::default()),
             discovery_v5_builder: None,
+            discovery_v5_enr_filter: None,
+            discovery_v5_topics: Vec::new(),
             boot_nodes: Default::default(),
             discovery_addr: None,
             listener_addr: None,
@@ -218,6 +233,8 @@ impl NetworkConfigBuilder {
             extra_protocols: Default::default(),
             head: None,
             tx_gossip_disabled: false,
+            external_ip_resolver: Default::default(),
+            nat_port_mapping: false,
             block_import: None,
             transactions_manager_config: Default::default(),
         }
@@ -352,12 +369,24 @@ impl NetworkConfigBuilder {
     /// This is a convenience function for setting the external ip resolver on the default
     /// [`Discv4Config`] config.
     pub fn external_ip_resolver(mut self, resolver: NatResolver) -> Self {
+        self.external_ip_resolver = resolver;
         self.discovery_v4_builder
             .get_or_insert_with(Discv4Config::builder)
             .external_ip_resolver(Some(resolver));
         self
     }
 
+    /// Sets whether the `RLPx` listener port and the discovery ports should be mapped on the NAT
+    /// gateway.
+    ///
+    /// This requires an external ip resolver that supports port mapping, see
+    /// [`NatResolver::supports_port_mapping`]. Mapped ports are announced in the local node
+    /// records of discv4 and discv5.
+    pub const fn nat_port_mapping(mut self, enabled: bool) -> Self {
+        self.nat_port_mapping = enabled;
+        self
+    }
+
     /// Sets the discv4 config to use.
     pub fn discovery(mut self, builder: Discv4ConfigBuilder) -> Self {
         self.discovery_v4_builder = Some(builder);
@@ -370,6 +399,24 @@ impl NetworkConfigBuilder {
         self
     }
 
+    /// Sets filter rules on the node records of peers discovered via discv5. Only peers that match
+    /// the filter are considered for outbound connections.
+    ///
+    /// Has no effect unless discv5 is configured, see [`Self::discovery_v5`].
+    pub fn discovery_v5_enr_filter(mut self, filter: EnrFilter) -> Self {
+        self.discovery_v5_enr_filter = Some(filter);
+        self
+    }
+
+    /// Sets the topics to advertise in the discv5 node record and to look up. Only discovered peers
+    /// that advertise any of the topics are considered for outbound connections.
+    ///
+    /// Has no effect unless discv5 is configured, see [`Self::discovery_v5`].
+    pub fn discovery_v5_topics(mut self, topics: impl IntoIterator<Item = Topic>) -> Self {
+        self.discovery_v5_topics = topics.into_iter().collect();
+        self
+    }
+
     /// Sets the dns discovery config to use.
     pub fn dns_discovery(mut self, config: DnsDiscoveryConfig) -> Self {
         self.dns_discovery_config = Some(config);
@@ -480,6 +527,8 @@ impl NetworkConfigBuilder {
             mut dns_discovery_config,
             discovery_v4_builder,
             mut discovery_v5_builder,
+            discovery_v5_enr_filter,
+            discovery_v5_topics,
             boot_nodes,
             discovery_addr,
             listener_addr,
@@ -492,6 +541,8 @@ impl NetworkConfigBuilder {
             extra_protocols,
             head,
             tx_gossip_disabled,
+            external_ip_resolver,
+            nat_port_mapping,
             block_import,
             transactions_manager_config,
         } = self;
@@ -501,11 +552,47 @@ impl NetworkConfigBuilder {
                 let fork_id = chain_spec.latest_fork_id();
                 builder = builder.fork(network_stack_id, fork_id)
             }
+            if let Some(filter) = discovery_v5_enr_filter {
+                builder = builder.enr_filter(filter)
+            }
+            if !discovery_v5_topics.is_empty() {
+                builder = builder
+                    .advertise_topics(discovery_v5_topics.clone())
+                    .lookup_topics(discovery_v5_topics)
+            }
 
             builder
         });
 
         let listener_addr = listener_addr.unwrap_or(DEFAULT_DISCOVERY_ADDRESS);
+        let discovery_v4_addr = discovery_addr.unwrap_or(DEFAULT_DISCOVERY_ADDRESS);
+        let discovery_v5_config = discovery_v5_builder.map(|builder| builder.build());
+
+        let discovery_v4_config = discovery_v4_builder.map(|mut builder| {
+            if nat_port_mapping {
+                builder
+                    .add_nat_port_mapping(PortMappingRequest::tcp(listener_addr.port()))
+                    .add_nat_port_mapping(PortMappingRequest::udp(discovery_v4_addr.port()));
+                if let Some(config) = &discovery_v5_config {
+                    builder.add_nat_port_mapping(PortMappingRequest::udp(
+                        config.discovery_socket().port(),
+                    ));
+                }
+            }
+            builder.build()
+        });
+
+        // without discv4, the ports are mapped on behalf of discv5
+        let discovery_v5_nat = discovery_v5_config
+            .as_ref()
+            .filter(|_| nat_port_mapping && discovery_v4_config.is_none())
+            .map(|config| {
+                let mappings = vec![
+                    PortMappingRequest::tcp(listener_addr.port()),
+                    PortMappingRequest::udp(config.discovery_socket().port()),
+                ];
+                (external_ip_resolver, mappings)
+            });
 
         let mut hello_message =
             hello_message.unwrap_or_else(|| HelloMessage::builder(peer_id).build());
@@ -541,9 +628,10 @@ impl NetworkConfigBuilder {
             secret_key,
             boot_nodes,
             dns_discovery_config,
-            discovery_v4_config: discovery_v4_builder.map(|builder| builder.build()),
-            discovery_v5_config: discovery_v5_builder.map(|builder| builder.build()),
-            discovery_v4_addr: discovery_addr.unwrap_or(DEFAULT_DISCOVERY_ADDRESS),
+            discovery_v4_config,
+            discovery_v5_config,
+            discovery_v5_nat,
+            discovery_v4_addr,
             listener_addr,
             peers_config: peers_config.unwrap_or_default(),
             sessions_config: sessions_config.unwrap_or_default(),
@@ -637,4 +725,38 @@ mod tests {
         assert_eq!(status.forkid.hash, genesis_fork_hash);
         assert_eq!(fork_filter.current().hash, genesis_fork_hash);
     }
+
+    #[test]
+    fn test_network_nat_port_mappings() {
+        let config = builder()
+            .external_ip_resolver(NatResolver::NatPmp(None))
+            .nat_port_mapping(true)
+            .listener_port(30304)
+            .discovery_port(30305)
+            .build(NoopProvider::default());
+
+        let mappings = config.discovery_v4_config.unwrap().nat_port_mappings;
+        assert_eq!(mappings, vec![PortMappingRequest::tcp(30304), PortMappingRequest::udp(30305)]);
+    }
+
+    #[test]
+    fn test_network_nat_port_mappings_discv5() {
+        let config = builder()
+            .external_ip_resolver(NatResolver::NatPmp(None))
+            .nat_port_mapping(true)
+            .listener_port(30304)
+            .disable_discv4_discovery()
+            .discovery_v5(reth_discv5::Config::builder("127.0.0.1:30304".parse().unwrap()))
+            .build(NoopProvider::default());
+
+        let (resolver, mappings) = config.discovery_v5_nat.unwrap();
+        assert_eq!(resolver, NatResolver::NatPmp(None));
+        assert_eq!(
+            mappings,
+            vec![
+                PortMappingRequest::tcp(30304),
+                PortMappingRequest::udp(reth_discv5::DEFAULT_DISCOVERY_V5_PORT)
+            ]
+        );
+    }
 }
@@ -6,11 +6,15 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{ready, Context, Poll},
+    time::Duration,
 };
 
 use enr::Enr;
 use futures::StreamExt;
-use reth_discv4::{DiscoveryUpdate, Discv4, Discv4Config};
+use reth_discv4::{
+    DiscoveryUpdate, Discv4, Discv4Config, NatResolver, NatUpdate, PortMappingProtocol,
+    PortMappingRequest, ResolveNatInterval,
+};
 use reth_discv5::{DiscoveredPeer, Discv5};
 use reth_dns_discovery::{
     DnsDiscoveryConfig, DnsDiscoveryHandle, DnsDiscoveryService, DnsNodeRecordUpdate, DnsResolver,
@@ -20,7 +24,10 @@ use reth_network_peers::{NodeRecord, PeerId};
 use reth_network_types::PeerAddr;
 use reth_primitives::{EnrForkIdEntry, ForkId};
 use secp256k1::SecretKey;
-use tokio::{sync::mpsc, task::JoinHandle};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
 use tokio_stream::{wrappers::ReceiverStream, Stream};
 use tracing::trace;
 
@@ -34,6 +41,10 @@ use crate::{
 /// Default is 10 000 peers.
 pub const DEFAULT_MAX_CAPACITY_DISCOVERED_PEERS_CACHE: u32 = 10_000;
 
+/// How often the external IP is resolved for discv5 if discv4 is disabled, same as the discv4
+/// default.
+const DISCV5_RESOLVE_EXTERNAL_IP_INTERVAL: Duration = Duration::from_secs(60 * 5);
+
 /// An abstraction over the configured discovery protocol.
 ///
 /// Listens for new discovered nodes and emits events for discovered nodes and their
@@ -54,8 +65,12 @@ pub struct Discovery {
     _discv4_service: Option<JoinHandle<()>>,
     /// Handler to interact with the Discovery v5 service
     discv5: Option<Discv5>,
+    /// Local address of the discv5 UDP socket.
+    discv5_addr: Option<SocketAddr>,
     /// All KAD table updates from the discv5 service.
     discv5_updates: Option<ReceiverStream<discv5::Event>>,
+    /// Resolves the external IP and maintains port mappings for discv5, if discv4 is disabled.
+    discv5_nat: Option<Discv5Nat>,
     /// Handler to interact with the DNS discovery service
     _dns_discovery: Option<DnsDiscoveryHandle>,
     /// Updates from the DNS discovery service.
@@ -79,6 +94,7 @@ impl Discovery {
         sk: SecretKey,
         discv4_config: Option<Discv4Config>,
         discv5_config: Option<reth_discv5::Config>, // contains discv5 listen address
+        discv5_nat: Option<(NatResolver, Vec<PortMappingRequest>)>,
         dns_discovery_config: Option<DnsDiscoveryConfig>,
     ) -> Result<Self, NetworkError> {
         // setup discv4 with the discovery address and tcp port
@@ -100,6 +116,7 @@ impl Discovery {
             Ok((Some(discv4), Some(discv4_updates), Some(discv4_service)))
         };
 
+        let discv5_addr = discv5_config.as_ref().map(|config| config.discovery_socket());
         let discv5_future = async {
             let Some(config) = discv5_config else { return Ok::<_, NetworkError>((None, None)) };
             let (discv5, discv5_updates, _local_enr_discv5) = Discv5::start(&sk, config).await?;
@@ -109,6 +126,10 @@ impl Discovery {
         let ((discv4, discv4_updates, _discv4_service), (discv5, discv5_updates)) =
             tokio::try_join!(discv4_future, discv5_future)?;
 
+        let discv5_nat = discv5_nat
+            .filter(|_| discv5.is_some())
+            .map(|(resolver, mappings)| Discv5Nat::spawn(resolver, mappings));
+
         // setup DNS discovery
         let (_dns_discovery, dns_discovery_updates, _dns_disc_service) =
             if let Some(dns_config) = dns_discovery_config {
@@ -130,7 +151,9 @@ impl Discovery {
             discv4_updates,
             _discv4_service,
             discv5,
+            discv5_addr,
             discv5_updates,
+            discv5_nat,
             discovered_nodes: LruMap::new(DEFAULT_MAX_CAPACITY_DISCOVERED_PEERS_CACHE),
             queued_events: Default::default(),
             _dns_disc_service,
@@ -221,6 +244,38 @@ impl Discovery {
             })
     }
 
+    /// Deletes all port mappings on the NAT gateway.
+    ///
+    /// This is intended to be called on shutdown.
+    pub(crate) async fn unmap_nat_ports(&self) {
+        if let Some(discv4) = &self.discv4 {
+            if let Err(err) = discv4.unmap_nat_ports().await {
+                trace!(target: "net::discovery", %err, "failed to unmap discv4 nat ports");
+            }
+        }
+        if let Some(nat) = &self.discv5_nat {
+            nat.unmap_ports().await
+        }
+    }
+
+    /// Announces the external address and ports mapped on the NAT gateway in the discv5 ENR.
+    ///
+    /// NAT traversal is driven by the discv4 service, which already updated its own ENR, or by
+    /// this type if discv4 is disabled.
+    fn on_nat_update(&self, update: &NatUpdate) {
+        let Some(discv5) = &self.discv5 else { return };
+        if let Some(socket) = self
+            .discv5_addr
+            .and_then(|addr| update.mapped_addr(PortMappingProtocol::Udp, addr.port()))
+        {
+            discv5.update_local_enr_socket(socket, false);
+        }
+        if let Some(socket) = update.mapped_addr(PortMappingProtocol::Tcp, self.local_enr.tcp_port)
+        {
+            discv5.update_local_enr_socket(socket, true);
+        }
+    }
+
     fn on_discv4_update(&mut self, update: DiscoveryUpdate) {
         match update {
             DiscoveryUpdate::Added(record) | DiscoveryUpdate::DiscoveredAtCapacity(record) => {
@@ -232,6 +287,7 @@ impl Discovery {
             DiscoveryUpdate::Removed(peer_id) => {
                 self.discovered_nodes.remove(&peer_id);
             }
+            DiscoveryUpdate::ExternalAddr(update) => self.on_nat_update(&update),
             DiscoveryUpdate::Batch(updates) => {
                 for update in updates {
                     self.on_discv4_update(update);
@@ -255,6 +311,13 @@ impl Discovery {
                 self.on_discv4_update(update)
             }
 
+            // apply the external address resolved on behalf of discv5
+            while let Some(Poll::Ready(Some(update))) =
+                self.discv5_nat.as_mut().map(|nat| nat.updates.poll_next_unpin(cx))
+            {
+                self.on_nat_update(&update);
+            }
+
             // drain the discv5 update stream
             while let Some(Poll::Ready(Some(update))) =
                 self.discv5_updates.as_mut().map(|updates| updates.poll_next_unpin(cx))
@@ -317,7 +380,9 @@ impl Discovery {
             discv4: Default::default(),
             discv4_updates: Default::default(),
             discv5: None,
+            discv5_addr: None,
             discv5_updates: None,
+            discv5_nat: None,
             queued_events: Default::default(),
             _discv4_service: Default::default(),
             _dns_discovery: None,
@@ -328,6 +393,53 @@ impl Discovery {
     }
 }
 
+/// Handle to the task that resolves the external IP and maintains the port mappings on the NAT
+/// gateway on behalf of discv5, if discv4 is disabled.
+#[derive(Debug)]
+struct Discv5Nat {
+    /// Every resolved update.
+    updates: ReceiverStream<NatUpdate>,
+    /// Requests to delete all port mappings, answered once they are deleted.
+    unmap: mpsc::Sender<oneshot::Sender<()>>,
+    /// The handle to the spawned task.
+    _task: JoinHandle<()>,
+}
+
+impl Discv5Nat {
+    /// Spawns the task, which runs until the handle is dropped.
+    fn spawn(resolver: NatResolver, mappings: Vec<PortMappingRequest>) -> Self {
+        let mut interval =
+            ResolveNatInterval::interval(resolver, DISCV5_RESOLVE_EXTERNAL_IP_INTERVAL)
+                .with_port_mappings(mappings);
+        let (updates_tx, updates) = mpsc::channel(1);
+        let (unmap, mut unmap_rx) = mpsc::channel::<oneshot::Sender<()>>(1);
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    update = interval.tick_update() => {
+                        if updates_tx.send(update).await.is_err() {
+                            break
+                        }
+                    }
+                    Some(tx) = unmap_rx.recv() => {
+                        interval.unmap_ports().await;
+                        let _ = tx.send(());
+                    }
+                }
+            }
+        });
+        Self { updates: ReceiverStream::new(updates), unmap, _task: task }
+    }
+
+    /// Deletes all port mappings.
+    async fn unmap_ports(&self) {
+        let (tx, rx) = oneshot::channel();
+        if self.unmap.send(tx).await.is_ok() {
+            let _ = rx.await;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,6 +458,7 @@ mod tests {
             secret_key,
             Default::default(),
             None,
+            None,
             Default::default(),
         )
         .await
@@ -377,6 +490,7 @@ mod tests {
             Some(discv4_config),
             Some(discv5_config),
             None,
+            None,
         )
         .await
         .expect("should build discv5 with discv4 downgrade")
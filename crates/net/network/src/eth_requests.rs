@@ -84,62 +84,7 @@ where
 {
     /// Returns the list of requested headers
     fn get_headers_response(&self, request: GetBlockHeaders) -> Vec<Header> {
-        let GetBlockHeaders { start_block, limit, skip, direction } = request;
-
-        let mut headers = Vec::new();
-
-        let mut block: BlockHashOrNumber = match start_block {
-            BlockHashOrNumber::Hash(start) => start.into(),
-            BlockHashOrNumber::Number(num) => {
-                let Some(hash) = self.client.block_hash(num).unwrap_or_default() else {
-                    return headers
-                };
-                hash.into()
-            }
-        };
-
-        let skip = skip as u64;
-        let mut total_bytes = 0;
-
-        for _ in 0..limit {
-            if let Some(header) = self.client.header_by_hash_or_number(block).unwrap_or_default() {
-                match direction {
-                    HeadersDirection::Rising => {
-                        if let Some(next) = (header.number + 1).checked_add(skip) {
-                            block = next.into()
-                        } else {
-                            break
-                        }
-                    }
-                    HeadersDirection::Falling => {
-                        if skip > 0 {
-                            // prevent under flows for block.number == 0 and `block.number - skip <
-                            // 0`
-                            if let Some(next) =
-                                header.number.checked_sub(1).and_then(|num| num.checked_sub(skip))
-                            {
-                                block = next.into()
-                            } else {
-                                break
-                            }
-                        } else {
-                            block = header.parent_hash.into()
-                        }
-                    }
-                }
-
-                total_bytes += header.length();
-                headers.push(header);
-
-                if headers.len() >= MAX_HEADERS_SERVE || total_bytes > SOFT_RESPONSE_LIMIT {
-                    break
-                }
-            } else {
-                break
-            }
-        }
-
-        headers
+        get_headers_response(&self.client, request, MAX_HEADERS_SERVE)
     }
 
     fn on_headers_request(
@@ -268,6 +213,72 @@ where
     }
 }
 
+/// Returns the list of requested headers, at most `max_headers` and roughly
+/// [`SOFT_RESPONSE_LIMIT`] bytes.
+pub(crate) fn get_headers_response<C>(
+    client: &C,
+    request: GetBlockHeaders,
+    max_headers: usize,
+) -> Vec<Header>
+where
+    C: BlockReader,
+{
+    let GetBlockHeaders { start_block, limit, skip, direction } = request;
+
+    let mut headers = Vec::new();
+
+    let mut block: BlockHashOrNumber = match start_block {
+        BlockHashOrNumber::Hash(start) => start.into(),
+        BlockHashOrNumber::Number(num) => {
+            let Some(hash) = client.block_hash(num).unwrap_or_default() else { return headers };
+            hash.into()
+        }
+    };
+
+    let skip = skip as u64;
+    let mut total_bytes = 0;
+
+    for _ in 0..limit {
+        if let Some(header) = client.header_by_hash_or_number(block).unwrap_or_default() {
+            match direction {
+                HeadersDirection::Rising => {
+                    if let Some(next) = (header.number + 1).checked_add(skip) {
+                        block = next.into()
+                    } else {
+                        break
+                    }
+                }
+                HeadersDirection::Falling => {
+                    if skip > 0 {
+                        // prevent under flows for block.number == 0 and `block.number - skip <
+                        // 0`
+                        if let Some(next) =
+                            header.number.checked_sub(1).and_then(|num| num.checked_sub(skip))
+                        {
+                            block = next.into()
+                        } else {
+                            break
+                        }
+                    } else {
+                        block = header.parent_hash.into()
+                    }
+                }
+            }
+
+            total_bytes += header.length();
+            headers.push(header);
+
+            if headers.len() >= max_headers || total_bytes > SOFT_RESPONSE_LIMIT {
+                break
+            }
+        } else {
+            break
+        }
+    }
+
+    headers
+}
+
 /// All `eth` request related to blocks delegated by the network.
 #[derive(Debug)]
 pub enum IncomingEthRequest {
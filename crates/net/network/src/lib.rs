@@ -119,6 +119,7 @@ pub mod config;
 pub mod error;
 pub mod eth_requests;
 pub mod import;
+pub mod light;
 pub mod message;
 pub mod peers;
 pub mod protocol;
@@ -145,7 +146,7 @@ pub use reth_network_api::{
 pub use reth_network_p2p::sync::{NetworkSyncUpdater, SyncState};
 pub use reth_network_types::{PeersConfig, SessionsConfig};
 pub use session::{
-    ActiveSessionHandle, ActiveSessionMessage, Direction, EthRlpxConnection, PeerInfo,
+    ActiveSessionHandle, ActiveSessionMessage, Direction, EthRlpxConnection, PeerInfo, PeerStream,
     PendingSessionEvent, PendingSessionHandle, PendingSessionHandshakeError, SessionCommand,
     SessionEvent, SessionId, SessionManager,
 };
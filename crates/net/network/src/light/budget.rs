@@ -0,0 +1,74 @@
+//! Request budgeting for `light` protocol peers.
+
+use tokio::time::Instant;
+
+/// A token bucket that limits the request units a peer can spend.
+///
+/// The budget starts full and regains units continuously, up to its capacity.
+#[derive(Debug, Clone)]
+pub struct RequestBudget {
+    /// Maximum number of units.
+    capacity: u64,
+    /// Units regained per second.
+    refill_per_sec: u64,
+    /// Units currently available.
+    available: f64,
+    /// When the budget was last refilled.
+    last_refill: Instant,
+}
+
+impl RequestBudget {
+    /// Returns a new, full budget.
+    pub fn new(capacity: u64, refill_per_sec: u64) -> Self {
+        Self { capacity, refill_per_sec, available: capacity as f64, last_refill: Instant::now() }
+    }
+
+    /// Returns the number of units currently available.
+    pub fn available(&mut self) -> u64 {
+        self.refill();
+        self.available as u64
+    }
+
+    /// Spends `cost` units if available, returns `false` if the budget is exhausted.
+    pub fn try_spend(&mut self, cost: u64) -> bool {
+        self.refill();
+        let cost = cost as f64;
+        if self.available < cost {
+            return false
+        }
+        self.available -= cost;
+        true
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.available =
+            elapsed.mul_add(self.refill_per_sec as f64, self.available).min(self.capacity as f64);
+        self.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn spend_and_refill() {
+        let mut budget = RequestBudget::new(100, 10);
+        assert!(budget.try_spend(60));
+        assert!(!budget.try_spend(60));
+        assert_eq!(budget.available(), 40);
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+        assert_eq!(budget.available(), 60);
+        assert!(budget.try_spend(60));
+
+        // never exceeds capacity
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(budget.available(), 100);
+        assert!(!budget.try_spend(101));
+    }
+}
@@ -0,0 +1,411 @@
+//! `light` protocol connections.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures::{stream::FuturesUnordered, Stream, StreamExt};
+use reth_eth_wire::{
+    capability::SharedCapabilities, multiplex::ProtocolConnection, protocol::Protocol,
+    GetBlockHeaders,
+};
+use reth_network_api::Direction;
+use reth_network_peers::PeerId;
+use reth_primitives::{BytesMut, Header};
+use reth_trie::AccountProof;
+use tokio::sync::{
+    mpsc::{self, error::TrySendError},
+    oneshot,
+};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::{debug, trace};
+
+use crate::{
+    light::{
+        GetProof, IncomingLightRequest, LightMessage, LightRequest, LightResponse,
+        LightServeConfig, RejectReason, RequestBudget,
+    },
+    protocol::{ConnectionHandler, OnNotSupported, ProtocolHandler},
+};
+
+/// Events emitted by the [`LightProtocolHandler`].
+#[derive(Debug)]
+pub enum LightProtocolEvent {
+    /// A `light` connection was established with a peer.
+    Established {
+        /// The peer.
+        peer_id: PeerId,
+        /// The direction of the connection.
+        direction: Direction,
+        /// Handle to send requests to the peer.
+        peer: LightPeerHandle,
+    },
+}
+
+/// The `RLPx` sub-protocol handler of the `light` protocol.
+///
+/// Serves the requests of peers if created by [`LightRequestHandler::new`], and emits
+/// [`LightProtocolEvent`]s if configured with [`LightProtocolHandler::with_events`].
+///
+/// [`LightRequestHandler::new`]: crate::light::LightRequestHandler::new
+#[derive(Debug, Clone)]
+pub struct LightProtocolHandler {
+    /// How requests of peers are served.
+    config: LightServeConfig,
+    /// Forwards the requests of peers to the request handler, `None` if requests aren't served.
+    to_request_handler: Option<mpsc::Sender<IncomingLightRequest>>,
+    /// Receives established connections.
+    events: Option<mpsc::UnboundedSender<LightProtocolEvent>>,
+}
+
+impl LightProtocolHandler {
+    /// Returns a handler that forwards requests of peers to the given sender.
+    pub(crate) const fn server(
+        config: LightServeConfig,
+        to_request_handler: mpsc::Sender<IncomingLightRequest>,
+    ) -> Self {
+        Self { config, to_request_handler: Some(to_request_handler), events: None }
+    }
+
+    /// Returns a handler that only sends requests, and rejects all requests of peers.
+    ///
+    /// Established connections are reported to the given sender.
+    pub fn client(events: mpsc::UnboundedSender<LightProtocolEvent>) -> Self {
+        Self { config: LightServeConfig::default(), to_request_handler: None, events: Some(events) }
+    }
+
+    /// Reports established connections to the given sender.
+    pub fn with_events(mut self, events: mpsc::UnboundedSender<LightProtocolEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    fn connection_handler(&self) -> LightConnectionHandler {
+        LightConnectionHandler {
+            config: self.config,
+            to_request_handler: self.to_request_handler.clone(),
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl ProtocolHandler for LightProtocolHandler {
+    type ConnectionHandler = LightConnectionHandler;
+
+    fn on_incoming(&self, _socket_addr: SocketAddr) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+
+    fn on_outgoing(
+        &self,
+        _socket_addr: SocketAddr,
+        _peer_id: PeerId,
+    ) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+}
+
+/// Establishes `light` connections, see [`LightProtocolHandler`].
+#[derive(Debug)]
+pub struct LightConnectionHandler {
+    config: LightServeConfig,
+    to_request_handler: Option<mpsc::Sender<IncomingLightRequest>>,
+    events: Option<mpsc::UnboundedSender<LightProtocolEvent>>,
+}
+
+impl ConnectionHandler for LightConnectionHandler {
+    type Connection = LightConnection;
+
+    fn protocol(&self) -> Protocol {
+        LightMessage::protocol()
+    }
+
+    fn on_unsupported_by_peer(
+        self,
+        _supported: &SharedCapabilities,
+        _direction: Direction,
+        _peer_id: PeerId,
+    ) -> OnNotSupported {
+        OnNotSupported::KeepAlive
+    }
+
+    fn into_connection(
+        self,
+        direction: Direction,
+        peer_id: PeerId,
+        conn: ProtocolConnection,
+    ) -> Self::Connection {
+        let (tx, rx) = mpsc::unbounded_channel();
+        if let Some(events) = &self.events {
+            let peer = LightPeerHandle { peer_id, to_connection: tx };
+            let _ = events.send(LightProtocolEvent::Established { peer_id, direction, peer });
+        }
+
+        LightConnection {
+            peer_id,
+            conn,
+            budget: RequestBudget::new(
+                self.config.budget_capacity,
+                self.config.budget_refill_per_sec,
+            ),
+            config: self.config,
+            to_request_handler: self.to_request_handler,
+            pending_responses: Default::default(),
+            commands: UnboundedReceiverStream::new(rx),
+            inflight_requests: Default::default(),
+            next_request_id: 0,
+        }
+    }
+}
+
+/// A `light` protocol connection with a peer.
+///
+/// Serves the requests of the peer and sends the requests of the [`LightPeerHandle`].
+#[derive(Debug)]
+#[must_use = "Connection does nothing unless polled."]
+pub struct LightConnection {
+    /// The remote peer.
+    peer_id: PeerId,
+    /// The underlying `RLPx` connection.
+    conn: ProtocolConnection,
+    /// How requests of the peer are served.
+    config: LightServeConfig,
+    /// The request budget of the peer.
+    budget: RequestBudget,
+    /// Forwards requests of the peer to the request handler.
+    to_request_handler: Option<mpsc::Sender<IncomingLightRequest>>,
+    /// Responses to requests of the peer.
+    pending_responses: FuturesUnordered<PendingResponse>,
+    /// Requests to send to the peer.
+    commands: UnboundedReceiverStream<LightCommand>,
+    /// Requests sent to the peer, by request id.
+    inflight_requests: HashMap<u64, InflightRequest>,
+    /// Id of the next request sent to the peer.
+    next_request_id: u64,
+}
+
+impl LightConnection {
+    /// Registers the request and returns the message to send.
+    fn on_command(&mut self, command: LightCommand) -> LightMessage {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+
+        match command {
+            LightCommand::GetBlockHeaders { request, response } => {
+                self.inflight_requests.insert(request_id, InflightRequest::BlockHeaders(response));
+                LightMessage::GetBlockHeaders { request_id, request }
+            }
+            LightCommand::GetProof { request, response } => {
+                self.inflight_requests.insert(request_id, InflightRequest::Proof(response));
+                LightMessage::GetProof { request_id, request }
+            }
+        }
+    }
+
+    /// Handles a message of the peer and returns the immediate reply, if any.
+    fn on_message(&mut self, msg: LightMessage) -> Option<LightMessage> {
+        let request_id = msg.request_id();
+        let request = match msg {
+            LightMessage::GetBlockHeaders { request, .. } => LightRequest::GetBlockHeaders(request),
+            LightMessage::GetProof { request, .. } => LightRequest::GetProof(request),
+            LightMessage::BlockHeaders { headers, .. } => {
+                self.on_response(request_id, LightResponse::BlockHeaders(headers));
+                return None
+            }
+            LightMessage::Proof { proof, .. } => {
+                self.on_response(request_id, LightResponse::Proof(proof));
+                return None
+            }
+            LightMessage::Rejected { reason, .. } => {
+                self.on_response(request_id, LightResponse::Rejected(reason));
+                return None
+            }
+        };
+
+        let reject = |reason| Some(LightMessage::Rejected { request_id, reason });
+
+        let Some(to_request_handler) = &self.to_request_handler else {
+            return reject(RejectReason::Unavailable)
+        };
+
+        let cost = match &request {
+            LightRequest::GetBlockHeaders(request) => self.config.headers_request_cost(request),
+            LightRequest::GetProof(request) => self.config.proof_request_cost(request),
+        };
+        if !self.budget.try_spend(cost) {
+            trace!(target: "net::light", peer_id=?self.peer_id, cost, "request budget exhausted");
+            return reject(RejectReason::Throttled)
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let incoming = IncomingLightRequest { peer_id: self.peer_id, request, response: tx };
+        match to_request_handler.try_send(incoming) {
+            Ok(()) => {
+                self.pending_responses.push(PendingResponse { request_id, response: rx });
+                None
+            }
+            Err(TrySendError::Full(_)) => reject(RejectReason::Throttled),
+            Err(TrySendError::Closed(_)) => reject(RejectReason::Unavailable),
+        }
+    }
+
+    /// Resolves the inflight request with the peer's response.
+    fn on_response(&mut self, request_id: u64, response: LightResponse) {
+        let Some(request) = self.inflight_requests.remove(&request_id) else {
+            debug!(target: "net::light", peer_id=?self.peer_id, request_id, "unexpected response");
+            return
+        };
+
+        match (request, response) {
+            (InflightRequest::BlockHeaders(tx), LightResponse::BlockHeaders(headers)) => {
+                let _ = tx.send(Ok(headers));
+            }
+            (InflightRequest::Proof(tx), LightResponse::Proof(proof)) => {
+                let _ = tx.send(Ok(proof.into()));
+            }
+            (request, LightResponse::Rejected(reason)) => {
+                request.send_err(LightRequestError::Rejected(reason))
+            }
+            (request, _) => request.send_err(LightRequestError::BadResponse),
+        }
+    }
+}
+
+impl Stream for LightConnection {
+    type Item = BytesMut;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Poll::Ready(Some(command)) = this.commands.poll_next_unpin(cx) {
+                return Poll::Ready(Some(this.on_command(command).encoded()))
+            }
+
+            if let Poll::Ready(Some((request_id, response))) =
+                this.pending_responses.poll_next_unpin(cx)
+            {
+                return Poll::Ready(Some(response.into_message(request_id).encoded()))
+            }
+
+            let Some(msg) = ready!(this.conn.poll_next_unpin(cx)) else { return Poll::Ready(None) };
+
+            let msg = match LightMessage::decode_message(&mut &msg[..]) {
+                Ok(msg) => msg,
+                Err(err) => {
+                    debug!(target: "net::light", peer_id=?this.peer_id, %err, "failed to decode message");
+                    return Poll::Ready(None)
+                }
+            };
+
+            if let Some(reply) = this.on_message(msg) {
+                return Poll::Ready(Some(reply.encoded()))
+            }
+        }
+    }
+}
+
+/// A response of the request handler to a request of the peer.
+#[derive(Debug)]
+struct PendingResponse {
+    request_id: u64,
+    response: oneshot::Receiver<LightResponse>,
+}
+
+impl Future for PendingResponse {
+    type Output = (u64, LightResponse);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let response = ready!(Pin::new(&mut self.response).poll(cx))
+            .unwrap_or(LightResponse::Rejected(RejectReason::Unavailable));
+        Poll::Ready((self.request_id, response))
+    }
+}
+
+/// A request sent to the peer, awaiting its response.
+#[derive(Debug)]
+enum InflightRequest {
+    BlockHeaders(oneshot::Sender<Result<Vec<Header>, LightRequestError>>),
+    Proof(oneshot::Sender<Result<AccountProof, LightRequestError>>),
+}
+
+impl InflightRequest {
+    fn send_err(self, err: LightRequestError) {
+        match self {
+            Self::BlockHeaders(tx) => {
+                let _ = tx.send(Err(err));
+            }
+            Self::Proof(tx) => {
+                let _ = tx.send(Err(err));
+            }
+        }
+    }
+}
+
+/// Requests of the [`LightPeerHandle`].
+#[derive(Debug)]
+enum LightCommand {
+    GetBlockHeaders {
+        request: GetBlockHeaders,
+        response: oneshot::Sender<Result<Vec<Header>, LightRequestError>>,
+    },
+    GetProof {
+        request: GetProof,
+        response: oneshot::Sender<Result<AccountProof, LightRequestError>>,
+    },
+}
+
+/// Errors of `light` protocol requests.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LightRequestError {
+    /// The peer didn't serve the request.
+    #[error("request rejected: {0}")]
+    Rejected(RejectReason),
+    /// The peer answered with a response of the wrong type.
+    #[error("bad response")]
+    BadResponse,
+    /// The connection was closed before the peer responded.
+    #[error("connection dropped")]
+    ConnectionDropped,
+}
+
+/// Sends `light` protocol requests to a connected peer.
+#[derive(Debug, Clone)]
+pub struct LightPeerHandle {
+    peer_id: PeerId,
+    to_connection: mpsc::UnboundedSender<LightCommand>,
+}
+
+impl LightPeerHandle {
+    /// Returns the id of the peer.
+    pub const fn peer_id(&self) -> &PeerId {
+        &self.peer_id
+    }
+
+    /// Requests block headers from the peer.
+    pub async fn get_block_headers(
+        &self,
+        request: GetBlockHeaders,
+    ) -> Result<Vec<Header>, LightRequestError> {
+        let (response, rx) = oneshot::channel();
+        self.to_connection
+            .send(LightCommand::GetBlockHeaders { request, response })
+            .map_err(|_| LightRequestError::ConnectionDropped)?;
+        rx.await.map_err(|_| LightRequestError::ConnectionDropped)?
+    }
+
+    /// Requests an account proof from the peer.
+    ///
+    /// The returned proof is not verified, see [`AccountProof::verify`].
+    pub async fn get_proof(&self, request: GetProof) -> Result<AccountProof, LightRequestError> {
+        let (response, rx) = oneshot::channel();
+        self.to_connection
+            .send(LightCommand::GetProof { request, response })
+            .map_err(|_| LightRequestError::ConnectionDropped)?;
+        rx.await.map_err(|_| LightRequestError::ConnectionDropped)?
+    }
+}
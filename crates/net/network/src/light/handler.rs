@@ -0,0 +1,224 @@
+//! Serves `light` protocol requests.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::StreamExt;
+use reth_eth_wire::GetBlockHeaders;
+use reth_network_peers::PeerId;
+use reth_primitives::{Header, B256};
+use reth_storage_api::{errors::provider::ProviderResult, BlockReader, StateProviderFactory};
+use reth_trie::HashedPostState;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::trace;
+
+use crate::{
+    budget::DEFAULT_BUDGET_TRY_DRAIN_DOWNLOADERS,
+    eth_requests::get_headers_response,
+    light::{
+        GetProof, LightMessage, LightProtocolHandler, LightServeConfig, ProofMessage, RejectReason,
+        DEFAULT_LIGHT_REQUEST_CHANNEL_SIZE,
+    },
+    metrics::LightRequestHandlerMetrics,
+    poll_nested_stream_with_budget,
+};
+
+/// Answers `light` protocol requests of peers.
+///
+/// This can be spawned to another task and is supposed to be run as background service.
+#[derive(Debug)]
+#[must_use = "Handler does nothing unless polled."]
+pub struct LightRequestHandler<C> {
+    /// The client type that can interact with the chain.
+    client: C,
+    /// How requests are served.
+    config: LightServeConfig,
+    /// Incoming requests from the `light` connections.
+    incoming_requests: ReceiverStream<IncomingLightRequest>,
+    /// Metrics for the light request handler.
+    metrics: LightRequestHandlerMetrics,
+}
+
+// === impl LightRequestHandler ===
+
+impl<C> LightRequestHandler<C> {
+    /// Creates a new handler, and the [`LightProtocolHandler`] that forwards the requests of
+    /// peers to it.
+    ///
+    /// The [`LightProtocolHandler`] must be added to the network as `RLPx` sub-protocol.
+    pub fn new(client: C, config: LightServeConfig) -> (Self, LightProtocolHandler) {
+        let (tx, rx) = mpsc::channel(DEFAULT_LIGHT_REQUEST_CHANNEL_SIZE);
+        let handler = Self {
+            client,
+            config,
+            incoming_requests: ReceiverStream::new(rx),
+            metrics: Default::default(),
+        };
+        (handler, LightProtocolHandler::server(config, tx))
+    }
+}
+
+impl<C> LightRequestHandler<C>
+where
+    C: BlockReader + StateProviderFactory,
+{
+    fn on_headers_request(&self, peer_id: PeerId, request: GetBlockHeaders) -> LightResponse {
+        self.metrics.light_headers_requests_received_total.increment(1);
+        trace!(target: "net::light", ?peer_id, ?request, "serving headers");
+        LightResponse::BlockHeaders(get_headers_response(
+            &self.client,
+            request,
+            self.config.max_headers,
+        ))
+    }
+
+    /// Returns `true` if the block is known and within the proof window.
+    fn is_provable(&self, block_hash: B256) -> ProviderResult<bool> {
+        let Some(header) = self.client.header(&block_hash)? else { return Ok(false) };
+        let best_number = self.client.best_block_number()?;
+        Ok(best_number.saturating_sub(header.number) <= self.config.max_proof_window)
+    }
+
+    fn reject(&self, reason: RejectReason) -> LightResponse {
+        self.metrics.light_requests_rejected_total.increment(1);
+        LightResponse::Rejected(reason)
+    }
+}
+
+impl<C> LightRequestHandler<C>
+where
+    C: BlockReader + StateProviderFactory + Clone + 'static,
+{
+    /// Answers the proof request on a blocking task, since computing the proof of a historical
+    /// block reverts the state to that block.
+    fn on_proof_request(
+        &self,
+        peer_id: PeerId,
+        request: GetProof,
+        response: oneshot::Sender<LightResponse>,
+    ) {
+        self.metrics.light_proof_requests_received_total.increment(1);
+        let GetProof { block_hash, address, storage_keys } = request;
+
+        if storage_keys.len() > self.config.max_storage_keys {
+            let _ = response.send(self.reject(RejectReason::LimitExceeded));
+            return
+        }
+
+        match self.is_provable(block_hash) {
+            Ok(true) => {}
+            Ok(false) => {
+                trace!(target: "net::light", ?peer_id, %block_hash, "rejecting proof outside of the proof window");
+                let _ = response.send(self.reject(RejectReason::Unavailable));
+                return
+            }
+            Err(err) => {
+                trace!(target: "net::light", ?peer_id, %block_hash, %err, "failed to look up proven block");
+                let _ = response.send(self.reject(RejectReason::Unavailable));
+                return
+            }
+        }
+
+        let client = self.client.clone();
+        let metrics = self.metrics.clone();
+        tokio::task::spawn_blocking(move || {
+            let proof = client.history_by_block_hash(block_hash).and_then(|state| {
+                state.hashed_proof(HashedPostState::default(), address, &storage_keys)
+            });
+            let resp = match proof {
+                Ok(proof) => LightResponse::Proof(proof.into()),
+                Err(err) => {
+                    trace!(target: "net::light", ?peer_id, %block_hash, %err, "failed to generate proof");
+                    metrics.light_requests_rejected_total.increment(1);
+                    LightResponse::Rejected(RejectReason::Unavailable)
+                }
+            };
+            let _ = response.send(resp);
+        });
+    }
+}
+
+/// An endless future.
+///
+/// This should be spawned or used as part of `tokio::select!`.
+impl<C> Future for LightRequestHandler<C>
+where
+    C: BlockReader + StateProviderFactory + Clone + Unpin + 'static,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let maybe_more_incoming_requests = poll_nested_stream_with_budget!(
+            "net::light",
+            "Incoming light requests stream",
+            DEFAULT_BUDGET_TRY_DRAIN_DOWNLOADERS,
+            this.incoming_requests.poll_next_unpin(cx),
+            |incoming| {
+                let IncomingLightRequest { peer_id, request, response } = incoming;
+                match request {
+                    LightRequest::GetBlockHeaders(request) => {
+                        let _ = response.send(this.on_headers_request(peer_id, request));
+                    }
+                    LightRequest::GetProof(request) => {
+                        this.on_proof_request(peer_id, request, response)
+                    }
+                }
+            },
+        );
+
+        if maybe_more_incoming_requests {
+            // make sure we're woken up again
+            cx.waker().wake_by_ref();
+        }
+
+        Poll::Pending
+    }
+}
+
+/// A `light` protocol request of a peer, delegated to the [`LightRequestHandler`].
+#[derive(Debug)]
+pub struct IncomingLightRequest {
+    /// The peer that sent the request.
+    pub peer_id: PeerId,
+    /// The request.
+    pub request: LightRequest,
+    /// The channel sender for the response.
+    pub response: oneshot::Sender<LightResponse>,
+}
+
+/// The requests of the `light` protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LightRequest {
+    /// Request for block headers.
+    GetBlockHeaders(GetBlockHeaders),
+    /// Request for an account proof.
+    GetProof(GetProof),
+}
+
+/// The responses of the `light` protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LightResponse {
+    /// The requested block headers.
+    BlockHeaders(Vec<Header>),
+    /// The requested account proof.
+    Proof(ProofMessage),
+    /// The request wasn't served.
+    Rejected(RejectReason),
+}
+
+impl LightResponse {
+    /// Converts the response into the [`LightMessage`] answering the request with the given id.
+    pub fn into_message(self, request_id: u64) -> LightMessage {
+        match self {
+            Self::BlockHeaders(headers) => LightMessage::BlockHeaders { request_id, headers },
+            Self::Proof(proof) => LightMessage::Proof { request_id, proof },
+            Self::Rejected(reason) => LightMessage::Rejected { request_id, reason },
+        }
+    }
+}
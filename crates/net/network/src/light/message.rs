@@ -0,0 +1,403 @@
+//! Messages of the `light` protocol.
+
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use reth_eth_wire::{protocol::Protocol, Capability, GetBlockHeaders};
+use reth_primitives::{
+    Account, Address, BufMut, Bytes, BytesMut, Header, B256, KECCAK_EMPTY, U256,
+};
+use reth_trie::{AccountProof, StorageProof};
+
+/// The ids of the `light` protocol messages.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightMessageId {
+    /// Request for block headers.
+    GetBlockHeaders = 0x00,
+    /// Response with block headers.
+    BlockHeaders = 0x01,
+    /// Request for an account proof.
+    GetProof = 0x02,
+    /// Response with an account proof.
+    Proof = 0x03,
+    /// Response to a request that wasn't served.
+    Rejected = 0x04,
+}
+
+impl LightMessageId {
+    /// Number of message ids reserved by the protocol.
+    pub const COUNT: u8 = 5;
+}
+
+impl TryFrom<u8> for LightMessageId {
+    type Error = alloy_rlp::Error;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        Ok(match id {
+            0x00 => Self::GetBlockHeaders,
+            0x01 => Self::BlockHeaders,
+            0x02 => Self::GetProof,
+            0x03 => Self::Proof,
+            0x04 => Self::Rejected,
+            _ => return Err(alloy_rlp::Error::Custom("unknown light message id")),
+        })
+    }
+}
+
+/// Request for the account and storage proofs of an account at a given block, like
+/// `eth_getProof`.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct GetProof {
+    /// Hash of the block whose state the proof is generated for.
+    pub block_hash: B256,
+    /// The address of the account.
+    pub address: Address,
+    /// The storage slots to prove.
+    pub storage_keys: Vec<B256>,
+}
+
+/// The storage proof of a single slot.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct StorageProofMessage {
+    /// The storage slot.
+    pub key: B256,
+    /// The value of the slot.
+    pub value: U256,
+    /// Trie nodes from the storage root to the slot.
+    pub proof: Vec<Bytes>,
+}
+
+/// The proof of an account and the requested storage slots, see also [`AccountProof`].
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct ProofMessage {
+    /// The address of the account.
+    pub address: Address,
+    /// Whether the account exists.
+    pub exists: bool,
+    /// The nonce of the account.
+    pub nonce: u64,
+    /// The balance of the account.
+    pub balance: U256,
+    /// The code hash of the account.
+    pub code_hash: B256,
+    /// The storage root of the account.
+    pub storage_root: B256,
+    /// Trie nodes from the state root to the account.
+    pub account_proof: Vec<Bytes>,
+    /// Proofs of the requested storage slots.
+    pub storage_proofs: Vec<StorageProofMessage>,
+}
+
+impl From<AccountProof> for ProofMessage {
+    fn from(proof: AccountProof) -> Self {
+        let AccountProof { address, info, proof, storage_root, storage_proofs } = proof;
+        let account = info.unwrap_or_default();
+        Self {
+            address,
+            exists: info.is_some(),
+            nonce: account.nonce,
+            balance: account.balance,
+            code_hash: account.bytecode_hash.unwrap_or(KECCAK_EMPTY),
+            storage_root,
+            account_proof: proof,
+            storage_proofs: storage_proofs
+                .into_iter()
+                .map(|StorageProof { key, value, proof, .. }| StorageProofMessage {
+                    key,
+                    value,
+                    proof,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<ProofMessage> for AccountProof {
+    fn from(msg: ProofMessage) -> Self {
+        let ProofMessage {
+            address,
+            exists,
+            nonce,
+            balance,
+            code_hash,
+            storage_root,
+            account_proof,
+            storage_proofs,
+        } = msg;
+        Self {
+            address,
+            info: exists.then(|| Account {
+                nonce,
+                balance,
+                bytecode_hash: (code_hash != KECCAK_EMPTY).then_some(code_hash),
+            }),
+            proof: account_proof,
+            storage_root,
+            storage_proofs: storage_proofs
+                .into_iter()
+                .map(|StorageProofMessage { key, value, proof }| StorageProof {
+                    value,
+                    proof,
+                    ..StorageProof::new(key)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Why a request was not served.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum RejectReason {
+    /// The request budget of the peer is exhausted.
+    #[error("request budget exhausted")]
+    Throttled = 0x00,
+    /// The node doesn't serve the request, e.g. because serving is disabled or the requested
+    /// state is not available.
+    #[error("request not served")]
+    Unavailable = 0x01,
+    /// The request exceeds the limits of the node.
+    #[error("request exceeds limits")]
+    LimitExceeded = 0x02,
+}
+
+impl Encodable for RejectReason {
+    fn encode(&self, out: &mut dyn BufMut) {
+        (*self as u8).encode(out)
+    }
+
+    fn length(&self) -> usize {
+        (*self as u8).length()
+    }
+}
+
+impl Decodable for RejectReason {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        Ok(match u8::decode(buf)? {
+            0x00 => Self::Throttled,
+            0x01 => Self::Unavailable,
+            0x02 => Self::LimitExceeded,
+            _ => return Err(alloy_rlp::Error::Custom("unknown reject reason")),
+        })
+    }
+}
+
+/// A message of the `light` protocol.
+///
+/// Every request carries an id that is echoed by its response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LightMessage {
+    /// Request for block headers.
+    GetBlockHeaders {
+        /// Id of the request.
+        request_id: u64,
+        /// The requested headers.
+        request: GetBlockHeaders,
+    },
+    /// Response with block headers.
+    BlockHeaders {
+        /// Id of the request.
+        request_id: u64,
+        /// The headers.
+        headers: Vec<Header>,
+    },
+    /// Request for an account proof.
+    GetProof {
+        /// Id of the request.
+        request_id: u64,
+        /// The requested proof.
+        request: GetProof,
+    },
+    /// Response with an account proof.
+    Proof {
+        /// Id of the request.
+        request_id: u64,
+        /// The proof.
+        proof: ProofMessage,
+    },
+    /// Response to a request that wasn't served.
+    Rejected {
+        /// Id of the request.
+        request_id: u64,
+        /// Why the request wasn't served.
+        reason: RejectReason,
+    },
+}
+
+#[derive(RlpEncodable)]
+struct EncodePair<'a, T> {
+    request_id: u64,
+    message: &'a T,
+}
+
+#[derive(RlpDecodable)]
+struct DecodePair<T> {
+    request_id: u64,
+    message: T,
+}
+
+impl LightMessage {
+    /// Returns the capability of the `light` protocol.
+    pub const fn capability() -> Capability {
+        Capability::new_static("light", 1)
+    }
+
+    /// Returns the `light` protocol.
+    pub const fn protocol() -> Protocol {
+        Protocol::new(Self::capability(), LightMessageId::COUNT)
+    }
+
+    /// Returns the id of the message.
+    pub const fn message_id(&self) -> LightMessageId {
+        match self {
+            Self::GetBlockHeaders { .. } => LightMessageId::GetBlockHeaders,
+            Self::BlockHeaders { .. } => LightMessageId::BlockHeaders,
+            Self::GetProof { .. } => LightMessageId::GetProof,
+            Self::Proof { .. } => LightMessageId::Proof,
+            Self::Rejected { .. } => LightMessageId::Rejected,
+        }
+    }
+
+    /// Returns the id of the request this message belongs to.
+    pub const fn request_id(&self) -> u64 {
+        match self {
+            Self::GetBlockHeaders { request_id, .. } |
+            Self::BlockHeaders { request_id, .. } |
+            Self::GetProof { request_id, .. } |
+            Self::Proof { request_id, .. } |
+            Self::Rejected { request_id, .. } => *request_id,
+        }
+    }
+
+    /// Encodes the message, prefixed with its id.
+    pub fn encoded(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        buf.put_u8(self.message_id() as u8);
+        let request_id = self.request_id();
+        match self {
+            Self::GetBlockHeaders { request, .. } => {
+                EncodePair { request_id, message: request }.encode(&mut buf)
+            }
+            Self::BlockHeaders { headers, .. } => {
+                EncodePair { request_id, message: headers }.encode(&mut buf)
+            }
+            Self::GetProof { request, .. } => {
+                EncodePair { request_id, message: request }.encode(&mut buf)
+            }
+            Self::Proof { proof, .. } => EncodePair { request_id, message: proof }.encode(&mut buf),
+            Self::Rejected { reason, .. } => {
+                EncodePair { request_id, message: reason }.encode(&mut buf)
+            }
+        }
+        buf
+    }
+
+    /// Decodes a message, prefixed with its id.
+    pub fn decode_message(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let Some((&id, rest)) = buf.split_first() else {
+            return Err(alloy_rlp::Error::InputTooShort)
+        };
+        *buf = rest;
+
+        Ok(match LightMessageId::try_from(id)? {
+            LightMessageId::GetBlockHeaders => {
+                let DecodePair { request_id, message } = DecodePair::decode(buf)?;
+                Self::GetBlockHeaders { request_id, request: message }
+            }
+            LightMessageId::BlockHeaders => {
+                let DecodePair { request_id, message } = DecodePair::decode(buf)?;
+                Self::BlockHeaders { request_id, headers: message }
+            }
+            LightMessageId::GetProof => {
+                let DecodePair { request_id, message } = DecodePair::decode(buf)?;
+                Self::GetProof { request_id, request: message }
+            }
+            LightMessageId::Proof => {
+                let DecodePair { request_id, message } = DecodePair::decode(buf)?;
+                Self::Proof { request_id, proof: message }
+            }
+            LightMessageId::Rejected => {
+                let DecodePair { request_id, message } = DecodePair::decode(buf)?;
+                Self::Rejected { request_id, reason: message }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reth_eth_wire::HeadersDirection;
+    use reth_primitives::{constants::EMPTY_ROOT_HASH, BlockHashOrNumber};
+
+    use super::*;
+
+    #[test]
+    fn light_message_roundtrip() {
+        let messages = [
+            LightMessage::GetBlockHeaders {
+                request_id: 1,
+                request: GetBlockHeaders {
+                    start_block: BlockHashOrNumber::Number(100),
+                    limit: 10,
+                    skip: 0,
+                    direction: HeadersDirection::Rising,
+                },
+            },
+            LightMessage::BlockHeaders {
+                request_id: 1,
+                headers: vec![Header { number: 100, ..Default::default() }],
+            },
+            LightMessage::GetProof {
+                request_id: 2,
+                request: GetProof {
+                    block_hash: B256::random(),
+                    address: Address::random(),
+                    storage_keys: vec![B256::random()],
+                },
+            },
+            LightMessage::Proof {
+                request_id: 2,
+                proof: AccountProof {
+                    address: Address::random(),
+                    info: Some(Account { nonce: 1, balance: U256::from(2), bytecode_hash: None }),
+                    proof: vec![Bytes::from_static(&[1, 2, 3])],
+                    storage_root: EMPTY_ROOT_HASH,
+                    storage_proofs: vec![StorageProof::new(B256::random())],
+                }
+                .into(),
+            },
+            LightMessage::Rejected { request_id: 3, reason: RejectReason::Throttled },
+        ];
+
+        for msg in messages {
+            let encoded = msg.encoded();
+            let decoded = LightMessage::decode_message(&mut &encoded[..]).unwrap();
+            assert_eq!(decoded, msg);
+        }
+
+        assert!(LightMessage::decode_message(&mut &[0x05, 0xc0][..]).is_err());
+        assert!(LightMessage::decode_message(&mut &[][..]).is_err());
+    }
+
+    #[test]
+    fn account_proof_conversion() {
+        let proof = AccountProof {
+            address: Address::random(),
+            info: Some(Account {
+                nonce: 1,
+                balance: U256::from(2),
+                bytecode_hash: Some(B256::random()),
+            }),
+            proof: vec![Bytes::from_static(&[1, 2, 3])],
+            storage_root: B256::random(),
+            storage_proofs: vec![StorageProof {
+                value: U256::from(3),
+                proof: vec![Bytes::from_static(&[4])],
+                ..StorageProof::new(B256::random())
+            }],
+        };
+        assert_eq!(AccountProof::from(ProofMessage::from(proof.clone())), proof);
+
+        let proof = AccountProof::new(Address::random());
+        assert_eq!(AccountProof::from(ProofMessage::from(proof.clone())), proof);
+    }
+}
@@ -0,0 +1,121 @@
+//! Light client serving over the `light` `RLPx` sub-protocol.
+//!
+//! The `light` protocol lets light clients request block headers and `eth_getProof` style account
+//! and storage proofs from full nodes, which they verify against the state root of a header they
+//! already trust.
+//!
+//! Requests are answered by the [`LightRequestHandler`], which is supposed to be spawned as a
+//! background service, while the [`LightProtocolHandler`] is added to the network as an `RLPx`
+//! sub-protocol. Every peer is charged for its requests against a [`RequestBudget`], requests that
+//! exceed it are rejected with [`RejectReason::Throttled`].
+//!
+//! Peers that also act as clients can subscribe to [`LightProtocolEvent`]s to obtain a
+//! [`LightPeerHandle`] for every established connection.
+
+mod budget;
+mod connection;
+mod handler;
+mod message;
+
+use reth_eth_wire::GetBlockHeaders;
+
+pub use budget::RequestBudget;
+pub use connection::{
+    LightConnection, LightConnectionHandler, LightPeerHandle, LightProtocolEvent,
+    LightProtocolHandler, LightRequestError,
+};
+pub use handler::{IncomingLightRequest, LightRequest, LightRequestHandler, LightResponse};
+pub use message::{
+    GetProof, LightMessage, LightMessageId, ProofMessage, RejectReason, StorageProofMessage,
+};
+
+/// Maximum number of block headers served per request.
+pub const DEFAULT_MAX_HEADERS_SERVE: usize = 192;
+
+/// Maximum number of storage slots proven per request.
+pub const DEFAULT_MAX_STORAGE_KEYS_SERVE: usize = 64;
+
+/// Maximum distance of the proven block from the tip, in blocks.
+///
+/// Like the default `eth_getProof` window, this only serves proofs for the latest block.
+pub const DEFAULT_MAX_PROOF_WINDOW_SERVE: u64 = 0;
+
+/// Capacity of the channel that forwards requests to the [`LightRequestHandler`].
+///
+/// Requests that don't fit into the channel are rejected with [`RejectReason::Throttled`].
+pub const DEFAULT_LIGHT_REQUEST_CHANNEL_SIZE: usize = 64;
+
+/// Configures how the node serves the `light` protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LightServeConfig {
+    /// Maximum number of block headers served per request.
+    pub max_headers: usize,
+    /// Maximum number of storage slots proven per request, requests for more slots are rejected.
+    pub max_storage_keys: usize,
+    /// Maximum distance of the proven block from the tip, requests for older blocks are rejected.
+    pub max_proof_window: u64,
+    /// Maximum number of request units a peer can spend at once.
+    pub budget_capacity: u64,
+    /// Number of request units a peer regains per second.
+    pub budget_refill_per_sec: u64,
+    /// Request units charged per requested header.
+    pub header_cost: u64,
+    /// Request units charged per account proof.
+    pub proof_cost: u64,
+    /// Request units charged per proven storage slot.
+    pub storage_key_cost: u64,
+}
+
+impl LightServeConfig {
+    /// Sets the maximum number of block headers served per request.
+    pub const fn with_max_headers(mut self, max_headers: usize) -> Self {
+        self.max_headers = max_headers;
+        self
+    }
+
+    /// Sets the maximum number of storage slots proven per request.
+    pub const fn with_max_storage_keys(mut self, max_storage_keys: usize) -> Self {
+        self.max_storage_keys = max_storage_keys;
+        self
+    }
+
+    /// Sets the maximum distance of the proven block from the tip.
+    pub const fn with_max_proof_window(mut self, max_proof_window: u64) -> Self {
+        self.max_proof_window = max_proof_window;
+        self
+    }
+
+    /// Sets the capacity and refill rate of the request budget of every peer.
+    pub const fn with_budget(mut self, capacity: u64, refill_per_sec: u64) -> Self {
+        self.budget_capacity = capacity;
+        self.budget_refill_per_sec = refill_per_sec;
+        self
+    }
+
+    /// Returns the request units charged for a [`GetBlockHeaders`] request.
+    pub fn headers_request_cost(&self, request: &GetBlockHeaders) -> u64 {
+        let headers = (request.limit as usize).min(self.max_headers) as u64;
+        self.header_cost.saturating_mul(headers)
+    }
+
+    /// Returns the request units charged for a [`GetProof`] request.
+    pub fn proof_request_cost(&self, request: &GetProof) -> u64 {
+        let storage_keys = request.storage_keys.len() as u64;
+        self.proof_cost.saturating_add(self.storage_key_cost.saturating_mul(storage_keys))
+    }
+}
+
+impl Default for LightServeConfig {
+    fn default() -> Self {
+        Self {
+            max_headers: DEFAULT_MAX_HEADERS_SERVE,
+            max_storage_keys: DEFAULT_MAX_STORAGE_KEYS_SERVE,
+            max_proof_window: DEFAULT_MAX_PROOF_WINDOW_SERVE,
+            budget_capacity: 2_000,
+            budget_refill_per_sec: 200,
+            header_cost: 1,
+            proof_cost: 20,
+            storage_key_cost: 5,
+        }
+    }
+}
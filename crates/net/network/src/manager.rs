@@ -66,6 +66,9 @@ use crate::{
     FetchClient, NetworkBuilder,
 };
 
+/// How long to wait for the NAT gateway to delete the port mappings on shutdown.
+const UNMAP_NAT_PORTS_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[cfg_attr(doc, aquamarine::aquamarine)]
 /// Manages the _entire_ state of the network.
 ///
@@ -175,6 +178,7 @@ impl NetworkManager {
             discovery_v4_addr,
             mut discovery_v4_config,
             mut discovery_v5_config,
+            discovery_v5_nat,
             listener_addr,
             peers_config,
             sessions_config,
@@ -223,6 +227,7 @@ impl NetworkManager {
             secret_key,
             discovery_v4_config,
             discovery_v5_config,
+            discovery_v5_nat,
             dns_discovery_config,
         )
         .await?;
@@ -355,6 +360,18 @@ impl NetworkManager {
         self.swarm.state().peers().handle()
     }
 
+    /// Accepts an incoming connection over the given stream as if it was accepted by the listener.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub(crate) fn on_incoming_stream(
+        &mut self,
+        stream: crate::session::PeerStream,
+        remote_addr: SocketAddr,
+    ) {
+        if let Some(event) = self.swarm.on_incoming(stream, remote_addr) {
+            self.on_swarm_event(event)
+        }
+    }
+
     /// Collect the peers from the [`NetworkManager`] and write them to the given
     /// `persistent_peers_file`.
     pub fn write_peers_to_file(&self, persistent_peers_file: &Path) -> Result<(), FsPathError> {
@@ -713,6 +730,14 @@ impl NetworkManager {
 
                 self.update_active_connection_metrics();
 
+                let peer_kind = self
+                    .swarm
+                    .state()
+                    .peers()
+                    .peer_by_id(peer_id)
+                    .map(|(_, kind)| kind)
+                    .unwrap_or_default();
+
                 self.event_sender.notify(NetworkEvent::SessionEstablished {
                     peer_id,
                     remote_addr,
@@ -721,6 +746,7 @@ impl NetworkManager {
                     version,
                     status,
                     messages,
+                    peer_kind,
                 });
             }
             SwarmEvent::PeerAdded(peer_id) => {
@@ -951,7 +977,8 @@ impl NetworkManager {
 impl NetworkManager {
     /// Drives the [`NetworkManager`] future until a [`GracefulShutdown`] signal is received.
     ///
-    /// This invokes the given function `shutdown_hook` while holding the graceful shutdown guard.
+    /// This deletes the port mappings on the NAT gateway and invokes the given function
+    /// `shutdown_hook` while holding the graceful shutdown guard.
     pub async fn run_until_graceful_shutdown<F, R>(
         mut self,
         shutdown: GracefulShutdown,
@@ -968,6 +995,16 @@ impl NetworkManager {
             },
         }
 
+        if tokio::time::timeout(
+            UNMAP_NAT_PORTS_TIMEOUT,
+            self.swarm.state().discovery().unmap_nat_ports(),
+        )
+        .await
+        .is_err()
+        {
+            debug!(target: "net", "timed out deleting nat port mappings");
+        }
+
         let res = shutdown_hook(self);
         drop(graceful_guard);
         res
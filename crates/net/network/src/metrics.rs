@@ -117,6 +117,10 @@ pub struct TransactionsManagerMetrics {
     /// Total number of times a transaction is sent that is already in the local pool.
     pub(crate) occurrences_transactions_already_in_pool: Counter,
 
+    /* -- Rate limited txns -- */
+    /// Total number of transactions that were dropped because the peer exceeded its rate limit.
+    pub(crate) occurrences_transactions_rate_limited: Counter,
+
     /* ================ POOL IMPORTS ================ */
     /// Number of transactions about to be imported into the pool.
     pub(crate) pending_pool_imports: Gauge,
@@ -329,6 +333,20 @@ pub struct EthRequestHandlerMetrics {
     pub(crate) acc_duration_poll_eth_req_handler: Gauge,
 }
 
+/// Metrics for the `LightRequestHandler`
+#[derive(Metrics, Clone)]
+#[metrics(scope = "network")]
+pub struct LightRequestHandlerMetrics {
+    /// Number of `light` `GetBlockHeaders` requests received
+    pub(crate) light_headers_requests_received_total: Counter,
+
+    /// Number of `light` `GetProof` requests received
+    pub(crate) light_proof_requests_received_total: Counter,
+
+    /// Number of `light` requests that were rejected
+    pub(crate) light_requests_rejected_total: Counter,
+}
+
 /// Eth67 announcement metrics, track entries by `TxType`
 #[derive(Metrics)]
 #[metrics(scope = "network.transaction_fetcher")]
@@ -8,7 +8,7 @@ use std::{
     pin::Pin,
     sync::{atomic::AtomicU64, Arc},
     task::{ready, Context, Poll},
-    time::{Duration, Instant},
+    time::Duration,
 };
 
 use futures::{stream::Fuse, SinkExt, StreamExt};
@@ -25,7 +25,7 @@ use reth_network_types::session::config::INITIAL_REQUEST_TIMEOUT;
 use rustc_hash::FxHashMap;
 use tokio::{
     sync::{mpsc::error::TrySendError, oneshot},
-    time::Interval,
+    time::{Instant, Interval},
 };
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::PollSender;
@@ -841,7 +841,7 @@ mod tests {
             tokio::task::spawn(start_pending_incoming_session(
                 disconnect_rx,
                 session_id,
-                stream,
+                stream.into(),
                 pending_sessions_tx,
                 remote_addr,
                 self.secret_key,
@@ -1,6 +1,8 @@
 //! Connection types for a session
 
 use std::{
+    io,
+    net::SocketAddr,
     pin::Pin,
     task::{Context, Poll},
 };
@@ -13,14 +15,93 @@ use reth_eth_wire::{
     multiplex::{ProtocolProxy, RlpxSatelliteStream},
     EthMessage, EthStream, EthVersion, P2PStream,
 };
-use tokio::net::TcpStream;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
 
 /// The type of the underlying peer network connection.
-pub type EthPeerConnection = EthStream<P2PStream<ECIESStream<TcpStream>>>;
+pub type EthPeerConnection = EthStream<P2PStream<ECIESStream<PeerStream>>>;
 
 /// Various connection types that at least support the ETH protocol.
 pub type EthSatelliteConnection =
-    RlpxSatelliteStream<ECIESStream<TcpStream>, EthStream<ProtocolProxy>>;
+    RlpxSatelliteStream<ECIESStream<PeerStream>, EthStream<ProtocolProxy>>;
+
+/// The transport a session is established on.
+///
+/// This is always a [`TcpStream`] outside of tests. With the `test-utils` feature enabled sessions
+/// can also be established over an in-memory pipe, see
+/// [`MockNetwork`](crate::test_utils::MockNetwork).
+#[derive(Debug)]
+pub enum PeerStream {
+    /// A TCP connection.
+    Tcp(TcpStream),
+    /// An in-memory connection.
+    #[cfg(any(test, feature = "test-utils"))]
+    InMemory(tokio::io::DuplexStream),
+}
+
+impl PeerStream {
+    /// Returns the local address of the connection, if any.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp(stream) => stream.local_addr().ok(),
+            #[cfg(any(test, feature = "test-utils"))]
+            Self::InMemory(_) => None,
+        }
+    }
+}
+
+impl From<TcpStream> for PeerStream {
+    fn from(stream: TcpStream) -> Self {
+        Self::Tcp(stream)
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl From<tokio::io::DuplexStream> for PeerStream {
+    fn from(stream: tokio::io::DuplexStream) -> Self {
+        Self::InMemory(stream)
+    }
+}
+
+macro_rules! delegate_io {
+    ($self:ident.$method:ident($($args:ident),*)) => {
+        match $self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).$method($($args),*),
+            #[cfg(any(test, feature = "test-utils"))]
+            Self::InMemory(stream) => Pin::new(stream).$method($($args),*),
+        }
+    }
+}
+
+impl AsyncRead for PeerStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        delegate_io!(self.poll_read(cx, buf))
+    }
+}
+
+impl AsyncWrite for PeerStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        delegate_io!(self.poll_write(cx, buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        delegate_io!(self.poll_flush(cx))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        delegate_io!(self.poll_shutdown(cx))
+    }
+}
 
 /// Connection types that support the ETH protocol.
 ///
@@ -49,7 +130,7 @@ impl EthRlpxConnection {
 
     /// Consumes this type and returns the wrapped [`P2PStream`].
     #[inline]
-    pub(crate) fn into_inner(self) -> P2PStream<ECIESStream<TcpStream>> {
+    pub(crate) fn into_inner(self) -> P2PStream<ECIESStream<PeerStream>> {
         match self {
             Self::EthOnly(conn) => conn.into_inner(),
             Self::Satellite(conn) => conn.into_inner(),
@@ -58,7 +139,7 @@ impl EthRlpxConnection {
 
     /// Returns mutable access to the underlying stream.
     #[inline]
-    pub(crate) fn inner_mut(&mut self) -> &mut P2PStream<ECIESStream<TcpStream>> {
+    pub(crate) fn inner_mut(&mut self) -> &mut P2PStream<ECIESStream<PeerStream>> {
         match self {
             Self::EthOnly(conn) => conn.inner_mut(),
             Self::Satellite(conn) => conn.inner_mut(),
@@ -67,7 +148,7 @@ impl EthRlpxConnection {
 
     /// Returns  access to the underlying stream.
     #[inline]
-    pub(crate) const fn inner(&self) -> &P2PStream<ECIESStream<TcpStream>> {
+    pub(crate) const fn inner(&self) -> &P2PStream<ECIESStream<PeerStream>> {
         match self {
             Self::EthOnly(conn) => conn.inner(),
             Self::Satellite(conn) => conn.inner(),
@@ -5,7 +5,7 @@ mod conn;
 mod counter;
 mod handle;
 
-pub use conn::EthRlpxConnection;
+pub use conn::{EthRlpxConnection, PeerStream};
 pub use handle::{
     ActiveSessionHandle, ActiveSessionMessage, PendingSessionEvent, PendingSessionHandle,
     SessionCommand,
@@ -219,13 +219,13 @@ impl SessionManager {
         transition
     }
 
-    /// An incoming TCP connection was received. This starts the authentication process to turn this
+    /// An incoming connection was received. This starts the authentication process to turn this
     /// stream into an active peer session.
     ///
     /// Returns an error if the configured limit has been reached.
     pub(crate) fn on_incoming(
         &mut self,
-        stream: TcpStream,
+        stream: PeerStream,
         remote_addr: SocketAddr,
     ) -> Result<SessionId, ExceedsSessionLimit> {
         self.counter.ensure_pending_inbound()?;
@@ -777,7 +777,7 @@ pub(crate) async fn pending_session_with_timeout<F>(
 pub(crate) async fn start_pending_incoming_session(
     disconnect_rx: oneshot::Receiver<()>,
     session_id: SessionId,
-    stream: TcpStream,
+    stream: PeerStream,
     events: mpsc::Sender<PendingSessionEvent>,
     remote_addr: SocketAddr,
     secret_key: SecretKey,
@@ -822,7 +822,7 @@ async fn start_pending_outbound_session(
             if let Err(err) = stream.set_nodelay(true) {
                 tracing::warn!(target: "net::session", "set nodelay failed: {:?}", err);
             }
-            stream
+            PeerStream::Tcp(stream)
         }
         Err(error) => {
             let _ = events
@@ -857,7 +857,7 @@ async fn start_pending_outbound_session(
 async fn authenticate(
     disconnect_rx: oneshot::Receiver<()>,
     events: mpsc::Sender<PendingSessionEvent>,
-    stream: TcpStream,
+    stream: PeerStream,
     session_id: SessionId,
     remote_addr: SocketAddr,
     secret_key: SecretKey,
@@ -867,7 +867,7 @@ async fn authenticate(
     fork_filter: ForkFilter,
     extra_handlers: RlpxSubProtocolHandlers,
 ) {
-    let local_addr = stream.local_addr().ok();
+    let local_addr = stream.local_addr();
     let stream = match get_eciess_stream(stream, secret_key, direction).await {
         Ok(stream) => stream,
         Err(error) => {
@@ -938,7 +938,7 @@ async fn get_eciess_stream<Io: AsyncRead + AsyncWrite + Unpin>(
 /// also negotiate the additional protocols.
 #[allow(clippy::too_many_arguments)]
 async fn authenticate_stream(
-    stream: UnauthedP2PStream<ECIESStream<TcpStream>>,
+    stream: UnauthedP2PStream<ECIESStream<PeerStream>>,
     session_id: SessionId,
     remote_addr: SocketAddr,
     local_addr: Option<SocketAddr>,
@@ -114,6 +114,11 @@ impl NetworkState {
         &mut self.peers_manager
     }
 
+    /// Returns access to the [`Discovery`]
+    pub(crate) const fn discovery(&self) -> &Discovery {
+        &self.discovery
+    }
+
     /// Returns mutable access to the [`Discovery`]
     pub(crate) fn discovery_mut(&mut self) -> &mut Discovery {
         &mut self.discovery
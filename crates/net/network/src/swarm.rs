@@ -19,7 +19,10 @@ use crate::{
     message::PeerMessage,
     peers::InboundConnectionError,
     protocol::IntoRlpxSubProtocol,
-    session::{Direction, PendingSessionHandshakeError, SessionEvent, SessionId, SessionManager},
+    session::{
+        Direction, PeerStream, PendingSessionHandshakeError, SessionEvent, SessionId,
+        SessionManager,
+    },
     state::{NetworkState, StateAction},
 };
 
@@ -182,42 +185,50 @@ impl Swarm {
     /// Depending on the event, this will produce a new [`SwarmEvent`].
     fn on_connection(&mut self, event: ListenerEvent) -> Option<SwarmEvent> {
         match event {
-            ListenerEvent::Error(err) => return Some(SwarmEvent::TcpListenerError(err)),
+            ListenerEvent::Error(err) => Some(SwarmEvent::TcpListenerError(err)),
             ListenerEvent::ListenerClosed { local_address: address } => {
-                return Some(SwarmEvent::TcpListenerClosed { remote_addr: address })
+                Some(SwarmEvent::TcpListenerClosed { remote_addr: address })
             }
             ListenerEvent::Incoming { stream, remote_addr } => {
-                // Reject incoming connection if node is shutting down.
-                if self.is_shutting_down() {
-                    return None
+                self.on_incoming(stream.into(), remote_addr)
+            }
+        }
+    }
+
+    /// Handles a new incoming connection from the given address.
+    ///
+    /// If the connection is accepted, this starts a new pending session.
+    pub(crate) fn on_incoming(
+        &mut self,
+        stream: PeerStream,
+        remote_addr: SocketAddr,
+    ) -> Option<SwarmEvent> {
+        // Reject incoming connection if node is shutting down.
+        if self.is_shutting_down() {
+            return None
+        }
+        // ensure we can handle an incoming connection from this address
+        if let Err(err) = self.state_mut().peers_mut().on_incoming_pending_session(remote_addr.ip())
+        {
+            match err {
+                InboundConnectionError::IpBanned => {
+                    trace!(target: "net", ?remote_addr, "The incoming ip address is in the ban list");
                 }
-                // ensure we can handle an incoming connection from this address
-                if let Err(err) =
-                    self.state_mut().peers_mut().on_incoming_pending_session(remote_addr.ip())
-                {
-                    match err {
-                        InboundConnectionError::IpBanned => {
-                            trace!(target: "net", ?remote_addr, "The incoming ip address is in the ban list");
-                        }
-                        InboundConnectionError::ExceedsCapacity => {
-                            trace!(target: "net", ?remote_addr, "No capacity for incoming connection");
-                        }
-                    }
-                    return None
+                InboundConnectionError::ExceedsCapacity => {
+                    trace!(target: "net", ?remote_addr, "No capacity for incoming connection");
                 }
+            }
+            return None
+        }
 
-                match self.sessions.on_incoming(stream, remote_addr) {
-                    Ok(session_id) => {
-                        trace!(target: "net", ?remote_addr, "Incoming connection");
-                        return Some(SwarmEvent::IncomingTcpConnection { session_id, remote_addr })
-                    }
-                    Err(err) => {
-                        trace!(target: "net", %err, "Incoming connection rejected, capacity already reached.");
-                        self.state_mut()
-                            .peers_mut()
-                            .on_incoming_pending_session_rejected_internally();
-                    }
-                }
+        match self.sessions.on_incoming(stream, remote_addr) {
+            Ok(session_id) => {
+                trace!(target: "net", ?remote_addr, "Incoming connection");
+                return Some(SwarmEvent::IncomingTcpConnection { session_id, remote_addr })
+            }
+            Err(err) => {
+                trace!(target: "net", %err, "Incoming connection rejected, capacity already reached.");
+                self.state_mut().peers_mut().on_incoming_pending_session_rejected_internally();
             }
         }
         None
@@ -0,0 +1,372 @@
+//! An in-memory network for deterministic multi-peer scenarios.
+//!
+//! Unlike [`Testnet`](super::Testnet), which connects real nodes over TCP, the [`MockNetwork`]
+//! runs a single node whose sessions are established over in-memory pipes. The remote end of each
+//! session is a scripted [`MockPeer`] that can send arbitrary, including malformed, messages.
+//!
+//! Everything runs on the tokio runtime of the test, so if the runtime's clock is paused (e.g.
+//! `#[tokio::test(start_paused = true)]`) timeouts can be triggered deterministically via
+//! [`MockNetwork::advance`].
+
+use std::{
+    fmt,
+    future::poll_fn,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    task::Poll,
+    time::Duration,
+};
+
+use alloy_rlp::Bytes;
+use futures::{FutureExt, SinkExt, StreamExt};
+use reth_ecies::stream::ECIESStream;
+use reth_eth_wire::{
+    errors::EthStreamError, CanDisconnect, DisconnectReason, EthMessage, EthMessageID, EthStream,
+    HelloMessageWithProtocols, P2PStream, Status, UnauthedEthStream, UnauthedP2PStream,
+};
+use reth_network_api::{
+    test_utils::PeersHandleProvider, NetworkEvent, NetworkEventListenerProvider, Peers,
+};
+use reth_network_p2p::sync::{NetworkSyncUpdater, SyncState};
+use reth_network_peers::{pk2id, PeerId};
+use reth_network_types::ReputationChangeKind;
+use reth_primitives::ForkFilter;
+use reth_provider::test_utils::NoopProvider;
+use reth_tokio_util::EventStream;
+use reth_transaction_pool::{
+    test_utils::{testing_pool, TestPool},
+    TransactionPool,
+};
+use secp256k1::{SecretKey, SECP256K1};
+use tokio::{io::DuplexStream, sync::mpsc, task::JoinHandle};
+
+use crate::{
+    transactions::{TransactionsHandle, TransactionsManagerConfig},
+    NetworkConfigBuilder, NetworkHandle, NetworkManager, PeerStream,
+};
+
+/// The buffer size of the in-memory pipe of a session.
+const MOCK_STREAM_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// The authenticated stream of a [`MockPeer`].
+type MockPeerStream = EthStream<P2PStream<ECIESStream<DuplexStream>>>;
+
+/// A single node whose sessions are established over in-memory pipes.
+///
+/// The node runs a [`NetworkManager`] with an installed
+/// [`TransactionsManager`](crate::transactions::TransactionsManager) and request handler, all
+/// driven by a single task.
+pub struct MockNetwork<Pool = TestPool> {
+    /// Handle to the node's network.
+    handle: NetworkHandle,
+    /// Handle to the node's transactions manager.
+    transactions: TransactionsHandle,
+    /// The pool of the node.
+    pool: Pool,
+    /// The hello message of the node, used as template for the hello of mock peers.
+    hello: HelloMessageWithProtocols,
+    /// The status of the node, mock peers respond with the same status.
+    status: Status,
+    /// The fork filter of the node.
+    fork_filter: ForkFilter,
+    /// Sender half to hand new incoming connections to the node.
+    to_network: mpsc::UnboundedSender<(PeerStream, SocketAddr)>,
+    /// Counter used to assign each mock peer a unique remote address.
+    next_peer: u32,
+    /// The task driving the node.
+    task: JoinHandle<()>,
+}
+
+// === impl MockNetwork ===
+
+impl MockNetwork<TestPool> {
+    /// Launches a new node with a [`TestPool`].
+    pub async fn new() -> Self {
+        Self::with_pool(testing_pool()).await
+    }
+}
+
+impl<Pool> MockNetwork<Pool>
+where
+    Pool: TransactionPool + Clone + Unpin + 'static,
+{
+    /// Launches a new node with the given pool.
+    pub async fn with_pool(pool: Pool) -> Self {
+        Self::with_pool_and_config(pool, TransactionsManagerConfig::default()).await
+    }
+
+    /// Launches a new node with the given pool and transactions manager configuration.
+    pub async fn with_pool_and_config(
+        pool: Pool,
+        transactions_manager_config: TransactionsManagerConfig,
+    ) -> Self {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let config = NetworkConfigBuilder::new(secret_key)
+            .listener_addr(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+            .disable_discovery()
+            .build(NoopProvider::default());
+
+        let hello = config.hello_message.clone();
+        let status = config.status;
+        let fork_filter = config.fork_filter.clone();
+
+        let (handle, mut network, mut transactions, mut request_handler) =
+            NetworkManager::builder(config)
+                .await
+                .expect("failed to launch mock network")
+                .transactions(pool.clone(), transactions_manager_config)
+                .request_handler(NoopProvider::default())
+                .split_with_handle();
+
+        // transactions are ignored while the node is syncing
+        handle.update_sync_state(SyncState::Idle);
+
+        let transactions_handle = transactions.handle();
+        let (to_network, mut incoming) = mpsc::unbounded_channel::<(PeerStream, SocketAddr)>();
+
+        // drive all components on a single task
+        let task = tokio::spawn(poll_fn(move |cx| {
+            while let Poll::Ready(Some((stream, remote_addr))) = incoming.poll_recv(cx) {
+                network.on_incoming_stream(stream, remote_addr);
+            }
+            let _ = transactions.poll_unpin(cx);
+            let _ = request_handler.poll_unpin(cx);
+            network.poll_unpin(cx)
+        }));
+
+        Self {
+            handle,
+            transactions: transactions_handle,
+            pool,
+            hello,
+            status,
+            fork_filter,
+            to_network,
+            next_peer: 0,
+            task,
+        }
+    }
+
+    /// Returns the [`NetworkHandle`] of the node.
+    pub const fn handle(&self) -> &NetworkHandle {
+        &self.handle
+    }
+
+    /// Returns the [`PeerId`] of the node.
+    pub fn peer_id(&self) -> PeerId {
+        *self.handle.peer_id()
+    }
+
+    /// Returns the [`TransactionsHandle`] of the node.
+    pub const fn transactions_handle(&self) -> &TransactionsHandle {
+        &self.transactions
+    }
+
+    /// Returns the pool of the node.
+    pub const fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    /// Returns a new stream of the node's [`NetworkEvent`]s.
+    pub fn event_listener(&self) -> EventStream<NetworkEvent> {
+        self.handle.event_listener()
+    }
+
+    /// Returns the current reputation the node assigns to the given peer, if the peer is known.
+    pub async fn reputation(&self, peer_id: PeerId) -> Option<i32> {
+        self.handle.peers_handle().peer_by_id(peer_id).await.map(|peer| peer.reputation)
+    }
+
+    /// Applies a reputation change to the given peer, as if the node had observed it.
+    pub fn reputation_change(&self, peer_id: PeerId, kind: ReputationChangeKind) {
+        self.handle.reputation_change(peer_id, kind)
+    }
+
+    /// Advances the paused clock of the runtime by the given duration.
+    ///
+    /// # Panics
+    ///
+    /// If the clock of the runtime is not paused.
+    pub async fn advance(&self, duration: Duration) {
+        tokio::time::advance(duration).await
+    }
+
+    /// Connects a new [`MockPeer`] with a random key to the node.
+    pub async fn connect_peer(&mut self) -> MockPeer {
+        self.connect_peer_with_key(SecretKey::new(&mut rand::thread_rng())).await
+    }
+
+    /// Connects a new [`MockPeer`] with the given key to the node.
+    ///
+    /// This returns once the `RLPx` and `eth` handshakes have completed from the peer's point of
+    /// view. The node emits [`NetworkEvent::SessionEstablished`] once it processed the session.
+    ///
+    /// # Panics
+    ///
+    /// If the node rejects the connection or any of the handshakes fail.
+    pub async fn connect_peer_with_key(&mut self, secret_key: SecretKey) -> MockPeer {
+        self.next_peer += 1;
+        let remote_addr =
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::from(0x0a00_0000 + self.next_peer)), 30303);
+        let (local, remote) = tokio::io::duplex(MOCK_STREAM_BUFFER_SIZE);
+        self.to_network.send((local.into(), remote_addr)).expect("mock network is running");
+
+        let peer_id = pk2id(&secret_key.public_key(SECP256K1));
+        let mut hello = self.hello.clone();
+        hello.id = peer_id;
+
+        let stream = ECIESStream::connect(remote, secret_key, self.peer_id())
+            .await
+            .expect("failed ecies handshake");
+        let (p2p_stream, _) =
+            UnauthedP2PStream::new(stream).handshake(hello).await.expect("failed p2p handshake");
+        let (eth_stream, _) = UnauthedEthStream::new(p2p_stream)
+            .handshake(self.status, self.fork_filter.clone())
+            .await
+            .expect("failed eth handshake");
+
+        MockPeer::spawn(peer_id, remote_addr, eth_stream)
+    }
+}
+
+impl<Pool> Drop for MockNetwork<Pool> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl<Pool> fmt::Debug for MockNetwork<Pool> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockNetwork")
+            .field("peer_id", self.handle.peer_id())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Events received by a [`MockPeer`] from the node.
+#[derive(Debug)]
+pub enum MockPeerEvent {
+    /// Received a message.
+    Message(EthMessage),
+    /// The session was closed, either gracefully or with the given error.
+    Closed(Option<EthStreamError>),
+}
+
+/// Commands sent to the task of a [`MockPeer`].
+#[derive(Debug)]
+enum MockPeerCommand {
+    /// Send the message.
+    Send(EthMessage),
+    /// Send the raw message bytes, the first byte is the message id.
+    SendRaw(Bytes),
+    /// Disconnect from the node.
+    Disconnect(DisconnectReason),
+}
+
+/// The remote end of a session with the [`MockNetwork`].
+///
+/// The peer's stream is driven by a dedicated task, so `Ping` messages are answered even if the
+/// peer isn't polled.
+#[derive(Debug)]
+pub struct MockPeer {
+    /// The identifier of the peer.
+    peer_id: PeerId,
+    /// The address the node sees for this peer.
+    remote_addr: SocketAddr,
+    /// Sender half for commands to the peer's task.
+    commands: mpsc::UnboundedSender<MockPeerCommand>,
+    /// Events received by the peer's task.
+    events: mpsc::UnboundedReceiver<MockPeerEvent>,
+}
+
+// === impl MockPeer ===
+
+impl MockPeer {
+    /// Spawns the task that drives the given stream.
+    fn spawn(peer_id: PeerId, remote_addr: SocketAddr, mut stream: MockPeerStream) -> Self {
+        let (commands_tx, mut commands) = mpsc::unbounded_channel();
+        let (events_tx, events) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let error = loop {
+                tokio::select! {
+                    cmd = commands.recv() => {
+                        let res = match cmd {
+                            Some(MockPeerCommand::Send(msg)) => stream.send(msg).await,
+                            Some(MockPeerCommand::SendRaw(bytes)) => {
+                                stream.inner_mut().send(bytes).await.map_err(Into::into)
+                            }
+                            Some(MockPeerCommand::Disconnect(reason)) => {
+                                let _ = stream.disconnect(reason).await;
+                                break None
+                            }
+                            None => {
+                                let _ = stream.disconnect(DisconnectReason::DisconnectRequested).await;
+                                break None
+                            }
+                        };
+                        if let Err(err) = res {
+                            break Some(err)
+                        }
+                    }
+                    msg = stream.next() => match msg {
+                        Some(Ok(msg)) => {
+                            let _ = events_tx.send(MockPeerEvent::Message(msg));
+                        }
+                        Some(Err(err)) => break Some(err),
+                        None => break None,
+                    }
+                }
+            };
+            let _ = events_tx.send(MockPeerEvent::Closed(error));
+        });
+
+        Self { peer_id, remote_addr, commands: commands_tx, events }
+    }
+
+    /// Returns the identifier of the peer.
+    pub const fn peer_id(&self) -> PeerId {
+        self.peer_id
+    }
+
+    /// Returns the address the node sees for this peer.
+    pub const fn remote_addr(&self) -> SocketAddr {
+        self.remote_addr
+    }
+
+    /// Sends the message to the node.
+    pub fn send(&self, msg: EthMessage) {
+        let _ = self.commands.send(MockPeerCommand::Send(msg));
+    }
+
+    /// Sends a message with the given id and raw payload to the node.
+    ///
+    /// The payload is sent as is, which allows sending malformed messages.
+    pub fn send_raw(&self, id: EthMessageID, payload: impl AsRef<[u8]>) {
+        let mut msg = Vec::with_capacity(payload.as_ref().len() + 1);
+        msg.push(id as u8);
+        msg.extend_from_slice(payload.as_ref());
+        let _ = self.commands.send(MockPeerCommand::SendRaw(msg.into()));
+    }
+
+    /// Disconnects from the node with the given reason.
+    pub fn disconnect(&self, reason: DisconnectReason) {
+        let _ = self.commands.send(MockPeerCommand::Disconnect(reason));
+    }
+
+    /// Returns the next event received from the node.
+    ///
+    /// Returns `None` after the session was closed.
+    pub async fn next_event(&mut self) -> Option<MockPeerEvent> {
+        self.events.recv().await
+    }
+
+    /// Returns the next message received from the node.
+    ///
+    /// Returns `None` once the session is closed.
+    pub async fn next_message(&mut self) -> Option<EthMessage> {
+        match self.next_event().await? {
+            MockPeerEvent::Message(msg) => Some(msg),
+            MockPeerEvent::Closed(_) => None,
+        }
+    }
+}
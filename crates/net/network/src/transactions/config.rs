@@ -1,7 +1,8 @@
 use derive_more::Constructor;
+use reth_transaction_pool::rate_limit::RateLimitConfig;
 
 use super::{
-    DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
+    TransactionPropagationKind, DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
     DEFAULT_SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESP_ON_PACK_GET_POOLED_TRANSACTIONS_REQ,
     SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESPONSE,
 };
@@ -17,6 +18,12 @@ pub struct TransactionsManagerConfig {
     pub transaction_fetcher_config: TransactionFetcherConfig,
     /// Max number of seen transactions to store for each peer.
     pub max_transactions_seen_by_peer_history: u32,
+    /// Limits how many transactions of each peer are imported into the pool, unlimited if `None`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub peer_rate_limit: Option<RateLimitConfig>,
+    /// Decides which peers transactions are propagated to.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub propagation_policy: TransactionPropagationKind,
 }
 
 impl Default for TransactionsManagerConfig {
@@ -24,6 +31,8 @@ impl Default for TransactionsManagerConfig {
         Self {
             transaction_fetcher_config: TransactionFetcherConfig::default(),
             max_transactions_seen_by_peer_history: DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
+            peer_rate_limit: None,
+            propagation_policy: TransactionPropagationKind::default(),
         }
     }
 }
@@ -6,6 +6,7 @@ pub mod config;
 pub mod constants;
 /// Component responsible for fetching transactions from [`NewPooledTransactionHashes`].
 pub mod fetcher;
+pub mod policy;
 pub mod validation;
 
 pub use self::constants::{
@@ -13,6 +14,10 @@ pub use self::constants::{
     SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESPONSE,
 };
 pub use config::{TransactionFetcherConfig, TransactionsManagerConfig};
+pub use policy::{
+    DefaultPropagationPolicy, ParsePropagationKindError, PropagationDecision, SenderAllowList,
+    TransactionPropagationKind, TransactionPropagationPolicy, TrustedPeersOnly, WithholdOrigins,
+};
 pub use validation::*;
 
 pub(crate) use fetcher::{FetchEvent, TransactionFetcher};
@@ -46,15 +51,17 @@ use reth_network_p2p::{
     sync::SyncStateProvider,
 };
 use reth_network_peers::PeerId;
-use reth_network_types::ReputationChangeKind;
+use reth_network_types::{PeerKind, ReputationChangeKind};
 use reth_primitives::{
-    PooledTransactionsElement, TransactionSigned, TransactionSignedEcRecovered, TxHash, B256,
+    Address, PooledTransactionsElement, TransactionSigned, TransactionSignedEcRecovered, TxHash,
+    B256,
 };
 use reth_tokio_util::EventStream;
 use reth_transaction_pool::{
     error::{PoolError, PoolResult},
+    rate_limit::KeyedRateLimiter,
     GetPooledTransactionLimit, PoolTransaction, PropagateKind, PropagatedTransactions,
-    TransactionPool, ValidPoolTransaction,
+    TransactionOrigin, TransactionPool, ValidPoolTransaction,
 };
 use tokio::sync::{mpsc, oneshot, oneshot::error::RecvError};
 use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
@@ -249,6 +256,10 @@ pub struct TransactionsManager<Pool> {
     transaction_events: UnboundedMeteredReceiver<NetworkTransactionEvent>,
     /// Max number of seen transactions to store for each peer.
     max_transactions_seen_by_peer_history: u32,
+    /// Decides per transaction and per peer how transactions are propagated.
+    propagation_policy: Box<dyn TransactionPropagationPolicy>,
+    /// Limits the number of transactions imported per peer, if configured.
+    peer_rate_limiter: Option<KeyedRateLimiter<PeerId>>,
     /// `TransactionsManager` metrics
     metrics: TransactionsManagerMetrics,
 }
@@ -301,9 +312,29 @@ impl<Pool: TransactionPool> TransactionsManager<Pool> {
             ),
             max_transactions_seen_by_peer_history: transactions_manager_config
                 .max_transactions_seen_by_peer_history,
+            propagation_policy: transactions_manager_config.propagation_policy.into_policy(),
+            peer_rate_limiter: transactions_manager_config
+                .peer_rate_limit
+                .map(KeyedRateLimiter::new),
             metrics,
         }
     }
+
+    /// Sets the [`TransactionPropagationPolicy`] that decides how transactions are propagated to
+    /// peers.
+    ///
+    /// By default the policy is selected by
+    /// [`TransactionsManagerConfig::propagation_policy`].
+    pub fn with_propagation_policy(mut self, policy: impl TransactionPropagationPolicy) -> Self {
+        self.set_propagation_policy(policy);
+        self
+    }
+
+    /// Replaces the [`TransactionPropagationPolicy`] that decides how transactions are propagated
+    /// to peers.
+    pub fn set_propagation_policy(&mut self, policy: impl TransactionPropagationPolicy) {
+        self.propagation_policy = Box::new(policy);
+    }
 }
 
 // === impl TransactionsManager ===
@@ -415,6 +446,9 @@ where
     /// The message for new pooled hashes depends on the negotiated version of the stream.
     /// See [`NewPooledTransactionHashes`]
     ///
+    /// How a transaction is propagated to a peer is subject to the configured
+    /// [`TransactionPropagationPolicy`].
+    ///
     /// Note: EIP-4844 are disallowed from being broadcast in full and are only ever sent as hashes, see also <https://eips.ethereum.org/EIPS/eip-4844#networking>.
     fn propagate_transactions(
         &mut self,
@@ -431,21 +465,26 @@ where
 
         // Note: Assuming ~random~ order due to random state of the peers map hasher
         for (peer_idx, (peer_id, peer)) in self.peers.iter_mut().enumerate() {
-            // determine whether to send full tx objects or hashes.
-            let mut builder = if peer_idx > max_num_full {
-                PropagateTransactionsBuilder::pooled(peer.version)
-            } else {
-                PropagateTransactionsBuilder::full(peer.version)
-            };
+            // determine whether to send full tx objects or hashes by default.
+            let full_by_default = peer_idx <= max_num_full;
+            let mut builder = FullTransactionsBuilder::new(peer.version);
 
             // Iterate through the transactions to propagate and fill the hashes and full
             // transaction lists, before deciding whether or not to send full transactions to the
             // peer.
             for tx in &to_propagate {
                 // Only proceed if the transaction is not in the peer's list of seen transactions
-                if !peer.seen_transactions.contains(&tx.hash()) {
+                if peer.seen_transactions.contains(&tx.hash()) {
+                    continue
+                }
+
+                match self.propagation_policy.decide(peer_id, peer, tx).send_full(full_by_default) {
+                    Some(true) => builder.push(tx),
                     // add transaction to the list of hashes to propagate
-                    builder.push(tx);
+                    Some(false) => builder.push_pooled(tx),
+                    None => {
+                        trace!(target: "net::tx", ?peer_id, hash=?tx.hash(), "Withholding transaction from peer");
+                    }
                 }
             }
 
@@ -516,8 +555,13 @@ where
 
         // Iterate through the transactions to propagate and fill the hashes and full transaction
         for tx in to_propagate {
-            if !peer.seen_transactions.contains(&tx.hash()) {
-                full_transactions.push(&tx);
+            if peer.seen_transactions.contains(&tx.hash()) {
+                continue
+            }
+            match self.propagation_policy.decide(&peer_id, peer, &tx).send_full(true) {
+                Some(true) => full_transactions.push(&tx),
+                Some(false) => full_transactions.push_pooled(&tx),
+                None => {}
             }
         }
 
@@ -579,6 +623,9 @@ where
             let mut hashes = PooledTransactionsHashesBuilder::new(peer.version);
 
             for tx in to_propagate {
+                if self.propagation_policy.decide(&peer_id, peer, &tx).is_withhold() {
+                    continue
+                }
                 if !peer.seen_transactions.insert(tx.hash()) {
                     hashes.push(&tx);
                 }
@@ -907,13 +954,19 @@ where
                 self.peers.remove(&peer_id);
             }
             NetworkEvent::SessionEstablished {
-                peer_id, client_version, messages, version, ..
+                peer_id,
+                client_version,
+                messages,
+                version,
+                peer_kind,
+                ..
             } => {
                 // Insert a new peer into the peerset.
                 let peer = PeerMetadata::new(
                     messages,
                     version,
                     client_version,
+                    peer_kind,
                     self.max_transactions_seen_by_peer_history,
                 );
                 let peer = match self.peers.entry(peer_id) {
@@ -940,9 +993,17 @@ where
                 }
 
                 let mut msg_builder = PooledTransactionsHashesBuilder::new(version);
-                for pooled_tx in pooled_txs {
-                    peer.seen_transactions.insert(*pooled_tx.hash());
-                    msg_builder.push_pooled(pooled_tx);
+                for tx in pooled_txs.into_iter().map(PropagateTransaction::new) {
+                    if self.propagation_policy.decide(&peer_id, peer, &tx).is_withhold() {
+                        continue
+                    }
+                    peer.seen_transactions.insert(tx.hash());
+                    msg_builder.push(&tx);
+                }
+
+                if msg_builder.is_empty() {
+                    // all transactions are withheld from this peer
+                    return
                 }
 
                 let msg = msg_builder.build();
@@ -997,6 +1058,7 @@ where
 
         // tracks the quality of the given transactions
         let mut has_bad_transactions = false;
+        let mut num_rate_limited = 0;
 
         // 2. filter out transactions that are invalid or already pending import
         if let Some(peer) = self.peers.get_mut(&peer_id) {
@@ -1024,7 +1086,16 @@ where
                         entry.get_mut().insert(peer_id);
                     }
                     Entry::Vacant(entry) => {
-                        if !self.bad_imports.contains(tx.hash()) {
+                        if self
+                            .peer_rate_limiter
+                            .as_ref()
+                            .is_some_and(|limiter| !limiter.try_acquire(peer_id))
+                        {
+                            // the peer sends more transactions than it is allowed to, drop the
+                            // transaction without marking it as imported so it can still be
+                            // imported from another peer
+                            num_rate_limited += 1;
+                        } else if !self.bad_imports.contains(tx.hash()) {
                             // this is a new transaction that should be imported into the pool
                             let pool_transaction = Pool::Transaction::from_pooled(tx);
                             new_txs.push(pool_transaction);
@@ -1044,6 +1115,11 @@ where
             }
             new_txs.shrink_to_fit();
 
+            if num_rate_limited > 0 {
+                self.metrics.occurrences_transactions_rate_limited.increment(num_rate_limited);
+                trace!(target: "net::tx", num_txs=%num_rate_limited, ?peer_id, client=?peer.client_version, "Peer exceeded its transaction rate limit");
+            }
+
             // 3. import new transactions as a batch to minimize lock contention on the underlying
             // pool
             if !new_txs.is_empty() {
@@ -1384,71 +1460,50 @@ where
 
 /// A transaction that's about to be propagated to multiple peers.
 #[derive(Debug, Clone)]
-struct PropagateTransaction {
+pub struct PropagateTransaction {
     size: usize,
     transaction: Arc<TransactionSigned>,
+    sender: Address,
+    origin: TransactionOrigin,
 }
 
 // === impl PropagateTransaction ===
 
 impl PropagateTransaction {
-    fn hash(&self) -> TxHash {
+    /// Returns the hash of the transaction.
+    pub fn hash(&self) -> TxHash {
         self.transaction.hash()
     }
 
-    /// Create a new instance from a pooled transaction
-    fn new<T: PoolTransaction<Consensus = TransactionSignedEcRecovered>>(
-        tx: Arc<ValidPoolTransaction<T>>,
-    ) -> Self {
-        let size = tx.encoded_length();
-        let transaction = Arc::new(tx.transaction.clone().into_consensus().into_signed());
-        Self { size, transaction }
+    /// Returns the encoded length of the transaction.
+    pub const fn size(&self) -> usize {
+        self.size
     }
-}
-
-/// Helper type to construct the appropriate message to send to the peer based on whether the peer
-/// should receive them in full or as pooled
-#[derive(Debug, Clone)]
-enum PropagateTransactionsBuilder {
-    Pooled(PooledTransactionsHashesBuilder),
-    Full(FullTransactionsBuilder),
-}
 
-impl PropagateTransactionsBuilder {
-    /// Create a builder for pooled transactions
-    fn pooled(version: EthVersion) -> Self {
-        Self::Pooled(PooledTransactionsHashesBuilder::new(version))
+    /// Returns the signed transaction.
+    pub fn transaction(&self) -> &TransactionSigned {
+        &self.transaction
     }
 
-    /// Create a builder that sends transactions in full and records transactions that don't fit.
-    fn full(version: EthVersion) -> Self {
-        Self::Full(FullTransactionsBuilder::new(version))
+    /// Returns the recovered sender of the transaction.
+    pub const fn sender(&self) -> Address {
+        self.sender
     }
 
-    /// Appends a transaction to the list.
-    fn push(&mut self, transaction: &PropagateTransaction) {
-        match self {
-            Self::Pooled(builder) => builder.push(transaction),
-            Self::Full(builder) => builder.push(transaction),
-        }
-    }
-
-    /// Returns true if no transactions are recorded.
-    fn is_empty(&self) -> bool {
-        match self {
-            Self::Pooled(builder) => builder.is_empty(),
-            Self::Full(builder) => builder.is_empty(),
-        }
+    /// Returns the origin of the transaction in the local pool.
+    pub const fn origin(&self) -> TransactionOrigin {
+        self.origin
     }
 
-    /// Consumes the type and returns the built messages that should be sent to the peer.
-    fn build(self) -> PropagateTransactions {
-        match self {
-            Self::Pooled(pooled) => {
-                PropagateTransactions { pooled: Some(pooled.build()), full: None }
-            }
-            Self::Full(full) => full.build(),
-        }
+    /// Create a new instance from a pooled transaction
+    fn new<T: PoolTransaction<Consensus = TransactionSignedEcRecovered>>(
+        tx: Arc<ValidPoolTransaction<T>>,
+    ) -> Self {
+        let size = tx.encoded_length();
+        let sender = tx.sender();
+        let origin = tx.origin;
+        let transaction = Arc::new(tx.transaction.clone().into_consensus().into_signed());
+        Self { size, transaction, sender, origin }
     }
 }
 
@@ -1521,6 +1576,11 @@ impl FullTransactionsBuilder {
         self.transactions.push(Arc::clone(&transaction.transaction));
     }
 
+    /// Appends a transaction to the list of transactions that are only announced to the peer.
+    fn push_pooled(&mut self, transaction: &PropagateTransaction) {
+        self.pooled.push(transaction)
+    }
+
     /// Returns whether or not any transactions are in the [`FullTransactionsBuilder`].
     fn is_empty(&self) -> bool {
         self.transactions.is_empty() && self.pooled.is_empty()
@@ -1545,18 +1605,6 @@ enum PooledTransactionsHashesBuilder {
 // === impl PooledTransactionsHashesBuilder ===
 
 impl PooledTransactionsHashesBuilder {
-    /// Push a transaction from the pool to the list.
-    fn push_pooled<T: PoolTransaction>(&mut self, pooled_tx: Arc<ValidPoolTransaction<T>>) {
-        match self {
-            Self::Eth66(msg) => msg.0.push(*pooled_tx.hash()),
-            Self::Eth68(msg) => {
-                msg.hashes.push(*pooled_tx.hash());
-                msg.sizes.push(pooled_tx.encoded_length());
-                msg.types.push(pooled_tx.transaction.tx_type());
-            }
-        }
-    }
-
     /// Returns whether or not any transactions are in the [`PooledTransactionsHashesBuilder`].
     fn is_empty(&self) -> bool {
         match self {
@@ -1622,6 +1670,8 @@ pub struct PeerMetadata {
     version: EthVersion,
     /// The peer's client version.
     client_version: Arc<str>,
+    /// The kind of the peer.
+    peer_kind: PeerKind,
 }
 
 impl PeerMetadata {
@@ -1630,6 +1680,7 @@ impl PeerMetadata {
         request_tx: PeerRequestSender,
         version: EthVersion,
         client_version: Arc<str>,
+        peer_kind: PeerKind,
         max_transactions_seen_by_peer: u32,
     ) -> Self {
         Self {
@@ -1637,8 +1688,24 @@ impl PeerMetadata {
             request_tx,
             version,
             client_version,
+            peer_kind,
         }
     }
+
+    /// Returns the negotiated version of the session.
+    pub const fn version(&self) -> EthVersion {
+        self.version
+    }
+
+    /// Returns the peer's client version.
+    pub fn client_version(&self) -> &str {
+        &self.client_version
+    }
+
+    /// Returns the kind of the peer.
+    pub const fn peer_kind(&self) -> PeerKind {
+        self.peer_kind
+    }
 }
 
 /// Commands to send to the [`TransactionsManager`]
@@ -1800,6 +1867,7 @@ mod tests {
                 PeerRequestSender::new(peer_id, to_mock_session_tx),
                 version,
                 Arc::from(""),
+                PeerKind::Basic,
                 DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
             ),
             to_mock_session_rx,
@@ -1855,6 +1923,7 @@ mod tests {
                     messages,
                     status,
                     version,
+                    peer_kind,
                 } => {
                     // to insert a new peer in transactions peerset
                     transactions.on_network_event(NetworkEvent::SessionEstablished {
@@ -1865,6 +1934,7 @@ mod tests {
                         messages,
                         status,
                         version,
+                        peer_kind,
                     })
                 }
                 NetworkEvent::PeerAdded(_peer_id) => continue,
@@ -1941,6 +2011,7 @@ mod tests {
                     messages,
                     status,
                     version,
+                    peer_kind,
                 } => {
                     // to insert a new peer in transactions peerset
                     transactions.on_network_event(NetworkEvent::SessionEstablished {
@@ -1951,6 +2022,7 @@ mod tests {
                         messages,
                         status,
                         version,
+                        peer_kind,
                     })
                 }
                 NetworkEvent::PeerAdded(_peer_id) => continue,
@@ -2025,6 +2097,7 @@ mod tests {
                     messages,
                     status,
                     version,
+                    peer_kind,
                 } => {
                     // to insert a new peer in transactions peerset
                     transactions.on_network_event(NetworkEvent::SessionEstablished {
@@ -2035,6 +2108,7 @@ mod tests {
                         messages,
                         status,
                         version,
+                        peer_kind,
                     })
                 }
                 NetworkEvent::PeerAdded(_peer_id) => continue,
@@ -2116,6 +2190,7 @@ mod tests {
                     messages,
                     status,
                     version,
+                    peer_kind,
                 } => transactions.on_network_event(NetworkEvent::SessionEstablished {
                     peer_id,
                     remote_addr,
@@ -2124,6 +2199,7 @@ mod tests {
                     messages,
                     status,
                     version,
+                    peer_kind,
                 }),
                 NetworkEvent::PeerAdded(_peer_id) => continue,
                 ev => {
@@ -2274,12 +2350,12 @@ mod tests {
 
     #[test]
     fn test_transaction_builder_empty() {
-        let mut builder = PropagateTransactionsBuilder::pooled(EthVersion::Eth68);
+        let mut builder = FullTransactionsBuilder::new(EthVersion::Eth68);
         assert!(builder.is_empty());
 
         let mut factory = MockTransactionFactory::default();
         let tx = PropagateTransaction::new(Arc::new(factory.create_eip1559()));
-        builder.push(&tx);
+        builder.push_pooled(&tx);
         assert!(!builder.is_empty());
 
         let txs = builder.build();
@@ -2290,7 +2366,7 @@ mod tests {
 
     #[test]
     fn test_transaction_builder_large() {
-        let mut builder = PropagateTransactionsBuilder::full(EthVersion::Eth68);
+        let mut builder = FullTransactionsBuilder::new(EthVersion::Eth68);
         assert!(builder.is_empty());
 
         let mut factory = MockTransactionFactory::default();
@@ -2318,7 +2394,7 @@ mod tests {
 
     #[test]
     fn test_transaction_builder_eip4844() {
-        let mut builder = PropagateTransactionsBuilder::full(EthVersion::Eth68);
+        let mut builder = FullTransactionsBuilder::new(EthVersion::Eth68);
         assert!(builder.is_empty());
 
         let mut factory = MockTransactionFactory::default();
@@ -2361,6 +2437,7 @@ mod tests {
             messages: PeerRequestSender::new(peer_id, tx),
             status: Arc::new(Default::default()),
             version: EthVersion::Eth68,
+            peer_kind: PeerKind::Basic,
         });
 
         let mut propagate = vec![];
@@ -2389,4 +2466,62 @@ mod tests {
         let propagated = tx_manager.propagate_transactions(propagate);
         assert!(propagated.0.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_propagation_policy() {
+        reth_tracing::init_test_tracing();
+
+        let (tx_manager, network) = new_tx_manager().await;
+        let mut tx_manager = tx_manager
+            .with_propagation_policy(TrustedPeersOnly::for_origins([TransactionOrigin::Local]));
+
+        // ensure not syncing
+        network.handle().update_sync_state(SyncState::Idle);
+
+        // mock a trusted and a basic peer
+        let trusted_peer = PeerId::random();
+        let basic_peer = PeerId::random();
+        for (peer_id, peer_kind) in
+            [(trusted_peer, PeerKind::Trusted), (basic_peer, PeerKind::Basic)]
+        {
+            let (tx, _rx) = mpsc::channel(1);
+            tx_manager.on_network_event(NetworkEvent::SessionEstablished {
+                peer_id,
+                remote_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
+                client_version: Arc::from(""),
+                capabilities: Arc::new(vec![].into()),
+                messages: PeerRequestSender::new(peer_id, tx),
+                status: Arc::new(Default::default()),
+                version: EthVersion::Eth68,
+                peer_kind,
+            });
+        }
+
+        let mut factory = MockTransactionFactory::default();
+        let local_tx = Arc::new(
+            factory.validated_with_origin(TransactionOrigin::Local, MockTransaction::eip1559()),
+        );
+        let external_tx = Arc::new(factory.create_eip1559());
+        let propagate = vec![
+            PropagateTransaction::new(local_tx.clone()),
+            PropagateTransaction::new(external_tx.clone()),
+        ];
+
+        let propagated = tx_manager.propagate_transactions(propagate);
+
+        // the local transaction is only sent to the trusted peer
+        let prop_txs = propagated.0.get(local_tx.transaction.hash()).unwrap();
+        assert_eq!(prop_txs.len(), 1);
+        assert_eq!(prop_txs[0].peer(), &trusted_peer);
+        assert!(!tx_manager
+            .peers
+            .get(&basic_peer)
+            .unwrap()
+            .seen_transactions
+            .contains(local_tx.transaction.hash()));
+
+        // the external transaction is propagated to all peers
+        let prop_txs = propagated.0.get(external_tx.transaction.hash()).unwrap();
+        assert_eq!(prop_txs.len(), 2);
+    }
 }
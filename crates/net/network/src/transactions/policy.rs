@@ -0,0 +1,330 @@
+//! Policies that decide how transactions are gossiped to peers.
+
+use std::{collections::HashSet, fmt, str::FromStr};
+
+use reth_network_peers::PeerId;
+use reth_primitives::Address;
+use reth_transaction_pool::TransactionOrigin;
+
+use super::{PeerMetadata, PropagateTransaction};
+
+/// Decides how a transaction is propagated to a specific peer.
+///
+/// The variants are ordered by precedence, if multiple policies are combined the decision with the
+/// highest precedence wins, see [`PropagationDecision::merge`]: any explicit decision overrides
+/// [`PropagationDecision::Default`], announcing overrides broadcasting and withholding overrides
+/// everything else.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PropagationDecision {
+    /// Follow the default rules of the [`TransactionsManager`](super::TransactionsManager).
+    ///
+    /// Full transactions are sent to a fraction (square root) of the connected peers and all other
+    /// peers only receive the hash.
+    #[default]
+    Default,
+    /// Broadcast the full transaction to the peer.
+    ///
+    /// Note: EIP-4844 transactions are never broadcast in full and are announced instead, see also
+    /// <https://eips.ethereum.org/EIPS/eip-4844#networking>.
+    Broadcast,
+    /// Only announce the hash of the transaction to the peer.
+    Announce,
+    /// Don't propagate the transaction to the peer.
+    Withhold,
+}
+
+// === impl PropagationDecision ===
+
+impl PropagationDecision {
+    /// Combines two decisions, keeping the one with the higher precedence.
+    pub fn merge(self, other: Self) -> Self {
+        self.max(other)
+    }
+
+    /// Returns `true` if the transaction must not be propagated to the peer.
+    pub const fn is_withhold(&self) -> bool {
+        matches!(self, Self::Withhold)
+    }
+
+    /// Resolves the decision for a peer that receives full transactions by default if
+    /// `full_by_default` is `true`.
+    ///
+    /// Returns `None` if the transaction is withheld, otherwise whether the transaction should be
+    /// sent in full.
+    pub const fn send_full(&self, full_by_default: bool) -> Option<bool> {
+        match self {
+            Self::Default => Some(full_by_default),
+            Self::Broadcast => Some(true),
+            Self::Announce => Some(false),
+            Self::Withhold => None,
+        }
+    }
+}
+
+/// A policy that decides per transaction and per peer whether and how a transaction is gossiped.
+///
+/// The policy is consulted by the [`TransactionsManager`](super::TransactionsManager) whenever it
+/// sends transactions to a peer: when new pending transactions are propagated, when the pool's
+/// transactions are announced to a newly connected peer and when propagation is requested
+/// manually via the [`TransactionsHandle`](super::TransactionsHandle).
+///
+/// Policies can be combined via tuples or a `Vec<Box<dyn TransactionPropagationPolicy>>`, in
+/// which case the decision with the highest precedence applies.
+pub trait TransactionPropagationPolicy: fmt::Debug + Send + Sync + Unpin + 'static {
+    /// Returns how the given transaction should be propagated to the peer.
+    fn decide(
+        &self,
+        peer_id: &PeerId,
+        peer: &PeerMetadata,
+        transaction: &PropagateTransaction,
+    ) -> PropagationDecision;
+}
+
+impl<A, B> TransactionPropagationPolicy for (A, B)
+where
+    A: TransactionPropagationPolicy,
+    B: TransactionPropagationPolicy,
+{
+    fn decide(
+        &self,
+        peer_id: &PeerId,
+        peer: &PeerMetadata,
+        transaction: &PropagateTransaction,
+    ) -> PropagationDecision {
+        self.0.decide(peer_id, peer, transaction).merge(self.1.decide(peer_id, peer, transaction))
+    }
+}
+
+impl TransactionPropagationPolicy for Vec<Box<dyn TransactionPropagationPolicy>> {
+    fn decide(
+        &self,
+        peer_id: &PeerId,
+        peer: &PeerMetadata,
+        transaction: &PropagateTransaction,
+    ) -> PropagationDecision {
+        self.iter().fold(PropagationDecision::Default, |decision, policy| {
+            decision.merge(policy.decide(peer_id, peer, transaction))
+        })
+    }
+}
+
+/// Propagates all transactions according to the default rules of the
+/// [`TransactionsManager`](super::TransactionsManager).
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct DefaultPropagationPolicy;
+
+impl TransactionPropagationPolicy for DefaultPropagationPolicy {
+    fn decide(
+        &self,
+        _: &PeerId,
+        _: &PeerMetadata,
+        _: &PropagateTransaction,
+    ) -> PropagationDecision {
+        PropagationDecision::Default
+    }
+}
+
+/// Only propagates transactions to [`PeerKind::Trusted`](reth_network_api::PeerKind::Trusted)
+/// peers.
+///
+/// By default this applies to all transactions, but it can be restricted to transactions of
+/// certain origins, e.g. to share locally submitted transactions with trusted peers only while
+/// gossiping everything else as usual.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedPeersOnly {
+    /// The origins this applies to, all origins if `None`.
+    origins: Option<Vec<TransactionOrigin>>,
+}
+
+// === impl TrustedPeersOnly ===
+
+impl TrustedPeersOnly {
+    /// Only propagates transactions of any origin to trusted peers.
+    pub const fn all() -> Self {
+        Self { origins: None }
+    }
+
+    /// Only propagates transactions of the given origins to trusted peers.
+    pub fn for_origins(origins: impl IntoIterator<Item = TransactionOrigin>) -> Self {
+        Self { origins: Some(origins.into_iter().collect()) }
+    }
+}
+
+impl TransactionPropagationPolicy for TrustedPeersOnly {
+    fn decide(
+        &self,
+        _: &PeerId,
+        peer: &PeerMetadata,
+        transaction: &PropagateTransaction,
+    ) -> PropagationDecision {
+        if peer.peer_kind().is_trusted() {
+            return PropagationDecision::Default
+        }
+        match &self.origins {
+            Some(origins) if !origins.contains(&transaction.origin()) => {
+                PropagationDecision::Default
+            }
+            _ => PropagationDecision::Withhold,
+        }
+    }
+}
+
+/// Never propagates transactions of the given origins.
+#[derive(Debug, Clone, Default)]
+pub struct WithholdOrigins {
+    /// The origins of transactions that are withheld.
+    origins: Vec<TransactionOrigin>,
+}
+
+// === impl WithholdOrigins ===
+
+impl WithholdOrigins {
+    /// Creates a new policy that withholds transactions of the given origins.
+    pub fn new(origins: impl IntoIterator<Item = TransactionOrigin>) -> Self {
+        Self { origins: origins.into_iter().collect() }
+    }
+}
+
+impl TransactionPropagationPolicy for WithholdOrigins {
+    fn decide(
+        &self,
+        _: &PeerId,
+        _: &PeerMetadata,
+        transaction: &PropagateTransaction,
+    ) -> PropagationDecision {
+        if self.origins.contains(&transaction.origin()) {
+            PropagationDecision::Withhold
+        } else {
+            PropagationDecision::Default
+        }
+    }
+}
+
+/// Only propagates transactions of the given senders.
+#[derive(Debug, Clone, Default)]
+pub struct SenderAllowList {
+    /// The senders whose transactions are propagated.
+    senders: HashSet<Address>,
+}
+
+// === impl SenderAllowList ===
+
+impl SenderAllowList {
+    /// Creates a new policy that only propagates transactions of the given senders.
+    pub fn new(senders: impl IntoIterator<Item = Address>) -> Self {
+        Self { senders: senders.into_iter().collect() }
+    }
+
+    /// Adds a sender to the allow list.
+    pub fn allow(&mut self, sender: Address) {
+        self.senders.insert(sender);
+    }
+}
+
+impl TransactionPropagationPolicy for SenderAllowList {
+    fn decide(
+        &self,
+        _: &PeerId,
+        _: &PeerMetadata,
+        transaction: &PropagateTransaction,
+    ) -> PropagationDecision {
+        if self.senders.contains(&transaction.sender()) {
+            PropagationDecision::Default
+        } else {
+            PropagationDecision::Withhold
+        }
+    }
+}
+
+/// The built-in propagation policies that can be selected via the
+/// [`TransactionsManagerConfig`](super::TransactionsManagerConfig).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum TransactionPropagationKind {
+    /// Propagate all transactions to all peers, see [`DefaultPropagationPolicy`].
+    #[default]
+    All,
+    /// Only propagate transactions to trusted peers, see [`TrustedPeersOnly::all`].
+    Trusted,
+    /// Only propagate local transactions to trusted peers and all other transactions to all peers,
+    /// see [`TrustedPeersOnly::for_origins`].
+    LocalTrusted,
+}
+
+// === impl TransactionPropagationKind ===
+
+impl TransactionPropagationKind {
+    /// Returns the [`TransactionPropagationPolicy`] of this kind.
+    pub fn into_policy(self) -> Box<dyn TransactionPropagationPolicy> {
+        match self {
+            Self::All => Box::new(DefaultPropagationPolicy),
+            Self::Trusted => Box::new(TrustedPeersOnly::all()),
+            Self::LocalTrusted => {
+                Box::new(TrustedPeersOnly::for_origins([TransactionOrigin::Local]))
+            }
+        }
+    }
+}
+
+impl fmt::Display for TransactionPropagationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => f.write_str("all"),
+            Self::Trusted => f.write_str("trusted"),
+            Self::LocalTrusted => f.write_str("local-trusted"),
+        }
+    }
+}
+
+impl FromStr for TransactionPropagationKind {
+    type Err = ParsePropagationKindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "trusted" => Ok(Self::Trusted),
+            "local-trusted" => Ok(Self::LocalTrusted),
+            _ => Err(ParsePropagationKindError(s.to_string())),
+        }
+    }
+}
+
+/// Error returned when parsing an unknown [`TransactionPropagationKind`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "unknown transaction propagation policy {0:?}, expected one of: all, trusted, local-trusted"
+)]
+pub struct ParsePropagationKindError(String);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_keeps_highest_precedence() {
+        use PropagationDecision::*;
+
+        assert_eq!(Default.merge(Broadcast), Broadcast);
+        assert_eq!(Broadcast.merge(Announce), Announce);
+        assert_eq!(Withhold.merge(Default), Withhold);
+        assert_eq!(Default.send_full(true), Some(true));
+        assert_eq!(Default.send_full(false), Some(false));
+        assert_eq!(Broadcast.send_full(false), Some(true));
+        assert_eq!(Announce.send_full(true), Some(false));
+        assert_eq!(Withhold.send_full(true), None);
+    }
+
+    #[test]
+    fn parse_propagation_kind() {
+        for kind in [
+            TransactionPropagationKind::All,
+            TransactionPropagationKind::Trusted,
+            TransactionPropagationKind::LocalTrusted,
+        ] {
+            assert_eq!(kind.to_string().parse::<TransactionPropagationKind>(), Ok(kind));
+        }
+        assert!("none".parse::<TransactionPropagationKind>().is_err());
+    }
+}
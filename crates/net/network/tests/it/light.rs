@@ -0,0 +1,151 @@
+#![allow(unreachable_pub)]
+//! Tests for the `light` sub-protocol.
+
+use reth_eth_wire::{GetBlockHeaders, HeadersDirection};
+use reth_network::{
+    light::{
+        GetProof, LightPeerHandle, LightProtocolEvent, LightProtocolHandler, LightRequestError,
+        LightRequestHandler, LightServeConfig, RejectReason,
+    },
+    test_utils::{Testnet, TestnetHandle},
+};
+use reth_primitives::{constants::EMPTY_ROOT_HASH, Address, BlockHashOrNumber, Header, B256};
+use reth_provider::test_utils::MockEthProvider;
+use reth_transaction_pool::test_utils::TestPool;
+use tokio::sync::mpsc;
+
+/// Spawns a network of a serving and a client peer, and returns the client's handle to the
+/// serving peer.
+async fn light_peers(
+    provider: MockEthProvider,
+    config: LightServeConfig,
+) -> (TestnetHandle<MockEthProvider, TestPool>, LightPeerHandle) {
+    let mut net = Testnet::create_with(2, provider.clone()).await;
+
+    let (handler, protocol) = LightRequestHandler::new(provider, config);
+    tokio::spawn(handler);
+    net.peers_mut()[0].add_rlpx_sub_protocol(protocol);
+
+    let (tx, mut events) = mpsc::unbounded_channel();
+    net.peers_mut()[1].add_rlpx_sub_protocol(LightProtocolHandler::client(tx));
+
+    let server = *net.peers()[0].peer_id();
+    let handle = net.spawn();
+    handle.connect_peers().await;
+
+    let LightProtocolEvent::Established { peer_id, peer, .. } = events.recv().await.unwrap();
+    assert_eq!(peer_id, server);
+
+    (handle, peer)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_light_serve_headers_and_proofs() {
+    reth_tracing::init_test_tracing();
+    let provider = MockEthProvider::default();
+
+    let mut parent_hash = B256::ZERO;
+    let mut headers = Vec::new();
+    for number in 0..10 {
+        let header = Header { number, parent_hash, ..Default::default() };
+        parent_hash = header.hash_slow();
+        provider.add_header(parent_hash, header.clone());
+        headers.push(header);
+    }
+
+    let config = LightServeConfig::default().with_max_headers(5).with_max_proof_window(2);
+    let (_net, peer) = light_peers(provider, config).await;
+
+    let request = GetBlockHeaders {
+        start_block: BlockHashOrNumber::Hash(headers[2].hash_slow()),
+        limit: 3,
+        skip: 0,
+        direction: HeadersDirection::Rising,
+    };
+    assert_eq!(peer.get_block_headers(request).await.unwrap(), headers[2..5]);
+
+    // capped by the limit of the server
+    let request = GetBlockHeaders {
+        start_block: BlockHashOrNumber::Hash(headers[9].hash_slow()),
+        limit: 10,
+        skip: 0,
+        direction: HeadersDirection::Falling,
+    };
+    let served = peer.get_block_headers(request).await.unwrap();
+    assert_eq!(served.len(), 5);
+    assert_eq!(served[0], headers[9]);
+
+    let address = Address::random();
+    let request =
+        GetProof { block_hash: headers[9].hash_slow(), address, storage_keys: vec![B256::ZERO] };
+    let proof = peer.get_proof(request).await.unwrap();
+    assert_eq!(proof.address, address);
+    proof.verify(EMPTY_ROOT_HASH).unwrap();
+
+    let request = GetProof {
+        block_hash: headers[9].hash_slow(),
+        address,
+        storage_keys: vec![B256::ZERO; LightServeConfig::default().max_storage_keys + 1],
+    };
+    assert_eq!(
+        peer.get_proof(request).await.unwrap_err(),
+        LightRequestError::Rejected(RejectReason::LimitExceeded)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_light_proof_window() {
+    reth_tracing::init_test_tracing();
+    let provider = MockEthProvider::default();
+
+    let mut parent_hash = B256::ZERO;
+    let mut hashes = Vec::new();
+    for number in 0..10 {
+        let header = Header { number, parent_hash, ..Default::default() };
+        parent_hash = header.hash_slow();
+        provider.add_header(parent_hash, header);
+        hashes.push(parent_hash);
+    }
+
+    let (_net, peer) =
+        light_peers(provider, LightServeConfig::default().with_max_proof_window(2)).await;
+
+    let proof = |block_hash| GetProof { block_hash, address: Address::ZERO, storage_keys: vec![] };
+    peer.get_proof(proof(hashes[7])).await.unwrap();
+
+    // older than the proof window, including genesis
+    for block_hash in [hashes[6], hashes[0]] {
+        assert_eq!(
+            peer.get_proof(proof(block_hash)).await.unwrap_err(),
+            LightRequestError::Rejected(RejectReason::Unavailable)
+        );
+    }
+
+    // unknown blocks are not proven
+    assert_eq!(
+        peer.get_proof(proof(B256::random())).await.unwrap_err(),
+        LightRequestError::Rejected(RejectReason::Unavailable)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_light_throttled() {
+    reth_tracing::init_test_tracing();
+    let provider = MockEthProvider::default();
+    let header = Header::default();
+    let block_hash = header.hash_slow();
+    provider.add_header(block_hash, header);
+
+    let config = LightServeConfig::default();
+    // enough for a single proof, and never refilled
+    let (_net, peer) =
+        light_peers(provider, config.with_budget(config.proof_cost + config.proof_cost / 2, 0))
+            .await;
+
+    let request = GetProof { block_hash, address: Address::ZERO, storage_keys: vec![] };
+    peer.get_proof(request.clone()).await.unwrap();
+    assert_eq!(
+        peer.get_proof(request).await.unwrap_err(),
+        LightRequestError::Rejected(RejectReason::Throttled)
+    );
+}
@@ -1,5 +1,7 @@
 mod big_pooled_txs_req;
 mod connect;
+mod light;
+mod mocknet;
 mod multiplex;
 mod requests;
 mod session;
@@ -0,0 +1,88 @@
+//! Deterministic session scenarios against the in-memory [`MockNetwork`].
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use reth_eth_wire::{EthMessage, EthMessageID, NewPooledTransactionHashes68};
+use reth_network::{
+    test_utils::{MockNetwork, MockPeerEvent},
+    NetworkEvent,
+};
+use reth_network_types::{
+    session::config::PROTOCOL_BREACH_REQUEST_TIMEOUT, ReputationChangeWeights,
+};
+use reth_primitives::B256;
+
+#[tokio::test(start_paused = true)]
+async fn test_malformed_announcement_drops_session() {
+    reth_tracing::init_test_tracing();
+
+    let mut net = MockNetwork::new().await;
+    let mut events = net.event_listener();
+    let mut peer = net.connect_peer().await;
+
+    let ev = events.next().await.unwrap();
+    assert!(
+        matches!(ev, NetworkEvent::SessionEstablished { peer_id, .. } if peer_id == peer.peer_id())
+    );
+    assert_eq!(net.reputation(peer.peer_id()).await, Some(0));
+
+    // lengths of the fields don't match, which fails to decode
+    let announcement = NewPooledTransactionHashes68 {
+        types: vec![2, 2],
+        sizes: vec![100],
+        hashes: vec![B256::random()],
+    };
+    peer.send_raw(EthMessageID::NewPooledTransactionHashes, alloy_rlp::encode(&announcement));
+
+    // the node closes the session without sending a disconnect message
+    assert!(matches!(peer.next_event().await, Some(MockPeerEvent::Closed(None))));
+    assert!(peer.next_event().await.is_none());
+
+    let ev = events.next().await.unwrap();
+    assert!(matches!(ev, NetworkEvent::PeerAdded(peer_id) if peer_id == peer.peer_id()));
+    let ev = events.next().await.unwrap();
+    assert!(matches!(ev, NetworkEvent::SessionClosed { peer_id, .. } if peer_id == peer.peer_id()));
+
+    assert_eq!(
+        net.reputation(peer.peer_id()).await,
+        Some(ReputationChangeWeights::default().dropped)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_unanswered_pooled_transactions_request() {
+    reth_tracing::init_test_tracing();
+
+    let mut net = MockNetwork::new().await;
+    let mut events = net.event_listener();
+    let mut peer = net.connect_peer().await;
+
+    let ev = events.next().await.unwrap();
+    assert!(
+        matches!(ev, NetworkEvent::SessionEstablished { peer_id, .. } if peer_id == peer.peer_id())
+    );
+
+    let hash = B256::random();
+    peer.send(EthMessage::NewPooledTransactionHashes68(NewPooledTransactionHashes68 {
+        types: vec![2],
+        sizes: vec![100],
+        hashes: vec![hash],
+    }));
+
+    // the announced hash is fetched from the peer
+    let Some(EthMessage::GetPooledTransactions(req)) = peer.next_message().await else {
+        panic!("expected GetPooledTransactions request")
+    };
+    assert_eq!(req.message.0, vec![hash]);
+
+    // not responding within the protocol breach timeout gets the peer disconnected and banned
+    net.advance(PROTOCOL_BREACH_REQUEST_TIMEOUT + Duration::from_secs(1)).await;
+    assert!(matches!(peer.next_event().await, Some(MockPeerEvent::Closed(Some(_)))));
+
+    let ev = events.next().await.unwrap();
+    assert!(matches!(ev, NetworkEvent::PeerAdded(peer_id) if peer_id == peer.peer_id()));
+    let ev = events.next().await.unwrap();
+    assert!(matches!(ev, NetworkEvent::SessionClosed { peer_id, .. } if peer_id == peer.peer_id()));
+    assert_eq!(net.reputation(peer.peer_id()).await, None);
+}
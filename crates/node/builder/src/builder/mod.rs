@@ -18,6 +18,7 @@ use reth_db_api::{
 };
 use reth_exex::ExExContext;
 use reth_network::{
+    light::{LightRequestHandler, LightServeConfig},
     NetworkBuilder, NetworkConfig, NetworkConfigBuilder, NetworkHandle, NetworkManager,
 };
 use reth_node_api::{FullNodeTypes, FullNodeTypesAdapter, NodeAddOns, NodeTypes};
@@ -580,10 +581,23 @@ impl<Node: FullNodeTypes> BuilderContext<Node> {
     ///
     /// Spawns the configured network and associated tasks and returns the [`NetworkHandle`]
     /// connected to that network.
-    pub fn start_network<Pool>(&self, builder: NetworkBuilder<(), ()>, pool: Pool) -> NetworkHandle
+    pub fn start_network<Pool>(
+        &self,
+        mut builder: NetworkBuilder<(), ()>,
+        pool: Pool,
+    ) -> NetworkHandle
     where
         Pool: TransactionPool + Unpin + 'static,
     {
+        if self.config().network.light_serve {
+            // serve proofs for the same blocks as `eth_getProof`
+            let config = LightServeConfig::default()
+                .with_max_proof_window(self.config().rpc.rpc_eth_proof_window);
+            let (light, protocol) = LightRequestHandler::new(self.provider().clone(), config);
+            builder.network_mut().add_rlpx_sub_protocol(protocol);
+            self.executor.spawn_critical("p2p light request handler", light);
+        }
+
         let (handle, network, txpool, eth) = builder
             .transactions(pool, Default::default())
             .request_handler(self.provider().clone())
@@ -47,12 +47,23 @@ use crate::{
 pub struct EngineNodeLauncher {
     /// The task executor for the node.
     pub ctx: LaunchContext,
+    /// The configuration of the engine tree.
+    pub tree_config: TreeConfig,
 }
 
 impl EngineNodeLauncher {
     /// Create a new instance of the ethereum node launcher.
-    pub const fn new(task_executor: TaskExecutor, data_dir: ChainPath<DataDirPath>) -> Self {
-        Self { ctx: LaunchContext::new(task_executor, data_dir) }
+    pub fn new(task_executor: TaskExecutor, data_dir: ChainPath<DataDirPath>) -> Self {
+        Self {
+            ctx: LaunchContext::new(task_executor, data_dir),
+            tree_config: TreeConfig::default(),
+        }
+    }
+
+    /// Sets the configuration of the engine tree.
+    pub const fn with_tree_config(mut self, tree_config: TreeConfig) -> Self {
+        self.tree_config = tree_config;
+        self
     }
 }
 
@@ -77,7 +88,7 @@ where
         self,
         target: NodeBuilderWithComponents<T, CB, AO>,
     ) -> eyre::Result<Self::Node> {
-        let Self { ctx } = self;
+        let Self { ctx, tree_config: engine_tree_config } = self;
         let NodeBuilderWithComponents {
             adapter: NodeTypesAdapter { database },
             components_builder,
@@ -154,7 +165,10 @@ where
             // Store messages _after_ skipping so that `replay-engine` command
             // would replay only the messages that were observed by the engine
             // during this run.
-            .maybe_store_messages(node_config.debug.engine_api_store.clone());
+            .maybe_store_messages(
+                node_config.debug.engine_api_store.clone(),
+                Box::new(ctx.task_executor().clone()),
+            );
 
         let max_block = ctx.max_block(network_client.clone()).await?;
         let mut hooks = EngineHooks::new();
@@ -212,7 +226,7 @@ where
             ctx.blockchain_db().clone(),
             pruner,
             ctx.components().payload_builder().clone(),
-            TreeConfig::default(),
+            engine_tree_config,
         );
 
         let event_sender = EventSender::default();
@@ -260,6 +274,7 @@ where
             Box::new(ctx.task_executor().clone()),
             client,
             EngineCapabilities::default(),
+            ctx.components().pool().clone(),
         );
         info!(target: "reth::cli", "Engine API handler initialized");
 
@@ -202,7 +202,10 @@ where
             // Store messages _after_ skipping so that `replay-engine` command
             // would replay only the messages that were observed by the engine
             // during this run.
-            .maybe_store_messages(node_config.debug.engine_api_store.clone());
+            .maybe_store_messages(
+                node_config.debug.engine_api_store.clone(),
+                Box::new(ctx.task_executor().clone()),
+            );
 
         let max_block = ctx.max_block(network_client.clone()).await?;
         let mut hooks = EngineHooks::new();
@@ -348,6 +351,7 @@ where
             Box::new(ctx.task_executor().clone()),
             client,
             EngineCapabilities::default(),
+            ctx.components().pool().clone(),
         );
         info!(target: "reth::cli", "Engine API handler initialized");
 
@@ -43,6 +43,9 @@ pub use reth_node_core::cli::config::{
     PayloadBuilderConfig, RethNetworkConfig, RethTransactionPoolConfig,
 };
 
+// re-export the payload building strategies of the payload builder config
+pub use reth_node_core::args::PayloadBuilderStrategy;
+
 // re-export the core config for convenience
 pub use reth_node_core::node_config::NodeConfig;
 
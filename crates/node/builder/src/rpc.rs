@@ -10,16 +10,18 @@ use reth_node_api::{BuilderProvider, FullNodeComponents};
 use reth_node_core::{
     node_config::NodeConfig,
     rpc::{
-        api::EngineApiServer,
-        eth::{EthApiTypes, FullEthApiServer},
+        api::{BlockSubmissionValidationApiServer, EngineApiServer, PayloadDebugApiServer},
+        eth::{EthApiTypes, EthCallBundleApiServer, FullEthApiServer},
     },
 };
 use reth_payload_builder::PayloadBuilderHandle;
+use reth_rpc::ValidationApi;
 use reth_rpc_builder::{
     auth::{AuthRpcModule, AuthServerHandle},
     config::RethRpcServerConfig,
-    RpcModuleBuilder, RpcRegistryInner, RpcServerHandle, TransportRpcModules,
+    RethRpcModule, RpcModuleBuilder, RpcRegistryInner, RpcServerHandle, TransportRpcModules,
 };
+use reth_rpc_engine_api::PayloadDebugApi;
 use reth_rpc_layer::JwtSecret;
 use reth_tasks::TaskExecutor;
 use reth_tracing::tracing::{debug, info};
@@ -316,6 +318,28 @@ where
         .with_evm_config(node.evm_config().clone())
         .build_with_auth_server(module_config, engine_api, EthApi::eth_api_builder());
 
+    // the block submission validation requires the block executor of the node
+    let validation_api = ValidationApi::new(
+        node.provider().clone(),
+        node.block_executor().clone(),
+        config.rpc.validation_api_config(),
+        Box::new(node.task_executor().clone()),
+    );
+    modules.merge_if_module_configured(RethRpcModule::Flashbots, validation_api.into_rpc())?;
+
+    // bundle simulations share the execution results of the payload builder
+    if config.builder.simulation_cache {
+        let bundle_api = registry
+            .bundle_api()
+            .with_simulation_cache(node.payload_builder().simulation_cache().clone());
+        modules.remove_method_from_configured("eth_callBundle");
+        modules.merge_if_module_configured(RethRpcModule::Eth, bundle_api.into_rpc())?;
+    }
+
+    // payload job introspection is only served next to the engine API
+    let payload_debug_api = PayloadDebugApi::new(node.payload_builder().clone().into());
+    auth_module.merge_auth_methods(payload_debug_api.into_rpc())?;
+
     let mut registry = RpcRegistry { registry };
     let ctx = RpcContext {
         node: node.clone(),
@@ -12,8 +12,9 @@ use reth_chainspec::ChainSpec;
 use reth_config::Config;
 use reth_discv4::{NodeRecord, DEFAULT_DISCOVERY_ADDR, DEFAULT_DISCOVERY_PORT};
 use reth_discv5::{
-    discv5::ListenConfig, DEFAULT_COUNT_BOOTSTRAP_LOOKUPS, DEFAULT_DISCOVERY_V5_PORT,
-    DEFAULT_SECONDS_BOOTSTRAP_LOOKUP_INTERVAL, DEFAULT_SECONDS_LOOKUP_INTERVAL,
+    discv5::ListenConfig, EnrFilter, Topic, DEFAULT_COUNT_BOOTSTRAP_LOOKUPS,
+    DEFAULT_DISCOVERY_V5_PORT, DEFAULT_SECONDS_BOOTSTRAP_LOOKUP_INTERVAL,
+    DEFAULT_SECONDS_LOOKUP_INTERVAL,
 };
 use reth_net_nat::NatResolver;
 use reth_network::{
@@ -27,13 +28,14 @@ use reth_network::{
                 DEFAULT_MAX_COUNT_PENDING_POOL_IMPORTS, DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
             },
         },
-        TransactionFetcherConfig, TransactionsManagerConfig,
+        TransactionFetcherConfig, TransactionPropagationKind, TransactionsManagerConfig,
         DEFAULT_SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESP_ON_PACK_GET_POOLED_TRANSACTIONS_REQ,
         SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESPONSE,
     },
     HelloMessageWithProtocols, NetworkConfigBuilder, SessionsConfig,
 };
 use reth_network_peers::{mainnet_nodes, TrustedPeer};
+use reth_transaction_pool::rate_limit::RateLimitConfig;
 use secp256k1::SecretKey;
 
 use crate::version::P2P_CLIENT_VERSION;
@@ -87,10 +89,18 @@ pub struct NetworkArgs {
     #[arg(long, verbatim_doc_comment)]
     pub no_persist_peers: bool,
 
-    /// NAT resolution method (any|none|upnp|publicip|extip:\<IP\>)
+    /// NAT resolution method
+    /// (any|none|upnp|natpmp[:\<GATEWAY\>]|pcp[:\<GATEWAY\>]|publicip|extip:\<IP\>)
     #[arg(long, default_value = "any")]
     pub nat: NatResolver,
 
+    /// Map the p2p and discovery ports on the NAT gateway.
+    ///
+    /// Requires `--nat` to be one of upnp, natpmp or pcp. Mappings are renewed periodically and
+    /// announced in the local node records.
+    #[arg(long = "nat.map-ports")]
+    pub nat_map_ports: bool,
+
     /// Network listening address
     #[arg(long = "addr", value_name = "ADDR", default_value_t = DEFAULT_DISCOVERY_ADDR)]
     pub addr: IpAddr,
@@ -144,6 +154,22 @@ pub struct NetworkArgs {
     /// Default is 128 KiB.
     #[arg(long = "pooled-tx-pack-soft-limit", value_name = "BYTES", default_value_t = DEFAULT_SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESP_ON_PACK_GET_POOLED_TRANSACTIONS_REQ, verbatim_doc_comment)]
     pub soft_limit_byte_size_pooled_transactions_response_on_pack_request: usize,
+
+    /// Max number of transactions per second to import from a single peer.
+    ///
+    /// Transactions above the limit are dropped. Unlimited by default.
+    #[arg(long = "tx-rate-limit-per-peer", value_name = "TXS_PER_SEC")]
+    pub tx_rate_limit_per_peer: Option<u32>,
+
+    /// Which peers transactions are propagated to (all|trusted|local-trusted).
+    ///
+    /// With `local-trusted` only locally submitted transactions are restricted to trusted peers.
+    #[arg(long = "tx-propagation-policy", value_name = "POLICY", default_value_t)]
+    pub tx_propagation_policy: TransactionPropagationKind,
+
+    /// Serve block headers and state proofs to light clients over the `light` sub-protocol.
+    #[arg(long = "light.serve")]
+    pub light_serve: bool,
 }
 
 impl NetworkArgs {
@@ -193,6 +219,8 @@ impl NetworkArgs {
                 self.soft_limit_byte_size_pooled_transactions_response_on_pack_request,
             ),
             max_transactions_seen_by_peer_history: self.max_seen_tx_history,
+            peer_rate_limit: self.tx_rate_limit_per_peer.map(RateLimitConfig::per_second),
+            propagation_policy: self.tx_propagation_policy,
         };
 
         // Configure basic network stack
@@ -201,6 +229,7 @@ impl NetworkArgs {
                 self.persistent_peers_file(peers_file).as_deref(),
             ))
             .external_ip_resolver(self.nat)
+            .nat_port_mapping(self.nat_map_ports)
             .sessions_config(
                 SessionsConfig::default().with_upscaled_event_buffer(peers_config.max_peers()),
             )
@@ -248,8 +277,8 @@ impl NetworkArgs {
     /// Sets the p2p and discovery ports to zero, allowing the OD to assign a random unused port
     /// when network components bind to sockets.
     pub const fn with_unused_ports(mut self) -> Self {
-        self = self.with_unused_p2p_port();
-        self.discovery = self.discovery.with_unused_discovery_port();
+        self.port = 0;
+        self.discovery.port = 0;
         self
     }
 
@@ -286,6 +315,7 @@ impl Default for NetworkArgs {
             p2p_secret_key: None,
             no_persist_peers: false,
             nat: NatResolver::Any,
+            nat_map_ports: false,
             addr: DEFAULT_DISCOVERY_ADDR,
             port: DEFAULT_DISCOVERY_PORT,
             max_outbound_peers: None,
@@ -297,6 +327,9 @@ impl Default for NetworkArgs {
             soft_limit_byte_size_pooled_transactions_response_on_pack_request: DEFAULT_SOFT_LIMIT_BYTE_SIZE_POOLED_TRANSACTIONS_RESP_ON_PACK_GET_POOLED_TRANSACTIONS_REQ,
             max_pending_pool_imports: DEFAULT_MAX_COUNT_PENDING_POOL_IMPORTS,
             max_seen_tx_history: DEFAULT_MAX_COUNT_TRANSACTIONS_SEEN_BY_PEER,
+            tx_rate_limit_per_peer: None,
+            tx_propagation_policy: TransactionPropagationKind::default(),
+            light_serve: false,
         }
     }
 }
@@ -365,6 +398,25 @@ pub struct DiscoveryArgs {
     #[arg(id = "discovery.v5.bootstrap.lookup-countdown", long = "discovery.v5.bootstrap.lookup-countdown", value_name = "DISCOVERY_V5_BOOTSTRAP_LOOKUP_COUNTDOWN",
         default_value_t = DEFAULT_COUNT_BOOTSTRAP_LOOKUPS)]
     pub discv5_bootstrap_lookup_countdown: u64,
+
+    /// Filter rules on the node records of peers discovered via discv5, e.g.
+    /// `has(opstack) && !has(eth2) && role == "sequencer"`. Only matching peers are connected to.
+    #[arg(
+        id = "discovery.v5.enr-filter",
+        long = "discovery.v5.enr-filter",
+        value_name = "DISCOVERY_V5_ENR_FILTER"
+    )]
+    pub discv5_enr_filter: Option<EnrFilter>,
+
+    /// Comma separated list of topics to advertise in the discv5 node record and to look up. Only
+    /// peers that advertise any of the topics are connected to.
+    #[arg(
+        id = "discovery.v5.topics",
+        long = "discovery.v5.topics",
+        value_name = "DISCOVERY_V5_TOPICS",
+        value_delimiter = ','
+    )]
+    pub discv5_topics: Vec<Topic>,
 }
 
 impl DiscoveryArgs {
@@ -405,6 +457,8 @@ impl DiscoveryArgs {
             discv5_lookup_interval,
             discv5_bootstrap_lookup_interval,
             discv5_bootstrap_lookup_countdown,
+            discv5_enr_filter,
+            discv5_topics,
             ..
         } = self;
 
@@ -418,7 +472,7 @@ impl DiscoveryArgs {
             SocketAddr::V6(addr) => Some(*addr.ip()),
         });
 
-        reth_discv5::Config::builder(rlpx_tcp_socket)
+        let mut builder = reth_discv5::Config::builder(rlpx_tcp_socket)
             .discv5_config(
                 reth_discv5::discv5::ConfigBuilder::new(ListenConfig::from_two_sockets(
                     discv5_addr_ipv4.map(|addr| SocketAddrV4::new(addr, *discv5_port)),
@@ -430,6 +484,14 @@ impl DiscoveryArgs {
             .lookup_interval(*discv5_lookup_interval)
             .bootstrap_lookup_interval(*discv5_bootstrap_lookup_interval)
             .bootstrap_lookup_countdown(*discv5_bootstrap_lookup_countdown)
+            .advertise_topics(discv5_topics.clone())
+            .lookup_topics(discv5_topics.clone());
+
+        if let Some(filter) = discv5_enr_filter {
+            builder = builder.enr_filter(filter.clone());
+        }
+
+        builder
     }
 
     /// Set the discovery port to zero, to allow the OS to assign a random unused port when
@@ -468,6 +530,8 @@ impl Default for DiscoveryArgs {
             discv5_lookup_interval: DEFAULT_SECONDS_LOOKUP_INTERVAL,
             discv5_bootstrap_lookup_interval: DEFAULT_SECONDS_BOOTSTRAP_LOOKUP_INTERVAL,
             discv5_bootstrap_lookup_countdown: DEFAULT_COUNT_BOOTSTRAP_LOOKUPS,
+            discv5_enr_filter: None,
+            discv5_topics: Vec::new(),
         }
     }
 }
@@ -491,6 +555,25 @@ mod tests {
         let args =
             CommandParser::<NetworkArgs>::parse_from(["reth", "--nat", "extip:0.0.0.0"]).args;
         assert_eq!(args.nat, NatResolver::ExternalIp("0.0.0.0".parse().unwrap()));
+
+        let args = CommandParser::<NetworkArgs>::parse_from([
+            "reth",
+            "--nat",
+            "natpmp:192.168.1.1",
+            "--nat.map-ports",
+        ])
+        .args;
+        assert_eq!(args.nat, NatResolver::NatPmp(Some("192.168.1.1".parse().unwrap())));
+        assert!(args.nat_map_ports);
+    }
+
+    #[test]
+    fn parse_light_serve_args() {
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth"]).args;
+        assert!(!args.light_serve);
+
+        let args = CommandParser::<NetworkArgs>::parse_from(["reth", "--light.serve"]).args;
+        assert!(args.light_serve);
     }
 
     #[test]
@@ -531,6 +614,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_discv5_filter_args() {
+        let args = CommandParser::<DiscoveryArgs>::parse_from([
+            "reth",
+            "--discovery.v5.enr-filter",
+            "has(opstack) && !has(eth2)",
+            "--discovery.v5.topics",
+            "sequencers,builders",
+        ])
+        .args;
+
+        assert_eq!(args.discv5_enr_filter, Some("has(opstack) && !has(eth2)".parse().unwrap()));
+        assert_eq!(args.discv5_topics, vec![Topic::new("sequencers"), Topic::new("builders")]);
+
+        let res = CommandParser::<DiscoveryArgs>::try_parse_from([
+            "reth",
+            "--discovery.v5.enr-filter",
+            "has(opstack",
+        ]);
+        assert!(res.is_err());
+    }
+
     #[test]
     fn parse_retry_strategy_args() {
         let tests = vec![0, 10];
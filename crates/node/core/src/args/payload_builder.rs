@@ -1,13 +1,19 @@
 use crate::{cli::config::PayloadBuilderConfig, version::default_extradata};
 use clap::{
     builder::{RangedU64ValueParser, TypedValueParser},
-    Arg, Args, Command,
+    Arg, Args, Command, ValueEnum,
 };
 use reth_cli_util::{parse_duration_from_secs, parse_duration_from_secs_or_ms};
 use reth_primitives::constants::{
     ETHEREUM_BLOCK_GAS_LIMIT, MAXIMUM_EXTRA_DATA_SIZE, SLOT_DURATION,
 };
-use std::{borrow::Cow, ffi::OsStr, time::Duration};
+use std::{
+    borrow::Cow,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use url::Url;
 
 /// Parameters for configuring the Payload Builder
 #[derive(Debug, Clone, Args, PartialEq, Eq)]
@@ -36,6 +42,74 @@ pub struct PayloadBuilderArgs {
     /// Maximum number of tasks to spawn for building a payload.
     #[arg(long = "builder.max-tasks", default_value = "3", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
     pub max_payload_tasks: usize,
+
+    /// Reuse transaction execution results across payload jobs and `eth_callBundle` calls if the
+    /// state the transaction read is unchanged.
+    #[arg(long = "builder.simulation-cache", default_value_t = false)]
+    pub simulation_cache: bool,
+
+    /// Payload building strategies that compete in every payload job, the payload with the
+    /// highest fees is served.
+    ///
+    /// Every strategy builds in its own task, so the max tasks are raised to the number of
+    /// strategies if needed. If no strategy is given, payload jobs build bundle-first.
+    #[arg(long = "builder.strategies", value_delimiter = ',', value_name = "STRATEGY")]
+    pub strategies: Vec<PayloadBuilderStrategy>,
+
+    /// IPC socket path of the external builder used by the `external` strategy.
+    #[arg(
+        long = "builder.external-ipc",
+        value_name = "PATH",
+        required_if_eq("strategies", "external")
+    )]
+    pub external_builder_ipc: Option<PathBuf>,
+
+    /// How long to wait for the external builder to respond.
+    ///
+    /// Timeout is specified in seconds or in milliseconds if the value ends with `ms`.
+    #[arg(long = "builder.external-timeout", value_parser = parse_duration_from_secs_or_ms, default_value = "500ms", value_name = "DURATION")]
+    pub external_builder_timeout: Duration,
+
+    /// MEV-Boost relays the payloads are submitted to, as comma separated base urls.
+    ///
+    /// Every better payload of a job is submitted as a bid to the relays that know the proposer
+    /// of the slot. Only supported on mainnet, sepolia and holesky.
+    #[arg(
+        long = "builder.relays",
+        value_delimiter = ',',
+        value_name = "URL",
+        requires = "relay_secret_key"
+    )]
+    pub relays: Vec<Url>,
+
+    /// Path to the file with the hex encoded BLS secret key the bids submitted to relays are
+    /// signed with.
+    #[arg(long = "builder.relay-secret-key", value_name = "PATH")]
+    pub relay_secret_key: Option<PathBuf>,
+}
+
+/// A payload building strategy, see [`PayloadBuilderArgs::strategies`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PayloadBuilderStrategy {
+    /// Fills the payload with the pool transactions that pay the highest tip and ignores
+    /// bundles.
+    Greedy,
+    /// Includes the bundles targeting the block first, followed by the best pool transactions.
+    BundleFirst,
+    /// Includes the transactions of the external builder at `--builder.external-ipc` first,
+    /// followed by the bundles and transactions of the pool.
+    External,
+}
+
+impl PayloadBuilderStrategy {
+    /// Returns the name of the strategy, as used on the command line.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Greedy => "greedy",
+            Self::BundleFirst => "bundle-first",
+            Self::External => "external",
+        }
+    }
 }
 
 impl Default for PayloadBuilderArgs {
@@ -46,6 +120,12 @@ impl Default for PayloadBuilderArgs {
             interval: Duration::from_secs(1),
             deadline: SLOT_DURATION,
             max_payload_tasks: 3,
+            simulation_cache: false,
+            strategies: Vec::new(),
+            external_builder_ipc: None,
+            external_builder_timeout: Duration::from_millis(500),
+            relays: Vec::new(),
+            relay_secret_key: None,
         }
     }
 }
@@ -70,6 +150,30 @@ impl PayloadBuilderConfig for PayloadBuilderArgs {
     fn max_payload_tasks(&self) -> usize {
         self.max_payload_tasks
     }
+
+    fn simulation_cache(&self) -> bool {
+        self.simulation_cache
+    }
+
+    fn strategies(&self) -> &[PayloadBuilderStrategy] {
+        &self.strategies
+    }
+
+    fn external_builder_ipc(&self) -> Option<&Path> {
+        self.external_builder_ipc.as_deref()
+    }
+
+    fn external_builder_timeout(&self) -> Duration {
+        self.external_builder_timeout
+    }
+
+    fn relays(&self) -> &[Url] {
+        &self.relays
+    }
+
+    fn relay_secret_key(&self) -> Option<&Path> {
+        self.relay_secret_key.as_deref()
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -174,4 +278,76 @@ mod tests {
                 .args;
         assert_eq!(args.interval, Duration::from_millis(50));
     }
+
+    #[test]
+    fn test_args_with_strategies() {
+        let args = CommandParser::<PayloadBuilderArgs>::parse_from([
+            "reth",
+            "--builder.strategies",
+            "greedy,bundle-first,external",
+            "--builder.external-ipc",
+            "/tmp/builder.ipc",
+            "--builder.external-timeout",
+            "200ms",
+        ])
+        .args;
+        assert_eq!(
+            args.strategies,
+            vec![
+                PayloadBuilderStrategy::Greedy,
+                PayloadBuilderStrategy::BundleFirst,
+                PayloadBuilderStrategy::External
+            ]
+        );
+        assert_eq!(args.external_builder_ipc, Some(PathBuf::from("/tmp/builder.ipc")));
+        assert_eq!(args.external_builder_timeout, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_args_external_strategy_requires_ipc() {
+        assert!(CommandParser::<PayloadBuilderArgs>::try_parse_from([
+            "reth",
+            "--builder.strategies",
+            "greedy,external"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_args_with_relays() {
+        let args = CommandParser::<PayloadBuilderArgs>::parse_from([
+            "reth",
+            "--builder.relays",
+            "https://relay-a.example,https://relay-b.example",
+            "--builder.relay-secret-key",
+            "/tmp/relay.key",
+        ])
+        .args;
+        assert_eq!(
+            args.relays,
+            vec![
+                Url::parse("https://relay-a.example").unwrap(),
+                Url::parse("https://relay-b.example").unwrap()
+            ]
+        );
+        assert_eq!(args.relay_secret_key, Some(PathBuf::from("/tmp/relay.key")));
+    }
+
+    #[test]
+    fn test_args_relays_require_secret_key() {
+        assert!(CommandParser::<PayloadBuilderArgs>::try_parse_from([
+            "reth",
+            "--builder.relays",
+            "https://relay.example"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_args_with_simulation_cache() {
+        let args =
+            CommandParser::<PayloadBuilderArgs>::parse_from(["reth", "--builder.simulation-cache"])
+                .args;
+        assert!(args.simulation_cache);
+    }
 }
@@ -1,9 +1,10 @@
 //! clap [Args](clap::Args) for RPC related arguments.
 
 use std::{
+    collections::HashSet,
     ffi::OsStr,
     net::{IpAddr, Ipv4Addr},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use alloy_rpc_types_engine::JwtSecret;
@@ -12,6 +13,7 @@ use clap::{
     Arg, Args, Command,
 };
 use rand::Rng;
+use reth_primitives::Address;
 use reth_rpc_server_types::{constants, RethRpcModule, RpcModuleSelection};
 
 use crate::args::{
@@ -170,6 +172,28 @@ pub struct RpcServerArgs {
     #[arg(long = "rpc.proof-permits", alias = "rpc-proof-permits", value_name = "COUNT", default_value_t = constants::DEFAULT_PROOF_PERMITS)]
     pub rpc_proof_permits: usize,
 
+    /// Maximum number of transactions per second a single client can submit via
+    /// `eth_sendRawTransaction`, `eth_sendRawTransactionConditional` and `eth_sendTransaction` on
+    /// the HTTP and WS servers.
+    ///
+    /// Clients are identified by the `sub` claim of their JWT if `--rpc.jwtsecret` is set,
+    /// otherwise by the IP address of their connection, see
+    /// `--rpc.tx-rate-limit.trust-forwarded-for`.
+    #[arg(long = "rpc.tx-rate-limit", value_name = "TXS_PER_SEC")]
+    pub rpc_tx_rate_limit: Option<u32>,
+
+    /// Identify clients that have no JWT subject by the `X-Forwarded-For` or `X-Real-IP` header
+    /// instead of the IP address of their connection for `--rpc.tx-rate-limit`.
+    ///
+    /// Only enable this if the RPC server is behind a reverse proxy that sets these headers.
+    #[arg(long = "rpc.tx-rate-limit.trust-forwarded-for", requires = "rpc_tx_rate_limit")]
+    pub rpc_tx_rate_limit_trust_forwarded_for: bool,
+
+    /// Path to a JSON file with an array of addresses whose blocks are rejected by the
+    /// `flashbots_validateBuilderSubmission` endpoints.
+    #[arg(long = "builder.disallow", value_name = "PATH", value_parser = read_address_list)]
+    pub builder_disallow: Option<HashSet<Address>>,
+
     /// State cache configuration.
     #[command(flatten)]
     pub rpc_state_cache: RpcStateCacheArgs,
@@ -304,10 +328,18 @@ impl Default for RpcServerArgs {
             gas_price_oracle: GasPriceOracleArgs::default(),
             rpc_state_cache: RpcStateCacheArgs::default(),
             rpc_proof_permits: constants::DEFAULT_PROOF_PERMITS,
+            rpc_tx_rate_limit: None,
+            rpc_tx_rate_limit_trust_forwarded_for: false,
+            builder_disallow: None,
         }
     }
 }
 
+/// Reads a JSON array of addresses from the file at the given path.
+fn read_address_list(path: &str) -> Result<HashSet<Address>, reth_fs_util::FsPathError> {
+    reth_fs_util::read_json_file(Path::new(path))
+}
+
 /// clap value parser for [`RpcModuleSelection`].
 #[derive(Clone, Debug, Default)]
 #[non_exhaustive]
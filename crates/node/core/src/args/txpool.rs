@@ -6,12 +6,13 @@ use reth_primitives::Address;
 use reth_transaction_pool::{
     blobstore::disk::DEFAULT_MAX_CACHED_BLOBS,
     pool::{NEW_TX_LISTENER_BUFFER_SIZE, PENDING_TX_LISTENER_BUFFER_SIZE},
-    validate::DEFAULT_MAX_TX_INPUT_BYTES,
+    validate::{SimulationConfig, DEFAULT_MAX_TX_INPUT_BYTES, DEFAULT_SIMULATION_GAS_BUDGET},
     LocalTransactionConfig, PoolConfig, PriceBumpConfig, SubPoolLimit, DEFAULT_PRICE_BUMP,
-    DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS, REPLACE_BLOB_PRICE_BUMP,
-    TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER, TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
-    TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
+    DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS, DEFAULT_TXPOOL_EXTERNAL_VALIDATION_TASKS,
+    REPLACE_BLOB_PRICE_BUMP, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER, TXPOOL_MAX_BUNDLES_DEFAULT,
+    TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT, TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
 };
+use std::path::PathBuf;
 /// Parameters for debugging purposes
 #[derive(Debug, Clone, Args, PartialEq, Eq)]
 #[command(next_help_heading = "TxPool")]
@@ -41,6 +42,10 @@ pub struct TxPoolArgs {
     #[arg(long = "txpool.max-account-slots", alias = "txpool.max_account_slots", default_value_t = TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER)]
     pub max_account_slots: usize,
 
+    /// Max number of bundles held for block builders
+    #[arg(long = "txpool.max-bundles", default_value_t = TXPOOL_MAX_BUNDLES_DEFAULT)]
+    pub max_bundles: usize,
+
     /// Price bump (in %) for the transaction pool underpriced check.
     #[arg(long = "txpool.pricebump", default_value_t = DEFAULT_PRICE_BUMP)]
     pub price_bump: u128,
@@ -69,6 +74,11 @@ pub struct TxPoolArgs {
     /// Number of additional transaction validation tasks to spawn.
     #[arg(long = "txpool.additional-validation-tasks", alias = "txpool.additional_validation_tasks", default_value_t = DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS)]
     pub additional_validation_tasks: usize,
+    /// Number of tasks that validate transactions from the network in their own queue, so that
+    /// they can't delay the validation of local transactions. If set to 0, all transactions share
+    /// the same queue.
+    #[arg(long = "txpool.external-validation-tasks", default_value_t = DEFAULT_TXPOOL_EXTERNAL_VALIDATION_TASKS)]
+    pub external_validation_tasks: usize,
 
     /// Maximum number of pending transactions from the network to buffer
     #[arg(long = "txpool.max-pending-txns", alias = "txpool.max_pending_txns", default_value_t = PENDING_TX_LISTENER_BUFFER_SIZE)]
@@ -77,6 +87,27 @@ pub struct TxPoolArgs {
     /// Maximum number of new transactions to buffer
     #[arg(long = "txpool.max-new-txns", alias = "txpool.max_new_txns", default_value_t = NEW_TX_LISTENER_BUFFER_SIZE)]
     pub new_tx_listener_buffer_size: usize,
+
+    /// Persist the entire transaction pool to a journal as it changes, and reinsert the journaled
+    /// transactions on startup.
+    ///
+    /// This also keeps the blob sidecars of the blob store across restarts.
+    #[arg(long = "txpool.journal")]
+    pub journal: bool,
+
+    /// Simulate new pending transactions against the latest state to detect transactions that
+    /// would revert or use far less gas than their limit.
+    #[arg(long = "txpool.simulate")]
+    pub simulate: bool,
+
+    /// Max amount of gas spent on simulating transactions per block.
+    #[arg(long = "txpool.simulation-gas-budget", default_value_t = DEFAULT_SIMULATION_GAS_BUDGET, requires = "simulate")]
+    pub simulation_gas_budget: u64,
+
+    /// Record all transactions entering the pool and all canonical state updates to the given
+    /// file, for replay with `reth txpool replay`.
+    #[arg(long = "txpool.record", value_name = "FILE")]
+    pub record: Option<PathBuf>,
 }
 
 impl Default for TxPoolArgs {
@@ -89,6 +120,7 @@ impl Default for TxPoolArgs {
             queued_max_count: TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
             queued_max_size: TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
             max_account_slots: TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
+            max_bundles: TXPOOL_MAX_BUNDLES_DEFAULT,
             price_bump: DEFAULT_PRICE_BUMP,
             blob_transaction_price_bump: REPLACE_BLOB_PRICE_BUMP,
             max_tx_input_bytes: DEFAULT_MAX_TX_INPUT_BYTES,
@@ -97,12 +129,27 @@ impl Default for TxPoolArgs {
             locals: Default::default(),
             no_local_transactions_propagation: false,
             additional_validation_tasks: DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS,
+            external_validation_tasks: DEFAULT_TXPOOL_EXTERNAL_VALIDATION_TASKS,
             pending_tx_listener_buffer_size: PENDING_TX_LISTENER_BUFFER_SIZE,
             new_tx_listener_buffer_size: NEW_TX_LISTENER_BUFFER_SIZE,
+            journal: false,
+            simulate: false,
+            simulation_gas_budget: DEFAULT_SIMULATION_GAS_BUDGET,
+            record: None,
         }
     }
 }
 
+impl TxPoolArgs {
+    /// Returns the configuration of the validator's simulation stage, if enabled.
+    pub fn simulation_config(&self) -> Option<SimulationConfig> {
+        self.simulate.then(|| SimulationConfig {
+            gas_budget_per_block: self.simulation_gas_budget,
+            ..Default::default()
+        })
+    }
+}
+
 impl RethTransactionPoolConfig for TxPoolArgs {
     /// Returns transaction pool configuration.
     fn pool_config(&self) -> PoolConfig {
@@ -129,12 +176,14 @@ impl RethTransactionPoolConfig for TxPoolArgs {
                 max_size: self.queued_max_size * 1024 * 1024,
             },
             max_account_slots: self.max_account_slots,
+            max_bundles: self.max_bundles,
             price_bumps: PriceBumpConfig {
                 default_price_bump: self.price_bump,
                 replace_blob_tx_price_bump: self.blob_transaction_price_bump,
             },
             pending_tx_listener_buffer_size: self.pending_tx_listener_buffer_size,
             new_tx_listener_buffer_size: self.new_tx_listener_buffer_size,
+            ..Default::default()
         }
     }
 }
@@ -157,4 +206,43 @@ mod tests {
         let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
         assert_eq!(args, default_args);
     }
+
+    #[test]
+    fn txpool_parse_journal() {
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth", "--txpool.journal"]).args;
+        assert!(args.journal);
+    }
+
+    #[test]
+    fn txpool_parse_simulation() {
+        let args = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.simulation_config(), None);
+
+        let args = CommandParser::<TxPoolArgs>::parse_from([
+            "reth",
+            "--txpool.simulate",
+            "--txpool.simulation-gas-budget",
+            "1000000",
+        ])
+        .args;
+        assert_eq!(args.simulation_config().unwrap().gas_budget_per_block, 1_000_000);
+    }
+
+    #[test]
+    fn txpool_parse_external_validation_tasks() {
+        let args = CommandParser::<TxPoolArgs>::parse_from([
+            "reth",
+            "--txpool.external-validation-tasks",
+            "0",
+        ])
+        .args;
+        assert_eq!(args.external_validation_tasks, 0);
+    }
+
+    #[test]
+    fn txpool_parse_record() {
+        let args =
+            CommandParser::<TxPoolArgs>::parse_from(["reth", "--txpool.record", "txpool.rlp"]).args;
+        assert_eq!(args.record, Some(PathBuf::from("txpool.rlp")));
+    }
 }
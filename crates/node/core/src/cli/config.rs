@@ -1,9 +1,11 @@
 //! Config traits for various node components.
 
+use crate::args::PayloadBuilderStrategy;
 use reth_network::protocol::IntoRlpxSubProtocol;
 use reth_primitives::Bytes;
 use reth_transaction_pool::PoolConfig;
-use std::{borrow::Cow, time::Duration};
+use std::{borrow::Cow, path::Path, time::Duration};
+use url::Url;
 
 /// A trait that provides payload builder settings.
 ///
@@ -29,6 +31,26 @@ pub trait PayloadBuilderConfig {
 
     /// Maximum number of tasks to spawn for building a payload.
     fn max_payload_tasks(&self) -> usize;
+
+    /// Whether transaction execution results are reused across payload jobs.
+    fn simulation_cache(&self) -> bool;
+
+    /// The payload building strategies that compete in every payload job.
+    ///
+    /// If empty, payload jobs build with a single bundle-first strategy.
+    fn strategies(&self) -> &[PayloadBuilderStrategy];
+
+    /// The IPC socket path of the external builder of [`PayloadBuilderStrategy::External`].
+    fn external_builder_ipc(&self) -> Option<&Path>;
+
+    /// How long to wait for the external builder to respond.
+    fn external_builder_timeout(&self) -> Duration;
+
+    /// The MEV-Boost relays the payloads are submitted to.
+    fn relays(&self) -> &[Url];
+
+    /// The path to the file with the BLS secret key the bids submitted to relays are signed with.
+    fn relay_secret_key(&self) -> Option<&Path>;
 }
 
 /// A trait that represents the configured network and can be used to apply additional configuration
@@ -2,7 +2,9 @@
 
 use std::sync::Arc;
 
-use reth_basic_payload_builder::{BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig};
+use reth_basic_payload_builder::{
+    BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig, DatabaseParallelStateRoot,
+};
 use reth_chainspec::ChainSpec;
 use reth_evm::ConfigureEvm;
 use reth_evm_optimism::{OpExecutorProvider, OptimismEvmConfig};
@@ -22,8 +24,8 @@ use reth_payload_builder::{PayloadBuilderHandle, PayloadBuilderService};
 use reth_provider::CanonStateSubscriptions;
 use reth_tracing::tracing::{debug, info};
 use reth_transaction_pool::{
-    blobstore::DiskFileBlobStore, CoinbaseTipOrdering, TransactionPool,
-    TransactionValidationTaskExecutor,
+    blobstore::{DiskFileBlobStore, DiskFileBlobStoreConfig, OpenDiskFileBlobStore},
+    CoinbaseTipOrdering, TransactionPool, TransactionValidationTaskExecutor,
 };
 
 use crate::{
@@ -152,12 +154,18 @@ where
 
     async fn build_pool(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::Pool> {
         let data_dir = ctx.config().datadir();
-        let blob_store = DiskFileBlobStore::open(data_dir.blobstore(), Default::default())?;
+        let mut blob_store_config = DiskFileBlobStoreConfig::default();
+        if ctx.config().txpool.journal {
+            // the journal relies on the blob store to keep the sidecars of blob transactions
+            blob_store_config = blob_store_config.with_open(OpenDiskFileBlobStore::ReIndex);
+        }
+        let blob_store = DiskFileBlobStore::open(data_dir.blobstore(), blob_store_config)?;
 
         let validator = TransactionValidationTaskExecutor::eth_builder(ctx.chain_spec())
             .with_head_timestamp(ctx.head().timestamp)
             .kzg_settings(ctx.kzg_settings()?)
             .with_additional_tasks(ctx.config().txpool.additional_validation_tasks)
+            .with_external_tasks(ctx.config().txpool.external_validation_tasks)
             .build_with_tasks(
                 ctx.provider().clone(),
                 ctx.task_executor().clone(),
@@ -198,6 +206,42 @@ where
                 },
             );
 
+            if ctx.config().txpool.journal {
+                let journal_config =
+                    reth_transaction_pool::journal::TransactionJournalConfig::with_journal(
+                        data_dir.txpool_journal(),
+                    );
+
+                ctx.task_executor().spawn_critical_with_graceful_shutdown_signal(
+                    "transaction pool journal task",
+                    |shutdown| {
+                        reth_transaction_pool::journal::journal_transactions_task(
+                            shutdown,
+                            pool.clone(),
+                            journal_config,
+                        )
+                    },
+                );
+            }
+
+            if let Some(recording_path) = ctx.config().txpool.record.clone() {
+                let client = ctx.provider().clone();
+                let chain_events = ctx.provider().canonical_state_stream();
+                ctx.task_executor().spawn_critical_with_graceful_shutdown_signal(
+                    "transaction pool recorder task",
+                    |shutdown| {
+                        reth_transaction_pool::record::record_pool_task(
+                            shutdown,
+                            client,
+                            pool.clone(),
+                            chain_events,
+                            recording_path,
+                        )
+                    },
+                );
+                info!(target: "reth::cli", "Recording transaction pool");
+            }
+
             // spawn the maintenance task
             ctx.task_executor().spawn_critical(
                 "txpool maintenance task",
@@ -269,7 +313,10 @@ where
             payload_job_config,
             ctx.chain_spec(),
             payload_builder,
-        );
+        )
+        .with_parallel_state_root(Arc::new(DatabaseParallelStateRoot::<Node::DB, _>::new(
+            ctx.provider().clone(),
+        )));
         let (payload_service, payload_builder) =
             PayloadBuilderService::new(payload_generator, ctx.provider().canonical_state_stream());
 
@@ -7,6 +7,7 @@ use reqwest::Client;
 use reth_rpc_eth_api::RawTransactionForwarder;
 use reth_rpc_eth_types::error::{EthApiError, EthResult};
 use reth_rpc_types::ToRpcError;
+use reth_transaction_pool::TransactionConditional;
 
 /// Error type when interacting with the Sequencer
 #[derive(Debug, thiserror::Error)]
@@ -71,10 +72,36 @@ impl SequencerClient {
 
     /// Forwards a transaction to the sequencer endpoint.
     pub async fn forward_raw_transaction(&self, tx: &[u8]) -> Result<(), SequencerRpcError> {
+        self.send_request(
+            "eth_sendRawTransaction",
+            serde_json::json!([format!("0x{}", reth_primitives::hex::encode(tx))]),
+        )
+        .await
+    }
+
+    /// Forwards a conditional transaction to the sequencer endpoint.
+    pub async fn forward_raw_transaction_conditional(
+        &self,
+        tx: &[u8],
+        conditional: &TransactionConditional,
+    ) -> Result<(), SequencerRpcError> {
+        self.send_request(
+            "eth_sendRawTransactionConditional",
+            serde_json::json!([format!("0x{}", reth_primitives::hex::encode(tx)), conditional]),
+        )
+        .await
+    }
+
+    /// Sends a request with the given method and params to the sequencer endpoint.
+    async fn send_request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<(), SequencerRpcError> {
         let body = serde_json::to_string(&serde_json::json!({
             "jsonrpc": "2.0",
-            "method": "eth_sendRawTransaction",
-            "params": [format!("0x{}", reth_primitives::hex::encode(tx))],
+            "method": method,
+            "params": params,
             "id": self.next_request_id()
         }))
         .map_err(|_| {
@@ -110,6 +137,15 @@ impl RawTransactionForwarder for SequencerClient {
         Self::forward_raw_transaction(self, tx).await?;
         Ok(())
     }
+
+    async fn forward_raw_transaction_conditional(
+        &self,
+        tx: &[u8],
+        conditional: &TransactionConditional,
+    ) -> EthResult<()> {
+        Self::forward_raw_transaction_conditional(self, tx, conditional).await?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Default)]
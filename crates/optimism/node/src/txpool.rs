@@ -6,6 +6,7 @@ use reth_primitives::{Block, GotExpected, InvalidTransactionError, SealedBlock};
 use reth_provider::{BlockReaderIdExt, StateProviderFactory};
 use reth_revm::L1BlockInfo;
 use reth_transaction_pool::{
+    conditional::{ConditionalOutcome, TransactionConditional},
     CoinbaseTipOrdering, EthPoolTransaction, EthPooledTransaction, EthTransactionValidator, Pool,
     TransactionOrigin, TransactionValidationOutcome, TransactionValidationTaskExecutor,
     TransactionValidator,
@@ -129,6 +130,8 @@ where
             state_nonce,
             transaction: valid_tx,
             propagate,
+            authorities,
+            simulation,
         } = outcome
         {
             let l1_block_info = self.block_info.l1_block_info.read().clone();
@@ -165,6 +168,8 @@ where
                 state_nonce,
                 transaction: valid_tx,
                 propagate,
+                authorities,
+                simulation,
             }
         }
 
@@ -210,6 +215,10 @@ where
         self.inner.on_new_head_block(new_tip_block);
         self.update_l1_block_info(&new_tip_block.clone().unseal());
     }
+
+    fn validate_conditional(&self, conditional: &TransactionConditional) -> ConditionalOutcome {
+        self.inner.validate_conditional(conditional)
+    }
 }
 
 /// Tracks additional infos for the current block.
@@ -247,6 +247,9 @@ where
     let state = StateProviderDatabase::new(state_provider);
     let mut db =
         State::builder().with_database_ref(cached_reads.as_db(state)).with_bundle_update().build();
+    // computes the state root in the background while transactions are executed
+    let state_root_task =
+        config.state_root.as_ref().map(|state_root| state_root.spawn(config.parent_block.hash()));
     let extra_data = config.extra_data();
     let PayloadConfig {
         initialized_block_env,
@@ -316,6 +319,10 @@ where
         PayloadBuilderError::other(OptimismPayloadBuilderError::ForceCreate2DeployerFail)
     })?;
 
+    if let Some(task) = &state_root_task {
+        task.on_state_changed(&db);
+    }
+
     let mut receipts = Vec::with_capacity(attributes.transactions.len());
     for sequencer_tx in &attributes.transactions {
         // Check if the job was cancelled, if so we can exit early.
@@ -382,7 +389,10 @@ where
         // to release the db reference drop evm.
         drop(evm);
         // commit changes
-        db.commit(state);
+        match &state_root_task {
+            Some(task) => task.commit(&mut db, state),
+            None => db.commit(state),
+        }
 
         let gas_used = result.gas_used();
 
@@ -429,6 +439,16 @@ where
                 continue
             }
 
+            // the pool only checks conditionals against the earliest possible next block, so
+            // they're re-checked against the block that's actually built
+            if pool_tx.conditional.as_ref().is_some_and(|c| {
+                !c.check_block(block_number, attributes.payload_attributes.timestamp).is_met()
+            }) {
+                trace!(target: "payload_builder", tx = ?pool_tx.hash(), "skipping transaction whose conditional the payload doesn't meet");
+                best_txs.mark_invalid(&pool_tx);
+                continue
+            }
+
             // check if the job was cancelled, if so we can exit early
             if cancel.is_cancelled() {
                 return Ok(BuildOutcome::Cancelled)
@@ -472,7 +492,10 @@ where
             // drop evm so db is released.
             drop(evm);
             // commit changes
-            db.commit(state);
+            match &state_root_task {
+                Some(task) => task.commit(&mut db, state),
+                None => db.commit(state),
+            }
 
             let gas_used = result.gas_used();
 
@@ -515,6 +538,13 @@ where
         attributes.clone().payload_attributes.withdrawals,
     )?;
 
+    if let Some(task) = &state_root_task {
+        task.on_accounts_changed(
+            &db,
+            withdrawals.iter().flatten().map(|withdrawal| withdrawal.address),
+        );
+    }
+
     // merge all transitions into bundle state, this would apply the withdrawal balance changes
     // and 4788 contract call
     db.merge_transitions(BundleRetention::PlainState);
@@ -530,19 +560,21 @@ where
         .expect("Number is in range");
     let logs_bloom = execution_outcome.block_logs_bloom(block_number).expect("Number is in range");
 
-    // calculate the state root
+    // calculate the state root, reusing the background computation if it covered the final state
     let hashed_state = HashedPostState::from_bundle_state(&execution_outcome.state().state);
     let (state_root, trie_output) = {
         let state_provider = db.database.0.inner.borrow_mut();
-        state_provider.db.hashed_state_root_with_updates(hashed_state.clone()).inspect_err(
-            |err| {
-                warn!(target: "payload_builder",
-                    parent_hash=%parent_block.hash(),
-                    %err,
-                    "failed to calculate state root for empty payload"
-                );
-            },
-        )?
+        match state_root_task {
+            Some(task) => task.finish(&state_provider.db.0, hashed_state.clone()),
+            None => state_provider.db.0.hashed_state_root_with_updates(hashed_state.clone()),
+        }
+        .inspect_err(|err| {
+            warn!(target: "payload_builder",
+                parent_hash=%parent_block.hash(),
+                %err,
+                "failed to calculate state root for empty payload"
+            );
+        })?
     };
 
     // create the block header
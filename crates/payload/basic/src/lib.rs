@@ -13,13 +13,15 @@ use futures_core::ready;
 use futures_util::FutureExt;
 use reth_chainspec::{ChainSpec, EthereumHardforks};
 use reth_payload_builder::{
-    database::CachedReads, error::PayloadBuilderError, KeepPayloadJobAlive, PayloadId, PayloadJob,
-    PayloadJobGenerator,
+    database::CachedReads, error::PayloadBuilderError, InclusionListStore, KeepPayloadJobAlive,
+    PayloadBuildCancelReason, PayloadBuildIteration, PayloadBuildIterationSender,
+    PayloadBuildOutcome, PayloadId, PayloadJob, PayloadJobGenerator, SimulationCache,
 };
 use reth_payload_primitives::{BuiltPayload, PayloadBuilderAttributes};
 use reth_primitives::{
     constants::{EMPTY_WITHDRAWALS, RETH_CLIENT_VERSION, SLOT_DURATION},
-    proofs, BlockNumberOrTag, Bytes, SealedBlock, Withdrawals, B256, U256,
+    proofs, BlockNumberOrTag, Bytes, SealedBlock, TransactionSignedEcRecovered, Withdrawals, B256,
+    U256,
 };
 use reth_provider::{
     BlockReaderIdExt, BlockSource, CanonStateNotification, ProviderError, StateProviderFactory,
@@ -36,9 +38,12 @@ use std::{
     future::Future,
     ops::Deref,
     pin::Pin,
-    sync::{atomic::AtomicBool, Arc},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tokio::{
     sync::{oneshot, Semaphore},
@@ -47,6 +52,16 @@ use tokio::{
 use tracing::{debug, trace, warn};
 
 mod metrics;
+mod state_root;
+pub use state_root::{
+    hashed_transitions, DatabaseParallelStateRoot, IncrementalStateRoot,
+    IncrementalStateRootSpawner, ParallelStateRootProvider,
+};
+mod strategy;
+pub use strategy::{
+    BoxedPayloadBuilder, MultiStrategyPayloadJob, MultiStrategyPayloadJobGenerator,
+    PayloadStrategy, ResolveBestStrategyPayload,
+};
 
 /// The [`PayloadJobGenerator`] that creates [`BasicPayloadJob`]s.
 #[derive(Debug)]
@@ -69,6 +84,14 @@ pub struct BasicPayloadJobGenerator<Client, Pool, Tasks, Builder> {
     builder: Builder,
     /// Stored `cached_reads` for new payload jobs.
     pre_cached: Option<PrecachedState>,
+    /// Where jobs report their build attempts to.
+    iteration_sender: PayloadBuildIterationSender,
+    /// The inclusion lists payloads must satisfy.
+    inclusion_lists: InclusionListStore,
+    /// The cache of transaction execution results shared with the service.
+    simulation_cache: SimulationCache,
+    /// Computes the state roots of payloads in the background, if configured.
+    parallel_state_root: Option<Arc<dyn ParallelStateRootProvider>>,
 }
 
 // === impl BasicPayloadJobGenerator ===
@@ -93,31 +116,28 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
             chain_spec,
             builder,
             pre_cached: None,
+            iteration_sender: Default::default(),
+            inclusion_lists: Default::default(),
+            simulation_cache: Default::default(),
+            parallel_state_root: None,
         }
     }
 
-    /// Returns the maximum duration a job should be allowed to run.
-    ///
-    /// This adheres to the following specification:
-    // > Client software SHOULD stop the updating process when either a call to engine_getPayload
-    // > with the build process's payloadId is made or SECONDS_PER_SLOT (12s in the Mainnet
-    // > configuration) have passed since the point in time identified by the timestamp parameter.
-    // See also <https://github.com/ethereum/execution-apis/blob/431cf72fd3403d946ca3e3afc36b973fc87e0e89/src/engine/paris.md?plain=1#L137>
-    #[inline]
-    fn max_job_duration(&self, unix_timestamp: u64) -> Duration {
-        let duration_until_timestamp = duration_until(unix_timestamp);
-
-        // safety in case clocks are bad
-        let duration_until_timestamp = duration_until_timestamp.min(self.config.deadline * 3);
-
-        self.config.deadline + duration_until_timestamp
+    /// Sets the provider that computes the state roots of payloads in parallel while their
+    /// transactions are executed.
+    pub fn with_parallel_state_root(
+        mut self,
+        parallel_state_root: Arc<dyn ParallelStateRootProvider>,
+    ) -> Self {
+        self.parallel_state_root = Some(parallel_state_root);
+        self
     }
 
     /// Returns the [Instant](tokio::time::Instant) at which the job should be terminated because it
     /// is considered timed out.
     #[inline]
     fn job_deadline(&self, unix_timestamp: u64) -> tokio::time::Instant {
-        tokio::time::Instant::now() + self.max_job_duration(unix_timestamp)
+        self.config.job_deadline(unix_timestamp)
     }
 
     /// Returns a reference to the tasks type
@@ -132,6 +152,34 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
     }
 }
 
+/// Returns the parent block to build a payload on for the given attributes.
+///
+/// If the parent is zero, the payload is built on the latest block.
+fn find_parent_block<Client, Attributes>(
+    client: &Client,
+    attributes: &Attributes,
+) -> Result<SealedBlock, PayloadBuilderError>
+where
+    Client: BlockReaderIdExt,
+    Attributes: PayloadBuilderAttributes,
+{
+    let parent_block = if attributes.parent().is_zero() {
+        // use latest block if parent is zero: genesis block
+        client
+            .block_by_number_or_tag(BlockNumberOrTag::Latest)?
+            .ok_or_else(|| PayloadBuilderError::MissingParentBlock(attributes.parent()))?
+            .seal_slow()
+    } else {
+        let block = client
+            .find_block_by_hash(attributes.parent(), BlockSource::Any)?
+            .ok_or_else(|| PayloadBuilderError::MissingParentBlock(attributes.parent()))?;
+
+        // we already know the hash, so we can seal it
+        block.seal(attributes.parent())
+    };
+    Ok(parent_block)
+}
+
 // === impl BasicPayloadJobGenerator ===
 
 impl<Client, Pool, Tasks, Builder> PayloadJobGenerator
@@ -150,28 +198,19 @@ where
         &self,
         attributes: <Self::Job as PayloadJob>::PayloadAttributes,
     ) -> Result<Self::Job, PayloadBuilderError> {
-        let parent_block = if attributes.parent().is_zero() {
-            // use latest block if parent is zero: genesis block
-            self.client
-                .block_by_number_or_tag(BlockNumberOrTag::Latest)?
-                .ok_or_else(|| PayloadBuilderError::MissingParentBlock(attributes.parent()))?
-                .seal_slow()
-        } else {
-            let block = self
-                .client
-                .find_block_by_hash(attributes.parent(), BlockSource::Any)?
-                .ok_or_else(|| PayloadBuilderError::MissingParentBlock(attributes.parent()))?;
-
-            // we already know the hash, so we can seal it
-            block.seal(attributes.parent())
-        };
+        let parent_block = find_parent_block(&self.client, &attributes)?;
 
         let config = PayloadConfig::new(
             Arc::new(parent_block),
             self.config.extradata.clone(),
             attributes,
             Arc::clone(&self.chain_spec),
-        );
+        )
+        .with_inclusion_lists(self.inclusion_lists.clone())
+        .with_simulation_cache(self.config.simulation_cache.then(|| self.simulation_cache.clone()))
+        .with_state_root(self.parallel_state_root.clone().map(|state_root| {
+            IncrementalStateRootSpawner::new(Box::new(self.executor.clone()), state_root)
+        }));
 
         let until = self.job_deadline(config.attributes.timestamp());
         let deadline = Box::pin(tokio::time::sleep_until(until));
@@ -192,6 +231,7 @@ where
             payload_task_guard: self.payload_task_guard.clone(),
             metrics: Default::default(),
             builder: self.builder.clone(),
+            iteration_sender: self.iteration_sender.clone(),
         };
 
         // start the first job right away
@@ -201,6 +241,36 @@ where
     }
 
     fn on_new_state(&mut self, new_state: CanonStateNotification) {
+        self.pre_cached = Some(PrecachedState::from_notification(&new_state));
+    }
+
+    fn set_iteration_sender(&mut self, sender: PayloadBuildIterationSender) {
+        self.iteration_sender = sender;
+    }
+
+    fn set_inclusion_lists(&mut self, inclusion_lists: InclusionListStore) {
+        self.inclusion_lists = inclusion_lists;
+    }
+
+    fn set_simulation_cache(&mut self, simulation_cache: SimulationCache) {
+        self.simulation_cache = simulation_cache;
+    }
+}
+
+/// Pre-filled [`CachedReads`] for a specific block.
+///
+/// This is extracted from the [`CanonStateNotification`] for the tip block.
+#[derive(Debug, Clone)]
+pub struct PrecachedState {
+    /// The block for which the state is pre-cached.
+    pub block: B256,
+    /// Cached state for the block.
+    pub cached: CachedReads,
+}
+
+impl PrecachedState {
+    /// Extracts the state changed by the committed chain of the notification.
+    pub fn from_notification(new_state: &CanonStateNotification) -> Self {
         let mut cached = CachedReads::default();
 
         // extract the state from the notification and put it into the cache
@@ -216,21 +286,10 @@ where
             }
         }
 
-        self.pre_cached = Some(PrecachedState { block: committed.tip().hash(), cached });
+        Self { block: committed.tip().hash(), cached }
     }
 }
 
-/// Pre-filled [`CachedReads`] for a specific block.
-///
-/// This is extracted from the [`CanonStateNotification`] for the tip block.
-#[derive(Debug, Clone)]
-pub struct PrecachedState {
-    /// The block for which the state is pre-cached.
-    pub block: B256,
-    /// Cached state for the block.
-    pub cached: CachedReads,
-}
-
 /// Restricts how many generator tasks can be executed at once.
 #[derive(Debug, Clone)]
 pub struct PayloadTaskGuard(Arc<Semaphore>);
@@ -265,6 +324,8 @@ pub struct BasicPayloadJobGeneratorConfig {
     deadline: Duration,
     /// Maximum number of tasks to spawn for building a payload.
     max_payload_tasks: usize,
+    /// Whether payload jobs reuse transaction execution results of the [`SimulationCache`].
+    simulation_cache: bool,
 }
 
 // === impl BasicPayloadJobGeneratorConfig ===
@@ -300,6 +361,39 @@ impl BasicPayloadJobGeneratorConfig {
         self.extradata = extradata;
         self
     }
+
+    /// Sets whether payload jobs reuse transaction execution results of the [`SimulationCache`]
+    /// shared with the payload builder service.
+    ///
+    /// Disabled by default.
+    pub const fn simulation_cache(mut self, enabled: bool) -> Self {
+        self.simulation_cache = enabled;
+        self
+    }
+
+    /// Returns the maximum duration a job should be allowed to run.
+    ///
+    /// This adheres to the following specification:
+    // > Client software SHOULD stop the updating process when either a call to engine_getPayload
+    // > with the build process's payloadId is made or SECONDS_PER_SLOT (12s in the Mainnet
+    // > configuration) have passed since the point in time identified by the timestamp parameter.
+    // See also <https://github.com/ethereum/execution-apis/blob/431cf72fd3403d946ca3e3afc36b973fc87e0e89/src/engine/paris.md?plain=1#L137>
+    #[inline]
+    fn max_job_duration(&self, unix_timestamp: u64) -> Duration {
+        let duration_until_timestamp = duration_until(unix_timestamp);
+
+        // safety in case clocks are bad
+        let duration_until_timestamp = duration_until_timestamp.min(self.deadline * 3);
+
+        self.deadline + duration_until_timestamp
+    }
+
+    /// Returns the [Instant](tokio::time::Instant) at which a job for the given timestamp should be
+    /// terminated because it is considered timed out.
+    #[inline]
+    fn job_deadline(&self, unix_timestamp: u64) -> tokio::time::Instant {
+        tokio::time::Instant::now() + self.max_job_duration(unix_timestamp)
+    }
 }
 
 impl Default for BasicPayloadJobGeneratorConfig {
@@ -310,6 +404,7 @@ impl Default for BasicPayloadJobGeneratorConfig {
             // 12s slot time
             deadline: SLOT_DURATION,
             max_payload_tasks: 3,
+            simulation_cache: false,
         }
     }
 }
@@ -349,6 +444,8 @@ where
     ///
     /// See [`PayloadBuilder`]
     builder: Builder,
+    /// Where build attempts are reported to.
+    iteration_sender: PayloadBuildIterationSender,
 }
 
 impl<Client, Pool, Tasks, Builder> BasicPayloadJob<Client, Pool, Tasks, Builder>
@@ -374,18 +471,22 @@ where
         self.metrics.inc_initiated_payload_builds();
         let cached_reads = self.cached_reads.take().unwrap_or_default();
         let builder = self.builder.clone();
+        let iteration_sender = self.iteration_sender.clone();
         self.executor.spawn_blocking(Box::pin(async move {
             // acquire the permit for executing the task
             let _permit = guard.acquire().await;
+            let started_at = Instant::now();
+            let id = payload_config.payload_id();
             let args = BuildArguments {
                 client,
                 pool,
                 cached_reads,
                 config: payload_config,
-                cancel,
+                cancel: cancel.clone(),
                 best_payload,
             };
             let result = builder.try_build(args);
+            iteration_sender.send(build_iteration(id, started_at, &cancel, &result));
             let _ = tx.send(result);
         }));
 
@@ -593,6 +694,9 @@ where
 
         if let Some(best) = this.best_payload.take() {
             debug!(target: "payload_builder", "resolving best payload");
+            if let Some(pending) = this.maybe_better.take() {
+                pending.cancel(PayloadBuildCancelReason::Resolved);
+            }
             return Poll::Ready(Ok(best))
         }
 
@@ -638,6 +742,11 @@ impl<P> PendingPayload<P> {
     ) -> Self {
         Self { _cancel: cancel, payload }
     }
+
+    /// Cancels the job for the given reason.
+    pub fn cancel(self, reason: PayloadBuildCancelReason) {
+        self._cancel.cancel(reason);
+    }
 }
 
 impl<P> Future for PendingPayload<P> {
@@ -651,22 +760,53 @@ impl<P> Future for PendingPayload<P> {
 
 /// A marker that can be used to cancel a job.
 ///
-/// If dropped, it will set the `cancelled` flag to true.
+/// If dropped, it will cancel the job with [`PayloadBuildCancelReason::Terminated`], unless it was
+/// already cancelled.
 #[derive(Default, Clone, Debug)]
-pub struct Cancelled(Arc<AtomicBool>);
+pub struct Cancelled(Arc<AtomicU8>);
 
 // === impl Cancelled ===
 
 impl Cancelled {
+    /// The job was not cancelled.
+    const NOT_CANCELLED: u8 = 0;
+    /// The job was cancelled with [`PayloadBuildCancelReason::Resolved`].
+    const RESOLVED: u8 = 1;
+    /// The job was cancelled with [`PayloadBuildCancelReason::Terminated`].
+    const TERMINATED: u8 = 2;
+
     /// Returns true if the job was cancelled.
     pub fn is_cancelled(&self) -> bool {
-        self.0.load(std::sync::atomic::Ordering::Relaxed)
+        self.0.load(Ordering::Relaxed) != Self::NOT_CANCELLED
+    }
+
+    /// Returns why the job was cancelled, if it was.
+    pub fn reason(&self) -> Option<PayloadBuildCancelReason> {
+        match self.0.load(Ordering::Relaxed) {
+            Self::RESOLVED => Some(PayloadBuildCancelReason::Resolved),
+            Self::TERMINATED => Some(PayloadBuildCancelReason::Terminated),
+            _ => None,
+        }
+    }
+
+    /// Cancels the job for the given reason, unless it was already cancelled.
+    pub fn cancel(&self, reason: PayloadBuildCancelReason) {
+        let reason = match reason {
+            PayloadBuildCancelReason::Resolved => Self::RESOLVED,
+            PayloadBuildCancelReason::Terminated => Self::TERMINATED,
+        };
+        let _ = self.0.compare_exchange(
+            Self::NOT_CANCELLED,
+            reason,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
     }
 }
 
 impl Drop for Cancelled {
     fn drop(&mut self) {
-        self.0.store(true, std::sync::atomic::Ordering::Relaxed);
+        self.cancel(PayloadBuildCancelReason::Terminated);
     }
 }
 
@@ -685,6 +825,12 @@ pub struct PayloadConfig<Attributes> {
     pub attributes: Attributes,
     /// The chain spec.
     pub chain_spec: Arc<ChainSpec>,
+    /// The inclusion lists received from the consensus layer.
+    pub inclusion_lists: InclusionListStore,
+    /// The cache of transaction execution results the builder can reuse, if enabled.
+    pub simulation_cache: Option<SimulationCache>,
+    /// Spawns the tasks that compute the state root while transactions are executed, if enabled.
+    pub state_root: Option<IncrementalStateRootSpawner>,
 }
 
 impl<Attributes> PayloadConfig<Attributes> {
@@ -692,6 +838,32 @@ impl<Attributes> PayloadConfig<Attributes> {
     pub fn extra_data(&self) -> Bytes {
         self.extra_data.clone()
     }
+
+    /// Sets the inclusion lists the payload must satisfy.
+    pub fn with_inclusion_lists(mut self, inclusion_lists: InclusionListStore) -> Self {
+        self.inclusion_lists = inclusion_lists;
+        self
+    }
+
+    /// Returns the inclusion list for the parent block of the payload, if any.
+    ///
+    /// Inclusion lists can arrive while the payload is being built, so this should be checked on
+    /// every build attempt.
+    pub fn inclusion_list(&self) -> Option<Arc<Vec<TransactionSignedEcRecovered>>> {
+        self.inclusion_lists.get(&self.parent_block.hash())
+    }
+
+    /// Sets the cache of transaction execution results the builder can reuse.
+    pub fn with_simulation_cache(mut self, simulation_cache: Option<SimulationCache>) -> Self {
+        self.simulation_cache = simulation_cache;
+        self
+    }
+
+    /// Sets the spawner of the tasks that compute the state root while transactions are executed.
+    pub fn with_state_root(mut self, state_root: Option<IncrementalStateRootSpawner>) -> Self {
+        self.state_root = state_root;
+        self
+    }
 }
 
 impl<Attributes> PayloadConfig<Attributes>
@@ -716,6 +888,9 @@ where
             extra_data,
             attributes,
             chain_spec,
+            inclusion_lists: Default::default(),
+            simulation_cache: None,
+            state_root: None,
         }
     }
 
@@ -918,6 +1093,28 @@ pub fn commit_withdrawals<DB: Database<Error = ProviderError>>(
     })
 }
 
+/// Summarizes the result of a build attempt that was started at the given instant.
+fn build_iteration<Payload: BuiltPayload>(
+    id: PayloadId,
+    started_at: Instant,
+    cancel: &Cancelled,
+    result: &Result<BuildOutcome<Payload>, PayloadBuilderError>,
+) -> PayloadBuildIteration {
+    let outcome = match result {
+        Ok(BuildOutcome::Better { payload, .. }) => PayloadBuildOutcome::Better {
+            transactions: payload.block().body.len(),
+            gas_used: payload.block().gas_used,
+            fees: payload.fees(),
+        },
+        Ok(BuildOutcome::Aborted { fees, .. }) => PayloadBuildOutcome::Aborted { fees: *fees },
+        Ok(BuildOutcome::Cancelled) => PayloadBuildOutcome::Cancelled(
+            cancel.reason().unwrap_or(PayloadBuildCancelReason::Terminated),
+        ),
+        Err(err) => PayloadBuildOutcome::Failed(err.to_string()),
+    };
+    PayloadBuildIteration { id, elapsed: started_at.elapsed(), outcome }
+}
+
 /// Checks if the new payload is better than the current best.
 ///
 /// This compares the total fees of the blocks, higher is better.
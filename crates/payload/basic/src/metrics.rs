@@ -27,3 +27,17 @@ impl PayloadBuilderMetrics {
         self.failed_payload_builds.increment(1);
     }
 }
+
+/// Metrics of a single strategy of a [`MultiStrategyPayloadJob`](crate::MultiStrategyPayloadJob)
+#[derive(Metrics, Clone)]
+#[metrics(scope = "payloads.strategy")]
+pub(crate) struct PayloadStrategyMetrics {
+    /// Total number of initiated payload build attempts of the strategy
+    pub(crate) initiated_payload_builds: Counter,
+    /// Total number of failed payload build attempts of the strategy
+    pub(crate) failed_payload_builds: Counter,
+    /// Total number of payloads of the strategy that became the best payload of their job
+    pub(crate) best_payloads: Counter,
+    /// Total number of resolved payloads that were built by the strategy
+    pub(crate) resolved_payloads: Counter,
+}
@@ -0,0 +1,451 @@
+//! Incremental state root computation that runs alongside payload execution.
+
+use reth_db_api::database::Database;
+use reth_primitives::{keccak256, Address, B256};
+use reth_provider::{
+    providers::ConsistentDbView, DatabaseProviderFactory, ProviderError, ProviderResult,
+    StateRootProvider,
+};
+use reth_tasks::TaskSpawner;
+use reth_trie::{updates::TrieUpdates, HashedPostState, HashedStorage};
+use reth_trie_parallel::parallel_root::ParallelStateRoot;
+use revm::{
+    db::{states::TransitionState, State},
+    primitives::EvmState,
+    DatabaseCommit,
+};
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{
+        mpsc::{channel, sync_channel, Receiver, Sender},
+        Arc,
+    },
+};
+use tracing::{debug, trace};
+
+/// Computes state roots in parallel on top of a block that is persisted in the database.
+pub trait ParallelStateRootProvider: Send + Sync + fmt::Debug {
+    /// Computes the state root of the hashed post state on top of the given parent block.
+    ///
+    /// Returns an error if the parent block is not the latest block in the database.
+    fn parallel_state_root_with_updates(
+        &self,
+        parent_hash: B256,
+        hashed_state: HashedPostState,
+    ) -> ProviderResult<(B256, TrieUpdates)>;
+}
+
+/// A [`ParallelStateRootProvider`] that computes state roots with [`ParallelStateRoot`].
+pub struct DatabaseParallelStateRoot<DB, Provider> {
+    /// The provider to open consistent database views with.
+    provider: Provider,
+    _database: PhantomData<DB>,
+}
+
+impl<DB, Provider> DatabaseParallelStateRoot<DB, Provider> {
+    /// Creates a new [`DatabaseParallelStateRoot`] for the given provider.
+    pub const fn new(provider: Provider) -> Self {
+        Self { provider, _database: PhantomData }
+    }
+}
+
+impl<DB, Provider> ParallelStateRootProvider for DatabaseParallelStateRoot<DB, Provider>
+where
+    DB: Database,
+    Provider: DatabaseProviderFactory<DB> + Clone + Send + Sync,
+{
+    fn parallel_state_root_with_updates(
+        &self,
+        parent_hash: B256,
+        hashed_state: HashedPostState,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        // the view fails on every read if the parent is not the tip of the database
+        let view = ConsistentDbView::new(self.provider.clone(), Some(parent_hash));
+        ParallelStateRoot::new(view, hashed_state)
+            .incremental_root_with_updates()
+            .map_err(ProviderError::from)
+    }
+}
+
+impl<DB, Provider> fmt::Debug for DatabaseParallelStateRoot<DB, Provider> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DatabaseParallelStateRoot").finish_non_exhaustive()
+    }
+}
+
+/// Spawns [`IncrementalStateRoot`] workers on a task executor.
+#[derive(Clone, Debug)]
+pub struct IncrementalStateRootSpawner {
+    /// The executor to spawn the workers on.
+    executor: Box<dyn TaskSpawner>,
+    /// Computes the state roots.
+    state_root: Arc<dyn ParallelStateRootProvider>,
+}
+
+impl IncrementalStateRootSpawner {
+    /// Creates a new [`IncrementalStateRootSpawner`].
+    pub fn new(
+        executor: Box<dyn TaskSpawner>,
+        state_root: Arc<dyn ParallelStateRootProvider>,
+    ) -> Self {
+        Self { executor, state_root }
+    }
+
+    /// Spawns a worker that computes the state root of a payload built on top of the given
+    /// parent block.
+    pub fn spawn(&self, parent_hash: B256) -> IncrementalStateRoot {
+        IncrementalStateRoot::spawn(&*self.executor, self.state_root.clone(), parent_hash)
+    }
+}
+
+/// Computes the state root of a payload incrementally while its transactions are being executed.
+///
+/// State changes committed to the payload's [`State`] are streamed to a blocking task that keeps
+/// computing the state root of everything committed so far with [`ParallelStateRoot`], batching the
+/// updates that arrive while an iteration is running. All changes of the block, including system
+/// calls and withdrawals, must be streamed for the result of the worker to be used: if the last
+/// iteration of the worker covered the final post state of the block,
+/// [`IncrementalStateRoot::finish`] returns its result.
+///
+/// The streamed state is only used as a hint: the final root is always computed for the
+/// authoritative post state of the block, so changes that are missing do not affect the result.
+#[derive(Debug)]
+pub struct IncrementalStateRoot {
+    /// Sender half for state updates of committed transactions.
+    updates: Sender<HashedPostState>,
+    /// Receives the progress of the worker once it exits.
+    progress: Receiver<IncrementalProgress>,
+    /// Computes the state roots.
+    state_root: Arc<dyn ParallelStateRootProvider>,
+    /// The parent block of the payload.
+    parent_hash: B256,
+}
+
+impl IncrementalStateRoot {
+    /// Spawns the worker that computes the state root on top of the given parent block as a
+    /// blocking task.
+    pub fn spawn(
+        executor: &dyn TaskSpawner,
+        state_root: Arc<dyn ParallelStateRootProvider>,
+        parent_hash: B256,
+    ) -> Self {
+        let (updates, rx) = channel();
+        let (progress_tx, progress) = sync_channel(1);
+        let worker_state_root = state_root.clone();
+        executor.spawn_blocking(Box::pin(async move {
+            let progress = run_worker(&*worker_state_root, parent_hash, rx);
+            let _ = progress_tx.send(progress);
+        }));
+        Self { updates, progress, state_root, parent_hash }
+    }
+
+    /// Commits the state changes of a transaction to the database and streams them to the worker.
+    pub fn commit<DB: revm::Database>(&self, db: &mut State<DB>, state: EvmState) {
+        let addresses: Vec<_> = state.keys().copied().collect();
+        db.commit(state);
+        self.on_accounts_changed(db, addresses);
+    }
+
+    /// Streams all changes committed to the database since the start of the block to the worker.
+    ///
+    /// This is meant for changes of system calls, where the changed accounts aren't known.
+    pub fn on_state_changed<DB>(&self, db: &State<DB>) {
+        if let Some(transitions) = &db.transition_state {
+            self.update(hashed_transitions(transitions, transitions.transitions.keys().copied()));
+        }
+    }
+
+    /// Streams the changes committed to the given accounts since the start of the block to the
+    /// worker.
+    pub fn on_accounts_changed<DB>(
+        &self,
+        db: &State<DB>,
+        addresses: impl IntoIterator<Item = Address>,
+    ) {
+        if let Some(transitions) = &db.transition_state {
+            self.update(hashed_transitions(transitions, addresses));
+        }
+    }
+
+    /// Streams already hashed state changes to the worker.
+    pub fn update(&self, hashed_state: HashedPostState) {
+        // the worker only exits early if it failed, in which case `finish` falls back to
+        // computing the root from scratch
+        let _ = self.updates.send(hashed_state);
+    }
+
+    /// Stops the worker and computes the final state root of the given post state.
+    ///
+    /// If the parent block is not the tip of the database, the root is computed with the
+    /// provided state provider instead, which must be at the parent block of the payload.
+    ///
+    /// Returns the state root and the trie updates relative to the parent state.
+    pub fn finish<P: StateRootProvider>(
+        self,
+        state_provider: &P,
+        hashed_state: HashedPostState,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        let Self { updates, progress, state_root, parent_hash } = self;
+        drop(updates);
+
+        let progress = progress.recv().unwrap_or_else(|_| {
+            debug!(target: "payload_builder", "payload state root worker exited unexpectedly");
+            IncrementalProgress::default()
+        });
+        trace!(target: "payload_builder", iterations = progress.iterations, "finishing incremental state root");
+
+        if progress.state == hashed_state {
+            if let Some(output) = progress.output {
+                return Ok(output)
+            }
+        }
+
+        if !progress.unavailable {
+            match state_root.parallel_state_root_with_updates(parent_hash, hashed_state.clone()) {
+                Ok(output) => return Ok(output),
+                Err(err) => {
+                    debug!(target: "payload_builder", %err, "failed to compute parallel state root")
+                }
+            }
+        }
+        state_provider.hashed_state_root_with_updates(hashed_state)
+    }
+}
+
+/// Hashes the transitions of the given accounts.
+///
+/// The transitions of an account contain all changes since the start of the block, they're hashed
+/// the same way as [`HashedPostState::from_bundle_state`] hashes the bundle state they're merged
+/// into at the end of the block.
+pub fn hashed_transitions(
+    transitions: &TransitionState,
+    addresses: impl IntoIterator<Item = Address>,
+) -> HashedPostState {
+    let mut hashed_state = HashedPostState::default();
+    for address in addresses {
+        let Some(account) = transitions.transitions.get(&address) else { continue };
+        let hashed_address = keccak256(address);
+        hashed_state.accounts.insert(hashed_address, account.info.clone().map(Into::into));
+        hashed_state.storages.insert(
+            hashed_address,
+            HashedStorage::from_plain_storage(
+                account.status,
+                account.storage.iter().map(|(slot, value)| (slot, &value.present_value)),
+            ),
+        );
+    }
+    hashed_state
+}
+
+/// Receives state updates and computes the state root until the sender is dropped.
+fn run_worker(
+    state_root: &dyn ParallelStateRootProvider,
+    parent_hash: B256,
+    rx: Receiver<HashedPostState>,
+) -> IncrementalProgress {
+    let mut progress = IncrementalProgress::default();
+    while let Ok(update) = rx.recv() {
+        let mut target = progress.state.clone();
+        target.extend(update);
+        // batch all updates that arrived while the previous iteration was running, the last batch
+        // is still computed once the sender is dropped since `finish` waits for it
+        while let Ok(update) = rx.try_recv() {
+            target.extend(update);
+        }
+
+        match state_root.parallel_state_root_with_updates(parent_hash, target.clone()) {
+            Ok(output) => {
+                progress.state = target;
+                progress.output = Some(output);
+                progress.iterations += 1;
+            }
+            Err(err) => {
+                debug!(target: "payload_builder", %err, "failed to advance incremental state root");
+                // the parent is not the tip of the database, so `finish` falls back to the
+                // state provider
+                progress.unavailable = true;
+                return progress
+            }
+        }
+    }
+    progress
+}
+
+/// The state the root has been computed for so far.
+#[derive(Debug, Default)]
+struct IncrementalProgress {
+    /// The hashed state the current output was computed for.
+    state: HashedPostState,
+    /// The state root and trie updates of the hashed state.
+    output: Option<(B256, TrieUpdates)>,
+    /// Whether the state root can't be computed in parallel.
+    unavailable: bool,
+    /// Number of completed iterations.
+    iterations: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::tables;
+    use reth_db_api::transaction::DbTxMut;
+    use reth_primitives::{Account, Address, Header, StorageEntry, U256};
+    use reth_provider::{
+        test_utils::create_test_provider_factory, HashingWriter, ProviderFactory, TrieWriter,
+    };
+    use reth_tasks::TokioTaskExecutor;
+    use reth_trie::StateRoot;
+    use reth_trie_db::DatabaseStateRoot;
+
+    const A: Address = Address::with_last_byte(1);
+    const B: Address = Address::with_last_byte(2);
+    const C: Address = Address::with_last_byte(3);
+    const D: Address = Address::with_last_byte(4);
+
+    fn account(balance: u64) -> Account {
+        Account { balance: U256::from(balance), ..Default::default() }
+    }
+
+    fn slot(slot: u64) -> B256 {
+        keccak256(B256::from(U256::from(slot)))
+    }
+
+    fn state(
+        accounts: impl IntoIterator<Item = (Address, Option<Account>)>,
+        storages: impl IntoIterator<Item = (Address, bool, Vec<(u64, u64)>)>,
+    ) -> HashedPostState {
+        let mut state = HashedPostState::default();
+        for (address, account) in accounts {
+            state.accounts.insert(keccak256(address), account);
+        }
+        for (address, wiped, slots) in storages {
+            let storage = HashedStorage::from_iter(
+                wiped,
+                slots.into_iter().map(|(key, value)| (slot(key), U256::from(value))),
+            );
+            state.storages.insert(keccak256(address), storage);
+        }
+        state
+    }
+
+    /// Writes the parent state, its trie and its header to a fresh database and returns the hash
+    /// of the parent block.
+    fn setup<DB: Database>(factory: &ProviderFactory<DB>) -> B256 {
+        let provider = factory.provider_rw().unwrap();
+
+        let mut accounts =
+            vec![(A, Some(account(1))), (B, Some(account(2))), (C, Some(account(3)))];
+        accounts.extend((100..200).map(|i| (Address::with_last_byte(i), Some(account(i.into())))));
+        provider.insert_account_for_hashing(accounts).unwrap();
+        provider
+            .insert_storage_for_hashing([
+                (
+                    A,
+                    (1..20)
+                        .map(|key| StorageEntry {
+                            key: B256::from(U256::from(key)),
+                            value: U256::from(key),
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+                (
+                    B,
+                    (1..20)
+                        .map(|key| StorageEntry {
+                            key: B256::from(U256::from(key)),
+                            value: U256::from(key + 100),
+                        })
+                        .collect(),
+                ),
+            ])
+            .unwrap();
+        let (_, updates) = StateRoot::from_tx(provider.tx_ref()).root_with_updates().unwrap();
+        provider.write_trie_updates(&updates).unwrap();
+
+        let parent = Header::default().seal_slow();
+        provider.tx_ref().put::<tables::Headers>(0, parent.header().clone()).unwrap();
+        provider.tx_ref().put::<tables::CanonicalHeaders>(0, parent.hash()).unwrap();
+        provider.commit().unwrap();
+        parent.hash()
+    }
+
+    /// Streams the updates to an incremental state root and asserts that the root and trie
+    /// updates of the final state are the same as the ones computed from scratch.
+    fn assert_incremental_root<DB: Database + Clone + 'static>(
+        factory: &ProviderFactory<DB>,
+        parent_hash: B256,
+        updates: Vec<HashedPostState>,
+        final_state: HashedPostState,
+    ) {
+        let expected =
+            factory.latest().unwrap().hashed_state_root_with_updates(final_state.clone()).unwrap();
+
+        let task = IncrementalStateRoot::spawn(
+            &TokioTaskExecutor::default(),
+            Arc::new(DatabaseParallelStateRoot::new(factory.clone())),
+            parent_hash,
+        );
+        for update in updates {
+            task.update(update);
+        }
+        let actual = task.finish(&factory.latest().unwrap(), final_state).unwrap();
+
+        assert_eq!(actual.0, expected.0);
+        assert_eq!(actual.1, expected.1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn incremental_root_matches_state_root() {
+        let factory = create_test_provider_factory();
+        let parent_hash = setup(&factory);
+
+        let updates = vec![
+            // the storage slots of `A` are changed, but revert to their parent values before the
+            // end of the block
+            state([(A, Some(account(10)))], [(A, false, vec![(1, 10), (2, 20), (3, 0)])]),
+            // a bundle that is discarded after its state was streamed
+            state([(C, Some(account(30)))], [(B, false, vec![(1, 0), (2, 7)])]),
+            // `B` is destroyed
+            state([(B, None)], [(B, true, vec![])]),
+            // `D` is created
+            state([(D, Some(account(4)))], [(D, true, vec![(1, 1), (2, 2)])]),
+            state([(A, Some(account(11)))], [(A, false, vec![(1, 1), (2, 2)])]),
+        ];
+        let final_state = state(
+            [(A, Some(account(11))), (B, None), (D, Some(account(4)))],
+            [(A, false, vec![(3, 3)]), (B, true, vec![]), (D, true, vec![(1, 1), (2, 2)])],
+        );
+
+        let parallel = DatabaseParallelStateRoot::new(factory.clone());
+        assert!(parallel
+            .parallel_state_root_with_updates(parent_hash, HashedPostState::default())
+            .is_ok());
+        assert!(parallel
+            .parallel_state_root_with_updates(B256::random(), HashedPostState::default())
+            .is_err());
+
+        // the parent is the tip of the database, so roots are computed in parallel
+        assert_incremental_root(&factory, parent_hash, updates.clone(), final_state.clone());
+        // the parent is not the tip of the database, so the state provider is used
+        assert_incremental_root(&factory, B256::random(), updates, final_state);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn incremental_root_reuses_streamed_state() {
+        let factory = create_test_provider_factory();
+        let parent_hash = setup(&factory);
+
+        let updates = vec![
+            state([(A, Some(account(10)))], [(A, false, vec![(1, 10), (2, 0)])]),
+            state([(B, None)], [(B, true, vec![])]),
+            state([(B, Some(account(20)))], [(B, true, vec![(5, 5)])]),
+        ];
+        let mut final_state = HashedPostState::default();
+        for update in updates.clone() {
+            final_state.extend(update);
+        }
+
+        assert_incremental_root(&factory, parent_hash, updates, final_state);
+    }
+}
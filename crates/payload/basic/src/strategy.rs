@@ -0,0 +1,881 @@
+//! Payload jobs that build payloads with several competing strategies.
+
+use crate::{
+    build_iteration, find_parent_block,
+    metrics::{PayloadBuilderMetrics, PayloadStrategyMetrics},
+    BasicPayloadJobGeneratorConfig, BuildArguments, BuildOutcome, Cancelled,
+    IncrementalStateRootSpawner, MissingPayloadBehaviour, ParallelStateRootProvider,
+    PayloadBuilder, PayloadConfig, PayloadTaskGuard, PendingPayload, PrecachedState,
+};
+use futures_util::FutureExt;
+use reth_chainspec::ChainSpec;
+use reth_payload_builder::{
+    database::CachedReads, error::PayloadBuilderError, InclusionListStore, KeepPayloadJobAlive,
+    PayloadBuildCancelReason, PayloadBuildIterationSender, PayloadJob, PayloadJobGenerator,
+    SimulationCache,
+};
+use reth_payload_primitives::{BuiltPayload, PayloadBuilderAttributes};
+use reth_primitives::B256;
+use reth_provider::{BlockReaderIdExt, CanonStateNotification, StateProviderFactory};
+use reth_tasks::TaskSpawner;
+use reth_transaction_pool::TransactionPool;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Instant,
+};
+use tokio::{
+    sync::oneshot,
+    time::{Interval, Sleep},
+};
+use tracing::{debug, trace, warn};
+
+/// A named [`PayloadBuilder`] that competes with the other strategies of a
+/// [`MultiStrategyPayloadJob`].
+///
+/// Strategies of different builder types can be combined with [`PayloadStrategy::boxed`].
+#[derive(Debug, Clone)]
+pub struct PayloadStrategy<Builder> {
+    /// The name of the strategy, used to label its metrics.
+    name: &'static str,
+    /// The builder of the strategy.
+    builder: Builder,
+}
+
+impl<Builder> PayloadStrategy<Builder> {
+    /// Creates a new strategy with the given name.
+    pub const fn new(name: &'static str, builder: Builder) -> Self {
+        Self { name, builder }
+    }
+
+    /// Returns the name of the strategy.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the builder of the strategy.
+    pub const fn builder(&self) -> &Builder {
+        &self.builder
+    }
+}
+
+impl<Pool, Client, Attributes, Payload>
+    PayloadStrategy<BoxedPayloadBuilder<Pool, Client, Attributes, Payload>>
+{
+    /// Creates a new strategy with the given name and type erased builder, so that it can be
+    /// combined with strategies of other builder types.
+    pub fn boxed<Builder>(name: &'static str, builder: Builder) -> Self
+    where
+        Builder:
+            PayloadBuilder<Pool, Client, Attributes = Attributes, BuiltPayload = Payload> + 'static,
+    {
+        Self::new(name, BoxedPayloadBuilder::new(builder))
+    }
+}
+
+/// A type erased [`PayloadBuilder`].
+pub struct BoxedPayloadBuilder<Pool, Client, Attributes, Payload>(
+    Arc<dyn DynPayloadBuilder<Pool, Client, Attributes, Payload>>,
+);
+
+impl<Pool, Client, Attributes, Payload> BoxedPayloadBuilder<Pool, Client, Attributes, Payload> {
+    /// Wraps the given builder.
+    pub fn new<Builder>(builder: Builder) -> Self
+    where
+        Builder:
+            PayloadBuilder<Pool, Client, Attributes = Attributes, BuiltPayload = Payload> + 'static,
+    {
+        Self(Arc::new(builder))
+    }
+}
+
+impl<Pool, Client, Attributes, Payload> Clone
+    for BoxedPayloadBuilder<Pool, Client, Attributes, Payload>
+{
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<Pool, Client, Attributes, Payload> fmt::Debug
+    for BoxedPayloadBuilder<Pool, Client, Attributes, Payload>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxedPayloadBuilder").finish_non_exhaustive()
+    }
+}
+
+impl<Pool, Client, Attributes, Payload> PayloadBuilder<Pool, Client>
+    for BoxedPayloadBuilder<Pool, Client, Attributes, Payload>
+where
+    Attributes: PayloadBuilderAttributes,
+    Payload: BuiltPayload,
+{
+    type Attributes = Attributes;
+    type BuiltPayload = Payload;
+
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Attributes, Payload>,
+    ) -> Result<BuildOutcome<Payload>, PayloadBuilderError> {
+        self.0.try_build(args)
+    }
+
+    fn on_missing_payload(
+        &self,
+        args: BuildArguments<Pool, Client, Attributes, Payload>,
+    ) -> MissingPayloadBehaviour<Payload> {
+        self.0.on_missing_payload(args)
+    }
+
+    fn build_empty_payload(
+        &self,
+        client: &Client,
+        config: PayloadConfig<Attributes>,
+    ) -> Result<Payload, PayloadBuilderError> {
+        self.0.build_empty_payload(client, config)
+    }
+}
+
+/// Object safe version of [`PayloadBuilder`], implemented by all builders.
+trait DynPayloadBuilder<Pool, Client, Attributes, Payload>: Send + Sync {
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Attributes, Payload>,
+    ) -> Result<BuildOutcome<Payload>, PayloadBuilderError>;
+
+    fn on_missing_payload(
+        &self,
+        args: BuildArguments<Pool, Client, Attributes, Payload>,
+    ) -> MissingPayloadBehaviour<Payload>;
+
+    fn build_empty_payload(
+        &self,
+        client: &Client,
+        config: PayloadConfig<Attributes>,
+    ) -> Result<Payload, PayloadBuilderError>;
+}
+
+impl<Pool, Client, Builder>
+    DynPayloadBuilder<Pool, Client, Builder::Attributes, Builder::BuiltPayload> for Builder
+where
+    Builder: PayloadBuilder<Pool, Client>,
+{
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Builder::Attributes, Builder::BuiltPayload>,
+    ) -> Result<BuildOutcome<Builder::BuiltPayload>, PayloadBuilderError> {
+        PayloadBuilder::try_build(self, args)
+    }
+
+    fn on_missing_payload(
+        &self,
+        args: BuildArguments<Pool, Client, Builder::Attributes, Builder::BuiltPayload>,
+    ) -> MissingPayloadBehaviour<Builder::BuiltPayload> {
+        PayloadBuilder::on_missing_payload(self, args)
+    }
+
+    fn build_empty_payload(
+        &self,
+        client: &Client,
+        config: PayloadConfig<Builder::Attributes>,
+    ) -> Result<Builder::BuiltPayload, PayloadBuilderError> {
+        PayloadBuilder::build_empty_payload(self, client, config)
+    }
+}
+
+/// The [`PayloadJobGenerator`] that creates [`MultiStrategyPayloadJob`]s.
+#[derive(Debug)]
+pub struct MultiStrategyPayloadJobGenerator<Client, Pool, Tasks, Builder> {
+    /// The client that can interact with the chain.
+    client: Client,
+    /// The transaction pool to pull transactions from.
+    pool: Pool,
+    /// The task executor to spawn payload building tasks on.
+    executor: Tasks,
+    /// The configuration for the job generator.
+    config: BasicPayloadJobGeneratorConfig,
+    /// Restricts how many generator tasks can be executed at once.
+    payload_task_guard: PayloadTaskGuard,
+    /// The chain spec.
+    chain_spec: Arc<ChainSpec>,
+    /// The competing strategies, the first one is the primary strategy.
+    strategies: Vec<PayloadStrategy<Builder>>,
+    /// Stored `cached_reads` for new payload jobs.
+    pre_cached: Option<PrecachedState>,
+    /// Where jobs report their build attempts to.
+    iteration_sender: PayloadBuildIterationSender,
+    /// The inclusion lists payloads must satisfy.
+    inclusion_lists: InclusionListStore,
+    /// The cache of transaction execution results shared with the service.
+    simulation_cache: SimulationCache,
+    /// Computes the state roots of payloads in the background, if configured.
+    parallel_state_root: Option<Arc<dyn ParallelStateRootProvider>>,
+}
+
+// === impl MultiStrategyPayloadJobGenerator ===
+
+impl<Client, Pool, Tasks, Builder> MultiStrategyPayloadJobGenerator<Client, Pool, Tasks, Builder> {
+    /// Creates a new [`MultiStrategyPayloadJobGenerator`] with the given config and competing
+    /// strategies.
+    ///
+    /// The first strategy is the primary strategy, which builds the empty payload if no strategy
+    /// built a payload yet when the job is resolved.
+    ///
+    /// Every strategy runs its own build task, so
+    /// [`max_payload_tasks`](BasicPayloadJobGeneratorConfig::max_payload_tasks) should be at least
+    /// the number of strategies.
+    ///
+    /// # Panics
+    ///
+    /// If no strategy is given.
+    pub fn new(
+        client: Client,
+        pool: Pool,
+        executor: Tasks,
+        config: BasicPayloadJobGeneratorConfig,
+        chain_spec: Arc<ChainSpec>,
+        strategies: Vec<PayloadStrategy<Builder>>,
+    ) -> Self {
+        assert!(!strategies.is_empty(), "at least one payload strategy is required");
+        Self {
+            client,
+            pool,
+            executor,
+            payload_task_guard: PayloadTaskGuard::new(config.max_payload_tasks),
+            config,
+            chain_spec,
+            strategies,
+            pre_cached: None,
+            iteration_sender: Default::default(),
+            inclusion_lists: Default::default(),
+            simulation_cache: Default::default(),
+            parallel_state_root: None,
+        }
+    }
+
+    /// Sets the provider that computes the state roots of payloads in parallel while their
+    /// transactions are executed.
+    pub fn with_parallel_state_root(
+        mut self,
+        parallel_state_root: Arc<dyn ParallelStateRootProvider>,
+    ) -> Self {
+        self.parallel_state_root = Some(parallel_state_root);
+        self
+    }
+
+    /// Returns a reference to the tasks type
+    pub const fn tasks(&self) -> &Tasks {
+        &self.executor
+    }
+
+    /// Returns the competing strategies.
+    pub fn strategies(&self) -> &[PayloadStrategy<Builder>] {
+        &self.strategies
+    }
+
+    /// Returns the pre-cached reads for the given parent block if it matches the cached state's
+    /// block.
+    fn maybe_pre_cached(&self, parent: B256) -> Option<CachedReads> {
+        self.pre_cached.as_ref().filter(|pc| pc.block == parent).map(|pc| pc.cached.clone())
+    }
+}
+
+impl<Client, Pool, Tasks, Builder> PayloadJobGenerator
+    for MultiStrategyPayloadJobGenerator<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + BlockReaderIdExt + Clone + Unpin + 'static,
+    Pool: TransactionPool + Unpin + 'static,
+    Tasks: TaskSpawner + Clone + Unpin + 'static,
+    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
+    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
+    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
+{
+    type Job = MultiStrategyPayloadJob<Client, Pool, Tasks, Builder>;
+
+    fn new_payload_job(
+        &self,
+        attributes: <Self::Job as PayloadJob>::PayloadAttributes,
+    ) -> Result<Self::Job, PayloadBuilderError> {
+        let parent_block = find_parent_block(&self.client, &attributes)?;
+
+        let config = PayloadConfig::new(
+            Arc::new(parent_block),
+            self.config.extradata.clone(),
+            attributes,
+            Arc::clone(&self.chain_spec),
+        )
+        .with_inclusion_lists(self.inclusion_lists.clone())
+        .with_simulation_cache(self.config.simulation_cache.then(|| self.simulation_cache.clone()))
+        .with_state_root(self.parallel_state_root.clone().map(|state_root| {
+            IncrementalStateRootSpawner::new(Box::new(self.executor.clone()), state_root)
+        }));
+
+        let until = self.config.job_deadline(config.attributes.timestamp());
+        let deadline = Box::pin(tokio::time::sleep_until(until));
+
+        // all strategies start from the same pre-cached state
+        let cached_reads = self.maybe_pre_cached(config.parent_block.hash());
+        let strategies = self
+            .strategies
+            .iter()
+            .map(|strategy| StrategyState {
+                strategy: strategy.clone(),
+                pending_block: None,
+                cached_reads: cached_reads.clone(),
+                metrics: PayloadStrategyMetrics::new_with_labels(&[("strategy", strategy.name)]),
+            })
+            .collect();
+
+        let mut job = MultiStrategyPayloadJob {
+            config,
+            client: self.client.clone(),
+            pool: self.pool.clone(),
+            executor: self.executor.clone(),
+            deadline,
+            // ticks immediately
+            interval: tokio::time::interval(self.config.interval),
+            strategies,
+            best_payload: None,
+            payload_task_guard: self.payload_task_guard.clone(),
+            metrics: Default::default(),
+            iteration_sender: self.iteration_sender.clone(),
+        };
+
+        // start the first jobs right away
+        job.spawn_build_jobs();
+
+        Ok(job)
+    }
+
+    fn on_new_state(&mut self, new_state: CanonStateNotification) {
+        self.pre_cached = Some(PrecachedState::from_notification(&new_state));
+    }
+
+    fn set_iteration_sender(&mut self, sender: PayloadBuildIterationSender) {
+        self.iteration_sender = sender;
+    }
+
+    fn set_inclusion_lists(&mut self, inclusion_lists: InclusionListStore) {
+        self.inclusion_lists = inclusion_lists;
+    }
+
+    fn set_simulation_cache(&mut self, simulation_cache: SimulationCache) {
+        self.simulation_cache = simulation_cache;
+    }
+}
+
+/// The state of a strategy within a [`MultiStrategyPayloadJob`].
+#[derive(Debug)]
+struct StrategyState<Builder, Payload> {
+    /// The strategy.
+    strategy: PayloadStrategy<Builder>,
+    /// Receiver for the block that is currently being built by the strategy.
+    pending_block: Option<PendingPayload<Payload>>,
+    /// Caches all disk reads of the strategy for the state the new payloads builds on.
+    cached_reads: Option<CachedReads>,
+    /// Metrics of the strategy.
+    metrics: PayloadStrategyMetrics,
+}
+
+/// A payload job that continuously builds payloads with several competing strategies.
+///
+/// On every interval, each strategy that isn't building a payload already starts a new attempt,
+/// so all strategies build concurrently. Every attempt competes with the best payload of all
+/// strategies, and the job resolves to the payload with the highest fees.
+#[derive(Debug)]
+pub struct MultiStrategyPayloadJob<Client, Pool, Tasks, Builder>
+where
+    Builder: PayloadBuilder<Pool, Client>,
+{
+    /// The configuration for how the payload will be created.
+    config: PayloadConfig<Builder::Attributes>,
+    /// The client that can interact with the chain.
+    client: Client,
+    /// The transaction pool.
+    pool: Pool,
+    /// How to spawn building tasks
+    executor: Tasks,
+    /// The deadline when this job should resolve.
+    deadline: Pin<Box<Sleep>>,
+    /// The interval at which the strategies should build a new payload after their last.
+    interval: Interval,
+    /// The competing strategies, the first one is the primary strategy.
+    strategies: Vec<StrategyState<Builder, Builder::BuiltPayload>>,
+    /// The best payload so far and the index of the strategy that built it.
+    best_payload: Option<(usize, Builder::BuiltPayload)>,
+    /// Restricts how many generator tasks can be executed at once.
+    payload_task_guard: PayloadTaskGuard,
+    /// metrics for this type
+    metrics: PayloadBuilderMetrics,
+    /// Where build attempts are reported to.
+    iteration_sender: PayloadBuildIterationSender,
+}
+
+impl<Client, Pool, Tasks, Builder> MultiStrategyPayloadJob<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + Clone + Unpin + 'static,
+    Pool: TransactionPool + Unpin + 'static,
+    Tasks: TaskSpawner + Clone + 'static,
+    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
+    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
+    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
+{
+    /// Spawns a new payload build task for every strategy that isn't building a payload.
+    fn spawn_build_jobs(&mut self) {
+        for index in 0..self.strategies.len() {
+            if self.strategies[index].pending_block.is_none() {
+                self.spawn_build_job(index);
+            }
+        }
+    }
+
+    /// Spawns a new payload build task for the strategy at the given index.
+    fn spawn_build_job(&mut self, index: usize) {
+        let best_payload = self.best_payload.as_ref().map(|(_, payload)| payload.clone());
+        let state = &mut self.strategies[index];
+        trace!(target: "payload_builder", strategy = state.strategy.name, "spawn new payload build task");
+        let (tx, rx) = oneshot::channel();
+        let client = self.client.clone();
+        let pool = self.pool.clone();
+        let cancel = Cancelled::default();
+        let _cancel = cancel.clone();
+        let guard = self.payload_task_guard.clone();
+        let payload_config = self.config.clone();
+        self.metrics.inc_initiated_payload_builds();
+        state.metrics.initiated_payload_builds.increment(1);
+        let cached_reads = state.cached_reads.take().unwrap_or_default();
+        let builder = state.strategy.builder.clone();
+        let iteration_sender = self.iteration_sender.clone();
+        self.executor.spawn_blocking(Box::pin(async move {
+            // acquire the permit for executing the task
+            let _permit = guard.acquire().await;
+            let started_at = Instant::now();
+            let id = payload_config.payload_id();
+            let args = BuildArguments {
+                client,
+                pool,
+                cached_reads,
+                config: payload_config,
+                cancel: cancel.clone(),
+                best_payload,
+            };
+            let result = builder.try_build(args);
+            iteration_sender.send(build_iteration(id, started_at, &cancel, &result));
+            let _ = tx.send(result);
+        }));
+
+        state.pending_block = Some(PendingPayload::new(_cancel, rx));
+    }
+
+    /// Handles the outcome of a build attempt of the strategy at the given index.
+    fn on_build_outcome(&mut self, index: usize, outcome: BuildOutcome<Builder::BuiltPayload>) {
+        let state = &mut self.strategies[index];
+        match outcome {
+            BuildOutcome::Better { payload, cached_reads } => {
+                state.cached_reads = Some(cached_reads);
+                // another strategy may have built a better payload in the meantime
+                let is_best = self
+                    .best_payload
+                    .as_ref()
+                    .map_or(true, |(_, best)| payload.fees() > best.fees());
+                if is_best {
+                    debug!(target: "payload_builder", strategy = state.strategy.name, value = %payload.fees(), "built better payload");
+                    state.metrics.best_payloads.increment(1);
+                    self.best_payload = Some((index, payload));
+                } else {
+                    trace!(target: "payload_builder", strategy = state.strategy.name, worse_fees = %payload.fees(), "payload is worse than the payload of another strategy");
+                }
+            }
+            BuildOutcome::Aborted { fees, cached_reads } => {
+                state.cached_reads = Some(cached_reads);
+                trace!(target: "payload_builder", strategy = state.strategy.name, worse_fees = %fees, "skipped payload build of worse block");
+            }
+            BuildOutcome::Cancelled => {
+                unreachable!("the cancel signal never fired")
+            }
+        }
+    }
+}
+
+impl<Client, Pool, Tasks, Builder> Future for MultiStrategyPayloadJob<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + Clone + Unpin + 'static,
+    Pool: TransactionPool + Unpin + 'static,
+    Tasks: TaskSpawner + Clone + 'static,
+    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
+    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
+    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
+{
+    type Output = Result<(), PayloadBuilderError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        // check if the deadline is reached
+        if this.deadline.as_mut().poll(cx).is_ready() {
+            trace!(target: "payload_builder", "payload building deadline reached");
+            return Poll::Ready(Ok(()))
+        }
+
+        // check if the interval is reached
+        while this.interval.poll_tick(cx).is_ready() {
+            // start new jobs for all strategies that have no pending block
+            this.spawn_build_jobs();
+        }
+
+        // poll the pending blocks of all strategies
+        for index in 0..this.strategies.len() {
+            let Some(mut fut) = this.strategies[index].pending_block.take() else { continue };
+            match fut.poll_unpin(cx) {
+                Poll::Ready(Ok(outcome)) => this.on_build_outcome(index, outcome),
+                Poll::Ready(Err(error)) => {
+                    // job failed, but we simply try again next interval
+                    let state = &this.strategies[index];
+                    debug!(target: "payload_builder", strategy = state.strategy.name, %error, "payload build attempt failed");
+                    state.metrics.failed_payload_builds.increment(1);
+                    this.metrics.inc_failed_payload_builds();
+                }
+                Poll::Pending => {
+                    this.strategies[index].pending_block = Some(fut);
+                }
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<Client, Pool, Tasks, Builder> PayloadJob
+    for MultiStrategyPayloadJob<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + Clone + Unpin + 'static,
+    Pool: TransactionPool + Unpin + 'static,
+    Tasks: TaskSpawner + Clone + 'static,
+    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
+    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
+    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
+{
+    type PayloadAttributes = Builder::Attributes;
+    type ResolvePayloadFuture = ResolveBestStrategyPayload<Self::BuiltPayload>;
+    type BuiltPayload = Builder::BuiltPayload;
+
+    fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        if let Some((_, ref payload)) = self.best_payload {
+            return Ok(payload.clone())
+        }
+        // No payload has been built yet, see also `BasicPayloadJob::best_payload`
+        self.metrics.inc_requested_empty_payload();
+        self.strategies[0].strategy.builder.build_empty_payload(&self.client, self.config.clone())
+    }
+
+    fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError> {
+        Ok(self.config.attributes.clone())
+    }
+
+    fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+        let best_payload = self.best_payload.take();
+
+        if best_payload.is_none() {
+            // ensure we have jobs scheduled if we don't have a best payload yet
+            self.spawn_build_jobs();
+        }
+
+        let maybe_better = self
+            .strategies
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, state)| state.pending_block.take().map(|fut| (index, fut)))
+            .collect();
+        let mut empty_payload = None;
+
+        if best_payload.is_none() {
+            debug!(target: "payload_builder", id=%self.config.payload_id(), "no best payload yet to resolve, building empty payload");
+
+            let primary = &mut self.strategies[0];
+            let args = BuildArguments {
+                client: self.client.clone(),
+                pool: self.pool.clone(),
+                cached_reads: primary.cached_reads.take().unwrap_or_default(),
+                config: self.config.clone(),
+                cancel: Cancelled::default(),
+                best_payload: None,
+            };
+
+            match primary.strategy.builder.on_missing_payload(args) {
+                MissingPayloadBehaviour::AwaitInProgress => {
+                    debug!(target: "payload_builder", id=%self.config.payload_id(), "awaiting in progress payload build jobs");
+                }
+                MissingPayloadBehaviour::RaceEmptyPayload => {
+                    debug!(target: "payload_builder", id=%self.config.payload_id(), "racing empty payload");
+
+                    self.metrics.inc_requested_empty_payload();
+                    let (tx, rx) = oneshot::channel();
+                    let client = self.client.clone();
+                    let config = self.config.clone();
+                    let builder = primary.strategy.builder.clone();
+                    self.executor.spawn_blocking(Box::pin(async move {
+                        let res = builder.build_empty_payload(&client, config);
+                        let _ = tx.send(res);
+                    }));
+
+                    empty_payload = Some(rx);
+                }
+                MissingPayloadBehaviour::RacePayload(job) => {
+                    debug!(target: "payload_builder", id=%self.config.payload_id(), "racing fallback payload");
+                    let (tx, rx) = oneshot::channel();
+                    self.executor.spawn_blocking(Box::pin(async move {
+                        let _ = tx.send(job());
+                    }));
+                    empty_payload = Some(rx);
+                }
+            };
+        }
+
+        let strategies = self
+            .strategies
+            .iter()
+            .map(|state| (state.strategy.name, state.metrics.clone()))
+            .collect();
+        let fut =
+            ResolveBestStrategyPayload { best_payload, maybe_better, empty_payload, strategies };
+
+        (fut, KeepPayloadJobAlive::No)
+    }
+}
+
+/// The future that returns the best payload of all strategies to be served to the consensus layer.
+///
+/// This behaves like [`ResolveBestPayload`](crate::ResolveBestPayload), but checks the in progress
+/// build jobs of all strategies for a better payload and records which strategy built the resolved
+/// payload.
+#[derive(Debug)]
+pub struct ResolveBestStrategyPayload<Payload> {
+    /// Best payload so far and the index of the strategy that built it.
+    best_payload: Option<(usize, Payload)>,
+    /// In progress build jobs of the strategies that might produce a better payload.
+    maybe_better: Vec<(usize, PendingPayload<Payload>)>,
+    /// The empty payload building job in progress, if any.
+    empty_payload: Option<oneshot::Receiver<Result<Payload, PayloadBuilderError>>>,
+    /// The names and metrics of the strategies.
+    strategies: Vec<(&'static str, PayloadStrategyMetrics)>,
+}
+
+impl<Payload> Future for ResolveBestStrategyPayload<Payload>
+where
+    Payload: BuiltPayload + Unpin,
+{
+    type Output = Result<Payload, PayloadBuilderError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        // check if there is a better payload before returning the best payload
+        this.maybe_better.retain_mut(|(index, fut)| {
+            let Poll::Ready(res) = fut.poll_unpin(cx) else { return true };
+            if let Ok(BuildOutcome::Better { payload, .. }) = res {
+                let is_best = this
+                    .best_payload
+                    .as_ref()
+                    .map_or(true, |(_, best)| payload.fees() > best.fees());
+                if is_best {
+                    this.best_payload = Some((*index, payload));
+                }
+            }
+            false
+        });
+
+        if let Some((index, best)) = this.best_payload.take() {
+            for (_, pending) in this.maybe_better.drain(..) {
+                pending.cancel(PayloadBuildCancelReason::Resolved);
+            }
+            let (strategy, metrics) = &this.strategies[index];
+            debug!(target: "payload_builder", strategy = *strategy, value = %best.fees(), "resolving best payload");
+            metrics.resolved_payloads.increment(1);
+            return Poll::Ready(Ok(best))
+        }
+
+        if let Some(fut) = Pin::new(&mut this.empty_payload).as_pin_mut() {
+            if let Poll::Ready(res) = fut.poll(cx) {
+                this.empty_payload = None;
+                return match res {
+                    Ok(res) => {
+                        if let Err(err) = &res {
+                            warn!(target: "payload_builder", %err, "failed to resolve empty payload");
+                        } else {
+                            debug!(target: "payload_builder", "resolving empty payload");
+                        }
+                        Poll::Ready(res)
+                    }
+                    Err(err) => Poll::Ready(Err(err.into())),
+                }
+            }
+        }
+
+        if this.maybe_better.is_empty() && this.empty_payload.is_none() {
+            return Poll::Ready(Err(PayloadBuilderError::MissingPayload))
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::is_better_payload;
+    use reth_chainspec::MAINNET;
+    use reth_payload_builder::{EthBuiltPayload, EthPayloadBuilderAttributes, PayloadId};
+    use reth_primitives::{Block, U256};
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_tasks::TokioTaskExecutor;
+    use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+    use std::time::Duration;
+
+    /// Builds payloads with fixed fees.
+    #[derive(Debug, Clone)]
+    struct FixedFeesBuilder(U256);
+
+    impl<Pool, Client> PayloadBuilder<Pool, Client> for FixedFeesBuilder {
+        type Attributes = EthPayloadBuilderAttributes;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn try_build(
+            &self,
+            args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+        ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+            let BuildArguments { cached_reads, config, best_payload, .. } = args;
+            if !is_better_payload(best_payload, self.0) {
+                return Ok(BuildOutcome::Aborted { fees: self.0, cached_reads })
+            }
+            Ok(BuildOutcome::Better { payload: payload(&config, self.0), cached_reads })
+        }
+
+        fn build_empty_payload(
+            &self,
+            _client: &Client,
+            config: PayloadConfig<Self::Attributes>,
+        ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+            Ok(payload(&config, U256::ZERO))
+        }
+    }
+
+    /// Fails to build any payload.
+    #[derive(Debug, Clone)]
+    struct FailingBuilder;
+
+    impl<Pool, Client> PayloadBuilder<Pool, Client> for FailingBuilder {
+        type Attributes = EthPayloadBuilderAttributes;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn try_build(
+            &self,
+            _args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+        ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+            Err(PayloadBuilderError::MissingPayload)
+        }
+
+        fn build_empty_payload(
+            &self,
+            _client: &Client,
+            _config: PayloadConfig<Self::Attributes>,
+        ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+            Err(PayloadBuilderError::MissingPayload)
+        }
+    }
+
+    type TestStrategy = PayloadStrategy<
+        BoxedPayloadBuilder<
+            TestPool,
+            MockEthProvider,
+            EthPayloadBuilderAttributes,
+            EthBuiltPayload,
+        >,
+    >;
+
+    fn payload(config: &PayloadConfig<EthPayloadBuilderAttributes>, fees: U256) -> EthBuiltPayload {
+        EthBuiltPayload::new(config.payload_id(), Block::default().seal_slow(), fees)
+    }
+
+    /// Creates a job that builds on a new block with the given strategies.
+    fn new_job(
+        strategies: Vec<TestStrategy>,
+    ) -> MultiStrategyPayloadJob<
+        MockEthProvider,
+        TestPool,
+        TokioTaskExecutor,
+        BoxedPayloadBuilder<
+            TestPool,
+            MockEthProvider,
+            EthPayloadBuilderAttributes,
+            EthBuiltPayload,
+        >,
+    > {
+        let client = MockEthProvider::default();
+        let parent = B256::with_last_byte(1);
+        client.add_block(parent, Block::default());
+        let config = BasicPayloadJobGeneratorConfig::default()
+            .interval(Duration::from_millis(10))
+            .max_payload_tasks(strategies.len());
+        let generator = MultiStrategyPayloadJobGenerator::new(
+            client,
+            testing_pool(),
+            TokioTaskExecutor::default(),
+            config,
+            MAINNET.clone(),
+            strategies,
+        );
+
+        let attributes = EthPayloadBuilderAttributes {
+            id: PayloadId::new([1; 8]),
+            parent,
+            timestamp: 0,
+            suggested_fee_recipient: Default::default(),
+            prev_randao: B256::ZERO,
+            withdrawals: Default::default(),
+            parent_beacon_block_root: None,
+        };
+        generator.new_payload_job(attributes).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolves_payload_of_best_strategy() {
+        // strategies of different builder types
+        let mut job = new_job(vec![
+            PayloadStrategy::boxed("low", FixedFeesBuilder(U256::from(1))),
+            PayloadStrategy::boxed("failing", FailingBuilder),
+            PayloadStrategy::boxed("high", FixedFeesBuilder(U256::from(2))),
+        ]);
+
+        // drive the job until all strategies finished building
+        let _ = tokio::time::timeout(Duration::from_millis(100), &mut job).await;
+        assert_eq!(job.best_payload().unwrap().fees(), U256::from(2));
+
+        let (resolved, keep_alive) = job.resolve();
+        assert_eq!(keep_alive, KeepPayloadJobAlive::No);
+        assert_eq!(resolved.await.unwrap().fees(), U256::from(2));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn primary_strategy_builds_empty_payload() {
+        let job = new_job(vec![
+            PayloadStrategy::boxed("primary", FixedFeesBuilder(U256::from(1))),
+            PayloadStrategy::boxed("failing", FailingBuilder),
+        ]);
+
+        // no strategy finished building before the job is polled
+        assert_eq!(job.best_payload().unwrap().fees(), U256::ZERO);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolves_error_if_no_strategy_builds() {
+        let mut job = new_job(vec![PayloadStrategy::boxed("failing", FailingBuilder)]);
+
+        let _ = tokio::time::timeout(Duration::from_millis(50), &mut job).await;
+        assert!(job.best_payload().is_err());
+        assert!(job.resolve().0.await.is_err());
+    }
+}
@@ -1,9 +1,12 @@
 use reth_payload_primitives::PayloadTypes;
+use reth_primitives::{B256, U256};
+use reth_rpc_types::engine::PayloadId;
 use std::{
     pin::Pin,
     task::{ready, Context, Poll},
+    time::Duration,
 };
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
 use tokio_stream::{
     wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
     Stream, StreamExt,
@@ -110,3 +113,105 @@ impl<T: PayloadTypes + 'static> Stream for PayloadAttributeStream<T> {
         }
     }
 }
+
+/// Events emitted over the lifecycle of payload jobs.
+///
+/// Unlike [`Events`], these are emitted for every build attempt of a job, which makes them useful
+/// to diagnose late or unexpectedly poor payloads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PayloadJobEvent {
+    /// A new payload job was created.
+    Started {
+        /// The identifier of the payload.
+        id: PayloadId,
+        /// The hash of the parent block the payload is built on.
+        parent: B256,
+        /// The timestamp of the payload.
+        timestamp: u64,
+    },
+    /// A build attempt of a payload job finished.
+    BuildIteration(PayloadBuildIteration),
+    /// A payload job was terminated and won't build any more payloads.
+    Terminated {
+        /// The identifier of the payload.
+        id: PayloadId,
+        /// Why the job was terminated.
+        reason: PayloadJobTerminationReason,
+    },
+}
+
+/// A single build attempt of a payload job.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayloadBuildIteration {
+    /// The identifier of the payload the attempt was made for.
+    pub id: PayloadId,
+    /// How long the attempt took, excluding the time spent waiting for a build permit.
+    pub elapsed: Duration,
+    /// The outcome of the attempt.
+    pub outcome: PayloadBuildOutcome,
+}
+
+/// The outcome of a [`PayloadBuildIteration`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PayloadBuildOutcome {
+    /// Built a payload that is better than the best payload of the job so far.
+    Better {
+        /// Number of transactions in the payload.
+        transactions: usize,
+        /// Gas used by the payload.
+        gas_used: u64,
+        /// Total fees of the payload.
+        fees: U256,
+    },
+    /// Built a payload that is not better than the best payload so far, so it was discarded.
+    Aborted {
+        /// Total fees of the discarded payload.
+        fees: U256,
+    },
+    /// The attempt was cancelled for the given reason.
+    Cancelled(PayloadBuildCancelReason),
+    /// The attempt failed with the given error.
+    Failed(String),
+}
+
+/// Why a [`PayloadBuildIteration`] was cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadBuildCancelReason {
+    /// The job was resolved while the attempt was in progress, without waiting for the attempt.
+    Resolved,
+    /// The job was terminated while the attempt was in progress, e.g. because its deadline was
+    /// reached.
+    Terminated,
+}
+
+/// Why a payload job was terminated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PayloadJobTerminationReason {
+    /// The payload was requested by the CL and the job was not kept alive.
+    Resolved,
+    /// The job completed on its own, e.g. because its deadline was reached.
+    Finished,
+    /// The job failed with the given error.
+    Failed(String),
+}
+
+/// The channel [`PayloadJob`](crate::PayloadJob)s report their [`PayloadBuildIteration`]s to the
+/// [`PayloadBuilderService`](crate::PayloadBuilderService) with.
+///
+/// The default sender discards all iterations.
+#[derive(Clone, Debug, Default)]
+pub struct PayloadBuildIterationSender(Option<mpsc::UnboundedSender<PayloadBuildIteration>>);
+
+impl PayloadBuildIterationSender {
+    /// Creates a new sender for the given channel.
+    pub const fn new(tx: mpsc::UnboundedSender<PayloadBuildIteration>) -> Self {
+        Self(Some(tx))
+    }
+
+    /// Reports a finished build attempt.
+    pub fn send(&self, iteration: PayloadBuildIteration) {
+        if let Some(tx) = &self.0 {
+            let _ = tx.send(iteration);
+        }
+    }
+}
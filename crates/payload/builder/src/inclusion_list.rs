@@ -0,0 +1,104 @@
+//! Inclusion lists (FOCIL) that payloads must satisfy.
+
+use parking_lot::RwLock;
+use reth_primitives::{TransactionSignedEcRecovered, B256};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+/// The number of parent blocks inclusion lists are kept for.
+const MAX_INCLUSION_LISTS: usize = 64;
+
+/// Shared store of the inclusion lists received from the consensus layer.
+///
+/// Inclusion lists are keyed by the hash of the parent block they apply to: payloads built on top
+/// of that block must include their transactions, and blocks building on it that omit any of them
+/// that could have been included are reported as not satisfying the inclusion list.
+///
+/// Only the lists of the most recent [`MAX_INCLUSION_LISTS`] parent blocks are kept.
+#[derive(Debug, Clone, Default)]
+pub struct InclusionListStore {
+    inner: Arc<RwLock<InclusionListsInner>>,
+}
+
+impl InclusionListStore {
+    /// Adds the transactions of an inclusion list for blocks building on the given parent.
+    ///
+    /// Multiple lists for the same parent are merged, transactions that are already listed are
+    /// ignored.
+    pub fn insert(&self, parent_hash: B256, transactions: Vec<TransactionSignedEcRecovered>) {
+        let mut inner = self.inner.write();
+        if !inner.lists.contains_key(&parent_hash) {
+            if inner.order.len() == MAX_INCLUSION_LISTS {
+                if let Some(oldest) = inner.order.pop_front() {
+                    inner.lists.remove(&oldest);
+                }
+            }
+            inner.order.push_back(parent_hash);
+        }
+
+        let list = Arc::make_mut(inner.lists.entry(parent_hash).or_default());
+        for tx in transactions {
+            if !list.iter().any(|listed| listed.hash == tx.hash) {
+                list.push(tx);
+            }
+        }
+    }
+
+    /// Returns the inclusion list for blocks building on the given parent, if any.
+    pub fn get(&self, parent_hash: &B256) -> Option<Arc<Vec<TransactionSignedEcRecovered>>> {
+        self.inner.read().lists.get(parent_hash).cloned()
+    }
+
+    /// Returns the number of parent blocks with an inclusion list.
+    pub fn len(&self) -> usize {
+        self.inner.read().lists.len()
+    }
+
+    /// Returns `true` if there are no inclusion lists.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Default)]
+struct InclusionListsInner {
+    /// Inclusion lists by parent hash.
+    lists: HashMap<B256, Arc<Vec<TransactionSignedEcRecovered>>>,
+    /// Parent hashes in insertion order, used for eviction.
+    order: VecDeque<B256>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Address, Signature, Transaction, TransactionSigned, TxEip1559};
+
+    fn tx(nonce: u64) -> TransactionSignedEcRecovered {
+        TransactionSignedEcRecovered::from_signed_transaction(
+            TransactionSigned::from_transaction_and_signature(
+                Transaction::Eip1559(TxEip1559 { nonce, ..Default::default() }),
+                Signature::default(),
+            ),
+            Address::ZERO,
+        )
+    }
+
+    #[test]
+    fn merge_and_evict_inclusion_lists() {
+        let store = InclusionListStore::default();
+        let parent = B256::with_last_byte(1);
+
+        store.insert(parent, vec![tx(0), tx(1)]);
+        store.insert(parent, vec![tx(1), tx(2)]);
+        let list = store.get(&parent).unwrap();
+        assert_eq!(list.iter().map(|tx| tx.nonce()).collect::<Vec<_>>(), vec![0, 1, 2]);
+
+        for i in 0..MAX_INCLUSION_LISTS as u64 {
+            store.insert(B256::left_padding_from(&(i + 2).to_be_bytes()), vec![tx(i)]);
+        }
+        assert_eq!(store.len(), MAX_INCLUSION_LISTS);
+        assert!(store.get(&parent).is_none());
+    }
+}
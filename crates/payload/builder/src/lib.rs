@@ -104,8 +104,10 @@
 pub mod database;
 pub mod error;
 mod events;
+mod inclusion_list;
 mod metrics;
 mod service;
+mod simulation;
 mod traits;
 
 pub mod noop;
@@ -113,11 +115,18 @@ pub mod noop;
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
 
-pub use events::{Events, PayloadEvents};
+pub use events::{
+    Events, PayloadBuildCancelReason, PayloadBuildIteration, PayloadBuildIterationSender,
+    PayloadBuildOutcome, PayloadEvents,
+    PayloadJobEvent, PayloadJobTerminationReason,
+};
+pub use inclusion_list::InclusionListStore;
 pub use reth_rpc_types::engine::PayloadId;
 pub use service::{
-    PayloadBuilderHandle, PayloadBuilderService, PayloadServiceCommand, PayloadStore,
+    PayloadBuilderHandle, PayloadBuilderService, PayloadJobInfo, PayloadServiceCommand,
+    PayloadStore,
 };
+pub use simulation::SimulationCache;
 pub use traits::{KeepPayloadJobAlive, PayloadJob, PayloadJobGenerator};
 
 // re-export the Ethereum engine primitives for convenience
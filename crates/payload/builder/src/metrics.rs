@@ -1,9 +1,11 @@
 //! Payload builder service metrics.
 
+use crate::events::{PayloadBuildIteration, PayloadBuildOutcome};
 use reth_metrics::{
-    metrics::{Counter, Gauge},
+    metrics::{Counter, Gauge, Histogram},
     Metrics,
 };
+use std::time::Duration;
 
 /// Payload builder service metrics
 #[derive(Metrics, Clone)]
@@ -23,6 +25,20 @@ pub(crate) struct PayloadBuilderServiceMetrics {
     pub(crate) resolved_revenue: Gauge,
     /// Current block returned as the resolved payload
     pub(crate) resolved_block: Gauge,
+    /// Duration of payload build attempts in seconds
+    pub(crate) build_duration_seconds: Histogram,
+    /// Number of transactions in payloads that were better than the best payload of their job
+    pub(crate) build_transactions: Histogram,
+    /// Gas used by payloads that were better than the best payload of their job
+    pub(crate) build_gas_used: Histogram,
+    /// Total number of payload build attempts that were cancelled
+    pub(crate) cancelled_builds: Counter,
+    /// Total number of payload build attempts that failed
+    pub(crate) failed_builds: Counter,
+    /// Number of build attempts per terminated job
+    pub(crate) job_build_iterations: Histogram,
+    /// Lifetime of terminated jobs in seconds
+    pub(crate) job_duration_seconds: Histogram,
 }
 
 impl PayloadBuilderServiceMetrics {
@@ -43,8 +59,38 @@ impl PayloadBuilderServiceMetrics {
         self.best_revenue.set(value)
     }
 
+    pub(crate) fn record_build_iteration(&self, iteration: &PayloadBuildIteration) {
+        self.build_duration_seconds.record(iteration.elapsed.as_secs_f64());
+        match iteration.outcome {
+            PayloadBuildOutcome::Better { transactions, gas_used, .. } => {
+                self.build_transactions.record(transactions as f64);
+                self.build_gas_used.record(gas_used as f64);
+            }
+            PayloadBuildOutcome::Aborted { .. } => {}
+            PayloadBuildOutcome::Cancelled(_) => self.cancelled_builds.increment(1),
+            PayloadBuildOutcome::Failed(_) => self.failed_builds.increment(1),
+        }
+    }
+
+    pub(crate) fn record_terminated_job(&self, iterations: u64, duration: Duration) {
+        self.job_build_iterations.record(iterations as f64);
+        self.job_duration_seconds.record(duration.as_secs_f64());
+    }
+
     pub(crate) fn set_resolved_revenue(&self, block: u64, value: f64) {
         self.resolved_block.set(block as f64);
         self.resolved_revenue.set(value)
     }
 }
+
+/// Metrics of the [`SimulationCache`](crate::SimulationCache).
+#[derive(Metrics, Clone)]
+#[metrics(scope = "payloads.simulation_cache")]
+pub(crate) struct SimulationCacheMetrics {
+    /// Total number of executions that reused a cached result
+    pub(crate) hits: Counter,
+    /// Total number of executions without a reusable cached result
+    pub(crate) misses: Counter,
+    /// Total number of transactions whose cached results were invalidated by new blocks
+    pub(crate) invalidated: Counter,
+}
@@ -52,7 +52,9 @@ where
                 PayloadServiceCommand::BestPayload(_, tx) => tx.send(None).ok(),
                 PayloadServiceCommand::PayloadAttributes(_, tx) => tx.send(None).ok(),
                 PayloadServiceCommand::Resolve(_, tx) => tx.send(None).ok(),
-                PayloadServiceCommand::Subscribe(_) => None,
+                PayloadServiceCommand::Subscribe(_) |
+                PayloadServiceCommand::SubscribeJobEvents(_) => None,
+                PayloadServiceCommand::PayloadJobs(tx) => tx.send(Vec::new()).ok(),
             };
         }
     }
@@ -5,20 +5,28 @@
 
 use crate::{
     error::PayloadBuilderError,
-    events::{Events, PayloadEvents},
+    events::{
+        Events, PayloadBuildIteration, PayloadBuildIterationSender, PayloadEvents, PayloadJobEvent,
+        PayloadJobTerminationReason,
+    },
+    inclusion_list::InclusionListStore,
     metrics::PayloadBuilderServiceMetrics,
+    simulation::SimulationCache,
     traits::PayloadJobGenerator,
     KeepPayloadJobAlive, PayloadJob,
 };
 use futures_util::{future::FutureExt, Stream, StreamExt};
 use reth_payload_primitives::{BuiltPayload, PayloadBuilderAttributes, PayloadTypes};
+use reth_primitives::{B256, U256};
 use reth_provider::CanonStateNotification;
 use reth_rpc_types::engine::PayloadId;
 use std::{
+    collections::HashMap,
     fmt,
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
 use tokio::sync::{
     broadcast, mpsc,
@@ -71,6 +79,21 @@ where
     ) -> Option<Result<Engine::PayloadBuilderAttributes, PayloadBuilderError>> {
         self.inner.payload_attributes(id).await
     }
+
+    /// Returns information about all active payload jobs.
+    pub async fn payload_jobs(&self) -> Option<Vec<PayloadJobInfo>> {
+        self.inner.payload_jobs().await
+    }
+
+    /// Returns the inclusion lists payloads must satisfy.
+    pub const fn inclusion_lists(&self) -> &InclusionListStore {
+        self.inner.inclusion_lists()
+    }
+
+    /// Returns the cache of transaction execution results shared with the payload jobs.
+    pub const fn simulation_cache(&self) -> &SimulationCache {
+        self.inner.simulation_cache()
+    }
 }
 
 impl<Engine> Clone for PayloadStore<Engine>
@@ -98,6 +121,10 @@ where
 pub struct PayloadBuilderHandle<Engine: PayloadTypes> {
     /// Sender half of the message channel to the [`PayloadBuilderService`].
     to_service: mpsc::UnboundedSender<PayloadServiceCommand<Engine>>,
+    /// The inclusion lists shared with the [`PayloadBuilderService`].
+    inclusion_lists: InclusionListStore,
+    /// The simulation cache shared with the [`PayloadBuilderService`].
+    simulation_cache: SimulationCache,
 }
 
 // === impl PayloadBuilderHandle ===
@@ -110,8 +137,39 @@ where
     ///
     /// Note: this is only used internally by the [`PayloadBuilderService`] to manage the payload
     /// building flow See [`PayloadBuilderService::poll`] for implementation details.
-    pub const fn new(to_service: mpsc::UnboundedSender<PayloadServiceCommand<Engine>>) -> Self {
-        Self { to_service }
+    pub fn new(to_service: mpsc::UnboundedSender<PayloadServiceCommand<Engine>>) -> Self {
+        Self::with_inclusion_lists(to_service, InclusionListStore::default())
+    }
+
+    /// Creates a new payload builder handle for the given channel that shares the given inclusion
+    /// lists with the service.
+    pub fn with_inclusion_lists(
+        to_service: mpsc::UnboundedSender<PayloadServiceCommand<Engine>>,
+        inclusion_lists: InclusionListStore,
+    ) -> Self {
+        Self { to_service, inclusion_lists, simulation_cache: SimulationCache::default() }
+    }
+
+    /// Sets the simulation cache shared with the service.
+    pub fn with_simulation_cache(mut self, simulation_cache: SimulationCache) -> Self {
+        self.simulation_cache = simulation_cache;
+        self
+    }
+
+    /// Returns the inclusion lists payloads must satisfy.
+    ///
+    /// Inclusion lists added here are picked up by the payload jobs building on top of the
+    /// parent block they apply to.
+    pub const fn inclusion_lists(&self) -> &InclusionListStore {
+        &self.inclusion_lists
+    }
+
+    /// Returns the cache of transaction execution results shared with the payload jobs.
+    ///
+    /// The cache is kept up to date with the canonical chain by the service, and can be consulted
+    /// outside of payload jobs, e.g. to simulate bundles.
+    pub const fn simulation_cache(&self) -> &SimulationCache {
+        &self.simulation_cache
     }
 
     /// Resolves the payload job and returns the best payload that has been built so far.
@@ -186,6 +244,23 @@ where
         let _ = self.to_service.send(PayloadServiceCommand::Subscribe(tx));
         Ok(PayloadEvents { receiver: rx.await? })
     }
+
+    /// Sends a message to the service to subscribe to the events of all payload jobs.
+    /// Returns a receiver that will receive them.
+    pub async fn subscribe_job_events(
+        &self,
+    ) -> Result<broadcast::Receiver<PayloadJobEvent>, RecvError> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.to_service.send(PayloadServiceCommand::SubscribeJobEvents(tx));
+        rx.await
+    }
+
+    /// Returns information about all active payload jobs.
+    pub async fn payload_jobs(&self) -> Option<Vec<PayloadJobInfo>> {
+        let (tx, rx) = oneshot::channel();
+        self.to_service.send(PayloadServiceCommand::PayloadJobs(tx)).ok()?;
+        rx.await.ok()
+    }
 }
 
 impl<Engine> Clone for PayloadBuilderHandle<Engine>
@@ -193,7 +268,11 @@ where
     Engine: PayloadTypes,
 {
     fn clone(&self) -> Self {
-        Self { to_service: self.to_service.clone() }
+        Self {
+            to_service: self.to_service.clone(),
+            inclusion_lists: self.inclusion_lists.clone(),
+            simulation_cache: self.simulation_cache.clone(),
+        }
     }
 }
 
@@ -227,10 +306,22 @@ where
     chain_events: St,
     /// Payload events handler, used to broadcast and subscribe to payload events.
     payload_events: broadcast::Sender<Events<Engine>>,
+    /// Receiver half of the channel jobs report their build attempts to.
+    iterations_rx: UnboundedReceiverStream<PayloadBuildIteration>,
+    /// Bookkeeping of the active jobs.
+    job_stats: HashMap<PayloadId, PayloadJobStats>,
+    /// Payload job events handler, used to broadcast and subscribe to payload job events.
+    job_events: broadcast::Sender<PayloadJobEvent>,
+    /// The inclusion lists shared with the handles and the generator.
+    inclusion_lists: InclusionListStore,
+    /// The simulation cache shared with the handles and the generator.
+    simulation_cache: SimulationCache,
 }
 
 const PAYLOAD_EVENTS_BUFFER_SIZE: usize = 20;
 
+const PAYLOAD_JOB_EVENTS_BUFFER_SIZE: usize = 100;
+
 // === impl PayloadBuilderService ===
 
 impl<Gen, St, Engine> PayloadBuilderService<Gen, St, Engine>
@@ -246,9 +337,17 @@ where
     /// This also takes a stream of chain events that will be forwarded to the generator to apply
     /// additional logic when new state is committed. See also
     /// [`PayloadJobGenerator::on_new_state`].
-    pub fn new(generator: Gen, chain_events: St) -> (Self, PayloadBuilderHandle<Engine>) {
+    pub fn new(mut generator: Gen, chain_events: St) -> (Self, PayloadBuilderHandle<Engine>) {
         let (service_tx, command_rx) = mpsc::unbounded_channel();
         let (payload_events, _) = broadcast::channel(PAYLOAD_EVENTS_BUFFER_SIZE);
+        let (job_events, _) = broadcast::channel(PAYLOAD_JOB_EVENTS_BUFFER_SIZE);
+
+        let (iterations_tx, iterations_rx) = mpsc::unbounded_channel();
+        generator.set_iteration_sender(PayloadBuildIterationSender::new(iterations_tx));
+        let inclusion_lists = InclusionListStore::default();
+        generator.set_inclusion_lists(inclusion_lists.clone());
+        let simulation_cache = SimulationCache::default();
+        generator.set_simulation_cache(simulation_cache.clone());
 
         let service = Self {
             generator,
@@ -258,6 +357,11 @@ where
             metrics: Default::default(),
             chain_events,
             payload_events,
+            iterations_rx: UnboundedReceiverStream::new(iterations_rx),
+            job_stats: HashMap::new(),
+            job_events,
+            inclusion_lists,
+            simulation_cache,
         };
 
         let handle = service.handle();
@@ -266,7 +370,11 @@ where
 
     /// Returns a handle to the service.
     pub fn handle(&self) -> PayloadBuilderHandle<Engine> {
-        PayloadBuilderHandle::new(self.service_tx.clone())
+        PayloadBuilderHandle::with_inclusion_lists(
+            self.service_tx.clone(),
+            self.inclusion_lists.clone(),
+        )
+        .with_simulation_cache(self.simulation_cache.clone())
     }
 
     /// Returns true if the given payload is currently being built.
@@ -302,6 +410,7 @@ where
         if keep_alive == KeepPayloadJobAlive::No {
             let (_, id) = self.payload_jobs.remove(job);
             trace!(%id, "terminated resolved job");
+            self.on_job_terminated(id, PayloadJobTerminationReason::Resolved);
         }
 
         // Since the fees will not be known until the payload future is resolved / awaited, we wrap
@@ -348,6 +457,62 @@ where
 
         attributes
     }
+
+    /// Returns information about all active payload jobs.
+    fn payload_jobs(&self) -> Vec<PayloadJobInfo> {
+        self.payload_jobs
+            .iter()
+            .filter_map(|(job, id)| {
+                let stats = self.job_stats.get(id)?;
+                let best_payload = job.best_payload().ok();
+                Some(PayloadJobInfo {
+                    id: *id,
+                    parent: stats.parent,
+                    timestamp: stats.timestamp,
+                    age: stats.started_at.elapsed(),
+                    iterations: stats.iterations,
+                    last_iteration: stats.last_iteration.clone(),
+                    best_block_hash: best_payload.as_ref().map(|payload| payload.block().hash()),
+                    best_fees: best_payload.as_ref().map(|payload| payload.fees()),
+                })
+            })
+            .collect()
+    }
+
+    /// Records a new payload job.
+    fn on_job_started(&mut self, id: PayloadId, parent: B256, timestamp: u64) {
+        self.job_stats.insert(
+            id,
+            PayloadJobStats {
+                parent,
+                timestamp,
+                started_at: Instant::now(),
+                iterations: 0,
+                last_iteration: None,
+            },
+        );
+        self.job_events.send(PayloadJobEvent::Started { id, parent, timestamp }).ok();
+    }
+
+    /// Records a finished build attempt of a job.
+    fn on_build_iteration(&mut self, iteration: PayloadBuildIteration) {
+        trace!(id=%iteration.id, elapsed=?iteration.elapsed, outcome=?iteration.outcome, "payload build attempt finished");
+        self.metrics.record_build_iteration(&iteration);
+        // attempts that were cancelled can arrive after their job was terminated
+        if let Some(stats) = self.job_stats.get_mut(&iteration.id) {
+            stats.iterations += 1;
+            stats.last_iteration = Some(iteration.clone());
+        }
+        self.job_events.send(PayloadJobEvent::BuildIteration(iteration)).ok();
+    }
+
+    /// Records a terminated payload job.
+    fn on_job_terminated(&mut self, id: PayloadId, reason: PayloadJobTerminationReason) {
+        if let Some(stats) = self.job_stats.remove(&id) {
+            self.metrics.record_terminated_job(stats.iterations, stats.started_at.elapsed());
+        }
+        self.job_events.send(PayloadJobEvent::Terminated { id, reason }).ok();
+    }
 }
 
 impl<Gen, St, Engine> Future for PayloadBuilderService<Gen, St, Engine>
@@ -366,9 +531,15 @@ where
         loop {
             // notify the generator of new chain events
             while let Poll::Ready(Some(new_head)) = this.chain_events.poll_next_unpin(cx) {
+                this.simulation_cache.on_new_state(&new_head);
                 this.generator.on_new_state(new_head);
             }
 
+            // record all finished build attempts of the jobs
+            while let Poll::Ready(Some(iteration)) = this.iterations_rx.poll_next_unpin(cx) {
+                this.on_build_iteration(iteration);
+            }
+
             // we poll all jobs first, so we always have the latest payload that we can report if
             // requests
             // we don't care about the order of the jobs, so we can just swap_remove them
@@ -380,11 +551,16 @@ where
                     Poll::Ready(Ok(_)) => {
                         this.metrics.set_active_jobs(this.payload_jobs.len());
                         trace!(%id, "payload job finished");
+                        this.on_job_terminated(id, PayloadJobTerminationReason::Finished);
                     }
                     Poll::Ready(Err(err)) => {
                         warn!(%err, ?id, "Payload builder job failed; resolving payload");
                         this.metrics.inc_failed_jobs();
                         this.metrics.set_active_jobs(this.payload_jobs.len());
+                        this.on_job_terminated(
+                            id,
+                            PayloadJobTerminationReason::Failed(err.to_string()),
+                        );
                     }
                     Poll::Pending => {
                         // still pending, put it back
@@ -414,6 +590,7 @@ where
                                     this.metrics.inc_initiated_jobs();
                                     new_job = true;
                                     this.payload_jobs.push((job, id));
+                                    this.on_job_started(id, parent, attr.timestamp());
                                     this.payload_events.send(Events::Attributes(attr.clone())).ok();
                                 }
                                 Err(err) => {
@@ -441,6 +618,13 @@ where
                         let new_rx = this.payload_events.subscribe();
                         let _ = tx.send(new_rx);
                     }
+                    PayloadServiceCommand::SubscribeJobEvents(tx) => {
+                        let new_rx = this.job_events.subscribe();
+                        let _ = tx.send(new_rx);
+                    }
+                    PayloadServiceCommand::PayloadJobs(tx) => {
+                        let _ = tx.send(this.payload_jobs());
+                    }
                 }
             }
 
@@ -472,6 +656,10 @@ pub enum PayloadServiceCommand<Engine: PayloadTypes> {
     Resolve(PayloadId, oneshot::Sender<Option<PayloadFuture<Engine::BuiltPayload>>>),
     /// Payload service events
     Subscribe(oneshot::Sender<broadcast::Receiver<Events<Engine>>>),
+    /// Payload job events
+    SubscribeJobEvents(oneshot::Sender<broadcast::Receiver<PayloadJobEvent>>),
+    /// Get information about all active payload jobs
+    PayloadJobs(oneshot::Sender<Vec<PayloadJobInfo>>),
 }
 
 impl<Engine> fmt::Debug for PayloadServiceCommand<Engine>
@@ -491,6 +679,150 @@ where
             }
             Self::Resolve(f0, _f1) => f.debug_tuple("Resolve").field(&f0).finish(),
             Self::Subscribe(f0) => f.debug_tuple("Subscribe").field(&f0).finish(),
+            Self::SubscribeJobEvents(f0) => f.debug_tuple("SubscribeJobEvents").field(&f0).finish(),
+            Self::PayloadJobs(f0) => f.debug_tuple("PayloadJobs").field(&f0).finish(),
         }
     }
 }
+
+/// Information about an active payload job.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayloadJobInfo {
+    /// The identifier of the payload.
+    pub id: PayloadId,
+    /// The hash of the parent block the payload is built on.
+    pub parent: B256,
+    /// The timestamp of the payload.
+    pub timestamp: u64,
+    /// How long ago the job was created.
+    pub age: Duration,
+    /// Number of finished build attempts of the job.
+    pub iterations: u64,
+    /// The most recent finished build attempt of the job.
+    pub last_iteration: Option<PayloadBuildIteration>,
+    /// The block hash of the best payload built so far.
+    pub best_block_hash: Option<B256>,
+    /// The fees of the best payload built so far.
+    pub best_fees: Option<U256>,
+}
+
+/// Bookkeeping of an active payload job.
+#[derive(Debug)]
+struct PayloadJobStats {
+    /// The hash of the parent block the payload is built on.
+    parent: B256,
+    /// The timestamp of the payload.
+    timestamp: u64,
+    /// When the job was created.
+    started_at: Instant,
+    /// Number of finished build attempts.
+    iterations: u64,
+    /// The most recent finished build attempt.
+    last_iteration: Option<PayloadBuildIteration>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{PayloadBuildCancelReason, PayloadBuildOutcome},
+        test_utils::{test_payload_service, TestPayloadJobGenerator},
+        EthPayloadBuilderAttributes,
+    };
+    use reth_ethereum_engine_primitives::EthEngineTypes;
+    use reth_primitives::Address;
+    use reth_rpc_types::engine::PayloadAttributes;
+
+    fn attributes(timestamp: u64) -> EthPayloadBuilderAttributes {
+        EthPayloadBuilderAttributes::new(
+            B256::with_last_byte(1),
+            PayloadAttributes {
+                timestamp,
+                prev_randao: B256::ZERO,
+                suggested_fee_recipient: Address::ZERO,
+                withdrawals: Some(Vec::new()),
+                parent_beacon_block_root: None,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn tracks_started_and_resolved_jobs() {
+        let (service, handle) = test_payload_service::<EthEngineTypes>();
+        tokio::spawn(service);
+        let mut events = handle.subscribe_job_events().await.unwrap();
+
+        let attr = attributes(1);
+        let id = handle.new_payload(attr.clone()).await.unwrap();
+        assert_eq!(
+            events.recv().await.unwrap(),
+            PayloadJobEvent::Started { id, parent: attr.parent, timestamp: 1 }
+        );
+
+        let jobs = handle.payload_jobs().await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].id, id);
+        assert_eq!(jobs[0].parent, attr.parent);
+        assert_eq!(jobs[0].timestamp, 1);
+        assert_eq!(jobs[0].iterations, 0);
+        assert_eq!(jobs[0].last_iteration, None);
+        assert!(jobs[0].best_block_hash.is_some());
+
+        // the test job is not kept alive after it was resolved
+        handle.resolve(id).await.unwrap().unwrap();
+        assert_eq!(
+            events.recv().await.unwrap(),
+            PayloadJobEvent::Terminated { id, reason: PayloadJobTerminationReason::Resolved }
+        );
+        assert!(handle.payload_jobs().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn counts_build_iterations() {
+        let (mut service, _handle) = test_payload_service::<EthEngineTypes>();
+        let mut events = service.job_events.subscribe();
+
+        let attr = attributes(1);
+        let id = attr.payload_id();
+        let job = TestPayloadJobGenerator::default().new_payload_job(attr.clone()).unwrap();
+        service.payload_jobs.push((job, id));
+        service.on_job_started(id, attr.parent, attr.timestamp);
+
+        let better = PayloadBuildIteration {
+            id,
+            elapsed: Duration::from_millis(10),
+            outcome: PayloadBuildOutcome::Better {
+                transactions: 1,
+                gas_used: 21_000,
+                fees: U256::from(1),
+            },
+        };
+        let cancelled = PayloadBuildIteration {
+            id,
+            elapsed: Duration::from_millis(5),
+            outcome: PayloadBuildOutcome::Cancelled(PayloadBuildCancelReason::Resolved),
+        };
+        service.on_build_iteration(better.clone());
+        service.on_build_iteration(cancelled.clone());
+
+        let jobs = service.payload_jobs();
+        assert_eq!(jobs[0].iterations, 2);
+        assert_eq!(jobs[0].last_iteration, Some(cancelled.clone()));
+
+        assert!(matches!(events.try_recv().unwrap(), PayloadJobEvent::Started { .. }));
+        assert_eq!(events.try_recv().unwrap(), PayloadJobEvent::BuildIteration(better));
+        assert_eq!(events.try_recv().unwrap(), PayloadJobEvent::BuildIteration(cancelled.clone()));
+
+        // iterations that arrive after the job was terminated are still reported
+        service.payload_jobs.clear();
+        service.on_job_terminated(id, PayloadJobTerminationReason::Finished);
+        assert!(service.job_stats.is_empty());
+        service.on_build_iteration(cancelled.clone());
+        assert!(service.job_stats.is_empty());
+        assert_eq!(
+            events.try_recv().unwrap(),
+            PayloadJobEvent::Terminated { id, reason: PayloadJobTerminationReason::Finished }
+        );
+        assert_eq!(events.try_recv().unwrap(), PayloadJobEvent::BuildIteration(cancelled));
+    }
+}
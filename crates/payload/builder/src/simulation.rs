@@ -0,0 +1,346 @@
+//! Cache of transaction execution results shared across payload jobs.
+
+use crate::metrics::SimulationCacheMetrics;
+use parking_lot::RwLock;
+use reth_evm::{read_set::TrackedExecution, ConfigureEvm};
+use reth_primitives::{
+    revm_primitives::{db::Database, Address, EVMError, EnvWithHandlerCfg, ResultAndState},
+    TxHash, U256,
+};
+use reth_provider::CanonStateNotification;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+};
+
+/// The number of transactions execution results are kept for.
+const MAX_CACHED_TRANSACTIONS: usize = 8192;
+
+/// The number of execution results kept per transaction.
+const MAX_RESULTS_PER_TRANSACTION: usize = 4;
+
+/// A cache of transaction execution results, keyed by transaction hash and the state the
+/// transaction read.
+///
+/// [`SimulationCache::transact`] records every account, storage slot and block hash a transaction
+/// reads while it is executed. When the same transaction is executed again in the same
+/// environment and all of these values are unchanged, the cached [`ResultAndState`] is returned
+/// instead of executing it again.
+///
+/// Every transaction credits the block's beneficiary, so the beneficiary's balance is only part of
+/// the read set if the transaction observed it, e.g. via `BALANCE` or by calling it. Otherwise the
+/// cached result is reused on top of any beneficiary balance, which allows reusing results of
+/// transactions that moved to another position of the payload.
+///
+/// The environment includes the block's timestamp and base fee, so results are reused by the
+/// build attempts of a payload job and by bundle simulations targeting the same block, but not by
+/// payload jobs of later blocks.
+///
+/// Entries are invalidated by the state changes of new canonical blocks, and results executed for
+/// blocks that are already canonical are evicted, see [`SimulationCache::on_new_state`].
+#[derive(Debug, Clone, Default)]
+pub struct SimulationCache {
+    inner: Arc<RwLock<SimulationCacheInner>>,
+    metrics: SimulationCacheMetrics,
+}
+
+impl SimulationCache {
+    /// Executes the transaction with the given hash in the given environment, or returns the cached
+    /// result of a previous execution if the state it read is unchanged.
+    ///
+    /// Only successful executions are cached, including reverted and halted transactions. Errors,
+    /// e.g. for invalid transactions, are returned as is.
+    pub fn transact<EvmConfig, DB>(
+        &self,
+        evm_config: &EvmConfig,
+        db: &mut DB,
+        env: EnvWithHandlerCfg,
+        tx_hash: TxHash,
+    ) -> Result<ResultAndState, EVMError<DB::Error>>
+    where
+        EvmConfig: ConfigureEvm,
+        DB: Database,
+    {
+        if let Some(result) = self.lookup(db, &env, tx_hash).map_err(EVMError::Database)? {
+            self.metrics.hits.increment(1);
+            return Ok(result)
+        }
+        self.metrics.misses.increment(1);
+
+        let execution = TrackedExecution::transact(evm_config, db, env.clone())?;
+        let result = execution.result().clone();
+        self.insert(tx_hash, CachedSimulation { env, execution });
+        Ok(result)
+    }
+
+    /// Returns the cached result of the transaction if it was executed in the same environment and
+    /// the state it read is unchanged.
+    fn lookup<DB: Database>(
+        &self,
+        db: &mut DB,
+        env: &EnvWithHandlerCfg,
+        tx_hash: TxHash,
+    ) -> Result<Option<ResultAndState>, DB::Error> {
+        let candidates = match self.inner.read().entries.get(&tx_hash) {
+            Some(entries) => entries.clone(),
+            None => return Ok(None),
+        };
+
+        for entry in candidates.iter().rev() {
+            if entry.env != *env {
+                continue
+            }
+            if let Some(result) = entry.execution.result_on(db)? {
+                return Ok(Some(result))
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Adds a new execution result of the transaction, evicting the oldest transactions if the
+    /// cache is full.
+    fn insert(&self, tx_hash: TxHash, entry: CachedSimulation) {
+        let mut inner = self.inner.write();
+        let addresses = entry.execution.reads().addresses().collect::<Vec<_>>();
+        for address in addresses {
+            inner.readers.entry(address).or_default().insert(tx_hash);
+        }
+
+        if !inner.entries.contains_key(&tx_hash) {
+            if inner.order.len() == MAX_CACHED_TRANSACTIONS {
+                if let Some(oldest) = inner.order.pop_front() {
+                    inner.remove(&oldest);
+                }
+            }
+            inner.order.push_back(tx_hash);
+        }
+
+        let entries = inner.entries.entry(tx_hash).or_default();
+        if entries.len() == MAX_RESULTS_PER_TRANSACTION {
+            entries.remove(0);
+        }
+        entries.push(Arc::new(entry));
+    }
+
+    /// Invalidates the cached results that read state changed by the new canonical blocks, the
+    /// results of transactions included in them and the results executed for blocks that are not
+    /// above the new tip.
+    ///
+    /// On reorgs the whole cache is cleared.
+    pub fn on_new_state(&self, new_state: &CanonStateNotification) {
+        let mut inner = self.inner.write();
+        if inner.entries.is_empty() {
+            return
+        }
+        if new_state.reverted().is_some() {
+            let invalidated = inner.entries.len();
+            *inner = SimulationCacheInner::default();
+            self.metrics.invalidated.increment(invalidated as u64);
+            return
+        }
+
+        let committed = new_state.committed();
+        let mut invalidated = 0;
+
+        // results executed in the environment of a canonical block can't be looked up again
+        let tip = U256::from(committed.tip().number);
+        let mut stale = Vec::new();
+        for (tx_hash, entries) in &mut inner.entries {
+            entries.retain(|entry| entry.env.block.number > tip);
+            if entries.is_empty() {
+                stale.push(*tx_hash);
+            }
+        }
+        for tx_hash in stale {
+            inner.remove(&tx_hash);
+            invalidated += 1;
+        }
+
+        for block in committed.blocks_iter() {
+            for tx in &block.body {
+                if inner.remove(&tx.hash) {
+                    invalidated += 1;
+                }
+            }
+        }
+
+        for (address, account) in committed.execution_outcome().bundle_accounts_iter() {
+            let Some(readers) = inner.readers.remove(&address) else { continue };
+            for tx_hash in readers {
+                let Some(entries) = inner.entries.get_mut(&tx_hash) else { continue };
+                entries.retain(|entry| {
+                    !entry.execution.reads().is_changed(
+                        address,
+                        account.info.as_ref(),
+                        account.status.was_destroyed(),
+                        account.storage.iter().map(|(slot, value)| (*slot, value.present_value)),
+                    )
+                });
+                if entries.is_empty() {
+                    inner.remove(&tx_hash);
+                    invalidated += 1;
+                } else {
+                    // the remaining results still read the account
+                    inner.readers.entry(address).or_default().insert(tx_hash);
+                }
+            }
+        }
+        self.metrics.invalidated.increment(invalidated);
+    }
+
+    /// Returns the number of transactions with cached results.
+    pub fn len(&self) -> usize {
+        self.inner.read().entries.len()
+    }
+
+    /// Returns `true` if there are no cached results.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Default)]
+struct SimulationCacheInner {
+    /// Execution results by transaction hash, oldest first.
+    entries: HashMap<TxHash, Vec<Arc<CachedSimulation>>>,
+    /// Transaction hashes in insertion order, used for eviction.
+    order: VecDeque<TxHash>,
+    /// Transactions that read an account or its storage, by account address.
+    readers: HashMap<Address, HashSet<TxHash>>,
+}
+
+impl SimulationCacheInner {
+    /// Removes all results of the transaction, returns `true` if there were any.
+    fn remove(&mut self, tx_hash: &TxHash) -> bool {
+        let Some(entries) = self.entries.remove(tx_hash) else { return false };
+        for address in entries.iter().flat_map(|entry| entry.execution.reads().addresses()) {
+            if let Some(readers) = self.readers.get_mut(&address) {
+                readers.remove(tx_hash);
+                if readers.is_empty() {
+                    self.readers.remove(&address);
+                }
+            }
+        }
+        self.order.retain(|hash| hash != tx_hash);
+        true
+    }
+}
+
+/// A cached execution result.
+#[derive(Debug)]
+struct CachedSimulation {
+    /// The environment the transaction was executed in.
+    env: EnvWithHandlerCfg,
+    /// The execution result and the state it read.
+    execution: TrackedExecution,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_evm_ethereum::EthEvmConfig;
+    use reth_primitives::{
+        revm_primitives::{AccountInfo, BlockEnv, Env, SpecId, TransactTo, TxEnv},
+        Header, SealedBlockWithSenders, SealedHeader, B256,
+    };
+    use reth_provider::{Chain, ExecutionOutcome};
+    use revm::{db::CacheDB, DatabaseCommit, InMemoryDB};
+
+    fn env(sender: Address, to: Address, coinbase: Address) -> EnvWithHandlerCfg {
+        EnvWithHandlerCfg::new_with_spec_id(
+            Box::new(Env {
+                block: BlockEnv {
+                    number: U256::from(1),
+                    coinbase,
+                    basefee: U256::from(1),
+                    ..Default::default()
+                },
+                tx: TxEnv {
+                    caller: sender,
+                    transact_to: TransactTo::Call(to),
+                    value: U256::from(10),
+                    gas_limit: 21_000,
+                    gas_price: U256::from(2),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            SpecId::CANCUN,
+        )
+    }
+
+    fn account(balance: u64, nonce: u64) -> AccountInfo {
+        AccountInfo { balance: U256::from(balance), nonce, ..Default::default() }
+    }
+
+    #[test]
+    fn reuses_results_until_reads_change() {
+        let (sender, to, coinbase) =
+            (Address::with_last_byte(1), Address::with_last_byte(2), Address::with_last_byte(3));
+        let tx_hash = TxHash::with_last_byte(1);
+        let mut db = CacheDB::new(InMemoryDB::default());
+        db.insert_account_info(sender, account(1_000_000, 0));
+        db.insert_account_info(coinbase, account(1, 0));
+
+        let cache = SimulationCache::default();
+        let first = cache
+            .transact(&EthEvmConfig::default(), &mut db, env(sender, to, coinbase), tx_hash)
+            .unwrap();
+        assert_eq!(cache.len(), 1);
+
+        // the beneficiary balance is not part of the read set
+        db.insert_account_info(coinbase, account(100, 0));
+        let second = cache
+            .transact(&EthEvmConfig::default(), &mut db, env(sender, to, coinbase), tx_hash)
+            .unwrap();
+        assert_eq!(second.result, first.result);
+        assert_eq!(
+            second.state[&coinbase].info.balance,
+            U256::from(100) + first.state[&coinbase].info.balance - U256::from(1)
+        );
+
+        // executing the transaction changes the sender, so the result can't be reused
+        db.commit(second.state);
+        let third = cache
+            .transact(&EthEvmConfig::default(), &mut db, env(sender, to, coinbase), tx_hash)
+            .unwrap();
+        assert_eq!(third.state[&sender].info.nonce, 2);
+    }
+
+    #[test]
+    fn evicts_results_of_canonical_blocks() {
+        let (sender, to, coinbase) =
+            (Address::with_last_byte(1), Address::with_last_byte(2), Address::with_last_byte(3));
+        let mut db = CacheDB::new(InMemoryDB::default());
+        db.insert_account_info(sender, account(1_000_000, 0));
+
+        let cache = SimulationCache::default();
+        cache
+            .transact(
+                &EthEvmConfig::default(),
+                &mut db,
+                env(sender, to, coinbase),
+                TxHash::with_last_byte(1),
+            )
+            .unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let new_state = |number| {
+            let mut block = SealedBlockWithSenders::default();
+            block.block.header =
+                SealedHeader::new(Header { number, ..Default::default() }, B256::ZERO);
+            CanonStateNotification::Commit {
+                new: Arc::new(Chain::from_block(block, ExecutionOutcome::default(), None)),
+            }
+        };
+
+        // the result was executed for block 1, which is still pending
+        cache.on_new_state(&new_state(0));
+        assert_eq!(cache.len(), 1);
+
+        // block 1 is canonical, so the result can't be reused anymore
+        cache.on_new_state(&new_state(1));
+        assert!(cache.is_empty());
+    }
+}
@@ -1,6 +1,9 @@
 //! Trait abstractions used by the payload crate.
 
-use crate::error::PayloadBuilderError;
+use crate::{
+    error::PayloadBuilderError, events::PayloadBuildIterationSender, InclusionListStore,
+    SimulationCache,
+};
 use reth_payload_primitives::{BuiltPayload, PayloadBuilderAttributes};
 use reth_provider::CanonStateNotification;
 use std::future::Future;
@@ -94,4 +97,31 @@ pub trait PayloadJobGenerator: Send + Sync {
     fn on_new_state(&mut self, new_state: CanonStateNotification) {
         let _ = new_state;
     }
+
+    /// Installs the sender that jobs created by this generator should report their build
+    /// attempts to.
+    ///
+    /// This is called once by the [`PayloadBuilderService`](crate::PayloadBuilderService) when it
+    /// is created.
+    fn set_iteration_sender(&mut self, sender: PayloadBuildIterationSender) {
+        let _ = sender;
+    }
+
+    /// Installs the inclusion lists that jobs created by this generator must satisfy.
+    ///
+    /// This is called once by the [`PayloadBuilderService`](crate::PayloadBuilderService) when it
+    /// is created, the store is shared with its
+    /// [`PayloadBuilderHandle`](crate::PayloadBuilderHandle).
+    fn set_inclusion_lists(&mut self, inclusion_lists: InclusionListStore) {
+        let _ = inclusion_lists;
+    }
+
+    /// Installs the cache of transaction execution results jobs created by this generator can
+    /// reuse.
+    ///
+    /// This is called once by the [`PayloadBuilderService`](crate::PayloadBuilderService) when it
+    /// is created, the service keeps the cache up to date with the canonical chain.
+    fn set_simulation_cache(&mut self, simulation_cache: SimulationCache) {
+        let _ = simulation_cache;
+    }
 }
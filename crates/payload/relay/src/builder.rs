@@ -0,0 +1,76 @@
+//! A [`PayloadBuilder`] that submits every better payload to the relays.
+
+use crate::service::RelayBuilderHandle;
+use reth_basic_payload_builder::{
+    BuildArguments, BuildOutcome, MissingPayloadBehaviour, PayloadBuilder, PayloadConfig,
+};
+use reth_ethereum_engine_primitives::EthBuiltPayload;
+use reth_payload_builder::error::PayloadBuilderError;
+use reth_primitives::U256;
+
+/// Wraps a [`PayloadBuilder`] and sends every better payload it builds to the
+/// [`RelayBuilderService`](crate::RelayBuilderService).
+///
+/// Since payload jobs only keep payloads that improve on their best payload, this submits a bid
+/// on every improvement of a job. Once the proposer of the payload's slot is known, payloads are
+/// built with the gas limit the proposer registered with the relays.
+#[derive(Debug, Clone)]
+pub struct RelayPayloadBuilder<Builder> {
+    /// The wrapped payload builder.
+    inner: Builder,
+    /// Sends better payloads to the relay service.
+    relay: RelayBuilderHandle,
+}
+
+impl<Builder> RelayPayloadBuilder<Builder> {
+    /// Creates a new builder that submits the payloads of the given builder.
+    pub const fn new(inner: Builder, relay: RelayBuilderHandle) -> Self {
+        Self { inner, relay }
+    }
+
+    /// Sets the gas limit of the payload to the gas limit the proposer registered, if known.
+    fn apply_registered_gas_limit<Attributes>(&self, config: &mut PayloadConfig<Attributes>) {
+        let timestamp = config.initialized_block_env.timestamp.saturating_to();
+        if let Some(gas_limit) = self.relay.gas_limit(timestamp, config.parent_block.gas_limit) {
+            config.initialized_block_env.gas_limit = U256::from(gas_limit);
+        }
+    }
+}
+
+impl<Pool, Client, Builder> PayloadBuilder<Pool, Client> for RelayPayloadBuilder<Builder>
+where
+    Builder: PayloadBuilder<Pool, Client, BuiltPayload = EthBuiltPayload>,
+{
+    type Attributes = Builder::Attributes;
+    type BuiltPayload = EthBuiltPayload;
+
+    fn try_build(
+        &self,
+        mut args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+        self.apply_registered_gas_limit(&mut args.config);
+        let parent_gas_limit = args.config.parent_block.gas_limit;
+        let outcome = self.inner.try_build(args)?;
+        if let BuildOutcome::Better { payload, .. } = &outcome {
+            self.relay.submit(payload.clone(), parent_gas_limit);
+        }
+        Ok(outcome)
+    }
+
+    fn on_missing_payload(
+        &self,
+        mut args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> MissingPayloadBehaviour<Self::BuiltPayload> {
+        self.apply_registered_gas_limit(&mut args.config);
+        self.inner.on_missing_payload(args)
+    }
+
+    fn build_empty_payload(
+        &self,
+        client: &Client,
+        mut config: PayloadConfig<Self::Attributes>,
+    ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        self.apply_registered_gas_limit(&mut config);
+        self.inner.build_empty_payload(client, config)
+    }
+}
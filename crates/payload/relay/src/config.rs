@@ -0,0 +1,83 @@
+//! Beacon chain parameters of the relay builder.
+
+use reth_chainspec::{Chain, NamedChain};
+use reth_primitives::constants::SLOT_DURATION;
+
+/// The beacon chain parameters required to map payloads to slots and to sign bids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeaconChainConfig {
+    /// The timestamp of the beacon chain genesis.
+    pub genesis_time: u64,
+    /// The duration of a slot in seconds.
+    pub seconds_per_slot: u64,
+    /// The genesis fork version, used to compute the builder domain.
+    pub genesis_fork_version: [u8; 4],
+}
+
+impl BeaconChainConfig {
+    /// The beacon chain parameters of mainnet.
+    pub const MAINNET: Self = Self::new(1606824023, [0x00, 0x00, 0x00, 0x00]);
+
+    /// The beacon chain parameters of sepolia.
+    pub const SEPOLIA: Self = Self::new(1655733600, [0x90, 0x00, 0x00, 0x69]);
+
+    /// The beacon chain parameters of holesky.
+    pub const HOLESKY: Self = Self::new(1695902400, [0x01, 0x01, 0x70, 0x00]);
+
+    /// Creates a new config with the default slot duration of 12 seconds.
+    pub const fn new(genesis_time: u64, genesis_fork_version: [u8; 4]) -> Self {
+        Self { genesis_time, seconds_per_slot: SLOT_DURATION.as_secs(), genesis_fork_version }
+    }
+
+    /// Returns the beacon chain parameters of the given chain, if known.
+    pub fn for_chain(chain: Chain) -> Option<Self> {
+        match chain.named()? {
+            NamedChain::Mainnet => Some(Self::MAINNET),
+            NamedChain::Sepolia => Some(Self::SEPOLIA),
+            NamedChain::Holesky => Some(Self::HOLESKY),
+            _ => None,
+        }
+    }
+
+    /// Sets the duration of a slot in seconds.
+    pub const fn with_seconds_per_slot(mut self, seconds_per_slot: u64) -> Self {
+        self.seconds_per_slot = seconds_per_slot;
+        self
+    }
+
+    /// Returns the slot of the block with the given timestamp, if the timestamp is not before
+    /// genesis.
+    pub const fn slot_at(&self, timestamp: u64) -> Option<u64> {
+        match timestamp.checked_sub(self.genesis_time) {
+            Some(elapsed) => Some(elapsed / self.seconds_per_slot),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_at_timestamp() {
+        let config = BeaconChainConfig::MAINNET;
+        assert_eq!(config.slot_at(1606824023 - 1), None);
+        assert_eq!(config.slot_at(1606824023), Some(0));
+        assert_eq!(config.slot_at(1606824023 + 11), Some(0));
+        assert_eq!(config.slot_at(1606824023 + 24), Some(2));
+    }
+
+    #[test]
+    fn config_for_chain() {
+        assert_eq!(
+            BeaconChainConfig::for_chain(Chain::mainnet()),
+            Some(BeaconChainConfig::MAINNET)
+        );
+        assert_eq!(
+            BeaconChainConfig::for_chain(Chain::holesky()),
+            Some(BeaconChainConfig::HOLESKY)
+        );
+        assert_eq!(BeaconChainConfig::for_chain(Chain::from_id(1337)), None);
+    }
+}
@@ -0,0 +1,51 @@
+//! Submits payloads built by reth to MEV-Boost relays.
+//!
+//! The [`RelayPayloadBuilder`] wraps a
+//! [`PayloadBuilder`](reth_basic_payload_builder::PayloadBuilder) and forwards every better payload
+//! of a payload job to the [`RelayBuilderService`]. The service signs a
+//! [`BidTrace`](reth_rpc_types::beacon::relay::BidTrace) with the builder's BLS key and submits the
+//! block to all configured relays that know the proposer of the slot. Payloads are built with the
+//! gas limit the proposer registered with the relays.
+//!
+//! The Ethereum node submits its payloads to the relays configured with `--builder.relays`.
+//!
+//! ## Usage
+//!
+//! ```ignore
+//! let signer = BuilderSigner::new(&secret_key, BeaconChainConfig::MAINNET.genesis_fork_version)?;
+//! let relays = vec![RelayClient::new("https://relay.example".parse()?)];
+//! let (service, handle) =
+//!     RelayBuilderService::new(relays, signer, chain_spec, BeaconChainConfig::MAINNET);
+//! executor.spawn_critical("relay builder", Box::pin(service.run()));
+//!
+//! let builder = RelayPayloadBuilder::new(EthereumPayloadBuilder::default(), handle);
+//! let generator = BasicPayloadJobGenerator::with_builder(
+//!     client, pool, executor, config, chain_spec, builder,
+//! );
+//! ```
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+mod builder;
+mod config;
+mod metrics;
+pub mod relay;
+mod service;
+pub mod signing;
+pub mod submission;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
+pub use builder::RelayPayloadBuilder;
+pub use config::BeaconChainConfig;
+pub use relay::{Relay, RelayClient, RelayError};
+pub use service::{RelayBuilderHandle, RelayBuilderService};
+pub use signing::BuilderSigner;
+pub use submission::{BlockSubmission, SignedBidSubmissionV4};
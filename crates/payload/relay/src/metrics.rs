@@ -0,0 +1,22 @@
+//! Metrics for the relay builder.
+
+use reth_metrics::{
+    metrics::{Counter, Histogram},
+    Metrics,
+};
+
+/// Relay builder metrics
+#[derive(Metrics)]
+#[metrics(scope = "payloads.relay")]
+pub(crate) struct RelayBuilderMetrics {
+    /// Total number of bids submitted to relays
+    pub(crate) submitted_bids: Counter,
+    /// Total number of bids rejected by relays or failed to be submitted
+    pub(crate) failed_submissions: Counter,
+    /// Total number of payloads that were not submitted because no relay knows the proposer
+    pub(crate) unregistered_proposer_payloads: Counter,
+    /// Total number of failed requests for the proposer duties of a relay
+    pub(crate) failed_duty_requests: Counter,
+    /// Time it took to submit a bid to a relay
+    pub(crate) submission_duration: Histogram,
+}
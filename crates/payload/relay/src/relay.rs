@@ -0,0 +1,109 @@
+//! Clients for the relay builder API.
+//!
+//! See also <https://flashbots.github.io/relay-specs/>
+
+use crate::submission::BlockSubmission;
+use reqwest::{header, Client, Url};
+use reth_rpc_types::beacon::relay::Validator;
+use std::{fmt, future::Future};
+
+/// The endpoint that returns the proposer duties of the current and next epoch.
+const VALIDATORS_PATH: &str = "/relay/v1/builder/validators";
+
+/// The endpoint that accepts block submissions.
+const BLOCKS_PATH: &str = "/relay/v1/builder/blocks";
+
+/// Errors returned by a [`Relay`].
+#[derive(Debug, thiserror::Error)]
+pub enum RelayError {
+    /// The request to the relay failed.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// The relay returned an unsuccessful status.
+    #[error("relay responded with {status}: {body}")]
+    Status {
+        /// The status code of the response.
+        status: reqwest::StatusCode,
+        /// The body of the response.
+        body: String,
+    },
+    /// The response or request could not be (de)serialized.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// A relay that accepts block submissions from builders.
+pub trait Relay: fmt::Debug + Send + Sync + 'static {
+    /// Returns the proposers of the current and next epoch that registered with the relay.
+    fn get_validators(&self) -> impl Future<Output = Result<Vec<Validator>, RelayError>> + Send;
+
+    /// Submits a signed block to the relay.
+    fn submit_block(
+        &self,
+        submission: &BlockSubmission,
+    ) -> impl Future<Output = Result<(), RelayError>> + Send;
+}
+
+/// A [`Relay`] that is accessed over HTTP.
+#[derive(Debug, Clone)]
+pub struct RelayClient {
+    /// The base url of the relay.
+    url: Url,
+    /// The HTTP client.
+    client: Client,
+}
+
+impl RelayClient {
+    /// Creates a new client for the relay at the given base url.
+    pub fn new(url: Url) -> Self {
+        Self::with_client(url, Client::new())
+    }
+
+    /// Creates a new client for the relay at the given base url with the given HTTP client.
+    pub const fn with_client(url: Url, client: Client) -> Self {
+        Self { url, client }
+    }
+
+    /// Returns the base url of the relay.
+    pub const fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the url of the given endpoint of the relay.
+    fn endpoint(&self, path: &str) -> Url {
+        let mut url = self.url.clone();
+        url.set_path(path);
+        url
+    }
+}
+
+/// Returns an error if the response has an unsuccessful status.
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, RelayError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response)
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(RelayError::Status { status, body })
+}
+
+impl Relay for RelayClient {
+    async fn get_validators(&self) -> Result<Vec<Validator>, RelayError> {
+        let response = self.client.get(self.endpoint(VALIDATORS_PATH)).send().await?;
+        let body = check_status(response).await?.bytes().await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    async fn submit_block(&self, submission: &BlockSubmission) -> Result<(), RelayError> {
+        let body = serde_json::to_vec(submission)?;
+        let response = self
+            .client
+            .post(self.endpoint(BLOCKS_PATH))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,473 @@
+//! The service that signs and submits better payloads to relays.
+
+use crate::{
+    config::BeaconChainConfig, metrics::RelayBuilderMetrics, relay::Relay, signing::BuilderSigner,
+    submission::BlockSubmission,
+};
+use futures_util::future::join_all;
+use parking_lot::RwLock;
+use reth_chainspec::ChainSpec;
+use reth_ethereum_engine_primitives::EthBuiltPayload;
+use reth_primitives::{constants::MINIMUM_GAS_LIMIT, U256};
+use reth_rpc_types::beacon::relay::{BidTrace, ValidatorRegistrationMessage};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tracing::{debug, trace, warn};
+
+/// The bound divisor of the gas limit, the gas limit of a block can move by less than
+/// `parent_gas_limit / GAS_LIMIT_BOUND_DIVISOR` from its parent's.
+const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+
+/// How often the proposer duties are requested from a relay before giving up until the next
+/// payload.
+const DUTY_REQUEST_ATTEMPTS: usize = 3;
+
+/// How long to wait before requesting the proposer duties from a relay again.
+const DUTY_REQUEST_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// The registrations of the proposers of upcoming slots, shared by the service and its handles.
+type Proposers = Arc<RwLock<BTreeMap<u64, ValidatorRegistrationMessage>>>;
+
+/// Messages sent to the [`RelayBuilderService`].
+#[derive(Debug)]
+enum RelayBuilderMessage {
+    /// A better payload to submit to the relays.
+    Payload {
+        /// The payload.
+        payload: Box<EthBuiltPayload>,
+        /// The gas limit of the payload's parent block.
+        parent_gas_limit: u64,
+    },
+    /// Requests the proposer duties of the slot, if they are unknown.
+    FetchDuties(u64),
+}
+
+/// A handle to the [`RelayBuilderService`].
+#[derive(Debug, Clone)]
+pub struct RelayBuilderHandle {
+    /// Sends messages to the service.
+    to_service: mpsc::UnboundedSender<RelayBuilderMessage>,
+    /// The beacon chain parameters.
+    beacon: BeaconChainConfig,
+    /// The registrations of the proposers of upcoming slots known by any relay.
+    proposers: Proposers,
+}
+
+impl RelayBuilderHandle {
+    /// Sends a better payload to the service to be submitted to the relays.
+    pub fn submit(&self, payload: EthBuiltPayload, parent_gas_limit: u64) {
+        let _ = self
+            .to_service
+            .send(RelayBuilderMessage::Payload { payload: Box::new(payload), parent_gas_limit });
+    }
+
+    /// Returns the gas limit of the block with the given timestamp that moves the parent's gas
+    /// limit towards the gas limit the proposer of the slot registered with the relays.
+    ///
+    /// Returns `None` and requests the proposer duties of the slot if the proposer is unknown.
+    pub fn gas_limit(&self, timestamp: u64, parent_gas_limit: u64) -> Option<u64> {
+        let slot = self.beacon.slot_at(timestamp)?;
+        if let Some(registration) = self.proposers.read().get(&slot) {
+            return Some(calculate_gas_limit(parent_gas_limit, registration.gas_limit))
+        }
+        let _ = self.to_service.send(RelayBuilderMessage::FetchDuties(slot));
+        None
+    }
+}
+
+/// The proposer duties known by a relay.
+#[derive(Debug)]
+struct RelayState<R> {
+    /// The relay.
+    relay: R,
+    /// The registrations of the proposers of upcoming slots.
+    duties: BTreeMap<u64, ValidatorRegistrationMessage>,
+    /// The slot for which the duties were last requested.
+    requested_for: Option<u64>,
+}
+
+impl<R: Relay> RelayState<R> {
+    /// Requests the proposer duties from the relay if the proposer of the slot is unknown.
+    ///
+    /// The duties are requested at most once per slot. A failed request is retried a few times,
+    /// and again for the next payload of the slot if all attempts failed.
+    async fn ensure_duty(&mut self, slot: u64, metrics: &RelayBuilderMetrics) {
+        if self.duties.contains_key(&slot) || self.requested_for == Some(slot) {
+            return
+        }
+
+        for attempt in 1..=DUTY_REQUEST_ATTEMPTS {
+            match self.relay.get_validators().await {
+                Ok(validators) => {
+                    self.duties = validators
+                        .into_iter()
+                        .filter(|validator| validator.slot >= slot)
+                        .map(|validator| (validator.slot, validator.entry.message))
+                        .collect();
+                    self.requested_for = Some(slot);
+                    return
+                }
+                Err(err) => {
+                    metrics.failed_duty_requests.increment(1);
+                    warn!(target: "payload_builder::relay", relay=?self.relay, attempt, %err, "failed to request proposer duties");
+                }
+            }
+            if attempt < DUTY_REQUEST_ATTEMPTS {
+                tokio::time::sleep(DUTY_REQUEST_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Submits better payloads to MEV-Boost relays.
+///
+/// For every payload received via the [`RelayBuilderHandle`], the service looks up the proposer of
+/// the payload's slot at each relay, signs a [`BidTrace`] with the builder key and submits the
+/// block in the format of the active fork.
+///
+/// The bid value is the amount of fees of the payload, so payloads are only submitted if their
+/// fee recipient is the fee recipient the proposer registered with the relay, and if their gas
+/// limit moves towards the gas limit the proposer registered.
+#[derive(Debug)]
+pub struct RelayBuilderService<R> {
+    /// The relays to submit to.
+    relays: Vec<RelayState<R>>,
+    /// Signs the bids.
+    signer: BuilderSigner,
+    /// The chain spec, used to determine the submission format.
+    chain_spec: Arc<ChainSpec>,
+    /// The beacon chain parameters.
+    beacon: BeaconChainConfig,
+    /// The registrations of the proposers of upcoming slots known by any relay.
+    proposers: Proposers,
+    /// Receives messages from the handles.
+    messages: mpsc::UnboundedReceiver<RelayBuilderMessage>,
+    /// The slot and value of the last submitted bid.
+    last_bid: Option<(u64, U256)>,
+    /// Metrics of the service.
+    metrics: RelayBuilderMetrics,
+}
+
+impl<R: Relay> RelayBuilderService<R> {
+    /// Creates a new service that submits to the given relays, and the handle to send payloads
+    /// to it.
+    pub fn new(
+        relays: Vec<R>,
+        signer: BuilderSigner,
+        chain_spec: Arc<ChainSpec>,
+        beacon: BeaconChainConfig,
+    ) -> (Self, RelayBuilderHandle) {
+        let (to_service, messages) = mpsc::unbounded_channel();
+        let relays = relays
+            .into_iter()
+            .map(|relay| RelayState { relay, duties: BTreeMap::new(), requested_for: None })
+            .collect();
+        let proposers = Proposers::default();
+        let service = Self {
+            relays,
+            signer,
+            chain_spec,
+            beacon,
+            proposers: proposers.clone(),
+            messages,
+            last_bid: None,
+            metrics: RelayBuilderMetrics::default(),
+        };
+        (service, RelayBuilderHandle { to_service, beacon, proposers })
+    }
+
+    /// Runs the service until all handles are dropped.
+    pub async fn run(mut self) {
+        while let Some(mut message) = self.messages.recv().await {
+            // only the latest payload is worth submitting if several were built in the meantime
+            let mut payload = None;
+            loop {
+                match message {
+                    RelayBuilderMessage::Payload { payload: next, parent_gas_limit } => {
+                        payload = Some((*next, parent_gas_limit))
+                    }
+                    RelayBuilderMessage::FetchDuties(slot) => self.ensure_duties(slot).await,
+                }
+                let Ok(next) = self.messages.try_recv() else { break };
+                message = next;
+            }
+
+            if let Some((payload, parent_gas_limit)) = payload {
+                self.on_payload(payload, parent_gas_limit).await;
+            }
+        }
+    }
+
+    /// Requests the proposer duties from all relays that don't know the proposer of the slot,
+    /// and drops the duties of past slots.
+    async fn ensure_duties(&mut self, slot: u64) {
+        let metrics = &self.metrics;
+        join_all(self.relays.iter_mut().map(|state| state.ensure_duty(slot, metrics))).await;
+
+        let mut proposers = BTreeMap::new();
+        for state in &mut self.relays {
+            state.duties = state.duties.split_off(&slot);
+
+            // relays may know different registrations of a proposer, the latest one applies
+            for (slot, registration) in &state.duties {
+                match proposers.entry(*slot) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(registration.clone());
+                    }
+                    Entry::Occupied(mut entry) => {
+                        if registration.timestamp > entry.get().timestamp {
+                            entry.insert(registration.clone());
+                        }
+                    }
+                }
+            }
+        }
+        *self.proposers.write() = proposers;
+    }
+
+    /// Submits the payload to all relays that know the proposer of its slot.
+    async fn on_payload(&mut self, payload: EthBuiltPayload, parent_gas_limit: u64) {
+        let block = payload.block();
+        let Some(slot) = self.beacon.slot_at(block.timestamp) else { return };
+
+        if let Some((last_slot, last_value)) = self.last_bid {
+            if last_slot == slot && payload.fees() <= last_value {
+                trace!(target: "payload_builder::relay", slot, value=%payload.fees(), "skipping payload that doesn't improve the last bid");
+                return
+            }
+        }
+
+        self.ensure_duties(slot).await;
+
+        let mut submissions = Vec::new();
+        for state in &self.relays {
+            let Some(registration) = state.duties.get(&slot) else { continue };
+            if registration.fee_recipient != block.beneficiary {
+                debug!(target: "payload_builder::relay", relay=?state.relay, slot, expected=%registration.fee_recipient, got=%block.beneficiary, "payload doesn't pay the registered fee recipient");
+                continue
+            }
+            let expected_gas_limit = calculate_gas_limit(parent_gas_limit, registration.gas_limit);
+            if block.gas_limit != expected_gas_limit {
+                debug!(target: "payload_builder::relay", relay=?state.relay, slot, expected=%expected_gas_limit, got=%block.gas_limit, "payload doesn't have the registered gas limit");
+                continue
+            }
+
+            let message = BidTrace {
+                slot,
+                parent_hash: block.parent_hash,
+                block_hash: block.hash(),
+                builder_pubkey: self.signer.public_key(),
+                proposer_pubkey: registration.pubkey,
+                proposer_fee_recipient: registration.fee_recipient,
+                gas_limit: block.gas_limit,
+                gas_used: block.gas_used,
+                value: payload.fees(),
+            };
+            let signature = self.signer.sign(&message);
+            let submission =
+                BlockSubmission::new(&self.chain_spec, message, payload.clone(), signature);
+            submissions.push((&state.relay, submission));
+        }
+
+        if submissions.is_empty() {
+            self.metrics.unregistered_proposer_payloads.increment(1);
+            debug!(target: "payload_builder::relay", slot, block_hash=%block.hash(), "no relay knows the proposer of the slot");
+            return
+        }
+
+        let results = join_all(submissions.iter().map(|(relay, submission)| async move {
+            let start = Instant::now();
+            let result = relay.submit_block(submission).await;
+            (relay, result, start.elapsed())
+        }))
+        .await;
+
+        let mut submitted = false;
+        for (relay, result, elapsed) in results {
+            self.metrics.submission_duration.record(elapsed);
+            match result {
+                Ok(()) => {
+                    submitted = true;
+                    self.metrics.submitted_bids.increment(1);
+                    debug!(target: "payload_builder::relay", ?relay, slot, block_hash=%block.hash(), value=%payload.fees(), ?elapsed, "submitted bid");
+                }
+                Err(err) => {
+                    self.metrics.failed_submissions.increment(1);
+                    warn!(target: "payload_builder::relay", ?relay, slot, block_hash=%block.hash(), %err, "failed to submit bid");
+                }
+            }
+        }
+
+        if submitted {
+            self.last_bid = Some((slot, payload.fees()));
+        }
+    }
+}
+
+/// Returns the gas limit of the child of a block with the given gas limit, that moves the gas
+/// limit as close as possible to the desired gas limit.
+///
+/// See <https://github.com/ethereum/go-ethereum/blob/v1.14.7/core/block_validator.go#L163-L187>
+const fn calculate_gas_limit(parent_gas_limit: u64, desired_gas_limit: u64) -> u64 {
+    let delta = (parent_gas_limit / GAS_LIMIT_BOUND_DIVISOR).saturating_sub(1);
+    let desired_gas_limit =
+        if desired_gas_limit < MINIMUM_GAS_LIMIT { MINIMUM_GAS_LIMIT } else { desired_gas_limit };
+
+    if parent_gas_limit < desired_gas_limit {
+        let limit = parent_gas_limit + delta;
+        if limit > desired_gas_limit {
+            desired_gas_limit
+        } else {
+            limit
+        }
+    } else if parent_gas_limit > desired_gas_limit {
+        let limit = parent_gas_limit - delta;
+        if limit < desired_gas_limit {
+            desired_gas_limit
+        } else {
+            limit
+        }
+    } else {
+        parent_gas_limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockRelay;
+    use reth_chainspec::MAINNET;
+    use reth_payload_builder::PayloadId;
+    use reth_primitives::{Address, Header, SealedBlock};
+    use reth_rpc_types::beacon::BlsPublicKey;
+
+    /// A timestamp after the cancun activation on mainnet.
+    const TIMESTAMP: u64 = 1_720_000_007;
+
+    /// The gas limit the proposers of [`MockRelay::add_validator`] register.
+    const GAS_LIMIT: u64 = 30_000_000;
+
+    fn payload_with_gas_limit(beneficiary: Address, fees: u64, gas_limit: u64) -> EthBuiltPayload {
+        let header = Header { beneficiary, timestamp: TIMESTAMP, gas_limit, ..Default::default() };
+        let block = SealedBlock { header: header.seal_slow(), ..Default::default() };
+        EthBuiltPayload::new(PayloadId::new([0; 8]), block, U256::from(fees))
+    }
+
+    fn payload(beneficiary: Address, fees: u64) -> EthBuiltPayload {
+        payload_with_gas_limit(beneficiary, fees, GAS_LIMIT)
+    }
+
+    #[tokio::test]
+    async fn submits_better_bids_to_registered_proposers() {
+        let signer = BuilderSigner::new(&[1; 32], [0, 0, 0, 0]).unwrap();
+        let relay = MockRelay::default();
+        let fee_recipient = Address::random();
+        let slot = BeaconChainConfig::MAINNET.slot_at(TIMESTAMP).unwrap();
+        relay.add_validator(slot, fee_recipient, BlsPublicKey::repeat_byte(2));
+
+        let (mut service, _handle) = RelayBuilderService::new(
+            vec![relay.clone()],
+            signer.clone(),
+            MAINNET.clone(),
+            BeaconChainConfig::MAINNET,
+        );
+
+        // the payload doesn't pay the proposer
+        service.on_payload(payload(Address::random(), 10), GAS_LIMIT).await;
+        assert!(relay.submissions().is_empty());
+
+        service.on_payload(payload(fee_recipient, 10), GAS_LIMIT).await;
+        let submissions = relay.submissions();
+        assert_eq!(submissions.len(), 1);
+        assert!(matches!(submissions[0], BlockSubmission::Deneb(_)));
+        let message = submissions[0].message();
+        assert_eq!(message.slot, slot);
+        assert_eq!(message.value, U256::from(10));
+        assert_eq!(message.proposer_pubkey, BlsPublicKey::repeat_byte(2));
+        assert!(signer.verify(message, submissions[0].signature()));
+
+        // worse payloads of the same slot are not submitted
+        service.on_payload(payload(fee_recipient, 5), GAS_LIMIT).await;
+        assert_eq!(relay.submissions().len(), 1);
+
+        service.on_payload(payload(fee_recipient, 11), GAS_LIMIT).await;
+        assert_eq!(relay.submissions().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn bids_with_registered_gas_limit() {
+        let signer = BuilderSigner::new(&[1; 32], [0, 0, 0, 0]).unwrap();
+        let relay = MockRelay::default();
+        let fee_recipient = Address::random();
+        let slot = BeaconChainConfig::MAINNET.slot_at(TIMESTAMP).unwrap();
+        let registered_gas_limit = 36_000_000;
+        relay.add_validator_with_gas_limit(
+            slot,
+            fee_recipient,
+            BlsPublicKey::repeat_byte(2),
+            registered_gas_limit,
+        );
+
+        let (mut service, handle) = RelayBuilderService::new(
+            vec![relay.clone()],
+            signer,
+            MAINNET.clone(),
+            BeaconChainConfig::MAINNET,
+        );
+
+        // the proposer is unknown until the duties are requested
+        assert_eq!(handle.gas_limit(TIMESTAMP, GAS_LIMIT), None);
+        service.ensure_duties(slot).await;
+        let gas_limit = GAS_LIMIT + GAS_LIMIT / GAS_LIMIT_BOUND_DIVISOR - 1;
+        assert_eq!(handle.gas_limit(TIMESTAMP, GAS_LIMIT), Some(gas_limit));
+        assert_eq!(
+            handle.gas_limit(TIMESTAMP, registered_gas_limit + 1),
+            Some(registered_gas_limit)
+        );
+
+        // the payload keeps the parent's gas limit
+        service.on_payload(payload(fee_recipient, 10), GAS_LIMIT).await;
+        assert!(relay.submissions().is_empty());
+
+        service.on_payload(payload_with_gas_limit(fee_recipient, 10, gas_limit), GAS_LIMIT).await;
+        let submissions = relay.submissions();
+        assert_eq!(submissions.len(), 1);
+        assert_eq!(submissions[0].message().gas_limit, gas_limit);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_failed_duty_requests() {
+        let signer = BuilderSigner::new(&[1; 32], [0, 0, 0, 0]).unwrap();
+        let relay = MockRelay::default();
+        let fee_recipient = Address::random();
+        let slot = BeaconChainConfig::MAINNET.slot_at(TIMESTAMP).unwrap();
+        relay.add_validator(slot, fee_recipient, BlsPublicKey::repeat_byte(2));
+
+        let (mut service, _handle) = RelayBuilderService::new(
+            vec![relay.clone()],
+            signer,
+            MAINNET.clone(),
+            BeaconChainConfig::MAINNET,
+        );
+
+        // all attempts fail, so the duties are requested again for the next payload
+        relay.fail_validator_requests(DUTY_REQUEST_ATTEMPTS);
+        service.on_payload(payload(fee_recipient, 10), GAS_LIMIT).await;
+        assert_eq!(relay.validator_requests(), DUTY_REQUEST_ATTEMPTS);
+        assert!(relay.submissions().is_empty());
+
+        relay.fail_validator_requests(1);
+        service.on_payload(payload(fee_recipient, 10), GAS_LIMIT).await;
+        assert_eq!(relay.validator_requests(), DUTY_REQUEST_ATTEMPTS + 2);
+        assert_eq!(relay.submissions().len(), 1);
+
+        // the duties are requested once per slot
+        service.on_payload(payload(fee_recipient, 11), GAS_LIMIT).await;
+        assert_eq!(relay.validator_requests(), DUTY_REQUEST_ATTEMPTS + 2);
+        assert_eq!(relay.submissions().len(), 2);
+    }
+}
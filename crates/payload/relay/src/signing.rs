@@ -0,0 +1,219 @@
+//! BLS signing of builder bids.
+//!
+//! Bids are signed over the SSZ hash tree root of the [`BidTrace`] in the builder domain, see
+//! <https://github.com/ethereum/builder-specs/blob/main/specs/bellatrix/builder.md#signing>
+
+use reth_primitives::{hex, B256, U256};
+use reth_rpc_types::beacon::{relay::BidTrace, BlsPublicKey, BlsSignature};
+use sha2::{Digest, Sha256};
+use std::{fmt, io, path::Path};
+
+/// The domain separation tag of the BLS signature scheme used by the beacon chain.
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// The domain type of builder messages, `DOMAIN_APPLICATION_BUILDER`.
+pub const DOMAIN_APPLICATION_BUILDER: [u8; 4] = [0x00, 0x00, 0x00, 0x01];
+
+/// Errors that can occur when creating a [`BuilderSigner`].
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    /// The secret key is not a valid BLS secret key.
+    #[error("invalid BLS secret key: {0:?}")]
+    InvalidSecretKey(blst::BLST_ERROR),
+    /// The secret key file could not be read.
+    #[error("failed to read BLS secret key file: {0}")]
+    ReadSecretKey(#[from] io::Error),
+    /// The secret key file does not contain a hex encoded secret key.
+    #[error("invalid hex encoded BLS secret key: {0}")]
+    InvalidHex(#[from] hex::FromHexError),
+}
+
+/// Signs [`BidTrace`]s with the BLS secret key of the builder.
+#[derive(Clone)]
+pub struct BuilderSigner {
+    /// The secret key of the builder.
+    secret_key: blst::min_pk::SecretKey,
+    /// The public key of the builder.
+    public_key: BlsPublicKey,
+    /// The builder domain of the chain.
+    domain: B256,
+}
+
+impl BuilderSigner {
+    /// Creates a new signer from the 32 byte secret key and the genesis fork version of the
+    /// beacon chain.
+    pub fn new(secret_key: &[u8], genesis_fork_version: [u8; 4]) -> Result<Self, SignerError> {
+        let secret_key = blst::min_pk::SecretKey::from_bytes(secret_key)
+            .map_err(SignerError::InvalidSecretKey)?;
+        let public_key = BlsPublicKey::from(secret_key.sk_to_pk().to_bytes());
+        Ok(Self { secret_key, public_key, domain: compute_builder_domain(genesis_fork_version) })
+    }
+
+    /// Creates a new signer from the hex encoded secret key in the file at the given path.
+    pub fn from_file(path: &Path, genesis_fork_version: [u8; 4]) -> Result<Self, SignerError> {
+        let secret_key = hex::decode(std::fs::read_to_string(path)?.trim())?;
+        Self::new(&secret_key, genesis_fork_version)
+    }
+
+    /// Returns the public key of the builder.
+    pub const fn public_key(&self) -> BlsPublicKey {
+        self.public_key
+    }
+
+    /// Returns the builder domain the signer signs in.
+    pub const fn domain(&self) -> B256 {
+        self.domain
+    }
+
+    /// Signs the given [`BidTrace`].
+    pub fn sign(&self, bid_trace: &BidTrace) -> BlsSignature {
+        let signing_root = compute_signing_root(bid_trace_root(bid_trace), self.domain);
+        BlsSignature::from(self.secret_key.sign(signing_root.as_slice(), BLS_DST, &[]).to_bytes())
+    }
+
+    /// Returns `true` if the signature was created by this signer for the given [`BidTrace`].
+    pub fn verify(&self, bid_trace: &BidTrace, signature: &BlsSignature) -> bool {
+        let Ok(signature) = blst::min_pk::Signature::from_bytes(signature.as_slice()) else {
+            return false
+        };
+        let signing_root = compute_signing_root(bid_trace_root(bid_trace), self.domain);
+        signature.verify(
+            true,
+            signing_root.as_slice(),
+            BLS_DST,
+            &[],
+            &self.secret_key.sk_to_pk(),
+            true,
+        ) == blst::BLST_ERROR::BLST_SUCCESS
+    }
+}
+
+impl fmt::Debug for BuilderSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never print the secret key
+        f.debug_struct("BuilderSigner")
+            .field("public_key", &self.public_key)
+            .field("domain", &self.domain)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Computes the builder domain for the given genesis fork version.
+///
+/// Builder messages are signed with the zero genesis validators root, so the domain is the same
+/// across forks.
+pub fn compute_builder_domain(genesis_fork_version: [u8; 4]) -> B256 {
+    // hash_tree_root(ForkData { current_version, genesis_validators_root: 0 })
+    let fork_data_root = hash_pair(&pad_chunk(&genesis_fork_version), &B256::ZERO);
+
+    let mut domain = B256::ZERO;
+    domain[..4].copy_from_slice(&DOMAIN_APPLICATION_BUILDER);
+    domain[4..].copy_from_slice(&fork_data_root[..28]);
+    domain
+}
+
+/// Computes the signing root of an object root in the given domain.
+fn compute_signing_root(object_root: B256, domain: B256) -> B256 {
+    // hash_tree_root(SigningData { object_root, domain })
+    hash_pair(&object_root, &domain)
+}
+
+/// Computes the SSZ hash tree root of a [`BidTrace`].
+pub fn bid_trace_root(bid_trace: &BidTrace) -> B256 {
+    let leaves = [
+        pad_chunk(&bid_trace.slot.to_le_bytes()),
+        bid_trace.parent_hash,
+        bid_trace.block_hash,
+        bls_public_key_root(&bid_trace.builder_pubkey),
+        bls_public_key_root(&bid_trace.proposer_pubkey),
+        pad_chunk(bid_trace.proposer_fee_recipient.as_slice()),
+        pad_chunk(&bid_trace.gas_limit.to_le_bytes()),
+        pad_chunk(&bid_trace.gas_used.to_le_bytes()),
+        B256::from(bid_trace.value.to_le_bytes::<{ U256::BYTES }>()),
+    ];
+    merkleize(&leaves)
+}
+
+/// Computes the SSZ hash tree root of a 48 byte public key, which spans two chunks.
+fn bls_public_key_root(public_key: &BlsPublicKey) -> B256 {
+    hash_pair(&pad_chunk(&public_key[..32]), &pad_chunk(&public_key[32..]))
+}
+
+/// Merkleizes the given chunks, padding them with zero chunks to the next power of two.
+fn merkleize(chunks: &[B256]) -> B256 {
+    let mut layer = chunks.to_vec();
+    layer.resize(chunks.len().next_power_of_two(), B256::ZERO);
+    while layer.len() > 1 {
+        layer = layer.chunks_exact(2).map(|pair| hash_pair(&pair[0], &pair[1])).collect();
+    }
+    layer[0]
+}
+
+/// Right pads the given bytes with zeros to a 32 byte chunk.
+fn pad_chunk(bytes: &[u8]) -> B256 {
+    let mut chunk = B256::ZERO;
+    chunk[..bytes.len()].copy_from_slice(bytes);
+    chunk
+}
+
+/// Returns the SHA-256 hash of the concatenation of both chunks.
+fn hash_pair(left: &B256, right: &B256) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    B256::from_slice(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{b256, Address};
+
+    #[test]
+    fn mainnet_builder_domain() {
+        assert_eq!(
+            compute_builder_domain([0, 0, 0, 0]),
+            b256!("00000001f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a9")
+        );
+    }
+
+    #[test]
+    fn sign_and_verify_bid_trace() {
+        let signer = BuilderSigner::new(&[1; 32], [0, 0, 0, 0]).unwrap();
+        let mut bid_trace = BidTrace {
+            slot: 1,
+            parent_hash: B256::random(),
+            block_hash: B256::random(),
+            builder_pubkey: signer.public_key(),
+            proposer_pubkey: BlsPublicKey::repeat_byte(2),
+            proposer_fee_recipient: Address::random(),
+            gas_limit: 30_000_000,
+            gas_used: 21_000,
+            value: U256::from(1_000),
+        };
+
+        let signature = signer.sign(&bid_trace);
+        assert!(signer.verify(&bid_trace, &signature));
+
+        bid_trace.value += U256::from(1);
+        assert!(!signer.verify(&bid_trace, &signature));
+    }
+
+    #[test]
+    fn signer_from_file() {
+        let path = std::env::temp_dir().join(format!("reth-relay-key-{}", std::process::id()));
+        std::fs::write(&path, format!("0x{}\n", hex::encode([1; 32]))).unwrap();
+        let signer = BuilderSigner::from_file(&path, [0, 0, 0, 0]).unwrap();
+        assert_eq!(
+            signer.public_key(),
+            BuilderSigner::new(&[1; 32], [0, 0, 0, 0]).unwrap().public_key()
+        );
+
+        std::fs::write(&path, "not hex").unwrap();
+        assert!(matches!(
+            BuilderSigner::from_file(&path, [0, 0, 0, 0]),
+            Err(SignerError::InvalidHex(_))
+        ));
+        let _ = std::fs::remove_file(path);
+    }
+}
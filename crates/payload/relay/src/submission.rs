@@ -0,0 +1,96 @@
+//! Block submissions for the `/relay/v1/builder/blocks` endpoint.
+
+use reth_chainspec::{ChainSpec, EthereumHardforks};
+use reth_ethereum_engine_primitives::EthBuiltPayload;
+use reth_rpc_types::{
+    beacon::{
+        relay::{BidTrace, SignedBidSubmissionV2, SignedBidSubmissionV3},
+        BlsSignature,
+    },
+    engine::{BlobsBundleV1, ExecutionPayloadV4},
+};
+use reth_rpc_types_compat::engine::payload::{
+    block_to_payload_v2, block_to_payload_v3, block_to_payload_v4,
+};
+use serde::{Deserialize, Serialize};
+
+/// Submission for the `/relay/v1/builder/blocks` endpoint (Electra).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignedBidSubmissionV4 {
+    /// The [`BidTrace`] message associated with the submission.
+    pub message: BidTrace,
+    /// The execution payload for the submission.
+    #[serde(with = "reth_rpc_types::beacon::payload::beacon_payload_v4")]
+    pub execution_payload: ExecutionPayloadV4,
+    /// The Electra block bundle for this bid.
+    pub blobs_bundle: BlobsBundleV1,
+    /// The signature associated with the submission.
+    pub signature: BlsSignature,
+}
+
+/// A signed block submission in the format of the fork active at the block's timestamp.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BlockSubmission {
+    /// A Capella submission.
+    Capella(SignedBidSubmissionV2),
+    /// A Deneb submission, including the blobs bundle.
+    Deneb(SignedBidSubmissionV3),
+    /// An Electra submission, including the blobs bundle.
+    Electra(SignedBidSubmissionV4),
+}
+
+impl BlockSubmission {
+    /// Creates the submission of the given payload with its signed [`BidTrace`].
+    pub fn new(
+        chain_spec: &ChainSpec,
+        message: BidTrace,
+        payload: EthBuiltPayload,
+        signature: BlsSignature,
+    ) -> Self {
+        let timestamp = payload.block().timestamp;
+        let blobs_bundle: BlobsBundleV1 = payload.sidecars().to_vec().into();
+        let block = payload.block().clone();
+
+        if chain_spec.is_prague_active_at_timestamp(timestamp) {
+            Self::Electra(SignedBidSubmissionV4 {
+                message,
+                execution_payload: block_to_payload_v4(block),
+                blobs_bundle,
+                signature,
+            })
+        } else if chain_spec.is_cancun_active_at_timestamp(timestamp) {
+            Self::Deneb(SignedBidSubmissionV3 {
+                message,
+                execution_payload: block_to_payload_v3(block),
+                blobs_bundle,
+                signature,
+            })
+        } else {
+            Self::Capella(SignedBidSubmissionV2 {
+                message,
+                execution_payload: block_to_payload_v2(block),
+                signature,
+            })
+        }
+    }
+
+    /// Returns the [`BidTrace`] of the submission.
+    pub const fn message(&self) -> &BidTrace {
+        match self {
+            Self::Capella(submission) => &submission.message,
+            Self::Deneb(submission) => &submission.message,
+            Self::Electra(submission) => &submission.message,
+        }
+    }
+
+    /// Returns the signature of the submission.
+    pub const fn signature(&self) -> &BlsSignature {
+        match self {
+            Self::Capella(submission) => &submission.signature,
+            Self::Deneb(submission) => &submission.signature,
+            Self::Electra(submission) => &submission.signature,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! A mock relay for testing.
+
+use crate::{
+    relay::{Relay, RelayError},
+    submission::BlockSubmission,
+};
+use parking_lot::Mutex;
+use reth_primitives::Address;
+use reth_rpc_types::beacon::{
+    relay::{Validator, ValidatorRegistration, ValidatorRegistrationMessage},
+    BlsPublicKey, BlsSignature,
+};
+use std::sync::Arc;
+
+/// An in-memory [`Relay`] that serves the registered validators and records all submissions.
+#[derive(Debug, Clone, Default)]
+pub struct MockRelay {
+    inner: Arc<Mutex<MockRelayInner>>,
+}
+
+#[derive(Debug, Default)]
+struct MockRelayInner {
+    validators: Vec<Validator>,
+    submissions: Vec<BlockSubmission>,
+    /// Number of upcoming validator requests that fail.
+    failing_validator_requests: usize,
+    /// Number of received validator requests.
+    validator_requests: usize,
+}
+
+impl MockRelay {
+    /// Registers a proposer for the given slot with a gas limit of 30M.
+    pub fn add_validator(&self, slot: u64, fee_recipient: Address, pubkey: BlsPublicKey) {
+        self.add_validator_with_gas_limit(slot, fee_recipient, pubkey, 30_000_000)
+    }
+
+    /// Registers a proposer for the given slot with the given gas limit.
+    pub fn add_validator_with_gas_limit(
+        &self,
+        slot: u64,
+        fee_recipient: Address,
+        pubkey: BlsPublicKey,
+        gas_limit: u64,
+    ) {
+        let entry = ValidatorRegistration {
+            message: ValidatorRegistrationMessage {
+                fee_recipient,
+                gas_limit,
+                timestamp: 0,
+                pubkey,
+            },
+            signature: BlsSignature::ZERO,
+        };
+        let validator_index = self.inner.lock().validators.len() as u64;
+        self.inner.lock().validators.push(Validator { slot, validator_index, entry });
+    }
+
+    /// Fails the given number of upcoming validator requests.
+    pub fn fail_validator_requests(&self, num: usize) {
+        self.inner.lock().failing_validator_requests = num;
+    }
+
+    /// Returns the number of validator requests the relay received.
+    pub fn validator_requests(&self) -> usize {
+        self.inner.lock().validator_requests
+    }
+
+    /// Returns all submissions received by the relay.
+    pub fn submissions(&self) -> Vec<BlockSubmission> {
+        self.inner.lock().submissions.clone()
+    }
+}
+
+impl Relay for MockRelay {
+    async fn get_validators(&self) -> Result<Vec<Validator>, RelayError> {
+        let mut inner = self.inner.lock();
+        inner.validator_requests += 1;
+        if inner.failing_validator_requests > 0 {
+            inner.failing_validator_requests -= 1;
+            return Err(RelayError::Status {
+                status: reqwest::StatusCode::SERVICE_UNAVAILABLE,
+                body: String::new(),
+            })
+        }
+        Ok(inner.validators.clone())
+    }
+
+    async fn submit_block(&self, submission: &BlockSubmission) -> Result<(), RelayError> {
+        self.inner.lock().submissions.push(submission.clone());
+        Ok(())
+    }
+}
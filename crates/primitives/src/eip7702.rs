@@ -1,5 +1,34 @@
 //! Types for working with EIP-7702 transactions.
 
+use crate::Address;
+
 /// Re-export from `alloy_eips`.
 #[doc(inline)]
 pub use alloy_eips::eip7702::{Authorization, OptionalNonce, SignedAuthorization};
+
+/// Prefix of the code of an account that delegated its code with an EIP-7702 authorization.
+pub const DELEGATION_DESIGNATOR: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// Returns the address the account delegates to if the given code is an EIP-7702 delegation
+/// designator: `0xef0100 || address`.
+pub fn delegated_address(code: &[u8]) -> Option<Address> {
+    let address = code.strip_prefix(&DELEGATION_DESIGNATOR)?;
+    (address.len() == Address::len_bytes()).then(|| Address::from_slice(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address;
+
+    #[test]
+    fn delegation_designator() {
+        let target = address!("00000000000000000000000000000000000000aa");
+        let mut code = DELEGATION_DESIGNATOR.to_vec();
+        code.extend_from_slice(target.as_slice());
+        assert_eq!(delegated_address(&code), Some(target));
+
+        assert_eq!(delegated_address(&code[..22]), None);
+        assert_eq!(delegated_address(&[0x60, 0x00]), None);
+    }
+}
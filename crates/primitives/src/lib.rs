@@ -59,7 +59,7 @@ pub use reth_primitives_traits::{
 pub use static_file::StaticFileSegment;
 
 pub use transaction::{
-    BlobTransaction, BlobTransactionSidecar, PooledTransactionsElement,
+    Blob, BlobTransaction, BlobTransactionSidecar, Bytes48, PooledTransactionsElement,
     PooledTransactionsElementEcRecovered,
 };
 
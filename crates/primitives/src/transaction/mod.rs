@@ -31,7 +31,7 @@ pub use pooled::{PooledTransactionsElement, PooledTransactionsElementEcRecovered
 pub use sidecar::generate_blob_sidecar;
 #[cfg(feature = "c-kzg")]
 pub use sidecar::BlobTransactionValidationError;
-pub use sidecar::{BlobTransaction, BlobTransactionSidecar};
+pub use sidecar::{Blob, BlobTransaction, BlobTransactionSidecar, Bytes48};
 
 pub use compat::FillTxEnv;
 pub use signature::{extract_chain_id, Signature};
@@ -7,7 +7,7 @@ use alloy_rlp::{Decodable, Encodable, Error as RlpError, Header};
 use serde::{Deserialize, Serialize};
 
 #[doc(inline)]
-pub use alloy_eips::eip4844::BlobTransactionSidecar;
+pub use alloy_eips::eip4844::{Blob, BlobTransactionSidecar, Bytes48};
 
 #[cfg(feature = "c-kzg")]
 pub use alloy_eips::eip4844::BlobTransactionValidationError;
@@ -9,7 +9,7 @@ use reth_engine_primitives::EngineTypes;
 use reth_primitives::{Address, BlockHash, BlockId, BlockNumberOrTag, Bytes, B256, U256, U64};
 use reth_rpc_types::{
     engine::{
-        ClientVersionV1, ExecutionPayloadBodiesV1, ExecutionPayloadBodiesV2,
+        BlobAndProofV1, ClientVersionV1, ExecutionPayloadBodiesV1, ExecutionPayloadBodiesV2,
         ExecutionPayloadInputV2, ExecutionPayloadV1, ExecutionPayloadV3, ExecutionPayloadV4,
         ForkchoiceState, ForkchoiceUpdated, PayloadId, PayloadStatus, TransitionConfiguration,
     },
@@ -213,6 +213,44 @@ pub trait EngineApi<Engine: EngineTypes> {
     /// See also <https://github.com/ethereum/execution-apis/blob/6452a6b194d7db269bf1dbd087a267251d3cc7f8/src/engine/common.md#capabilities>
     #[method(name = "exchangeCapabilities")]
     async fn exchange_capabilities(&self, capabilities: Vec<String>) -> RpcResult<Vec<String>>;
+
+    /// Fetch blobs for the consensus layer from the blob store of the transaction pool.
+    ///
+    /// Returns the blob and proof for every requested versioned hash, in the same order, or `null`
+    /// for blobs that are not available.
+    ///
+    /// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/cancun.md#engine_getblobsv1>
+    #[method(name = "getBlobsV1")]
+    async fn get_blobs_v1(
+        &self,
+        versioned_hashes: Vec<B256>,
+    ) -> RpcResult<Vec<Option<BlobAndProofV1>>>;
+
+    /// Receives an inclusion list for blocks building on the given parent block.
+    ///
+    /// The transactions are EIP-2718 encoded and must not be blob transactions. Payloads built on
+    /// top of the parent must include them if they are valid, and `engine_newPayload` reports
+    /// blocks omitting valid ones as `INCLUSION_LIST_UNSATISFIED`.
+    ///
+    /// This is part of the experimental FOCIL (EIP-7805) support.
+    #[method(name = "newInclusionListV1")]
+    async fn new_inclusion_list_v1(
+        &self,
+        parent_hash: B256,
+        inclusion_list: Vec<Bytes>,
+    ) -> RpcResult<()>;
+
+    /// Adds an inclusion list to the payload that is being built for the given payload id.
+    ///
+    /// Returns the payload id, or `null` if the payload is unknown.
+    ///
+    /// This is part of the experimental FOCIL (EIP-7805) support.
+    #[method(name = "updatePayloadWithInclusionListV1")]
+    async fn update_payload_with_inclusion_list_v1(
+        &self,
+        payload_id: PayloadId,
+        inclusion_list: Vec<Bytes>,
+    ) -> RpcResult<Option<PayloadId>>;
 }
 
 /// A subset of the ETH rpc interface: <https://ethereum.github.io/execution-apis/api-documentation/>
@@ -23,6 +23,7 @@ mod hardhat;
 mod mev;
 mod net;
 mod otterscan;
+mod payload;
 mod reth;
 mod rpc;
 mod trace;
@@ -30,6 +31,12 @@ mod txpool;
 mod validation;
 mod web3;
 
+pub use payload::{
+    DebugBuiltPayload, PayloadBuildIterationStatus, PayloadBuildIterationSummary, PayloadJobSummary,
+};
+pub use txpool::TxpoolSubscriptionFilter;
+pub use validation::BuilderBlockValidationRequestV3;
+
 /// re-export of all server traits
 pub use servers::*;
 
@@ -42,6 +49,7 @@ pub mod servers {
         mev::MevApiServer,
         net::NetApiServer,
         otterscan::OtterscanServer,
+        payload::PayloadDebugApiServer,
         reth::RethApiServer,
         rpc::RpcApiServer,
         trace::TraceApiServer,
@@ -72,6 +80,7 @@ pub mod clients {
         mev::MevApiClient,
         net::NetApiClient,
         otterscan::OtterscanClient,
+        payload::PayloadDebugApiClient,
         rpc::RpcApiServer,
         trace::TraceApiClient,
         txpool::TxPoolApiClient,
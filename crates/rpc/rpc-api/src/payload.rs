@@ -0,0 +1,89 @@
+//! API for introspecting the payload jobs of the payload builder.
+
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_primitives::{B256, U256, U64};
+use reth_rpc_types::engine::{ExecutionPayload, PayloadId};
+use serde::{Deserialize, Serialize};
+
+/// Summary of an active payload job.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadJobSummary {
+    /// The identifier of the payload.
+    pub payload_id: PayloadId,
+    /// The hash of the parent block the payload is built on.
+    pub parent_hash: B256,
+    /// The timestamp of the payload.
+    pub timestamp: U64,
+    /// Milliseconds since the job was created.
+    pub age_ms: U64,
+    /// Number of finished build attempts of the job.
+    pub iterations: U64,
+    /// The most recent finished build attempt of the job.
+    pub last_iteration: Option<PayloadBuildIterationSummary>,
+    /// The block hash of the best payload built so far.
+    pub best_block_hash: Option<B256>,
+    /// The fees of the best payload built so far.
+    pub best_fees: Option<U256>,
+}
+
+/// Summary of a finished build attempt of a payload job.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadBuildIterationSummary {
+    /// Milliseconds the attempt took.
+    pub elapsed_ms: U64,
+    /// The outcome of the attempt.
+    pub status: PayloadBuildIterationStatus,
+    /// Number of transactions in the payload, if it was better than the best payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<U64>,
+    /// Gas used by the payload, if it was better than the best payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<U64>,
+    /// Total fees of the payload, if one was built.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fees: Option<U256>,
+    /// The error of the attempt, if it failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The outcome of a build attempt of a payload job.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PayloadBuildIterationStatus {
+    /// Built a payload that is better than the best payload so far.
+    Better,
+    /// Built a payload that was discarded because it was not better than the best payload.
+    Aborted,
+    /// The attempt was cancelled because its job was resolved or terminated.
+    Cancelled,
+    /// The attempt failed.
+    Failed,
+}
+
+/// The best payload an active payload job built so far.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugBuiltPayload {
+    /// Total fees of the payload.
+    pub fees: U256,
+    /// The payload.
+    pub execution_payload: ExecutionPayload,
+}
+
+/// Payload job introspection rpc interface.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "debug"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "debug"))]
+pub trait PayloadDebugApi {
+    /// Returns all active payload jobs of the payload builder.
+    #[method(name = "payloadJobs")]
+    async fn payload_jobs(&self) -> RpcResult<Vec<PayloadJobSummary>>;
+
+    /// Returns the best payload the active payload job with the given id built so far.
+    ///
+    /// Unlike `engine_getPayload`, this does not resolve the job.
+    #[method(name = "getBuiltPayload")]
+    async fn built_payload(&self, payload_id: PayloadId) -> RpcResult<Option<DebugBuiltPayload>>;
+}
@@ -1,6 +1,48 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use reth_primitives::Address;
-use reth_rpc_types::txpool::{TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolStatus};
+use reth_primitives::{Address, TxHash};
+use reth_rpc_types::{
+    txpool::{TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolSizeByOrigin, TxpoolStatus},
+    Transaction,
+};
+use reth_transaction_pool::{validate::TransactionSimulation, SubPool};
+use serde::{Deserialize, Serialize};
+
+/// Filter for `txpool_subscribe`.
+///
+/// An empty list matches everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxpoolSubscriptionFilter {
+    /// Only emit events of transactions from these senders.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub senders: Vec<Address>,
+    /// Only emit events of transactions that were last seen in one of these subpools.
+    ///
+    /// Pool events only distinguish pending from queued transactions, so transactions parked in
+    /// the [`BaseFee`](SubPool::BaseFee) or [`Blob`](SubPool::Blob) subpool are reported as
+    /// [`Queued`](SubPool::Queued).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subpools: Vec<SubPool>,
+}
+
+impl TxpoolSubscriptionFilter {
+    /// Returns `true` if the filter matches every event.
+    pub fn is_empty(&self) -> bool {
+        self.senders.is_empty() && self.subpools.is_empty()
+    }
+
+    /// Returns `true` if a transaction of the given sender that was last seen in the given
+    /// subpool matches the filter.
+    ///
+    /// Unknown values only match if the filter doesn't restrict them.
+    pub fn matches(&self, sender: Option<&Address>, subpool: Option<SubPool>) -> bool {
+        let sender_matches =
+            self.senders.is_empty() || sender.is_some_and(|sender| self.senders.contains(sender));
+        let subpool_matches = self.subpools.is_empty() ||
+            subpool.is_some_and(|subpool| self.subpools.contains(&subpool));
+        sender_matches && subpool_matches
+    }
+}
 
 /// Txpool rpc interface.
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "txpool"))]
@@ -33,4 +75,39 @@ pub trait TxPoolApi {
     /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_content) for more details
     #[method(name = "content")]
     async fn txpool_content(&self) -> RpcResult<TxpoolContent>;
+
+    /// Returns the transaction of the given sender with the given nonce, if it is in the pool.
+    #[method(name = "getBySenderAndNonce")]
+    async fn txpool_get_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> RpcResult<Option<Transaction>>;
+
+    /// Returns the outcome of simulating the transaction with the given hash against the latest
+    /// state when it entered the pool, if it is in the pool and was simulated.
+    ///
+    /// Block builders try transactions that are expected to revert or to use far less gas than
+    /// their limit after all other transactions.
+    #[method(name = "getSimulation")]
+    async fn txpool_get_simulation(&self, hash: TxHash)
+        -> RpcResult<Option<TransactionSimulation>>;
+
+    /// Returns the number of transactions in the pool by the origin they were submitted from.
+    #[method(name = "sizeByOrigin")]
+    async fn txpool_size_by_origin(&self) -> RpcResult<TxpoolSizeByOrigin>;
+
+    /// Creates a subscription that emits an event whenever a transaction is added to, moved
+    /// within or removed from the pool.
+    ///
+    /// The optional filter restricts the events to certain senders or subpools.
+    #[subscription(
+        name = "subscribe" => "subscription",
+        unsubscribe = "unsubscribe",
+        item = reth_rpc_types::txpool::TxpoolEvent
+    )]
+    async fn txpool_subscribe(
+        &self,
+        filter: Option<TxpoolSubscriptionFilter>,
+    ) -> jsonrpsee::core::SubscriptionResult;
 }
@@ -2,9 +2,11 @@ use std::{net::SocketAddr, path::PathBuf};
 
 use jsonrpsee::server::ServerBuilder;
 use reth_node_core::{args::RpcServerArgs, utils::get_or_create_jwt_secret_from_path};
+use reth_rpc::ValidationApiConfig;
 use reth_rpc_eth_types::{EthConfig, EthStateCacheConfig, GasPriceOracleConfig};
 use reth_rpc_layer::{JwtError, JwtSecret};
 use reth_rpc_server_types::RpcModuleSelection;
+use reth_transaction_pool::rate_limit::RateLimitConfig;
 use tower::layer::util::Identity;
 use tracing::debug;
 
@@ -27,6 +29,9 @@ pub trait RethRpcServerConfig {
     /// The configured ethereum RPC settings.
     fn eth_config(&self) -> EthConfig;
 
+    /// The configured settings of the `flashbots` block submission validation.
+    fn validation_api_config(&self) -> ValidationApiConfig;
+
     /// Returns state cache configuration.
     fn state_cache_config(&self) -> EthStateCacheConfig;
 
@@ -100,6 +105,13 @@ impl RethRpcServerConfig for RpcServerArgs {
             .proof_permits(self.rpc_proof_permits)
     }
 
+    fn validation_api_config(&self) -> ValidationApiConfig {
+        ValidationApiConfig {
+            disallow: self.builder_disallow.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
     fn state_cache_config(&self) -> EthStateCacheConfig {
         EthStateCacheConfig {
             max_blocks: self.rpc_state_cache.max_blocks,
@@ -165,7 +177,10 @@ impl RethRpcServerConfig for RpcServerArgs {
     }
 
     fn rpc_server_config(&self) -> RpcServerConfig {
-        let mut config = RpcServerConfig::default().with_jwt_secret(self.rpc_secret_key());
+        let mut config = RpcServerConfig::default()
+            .with_jwt_secret(self.rpc_secret_key())
+            .with_tx_rate_limit(self.rpc_tx_rate_limit.map(RateLimitConfig::per_second))
+            .with_tx_rate_limit_trust_forwarded_for(self.rpc_tx_rate_limit_trust_forwarded_for);
 
         if self.http {
             let socket_address = SocketAddr::new(self.http_addr, self.http_port);
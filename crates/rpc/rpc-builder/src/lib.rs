@@ -173,7 +173,9 @@ use reth_rpc_eth_api::{
 use reth_rpc_eth_types::{EthConfig, EthStateCache, EthSubscriptionIdProvider};
 use reth_rpc_layer::{AuthLayer, Claims, JwtAuthValidator, JwtSecret};
 use reth_tasks::{pool::BlockingTaskGuard, TaskSpawner, TokioTaskExecutor};
-use reth_transaction_pool::{noop::NoopTransactionPool, TransactionPool};
+use reth_transaction_pool::{
+    noop::NoopTransactionPool, rate_limit::RateLimitConfig, TransactionPool,
+};
 use serde::{Deserialize, Serialize};
 use tower::Layer;
 use tower_http::cors::CorsLayer;
@@ -210,6 +212,12 @@ pub use eth::EthHandlers;
 mod metrics;
 pub use metrics::{MeteredRequestFuture, RpcRequestMetricsService};
 
+/// Rate limiting of transaction submissions.
+pub mod rate_limit;
+use rate_limit::{
+    RpcClientKeyLayer, RpcServer, RpcTxRateLimit, TxRateLimitLayer, TxRateLimitService,
+};
+
 /// Convenience function for starting a server in one step.
 #[allow(clippy::too_many_arguments)]
 pub async fn launch<Provider, Pool, Network, Tasks, Events, EvmConfig, EthApi>(
@@ -1091,9 +1099,12 @@ where
                         .into_rpc()
                         .into(),
                         RethRpcModule::Web3 => Web3Api::new(self.network.clone()).into_rpc().into(),
-                        RethRpcModule::Txpool => {
-                            TxPoolApi::new(self.pool.clone()).into_rpc().into()
-                        }
+                        RethRpcModule::Txpool => TxPoolApi::with_spawner(
+                            self.pool.clone(),
+                            Box::new(self.executor.clone()),
+                        )
+                        .into_rpc()
+                        .into(),
                         RethRpcModule::Rpc => RPCApi::new(
                             namespaces
                                 .iter()
@@ -1108,6 +1119,10 @@ where
                                 .into_rpc()
                                 .into()
                         }
+                        // validating block submissions requires a block executor, which the
+                        // registry doesn't have, see
+                        // [`TransportRpcModules::merge_if_module_configured`]
+                        RethRpcModule::Flashbots => Methods::default(),
                     })
                     .clone()
             })
@@ -1146,6 +1161,10 @@ pub struct RpcServerConfig<RpcMiddleware = Identity> {
     ipc_endpoint: Option<String>,
     /// JWT secret for authentication
     jwt_secret: Option<JwtSecret>,
+    /// Transaction submission rate limit per client for http and ws
+    tx_rate_limit: Option<RateLimitConfig>,
+    /// Whether clients may be identified by forwarded headers for the transaction rate limit
+    tx_rate_limit_trust_forwarded_for: bool,
     /// Configurable RPC middleware
     rpc_middleware: RpcServiceBuilder<RpcMiddleware>,
 }
@@ -1165,6 +1184,8 @@ impl Default for RpcServerConfig<Identity> {
             ipc_server_config: None,
             ipc_endpoint: None,
             jwt_secret: None,
+            tx_rate_limit: None,
+            tx_rate_limit_trust_forwarded_for: false,
             rpc_middleware: RpcServiceBuilder::new(),
         }
     }
@@ -1228,6 +1249,8 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
             ipc_server_config: self.ipc_server_config,
             ipc_endpoint: self.ipc_endpoint,
             jwt_secret: self.jwt_secret,
+            tx_rate_limit: self.tx_rate_limit,
+            tx_rate_limit_trust_forwarded_for: self.tx_rate_limit_trust_forwarded_for,
             rpc_middleware,
         }
     }
@@ -1301,6 +1324,26 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
         self
     }
 
+    /// Configures the maximum rate at which a single client can submit transactions via the http
+    /// and ws servers.
+    ///
+    /// Clients are identified by the `sub` claim of their JWT, see [`Self::with_jwt_secret`], or
+    /// by the IP address of their connection.
+    pub const fn with_tx_rate_limit(mut self, limit: Option<RateLimitConfig>) -> Self {
+        self.tx_rate_limit = limit;
+        self
+    }
+
+    /// Configures whether clients without a JWT subject are identified by the `X-Forwarded-For`
+    /// or `X-Real-IP` headers for the transaction rate limit.
+    ///
+    /// This should only be enabled if the servers are behind a reverse proxy that sets these
+    /// headers, in which case all clients would otherwise share the limit of the proxy.
+    pub const fn with_tx_rate_limit_trust_forwarded_for(mut self, trust: bool) -> Self {
+        self.tx_rate_limit_trust_forwarded_for = trust;
+        self
+    }
+
     /// Returns true if any server is configured.
     ///
     /// If no server is configured, no server will be launched on [`RpcServerConfig::start`].
@@ -1335,6 +1378,16 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
         jwt_secret.map(|secret| AuthLayer::new(JwtAuthValidator::new(secret)))
     }
 
+    /// Creates the [`RpcClientKeyLayer`] if a transaction rate limit is configured
+    fn maybe_client_key_layer(&self) -> Option<RpcClientKeyLayer> {
+        self.tx_rate_limit.map(|_| {
+            RpcClientKeyLayer::new(
+                self.jwt_secret.is_some(),
+                self.tx_rate_limit_trust_forwarded_for,
+            )
+        })
+    }
+
     /// Builds and starts the configured server(s): http, ws, ipc.
     ///
     /// If both http and ws are on the same port, they are combined into one server.
@@ -1342,8 +1395,11 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
     /// Returns the [`RpcServerHandle`] with the handle to the started servers.
     pub async fn start(self, modules: &TransportRpcModules) -> Result<RpcServerHandle, RpcError>
     where
-        RpcMiddleware: Layer<RpcRequestMetricsService<RpcService>> + Clone + Send + 'static,
-        for<'a> <RpcMiddleware as Layer<RpcRequestMetricsService<RpcService>>>::Service:
+        RpcMiddleware: Layer<RpcRequestMetricsService<TxRateLimitService<RpcService>>>
+            + Clone
+            + Send
+            + 'static,
+        for<'a> <RpcMiddleware as Layer<RpcRequestMetricsService<TxRateLimitService<RpcService>>>>::Service:
             Send + Sync + 'static + RpcServiceT<'a>,
     {
         let mut http_handle = None;
@@ -1360,6 +1416,10 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
             constants::DEFAULT_WS_RPC_PORT,
         )));
 
+        // the limit is shared by all http and ws servers
+        let tx_rate_limit = self.tx_rate_limit.map(RpcTxRateLimit::new);
+        let client_key_layer = self.maybe_client_key_layer();
+
         let metrics = modules.ipc.as_ref().map(RpcRequestMetrics::ipc).unwrap_or_default();
         let ipc_path =
             self.ipc_endpoint.clone().unwrap_or_else(|| constants::DEFAULT_IPC_ENDPOINT.into());
@@ -1399,23 +1459,25 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
                     .set_http_middleware(
                         tower::ServiceBuilder::new()
                             .option_layer(Self::maybe_cors_layer(cors)?)
-                            .option_layer(Self::maybe_jwt_layer(self.jwt_secret)),
+                            .option_layer(Self::maybe_jwt_layer(self.jwt_secret))
+                            .option_layer(client_key_layer),
                     )
                     .set_rpc_middleware(
-                        self.rpc_middleware.clone().layer(
-                            modules
-                                .http
-                                .as_ref()
-                                .or(modules.ws.as_ref())
-                                .map(RpcRequestMetrics::same_port)
-                                .unwrap_or_default(),
-                        ),
-                    )
-                    .build(http_socket_addr)
-                    .await
-                    .map_err(|err| {
-                        RpcError::server_error(err, ServerKind::WsHttp(http_socket_addr))
-                    })?;
+                        self.rpc_middleware
+                            .clone()
+                            .layer(
+                                modules
+                                    .http
+                                    .as_ref()
+                                    .or(modules.ws.as_ref())
+                                    .map(RpcRequestMetrics::same_port)
+                                    .unwrap_or_default(),
+                            )
+                            .layer(TxRateLimitLayer::new(tx_rate_limit.as_ref())),
+                    );
+                let server = RpcServer::bind(server, http_socket_addr).await.map_err(|err| {
+                    RpcError::server_error(err, ServerKind::WsHttp(http_socket_addr))
+                })?;
                 let addr = server.local_addr().map_err(|err| {
                     RpcError::server_error(err, ServerKind::WsHttp(http_socket_addr))
                 })?;
@@ -1447,14 +1509,16 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
                 .set_http_middleware(
                     tower::ServiceBuilder::new()
                         .option_layer(Self::maybe_cors_layer(self.ws_cors_domains.clone())?)
-                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret)),
+                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret))
+                        .option_layer(client_key_layer),
                 )
                 .set_rpc_middleware(
                     self.rpc_middleware
                         .clone()
-                        .layer(modules.ws.as_ref().map(RpcRequestMetrics::ws).unwrap_or_default()),
-                )
-                .build(ws_socket_addr)
+                        .layer(modules.ws.as_ref().map(RpcRequestMetrics::ws).unwrap_or_default())
+                        .layer(TxRateLimitLayer::new(tx_rate_limit.as_ref())),
+                );
+            let server = RpcServer::bind(server, ws_socket_addr)
                 .await
                 .map_err(|err| RpcError::server_error(err, ServerKind::WS(ws_socket_addr)))?;
 
@@ -1472,14 +1536,18 @@ impl<RpcMiddleware> RpcServerConfig<RpcMiddleware> {
                 .set_http_middleware(
                     tower::ServiceBuilder::new()
                         .option_layer(Self::maybe_cors_layer(self.http_cors_domains.clone())?)
-                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret)),
+                        .option_layer(Self::maybe_jwt_layer(self.jwt_secret))
+                        .option_layer(client_key_layer),
                 )
                 .set_rpc_middleware(
-                    self.rpc_middleware.clone().layer(
-                        modules.http.as_ref().map(RpcRequestMetrics::http).unwrap_or_default(),
-                    ),
-                )
-                .build(http_socket_addr)
+                    self.rpc_middleware
+                        .clone()
+                        .layer(
+                            modules.http.as_ref().map(RpcRequestMetrics::http).unwrap_or_default(),
+                        )
+                        .layer(TxRateLimitLayer::new(tx_rate_limit.as_ref())),
+                );
+            let server = RpcServer::bind(server, http_socket_addr)
                 .await
                 .map_err(|err| RpcError::server_error(err, ServerKind::Http(http_socket_addr)))?;
             let local_addr = server
@@ -1710,6 +1778,30 @@ impl TransportRpcModules {
         Ok(())
     }
 
+    /// Merge the given [Methods] in the transports that are configured with the given module.
+    ///
+    /// This is used to install modules that can't be created by the [`RpcRegistryInner`], like
+    /// [`RethRpcModule::Flashbots`].
+    ///
+    /// Fails if any of the methods in other is present already.
+    pub fn merge_if_module_configured(
+        &mut self,
+        module: RethRpcModule,
+        other: impl Into<Methods>,
+    ) -> Result<(), RegisterMethodError> {
+        let other = other.into();
+        if self.config.http().is_some_and(|http| http.contains(&module)) {
+            self.merge_http(other.clone())?;
+        }
+        if self.config.ws().is_some_and(|ws| ws.contains(&module)) {
+            self.merge_ws(other.clone())?;
+        }
+        if self.config.ipc().is_some_and(|ipc| ipc.contains(&module)) {
+            self.merge_ipc(other)?;
+        }
+        Ok(())
+    }
+
     /// Removes the method with the given name from the configured http methods.
     ///
     /// Returns `true` if the method was found and removed, `false` otherwise.
@@ -1964,6 +2056,7 @@ mod tests {
                 "rpc" => RethRpcModule::Rpc,
                 "ots" => RethRpcModule::Ots,
                 "reth" => RethRpcModule::Reth,
+                "flashbots" => RethRpcModule::Flashbots,
             );
     }
 
@@ -0,0 +1,406 @@
+//! Rate limiting of transaction submissions over RPC.
+//!
+//! Transaction submissions are limited per client. The client is identified by an
+//! [`RpcClientKeyLayer`] on the HTTP level that stores a [`RpcClientKey`] in the request
+//! extensions, which are then available to the [`TxRateLimitService`] on the RPC level.
+//!
+//! The servers are started with an [`RpcServer`], which stores the [`RemoteAddr`] of each
+//! connection in the request extensions, because the jsonrpsee server does not expose it.
+
+use http::{header, HeaderMap};
+use jsonrpsee::{
+    core::BoxError,
+    server::{
+        middleware::rpc::RpcServiceT, serve_with_graceful_shutdown, stop_channel, HttpBody,
+        HttpRequest, HttpResponse, Methods, ServerBuilder, ServerHandle, TowerService,
+        TowerServiceBuilder,
+    },
+    types::{ErrorObject, Request},
+    MethodResponse,
+};
+use reth_transaction_pool::rate_limit::{KeyedRateLimiter, RateLimitConfig};
+use std::{
+    future::Future,
+    io,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::net::TcpListener;
+use tower::{Layer, Service};
+use tracing::debug;
+
+/// The error code returned if a client exceeds its transaction rate limit.
+pub const TX_RATE_LIMIT_EXCEEDED_CODE: i32 = -32005;
+
+/// RPC methods that submit transactions to the pool and are subject to the rate limit.
+const RATE_LIMITED_METHODS: [&str; 3] =
+    ["eth_sendRawTransaction", "eth_sendRawTransactionConditional", "eth_sendTransaction"];
+
+/// Identifies the client of an RPC request for rate limiting.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RpcClientKey {
+    /// The `sub` claim of the JWT the request was authenticated with.
+    JwtSubject(String),
+    /// The IP address of the client.
+    Ip(IpAddr),
+    /// The client could not be identified, all such clients share the same limit.
+    Anonymous,
+}
+
+impl RpcClientKey {
+    /// Derives the client key from the headers of a request and the remote address of its
+    /// connection.
+    ///
+    /// The `sub` claim of a bearer token is only considered if `jwt_auth` is set, in which case the
+    /// token was already validated by the auth layer. The `X-Forwarded-For` and `X-Real-IP`
+    /// headers are only considered if `trust_forwarded_for` is set, and take precedence over the
+    /// remote address, which is the address of the proxy in that case.
+    pub fn new(
+        headers: &HeaderMap,
+        remote_addr: Option<RemoteAddr>,
+        jwt_auth: bool,
+        trust_forwarded_for: bool,
+    ) -> Self {
+        if jwt_auth {
+            if let Some(sub) = jwt_subject(headers) {
+                return Self::JwtSubject(sub)
+            }
+        }
+        if trust_forwarded_for {
+            if let Some(ip) = forwarded_ip(headers) {
+                return Self::Ip(ip)
+            }
+        }
+        remote_addr.map_or(Self::Anonymous, |addr| Self::Ip(addr.0.ip()))
+    }
+}
+
+/// The remote address of the connection a request was received on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteAddr(pub SocketAddr);
+
+/// Returns the `sub` claim of the bearer token in the `Authorization` header, if any.
+fn jwt_subject(headers: &HeaderMap) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct Subject {
+        sub: Option<String>,
+    }
+
+    let token = headers.get(header::AUTHORIZATION)?.to_str().ok()?.strip_prefix("Bearer ")?;
+
+    // the signature and the claims are checked by the auth layer
+    let mut validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256);
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.required_spec_claims.clear();
+
+    jsonwebtoken::decode::<Subject>(
+        token,
+        &jsonwebtoken::DecodingKey::from_secret(&[]),
+        &validation,
+    )
+    .ok()?
+    .claims
+    .sub
+}
+
+/// Returns the client IP reported by a reverse proxy, if any.
+fn forwarded_ip(headers: &HeaderMap) -> Option<IpAddr> {
+    let forwarded_for = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next());
+    let real_ip = headers.get("x-real-ip").and_then(|value| value.to_str().ok());
+    forwarded_for.or(real_ip).and_then(|ip| ip.trim().parse().ok())
+}
+
+/// A shared transaction rate limit for all RPC servers.
+#[derive(Debug, Clone)]
+pub(crate) struct RpcTxRateLimit {
+    limiter: Arc<KeyedRateLimiter<RpcClientKey>>,
+}
+
+impl RpcTxRateLimit {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        Self { limiter: Arc::new(KeyedRateLimiter::new(config)) }
+    }
+}
+
+/// A HTTP [`Layer`] that stores the [`RpcClientKey`] of a request in its extensions.
+#[derive(Debug, Clone, Copy)]
+pub struct RpcClientKeyLayer {
+    jwt_auth: bool,
+    trust_forwarded_for: bool,
+}
+
+impl RpcClientKeyLayer {
+    /// Creates a new layer, see [`RpcClientKey::new`].
+    pub const fn new(jwt_auth: bool, trust_forwarded_for: bool) -> Self {
+        Self { jwt_auth, trust_forwarded_for }
+    }
+}
+
+impl<S> Layer<S> for RpcClientKeyLayer {
+    type Service = RpcClientKeyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcClientKeyService { layer: *self, inner }
+    }
+}
+
+/// A HTTP [`Service`] that stores the [`RpcClientKey`] of a request in its extensions.
+#[derive(Debug, Clone)]
+pub struct RpcClientKeyService<S> {
+    layer: RpcClientKeyLayer,
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for RpcClientKeyService<S>
+where
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        let key = RpcClientKey::new(
+            req.headers(),
+            req.extensions().get::<RemoteAddr>().copied(),
+            self.layer.jwt_auth,
+            self.layer.trust_forwarded_for,
+        );
+        req.extensions_mut().insert(key);
+        self.inner.call(req)
+    }
+}
+
+/// A http and ws server that stores the [`RemoteAddr`] of each connection in the extensions of its
+/// requests.
+#[derive(Debug)]
+pub(crate) struct RpcServer<RpcMiddleware, HttpMiddleware> {
+    listener: TcpListener,
+    builder: TowerServiceBuilder<RpcMiddleware, HttpMiddleware>,
+}
+
+impl<RpcMiddleware, HttpMiddleware> RpcServer<RpcMiddleware, HttpMiddleware>
+where
+    TowerService<RpcMiddleware, HttpMiddleware>:
+        Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Clone + Send + 'static,
+    <TowerService<RpcMiddleware, HttpMiddleware> as Service<HttpRequest>>::Future: Send,
+    RpcMiddleware: Clone + Send + 'static,
+    HttpMiddleware: Clone + Send + 'static,
+{
+    /// Binds the server configured by the given builder to the given address.
+    pub(crate) async fn bind(
+        builder: ServerBuilder<HttpMiddleware, RpcMiddleware>,
+        addr: SocketAddr,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self { listener, builder: builder.to_service_builder() })
+    }
+
+    /// Returns the address the server is bound to.
+    pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Starts serving the given methods until the returned [`ServerHandle`] is stopped.
+    pub(crate) fn start(self, methods: impl Into<Methods>) -> ServerHandle {
+        let Self { listener, builder } = self;
+        let methods = methods.into();
+        let (stop_handle, server_handle) = stop_channel();
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, remote_addr) = tokio::select! {
+                    res = listener.accept() => match res {
+                        Ok(conn) => conn,
+                        Err(err) => {
+                            debug!(target: "rpc", %err, "Failed to accept connection");
+                            continue
+                        }
+                    },
+                    _ = stop_handle.clone().shutdown() => break,
+                };
+
+                let service = RemoteAddrService {
+                    remote_addr: RemoteAddr(remote_addr),
+                    inner: builder.clone().build(methods.clone(), stop_handle.clone()),
+                };
+                tokio::spawn(serve_with_graceful_shutdown(
+                    stream,
+                    service,
+                    stop_handle.clone().shutdown(),
+                ));
+            }
+        });
+
+        server_handle
+    }
+}
+
+/// A HTTP [`Service`] that stores the [`RemoteAddr`] of the connection in the request extensions.
+#[derive(Debug, Clone)]
+struct RemoteAddrService<S> {
+    remote_addr: RemoteAddr,
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for RemoteAddrService<S>
+where
+    S: Service<HttpRequest>,
+    B: http_body::Body<Data = bytes::Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        req.extensions_mut().insert(self.remote_addr);
+        self.inner.call(req.map(HttpBody::new))
+    }
+}
+
+/// A RPC [`Layer`] that rejects transaction submissions of clients that exceed the rate limit.
+///
+/// If no limit is configured, all requests are passed through.
+#[derive(Debug, Clone, Default)]
+pub struct TxRateLimitLayer {
+    limiter: Option<Arc<KeyedRateLimiter<RpcClientKey>>>,
+}
+
+impl TxRateLimitLayer {
+    pub(crate) fn new(limit: Option<&RpcTxRateLimit>) -> Self {
+        Self { limiter: limit.map(|limit| limit.limiter.clone()) }
+    }
+}
+
+impl<S> Layer<S> for TxRateLimitLayer {
+    type Service = TxRateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TxRateLimitService { limiter: self.limiter.clone(), inner }
+    }
+}
+
+/// A [`RpcServiceT`] middleware that rejects transaction submissions of clients that exceed the
+/// rate limit.
+#[derive(Debug, Clone)]
+pub struct TxRateLimitService<S> {
+    limiter: Option<Arc<KeyedRateLimiter<RpcClientKey>>>,
+    inner: S,
+}
+
+impl<'a, S> RpcServiceT<'a> for TxRateLimitService<S>
+where
+    S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
+{
+    type Future = TxRateLimitFuture<S::Future>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        if let Some(limiter) = &self.limiter {
+            if RATE_LIMITED_METHODS.contains(&req.method_name()) {
+                let key = req
+                    .extensions()
+                    .get::<RpcClientKey>()
+                    .cloned()
+                    .unwrap_or(RpcClientKey::Anonymous);
+                if !limiter.try_acquire(key) {
+                    let err = ErrorObject::owned(
+                        TX_RATE_LIMIT_EXCEEDED_CODE,
+                        "transaction rate limit exceeded",
+                        None::<()>,
+                    );
+                    return TxRateLimitFuture::Rejected {
+                        response: Some(MethodResponse::error(req.id, err)),
+                    }
+                }
+            }
+        }
+        TxRateLimitFuture::Call { fut: self.inner.call(req) }
+    }
+}
+
+/// Response future of the [`TxRateLimitService`].
+#[pin_project::pin_project(project = TxRateLimitFutureProj)]
+#[derive(Debug)]
+pub enum TxRateLimitFuture<F> {
+    /// The request was passed to the inner service.
+    Call {
+        /// The response future of the inner service.
+        #[pin]
+        fut: F,
+    },
+    /// The request was rejected.
+    Rejected {
+        /// The error response.
+        response: Option<MethodResponse>,
+    },
+}
+
+impl<F: Future<Output = MethodResponse>> Future for TxRateLimitFuture<F> {
+    type Output = MethodResponse;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            TxRateLimitFutureProj::Call { fut } => fut.poll(cx),
+            TxRateLimitFutureProj::Rejected { response } => {
+                Poll::Ready(response.take().expect("polled after completion"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn client_key_from_request() {
+        let remote_addr = Some(RemoteAddr("10.0.0.9:30303".parse().unwrap()));
+        let mut headers = HeaderMap::new();
+        assert_eq!(RpcClientKey::new(&headers, None, true, true), RpcClientKey::Anonymous);
+        assert_eq!(
+            RpcClientKey::new(&headers, remote_addr, true, true),
+            RpcClientKey::Ip("10.0.0.9".parse().unwrap())
+        );
+
+        headers.insert("x-forwarded-for", HeaderValue::from_static("10.0.0.1, 10.0.0.2"));
+        assert_eq!(
+            RpcClientKey::new(&headers, remote_addr, true, false),
+            RpcClientKey::Ip("10.0.0.9".parse().unwrap())
+        );
+        assert_eq!(
+            RpcClientKey::new(&headers, remote_addr, true, true),
+            RpcClientKey::Ip("10.0.0.1".parse().unwrap())
+        );
+
+        // {"alg":"HS256","typ":"JWT"}.{"sub":"alice","iat":1}
+        let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiJhbGljZSIsImlhdCI6MX0.sig";
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+        assert_eq!(
+            RpcClientKey::new(&headers, remote_addr, false, true),
+            RpcClientKey::Ip("10.0.0.1".parse().unwrap())
+        );
+        assert_eq!(
+            RpcClientKey::new(&headers, remote_addr, true, true),
+            RpcClientKey::JwtSubject("alice".to_string())
+        );
+    }
+}
@@ -1,14 +1,19 @@
 use crate::utils::{test_address, test_rpc_builder};
 use jsonrpsee::{
+    core::client::Error,
     server::{middleware::rpc::RpcServiceT, RpcServiceBuilder},
     types::Request,
     MethodResponse,
 };
+use reth_primitives::Bytes;
 use reth_rpc::EthApi;
-use reth_rpc_builder::{RpcServerConfig, TransportRpcModuleConfig};
+use reth_rpc_builder::{
+    rate_limit::TX_RATE_LIMIT_EXCEEDED_CODE, RpcServerConfig, TransportRpcModuleConfig,
+};
 use reth_rpc_eth_api::EthApiClient;
 use reth_rpc_server_types::RpcModuleSelection;
 use reth_rpc_types::{RichBlock, Transaction};
+use reth_transaction_pool::rate_limit::RateLimitConfig;
 use std::{
     future::Future,
     pin::Pin,
@@ -79,3 +84,32 @@ async fn test_rpc_middleware() {
     let count = mylayer.count.load(Ordering::Relaxed);
     assert_eq!(count, 1);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tx_rate_limit_by_remote_addr() {
+    let builder = test_rpc_builder();
+    let modules = builder.build(
+        TransportRpcModuleConfig::set_http(RpcModuleSelection::All),
+        Box::new(EthApi::with_spawner),
+    );
+
+    let handle = RpcServerConfig::http(Default::default())
+        .with_http_address(test_address())
+        .with_tx_rate_limit(Some(RateLimitConfig::per_second(1)))
+        .start(&modules)
+        .await
+        .unwrap();
+
+    let is_rate_limited = |res: Result<_, Error>| matches!(res, Err(Error::Call(err)) if err.code() == TX_RATE_LIMIT_EXCEEDED_CODE);
+
+    // the first submission of the client is handled, the second one exceeds its limit, even
+    // over a new connection
+    let client = handle.http_client().unwrap();
+    let res =
+        EthApiClient::<Transaction, RichBlock>::send_raw_transaction(&client, Bytes::new()).await;
+    assert!(!is_rate_limited(res));
+    let client = handle.http_client().unwrap();
+    let res =
+        EthApiClient::<Transaction, RichBlock>::send_raw_transaction(&client, Bytes::new()).await;
+    assert!(is_rate_limited(res));
+}
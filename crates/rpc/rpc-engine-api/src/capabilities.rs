@@ -19,6 +19,9 @@ pub const CAPABILITIES: &[&str] = &[
     "engine_getPayloadBodiesByRangeV1",
     "engine_getPayloadBodiesByHashV2",
     "engine_getPayloadBodiesByRangeV2",
+    "engine_getBlobsV1",
+    "engine_newInclusionListV1",
+    "engine_updatePayloadWithInclusionListV1",
 ];
 
 // The list of all supported Engine capabilities available over the engine endpoint.
@@ -4,29 +4,31 @@ use crate::{
 use async_trait::async_trait;
 use jsonrpsee_core::RpcResult;
 use reth_beacon_consensus::BeaconConsensusEngineHandle;
-use reth_chainspec::ChainSpec;
+use reth_chainspec::{ChainSpec, EthereumHardforks, ForkCondition};
 use reth_engine_primitives::EngineTypes;
 use reth_evm::provider::EvmEnvProvider;
 use reth_payload_builder::PayloadStore;
 use reth_payload_primitives::{
-    validate_payload_timestamp, EngineApiMessageVersion, PayloadAttributes,
-    PayloadBuilderAttributes, PayloadOrAttributes,
+    validate_payload_timestamp, EngineApiMessageVersion, EngineObjectValidationError,
+    PayloadAttributes, PayloadBuilderAttributes, PayloadOrAttributes,
 };
 use reth_primitives::{
-    Block, BlockHash, BlockHashOrNumber, BlockNumber, EthereumHardfork, B256, U64,
+    Block, BlockHash, BlockHashOrNumber, BlockNumber, Bytes, EthereumHardfork, TransactionSigned,
+    TransactionSignedEcRecovered, B256, U64,
 };
 use reth_rpc_api::EngineApiServer;
 use reth_rpc_types::engine::{
-    CancunPayloadFields, ClientVersionV1, ExecutionPayload, ExecutionPayloadBodiesV1,
-    ExecutionPayloadBodiesV2, ExecutionPayloadInputV2, ExecutionPayloadV1, ExecutionPayloadV3,
-    ExecutionPayloadV4, ForkchoiceState, ForkchoiceUpdated, PayloadId, PayloadStatus,
-    TransitionConfiguration,
+    BlobAndProofV1, CancunPayloadFields, ClientVersionV1, ExecutionPayload,
+    ExecutionPayloadBodiesV1, ExecutionPayloadBodiesV2, ExecutionPayloadInputV2,
+    ExecutionPayloadV1, ExecutionPayloadV3, ExecutionPayloadV4, ForkchoiceState, ForkchoiceUpdated,
+    PayloadId, PayloadStatus, TransitionConfiguration,
 };
 use reth_rpc_types_compat::engine::payload::{
     convert_payload_input_v2_to_payload, convert_to_payload_body_v1, convert_to_payload_body_v2,
 };
 use reth_storage_api::{BlockReader, HeaderProvider, StateProviderFactory};
 use reth_tasks::TaskSpawner;
+use reth_transaction_pool::{blobstore::BlobAndProof, TransactionPool};
 use std::{sync::Arc, time::Instant};
 use tokio::sync::oneshot;
 use tracing::{trace, warn};
@@ -37,13 +39,16 @@ pub type EngineApiSender<Ok> = oneshot::Sender<EngineApiResult<Ok>>;
 /// The upper limit for payload bodies request.
 const MAX_PAYLOAD_BODIES_LIMIT: u64 = 1024;
 
+/// The upper limit for blobs requested in `engine_getBlobsV1`.
+const MAX_BLOB_LIMIT: usize = 128;
+
 /// The Engine API implementation that grants the Consensus layer access to data and
 /// functions in the Execution layer that are crucial for the consensus process.
-pub struct EngineApi<Provider, EngineT: EngineTypes> {
-    inner: Arc<EngineApiInner<Provider, EngineT>>,
+pub struct EngineApi<Provider, EngineT: EngineTypes, Pool> {
+    inner: Arc<EngineApiInner<Provider, EngineT, Pool>>,
 }
 
-struct EngineApiInner<Provider, EngineT: EngineTypes> {
+struct EngineApiInner<Provider, EngineT: EngineTypes, Pool> {
     /// The provider to interact with the chain.
     provider: Provider,
     /// Consensus configuration
@@ -60,14 +65,18 @@ struct EngineApiInner<Provider, EngineT: EngineTypes> {
     client: ClientVersionV1,
     /// The list of all supported Engine capabilities available over the engine endpoint.
     capabilities: EngineCapabilities,
+    /// The transaction pool, used to serve blobs of pending transactions.
+    tx_pool: Pool,
 }
 
-impl<Provider, EngineT> EngineApi<Provider, EngineT>
+impl<Provider, EngineT, Pool> EngineApi<Provider, EngineT, Pool>
 where
     Provider: HeaderProvider + BlockReader + StateProviderFactory + EvmEnvProvider + 'static,
     EngineT: EngineTypes,
+    Pool: TransactionPool + 'static,
 {
     /// Create new instance of [`EngineApi`].
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         provider: Provider,
         chain_spec: Arc<ChainSpec>,
@@ -76,6 +85,7 @@ where
         task_spawner: Box<dyn TaskSpawner>,
         client: ClientVersionV1,
         capabilities: EngineCapabilities,
+        tx_pool: Pool,
     ) -> Self {
         let inner = Arc::new(EngineApiInner {
             provider,
@@ -86,6 +96,7 @@ where
             metrics: EngineApiMetrics::default(),
             client,
             capabilities,
+            tx_pool,
         });
         Self { inner }
     }
@@ -499,6 +510,68 @@ where
         self.get_payload_bodies_by_hash_with(hashes, convert_to_payload_body_v2)
     }
 
+    /// Returns the blobs and proofs for the given versioned hashes from the blob store of the
+    /// transaction pool.
+    ///
+    /// Blobs that are not available are returned as `None`.
+    pub fn get_blobs_v1(
+        &self,
+        versioned_hashes: Vec<B256>,
+    ) -> EngineApiResult<Vec<Option<BlobAndProofV1>>> {
+        if versioned_hashes.len() > MAX_BLOB_LIMIT {
+            return Err(EngineApiError::BlobRequestTooLarge { len: versioned_hashes.len() })
+        }
+
+        Ok(self
+            .inner
+            .tx_pool
+            .get_blobs_for_versioned_hashes(&versioned_hashes)
+            .map_err(|err| EngineApiError::Internal(Box::new(err)))?
+            .into_iter()
+            .map(|blob| blob.map(|BlobAndProof { blob, proof }| BlobAndProofV1 { blob, proof }))
+            .collect())
+    }
+
+    /// Stores the inclusion list for blocks building on the given parent.
+    ///
+    /// Returns an error if inclusion lists are not scheduled for this chain.
+    pub fn new_inclusion_list_v1(
+        &self,
+        parent_hash: B256,
+        inclusion_list: Vec<Bytes>,
+    ) -> EngineApiResult<()> {
+        if self.inner.chain_spec.fork(EthereumHardfork::Focil) == ForkCondition::Never {
+            return Err(EngineObjectValidationError::UnsupportedFork.into())
+        }
+
+        let transactions = decode_inclusion_list(inclusion_list)?;
+        self.inner.payload_store.inclusion_lists().insert(parent_hash, transactions);
+        Ok(())
+    }
+
+    /// Adds the inclusion list to the payload that is being built for the given payload id.
+    ///
+    /// The list applies to every payload building on the same parent, and is picked up by the
+    /// job on its next build attempt. Returns `None` if the payload is unknown.
+    pub async fn update_payload_with_inclusion_list_v1(
+        &self,
+        payload_id: PayloadId,
+        inclusion_list: Vec<Bytes>,
+    ) -> EngineApiResult<Option<PayloadId>> {
+        let Some(attributes) = self.inner.payload_store.payload_attributes(payload_id).await else {
+            return Ok(None)
+        };
+        let attributes = attributes?;
+
+        if !self.inner.chain_spec.is_focil_active_at_timestamp(attributes.timestamp()) {
+            return Err(EngineObjectValidationError::UnsupportedFork.into())
+        }
+
+        let transactions = decode_inclusion_list(inclusion_list)?;
+        self.inner.payload_store.inclusion_lists().insert(attributes.parent(), transactions);
+        Ok(Some(payload_id))
+    }
+
     /// Called to verify network configuration parameters and ensure that Consensus and Execution
     /// layers are using the latest configuration.
     pub fn exchange_transition_configuration(
@@ -609,10 +682,11 @@ where
 }
 
 #[async_trait]
-impl<Provider, EngineT> EngineApiServer<EngineT> for EngineApi<Provider, EngineT>
+impl<Provider, EngineT, Pool> EngineApiServer<EngineT> for EngineApi<Provider, EngineT, Pool>
 where
     Provider: HeaderProvider + BlockReader + StateProviderFactory + EvmEnvProvider + 'static,
     EngineT: EngineTypes,
+    Pool: TransactionPool + 'static,
 {
     /// Handler for `engine_newPayloadV1`
     /// See also <https://github.com/ethereum/execution-apis/blob/3d627c95a4d3510a8187dd02e0250ecb4331d27e/src/engine/paris.md#engine_newpayloadv1>
@@ -904,9 +978,69 @@ where
     async fn exchange_capabilities(&self, _capabilities: Vec<String>) -> RpcResult<Vec<String>> {
         Ok(self.inner.capabilities.list())
     }
+
+    /// Handler for `engine_getBlobsV1`
+    /// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/cancun.md#engine_getblobsv1>
+    async fn get_blobs_v1(
+        &self,
+        versioned_hashes: Vec<B256>,
+    ) -> RpcResult<Vec<Option<BlobAndProofV1>>> {
+        trace!(target: "rpc::engine", "Serving engine_getBlobsV1");
+        let start = Instant::now();
+        let res = Self::get_blobs_v1(self, versioned_hashes);
+        self.inner.metrics.latency.get_blobs_v1.record(start.elapsed());
+        Ok(res?)
+    }
+
+    /// Handler for `engine_newInclusionListV1`
+    async fn new_inclusion_list_v1(
+        &self,
+        parent_hash: B256,
+        inclusion_list: Vec<Bytes>,
+    ) -> RpcResult<()> {
+        trace!(target: "rpc::engine", %parent_hash, "Serving engine_newInclusionListV1");
+        let start = Instant::now();
+        let res = Self::new_inclusion_list_v1(self, parent_hash, inclusion_list);
+        self.inner.metrics.latency.new_inclusion_list_v1.record(start.elapsed());
+        Ok(res?)
+    }
+
+    /// Handler for `engine_updatePayloadWithInclusionListV1`
+    async fn update_payload_with_inclusion_list_v1(
+        &self,
+        payload_id: PayloadId,
+        inclusion_list: Vec<Bytes>,
+    ) -> RpcResult<Option<PayloadId>> {
+        trace!(target: "rpc::engine", %payload_id, "Serving engine_updatePayloadWithInclusionListV1");
+        let start = Instant::now();
+        let res =
+            Self::update_payload_with_inclusion_list_v1(self, payload_id, inclusion_list).await;
+        self.inner.metrics.latency.update_payload_with_inclusion_list_v1.record(start.elapsed());
+        Ok(res?)
+    }
 }
 
-impl<Provider, EngineT> std::fmt::Debug for EngineApi<Provider, EngineT>
+/// Decodes the EIP-2718 encoded transactions of an inclusion list and recovers their signers.
+///
+/// Blob transactions can't be part of an inclusion list since their sidecars are not propagated
+/// with it.
+fn decode_inclusion_list(
+    inclusion_list: Vec<Bytes>,
+) -> EngineApiResult<Vec<TransactionSignedEcRecovered>> {
+    inclusion_list
+        .into_iter()
+        .enumerate()
+        .map(|(index, bytes)| {
+            TransactionSigned::decode_enveloped(&mut bytes.as_ref())
+                .ok()
+                .filter(|tx| !tx.is_eip4844())
+                .and_then(TransactionSigned::into_ecrecovered)
+                .ok_or(EngineApiError::InvalidInclusionListTransaction { index })
+        })
+        .collect()
+}
+
+impl<Provider, EngineT, Pool> std::fmt::Debug for EngineApi<Provider, EngineT, Pool>
 where
     EngineT: EngineTypes,
 {
@@ -931,9 +1065,11 @@ mod tests {
     use reth_rpc_types_compat::engine::payload::execution_payload_from_sealed_block;
     use reth_tasks::TokioTaskExecutor;
     use reth_tokio_util::EventSender;
+    use reth_transaction_pool::noop::NoopTransactionPool;
     use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
 
-    fn setup_engine_api() -> (EngineApiTestHandle, EngineApi<Arc<MockEthProvider>, EthEngineTypes>)
+    fn setup_engine_api(
+    ) -> (EngineApiTestHandle, EngineApi<Arc<MockEthProvider>, EthEngineTypes, NoopTransactionPool>)
     {
         let client = ClientVersionV1 {
             code: ClientCode::RH,
@@ -956,6 +1092,7 @@ mod tests {
             task_executor,
             client,
             EngineCapabilities::default(),
+            NoopTransactionPool::default(),
         );
         let handle = EngineApiTestHandle { chain_spec, provider, from_api: engine_rx };
         (handle, api)
@@ -974,6 +1111,30 @@ mod tests {
         assert_eq!(res.unwrap(), vec![client]);
     }
 
+    #[tokio::test]
+    async fn get_blobs_v1_request_limit() {
+        let (_, api) = setup_engine_api();
+
+        let res = api.get_blobs_v1(vec![B256::random(); MAX_BLOB_LIMIT + 1]);
+        assert_matches!(res, Err(EngineApiError::BlobRequestTooLarge { .. }));
+
+        let res = api.get_blobs_v1(vec![B256::random(); 2]).unwrap();
+        assert_eq!(res, vec![None, None]);
+    }
+
+    #[tokio::test]
+    async fn new_inclusion_list_v1_requires_focil() {
+        let (_, api) = setup_engine_api();
+
+        let res = api.new_inclusion_list_v1(B256::random(), Vec::new());
+        assert_matches!(
+            res,
+            Err(EngineApiError::EngineObjectValidationError(
+                EngineObjectValidationError::UnsupportedFork
+            ))
+        );
+    }
+
     struct EngineApiTestHandle {
         chain_spec: Arc<ChainSpec>,
         provider: Arc<MockEthProvider>,
@@ -42,6 +42,19 @@ pub enum EngineApiError {
         /// The length that was requested.
         len: u64,
     },
+    /// The number of requested blobs is too large.
+    #[error("requested blob count too large: {len}")]
+    BlobRequestTooLarge {
+        /// The number of blobs that was requested.
+        len: usize,
+    },
+    /// A transaction of an inclusion list could not be decoded, is not signed properly or is a
+    /// blob transaction.
+    #[error("invalid inclusion list transaction at index {index}")]
+    InvalidInclusionListTransaction {
+        /// The index of the transaction in the inclusion list.
+        index: usize,
+    },
     /// Thrown if `engine_getPayloadBodiesByRangeV1` contains an invalid range
     #[error("invalid start ({start}) or count ({count})")]
     InvalidBodiesRange {
@@ -117,6 +130,7 @@ impl From<EngineApiError> for jsonrpsee_types::error::ErrorObject<'static> {
     fn from(error: EngineApiError) -> Self {
         match error {
             EngineApiError::InvalidBodiesRange { .. } |
+            EngineApiError::InvalidInclusionListTransaction { .. } |
             EngineApiError::EngineObjectValidationError(EngineObjectValidationError::Payload(
                 _,
             )) |
@@ -147,7 +161,8 @@ impl From<EngineApiError> for jsonrpsee_types::error::ErrorObject<'static> {
                 error.to_string(),
                 None::<()>,
             ),
-            EngineApiError::PayloadRequestTooLarge { .. } => {
+            EngineApiError::PayloadRequestTooLarge { .. } |
+            EngineApiError::BlobRequestTooLarge { .. } => {
                 jsonrpsee_types::error::ErrorObject::owned(
                     REQUEST_TOO_LARGE_CODE,
                     REQUEST_TOO_LARGE_MESSAGE,
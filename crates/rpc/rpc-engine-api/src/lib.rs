@@ -24,12 +24,16 @@ mod error;
 /// Engine API metrics.
 mod metrics;
 
+/// Payload job introspection API.
+mod payload_debug;
+
 pub use engine_api::{EngineApi, EngineApiSender};
 pub use error::*;
 pub use message::EngineApiMessageVersion;
+pub use payload_debug::PayloadDebugApi;
 
-// re-export server trait for convenience
-pub use reth_rpc_api::EngineApiServer;
+// re-export server traits for convenience
+pub use reth_rpc_api::{EngineApiServer, PayloadDebugApiServer};
 
 #[cfg(test)]
 #[allow(unused_imports)]
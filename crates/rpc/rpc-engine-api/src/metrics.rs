@@ -52,6 +52,12 @@ pub(crate) struct EngineApiLatencyMetrics {
     pub(crate) get_payload_bodies_by_hash_v2: Histogram,
     /// Latency for `engine_exchangeTransitionConfigurationV1`
     pub(crate) exchange_transition_configuration: Histogram,
+    /// Latency for `engine_getBlobsV1`
+    pub(crate) get_blobs_v1: Histogram,
+    /// Latency for `engine_newInclusionListV1`
+    pub(crate) new_inclusion_list_v1: Histogram,
+    /// Latency for `engine_updatePayloadWithInclusionListV1`
+    pub(crate) update_payload_with_inclusion_list_v1: Histogram,
 }
 
 /// Metrics for engine API forkchoiceUpdated responses.
@@ -0,0 +1,141 @@
+use crate::EngineApiError;
+use async_trait::async_trait;
+use jsonrpsee_core::RpcResult;
+use reth_engine_primitives::EngineTypes;
+use reth_payload_builder::{
+    error::PayloadBuilderError, PayloadBuildIteration, PayloadBuildOutcome, PayloadJobInfo,
+    PayloadStore,
+};
+use reth_payload_primitives::BuiltPayload;
+use reth_primitives::U64;
+use reth_rpc_api::{
+    DebugBuiltPayload, PayloadBuildIterationStatus, PayloadBuildIterationSummary,
+    PayloadDebugApiServer, PayloadJobSummary,
+};
+use reth_rpc_types::engine::PayloadId;
+use reth_rpc_types_compat::engine::payload::block_to_payload;
+use tracing::trace;
+
+/// Introspection of the payload jobs of the payload builder.
+///
+/// This is served next to the Engine API, so it's only reachable with the JWT secret of the
+/// node.
+#[derive(Debug)]
+pub struct PayloadDebugApi<EngineT: EngineTypes> {
+    /// Access to the payloads of the payload builder.
+    payload_store: PayloadStore<EngineT>,
+}
+
+impl<EngineT: EngineTypes> PayloadDebugApi<EngineT> {
+    /// Creates a new instance of `PayloadDebugApi`.
+    pub const fn new(payload_store: PayloadStore<EngineT>) -> Self {
+        Self { payload_store }
+    }
+}
+
+impl<EngineT: EngineTypes> Clone for PayloadDebugApi<EngineT> {
+    fn clone(&self) -> Self {
+        Self { payload_store: self.payload_store.clone() }
+    }
+}
+
+#[async_trait]
+impl<EngineT> PayloadDebugApiServer for PayloadDebugApi<EngineT>
+where
+    EngineT: EngineTypes,
+{
+    /// Handler for `debug_payloadJobs`
+    async fn payload_jobs(&self) -> RpcResult<Vec<PayloadJobSummary>> {
+        trace!(target: "rpc::engine", "Serving debug_payloadJobs");
+        let jobs = self
+            .payload_store
+            .payload_jobs()
+            .await
+            .ok_or(EngineApiError::GetPayloadError(PayloadBuilderError::ChannelClosed))?;
+        Ok(jobs.into_iter().map(job_summary).collect())
+    }
+
+    /// Handler for `debug_getBuiltPayload`
+    async fn built_payload(&self, payload_id: PayloadId) -> RpcResult<Option<DebugBuiltPayload>> {
+        trace!(target: "rpc::engine", %payload_id, "Serving debug_getBuiltPayload");
+        let Some(payload) = self.payload_store.best_payload(payload_id).await else {
+            return Ok(None)
+        };
+        let payload = payload.map_err(EngineApiError::GetPayloadError)?;
+        Ok(Some(DebugBuiltPayload {
+            fees: payload.fees(),
+            execution_payload: block_to_payload(payload.block().clone()),
+        }))
+    }
+}
+
+/// Converts the [`PayloadJobInfo`] of a job into its rpc representation.
+fn job_summary(job: PayloadJobInfo) -> PayloadJobSummary {
+    PayloadJobSummary {
+        payload_id: job.id,
+        parent_hash: job.parent,
+        timestamp: U64::from(job.timestamp),
+        age_ms: U64::from(job.age.as_millis() as u64),
+        iterations: U64::from(job.iterations),
+        last_iteration: job.last_iteration.map(iteration_summary),
+        best_block_hash: job.best_block_hash,
+        best_fees: job.best_fees,
+    }
+}
+
+/// Converts a [`PayloadBuildIteration`] into its rpc representation.
+fn iteration_summary(iteration: PayloadBuildIteration) -> PayloadBuildIterationSummary {
+    let mut summary = PayloadBuildIterationSummary {
+        elapsed_ms: U64::from(iteration.elapsed.as_millis() as u64),
+        status: PayloadBuildIterationStatus::Cancelled,
+        transactions: None,
+        gas_used: None,
+        fees: None,
+        error: None,
+    };
+    match iteration.outcome {
+        PayloadBuildOutcome::Better { transactions, gas_used, fees } => {
+            summary.status = PayloadBuildIterationStatus::Better;
+            summary.transactions = Some(U64::from(transactions));
+            summary.gas_used = Some(U64::from(gas_used));
+            summary.fees = Some(fees);
+        }
+        PayloadBuildOutcome::Aborted { fees } => {
+            summary.status = PayloadBuildIterationStatus::Aborted;
+            summary.fees = Some(fees);
+        }
+        PayloadBuildOutcome::Cancelled(_) => {}
+        PayloadBuildOutcome::Failed(error) => {
+            summary.status = PayloadBuildIterationStatus::Failed;
+            summary.error = Some(error);
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::U256;
+    use std::time::Duration;
+
+    #[test]
+    fn iteration_summary_of_better_payload() {
+        let iteration = PayloadBuildIteration {
+            id: PayloadId::new([1; 8]),
+            elapsed: Duration::from_millis(120),
+            outcome: PayloadBuildOutcome::Better {
+                transactions: 3,
+                gas_used: 63_000,
+                fees: U256::from(100),
+            },
+        };
+        let summary = iteration_summary(iteration);
+        assert_eq!(summary.status, PayloadBuildIterationStatus::Better);
+        assert_eq!(summary.elapsed_ms, U64::from(120));
+        assert_eq!(summary.transactions, Some(U64::from(3)));
+        assert_eq!(summary.gas_used, Some(U64::from(63_000)));
+        assert_eq!(summary.fees, Some(U256::from(100)));
+        assert_eq!(summary.error, None);
+    }
+}
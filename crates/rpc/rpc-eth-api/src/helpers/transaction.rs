@@ -22,7 +22,10 @@ use reth_rpc_types::{
     AnyTransactionReceipt, TransactionInfo, TransactionRequest, TypedTransactionRequest,
 };
 use reth_rpc_types_compat::transaction::from_recovered_with_block_context;
-use reth_transaction_pool::{PoolTransaction, TransactionOrigin, TransactionPool};
+use reth_transaction_pool::{
+    PoolTransaction, TransactionConditional, TransactionOrigin, TransactionPool,
+    ValidPoolTransaction,
+};
 
 use crate::{FromEthApiError, IntoEthApiError, RpcTransaction};
 
@@ -269,6 +272,49 @@ pub trait EthTransactions: LoadTransaction {
                 .add_transaction(TransactionOrigin::Local, pool_transaction)
                 .await
                 .map_err(Self::Error::from_eth_err)?;
+            debug_if_deprioritized(self.pool().get(&hash));
+
+            Ok(hash)
+        }
+    }
+
+    /// Decodes and recovers the transaction and submits it to the pool with the given
+    /// [`TransactionConditional`].
+    ///
+    /// Returns the hash of the transaction.
+    fn send_raw_transaction_conditional(
+        &self,
+        tx: Bytes,
+        conditional: TransactionConditional,
+    ) -> impl Future<Output = Result<B256, Self::Error>> + Send {
+        async move {
+            let recovered = recover_raw_transaction(tx.clone())?;
+            let pool_transaction =
+                <Self::Pool as TransactionPool>::Transaction::from_pooled(recovered);
+
+            // On optimism, conditional transactions are forwarded to the sequencer together with
+            // their conditions.
+            if let Some(client) = self.raw_tx_forwarder().as_ref() {
+                tracing::debug!(target: "rpc::eth", "forwarding raw conditional transaction");
+                let _ = client
+                    .forward_raw_transaction_conditional(&tx, &conditional)
+                    .await
+                    .inspect_err(|err| {
+                        tracing::debug!(target: "rpc::eth", %err, hash=% *pool_transaction.hash(), "failed to forward raw conditional transaction");
+                    });
+            }
+
+            // submit the transaction to the pool with a `Local` origin
+            let hash = self
+                .pool()
+                .add_conditional_transaction(
+                    TransactionOrigin::Local,
+                    pool_transaction,
+                    conditional,
+                )
+                .await
+                .map_err(Self::Error::from_eth_err)?;
+            debug_if_deprioritized(self.pool().get(&hash));
 
             Ok(hash)
         }
@@ -478,6 +524,7 @@ pub trait EthTransactions: LoadTransaction {
                 .add_transaction(TransactionOrigin::Local, pool_transaction)
                 .await
                 .map_err(Self::Error::from_eth_err)?;
+            debug_if_deprioritized(LoadTransaction::pool(self).get(&hash));
 
             Ok(hash)
         }
@@ -668,6 +715,16 @@ pub trait LoadTransaction: SpawnBlocking {
 pub trait RawTransactionForwarder: fmt::Debug + Send + Sync + 'static {
     /// Forwards raw transaction bytes for `eth_sendRawTransaction`
     async fn forward_raw_transaction(&self, raw: &[u8]) -> EthResult<()>;
+
+    /// Forwards raw transaction bytes and their conditions for
+    /// `eth_sendRawTransactionConditional`
+    async fn forward_raw_transaction_conditional(
+        &self,
+        _raw: &[u8],
+        _conditional: &TransactionConditional,
+    ) -> EthResult<()> {
+        Err(EthApiError::Unsupported("conditional transactions are not forwarded"))
+    }
 }
 
 /// Configure server's forwarder for `eth_sendRawTransaction`, at runtime.
@@ -687,3 +744,17 @@ where
         self.deref().deref().set_eth_raw_transaction_forwarder(forwarder);
     }
 }
+
+/// Logs if the simulation of a submitted transaction predicts that it reverts or uses far less gas
+/// than its limit, which makes block builders try it after all other transactions.
+///
+/// The sender can look up the outcome of the simulation with `txpool_getSimulation`.
+fn debug_if_deprioritized<T: PoolTransaction>(transaction: Option<Arc<ValidPoolTransaction<T>>>) {
+    let Some(transaction) = transaction else { return };
+    let Some(simulation) = transaction.simulation else { return };
+    if simulation.is_revert() {
+        tracing::debug!(target: "rpc::eth", hash=%transaction.hash(), status=?simulation.status, gas_used=simulation.gas_used, "submitted transaction is expected to revert");
+    } else if simulation.is_low_gas_usage() {
+        tracing::debug!(target: "rpc::eth", hash=%transaction.hash(), gas_used=simulation.gas_used, gas_limit=simulation.gas_limit, "submitted transaction is expected to use far less gas than its limit");
+    }
+}
@@ -11,9 +11,12 @@ use reth_rpc_server_types::result::{
 use reth_rpc_types::{
     error::EthRpcErrorCode, request::TransactionInputError, BlockError, ToRpcError,
 };
-use reth_transaction_pool::error::{
-    Eip4844PoolTransactionError, InvalidPoolTransactionError, PoolError, PoolErrorKind,
-    PoolTransactionError,
+use reth_transaction_pool::{
+    conditional::ConditionalError,
+    error::{
+        Eip4844PoolTransactionError, Eip7702PoolTransactionError, InvalidPoolTransactionError,
+        PoolError, PoolErrorKind, PoolTransactionError,
+    },
 };
 use revm::primitives::{EVMError, ExecutionResult, HaltReason, OutOfGasError};
 use revm_inspectors::tracing::MuxError;
@@ -596,12 +599,18 @@ pub enum RpcPoolError {
     /// Eip-4844 related error
     #[error(transparent)]
     Eip4844(#[from] Eip4844PoolTransactionError),
+    /// Eip-7702 related error
+    #[error(transparent)]
+    Eip7702(#[from] Eip7702PoolTransactionError),
     /// Thrown if a conflicting transaction type is already in the pool
     ///
     /// In other words, thrown if a transaction with the same sender that violates the exclusivity
     /// constraint (blob vs normal tx)
     #[error("address already reserved")]
     AddressAlreadyReserved,
+    /// Thrown if the conditions of a conditional transaction can't be met
+    #[error(transparent)]
+    ConditionalNotMet(#[from] ConditionalError),
     /// Other unspecified error
     #[error(transparent)]
     Other(Box<dyn std::error::Error + Send + Sync>),
@@ -611,6 +620,9 @@ impl From<RpcPoolError> for jsonrpsee_types::error::ErrorObject<'static> {
     fn from(error: RpcPoolError) -> Self {
         match error {
             RpcPoolError::Invalid(err) => err.into(),
+            RpcPoolError::ConditionalNotMet(err) => {
+                rpc_error_with_code(EthRpcErrorCode::TransactionRejected.code(), err.to_string())
+            }
             error => internal_rpc_err(error.to_string()),
         }
     }
@@ -626,6 +638,9 @@ impl From<PoolError> for RpcPoolError {
             }
             PoolErrorKind::InvalidTransaction(err) => err.into(),
             PoolErrorKind::Other(err) => Self::Other(err),
+            PoolErrorKind::PolicyViolation(err) => Self::Other(Box::new(err)),
+            PoolErrorKind::InvalidBundle(err) => Self::Other(Box::new(err)),
+            PoolErrorKind::ConditionalNotMet(err) => Self::ConditionalNotMet(err),
             PoolErrorKind::AlreadyImported => Self::AlreadyKnown,
             PoolErrorKind::ExistingConflictingTransactionType(_, _) => Self::AddressAlreadyReserved,
         }
@@ -647,6 +662,7 @@ impl From<InvalidPoolTransactionError> for RpcPoolError {
             InvalidPoolTransactionError::Underpriced => Self::Underpriced,
             InvalidPoolTransactionError::Other(err) => Self::PoolTransactionError(err),
             InvalidPoolTransactionError::Eip4844(err) => Self::Eip4844(err),
+            InvalidPoolTransactionError::Eip7702(err) => Self::Eip7702(err),
             InvalidPoolTransactionError::Overdraft => {
                 Self::Invalid(RpcInvalidTransactionError::InsufficientFunds)
             }
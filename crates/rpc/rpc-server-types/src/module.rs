@@ -98,6 +98,15 @@ impl RpcModuleSelection {
         }
     }
 
+    /// Returns true if the selection contains the given module.
+    pub fn contains(&self, module: &RethRpcModule) -> bool {
+        match self {
+            Self::All => true,
+            Self::Standard => Self::STANDARD_MODULES.contains(module),
+            Self::Selection(s) => s.contains(module),
+        }
+    }
+
     /// Returns an iterator over all configured [`RethRpcModule`]
     pub fn iter_selection(&self) -> Box<dyn Iterator<Item = RethRpcModule> + '_> {
         match self {
@@ -255,6 +264,8 @@ pub enum RethRpcModule {
     Reth,
     /// `ots_` module
     Ots,
+    /// `flashbots_` module
+    Flashbots,
 }
 
 // === impl RethRpcModule ===
@@ -303,6 +314,7 @@ impl FromStr for RethRpcModule {
             "rpc" => Self::Rpc,
             "reth" => Self::Reth,
             "ots" => Self::Ots,
+            "flashbots" => Self::Flashbots,
             _ => return Err(ParseError::VariantNotFound),
         })
     }
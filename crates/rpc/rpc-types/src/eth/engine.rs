@@ -0,0 +1,18 @@
+//! Engine API types, extending the ones provided by alloy.
+
+use alloy_primitives::FixedBytes;
+use serde::{Deserialize, Serialize};
+
+pub use alloy_rpc_types_engine::*;
+
+/// Size of a single blob in bytes.
+pub const BYTES_PER_BLOB: usize = 131_072;
+
+/// A blob together with its KZG proof, as returned by `engine_getBlobsV1`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobAndProofV1 {
+    /// The blob data.
+    pub blob: Box<FixedBytes<BYTES_PER_BLOB>>,
+    /// The KZG proof for the blob.
+    pub proof: FixedBytes<48>,
+}
@@ -1,8 +1,7 @@
 //! Ethereum related types
 
+#[cfg(feature = "jsonrpsee-types")]
+pub mod engine;
 pub(crate) mod error;
 pub mod transaction;
-
-// re-export
-#[cfg(feature = "jsonrpsee-types")]
-pub use alloy_rpc_types_engine as engine;
+pub(crate) mod txpool;
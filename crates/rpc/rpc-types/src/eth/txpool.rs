@@ -0,0 +1,57 @@
+//! Reth specific types for the `txpool_` namespace.
+
+use alloy_primitives::{Address, TxHash, B256};
+use serde::{Deserialize, Serialize};
+
+/// Number of transactions in the pool by the origin they were submitted from.
+///
+/// Returned by `txpool_sizeByOrigin`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolSizeByOrigin {
+    /// Transactions submitted locally, e.g. via `eth_sendRawTransaction`.
+    pub local: u64,
+    /// Transactions received from the network.
+    pub external: u64,
+    /// Local transactions that are not propagated to the network.
+    pub private: u64,
+}
+
+/// An event emitted by `txpool_subscribe`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxpoolEvent {
+    /// Hash of the transaction.
+    pub hash: TxHash,
+    /// Sender of the transaction, if it is known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<Address>,
+    /// What happened to the transaction.
+    #[serde(flatten)]
+    pub kind: TxpoolEventKind,
+}
+
+/// What happened to a transaction in the pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum TxpoolEventKind {
+    /// Transaction was added or promoted to the pending pool.
+    Pending,
+    /// Transaction was added to the queued pool.
+    Queued,
+    /// Transaction was replaced by another transaction with the same sender and nonce.
+    #[serde(rename_all = "camelCase")]
+    Replaced {
+        /// Hash of the replacement.
+        replaced_by: TxHash,
+    },
+    /// Transaction was dropped due to the pool's limits.
+    Discarded,
+    /// Transaction became invalid.
+    Invalid,
+    /// Transaction was included in a block.
+    #[serde(rename_all = "camelCase")]
+    Mined {
+        /// Hash of the block that includes the transaction.
+        block_hash: B256,
+    },
+}
@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use jsonrpsee::core::RpcResult;
 use reth_evm::{ConfigureEvm, ConfigureEvmEnv};
+use reth_payload_builder::SimulationCache;
 use reth_primitives::{
     keccak256,
     revm_primitives::db::{DatabaseCommit, DatabaseRef},
@@ -35,7 +36,29 @@ pub struct EthBundle<Eth> {
 impl<Eth> EthBundle<Eth> {
     /// Create a new `EthBundle` instance.
     pub fn new(eth_api: Eth, blocking_task_guard: BlockingTaskGuard) -> Self {
-        Self { inner: Arc::new(EthBundleInner { eth_api, blocking_task_guard }) }
+        Self {
+            inner: Arc::new(EthBundleInner {
+                eth_api,
+                blocking_task_guard,
+                simulation_cache: None,
+            }),
+        }
+    }
+
+    /// Reuses the transaction execution results of the given [`SimulationCache`] when simulating
+    /// bundles, and adds the results of simulated transactions to it.
+    pub fn with_simulation_cache(self, simulation_cache: SimulationCache) -> Self
+    where
+        Eth: Clone,
+    {
+        let EthBundleInner { eth_api, blocking_task_guard, .. } = &*self.inner;
+        Self {
+            inner: Arc::new(EthBundleInner {
+                eth_api: eth_api.clone(),
+                blocking_task_guard: blocking_task_guard.clone(),
+                simulation_cache: Some(simulation_cache),
+            }),
+        }
     }
 }
 
@@ -143,14 +166,14 @@ where
         block_env.number = U256::from(block_number);
 
         let eth_api = self.inner.eth_api.clone();
+        let simulation_cache = self.inner.simulation_cache.clone();
 
         self.inner
             .eth_api
             .spawn_with_state_at_block(at, move |state| {
                 let coinbase = block_env.coinbase;
                 let basefee = Some(block_env.basefee.to::<u64>());
-                let env = EnvWithHandlerCfg::new_with_cfg_env(cfg, block_env, TxEnv::default());
-                let db = CacheDB::new(StateProviderDatabase::new(state));
+                let mut db = CacheDB::new(StateProviderDatabase::new(state));
 
                 let initial_coinbase = DatabaseRef::basic_ref(&db, coinbase)
                     .map_err(Eth::Error::from_eth_err)?
@@ -162,7 +185,7 @@ where
                 let mut total_gas_fess = U256::ZERO;
                 let mut hash_bytes = Vec::with_capacity(32 * transactions.len());
 
-                let mut evm = Call::evm_config(&eth_api).evm_with_env(db, env);
+                let evm_config = Call::evm_config(&eth_api);
 
                 let mut results = Vec::with_capacity(transactions.len());
                 let mut transactions = transactions.into_iter().peekable();
@@ -183,9 +206,15 @@ where
                         .effective_tip_per_gas(basefee)
                         .ok_or_else(|| RpcInvalidTransactionError::FeeCapTooLow)
                         .map_err(Eth::Error::from_eth_err)?;
-                    Call::evm_config(&eth_api).fill_tx_env(evm.tx_mut(), &tx, signer);
-                    let ResultAndState { result, state } =
-                        evm.transact().map_err(Eth::Error::from_evm_err)?;
+                    let mut tx_env = TxEnv::default();
+                    evm_config.fill_tx_env(&mut tx_env, &tx, signer);
+                    let env =
+                        EnvWithHandlerCfg::new_with_cfg_env(cfg.clone(), block_env.clone(), tx_env);
+                    let ResultAndState { result, state } = match &simulation_cache {
+                        Some(cache) => cache.transact(evm_config, &mut db, env, tx.hash()),
+                        None => evm_config.evm_with_env(&mut db, env).transact(),
+                    }
+                    .map_err(Eth::Error::from_evm_err)?;
 
                     let gas_used = result.gas_used();
                     total_gas_used += gas_used;
@@ -231,7 +260,7 @@ where
                     if transactions.peek().is_some() {
                         // need to apply the state changes of this call before executing
                         // the next call
-                        evm.context.evm.db.commit(state)
+                        db.commit(state)
                     }
                 }
 
@@ -276,6 +305,8 @@ struct EthBundleInner<Eth> {
     // restrict the number of concurrent tracing calls.
     #[allow(dead_code)]
     blocking_task_guard: BlockingTaskGuard,
+    /// Cache of transaction execution results shared with the payload builder, if enabled.
+    simulation_cache: Option<SimulationCache>,
 }
 
 impl<Eth> std::fmt::Debug for EthBundle<Eth> {
@@ -42,6 +42,7 @@ mod reth;
 mod rpc;
 mod trace;
 mod txpool;
+mod validation;
 mod web3;
 pub use admin::AdminApi;
 pub use debug::DebugApi;
@@ -53,4 +54,5 @@ pub use reth::RethApi;
 pub use rpc::RPCApi;
 pub use trace::TraceApi;
 pub use txpool::TxPoolApi;
+pub use validation::{ValidationApi, ValidationApiConfig, ValidationApiError};
 pub use web3::Web3Api;
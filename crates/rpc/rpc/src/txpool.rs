@@ -1,13 +1,24 @@
 use async_trait::async_trait;
-use jsonrpsee::core::RpcResult as Result;
-use reth_primitives::{Address, TransactionSignedEcRecovered};
-use reth_rpc_api::TxPoolApiServer;
+use futures::StreamExt;
+use jsonrpsee::{
+    core::RpcResult as Result, server::SubscriptionMessage, PendingSubscriptionSink,
+    SubscriptionSink,
+};
+use reth_primitives::{Address, TransactionSignedEcRecovered, TxHash};
+use reth_rpc_api::{TxPoolApiServer, TxpoolSubscriptionFilter};
 use reth_rpc_types::{
-    txpool::{TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolInspectSummary, TxpoolStatus},
+    txpool::{
+        TxpoolContent, TxpoolContentFrom, TxpoolEvent, TxpoolEventKind, TxpoolInspect,
+        TxpoolInspectSummary, TxpoolSizeByOrigin, TxpoolStatus,
+    },
     Transaction,
 };
-use reth_transaction_pool::{AllPoolTransactions, PoolTransaction, TransactionPool};
-use std::collections::BTreeMap;
+use reth_tasks::TaskSpawner;
+use reth_transaction_pool::{
+    validate::TransactionSimulation, AllPoolTransactions, AllTransactionsEvents,
+    FullTransactionEvent, PoolTransaction, SubPool, TransactionOrigin, TransactionPool,
+};
+use std::collections::{BTreeMap, HashMap};
 use tracing::trace;
 
 /// `txpool` API implementation.
@@ -17,12 +28,21 @@ use tracing::trace;
 pub struct TxPoolApi<Pool> {
     /// An interface to interact with the pool
     pool: Pool,
+    /// The type that's used to spawn subscription tasks, if not [`tokio::task::spawn`].
+    subscription_task_spawner: Option<Box<dyn TaskSpawner>>,
 }
 
 impl<Pool> TxPoolApi<Pool> {
     /// Creates a new instance of `TxpoolApi`.
+    ///
+    /// Subscription tasks are spawned via [`tokio::task::spawn`]
     pub const fn new(pool: Pool) -> Self {
-        Self { pool }
+        Self { pool, subscription_task_spawner: None }
+    }
+
+    /// Creates a new instance of `TxpoolApi` that spawns subscription tasks on the given spawner.
+    pub const fn with_spawner(pool: Pool, subscription_task_spawner: Box<dyn TaskSpawner>) -> Self {
+        Self { pool, subscription_task_spawner: Some(subscription_task_spawner) }
     }
 }
 
@@ -132,6 +152,169 @@ where
         trace!(target: "rpc::eth", "Serving txpool_content");
         Ok(self.content())
     }
+
+    /// Handler for `txpool_getBySenderAndNonce`
+    async fn txpool_get_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> Result<Option<Transaction>> {
+        trace!(target: "rpc::eth", ?sender, nonce, "Serving txpool_getBySenderAndNonce");
+        Ok(self.pool.get_transaction_by_sender_and_nonce(sender, nonce).map(|tx| {
+            reth_rpc_types_compat::transaction::from_recovered(
+                tx.transaction.clone().into_consensus(),
+            )
+        }))
+    }
+
+    /// Handler for `txpool_getSimulation`
+    async fn txpool_get_simulation(&self, hash: TxHash) -> Result<Option<TransactionSimulation>> {
+        trace!(target: "rpc::eth", ?hash, "Serving txpool_getSimulation");
+        Ok(self.pool.get(&hash).and_then(|tx| tx.simulation))
+    }
+
+    /// Handler for `txpool_sizeByOrigin`
+    async fn txpool_size_by_origin(&self) -> Result<TxpoolSizeByOrigin> {
+        trace!(target: "rpc::eth", "Serving txpool_sizeByOrigin");
+        let size = |origin| self.pool.get_transactions_by_origin(origin).len() as u64;
+        Ok(TxpoolSizeByOrigin {
+            local: size(TransactionOrigin::Local),
+            external: size(TransactionOrigin::External),
+            private: size(TransactionOrigin::Private),
+        })
+    }
+
+    /// Handler for `txpool_subscribe`
+    async fn txpool_subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        filter: Option<TxpoolSubscriptionFilter>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        // subscribe before accepting so no events are missed
+        let events = self.pool.all_transactions_event_listener();
+        let sink = pending.accept().await?;
+        let pool = self.pool.clone();
+        let fut = async move {
+            let tracker = TxpoolEventTracker::new(pool, filter.unwrap_or_default());
+            tracker.pipe(sink, events).await;
+        };
+        match &self.subscription_task_spawner {
+            Some(spawner) => {
+                spawner.spawn(Box::pin(fut));
+            }
+            None => {
+                tokio::task::spawn(fut);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts the pool's [`FullTransactionEvent`]s into [`TxpoolEvent`]s and applies the
+/// subscription's filter.
+///
+/// Most pool events only carry the transaction hash, so this keeps track of the sender and the
+/// last subpool of every transaction it has seen until no more events are expected for it.
+#[derive(Debug)]
+struct TxpoolEventTracker<Pool> {
+    pool: Pool,
+    filter: TxpoolSubscriptionFilter,
+    /// Sender and last known subpool of all tracked transactions.
+    transactions: HashMap<TxHash, (Address, SubPool)>,
+}
+
+impl<Pool> TxpoolEventTracker<Pool>
+where
+    Pool: TransactionPool + 'static,
+{
+    fn new(pool: Pool, filter: TxpoolSubscriptionFilter) -> Self {
+        Self { pool, filter, transactions: Default::default() }
+    }
+
+    /// Forwards all matching events to the sink until either the sink or the event stream is
+    /// closed.
+    async fn pipe(
+        mut self,
+        sink: SubscriptionSink,
+        mut events: AllTransactionsEvents<Pool::Transaction>,
+    ) {
+        loop {
+            tokio::select! {
+                _ = sink.closed() => {
+                    // connection dropped
+                    break
+                },
+                maybe_event = events.next() => {
+                    let Some(event) = maybe_event else {
+                        // pool dropped
+                        break
+                    };
+                    let Some(event) = self.on_event(event) else { continue };
+                    let Ok(msg) = SubscriptionMessage::from_json(&event) else { break };
+                    if sink.send(msg).await.is_err() {
+                        break
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the [`TxpoolEvent`] for the pool event, if it matches the filter.
+    fn on_event(&mut self, event: FullTransactionEvent<Pool::Transaction>) -> Option<TxpoolEvent> {
+        let (hash, sender, kind) = match event {
+            FullTransactionEvent::Pending(hash) => {
+                return self.on_added(hash, SubPool::Pending, TxpoolEventKind::Pending)
+            }
+            FullTransactionEvent::Queued(hash) => {
+                return self.on_added(hash, SubPool::Queued, TxpoolEventKind::Queued)
+            }
+            FullTransactionEvent::Replaced { transaction, replaced_by } => (
+                *transaction.hash(),
+                Some(transaction.sender()),
+                TxpoolEventKind::Replaced { replaced_by },
+            ),
+            FullTransactionEvent::Mined { tx_hash, block_hash } => {
+                (tx_hash, None, TxpoolEventKind::Mined { block_hash })
+            }
+            FullTransactionEvent::Discarded(hash) => (hash, None, TxpoolEventKind::Discarded),
+            FullTransactionEvent::Invalid(hash) => (hash, None, TxpoolEventKind::Invalid),
+            FullTransactionEvent::Propagated(_) => return None,
+        };
+
+        // no more events are expected for this transaction
+        let (tracked_sender, subpool) = self.transactions.remove(&hash).unzip();
+        let sender = sender.or(tracked_sender);
+        self.filter.matches(sender.as_ref(), subpool).then_some(TxpoolEvent { hash, sender, kind })
+    }
+
+    /// Records that the transaction was added to the given subpool.
+    fn on_added(
+        &mut self,
+        hash: TxHash,
+        subpool: SubPool,
+        kind: TxpoolEventKind,
+    ) -> Option<TxpoolEvent> {
+        let sender = match self.transactions.get_mut(&hash) {
+            Some(tracked) => {
+                tracked.1 = subpool;
+                Some(tracked.0)
+            }
+            None => {
+                // the transaction may have already left the pool again
+                let sender = self.pool.get(&hash).map(|tx| tx.sender());
+                if let Some(sender) = sender {
+                    self.transactions.insert(hash, (sender, subpool));
+                }
+                sender
+            }
+        };
+        self.filter.matches(sender.as_ref(), Some(subpool)).then_some(TxpoolEvent {
+            hash,
+            sender,
+            kind,
+        })
+    }
 }
 
 impl<Pool> std::fmt::Debug for TxPoolApi<Pool> {
@@ -139,3 +322,97 @@ impl<Pool> std::fmt::Debug for TxPoolApi<Pool> {
         f.debug_struct("TxpoolApi").finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::U256;
+    use reth_transaction_pool::{
+        blobstore::InMemoryBlobStore,
+        test_utils::{testing_pool, MockOrdering, MockTransaction},
+        validate::{SimulationStatus, ValidTransaction},
+        Pool, TransactionValidationOutcome, TransactionValidator,
+    };
+
+    /// Validator that reports every transaction as reverting in its simulation.
+    #[derive(Debug, Clone)]
+    struct RevertingValidator;
+
+    impl TransactionValidator for RevertingValidator {
+        type Transaction = MockTransaction;
+
+        async fn validate_transaction(
+            &self,
+            _origin: TransactionOrigin,
+            transaction: Self::Transaction,
+        ) -> TransactionValidationOutcome<Self::Transaction> {
+            let simulation = TransactionSimulation {
+                status: SimulationStatus::Revert,
+                gas_used: 21_000,
+                gas_limit: transaction.gas_limit(),
+                block_number: 1,
+                low_gas_usage: false,
+            };
+            TransactionValidationOutcome::Valid {
+                balance: U256::MAX,
+                state_nonce: 0,
+                transaction: ValidTransaction::Valid(transaction),
+                propagate: true,
+                authorities: None,
+                simulation: Some(simulation),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn get_simulation() {
+        let pool = Pool::new(
+            RevertingValidator,
+            MockOrdering::default(),
+            InMemoryBlobStore::default(),
+            Default::default(),
+        );
+        let api = TxPoolApi::new(pool.clone());
+
+        let tx = MockTransaction::eip1559();
+        pool.add_external_transaction(tx.clone()).await.unwrap();
+
+        let simulation = api.txpool_get_simulation(tx.get_hash()).await.unwrap().unwrap();
+        assert_eq!(simulation.status, SimulationStatus::Revert);
+        assert!(api.txpool_get_simulation(TxHash::random()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn track_events_by_sender() {
+        let pool = testing_pool();
+        let mut events = pool.all_transactions_event_listener();
+
+        let ours = MockTransaction::eip1559();
+        let theirs = MockTransaction::eip1559();
+        let filter =
+            TxpoolSubscriptionFilter { senders: vec![ours.get_sender()], ..Default::default() };
+        let mut tracker = TxpoolEventTracker::new(pool.clone(), filter);
+
+        pool.add_external_transaction(ours.clone()).await.unwrap();
+        pool.add_external_transaction(theirs.clone()).await.unwrap();
+
+        let event = tracker.on_event(events.next().await.unwrap()).unwrap();
+        assert_eq!(event.hash, ours.get_hash());
+        assert_eq!(event.sender, Some(ours.get_sender()));
+        assert!(tracker.on_event(events.next().await.unwrap()).is_none());
+
+        // removed transactions are no longer in the pool but still matched by their sender
+        pool.remove_transactions(vec![ours.get_hash(), theirs.get_hash()]);
+        let event = tracker.on_event(events.next().await.unwrap()).unwrap();
+        assert_eq!(
+            event,
+            TxpoolEvent {
+                hash: ours.get_hash(),
+                sender: Some(ours.get_sender()),
+                kind: TxpoolEventKind::Discarded
+            }
+        );
+        assert!(tracker.on_event(events.next().await.unwrap()).is_none());
+        assert!(tracker.transactions.is_empty());
+    }
+}
@@ -0,0 +1,602 @@
+use std::{collections::HashSet, future::Future, sync::Arc};
+
+use async_trait::async_trait;
+use jsonrpsee::{core::RpcResult, types::ErrorObject};
+use reth_chainspec::{ChainSpec, ChainSpecProvider, EthereumHardforks};
+use reth_consensus_common::validation::{
+    validate_against_parent_4844, validate_against_parent_eip1559_base_fee,
+    validate_against_parent_hash_number, validate_block_pre_execution,
+};
+use reth_errors::{BlockExecutionError, ConsensusError, ProviderError};
+use reth_ethereum_consensus::validate_block_post_execution;
+use reth_evm::execute::{BlockExecutorProvider, Executor};
+use reth_primitives::{
+    constants::MINIMUM_GAS_LIMIT,
+    proofs,
+    revm_primitives::{EnvKzgSettings, KzgSettings},
+    Address, BlobTransactionSidecar, BlobTransactionValidationError, BlockWithSenders, GotExpected,
+    Receipt, SealedBlock, SealedHeader, B256, U256,
+};
+use reth_provider::{HeaderProvider, StateProviderFactory};
+use reth_revm::{database::StateProviderDatabase, db::BundleState};
+use reth_rpc_api::{BlockSubmissionValidationApiServer, BuilderBlockValidationRequestV3};
+use reth_rpc_server_types::result::{internal_rpc_err, invalid_params_rpc_err};
+use reth_rpc_types::{
+    beacon::relay::{BidTrace, BuilderBlockValidationRequest, BuilderBlockValidationRequestV2},
+    engine::{BlobsBundleV1, ExecutionPayload, PayloadError},
+};
+use reth_rpc_types_compat::engine::payload::try_into_sealed_block;
+use reth_tasks::TaskSpawner;
+use tokio::sync::oneshot;
+use tracing::debug;
+
+/// The bound divisor of the gas limit, used in update calculations.
+const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+
+/// Configuration of the [`ValidationApi`].
+#[derive(Debug, Clone, Default)]
+pub struct ValidationApiConfig {
+    /// Addresses that may not be the sender or recipient of any transaction, the fee recipient of
+    /// the block or an account whose state is changed by the block.
+    pub disallow: HashSet<Address>,
+    /// The KZG settings the blobs of Deneb submissions are verified with.
+    pub kzg_settings: EnvKzgSettings,
+}
+
+/// Errors that can occur when validating a block submission.
+#[derive(Debug, thiserror::Error)]
+pub enum ValidationApiError {
+    /// The parent hash of the bid trace doesn't match the block.
+    #[error("parent hash mismatch: {0}")]
+    ParentHashMismatch(GotExpected<B256>),
+    /// The block hash of the bid trace doesn't match the block.
+    #[error("block hash mismatch: {0}")]
+    BlockHashMismatch(GotExpected<B256>),
+    /// The gas limit of the bid trace or the block is invalid.
+    #[error("gas limit mismatch: {0}")]
+    GasLimitMismatch(GotExpected<u64>),
+    /// The gas used of the bid trace doesn't match the block.
+    #[error("gas used mismatch: {0}")]
+    GasUsedMismatch(GotExpected<u64>),
+    /// The withdrawals root of the request doesn't match the block.
+    #[error("withdrawals root mismatch: {0}")]
+    WithdrawalsRootMismatch(GotExpected<B256>),
+    /// The parent of the block is unknown.
+    #[error("parent block {0} not found")]
+    MissingParentBlock(B256),
+    /// A transaction of the block has an invalid signature.
+    #[error("invalid transaction signature")]
+    InvalidTransactionSignature,
+    /// The block interacts with a disallowed address.
+    #[error("block interacts with disallowed address {0}")]
+    Blacklist(Address),
+    /// The block doesn't pay the proposer the value of the bid.
+    #[error("proposer payment invalid: {0}")]
+    ProposerPayment(&'static str),
+    /// The blobs bundle doesn't match the blob transactions of the block.
+    #[error("invalid blobs bundle: {0}")]
+    InvalidBlobsBundle(&'static str),
+    /// The blobs of the bundle don't match their commitments and proofs.
+    #[error("invalid blobs bundle: {0}")]
+    Blobs(#[from] BlobTransactionValidationError),
+    /// The payload couldn't be converted into a block.
+    #[error(transparent)]
+    Payload(#[from] PayloadError),
+    /// The block is invalid.
+    #[error(transparent)]
+    Consensus(#[from] ConsensusError),
+    /// The block failed to execute.
+    #[error(transparent)]
+    Execution(#[from] BlockExecutionError),
+    /// The state required to validate the block is unavailable.
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// The validation task was dropped.
+    #[error("internal validation error")]
+    Internal,
+}
+
+impl From<ValidationApiError> for ErrorObject<'static> {
+    fn from(error: ValidationApiError) -> Self {
+        match error {
+            ValidationApiError::Provider(_) | ValidationApiError::Internal => {
+                internal_rpc_err(error.to_string())
+            }
+            err => invalid_params_rpc_err(err.to_string()),
+        }
+    }
+}
+
+/// `flashbots` API implementation.
+///
+/// This type provides the functionality for validating block submissions of builders to relays.
+pub struct ValidationApi<Provider, E> {
+    inner: Arc<ValidationApiInner<Provider, E>>,
+}
+
+// === impl ValidationApi ===
+
+impl<Provider, E> ValidationApi<Provider, E> {
+    /// Create a new instance of the [`ValidationApi`]
+    pub fn new(
+        provider: Provider,
+        executor_provider: E,
+        config: ValidationApiConfig,
+        task_spawner: Box<dyn TaskSpawner>,
+    ) -> Self {
+        let ValidationApiConfig { disallow, kzg_settings } = config;
+        let inner = Arc::new(ValidationApiInner {
+            provider,
+            executor_provider,
+            disallow,
+            kzg_settings,
+            task_spawner,
+        });
+        Self { inner }
+    }
+
+    /// The provider that can interact with the chain.
+    pub fn provider(&self) -> &Provider {
+        &self.inner.provider
+    }
+}
+
+impl<Provider, E> ValidationApi<Provider, E>
+where
+    Provider: HeaderProvider
+        + StateProviderFactory
+        + ChainSpecProvider<ChainSpec = ChainSpec>
+        + Clone
+        + 'static,
+    E: BlockExecutorProvider,
+{
+    /// Executes the future on a new blocking task.
+    async fn on_blocking_task<C, F, R>(&self, c: C) -> Result<R, ValidationApiError>
+    where
+        C: FnOnce(Self) -> F,
+        F: Future<Output = Result<R, ValidationApiError>> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let this = self.clone();
+        let f = c(this);
+        self.inner.task_spawner.spawn_blocking(Box::pin(async move {
+            let res = f.await;
+            let _ = tx.send(res);
+        }));
+        rx.await.map_err(|_| ValidationApiError::Internal)?
+    }
+
+    /// Validates the given block against the [`BidTrace`] of its submission.
+    ///
+    /// This checks that the block:
+    ///  - matches the bid trace and extends a known parent
+    ///  - moves the gas limit towards the gas limit registered by the proposer
+    ///  - doesn't interact with any disallowed address
+    ///  - executes successfully and commits to the resulting receipts, logs bloom, gas used and
+    ///    state root
+    ///  - pays the proposer the value of the bid
+    pub fn validate_message_against_block(
+        &self,
+        block: SealedBlock,
+        message: BidTrace,
+        registered_gas_limit: u64,
+    ) -> Result<(), ValidationApiError> {
+        if message.parent_hash != block.parent_hash {
+            return Err(ValidationApiError::ParentHashMismatch(GotExpected {
+                got: message.parent_hash,
+                expected: block.parent_hash,
+            }))
+        }
+        if message.block_hash != block.hash() {
+            return Err(ValidationApiError::BlockHashMismatch(GotExpected {
+                got: message.block_hash,
+                expected: block.hash(),
+            }))
+        }
+        if message.gas_limit != block.gas_limit {
+            return Err(ValidationApiError::GasLimitMismatch(GotExpected {
+                got: message.gas_limit,
+                expected: block.gas_limit,
+            }))
+        }
+        if message.gas_used != block.gas_used {
+            return Err(ValidationApiError::GasUsedMismatch(GotExpected {
+                got: message.gas_used,
+                expected: block.gas_used,
+            }))
+        }
+
+        self.ensure_allowed(block.beneficiary)?;
+        self.ensure_allowed(message.proposer_fee_recipient)?;
+
+        let chain_spec = self.provider().chain_spec();
+        let parent = self
+            .provider()
+            .header(&block.parent_hash)?
+            .map(|header| SealedHeader::new(header, block.parent_hash))
+            .ok_or(ValidationApiError::MissingParentBlock(block.parent_hash))?;
+
+        self.validate_against_parent(&block, &parent, &chain_spec)?;
+
+        let expected_gas_limit = calculate_gas_limit(parent.gas_limit, registered_gas_limit);
+        if block.gas_limit != expected_gas_limit {
+            return Err(ValidationApiError::GasLimitMismatch(GotExpected {
+                got: block.gas_limit,
+                expected: expected_gas_limit,
+            }))
+        }
+
+        validate_block_pre_execution(&block, &chain_spec)?;
+
+        let block_hash = block.hash();
+        let block =
+            block.seal_with_senders().ok_or(ValidationApiError::InvalidTransactionSignature)?;
+        for (sender, tx) in block.transactions_with_sender() {
+            self.ensure_allowed(*sender)?;
+            if let Some(to) = tx.to() {
+                self.ensure_allowed(to)?;
+            }
+        }
+
+        let state_provider = self.provider().history_by_block_hash(block.parent_hash)?;
+        let executor =
+            self.inner.executor_provider.executor(StateProviderDatabase::new(&state_provider));
+        let block = block.unseal();
+        let output = executor.execute((&block, U256::MAX).into())?;
+        validate_block_post_execution(&block, &chain_spec, &output.receipts, &output.requests)?;
+
+        for address in output.state.state().keys() {
+            self.ensure_allowed(*address)?;
+        }
+
+        let fee_recipient_balance_before =
+            state_provider.account_balance(message.proposer_fee_recipient)?.unwrap_or_default();
+        validate_proposer_payment(
+            &block,
+            &output.receipts,
+            &output.state,
+            &message,
+            fee_recipient_balance_before,
+        )?;
+
+        let state_root = state_provider.state_root(&output.state)?;
+        if state_root != block.state_root {
+            return Err(ConsensusError::BodyStateRootDiff(
+                GotExpected { got: state_root, expected: block.state_root }.into(),
+            )
+            .into())
+        }
+
+        debug!(target: "rpc::flashbots", %block_hash, number = block.number, "validated block submission");
+
+        Ok(())
+    }
+
+    /// Validates the header of the block against its parent.
+    fn validate_against_parent(
+        &self,
+        block: &SealedBlock,
+        parent: &SealedHeader,
+        chain_spec: &ChainSpec,
+    ) -> Result<(), ValidationApiError> {
+        validate_against_parent_hash_number(block.header(), parent)?;
+
+        if block.timestamp <= parent.timestamp {
+            return Err(ConsensusError::TimestampIsInPast {
+                parent_timestamp: parent.timestamp,
+                timestamp: block.timestamp,
+            }
+            .into())
+        }
+
+        validate_against_parent_eip1559_base_fee(block.header(), parent, chain_spec)?;
+
+        if chain_spec.is_cancun_active_at_timestamp(block.timestamp) {
+            validate_against_parent_4844(block.header(), parent)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if the given address is disallowed.
+    fn ensure_allowed(&self, address: Address) -> Result<(), ValidationApiError> {
+        if self.inner.disallow.contains(&address) {
+            return Err(ValidationApiError::Blacklist(address))
+        }
+        Ok(())
+    }
+
+    /// Validates a submission of a payload without blobs.
+    async fn validate_payload(
+        &self,
+        payload: ExecutionPayload,
+        message: BidTrace,
+        registered_gas_limit: u64,
+        withdrawals_root: Option<B256>,
+    ) -> Result<(), ValidationApiError> {
+        self.on_blocking_task(|this| async move {
+            let block = try_into_sealed_block(payload, None)?;
+            if let Some(expected) = withdrawals_root {
+                let got = proofs::calculate_withdrawals_root(
+                    block.withdrawals.as_deref().map(Vec::as_slice).unwrap_or_default(),
+                );
+                if got != expected {
+                    return Err(ValidationApiError::WithdrawalsRootMismatch(GotExpected {
+                        got,
+                        expected,
+                    }))
+                }
+            }
+            this.validate_message_against_block(block, message, registered_gas_limit)
+        })
+        .await
+    }
+
+    /// Validates a Deneb submission, including the blobs bundle.
+    async fn validate_payload_v3(
+        &self,
+        request: BuilderBlockValidationRequestV3,
+    ) -> Result<(), ValidationApiError> {
+        self.on_blocking_task(|this| async move {
+            let BuilderBlockValidationRequestV3 {
+                request,
+                registered_gas_limit,
+                parent_beacon_block_root,
+            } = request;
+            let block = try_into_sealed_block(
+                ExecutionPayload::V3(request.execution_payload),
+                Some(parent_beacon_block_root),
+            )?;
+            validate_blobs_bundle(&block, request.blobs_bundle, this.inner.kzg_settings.get())?;
+            this.validate_message_against_block(block, request.message, registered_gas_limit)
+        })
+        .await
+    }
+}
+
+/// Returns the gas limit of the child of a block with the given gas limit, that moves the gas
+/// limit as close as possible to the desired gas limit.
+///
+/// See <https://github.com/ethereum/go-ethereum/blob/v1.14.7/core/block_validator.go#L163-L187>
+pub(crate) const fn calculate_gas_limit(parent_gas_limit: u64, desired_gas_limit: u64) -> u64 {
+    let delta = (parent_gas_limit / GAS_LIMIT_BOUND_DIVISOR).saturating_sub(1);
+    let desired_gas_limit =
+        if desired_gas_limit < MINIMUM_GAS_LIMIT { MINIMUM_GAS_LIMIT } else { desired_gas_limit };
+
+    if parent_gas_limit < desired_gas_limit {
+        let limit = parent_gas_limit + delta;
+        if limit > desired_gas_limit {
+            desired_gas_limit
+        } else {
+            limit
+        }
+    } else if parent_gas_limit > desired_gas_limit {
+        let limit = parent_gas_limit - delta;
+        if limit < desired_gas_limit {
+            desired_gas_limit
+        } else {
+            limit
+        }
+    } else {
+        parent_gas_limit
+    }
+}
+
+/// Validates that the block pays the proposer the value of the bid.
+///
+/// The payment is valid if the balance of the fee recipient increases by at least the value of
+/// the bid, or if the last transaction of the block is a plain transfer of the bid value from the
+/// block's fee recipient to the proposer's fee recipient.
+fn validate_proposer_payment(
+    block: &BlockWithSenders,
+    receipts: &[Receipt],
+    state: &BundleState,
+    message: &BidTrace,
+    balance_before: U256,
+) -> Result<(), ValidationApiError> {
+    let fee_recipient = message.proposer_fee_recipient;
+    let balance_after = match state.account(&fee_recipient) {
+        Some(account) => account.info.as_ref().map(|info| info.balance).unwrap_or_default(),
+        None => balance_before,
+    };
+
+    if balance_after >= balance_before &&
+        balance_after - balance_before >= message.value &&
+        block.beneficiary == fee_recipient
+    {
+        return Ok(())
+    }
+
+    let (Some((sender, tx)), Some(receipt)) =
+        (block.transactions_with_sender().last(), receipts.last())
+    else {
+        return Err(ValidationApiError::ProposerPayment("no proposer payment transaction"))
+    };
+
+    if !receipt.success {
+        return Err(ValidationApiError::ProposerPayment("payment transaction reverted"))
+    }
+    if *sender != block.beneficiary {
+        return Err(ValidationApiError::ProposerPayment("payment not sent by the block builder"))
+    }
+    if tx.to() != Some(fee_recipient) {
+        return Err(ValidationApiError::ProposerPayment("payment not sent to the fee recipient"))
+    }
+    if tx.value() != message.value {
+        return Err(ValidationApiError::ProposerPayment("payment value doesn't match the bid"))
+    }
+    if !tx.input().is_empty() {
+        return Err(ValidationApiError::ProposerPayment("payment transaction has calldata"))
+    }
+    if balance_after < balance_before || balance_after - balance_before < message.value {
+        return Err(ValidationApiError::ProposerPayment("fee recipient balance not increased"))
+    }
+
+    Ok(())
+}
+
+/// Validates that the blobs bundle contains exactly the blobs of the block's transactions, and
+/// that every blob matches its commitment and proof.
+fn validate_blobs_bundle(
+    block: &SealedBlock,
+    blobs_bundle: BlobsBundleV1,
+    kzg_settings: &KzgSettings,
+) -> Result<(), ValidationApiError> {
+    if blobs_bundle.commitments.len() != blobs_bundle.proofs.len() ||
+        blobs_bundle.commitments.len() != blobs_bundle.blobs.len()
+    {
+        return Err(ValidationApiError::InvalidBlobsBundle(
+            "number of commitments, proofs and blobs differ",
+        ))
+    }
+
+    let versioned_hashes: Vec<B256> = block.blob_versioned_hashes().into_iter().copied().collect();
+    if versioned_hashes.len() != blobs_bundle.commitments.len() {
+        return Err(ValidationApiError::InvalidBlobsBundle(
+            "number of blobs doesn't match the blob transactions",
+        ))
+    }
+
+    let BlobsBundleV1 { commitments, proofs, blobs } = blobs_bundle;
+    BlobTransactionSidecar { blobs, commitments, proofs }
+        .validate(&versioned_hashes, kzg_settings)?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl<Provider, E> BlockSubmissionValidationApiServer for ValidationApi<Provider, E>
+where
+    Provider: HeaderProvider
+        + StateProviderFactory
+        + ChainSpecProvider<ChainSpec = ChainSpec>
+        + Clone
+        + 'static,
+    E: BlockExecutorProvider,
+{
+    /// Handler for `flashbots_validateBuilderSubmissionV1`
+    async fn validate_builder_submission_v1(
+        &self,
+        request: BuilderBlockValidationRequest,
+    ) -> RpcResult<()> {
+        let BuilderBlockValidationRequest { request, registered_gas_limit } = request;
+        Ok(self
+            .validate_payload(
+                request.execution_payload,
+                request.message,
+                registered_gas_limit,
+                None,
+            )
+            .await?)
+    }
+
+    /// Handler for `flashbots_validateBuilderSubmissionV2`
+    async fn validate_builder_submission_v2(
+        &self,
+        request: BuilderBlockValidationRequestV2,
+    ) -> RpcResult<()> {
+        let BuilderBlockValidationRequestV2 { request, registered_gas_limit, withdrawals_root } =
+            request;
+        Ok(self
+            .validate_payload(
+                request.execution_payload,
+                request.message,
+                registered_gas_limit,
+                Some(withdrawals_root),
+            )
+            .await?)
+    }
+
+    /// Handler for `flashbots_validateBuilderSubmissionV3`
+    async fn validate_builder_submission_v3(
+        &self,
+        request: BuilderBlockValidationRequestV3,
+    ) -> RpcResult<()> {
+        Ok(self.validate_payload_v3(request).await?)
+    }
+}
+
+impl<Provider, E> std::fmt::Debug for ValidationApi<Provider, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ValidationApi").finish_non_exhaustive()
+    }
+}
+
+impl<Provider, E> Clone for ValidationApi<Provider, E> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+struct ValidationApiInner<Provider, E> {
+    /// The provider that can interact with the chain.
+    provider: Provider,
+    /// Executes the blocks of the submissions.
+    executor_provider: E,
+    /// Addresses the blocks may not interact with.
+    disallow: HashSet<Address>,
+    /// The KZG settings the blobs of submissions are verified with.
+    kzg_settings: EnvKzgSettings,
+    /// The type that can spawn tasks which would otherwise block.
+    task_spawner: Box<dyn TaskSpawner>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{
+        eip4844::kzg_to_versioned_hash, Blob, Block, Bytes48, Signature, Transaction,
+        TransactionSigned, TxEip4844,
+    };
+
+    /// Returns a block with a single blob transaction that commits to the given commitment.
+    fn block_with_blob(commitment: Bytes48) -> SealedBlock {
+        let tx = Transaction::Eip4844(TxEip4844 {
+            blob_versioned_hashes: vec![kzg_to_versioned_hash(commitment.as_slice())],
+            ..Default::default()
+        });
+        Block {
+            body: vec![TransactionSigned::from_transaction_and_signature(tx, Signature::default())],
+            ..Default::default()
+        }
+        .seal_slow()
+    }
+
+    #[test]
+    fn blobs_bundle_proofs_are_verified() {
+        let kzg_settings = EnvKzgSettings::Default;
+        // the commitment and proof of the zero blob are the point at infinity
+        let mut infinity = Bytes48::ZERO;
+        infinity[0] = 0xc0;
+        let block = block_with_blob(infinity);
+
+        let bundle = BlobsBundleV1 {
+            commitments: vec![infinity],
+            proofs: vec![infinity],
+            blobs: vec![Blob::ZERO],
+        };
+        assert!(validate_blobs_bundle(&block, bundle.clone(), kzg_settings.get()).is_ok());
+
+        // the blob doesn't match the commitment and proof
+        let mut blob = Blob::ZERO;
+        blob[31] = 1;
+        let bundle = BlobsBundleV1 { blobs: vec![blob], ..bundle };
+        assert!(matches!(
+            validate_blobs_bundle(&block, bundle, kzg_settings.get()),
+            Err(ValidationApiError::Blobs(_))
+        ));
+    }
+
+    #[test]
+    fn gas_limit_moves_towards_desired() {
+        let parent = 30_000_000;
+        let delta = parent / GAS_LIMIT_BOUND_DIVISOR - 1;
+        assert_eq!(calculate_gas_limit(parent, parent), parent);
+        assert_eq!(calculate_gas_limit(parent, 36_000_000), parent + delta);
+        assert_eq!(calculate_gas_limit(parent, parent + 1), parent + 1);
+        assert_eq!(calculate_gas_limit(parent, 10_000_000), parent - delta);
+        assert_eq!(calculate_gas_limit(parent, parent - 1), parent - 1);
+        assert_eq!(calculate_gas_limit(MINIMUM_GAS_LIMIT, 0), MINIMUM_GAS_LIMIT);
+    }
+}
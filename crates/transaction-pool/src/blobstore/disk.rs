@@ -1,6 +1,9 @@
 //! A simple diskstore for blobs
 
-use crate::blobstore::{BlobStore, BlobStoreCleanupStat, BlobStoreError, BlobStoreSize};
+use crate::blobstore::{
+    fill_blobs_from_sidecar, BlobAndProof, BlobStore, BlobStoreCleanupStat, BlobStoreError,
+    BlobStoreSize,
+};
 use alloy_rlp::{Decodable, Encodable};
 use parking_lot::{Mutex, RwLock};
 use reth_primitives::{BlobTransactionSidecar, TxHash, B256};
@@ -11,6 +14,9 @@ use tracing::{debug, trace};
 /// How many [`BlobTransactionSidecar`] to cache in memory.
 pub const DEFAULT_MAX_CACHED_BLOBS: u32 = 100;
 
+/// Maximum number of blobs a single transaction can carry, used to size the versioned hash index.
+const MAX_BLOBS_PER_TX: u32 = 6;
+
 /// A blob store that stores blob data on disk.
 ///
 /// The type uses deferred deletion, meaning that blobs are not immediately deleted from disk, but
@@ -28,12 +34,20 @@ impl DiskFileBlobStore {
         opts: DiskFileBlobStoreConfig,
     ) -> Result<Self, DiskFileBlobStoreError> {
         let blob_dir = blob_dir.into();
-        let DiskFileBlobStoreConfig { max_cached_entries, .. } = opts;
+        let DiskFileBlobStoreConfig { max_cached_entries, open } = opts;
         let inner = DiskFileBlobStoreInner::new(blob_dir, max_cached_entries);
 
         // initialize the blob store
-        inner.delete_all()?;
-        inner.create_blob_dir()?;
+        match open {
+            OpenDiskFileBlobStore::Clear => {
+                inner.delete_all()?;
+                inner.create_blob_dir()?;
+            }
+            OpenDiskFileBlobStore::ReIndex => {
+                inner.create_blob_dir()?;
+                inner.reindex()?;
+            }
+        }
 
         Ok(Self { inner: Arc::new(inner) })
     }
@@ -110,6 +124,10 @@ impl BlobStore for DiskFileBlobStore {
         self.inner.contains(tx)
     }
 
+    fn all_hashes(&self) -> Result<Vec<B256>, BlobStoreError> {
+        self.inner.all_hashes()
+    }
+
     fn get_all(
         &self,
         txs: Vec<B256>,
@@ -127,6 +145,36 @@ impl BlobStore for DiskFileBlobStore {
         self.inner.get_exact(txs)
     }
 
+    fn get_by_versioned_hashes(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProof>>, BlobStoreError> {
+        let mut blobs = vec![None; versioned_hashes.len()];
+
+        // first check the cached sidecars
+        for (_, sidecar) in self.inner.blob_cache.lock().iter() {
+            fill_blobs_from_sidecar(sidecar, versioned_hashes, &mut blobs);
+        }
+
+        // then look up the transactions of the remaining blobs and read them from disk
+        let missing = {
+            let mut index = self.inner.versioned_hashes_to_txhash.lock();
+            versioned_hashes
+                .iter()
+                .zip(&blobs)
+                .filter(|(_, blob)| blob.is_none())
+                .filter_map(|(hash, _)| index.get(hash).copied())
+                .collect::<HashSet<_>>()
+        };
+        for tx in missing {
+            if let Some(sidecar) = self.inner.get_one(tx)? {
+                fill_blobs_from_sidecar(&sidecar, versioned_hashes, &mut blobs);
+            }
+        }
+
+        Ok(blobs)
+    }
+
     fn data_size_hint(&self) -> Option<usize> {
         Some(self.inner.size_tracker.data_size())
     }
@@ -139,6 +187,7 @@ impl BlobStore for DiskFileBlobStore {
 struct DiskFileBlobStoreInner {
     blob_dir: PathBuf,
     blob_cache: Mutex<LruMap<TxHash, BlobTransactionSidecar, ByLength>>,
+    versioned_hashes_to_txhash: Mutex<LruMap<B256, TxHash, ByLength>>,
     size_tracker: BlobStoreSize,
     file_lock: RwLock<()>,
     txs_to_delete: RwLock<HashSet<B256>>,
@@ -150,6 +199,9 @@ impl DiskFileBlobStoreInner {
         Self {
             blob_dir,
             blob_cache: Mutex::new(LruMap::new(ByLength::new(max_length))),
+            versioned_hashes_to_txhash: Mutex::new(LruMap::new(ByLength::new(
+                max_length.saturating_mul(MAX_BLOBS_PER_TX),
+            ))),
             size_tracker: Default::default(),
             file_lock: Default::default(),
             txs_to_delete: Default::default(),
@@ -175,10 +227,53 @@ impl DiskFileBlobStoreInner {
         Ok(())
     }
 
+    /// Tracks the size of the blobs that are already stored on disk.
+    fn reindex(&self) -> Result<(), DiskFileBlobStoreError> {
+        let entries = fs::read_dir(&self.blob_dir)
+            .map_err(|e| DiskFileBlobStoreError::Open(self.blob_dir.clone(), e))?;
+        for entry in entries {
+            let metadata = entry
+                .and_then(|entry| entry.metadata())
+                .map_err(|e| DiskFileBlobStoreError::Open(self.blob_dir.clone(), e))?;
+            if metadata.is_file() {
+                self.size_tracker.add_size(metadata.len() as usize);
+                self.size_tracker.inc_len(1);
+            }
+        }
+        debug!(target:"txpool::blob", blob_dir = ?self.blob_dir, num_blobs=%self.size_tracker.blobs_len(), "Reindexed blob store");
+        Ok(())
+    }
+
+    /// Returns the transaction hashes of all blob files that are not marked for deletion.
+    fn all_hashes(&self) -> Result<Vec<B256>, BlobStoreError> {
+        let entries = fs::read_dir(&self.blob_dir)
+            .map_err(|e| DiskFileBlobStoreError::Open(self.blob_dir.clone(), e))?;
+        let txs_to_delete = self.txs_to_delete.read();
+        let mut hashes = Vec::new();
+        for entry in entries {
+            let entry =
+                entry.map_err(|e| DiskFileBlobStoreError::Open(self.blob_dir.clone(), e))?;
+            let Some(tx) = entry.file_name().to_str().and_then(|name| name.parse::<B256>().ok())
+            else {
+                continue
+            };
+            if !txs_to_delete.contains(&tx) {
+                hashes.push(tx);
+            }
+        }
+        Ok(hashes)
+    }
+
     /// Ensures blob is in the blob cache and written to the disk.
     fn insert_one(&self, tx: B256, data: BlobTransactionSidecar) -> Result<(), BlobStoreError> {
         let mut buf = Vec::with_capacity(data.fields_len());
         data.encode(&mut buf);
+        {
+            let mut index = self.versioned_hashes_to_txhash.lock();
+            for hash in data.versioned_hashes() {
+                index.insert(hash, tx);
+            }
+        }
         self.blob_cache.lock().insert(tx, data);
         let size = self.write_one_encoded(tx, &buf)?;
 
@@ -198,6 +293,14 @@ impl DiskFileBlobStoreInner {
             })
             .collect::<Vec<_>>();
 
+        {
+            let mut index = self.versioned_hashes_to_txhash.lock();
+            for (tx, data) in &txs {
+                for hash in data.versioned_hashes() {
+                    index.insert(hash, *tx);
+                }
+            }
+        }
         {
             let mut cache = self.blob_cache.lock();
             for (tx, data) in txs {
@@ -450,6 +553,12 @@ impl DiskFileBlobStoreConfig {
         self.max_cached_entries = max_cached_entries;
         self
     }
+
+    /// Set how to open the blob store.
+    pub const fn with_open(mut self, open: OpenDiskFileBlobStore) -> Self {
+        self.open = open;
+        self
+    }
 }
 
 /// How to open a disk file blob store.
@@ -519,4 +628,35 @@ mod tests {
         assert_eq!(store.data_size_hint(), Some(0));
         assert_eq!(store.inner.size_tracker.num_blobs.load(Ordering::Relaxed), 0);
     }
+
+    #[test]
+    fn disk_reopen_reindex() {
+        let (store, dir) = tmp_store();
+
+        let blobs = rng_blobs(10);
+        store.insert_all(blobs.clone()).unwrap();
+        let data_size = store.data_size_hint();
+        drop(store);
+
+        let opts = DiskFileBlobStoreConfig::default().with_open(OpenDiskFileBlobStore::ReIndex);
+        let store = DiskFileBlobStore::open(dir.path(), opts).unwrap();
+        assert_eq!(store.blobs_len(), 10);
+        assert_eq!(store.data_size_hint(), data_size);
+        for (tx, blob) in &blobs {
+            assert_eq!(store.get(*tx).unwrap().unwrap(), *blob);
+        }
+
+        // reindexed blobs are listed until they are deleted
+        let mut hashes = blobs.iter().map(|(tx, _)| *tx).collect::<Vec<_>>();
+        let deleted = hashes.pop().unwrap();
+        store.delete(deleted).unwrap();
+        let mut all_hashes = store.all_hashes().unwrap();
+        all_hashes.sort();
+        hashes.sort();
+        assert_eq!(all_hashes, hashes);
+
+        let store = DiskFileBlobStore::open(dir.path(), Default::default()).unwrap();
+        assert_eq!(store.blobs_len(), 0);
+        assert!(store.get(blobs[0].0).unwrap().is_none());
+    }
 }
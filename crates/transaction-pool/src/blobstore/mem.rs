@@ -1,5 +1,6 @@
 use crate::blobstore::{
-    BlobStore, BlobStoreCleanupStat, BlobStoreError, BlobStoreSize, BlobTransactionSidecar,
+    fill_blobs_from_sidecar, BlobAndProof, BlobStore, BlobStoreCleanupStat, BlobStoreError,
+    BlobStoreSize, BlobTransactionSidecar,
 };
 use parking_lot::RwLock;
 use reth_primitives::B256;
@@ -84,6 +85,10 @@ impl BlobStore for InMemoryBlobStore {
         Ok(store.contains_key(&tx))
     }
 
+    fn all_hashes(&self) -> Result<Vec<B256>, BlobStoreError> {
+        Ok(self.inner.store.read().keys().copied().collect())
+    }
+
     fn get_all(
         &self,
         txs: Vec<B256>,
@@ -113,6 +118,20 @@ impl BlobStore for InMemoryBlobStore {
         Ok(items)
     }
 
+    fn get_by_versioned_hashes(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProof>>, BlobStoreError> {
+        let mut blobs = vec![None; versioned_hashes.len()];
+        for sidecar in self.inner.store.read().values() {
+            fill_blobs_from_sidecar(sidecar, versioned_hashes, &mut blobs);
+            if blobs.iter().all(Option::is_some) {
+                break
+            }
+        }
+        Ok(blobs)
+    }
+
     fn data_size_hint(&self) -> Option<usize> {
         Some(self.inner.size_tracker.data_size())
     }
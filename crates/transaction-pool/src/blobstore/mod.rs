@@ -3,7 +3,8 @@
 pub use disk::{DiskFileBlobStore, DiskFileBlobStoreConfig, OpenDiskFileBlobStore};
 pub use mem::InMemoryBlobStore;
 pub use noop::NoopBlobStore;
-use reth_primitives::{BlobTransactionSidecar, B256};
+use reth_primitives::{Blob, BlobTransactionSidecar, Bytes48, B256};
+pub use segmented::{SegmentedBlobStore, SegmentedBlobStoreConfig, SegmentedBlobStoreError};
 use std::{
     fmt,
     sync::atomic::{AtomicUsize, Ordering},
@@ -13,6 +14,7 @@ pub use tracker::{BlobStoreCanonTracker, BlobStoreUpdates};
 pub mod disk;
 mod mem;
 mod noop;
+pub mod segmented;
 mod tracker;
 
 /// A blob store that can be used to store blob data of EIP4844 transactions.
@@ -47,6 +49,9 @@ pub trait BlobStore: fmt::Debug + Send + Sync + 'static {
     /// Checks if the given transaction hash is in the blob store.
     fn contains(&self, tx: B256) -> Result<bool, BlobStoreError>;
 
+    /// Returns the transaction hashes of all blobs in the store that are not marked for deletion.
+    fn all_hashes(&self) -> Result<Vec<B256>, BlobStoreError>;
+
     /// Retrieves all decoded blob data for the given transaction hashes.
     ///
     /// This only returns the blobs that were found in the store.
@@ -64,6 +69,15 @@ pub trait BlobStore: fmt::Debug + Send + Sync + 'static {
     /// Returns an error if any of the blobs are not found in the blob store.
     fn get_exact(&self, txs: Vec<B256>) -> Result<Vec<BlobTransactionSidecar>, BlobStoreError>;
 
+    /// Retrieves the blobs and their proofs for the given versioned hashes.
+    ///
+    /// The result has the same length and order as the requested hashes, blobs that are not in
+    /// the store are returned as `None`.
+    fn get_by_versioned_hashes(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProof>>, BlobStoreError>;
+
     /// Data size of all transactions in the blob store.
     fn data_size_hint(&self) -> Option<usize>;
 
@@ -85,6 +99,33 @@ pub enum BlobStoreError {
     Other(Box<dyn std::error::Error + Send + Sync>),
 }
 
+/// A single blob and its KZG proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobAndProof {
+    /// The blob data.
+    pub blob: Box<Blob>,
+    /// The KZG proof for the blob.
+    pub proof: Bytes48,
+}
+
+/// Fills in all requested blobs that are still missing and are part of the given sidecar.
+pub(crate) fn fill_blobs_from_sidecar(
+    sidecar: &BlobTransactionSidecar,
+    versioned_hashes: &[B256],
+    blobs: &mut [Option<BlobAndProof>],
+) {
+    for (idx, hash) in sidecar.versioned_hashes().enumerate() {
+        let (Some(blob), Some(proof)) = (sidecar.blobs.get(idx), sidecar.proofs.get(idx)) else {
+            continue
+        };
+        for (requested, found) in versioned_hashes.iter().zip(blobs.iter_mut()) {
+            if found.is_none() && *requested == hash {
+                *found = Some(BlobAndProof { blob: Box::new(*blob), proof: *proof });
+            }
+        }
+    }
+}
+
 /// Keeps track of the size of the blob store.
 #[derive(Debug, Default)]
 pub(crate) struct BlobStoreSize {
@@ -1,4 +1,6 @@
-use crate::blobstore::{BlobStore, BlobStoreCleanupStat, BlobStoreError, BlobTransactionSidecar};
+use crate::blobstore::{
+    BlobAndProof, BlobStore, BlobStoreCleanupStat, BlobStoreError, BlobTransactionSidecar,
+};
 use reth_primitives::B256;
 
 /// A blobstore implementation that does nothing
@@ -35,6 +37,10 @@ impl BlobStore for NoopBlobStore {
         Ok(false)
     }
 
+    fn all_hashes(&self) -> Result<Vec<B256>, BlobStoreError> {
+        Ok(vec![])
+    }
+
     fn get_all(
         &self,
         _txs: Vec<B256>,
@@ -49,6 +55,13 @@ impl BlobStore for NoopBlobStore {
         Err(BlobStoreError::MissingSidecar(txs[0]))
     }
 
+    fn get_by_versioned_hashes(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProof>>, BlobStoreError> {
+        Ok(vec![None; versioned_hashes.len()])
+    }
+
     fn data_size_hint(&self) -> Option<usize> {
         Some(0)
     }
@@ -0,0 +1,936 @@
+//! An append-only blob store that keeps blobs in segment files.
+
+use crate::{
+    blobstore::{
+        disk::OpenDiskFileBlobStore, fill_blobs_from_sidecar, BlobAndProof, BlobStore,
+        BlobStoreCleanupStat, BlobStoreError, BlobStoreSize,
+    },
+    metrics::SegmentedBlobStoreMetrics,
+};
+use alloy_rlp::{Decodable, Encodable};
+use parking_lot::{Mutex, RwLock};
+use reth_primitives::{BlobTransactionSidecar, TxHash, B256};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::debug;
+
+/// The default maximum size of a segment file before a new one is started: 64 MiB.
+pub const DEFAULT_MAX_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The default retention of blobs: the data availability window of 4096 epochs.
+pub const DEFAULT_BLOB_RETENTION: Duration = Duration::from_secs(4096 * 32 * 12);
+
+/// The default share of live data, in percent, below which a sealed segment is compacted.
+pub const DEFAULT_MIN_LIVE_PERCENT: u8 = 50;
+
+/// Length of the header of every record: transaction hash, insertion time and payload length.
+const RECORD_HEADER_LEN: u64 = 32 + 8 + 4;
+
+/// File name prefix of the segment files.
+const SEGMENT_FILE_PREFIX: &str = "segment-";
+
+/// A blob store that appends blob data to segment files on disk.
+///
+/// Sidecars are appended to the active segment until it exceeds the configured maximum size, after
+/// which a new segment is started. Like [`DiskFileBlobStore`](crate::blobstore::DiskFileBlobStore)
+/// this uses deferred deletion: [`BlobStore::cleanup`] removes deleted blobs and blobs that fell
+/// out of the retention window from the index. Sealed segments with little live data left are then
+/// compacted on a background thread, by moving their remaining blobs into the active segment.
+///
+/// Blobs that were inserted since the store was opened are referenced by the pool and never expire,
+/// they are only removed once deleted. Deletions are not written to disk. When reopened with
+/// [`OpenDiskFileBlobStore::ReIndex`], blobs that were deleted but whose segment was not compacted
+/// yet are restored, and kept until they expire unless the pool inserts them again or the
+/// [`journal`](crate::journal) deletes them after replay.
+#[derive(Clone, Debug)]
+pub struct SegmentedBlobStore {
+    inner: Arc<SegmentedBlobStoreInner>,
+}
+
+impl SegmentedBlobStore {
+    /// Opens and initializes a new segmented blob store according to the given options.
+    pub fn open(
+        blob_dir: impl Into<PathBuf>,
+        opts: SegmentedBlobStoreConfig,
+    ) -> Result<Self, SegmentedBlobStoreError> {
+        let blob_dir = blob_dir.into();
+        let SegmentedBlobStoreConfig { max_segment_size, retention, min_live_percent, open } = opts;
+
+        let mut state = SegmentState::default();
+        match open {
+            OpenDiskFileBlobStore::Clear => {
+                match fs::remove_dir_all(&blob_dir) {
+                    Ok(_) => {
+                        debug!(target:"txpool::blob", ?blob_dir, "Removed blob store directory");
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(SegmentedBlobStoreError::Open(blob_dir, err)),
+                }
+                create_blob_dir(&blob_dir)?;
+            }
+            OpenDiskFileBlobStore::ReIndex => {
+                create_blob_dir(&blob_dir)?;
+                state.reindex(&blob_dir)?;
+            }
+        }
+        state.start_segment(&blob_dir)?;
+
+        let inner = SegmentedBlobStoreInner {
+            blob_dir,
+            max_segment_size,
+            retention,
+            min_live_percent,
+            state: RwLock::new(state),
+            txs_to_delete: Default::default(),
+            compaction: Default::default(),
+            size_tracker: Default::default(),
+            metrics: Default::default(),
+        };
+        {
+            let state = inner.state.read();
+            inner.size_tracker.add_size(state.live_size() as usize);
+            inner.size_tracker.update_len(state.index.len());
+            inner.update_metrics(&state);
+        }
+
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    /// Compacts the sealed segments on a background thread, unless a compaction is still running.
+    fn spawn_compaction(&self) {
+        let mut compaction = self.inner.compaction.lock();
+        if compaction.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return
+        }
+        let inner = Arc::clone(&self.inner);
+        match thread::Builder::new()
+            .name("blobstore-compaction".to_string())
+            .spawn(move || inner.compact())
+        {
+            Ok(handle) => *compaction = Some(handle),
+            Err(err) => {
+                debug!(target:"txpool::blob", %err, "Failed to spawn blob store compaction");
+            }
+        }
+    }
+}
+
+impl BlobStore for SegmentedBlobStore {
+    fn insert(&self, tx: B256, data: BlobTransactionSidecar) -> Result<(), BlobStoreError> {
+        let mut state = self.inner.state.write();
+        let res = self.inner.insert_one(&mut state, tx, &data);
+        self.inner.size_tracker.update_len(state.index.len());
+        self.inner.update_metrics(&state);
+        res
+    }
+
+    fn insert_all(&self, txs: Vec<(B256, BlobTransactionSidecar)>) -> Result<(), BlobStoreError> {
+        if txs.is_empty() {
+            return Ok(())
+        }
+        let mut state = self.inner.state.write();
+        for (tx, data) in txs {
+            if let Err(err) = self.inner.insert_one(&mut state, tx, &data) {
+                debug!(target:"txpool::blob", %err, ?tx, "Failed to append blob");
+            }
+        }
+        self.inner.size_tracker.update_len(state.index.len());
+        self.inner.update_metrics(&state);
+        Ok(())
+    }
+
+    fn delete(&self, tx: B256) -> Result<(), BlobStoreError> {
+        if self.contains(tx)? {
+            self.inner.txs_to_delete.write().insert(tx);
+        }
+        Ok(())
+    }
+
+    fn delete_all(&self, txs: Vec<B256>) -> Result<(), BlobStoreError> {
+        let txs = {
+            let state = self.inner.state.read();
+            txs.into_iter().filter(|tx| state.index.contains_key(tx)).collect::<Vec<_>>()
+        };
+        self.inner.txs_to_delete.write().extend(txs);
+        Ok(())
+    }
+
+    fn cleanup(&self) -> BlobStoreCleanupStat {
+        let txs_to_delete = std::mem::take(&mut *self.inner.txs_to_delete.write());
+        let mut stat = BlobStoreCleanupStat::default();
+        let mut subsize = 0;
+
+        let mut state = self.inner.state.write();
+        for tx in txs_to_delete {
+            if let Some(location) = state.untrack(&tx) {
+                stat.delete_succeed += 1;
+                subsize += location.record_len();
+            }
+        }
+
+        let now = unix_timestamp();
+        let retention = self.inner.retention.as_secs();
+        let expired = state
+            .index
+            .iter()
+            .filter(|(tx, location)| {
+                !state.referenced.contains(*tx) &&
+                    now.saturating_sub(location.inserted_at) >= retention
+            })
+            .map(|(tx, _)| *tx)
+            .collect::<Vec<_>>();
+        if !expired.is_empty() {
+            debug!(target:"txpool::blob", num_blobs=%expired.len(), "Removing expired blobs");
+            self.inner.metrics.blobstore_expired_blobs.increment(expired.len() as u64);
+        }
+        for tx in expired {
+            if let Some(location) = state.untrack(&tx) {
+                stat.delete_succeed += 1;
+                subsize += location.record_len();
+            }
+        }
+
+        self.inner.size_tracker.sub_size(subsize as usize);
+        self.inner.size_tracker.update_len(state.index.len());
+        self.inner.update_metrics(&state);
+
+        let compact = !self.inner.segments_to_compact(&state).is_empty();
+        drop(state);
+        if compact {
+            self.spawn_compaction();
+        }
+        stat
+    }
+
+    fn get(&self, tx: B256) -> Result<Option<BlobTransactionSidecar>, BlobStoreError> {
+        let state = self.inner.state.read();
+        state.get(&self.inner.blob_dir, &tx)
+    }
+
+    fn contains(&self, tx: B256) -> Result<bool, BlobStoreError> {
+        Ok(self.inner.state.read().index.contains_key(&tx))
+    }
+
+    fn all_hashes(&self) -> Result<Vec<B256>, BlobStoreError> {
+        let state = self.inner.state.read();
+        let txs_to_delete = self.inner.txs_to_delete.read();
+        Ok(state.index.keys().filter(|tx| !txs_to_delete.contains(*tx)).copied().collect())
+    }
+
+    fn get_all(
+        &self,
+        txs: Vec<B256>,
+    ) -> Result<Vec<(B256, BlobTransactionSidecar)>, BlobStoreError> {
+        let state = self.inner.state.read();
+        let mut res = Vec::with_capacity(txs.len());
+        for tx in txs {
+            if let Some(sidecar) = state.get(&self.inner.blob_dir, &tx)? {
+                res.push((tx, sidecar));
+            }
+        }
+        Ok(res)
+    }
+
+    fn get_exact(&self, txs: Vec<B256>) -> Result<Vec<BlobTransactionSidecar>, BlobStoreError> {
+        let state = self.inner.state.read();
+        let mut res = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let sidecar =
+                state.get(&self.inner.blob_dir, &tx)?.ok_or(BlobStoreError::MissingSidecar(tx))?;
+            res.push(sidecar);
+        }
+        Ok(res)
+    }
+
+    fn get_by_versioned_hashes(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProof>>, BlobStoreError> {
+        let mut blobs = vec![None; versioned_hashes.len()];
+        let state = self.inner.state.read();
+        let txs = versioned_hashes
+            .iter()
+            .filter_map(|hash| state.versioned_hashes.get(hash).copied())
+            .collect::<HashSet<_>>();
+        for tx in txs {
+            if let Some(sidecar) = state.get(&self.inner.blob_dir, &tx)? {
+                fill_blobs_from_sidecar(&sidecar, versioned_hashes, &mut blobs);
+            }
+        }
+        Ok(blobs)
+    }
+
+    fn data_size_hint(&self) -> Option<usize> {
+        Some(self.inner.size_tracker.data_size())
+    }
+
+    fn blobs_len(&self) -> usize {
+        self.inner.size_tracker.blobs_len()
+    }
+}
+
+struct SegmentedBlobStoreInner {
+    blob_dir: PathBuf,
+    max_segment_size: u64,
+    retention: Duration,
+    min_live_percent: u8,
+    state: RwLock<SegmentState>,
+    txs_to_delete: RwLock<HashSet<B256>>,
+    /// Handle of the last background compaction.
+    compaction: Mutex<Option<JoinHandle<()>>>,
+    size_tracker: BlobStoreSize,
+    metrics: SegmentedBlobStoreMetrics,
+}
+
+impl SegmentedBlobStoreInner {
+    /// Appends the sidecar to the active segment, unless it is already stored.
+    ///
+    /// In both cases the blob is marked as referenced by the pool.
+    fn insert_one(
+        &self,
+        state: &mut SegmentState,
+        tx: B256,
+        data: &BlobTransactionSidecar,
+    ) -> Result<(), BlobStoreError> {
+        if state.index.contains_key(&tx) {
+            state.referenced.insert(tx);
+            debug!(target:"txpool::blob", ?tx, "Blob already exists");
+            return Ok(())
+        }
+        let mut buf = Vec::with_capacity(data.fields_len());
+        data.encode(&mut buf);
+        let record_len = state.append(
+            &self.blob_dir,
+            self.max_segment_size,
+            tx,
+            unix_timestamp(),
+            &buf,
+            data.versioned_hashes().collect(),
+        )?;
+        state.referenced.insert(tx);
+        self.size_tracker.add_size(record_len as usize);
+        Ok(())
+    }
+
+    /// Returns the sealed segments whose share of live data dropped below the configured
+    /// threshold.
+    fn segments_to_compact(&self, state: &SegmentState) -> Vec<u64> {
+        let min_live_percent = self.min_live_percent as u64;
+        state
+            .segments
+            .iter()
+            .filter(|(id, segment)| {
+                **id != state.active &&
+                    (segment.live == 0 || segment.live * 100 < segment.size * min_live_percent)
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Compacts all sealed segments whose share of live data dropped below the configured
+    /// threshold.
+    fn compact(&self) {
+        let to_compact = self.segments_to_compact(&self.state.read());
+        for id in to_compact {
+            self.compact_segment(id);
+        }
+        self.update_metrics(&self.state.read());
+    }
+
+    /// Appends the live blobs of the segment to the active segment and removes the segment file.
+    ///
+    /// The blobs are read without holding the lock, so that the store can be used while the
+    /// segment is compacted. Blobs that were removed in the meantime are not moved.
+    fn compact_segment(&self, id: u64) {
+        let live = self
+            .state
+            .read()
+            .index
+            .iter()
+            .filter(|(_, location)| location.segment == id)
+            .map(|(tx, location)| (*tx, location.clone()))
+            .collect::<Vec<_>>();
+
+        let mut records = Vec::with_capacity(live.len());
+        for (tx, location) in live {
+            match read_record(&self.blob_dir, &location) {
+                Ok(payload) => records.push((tx, location, payload)),
+                Err(err) => {
+                    debug!(target:"txpool::blob", %err, segment=%id, ?tx, "Failed to move blob");
+                    return
+                }
+            }
+        }
+
+        let mut state = self.state.write();
+        let mut num_moved = 0;
+        for (tx, location, payload) in records {
+            let is_live = state.index.get(&tx).is_some_and(|current| {
+                current.segment == location.segment && current.offset == location.offset
+            });
+            if !is_live {
+                continue
+            }
+            if let Err(err) = state.append(
+                &self.blob_dir,
+                self.max_segment_size,
+                tx,
+                location.inserted_at,
+                &payload,
+                location.versioned_hashes,
+            ) {
+                debug!(target:"txpool::blob", %err, segment=%id, ?tx, "Failed to move blob");
+                return
+            }
+            num_moved += 1;
+        }
+
+        let path = segment_path(&self.blob_dir, id);
+        match fs::remove_file(&path) {
+            Ok(_) => {
+                debug!(target:"txpool::blob", segment=%id, %num_moved, "Compacted segment");
+                state.segments.remove(&id);
+                self.metrics.blobstore_compacted_segments.increment(1);
+            }
+            Err(e) => {
+                let err = SegmentedBlobStoreError::DeleteSegment(id, path, e);
+                debug!(target:"txpool::blob", %err);
+            }
+        }
+    }
+
+    fn update_metrics(&self, state: &SegmentState) {
+        self.metrics.blobstore_disk_usage.set(state.disk_size() as f64);
+        self.metrics.blobstore_live_bytes.set(state.live_size() as f64);
+        self.metrics.blobstore_segments.set(state.segments.len() as f64);
+    }
+}
+
+impl fmt::Debug for SegmentedBlobStoreInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SegmentedBlobStoreInner")
+            .field("blob_dir", &self.blob_dir)
+            .field("max_segment_size", &self.max_segment_size)
+            .field("retention", &self.retention)
+            .field("min_live_percent", &self.min_live_percent)
+            .field("segments", &self.state.try_read().map(|state| state.segments.clone()))
+            .field("txs_to_delete", &self.txs_to_delete.try_read())
+            .finish()
+    }
+}
+
+/// Where a blob sidecar is stored.
+#[derive(Debug, Clone)]
+struct BlobLocation {
+    /// Id of the segment the record is in.
+    segment: u64,
+    /// Offset of the record in the segment.
+    offset: u64,
+    /// Length of the encoded sidecar.
+    len: u32,
+    /// Unix timestamp in seconds of when the blob was first inserted.
+    inserted_at: u64,
+    /// Versioned hashes of all blobs in the sidecar.
+    versioned_hashes: Vec<B256>,
+}
+
+impl BlobLocation {
+    /// Length of the entire record, including the header.
+    const fn record_len(&self) -> u64 {
+        RECORD_HEADER_LEN + self.len as u64
+    }
+}
+
+/// Size bookkeeping of a segment file.
+#[derive(Debug, Clone, Copy, Default)]
+struct Segment {
+    /// Size of the segment file.
+    size: u64,
+    /// Bytes of all records in the segment that are still live.
+    live: u64,
+}
+
+/// The index of all stored blobs and the segments they are in.
+#[derive(Debug, Default)]
+struct SegmentState {
+    /// Location of every live blob sidecar.
+    index: HashMap<TxHash, BlobLocation>,
+    /// Blobs that were inserted by the pool since the store was opened and were not removed yet.
+    referenced: HashSet<TxHash>,
+    /// Maps the versioned hash of every live blob to its transaction.
+    versioned_hashes: HashMap<B256, TxHash>,
+    /// All segments on disk, by id.
+    segments: BTreeMap<u64, Segment>,
+    /// Id of the segment new records are appended to.
+    active: u64,
+    /// Handle to the active segment file.
+    writer: Option<fs::File>,
+}
+
+impl SegmentState {
+    /// Rebuilds the index from all segment files in the directory.
+    fn reindex(&mut self, blob_dir: &Path) -> Result<(), SegmentedBlobStoreError> {
+        let entries = fs::read_dir(blob_dir)
+            .map_err(|e| SegmentedBlobStoreError::Open(blob_dir.into(), e))?;
+        let mut ids = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| SegmentedBlobStoreError::Open(blob_dir.into(), e))?;
+            if let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix(SEGMENT_FILE_PREFIX))
+                .and_then(|id| id.parse::<u64>().ok())
+            {
+                ids.push(id);
+            }
+        }
+        ids.sort_unstable();
+
+        for id in ids {
+            let path = segment_path(blob_dir, id);
+            let data =
+                fs::read(&path).map_err(|e| SegmentedBlobStoreError::ReadSegment(id, path, e))?;
+            self.segments.insert(id, Segment { size: data.len() as u64, live: 0 });
+
+            let mut offset = 0;
+            while let Some((tx, inserted_at, payload)) = decode_record(&data[offset..]) {
+                let len = payload.len() as u32;
+                match BlobTransactionSidecar::decode(&mut &payload[..]) {
+                    Ok(sidecar) => {
+                        let location = BlobLocation {
+                            segment: id,
+                            offset: offset as u64,
+                            len,
+                            inserted_at,
+                            versioned_hashes: sidecar.versioned_hashes().collect(),
+                        };
+                        self.track(tx, location);
+                    }
+                    Err(err) => {
+                        debug!(target:"txpool::blob", %err, segment=%id, ?tx, "Skipping undecodable blob");
+                    }
+                }
+                offset += RECORD_HEADER_LEN as usize + len as usize;
+            }
+            if offset < data.len() {
+                debug!(target:"txpool::blob", segment=%id, %offset, "Ignoring truncated segment tail");
+            }
+        }
+        debug!(target:"txpool::blob", ?blob_dir, num_blobs=%self.index.len(), num_segments=%self.segments.len(), "Reindexed blob store");
+        Ok(())
+    }
+
+    /// Starts a new active segment after the last existing one.
+    fn start_segment(&mut self, blob_dir: &Path) -> Result<(), SegmentedBlobStoreError> {
+        let id = self.segments.keys().next_back().map_or(0, |id| id + 1);
+        let path = segment_path(blob_dir, id);
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| SegmentedBlobStoreError::WriteSegment(id, path, e))?;
+        self.segments.insert(id, Segment::default());
+        self.active = id;
+        self.writer = Some(file);
+        Ok(())
+    }
+
+    /// Appends a record to the active segment and returns the length of the record.
+    ///
+    /// Starts a new segment first if the active segment reached the maximum size.
+    fn append(
+        &mut self,
+        blob_dir: &Path,
+        max_segment_size: u64,
+        tx: TxHash,
+        inserted_at: u64,
+        payload: &[u8],
+        versioned_hashes: Vec<B256>,
+    ) -> Result<u64, SegmentedBlobStoreError> {
+        if self.segments.get(&self.active).map_or(true, |segment| segment.size >= max_segment_size)
+        {
+            self.start_segment(blob_dir)?;
+        }
+
+        let mut record = Vec::with_capacity(RECORD_HEADER_LEN as usize + payload.len());
+        record.extend_from_slice(tx.as_slice());
+        record.extend_from_slice(&inserted_at.to_be_bytes());
+        record.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        record.extend_from_slice(payload);
+
+        let id = self.active;
+        let writer = self.writer.as_mut().expect("active segment is open");
+        let segment = self.segments.entry(id).or_default();
+        if let Err(e) = writer.write_all(&record) {
+            // a partial write leaves garbage at the end of the segment, which is skipped on
+            // reindex
+            if let Ok(metadata) = writer.metadata() {
+                segment.size = metadata.len();
+            }
+            return Err(SegmentedBlobStoreError::WriteSegment(id, segment_path(blob_dir, id), e))
+        }
+        let offset = segment.size;
+        segment.size += record.len() as u64;
+
+        let location = BlobLocation {
+            segment: id,
+            offset,
+            len: payload.len() as u32,
+            inserted_at,
+            versioned_hashes,
+        };
+        self.track(tx, location);
+        Ok(record.len() as u64)
+    }
+
+    /// Reads and decodes the sidecar of the given transaction.
+    fn get(
+        &self,
+        blob_dir: &Path,
+        tx: &TxHash,
+    ) -> Result<Option<BlobTransactionSidecar>, BlobStoreError> {
+        let Some(location) = self.index.get(tx) else { return Ok(None) };
+        let payload = read_record(blob_dir, location)?;
+        BlobTransactionSidecar::decode(&mut payload.as_slice())
+            .map(Some)
+            .map_err(BlobStoreError::DecodeError)
+    }
+
+    /// Adds the location of a blob to the index, replacing any previous location.
+    fn track(&mut self, tx: TxHash, location: BlobLocation) {
+        if let Some(segment) = self.segments.get_mut(&location.segment) {
+            segment.live += location.record_len();
+        }
+        for hash in &location.versioned_hashes {
+            self.versioned_hashes.insert(*hash, tx);
+        }
+        if let Some(previous) = self.index.insert(tx, location) {
+            if let Some(segment) = self.segments.get_mut(&previous.segment) {
+                segment.live = segment.live.saturating_sub(previous.record_len());
+            }
+        }
+    }
+
+    /// Removes the blob from the index and returns its location.
+    fn untrack(&mut self, tx: &TxHash) -> Option<BlobLocation> {
+        let location = self.index.remove(tx)?;
+        self.referenced.remove(tx);
+        if let Some(segment) = self.segments.get_mut(&location.segment) {
+            segment.live = segment.live.saturating_sub(location.record_len());
+        }
+        for hash in &location.versioned_hashes {
+            if self.versioned_hashes.get(hash) == Some(tx) {
+                self.versioned_hashes.remove(hash);
+            }
+        }
+        Some(location)
+    }
+
+    /// Total size of all segment files.
+    fn disk_size(&self) -> u64 {
+        self.segments.values().map(|segment| segment.size).sum()
+    }
+
+    /// Total size of all live records.
+    fn live_size(&self) -> u64 {
+        self.segments.values().map(|segment| segment.live).sum()
+    }
+}
+
+/// Decodes the record at the start of the given buffer.
+///
+/// Returns `None` if the buffer does not contain a complete record.
+fn decode_record(buf: &[u8]) -> Option<(TxHash, u64, &[u8])> {
+    let header = buf.get(..RECORD_HEADER_LEN as usize)?;
+    let tx = TxHash::from_slice(&header[..32]);
+    let inserted_at = u64::from_be_bytes(header[32..40].try_into().ok()?);
+    let len = u32::from_be_bytes(header[40..44].try_into().ok()?) as usize;
+    let payload = buf.get(RECORD_HEADER_LEN as usize..RECORD_HEADER_LEN as usize + len)?;
+    Some((tx, inserted_at, payload))
+}
+
+/// Reads the encoded sidecar at the given location.
+fn read_record(
+    blob_dir: &Path,
+    location: &BlobLocation,
+) -> Result<Vec<u8>, SegmentedBlobStoreError> {
+    let path = segment_path(blob_dir, location.segment);
+    let mut payload = vec![0; location.len as usize];
+    fs::File::open(&path)
+        .and_then(|mut file| {
+            file.seek(SeekFrom::Start(location.offset + RECORD_HEADER_LEN))?;
+            file.read_exact(&mut payload)
+        })
+        .map_err(|e| SegmentedBlobStoreError::ReadSegment(location.segment, path, e))?;
+    Ok(payload)
+}
+
+/// Returns the path of the segment file with the given id.
+fn segment_path(blob_dir: &Path, id: u64) -> PathBuf {
+    blob_dir.join(format!("{SEGMENT_FILE_PREFIX}{id:010}"))
+}
+
+/// Creates the directory where the segments are stored.
+fn create_blob_dir(blob_dir: &Path) -> Result<(), SegmentedBlobStoreError> {
+    debug!(target:"txpool::blob", ?blob_dir, "Creating blob store");
+    fs::create_dir_all(blob_dir).map_err(|e| SegmentedBlobStoreError::Open(blob_dir.into(), e))
+}
+
+/// Returns the current unix timestamp in seconds.
+fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Errors that can occur when interacting with a segmented blob store.
+#[derive(Debug, thiserror::Error)]
+pub enum SegmentedBlobStoreError {
+    /// Thrown during [`SegmentedBlobStore::open`] if the blob store directory cannot be opened.
+    #[error("failed to open blobstore at {0}: {1}")]
+    Open(PathBuf, io::Error),
+    /// Failure while reading a segment file.
+    #[error("failed to read segment {0} at {1}: {2}")]
+    ReadSegment(u64, PathBuf, io::Error),
+    /// Failure while writing a segment file.
+    #[error("failed to write segment {0} at {1}: {2}")]
+    WriteSegment(u64, PathBuf, io::Error),
+    /// Failure while deleting a compacted segment file.
+    #[error("failed to delete segment {0} at {1}: {2}")]
+    DeleteSegment(u64, PathBuf, io::Error),
+}
+
+impl From<SegmentedBlobStoreError> for BlobStoreError {
+    fn from(value: SegmentedBlobStoreError) -> Self {
+        Self::Other(Box::new(value))
+    }
+}
+
+/// Configuration for a segmented blob store.
+#[derive(Debug, Clone)]
+pub struct SegmentedBlobStoreConfig {
+    /// The size a segment can grow to before a new segment is started.
+    pub max_segment_size: u64,
+    /// How long blobs the pool does not reference are kept before they are removed.
+    pub retention: Duration,
+    /// The share of live data in percent below which a sealed segment is compacted.
+    pub min_live_percent: u8,
+    /// How to open the blob store.
+    pub open: OpenDiskFileBlobStore,
+}
+
+impl Default for SegmentedBlobStoreConfig {
+    fn default() -> Self {
+        Self {
+            max_segment_size: DEFAULT_MAX_SEGMENT_SIZE,
+            retention: DEFAULT_BLOB_RETENTION,
+            min_live_percent: DEFAULT_MIN_LIVE_PERCENT,
+            open: Default::default(),
+        }
+    }
+}
+
+impl SegmentedBlobStoreConfig {
+    /// Set the size a segment can grow to before a new segment is started.
+    pub const fn with_max_segment_size(mut self, max_segment_size: u64) -> Self {
+        self.max_segment_size = max_segment_size;
+        self
+    }
+
+    /// Set how long blobs the pool does not reference are kept.
+    pub const fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Set the share of live data in percent below which a sealed segment is compacted.
+    pub const fn with_min_live_percent(mut self, min_live_percent: u8) -> Self {
+        self.min_live_percent = min_live_percent;
+        self
+    }
+
+    /// Set how to open the blob store.
+    pub const fn with_open(mut self, open: OpenDiskFileBlobStore) -> Self {
+        self.open = open;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Blob, Bytes48};
+
+    fn tmp_store(config: SegmentedBlobStoreConfig) -> (SegmentedBlobStore, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SegmentedBlobStore::open(dir.path(), config).unwrap();
+        (store, dir)
+    }
+
+    fn wait_for_compaction(store: &SegmentedBlobStore) {
+        if let Some(handle) = store.inner.compaction.lock().take() {
+            handle.join().unwrap();
+        }
+    }
+
+    fn rng_blobs(num: usize) -> Vec<(TxHash, BlobTransactionSidecar)> {
+        let mut rng = rand::thread_rng();
+        (0..num)
+            .map(|_| {
+                let tx = TxHash::random_with(&mut rng);
+                let blob = BlobTransactionSidecar {
+                    blobs: vec![Blob::repeat_byte(tx[0])],
+                    commitments: vec![Bytes48::from_slice(&[tx.as_slice(), &[0; 16]].concat())],
+                    proofs: vec![Bytes48::repeat_byte(tx[1])],
+                };
+                (tx, blob)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn segmented_insert_get_delete() {
+        let (store, _dir) = tmp_store(Default::default());
+
+        let blobs = rng_blobs(10);
+        let all_hashes = blobs.iter().map(|(tx, _)| *tx).collect::<Vec<_>>();
+        store.insert_all(blobs.clone()).unwrap();
+        assert_eq!(store.blobs_len(), 10);
+        for (tx, blob) in &blobs {
+            assert_eq!(store.get(*tx).unwrap().unwrap(), *blob);
+        }
+        assert_eq!(store.get_exact(all_hashes.clone()).unwrap().len(), 10);
+
+        store.delete_all(all_hashes.clone()).unwrap();
+        // deletion is deferred until cleanup
+        assert!(store.contains(all_hashes[0]).unwrap());
+        let stat = store.cleanup();
+        assert_eq!(stat.delete_succeed, 10);
+
+        assert!(!store.contains(all_hashes[0]).unwrap());
+        assert!(store.get_all(all_hashes.clone()).unwrap().is_empty());
+        assert!(store.get_exact(all_hashes).is_err());
+        assert_eq!(store.data_size_hint(), Some(0));
+        assert_eq!(store.blobs_len(), 0);
+    }
+
+    #[test]
+    fn segmented_get_by_versioned_hashes() {
+        let (store, _dir) = tmp_store(Default::default());
+
+        let blobs = rng_blobs(3);
+        store.insert_all(blobs.clone()).unwrap();
+
+        let (_, sidecar) = &blobs[1];
+        let versioned_hash = sidecar.versioned_hashes().next().unwrap();
+        let found = store.get_by_versioned_hashes(&[B256::random(), versioned_hash]).unwrap();
+        assert_eq!(
+            found,
+            vec![
+                None,
+                Some(BlobAndProof { blob: Box::new(sidecar.blobs[0]), proof: sidecar.proofs[0] })
+            ]
+        );
+    }
+
+    #[test]
+    fn segmented_compaction() {
+        let blobs = rng_blobs(10);
+        let record_len = {
+            let mut buf = Vec::new();
+            blobs[0].1.encode(&mut buf);
+            RECORD_HEADER_LEN + buf.len() as u64
+        };
+        // two records per segment, compact segments that are half empty
+        let config = SegmentedBlobStoreConfig::default()
+            .with_max_segment_size(2 * record_len)
+            .with_min_live_percent(60);
+        let (store, dir) = tmp_store(config);
+
+        store.insert_all(blobs.clone()).unwrap();
+        assert_eq!(store.inner.state.read().segments.len(), 5);
+
+        // delete one blob of every segment and all of the first segment
+        let deleted = [0, 1, 2, 4, 6].into_iter().map(|idx| blobs[idx].0).collect::<Vec<_>>();
+        store.delete_all(deleted.clone()).unwrap();
+        store.cleanup();
+        wait_for_compaction(&store);
+
+        // every sealed segment was below the threshold and was compacted into new segments
+        let state = store.inner.state.read();
+        assert!(!state.segments.contains_key(&0));
+        assert_eq!(state.live_size(), 5 * record_len);
+        assert_eq!(state.disk_size(), 5 * record_len);
+        let files = fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(files, state.segments.len());
+        drop(state);
+
+        for (idx, (tx, blob)) in blobs.iter().enumerate() {
+            if deleted.contains(tx) {
+                assert!(store.get(*tx).unwrap().is_none());
+            } else {
+                assert_eq!(store.get(*tx).unwrap().unwrap(), *blob, "missing blob {idx}");
+            }
+        }
+    }
+
+    #[test]
+    fn segmented_retention() {
+        let (store, dir) = tmp_store(Default::default());
+        let blobs = rng_blobs(4);
+        store.insert_all(blobs.clone()).unwrap();
+        drop(store);
+
+        let config = SegmentedBlobStoreConfig::default()
+            .with_retention(Duration::ZERO)
+            .with_open(OpenDiskFileBlobStore::ReIndex);
+        let store = SegmentedBlobStore::open(dir.path(), config).unwrap();
+        assert_eq!(store.blobs_len(), 4);
+
+        // blobs the pool inserts again are referenced and don't expire
+        store.insert(blobs[0].0, blobs[0].1.clone()).unwrap();
+        let fresh = rng_blobs(1);
+        store.insert_all(fresh.clone()).unwrap();
+
+        let stat = store.cleanup();
+        assert_eq!(stat.delete_succeed, 3);
+        assert_eq!(store.blobs_len(), 2);
+        assert_eq!(store.get(blobs[0].0).unwrap().unwrap(), blobs[0].1);
+        assert_eq!(store.get(fresh[0].0).unwrap().unwrap(), fresh[0].1);
+        assert!(store.get(blobs[1].0).unwrap().is_none());
+
+        // once deleted, referenced blobs are removed
+        store.delete(blobs[0].0).unwrap();
+        assert_eq!(store.cleanup().delete_succeed, 1);
+        assert!(store.get(blobs[0].0).unwrap().is_none());
+    }
+
+    #[test]
+    fn segmented_reopen_reindex() {
+        let (store, dir) = tmp_store(Default::default());
+
+        let blobs = rng_blobs(10);
+        store.insert_all(blobs.clone()).unwrap();
+        let data_size = store.data_size_hint();
+        drop(store);
+
+        let opts = SegmentedBlobStoreConfig::default().with_open(OpenDiskFileBlobStore::ReIndex);
+        let store = SegmentedBlobStore::open(dir.path(), opts).unwrap();
+        assert_eq!(store.blobs_len(), 10);
+        assert_eq!(store.data_size_hint(), data_size);
+        for (tx, blob) in &blobs {
+            assert_eq!(store.get(*tx).unwrap().unwrap(), *blob);
+        }
+        let versioned_hash = blobs[3].1.versioned_hashes().next().unwrap();
+        assert!(store.get_by_versioned_hashes(&[versioned_hash]).unwrap()[0].is_some());
+
+        let store = SegmentedBlobStore::open(dir.path(), Default::default()).unwrap();
+        assert_eq!(store.blobs_len(), 0);
+        assert!(store.get(blobs[0].0).unwrap().is_none());
+    }
+}
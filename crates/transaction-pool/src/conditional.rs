@@ -0,0 +1,329 @@
+//! Conditional transactions, as submitted via `eth_sendRawTransactionConditional`.
+//!
+//! A conditional transaction carries a [`TransactionConditional`] that restricts the blocks it can
+//! be included in. The conditions are checked against the block following the latest block when the
+//! transaction is added to the pool and again on every canonical state change: transactions whose
+//! conditions can no longer be met are removed from the pool, and transactions whose conditions
+//! are not met yet are excluded from the best transactions, together with their descendants.
+//! Since the pool doesn't know the timestamp of the next block, payload builders re-check the block
+//! conditions against the block they build.
+
+use reth_primitives::{Address, B256, U256};
+use reth_storage_api::{errors::provider::ProviderResult, StateProvider};
+use reth_trie::HashedStorage;
+use std::collections::HashMap;
+
+/// The maximum number of storage slots and storage roots a [`TransactionConditional`] can check.
+pub const MAX_KNOWN_ACCOUNTS_COST: usize = 1000;
+
+/// The expected state of an account of a [`TransactionConditional`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(untagged))]
+pub enum KnownAccountState {
+    /// The storage root of the account.
+    StorageRoot(B256),
+    /// The values of individual storage slots of the account.
+    Slots(HashMap<B256, B256>),
+}
+
+/// The conditions of a conditional transaction.
+///
+/// All block conditions are inclusive and are checked against the block the transaction would be
+/// included in, the known accounts are checked against the state that block builds on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct TransactionConditional {
+    /// The expected storage of accounts.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub known_accounts: HashMap<Address, KnownAccountState>,
+    /// The minimum block number.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "alloy_serde::quantity::opt"
+        )
+    )]
+    pub block_number_min: Option<u64>,
+    /// The maximum block number.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "alloy_serde::quantity::opt"
+        )
+    )]
+    pub block_number_max: Option<u64>,
+    /// The minimum block timestamp.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "alloy_serde::quantity::opt"
+        )
+    )]
+    pub timestamp_min: Option<u64>,
+    /// The maximum block timestamp.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "alloy_serde::quantity::opt"
+        )
+    )]
+    pub timestamp_max: Option<u64>,
+}
+
+impl TransactionConditional {
+    /// Returns the total number of storage slots and storage roots that need to be checked.
+    pub fn known_accounts_cost(&self) -> usize {
+        self.known_accounts
+            .values()
+            .map(|state| match state {
+                KnownAccountState::StorageRoot(_) => 1,
+                KnownAccountState::Slots(slots) => slots.len(),
+            })
+            .sum()
+    }
+
+    /// Checks the block number and timestamp conditions against the block following the latest
+    /// block with the given number and timestamp.
+    ///
+    /// The timestamp of the next block isn't known yet, it's checked against the earliest possible
+    /// timestamp, one second after the latest block.
+    pub const fn check_next_block(
+        &self,
+        latest_number: u64,
+        latest_timestamp: u64,
+    ) -> ConditionalOutcome {
+        self.check_block(latest_number + 1, latest_timestamp + 1)
+    }
+
+    /// Checks the block number and timestamp conditions against the given block.
+    pub const fn check_block(&self, number: u64, timestamp: u64) -> ConditionalOutcome {
+        if let Some(max) = self.block_number_max {
+            if number > max {
+                return ConditionalOutcome::Invalid(ConditionalError::BlockNumberMax { max, number })
+            }
+        }
+        if let Some(max) = self.timestamp_max {
+            if timestamp > max {
+                return ConditionalOutcome::Invalid(ConditionalError::TimestampMax {
+                    max,
+                    timestamp,
+                })
+            }
+        }
+        if let Some(min) = self.block_number_min {
+            if number < min {
+                return ConditionalOutcome::Pending
+            }
+        }
+        if let Some(min) = self.timestamp_min {
+            if timestamp < min {
+                return ConditionalOutcome::Pending
+            }
+        }
+        ConditionalOutcome::Met
+    }
+
+    /// Checks the known accounts against the given state.
+    pub fn check_known_accounts<S: StateProvider>(
+        &self,
+        state: &S,
+    ) -> ProviderResult<ConditionalOutcome> {
+        for (address, expected) in &self.known_accounts {
+            match expected {
+                KnownAccountState::StorageRoot(root) => {
+                    let actual = state.hashed_storage_root(*address, HashedStorage::default())?;
+                    if actual != *root {
+                        return Ok(ConditionalOutcome::Invalid(
+                            ConditionalError::StorageRootMismatch(*address),
+                        ))
+                    }
+                }
+                KnownAccountState::Slots(slots) => {
+                    for (slot, value) in slots {
+                        let actual = state.storage(*address, *slot)?.unwrap_or_default();
+                        if actual != U256::from_be_bytes(value.0) {
+                            return Ok(ConditionalOutcome::Invalid(
+                                ConditionalError::StorageSlotMismatch(*address, *slot),
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(ConditionalOutcome::Met)
+    }
+}
+
+/// The result of checking a [`TransactionConditional`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionalOutcome {
+    /// All conditions are met, the transaction can be included.
+    Met,
+    /// The conditions are not met yet, but may be met by a future block.
+    Pending,
+    /// The conditions can't be met anymore.
+    Invalid(ConditionalError),
+}
+
+impl ConditionalOutcome {
+    /// Returns `true` if all conditions are met.
+    pub const fn is_met(&self) -> bool {
+        matches!(self, Self::Met)
+    }
+
+    /// Returns the outcome of checking both, `self` and `other`.
+    pub const fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Invalid(err), _) | (_, Self::Invalid(err)) => Self::Invalid(err),
+            (Self::Pending, _) | (_, Self::Pending) => Self::Pending,
+            (Self::Met, Self::Met) => Self::Met,
+        }
+    }
+}
+
+/// Reasons why the conditions of a transaction can't be met.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConditionalError {
+    /// The maximum block number has been exceeded.
+    #[error("block number {number} exceeds maximum {max}")]
+    BlockNumberMax {
+        /// The maximum block number of the conditional.
+        max: u64,
+        /// The number of the latest block.
+        number: u64,
+    },
+    /// The maximum timestamp has been exceeded.
+    #[error("block timestamp {timestamp} exceeds maximum {max}")]
+    TimestampMax {
+        /// The maximum timestamp of the conditional.
+        max: u64,
+        /// The timestamp of the latest block.
+        timestamp: u64,
+    },
+    /// The storage root of a known account doesn't match.
+    #[error("storage root of {0} doesn't match")]
+    StorageRootMismatch(Address),
+    /// A storage slot of a known account doesn't match.
+    #[error("storage slot {1} of {0} doesn't match")]
+    StorageSlotMismatch(Address, B256),
+    /// The conditional checks too many storage slots.
+    #[error("conditional checks {cost} storage slots, maximum is {max}")]
+    TooManyKnownAccounts {
+        /// The number of storage slots and roots to check.
+        cost: usize,
+        /// The maximum allowed number.
+        max: usize,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+
+    #[test]
+    fn check_block() {
+        let conditional = TransactionConditional {
+            block_number_min: Some(10),
+            block_number_max: Some(20),
+            timestamp_max: Some(1000),
+            ..Default::default()
+        };
+
+        assert_eq!(conditional.check_block(9, 0), ConditionalOutcome::Pending);
+        assert_eq!(conditional.check_block(10, 0), ConditionalOutcome::Met);
+        assert_eq!(conditional.check_block(20, 1000), ConditionalOutcome::Met);
+        assert_eq!(
+            conditional.check_block(21, 0),
+            ConditionalOutcome::Invalid(ConditionalError::BlockNumberMax { max: 20, number: 21 })
+        );
+        assert_eq!(
+            conditional.check_block(15, 1001),
+            ConditionalOutcome::Invalid(ConditionalError::TimestampMax {
+                max: 1000,
+                timestamp: 1001
+            })
+        );
+
+        // the next block is checked
+        assert_eq!(conditional.check_next_block(9, 0), ConditionalOutcome::Met);
+        assert_eq!(
+            conditional.check_next_block(20, 0),
+            ConditionalOutcome::Invalid(ConditionalError::BlockNumberMax { max: 20, number: 21 })
+        );
+        assert_eq!(
+            conditional.check_next_block(15, 1000),
+            ConditionalOutcome::Invalid(ConditionalError::TimestampMax {
+                max: 1000,
+                timestamp: 1001
+            })
+        );
+    }
+
+    #[test]
+    fn check_known_account_slots() {
+        let address = Address::random();
+        let slot = B256::with_last_byte(1);
+        let provider = MockEthProvider::default();
+        provider.add_account(
+            address,
+            ExtendedAccount::new(0, U256::ZERO).extend_storage([(slot, U256::from(42))]),
+        );
+
+        let mut conditional = TransactionConditional::default();
+        conditional.known_accounts.insert(
+            address,
+            KnownAccountState::Slots(HashMap::from([(slot, B256::from(U256::from(42)))])),
+        );
+        assert_eq!(conditional.check_known_accounts(&provider).unwrap(), ConditionalOutcome::Met);
+
+        conditional.known_accounts.insert(
+            address,
+            KnownAccountState::Slots(HashMap::from([(slot, B256::from(U256::from(1)))])),
+        );
+        assert_eq!(
+            conditional.check_known_accounts(&provider).unwrap(),
+            ConditionalOutcome::Invalid(ConditionalError::StorageSlotMismatch(address, slot))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_conditional() {
+        let conditional: TransactionConditional = serde_json::from_str(
+            r#"{
+                "knownAccounts": {
+                    "0x000000000000000000000000000000000000dead": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    "0x000000000000000000000000000000000000beef": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000002": "0x0000000000000000000000000000000000000000000000000000000000000003"
+                    }
+                },
+                "blockNumberMin": "0x10",
+                "timestampMax": "0x64"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(conditional.block_number_min, Some(16));
+        assert_eq!(conditional.block_number_max, None);
+        assert_eq!(conditional.timestamp_max, Some(100));
+        assert_eq!(
+            conditional.known_accounts
+                [&"0x000000000000000000000000000000000000dead".parse::<Address>().unwrap()],
+            KnownAccountState::StorageRoot(B256::with_last_byte(1))
+        );
+        assert_eq!(conditional.known_accounts_cost(), 2);
+    }
+}
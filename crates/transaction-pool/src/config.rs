@@ -1,9 +1,10 @@
 use crate::{
+    policy::{NoopPoolPolicy, PoolPolicy},
     pool::{NEW_TX_LISTENER_BUFFER_SIZE, PENDING_TX_LISTENER_BUFFER_SIZE},
     PoolSize, TransactionOrigin,
 };
 use reth_primitives::{Address, EIP4844_TX_TYPE_ID};
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 /// Guarantees max transactions for one sender, compatible with geth/erigon
 pub const TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER: usize = 16;
 
@@ -13,9 +14,15 @@ pub const TXPOOL_SUBPOOL_MAX_TXS_DEFAULT: usize = 10_000;
 /// The default maximum allowed size of the given subpool.
 pub const TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT: usize = 20;
 
+/// The default maximum number of bundles in the pool.
+pub const TXPOOL_MAX_BUNDLES_DEFAULT: usize = 1_000;
+
 /// The default additional validation tasks size.
 pub const DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS: usize = 1;
 
+/// The default number of tasks that validate transactions from the network in their own queue.
+pub const DEFAULT_TXPOOL_EXTERNAL_VALIDATION_TASKS: usize = 1;
+
 /// Default price bump (in %) for the transaction pool underpriced check.
 pub const DEFAULT_PRICE_BUMP: u128 = 10;
 
@@ -37,6 +44,8 @@ pub struct PoolConfig {
     pub blob_limit: SubPoolLimit,
     /// Max number of executable transaction slots guaranteed per account
     pub max_account_slots: usize,
+    /// Max number of bundles in the pool
+    pub max_bundles: usize,
     /// Price bump (in %) for the transaction pool underpriced check.
     pub price_bumps: PriceBumpConfig,
     /// How to handle locally received transactions:
@@ -46,9 +55,17 @@ pub struct PoolConfig {
     pub pending_tx_listener_buffer_size: usize,
     /// Bound on number of new transactions from `reth_network::TransactionsManager` to buffer.
     pub new_tx_listener_buffer_size: usize,
+    /// Additional rules consulted on insertion, replacement and eviction of transactions.
+    pub policy: Arc<dyn PoolPolicy>,
 }
 
 impl PoolConfig {
+    /// Sets the [`PoolPolicy`] of the pool.
+    pub fn with_policy(mut self, policy: impl PoolPolicy + 'static) -> Self {
+        self.policy = Arc::new(policy);
+        self
+    }
+
     /// Returns whether or not the size and amount constraints in any sub-pools are exceeded.
     #[inline]
     pub const fn is_exceeded(&self, pool_size: PoolSize) -> bool {
@@ -67,10 +84,12 @@ impl Default for PoolConfig {
             queued_limit: Default::default(),
             blob_limit: Default::default(),
             max_account_slots: TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
+            max_bundles: TXPOOL_MAX_BUNDLES_DEFAULT,
             price_bumps: Default::default(),
             local_transactions_config: Default::default(),
             pending_tx_listener_buffer_size: PENDING_TX_LISTENER_BUFFER_SIZE,
             new_tx_listener_buffer_size: NEW_TX_LISTENER_BUFFER_SIZE,
+            policy: Arc::new(NoopPoolPolicy),
         }
     }
 }
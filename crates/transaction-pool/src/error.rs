@@ -1,5 +1,6 @@
 //! Transaction pool errors
 
+use crate::{conditional::ConditionalError, policy::PolicyViolation};
 use reth_primitives::{Address, BlobTransactionValidationError, InvalidTransactionError, TxHash};
 
 /// Transaction pool result type.
@@ -49,10 +50,20 @@ pub enum PoolErrorKind {
     /// Thrown when the number of unique transactions of a sender exceeded the slot capacity.
     #[error("rejected due to {0} being identified as a spammer")]
     SpammerExceededCapacity(Address),
+    /// Thrown when the configured [`PoolPolicy`](crate::policy::PoolPolicy) rejected the
+    /// transaction.
+    #[error(transparent)]
+    PolicyViolation(#[from] PolicyViolation),
     /// Thrown when a new transaction is added to the pool, but then immediately discarded to
     /// respect the size limits of the pool.
     #[error("transaction discarded outright due to pool size constraints")]
     DiscardedOnInsert,
+    /// Thrown when a bundle can't be added to the pool.
+    #[error(transparent)]
+    InvalidBundle(#[from] InvalidBundleError),
+    /// Thrown when the conditions of a conditional transaction can't be met.
+    #[error(transparent)]
+    ConditionalNotMet(#[from] ConditionalError),
     /// Thrown when the transaction is considered invalid.
     #[error(transparent)]
     InvalidTransaction(#[from] InvalidPoolTransactionError),
@@ -116,10 +127,22 @@ impl PoolError {
                 // (pool lags behind) and old transaction still occupy a slot in the pool
                 false
             }
+            PoolErrorKind::PolicyViolation(_) => {
+                // the transaction is valid but the pool's policy does not admit it
+                false
+            }
             PoolErrorKind::DiscardedOnInsert => {
                 // valid tx but dropped due to size constraints
                 false
             }
+            PoolErrorKind::InvalidBundle(_) => {
+                // bundles are submitted locally and never received from peers
+                false
+            }
+            PoolErrorKind::ConditionalNotMet(_) => {
+                // conditional transactions are submitted locally and never received from peers
+                false
+            }
             PoolErrorKind::InvalidTransaction(err) => {
                 // transaction rejected because it violates constraints
                 err.is_bad_transaction()
@@ -137,6 +160,28 @@ impl PoolError {
     }
 }
 
+/// Represents errors that can happen when a bundle is added to the pool.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidBundleError {
+    /// The bundle doesn't contain any transactions.
+    #[error("bundle contains no transactions")]
+    Empty,
+    /// The bundle targets a block that has already been mined.
+    #[error("bundle targets block {target} but block {current} has already been mined")]
+    Outdated {
+        /// The block the bundle targets.
+        target: u64,
+        /// The last block the pool has seen.
+        current: u64,
+    },
+    /// Blob transactions can't be part of a bundle.
+    #[error("blob transaction {0} can't be part of a bundle")]
+    BlobTransaction(TxHash),
+    /// The bundle contains the same transaction more than once.
+    #[error("transaction {0} is included in the bundle more than once")]
+    DuplicateTransaction(TxHash),
+}
+
 /// Represents all errors that can happen when validating transactions for the pool for EIP-4844
 /// transactions
 #[derive(Debug, thiserror::Error)]
@@ -168,6 +213,33 @@ pub enum Eip4844PoolTransactionError {
     Eip4844NonceGap,
 }
 
+/// Represents all errors that can happen when validating transactions for the pool for EIP-7702
+/// transactions and transactions of accounts with delegated code.
+#[derive(Debug, thiserror::Error)]
+pub enum Eip7702PoolTransactionError {
+    /// Thrown if an EIP-7702 transaction without any authorizations arrives
+    #[error("empty authorization list")]
+    MissingEip7702AuthorizationList,
+    /// Thrown if an authorization of an EIP-7702 transaction is for a different chain
+    #[error("authorization chain id {0} does not match the chain")]
+    AuthorizationChainIdMismatch(u64),
+    /// Thrown if the authority of an authorization of an EIP-7702 transaction can't be recovered
+    #[error("invalid authorization signature")]
+    InvalidAuthorizationSignature,
+    /// Accounts with delegated code can spend their balance through any call into their code,
+    /// so only a single in-flight transaction, the one with the account's current nonce, is
+    /// accepted.
+    ///
+    /// This error is thrown on validation if a transaction of an account with delegated code
+    /// arrives with a nonce that is not the account's current nonce.
+    #[error("out of order transaction from an account with delegated code")]
+    OutOfOrderTxFromDelegated,
+    /// Thrown if a transaction is sent by an account that is the authority of a pending
+    /// EIP-7702 transaction in the pool, which will bump the account's nonce.
+    #[error("authority already reserved by a pending delegation")]
+    AuthorityReserved,
+}
+
 /// Represents errors that can happen when validating transactions for the pool
 ///
 /// See [`TransactionValidator`](crate::TransactionValidator).
@@ -198,6 +270,9 @@ pub enum InvalidPoolTransactionError {
     /// Eip-4844 related errors
     #[error(transparent)]
     Eip4844(#[from] Eip4844PoolTransactionError),
+    /// Eip-7702 related errors
+    #[error(transparent)]
+    Eip7702(#[from] Eip7702PoolTransactionError),
     /// Any other error that occurred while inserting/validating that is transaction specific
     #[error(transparent)]
     Other(Box<dyn PoolTransactionError>),
@@ -289,6 +364,21 @@ impl InvalidPoolTransactionError {
                     }
                 }
             }
+            Self::Eip7702(eip7702_err) => {
+                match eip7702_err {
+                    Eip7702PoolTransactionError::MissingEip7702AuthorizationList |
+                    Eip7702PoolTransactionError::AuthorizationChainIdMismatch(_) |
+                    Eip7702PoolTransactionError::InvalidAuthorizationSignature => {
+                        // this is a malformed transaction and should not be sent over the network
+                        true
+                    }
+                    Eip7702PoolTransactionError::OutOfOrderTxFromDelegated |
+                    Eip7702PoolTransactionError::AuthorityReserved => {
+                        // these depend on the state of the chain and the pool
+                        false
+                    }
+                }
+            }
         }
     }
 
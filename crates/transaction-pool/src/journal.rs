@@ -0,0 +1,659 @@
+//! Journal that persists the entire transaction pool across restarts.
+//!
+//! Unlike the local transactions backup (see
+//! [`backup_local_transactions_task`](crate::maintain::backup_local_transactions_task)), which only
+//! saves local transactions on shutdown, the journal records every transaction of every subpool
+//! together with its [`TransactionOrigin`] as soon as it enters the pool, and records its removal
+//! when it leaves the pool. On startup the journaled transactions are validated and reinserted
+//! into the pool.
+//!
+//! Blob sidecars are not journaled, they are expected to be kept by the blob store, see
+//! [`OpenDiskFileBlobStore::ReIndex`](crate::blobstore::OpenDiskFileBlobStore::ReIndex). Once the
+//! journal is replayed, the blobs of transactions that were not reinserted are deleted.
+
+use crate::{
+    error::PoolError, AllPoolTransactions, EthPoolTransaction, FullTransactionEvent,
+    PoolTransaction, TransactionOrigin, TransactionPool, TransactionPoolExt,
+};
+use alloy_rlp::{Buf, BufMut, Decodable, Encodable, Header};
+use futures_util::{FutureExt, StreamExt};
+use reth_fs_util::FsPathError;
+use reth_primitives::{
+    IntoRecoveredTransaction, PooledTransactionsElementEcRecovered, TransactionSigned, TxHash,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error, info, trace, warn};
+
+/// Number of stale journal entries after which the journal is compacted by default.
+pub const DEFAULT_JOURNAL_COMPACTION_THRESHOLD: usize = 10_000;
+
+/// Settings for the transaction pool journal task.
+#[derive(Debug, Clone)]
+pub struct TransactionJournalConfig {
+    /// Path to the journal file, the journal is disabled if unset.
+    pub journal_path: Option<PathBuf>,
+    /// Number of entries of transactions that left the pool after which the journal is rewritten
+    /// from the current pool content.
+    pub compaction_threshold: usize,
+}
+
+impl TransactionJournalConfig {
+    /// Receive path to the journal file and return initialized config
+    pub const fn with_journal(journal_path: PathBuf) -> Self {
+        Self {
+            journal_path: Some(journal_path),
+            compaction_threshold: DEFAULT_JOURNAL_COMPACTION_THRESHOLD,
+        }
+    }
+
+    /// Sets the number of stale entries after which the journal is compacted.
+    pub const fn with_compaction_threshold(mut self, compaction_threshold: usize) -> Self {
+        self.compaction_threshold = compaction_threshold;
+        self
+    }
+}
+
+impl Default for TransactionJournalConfig {
+    fn default() -> Self {
+        Self { journal_path: None, compaction_threshold: DEFAULT_JOURNAL_COMPACTION_THRESHOLD }
+    }
+}
+
+/// A record of the transaction pool journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalEntry {
+    /// A transaction entered the pool.
+    Add {
+        /// Where the transaction originates from.
+        origin: TransactionOrigin,
+        /// The transaction, without blob sidecar.
+        transaction: Box<TransactionSigned>,
+    },
+    /// A transaction left the pool.
+    Remove(TxHash),
+}
+
+impl JournalEntry {
+    const ADD: u8 = 0;
+    const REMOVE: u8 = 1;
+
+    const fn encode_origin(origin: TransactionOrigin) -> u8 {
+        match origin {
+            TransactionOrigin::Local => 0,
+            TransactionOrigin::External => 1,
+            TransactionOrigin::Private => 2,
+        }
+    }
+
+    fn decode_origin(buf: &mut &[u8]) -> alloy_rlp::Result<TransactionOrigin> {
+        match u8::decode(buf)? {
+            0 => Ok(TransactionOrigin::Local),
+            1 => Ok(TransactionOrigin::External),
+            2 => Ok(TransactionOrigin::Private),
+            _ => Err(alloy_rlp::Error::Custom("unknown transaction origin")),
+        }
+    }
+
+    fn payload_length(&self) -> usize {
+        match self {
+            Self::Add { origin, transaction } => {
+                Self::ADD.length() + Self::encode_origin(*origin).length() + transaction.length()
+            }
+            Self::Remove(hash) => Self::REMOVE.length() + hash.length(),
+        }
+    }
+}
+
+impl Encodable for JournalEntry {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header { list: true, payload_length: self.payload_length() }.encode(out);
+        match self {
+            Self::Add { origin, transaction } => {
+                Self::ADD.encode(out);
+                Self::encode_origin(*origin).encode(out);
+                transaction.encode(out);
+            }
+            Self::Remove(hash) => {
+                Self::REMOVE.encode(out);
+                hash.encode(out);
+            }
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + alloy_rlp::length_of_length(payload_length)
+    }
+}
+
+impl Decodable for JournalEntry {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(alloy_rlp::Error::UnexpectedString)
+        }
+        if buf.len() < header.payload_length {
+            return Err(alloy_rlp::Error::InputTooShort)
+        }
+        let started_len = buf.len();
+
+        let entry = match u8::decode(buf)? {
+            Self::ADD => {
+                let origin = Self::decode_origin(buf)?;
+                let transaction = Box::new(TransactionSigned::decode(buf)?);
+                Self::Add { origin, transaction }
+            }
+            Self::REMOVE => Self::Remove(TxHash::decode(buf)?),
+            _ => return Err(alloy_rlp::Error::Custom("unknown journal entry")),
+        };
+
+        let consumed = started_len - buf.len();
+        if consumed != header.payload_length {
+            return Err(alloy_rlp::Error::ListLengthMismatch {
+                expected: header.payload_length,
+                got: consumed,
+            })
+        }
+        Ok(entry)
+    }
+}
+
+/// Append-only file of [`JournalEntry`]s.
+#[derive(Debug)]
+pub struct TransactionJournal {
+    /// Path to the journal file.
+    path: PathBuf,
+    /// Appends entries to the journal file.
+    writer: BufWriter<File>,
+    /// Number of entries in the journal file.
+    entries: usize,
+}
+
+impl TransactionJournal {
+    /// Reads the journal file at the given path and returns the transactions that are still in
+    /// the pool, in the order they entered it.
+    ///
+    /// A truncated entry at the end of the journal, e.g. due to a crash, is skipped.
+    pub fn load(
+        path: &Path,
+    ) -> Result<Vec<(TransactionOrigin, TransactionSigned)>, TransactionJournalError> {
+        if !path.exists() {
+            return Ok(Vec::new())
+        }
+
+        let data = reth_fs_util::read(path)?;
+        let mut buf = data.as_slice();
+
+        let mut transactions = Vec::new();
+        let mut index = HashMap::new();
+        while buf.has_remaining() {
+            let entry = match JournalEntry::decode(&mut buf) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    warn!(target: "txpool", %err, journal=?path, "Skipping corrupted tail of transaction journal");
+                    break
+                }
+            };
+            match entry {
+                JournalEntry::Add { origin, transaction } => {
+                    if let Some(idx) = index.insert(transaction.hash(), transactions.len()) {
+                        transactions[idx] = None;
+                    }
+                    transactions.push(Some((origin, *transaction)));
+                }
+                JournalEntry::Remove(hash) => {
+                    if let Some(idx) = index.remove(&hash) {
+                        transactions[idx] = None;
+                    }
+                }
+            }
+        }
+
+        Ok(transactions.into_iter().flatten().collect())
+    }
+
+    /// Replaces the journal file at the given path with one that contains the given transactions,
+    /// and opens it for appending.
+    pub fn create(
+        path: impl Into<PathBuf>,
+        transactions: impl IntoIterator<Item = (TransactionOrigin, TransactionSigned)>,
+    ) -> Result<Self, TransactionJournalError> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            reth_fs_util::create_dir_all(parent)?;
+        }
+
+        // write to a temporary file first, so that the journal is never lost
+        let tmp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(reth_fs_util::create_file(&tmp_path)?);
+        let mut entries = 0;
+        let mut buf = Vec::new();
+        for (origin, transaction) in transactions {
+            buf.clear();
+            JournalEntry::Add { origin, transaction: Box::new(transaction) }.encode(&mut buf);
+            writer.write_all(&buf).map_err(|err| FsPathError::write(err, &tmp_path))?;
+            entries += 1;
+        }
+        writer.flush().map_err(|err| FsPathError::write(err, &tmp_path))?;
+        drop(writer);
+        reth_fs_util::rename(&tmp_path, &path)?;
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map_err(|err| FsPathError::open(err, &path))?;
+
+        Ok(Self { path, writer: BufWriter::new(file), entries })
+    }
+
+    /// Appends the entry to the journal.
+    ///
+    /// The entry is buffered until [`TransactionJournal::flush`] is called.
+    pub fn append(&mut self, entry: &JournalEntry) -> Result<(), TransactionJournalError> {
+        let mut buf = Vec::with_capacity(entry.length());
+        entry.encode(&mut buf);
+        self.writer.write_all(&buf).map_err(|err| FsPathError::write(err, &self.path))?;
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Writes all buffered entries to the journal file.
+    pub fn flush(&mut self) -> Result<(), TransactionJournalError> {
+        self.writer.flush().map_err(|err| FsPathError::write(err, &self.path))?;
+        Ok(())
+    }
+
+    /// Returns the number of entries in the journal.
+    pub const fn len(&self) -> usize {
+        self.entries
+    }
+
+    /// Returns `true` if the journal has no entries.
+    pub const fn is_empty(&self) -> bool {
+        self.entries == 0
+    }
+
+    /// Returns the path to the journal file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Errors possible while reading or writing the transaction pool journal.
+#[derive(thiserror::Error, Debug)]
+pub enum TransactionJournalError {
+    /// Error during file access
+    #[error("transaction journal file error: {0}")]
+    FsPath(#[from] FsPathError),
+    /// Error adding transactions to the transaction pool
+    #[error("failed to reinsert journaled transactions. Encountered pool error: {0}")]
+    Pool(#[from] PoolError),
+}
+
+/// Reinserts the journaled transactions into the pool.
+///
+/// Blob transactions are reinserted with their sidecars from the pool's blob store, and skipped if
+/// the blob store doesn't have them.
+async fn reinsert_journaled_transactions<P>(
+    pool: &P,
+    transactions: Vec<(TransactionOrigin, TransactionSigned)>,
+) -> usize
+where
+    P: TransactionPool,
+    P::Transaction: EthPoolTransaction,
+{
+    let mut by_origin: HashMap<TransactionOrigin, Vec<P::Transaction>> = HashMap::new();
+    for (origin, transaction) in transactions {
+        let Some(transaction) = transaction.try_ecrecovered() else { continue };
+        let transaction = if transaction.is_eip4844() {
+            let Ok(Some(sidecar)) = pool.get_blob(transaction.hash()) else {
+                trace!(target: "txpool", hash=%transaction.hash(), "Missing sidecar of journaled blob transaction");
+                continue
+            };
+            let Ok(pooled) = PooledTransactionsElementEcRecovered::try_from_blob_transaction(
+                transaction,
+                sidecar,
+            ) else {
+                continue
+            };
+            P::Transaction::from_pooled(pooled)
+        } else {
+            let Ok(transaction) = P::Transaction::try_from_consensus(transaction) else { continue };
+            transaction
+        };
+        by_origin.entry(origin).or_default().push(transaction);
+    }
+
+    let mut reinserted = 0;
+    for (origin, transactions) in by_origin {
+        let outcome = pool.add_transactions(origin, transactions).await;
+        reinserted += outcome.iter().filter(|res| res.is_ok()).count();
+    }
+    reinserted
+}
+
+/// Deletes the given blobs of the blob store whose transactions are not in the pool, and returns
+/// the number of deleted blobs.
+fn delete_orphaned_blobs<P>(pool: &P, blob_hashes: Vec<TxHash>) -> usize
+where
+    P: TransactionPoolExt,
+{
+    let orphaned = blob_hashes.into_iter().filter(|hash| !pool.contains(hash)).collect::<Vec<_>>();
+    let num_orphaned = orphaned.len();
+    if num_orphaned > 0 {
+        pool.delete_blobs(orphaned);
+        pool.cleanup_blobs();
+    }
+    num_orphaned
+}
+
+/// Returns all transactions of the pool, with their origin.
+fn pool_snapshot<P>(pool: &P) -> Vec<(TransactionOrigin, TransactionSigned)>
+where
+    P: TransactionPool,
+{
+    let AllPoolTransactions { pending, queued } = pool.all_transactions();
+    pending
+        .into_iter()
+        .chain(queued)
+        .map(|tx| (tx.origin, tx.to_recovered_transaction().into_signed()))
+        .collect()
+}
+
+/// Tracks the transactions recorded in the journal.
+#[derive(Debug)]
+struct JournalState {
+    journal: TransactionJournal,
+    /// Hashes of the journaled transactions that are still in the pool.
+    live: HashSet<TxHash>,
+    compaction_threshold: usize,
+}
+
+impl JournalState {
+    fn new<P: TransactionPool>(
+        pool: &P,
+        path: &Path,
+        compaction_threshold: usize,
+    ) -> Result<Self, TransactionJournalError> {
+        let snapshot = pool_snapshot(pool);
+        let live = snapshot.iter().map(|(_, tx)| tx.hash()).collect();
+        let journal = TransactionJournal::create(path, snapshot)?;
+        Ok(Self { journal, live, compaction_threshold })
+    }
+
+    fn on_event<P: TransactionPool>(
+        &mut self,
+        pool: &P,
+        event: FullTransactionEvent<P::Transaction>,
+    ) -> Result<(), TransactionJournalError> {
+        match event {
+            FullTransactionEvent::Pending(hash) | FullTransactionEvent::Queued(hash) => {
+                if self.live.contains(&hash) {
+                    return Ok(())
+                }
+                let Some(tx) = pool.get(&hash) else { return Ok(()) };
+                let transaction = Box::new(tx.to_recovered_transaction().into_signed());
+                self.journal.append(&JournalEntry::Add { origin: tx.origin, transaction })?;
+                self.live.insert(hash);
+            }
+            FullTransactionEvent::Mined { tx_hash: hash, .. } |
+            FullTransactionEvent::Discarded(hash) |
+            FullTransactionEvent::Invalid(hash) => self.remove(hash)?,
+            FullTransactionEvent::Replaced { transaction, .. } => {
+                self.remove(*transaction.hash())?
+            }
+            FullTransactionEvent::Propagated(_) => {}
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, hash: TxHash) -> Result<(), TransactionJournalError> {
+        if self.live.remove(&hash) {
+            self.journal.append(&JournalEntry::Remove(hash))?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites the journal from the current pool content if it has too many stale entries.
+    fn maybe_compact<P: TransactionPool>(
+        &mut self,
+        pool: &P,
+    ) -> Result<(), TransactionJournalError> {
+        let stale = self.journal.len().saturating_sub(self.live.len());
+        if stale >= self.compaction_threshold {
+            debug!(target: "txpool", journal=?self.journal.path(), stale, "Compacting transaction journal");
+            *self = Self::new(pool, self.journal.path(), self.compaction_threshold)?;
+        }
+        Ok(())
+    }
+}
+
+/// Task which persists the entire transaction pool to a journal file as it changes.
+///
+/// On startup the journaled transactions are validated and reinserted into the pool, and the blobs
+/// the blob store kept for transactions that were not reinserted are deleted. The journal is
+/// rewritten from the pool content on shutdown.
+pub async fn journal_transactions_task<P>(
+    shutdown: reth_tasks::shutdown::GracefulShutdown,
+    pool: P,
+    config: TransactionJournalConfig,
+) where
+    P: TransactionPoolExt + Clone,
+    P::Transaction: EthPoolTransaction,
+{
+    let TransactionJournalConfig { journal_path, compaction_threshold } = config;
+    let Some(journal_path) = journal_path else {
+        // nothing to do
+        return
+    };
+
+    match TransactionJournal::load(&journal_path) {
+        Ok(transactions) => {
+            // only blobs that were kept from before the restart can be orphaned
+            let blob_hashes = pool.get_all_blob_hashes().unwrap_or_else(|err| {
+                warn!(target: "txpool", %err, "Failed to list blobs of the blob store");
+                Vec::new()
+            });
+
+            let num_journaled = transactions.len();
+            let num_reinserted = reinsert_journaled_transactions(&pool, transactions).await;
+            let num_orphaned_blobs = delete_orphaned_blobs(&pool, blob_hashes);
+            info!(target: "txpool", journal=?journal_path, num_journaled, num_reinserted, num_orphaned_blobs, "Reinserted journaled transactions");
+        }
+        Err(err) => {
+            error!(target: "txpool", %err, journal=?journal_path, "Failed to load transaction journal")
+        }
+    }
+
+    // subscribe before taking the snapshot, so that no changes are missed
+    let mut events = pool.all_transactions_event_listener().take_until(shutdown);
+
+    let mut state = match JournalState::new(&pool, &journal_path, compaction_threshold) {
+        Ok(state) => state,
+        Err(err) => {
+            error!(target: "txpool", %err, journal=?journal_path, "Failed to create transaction journal");
+            return
+        }
+    };
+
+    while let Some(event) = events.next().await {
+        let mut res = state.on_event(&pool, event);
+        // drain all ready events before writing to disk
+        while res.is_ok() {
+            let Some(Some(event)) = events.next().now_or_never() else { break };
+            res = state.on_event(&pool, event);
+        }
+
+        if let Err(err) =
+            res.and_then(|_| state.journal.flush()).and_then(|_| state.maybe_compact(&pool))
+        {
+            warn!(target: "txpool", %err, journal=?journal_path, "Failed to write transaction journal");
+        }
+    }
+
+    let graceful_guard = events.take_result();
+
+    // write the final pool content to disk
+    match JournalState::new(&pool, &journal_path, compaction_threshold) {
+        Ok(state) => {
+            info!(target: "txpool", journal=?journal_path, num_txs=%state.live.len(), "Wrote transaction journal")
+        }
+        Err(err) => {
+            warn!(target: "txpool", %err, journal=?journal_path, "Failed to write transaction journal")
+        }
+    }
+
+    drop(graceful_guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blobstore::{BlobStore, InMemoryBlobStore},
+        validate::EthTransactionValidatorBuilder,
+        CoinbaseTipOrdering, EthPooledTransaction, Pool,
+    };
+    use reth_chainspec::MAINNET;
+    use reth_primitives::{hex, BlobTransactionSidecar, PooledTransactionsElement, U256};
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_tasks::TaskManager;
+    use std::time::Duration;
+
+    fn transaction() -> PooledTransactionsElement {
+        let tx_bytes = hex!("02f87201830655c2808505ef61f08482565f94388c818ca8b9251b393131c08a736a67ccb192978801049e39c4b5b1f580c001a01764ace353514e8abdfb92446de356b260e3c1225b73fc4c8876a6258d12a129a04f02294aa61ca7676061cd99f29275491218b4754b46a0248e5e42bc5091f507");
+        PooledTransactionsElement::decode_enveloped(&mut &tx_bytes[..]).unwrap()
+    }
+
+    #[test]
+    fn journal_load_skips_removed_and_truncated_entries() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("journal.rlp");
+        let tx = transaction().into_transaction();
+
+        let mut journal = TransactionJournal::create(&path, []).unwrap();
+        journal
+            .append(&JournalEntry::Add {
+                origin: TransactionOrigin::External,
+                transaction: Box::new(tx.clone()),
+            })
+            .unwrap();
+        journal.flush().unwrap();
+        assert_eq!(
+            TransactionJournal::load(&path).unwrap(),
+            vec![(TransactionOrigin::External, tx.clone())]
+        );
+
+        journal.append(&JournalEntry::Remove(tx.hash())).unwrap();
+        journal.flush().unwrap();
+        assert_eq!(journal.len(), 2);
+        assert!(TransactionJournal::load(&path).unwrap().is_empty());
+
+        // re-adding after removal and a truncated entry at the end
+        journal
+            .append(&JournalEntry::Add {
+                origin: TransactionOrigin::Private,
+                transaction: Box::new(tx.clone()),
+            })
+            .unwrap();
+        journal.flush().unwrap();
+        let mut data = reth_fs_util::read(&path).unwrap();
+        let mut entry = Vec::new();
+        JournalEntry::Remove(tx.hash()).encode(&mut entry);
+        data.extend_from_slice(&entry[..entry.len() - 1]);
+        reth_fs_util::write(&path, data).unwrap();
+
+        assert_eq!(
+            TransactionJournal::load(&path).unwrap(),
+            vec![(TransactionOrigin::Private, tx)]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn journal_and_reinsert_pool() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("journal.rlp");
+        let config = TransactionJournalConfig::with_journal(path.clone());
+
+        let transaction: EthPooledTransaction =
+            transaction().try_into_ecrecovered().unwrap().into();
+        let hash = *transaction.hash();
+        let provider = MockEthProvider::default();
+        let sender = hex!("1f9090aaE28b8a3dCeaDf281B0F12828e676c326").into();
+        provider.add_account(sender, ExtendedAccount::new(42, U256::MAX));
+
+        let new_pool = |blob_store: InMemoryBlobStore| {
+            let validator = EthTransactionValidatorBuilder::new(MAINNET.clone())
+                .build(provider.clone(), blob_store.clone());
+            Pool::new(validator, CoinbaseTipOrdering::default(), blob_store, Default::default())
+        };
+
+        // journal the pool while it changes
+        let txpool = new_pool(Default::default());
+        let manager = TaskManager::new(tokio::runtime::Handle::current());
+        manager.executor().spawn_critical_with_graceful_shutdown_signal("journal", |shutdown| {
+            journal_transactions_task(shutdown, txpool.clone(), config.clone())
+        });
+
+        // wait for the journal to be created
+        while !path.exists() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        txpool.add_transaction(TransactionOrigin::External, transaction).await.unwrap();
+
+        let mut journaled = Vec::new();
+        for _ in 0..100 {
+            journaled = TransactionJournal::load(&path).unwrap();
+            if !journaled.is_empty() {
+                break
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(journaled.len(), 1);
+        assert_eq!(journaled[0].0, TransactionOrigin::External);
+        assert_eq!(journaled[0].1.hash(), hash);
+
+        manager.graceful_shutdown();
+        assert_eq!(TransactionJournal::load(&path).unwrap().len(), 1);
+
+        // reinsert into a fresh pool, with a blob store that kept blobs from before the restart
+        let blob_store = InMemoryBlobStore::default();
+        let orphaned = TxHash::with_last_byte(1);
+        let sidecar = BlobTransactionSidecar { blobs: vec![], commitments: vec![], proofs: vec![] };
+        blob_store.insert_all(vec![(hash, sidecar.clone()), (orphaned, sidecar)]).unwrap();
+        let txpool = new_pool(blob_store.clone());
+        let manager = TaskManager::new(tokio::runtime::Handle::current());
+        manager.executor().spawn_critical_with_graceful_shutdown_signal("journal", |shutdown| {
+            journal_transactions_task(shutdown, txpool.clone(), config.clone())
+        });
+
+        let mut reinserted = None;
+        for _ in 0..100 {
+            reinserted = txpool.get(&hash);
+            if reinserted.is_some() {
+                break
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(reinserted.expect("transaction reinserted").origin, TransactionOrigin::External);
+
+        // the blob without pool transaction is deleted once the journal is replayed
+        for _ in 0..100 {
+            if !blob_store.contains(orphaned).unwrap() {
+                break
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!blob_store.contains(orphaned).unwrap());
+        assert!(blob_store.contains(hash).unwrap());
+
+        txpool.remove_transactions(vec![hash]);
+        manager.graceful_shutdown();
+        assert!(TransactionJournal::load(&path).unwrap().is_empty());
+    }
+}
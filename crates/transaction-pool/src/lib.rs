@@ -150,29 +150,37 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 
-use crate::{identifier::TransactionId, pool::PoolInner};
+use crate::{
+    error::{InvalidBundleError, PoolError},
+    identifier::TransactionId,
+    pool::PoolInner,
+};
 use aquamarine as _;
 use reth_eth_wire_types::HandleMempoolData;
 use reth_execution_types::ChangedAccount;
-use reth_primitives::{Address, BlobTransactionSidecar, PooledTransactionsElement, TxHash, U256};
+use reth_primitives::{
+    Address, BlobTransactionSidecar, PooledTransactionsElement, TxHash, B256, U256,
+};
 use reth_storage_api::StateProviderFactory;
 use std::{collections::HashSet, sync::Arc};
 use tokio::sync::mpsc::Receiver;
 use tracing::{instrument, trace};
 
 pub use crate::{
-    blobstore::{BlobStore, BlobStoreError},
+    blobstore::{BlobAndProof, BlobStore, BlobStoreError},
+    conditional::TransactionConditional,
     config::{
         LocalTransactionConfig, PoolConfig, PriceBumpConfig, SubPoolLimit, DEFAULT_PRICE_BUMP,
-        DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS, REPLACE_BLOB_PRICE_BUMP,
-        TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER, TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
-        TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
+        DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS, DEFAULT_TXPOOL_EXTERNAL_VALIDATION_TASKS,
+        REPLACE_BLOB_PRICE_BUMP, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER, TXPOOL_MAX_BUNDLES_DEFAULT,
+        TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT, TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
     },
     error::PoolResult,
     ordering::{CoinbaseTipOrdering, Priority, TransactionOrdering},
     pool::{
-        blob_tx_priority, fee_delta, state::SubPool, AllTransactionsEvents, FullTransactionEvent,
-        TransactionEvent, TransactionEvents,
+        blob_tx_priority, fee_delta, state::SubPool, AllTransactionsEvents,
+        BestBundlesAndTransactions, BundleOrTransaction, FullTransactionEvent, NewBundle,
+        PoolBundle, TransactionEvent, TransactionEvents,
     },
     traits::*,
     validate::{
@@ -182,13 +190,19 @@ pub use crate::{
 };
 
 pub mod error;
+pub mod journal;
 pub mod maintain;
 pub mod metrics;
 pub mod noop;
+pub mod policy;
 pub mod pool;
+pub mod rate_limit;
+pub mod record;
+pub mod replay;
 pub mod validate;
 
 pub mod blobstore;
+pub mod conditional;
 mod config;
 pub mod identifier;
 mod ordering;
@@ -367,6 +381,55 @@ where
         self.pool.add_transactions(origin, validated.into_iter().map(|(_, tx)| tx))
     }
 
+    async fn add_conditional_transaction(
+        &self,
+        origin: TransactionOrigin,
+        transaction: Self::Transaction,
+        conditional: TransactionConditional,
+    ) -> PoolResult<TxHash> {
+        let (_, tx) = self.validate(origin, transaction).await;
+        self.pool.add_conditional_transaction(origin, tx, conditional)
+    }
+
+    async fn add_bundle(
+        &self,
+        origin: TransactionOrigin,
+        bundle: NewBundle<Self::Transaction>,
+    ) -> PoolResult<B256> {
+        let NewBundle { transactions, reverting_tx_hashes, block_number } = bundle;
+        let Some(first) = transactions.first() else {
+            return Err(PoolError::new(B256::ZERO, InvalidBundleError::Empty))
+        };
+        let first = *first.hash();
+
+        let mut hashes = HashSet::with_capacity(transactions.len());
+        for tx in &transactions {
+            let hash = *tx.hash();
+            if tx.is_eip4844() {
+                return Err(PoolError::new(first, InvalidBundleError::BlobTransaction(hash)))
+            }
+            if !hashes.insert(hash) {
+                return Err(PoolError::new(first, InvalidBundleError::DuplicateTransaction(hash)))
+            }
+        }
+
+        let validated = self.validate_all(origin, transactions).await;
+        self.pool.add_bundle(
+            origin,
+            validated.into_iter().map(|(_, tx)| tx).collect(),
+            reverting_tx_hashes,
+            block_number,
+        )
+    }
+
+    fn remove_bundle(&self, hash: B256) -> Option<Arc<PoolBundle<Self::Transaction>>> {
+        self.pool.remove_bundle(&hash)
+    }
+
+    fn get_bundle(&self, hash: B256) -> Option<Arc<PoolBundle<Self::Transaction>>> {
+        self.pool.get_bundle(&hash)
+    }
+
     fn transaction_event_listener(&self, tx_hash: TxHash) -> Option<TransactionEvents> {
         self.pool.add_transaction_event_listener(tx_hash)
     }
@@ -424,7 +487,7 @@ where
     fn best_transactions(
         &self,
     ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<Self::Transaction>>>> {
-        Box::new(self.pool.best_transactions())
+        self.pool.best_transactions()
     }
 
     fn best_transactions_with_base_fee(
@@ -441,6 +504,14 @@ where
         self.pool.best_transactions_with_attributes(best_transactions_attributes)
     }
 
+    fn best_bundles_and_transactions(
+        &self,
+        best_transactions_attributes: BestTransactionsAttributes,
+        block_number: u64,
+    ) -> BestBundlesAndTransactions<Self::Transaction> {
+        self.pool.best_bundles_and_transactions(best_transactions_attributes, block_number)
+    }
+
     fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
         self.pool.pending_transactions()
     }
@@ -518,6 +589,10 @@ where
         self.pool.blob_store().get_all(tx_hashes)
     }
 
+    fn get_all_blob_hashes(&self) -> Result<Vec<TxHash>, BlobStoreError> {
+        self.pool.blob_store().all_hashes()
+    }
+
     fn get_all_blobs_exact(
         &self,
         tx_hashes: Vec<TxHash>,
@@ -525,6 +600,13 @@ where
         self.pool.blob_store().get_exact(tx_hashes)
     }
 
+    fn get_blobs_for_versioned_hashes(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProof>>, BlobStoreError> {
+        self.pool.blob_store().get_by_versioned_hashes(versioned_hashes)
+    }
+
     /// Returns all pending transactions filtered by [`TransactionOrigin`]
     fn get_pending_transactions_by_origin(
         &self,
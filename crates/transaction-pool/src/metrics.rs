@@ -57,6 +57,22 @@ pub struct BlobStoreMetrics {
     pub(crate) blobstore_entries: Gauge,
 }
 
+/// Segmented blobstore metrics
+#[derive(Metrics)]
+#[metrics(scope = "transaction_pool")]
+pub struct SegmentedBlobStoreMetrics {
+    /// The number of bytes the blob segments take up on disk, including data not yet compacted
+    pub(crate) blobstore_disk_usage: Gauge,
+    /// The number of bytes of blobs that are still live
+    pub(crate) blobstore_live_bytes: Gauge,
+    /// How many segment files are currently on disk
+    pub(crate) blobstore_segments: Gauge,
+    /// Number of segments that were compacted
+    pub(crate) blobstore_compacted_segments: Counter,
+    /// Number of blobs that were removed because they fell out of the retention window
+    pub(crate) blobstore_expired_blobs: Counter,
+}
+
 /// Transaction pool maintenance metrics
 #[derive(Metrics)]
 #[metrics(scope = "transaction_pool")]
@@ -4,8 +4,9 @@
 //! to be generic over it.
 
 use crate::{
-    blobstore::BlobStoreError,
+    blobstore::{BlobAndProof, BlobStoreError},
     error::PoolError,
+    pool::bundle::{BestBundlesAndTransactions, NewBundle, PoolBundle},
     traits::{
         BestTransactionsAttributes, GetPooledTransactionLimit, NewBlobSidecar,
         TransactionListenerKind,
@@ -13,11 +14,12 @@ use crate::{
     validate::ValidTransaction,
     AllPoolTransactions, AllTransactionsEvents, BestTransactions, BlockInfo, EthPoolTransaction,
     EthPooledTransaction, NewTransactionEvent, PoolResult, PoolSize, PoolTransaction,
-    PooledTransactionsElement, PropagatedTransactions, TransactionEvents, TransactionOrigin,
-    TransactionPool, TransactionValidationOutcome, TransactionValidator, ValidPoolTransaction,
+    PooledTransactionsElement, PropagatedTransactions, TransactionConditional, TransactionEvents,
+    TransactionOrigin, TransactionPool, TransactionValidationOutcome, TransactionValidator,
+    ValidPoolTransaction,
 };
 use reth_eth_wire_types::HandleMempoolData;
-use reth_primitives::{Address, BlobTransactionSidecar, TxHash, U256};
+use reth_primitives::{Address, BlobTransactionSidecar, TxHash, B256, U256};
 use std::{collections::HashSet, marker::PhantomData, sync::Arc};
 use tokio::sync::{mpsc, mpsc::Receiver};
 
@@ -77,6 +79,33 @@ impl TransactionPool for NoopTransactionPool {
             .collect()
     }
 
+    async fn add_conditional_transaction(
+        &self,
+        _origin: TransactionOrigin,
+        transaction: Self::Transaction,
+        _conditional: TransactionConditional,
+    ) -> PoolResult<TxHash> {
+        let hash = *transaction.hash();
+        Err(PoolError::other(hash, Box::new(NoopInsertError::new(transaction))))
+    }
+
+    async fn add_bundle(
+        &self,
+        _origin: TransactionOrigin,
+        bundle: NewBundle<Self::Transaction>,
+    ) -> PoolResult<B256> {
+        let hash = bundle.transactions.first().map(|tx| *tx.hash()).unwrap_or_default();
+        Err(PoolError::other(hash, "bundles are not supported by the noop pool"))
+    }
+
+    fn remove_bundle(&self, _hash: B256) -> Option<Arc<PoolBundle<Self::Transaction>>> {
+        None
+    }
+
+    fn get_bundle(&self, _hash: B256) -> Option<Arc<PoolBundle<Self::Transaction>>> {
+        None
+    }
+
     fn transaction_event_listener(&self, _tx_hash: TxHash) -> Option<TransactionEvents> {
         None
     }
@@ -161,6 +190,14 @@ impl TransactionPool for NoopTransactionPool {
         Box::new(std::iter::empty())
     }
 
+    fn best_bundles_and_transactions(
+        &self,
+        _: BestTransactionsAttributes,
+        _: u64,
+    ) -> BestBundlesAndTransactions<Self::Transaction> {
+        BestBundlesAndTransactions::transactions_only(Box::new(std::iter::empty()))
+    }
+
     fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
         vec![]
     }
@@ -233,6 +270,10 @@ impl TransactionPool for NoopTransactionPool {
         Ok(vec![])
     }
 
+    fn get_all_blob_hashes(&self) -> Result<Vec<TxHash>, BlobStoreError> {
+        Ok(vec![])
+    }
+
     fn get_all_blobs_exact(
         &self,
         tx_hashes: Vec<TxHash>,
@@ -243,6 +284,13 @@ impl TransactionPool for NoopTransactionPool {
         Err(BlobStoreError::MissingSidecar(tx_hashes[0]))
     }
 
+    fn get_blobs_for_versioned_hashes(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProof>>, BlobStoreError> {
+        Ok(vec![None; versioned_hashes.len()])
+    }
+
     fn get_pending_transactions_by_origin(
         &self,
         _origin: TransactionOrigin,
@@ -279,6 +327,8 @@ impl<T: EthPoolTransaction> TransactionValidator for MockTransactionValidator<T>
                 TransactionOrigin::Local => self.propagate_local,
                 TransactionOrigin::Private => false,
             },
+            authorities: None,
+            simulation: None,
         }
     }
 }
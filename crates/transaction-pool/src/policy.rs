@@ -0,0 +1,399 @@
+//! Pluggable admission and eviction policies for the transaction pool.
+//!
+//! The pool enforces a fixed set of rules on its own: subpool size limits, replacement price bumps
+//! and the number of slots per account. A [`PoolPolicy`] configured via
+//! [`PoolConfig::policy`](crate::PoolConfig::policy) is consulted in addition to these rules when
+//! a transaction is inserted, when it replaces an existing transaction and when the pool needs to
+//! evict transactions because a subpool exceeds its limits.
+//!
+//! This module ships a few policies that can be combined, see [`SenderQuotaPolicy`],
+//! [`SpamScorePolicy`] and [`TrustedSendersPolicy`].
+
+use crate::{error::PoolErrorKind, PoolTransaction, TransactionOrigin, ValidPoolTransaction};
+use parking_lot::Mutex;
+use reth_primitives::{Address, TxHash};
+use schnellru::{ByLength, LruMap};
+use std::{collections::HashSet, fmt};
+
+/// The default number of senders tracked by the [`SpamScorePolicy`].
+pub const DEFAULT_SPAM_SCORE_TRACKED_SENDERS: u32 = 4096;
+
+/// Rules consulted by the pool on insertion, replacement and eviction of transactions.
+///
+/// All functions have permissive default implementations, so an implementation only needs to
+/// override the hooks it cares about.
+pub trait PoolPolicy: fmt::Debug + Send + Sync {
+    /// Returns an error if the new transaction must not enter the pool.
+    ///
+    /// This is only called for transactions that do not replace an existing transaction, after
+    /// the pool's own checks passed.
+    fn check_insert(
+        &self,
+        _transaction: &PolicyTransaction,
+        _context: &InsertContext,
+    ) -> Result<(), PolicyViolation> {
+        Ok(())
+    }
+
+    /// Returns an error if `replacement` must not replace `existing`.
+    ///
+    /// This is called after the replacement passed the price bump check.
+    fn check_replacement(
+        &self,
+        _existing: &PolicyTransaction,
+        _replacement: &PolicyTransaction,
+    ) -> Result<(), PolicyViolation> {
+        Ok(())
+    }
+
+    /// Returns `true` if the transaction should be evicted before any other transaction of its
+    /// subpool once the subpool exceeds its limits.
+    ///
+    /// Transactions for which this returns `false` are evicted according to the subpool's own
+    /// ordering.
+    fn evict_first(&self, _transaction: &PolicyTransaction) -> bool {
+        false
+    }
+
+    /// Invoked when a transaction of `sender` was rejected by the pool.
+    ///
+    /// This includes transactions that failed validation.
+    fn on_rejected(&self, _sender: Address, _origin: TransactionOrigin, _error: &PoolErrorKind) {}
+}
+
+/// A [`PoolPolicy`] that does not impose any additional rules.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct NoopPoolPolicy;
+
+impl PoolPolicy for NoopPoolPolicy {}
+
+/// Combines two policies: a transaction is only admitted if both policies admit it, and evicted
+/// first if either policy wants it evicted first.
+impl<A: PoolPolicy, B: PoolPolicy> PoolPolicy for (A, B) {
+    fn check_insert(
+        &self,
+        transaction: &PolicyTransaction,
+        context: &InsertContext,
+    ) -> Result<(), PolicyViolation> {
+        self.0.check_insert(transaction, context)?;
+        self.1.check_insert(transaction, context)
+    }
+
+    fn check_replacement(
+        &self,
+        existing: &PolicyTransaction,
+        replacement: &PolicyTransaction,
+    ) -> Result<(), PolicyViolation> {
+        self.0.check_replacement(existing, replacement)?;
+        self.1.check_replacement(existing, replacement)
+    }
+
+    fn evict_first(&self, transaction: &PolicyTransaction) -> bool {
+        self.0.evict_first(transaction) || self.1.evict_first(transaction)
+    }
+
+    fn on_rejected(&self, sender: Address, origin: TransactionOrigin, error: &PoolErrorKind) {
+        self.0.on_rejected(sender, origin, error);
+        self.1.on_rejected(sender, origin, error);
+    }
+}
+
+/// The properties of a pool transaction a [`PoolPolicy`] can base its decisions on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyTransaction {
+    /// Hash of the transaction.
+    pub hash: TxHash,
+    /// Sender of the transaction.
+    pub sender: Address,
+    /// Nonce of the transaction.
+    pub nonce: u64,
+    /// Where the transaction originates from.
+    pub origin: TransactionOrigin,
+    /// The EIP-2718 type of the transaction.
+    pub tx_type: u8,
+    /// Max fee per gas of the transaction.
+    pub max_fee_per_gas: u128,
+    /// Max priority fee per gas of the transaction, if it has one.
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// Gas limit of the transaction.
+    pub gas_limit: u64,
+    /// Heap size of the transaction.
+    pub size: usize,
+}
+
+impl<T: PoolTransaction> From<&ValidPoolTransaction<T>> for PolicyTransaction {
+    fn from(tx: &ValidPoolTransaction<T>) -> Self {
+        Self {
+            hash: *tx.hash(),
+            sender: tx.sender(),
+            nonce: tx.nonce(),
+            origin: tx.origin,
+            tx_type: tx.tx_type(),
+            max_fee_per_gas: tx.max_fee_per_gas(),
+            max_priority_fee_per_gas: tx.transaction.max_priority_fee_per_gas(),
+            gas_limit: tx.gas_limit(),
+            size: tx.size(),
+        }
+    }
+}
+
+/// The state of the pool at the time a new transaction is inserted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsertContext {
+    /// Whether the transaction is treated as local, see
+    /// [`LocalTransactionConfig`](crate::LocalTransactionConfig).
+    pub is_local: bool,
+    /// Number of transactions of the sender that are currently in the pool.
+    pub sender_transactions: usize,
+    /// Number of transactions that are currently in the pool.
+    pub pool_transactions: usize,
+}
+
+/// The reason a [`PoolPolicy`] rejected a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PolicyViolation {
+    /// The sender already occupies its share of the pool.
+    #[error("sender {0} exceeded its share of the pool")]
+    SenderQuotaExceeded(Address),
+    /// The sender submitted too many transactions that were rejected.
+    #[error("sender {0} exceeded the number of rejected transactions")]
+    SpamScoreExceeded(Address),
+    /// A custom reason.
+    #[error("{0}")]
+    Other(String),
+}
+
+/// Limits the share of the pool a single non-local sender can occupy.
+///
+/// A sender can always insert up to `min_slots` transactions, beyond that its transactions must
+/// not exceed `max_share_percent` of all transactions in the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SenderQuotaPolicy {
+    /// Number of transactions a sender can insert regardless of its share.
+    pub min_slots: usize,
+    /// Max share of the pool, in percent, a single sender can occupy.
+    pub max_share_percent: usize,
+}
+
+impl SenderQuotaPolicy {
+    /// Creates a new policy with the given limits.
+    pub const fn new(min_slots: usize, max_share_percent: usize) -> Self {
+        Self { min_slots, max_share_percent }
+    }
+
+    /// Returns the number of transactions a sender can hold in a pool of the given size.
+    pub const fn quota(&self, pool_transactions: usize) -> usize {
+        let share = pool_transactions.saturating_mul(self.max_share_percent) / 100;
+        if share > self.min_slots {
+            share
+        } else {
+            self.min_slots
+        }
+    }
+}
+
+impl PoolPolicy for SenderQuotaPolicy {
+    fn check_insert(
+        &self,
+        transaction: &PolicyTransaction,
+        context: &InsertContext,
+    ) -> Result<(), PolicyViolation> {
+        if !context.is_local && context.sender_transactions >= self.quota(context.pool_transactions)
+        {
+            return Err(PolicyViolation::SenderQuotaExceeded(transaction.sender))
+        }
+        Ok(())
+    }
+}
+
+/// Scores senders by the number of their transactions the pool rejected.
+///
+/// Senders that reach `max_rejections` can no longer insert non-local transactions, and their
+/// transactions are evicted first. Only the most recently scored senders are tracked.
+#[derive(Debug)]
+pub struct SpamScorePolicy {
+    /// Number of rejections after which a sender is considered a spammer.
+    max_rejections: u32,
+    /// Number of rejected transactions by sender.
+    rejections: Mutex<LruMap<Address, u32, ByLength>>,
+}
+
+impl SpamScorePolicy {
+    /// Creates a new policy that tracks up to [`DEFAULT_SPAM_SCORE_TRACKED_SENDERS`] senders.
+    pub fn new(max_rejections: u32) -> Self {
+        Self::with_tracked_senders(max_rejections, DEFAULT_SPAM_SCORE_TRACKED_SENDERS)
+    }
+
+    /// Creates a new policy that tracks up to `tracked_senders` senders.
+    pub fn with_tracked_senders(max_rejections: u32, tracked_senders: u32) -> Self {
+        Self { max_rejections, rejections: Mutex::new(LruMap::new(ByLength::new(tracked_senders))) }
+    }
+
+    /// Returns the number of rejected transactions of the sender.
+    pub fn rejections(&self, sender: &Address) -> u32 {
+        self.rejections.lock().peek(sender).copied().unwrap_or_default()
+    }
+
+    /// Returns `true` if the sender is considered a spammer.
+    pub fn is_spammer(&self, sender: &Address) -> bool {
+        self.rejections(sender) >= self.max_rejections
+    }
+}
+
+impl PoolPolicy for SpamScorePolicy {
+    fn check_insert(
+        &self,
+        transaction: &PolicyTransaction,
+        context: &InsertContext,
+    ) -> Result<(), PolicyViolation> {
+        if !context.is_local && self.is_spammer(&transaction.sender) {
+            return Err(PolicyViolation::SpamScoreExceeded(transaction.sender))
+        }
+        Ok(())
+    }
+
+    fn evict_first(&self, transaction: &PolicyTransaction) -> bool {
+        self.is_spammer(&transaction.sender)
+    }
+
+    fn on_rejected(&self, sender: Address, _origin: TransactionOrigin, error: &PoolErrorKind) {
+        if matches!(error, PoolErrorKind::AlreadyImported) {
+            // receiving the same transaction multiple times is expected
+            return
+        }
+        let mut rejections = self.rejections.lock();
+        if let Some(count) = rejections.get_or_insert(sender, || 0) {
+            *count = count.saturating_add(1);
+        }
+    }
+}
+
+/// Exempts trusted senders from the rules of the wrapped policy.
+///
+/// Transactions of trusted senders are always admitted by the wrapped policy and never evicted
+/// first. Rejections of their transactions are not reported to the wrapped policy.
+#[derive(Debug, Clone)]
+pub struct TrustedSendersPolicy<P> {
+    /// The policy applied to all other senders.
+    inner: P,
+    /// Senders exempt from the policy.
+    trusted: HashSet<Address>,
+}
+
+impl<P> TrustedSendersPolicy<P> {
+    /// Wraps the given policy, exempting the given senders.
+    pub fn new(inner: P, trusted: impl IntoIterator<Item = Address>) -> Self {
+        Self { inner, trusted: trusted.into_iter().collect() }
+    }
+
+    /// Returns `true` if the sender is exempt from the policy.
+    pub fn is_trusted(&self, sender: &Address) -> bool {
+        self.trusted.contains(sender)
+    }
+}
+
+impl<P: PoolPolicy> PoolPolicy for TrustedSendersPolicy<P> {
+    fn check_insert(
+        &self,
+        transaction: &PolicyTransaction,
+        context: &InsertContext,
+    ) -> Result<(), PolicyViolation> {
+        if self.is_trusted(&transaction.sender) {
+            return Ok(())
+        }
+        self.inner.check_insert(transaction, context)
+    }
+
+    fn check_replacement(
+        &self,
+        existing: &PolicyTransaction,
+        replacement: &PolicyTransaction,
+    ) -> Result<(), PolicyViolation> {
+        if self.is_trusted(&replacement.sender) {
+            return Ok(())
+        }
+        self.inner.check_replacement(existing, replacement)
+    }
+
+    fn evict_first(&self, transaction: &PolicyTransaction) -> bool {
+        !self.is_trusted(&transaction.sender) && self.inner.evict_first(transaction)
+    }
+
+    fn on_rejected(&self, sender: Address, origin: TransactionOrigin, error: &PoolErrorKind) {
+        if !self.is_trusted(&sender) {
+            self.inner.on_rejected(sender, origin, error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::InvalidPoolTransactionError;
+
+    fn context(sender_transactions: usize, pool_transactions: usize) -> InsertContext {
+        InsertContext { is_local: false, sender_transactions, pool_transactions }
+    }
+
+    fn transaction(sender: Address) -> PolicyTransaction {
+        PolicyTransaction {
+            hash: TxHash::random(),
+            sender,
+            nonce: 0,
+            origin: TransactionOrigin::External,
+            tx_type: 2,
+            max_fee_per_gas: 10,
+            max_priority_fee_per_gas: Some(1),
+            gas_limit: 21_000,
+            size: 100,
+        }
+    }
+
+    #[test]
+    fn sender_quota() {
+        let policy = SenderQuotaPolicy::new(4, 10);
+        let tx = transaction(Address::random());
+
+        assert!(policy.check_insert(&tx, &context(3, 10)).is_ok());
+        assert_eq!(
+            policy.check_insert(&tx, &context(4, 10)),
+            Err(PolicyViolation::SenderQuotaExceeded(tx.sender))
+        );
+        // larger pools allow larger shares
+        assert!(policy.check_insert(&tx, &context(9, 100)).is_ok());
+        assert!(policy.check_insert(&tx, &context(10, 100)).is_err());
+        // locals are exempt
+        let local = InsertContext { is_local: true, ..context(10, 100) };
+        assert!(policy.check_insert(&tx, &local).is_ok());
+    }
+
+    #[test]
+    fn spam_score_and_trusted_senders() {
+        let spammer = Address::random();
+        let trusted = Address::random();
+        let policy = TrustedSendersPolicy::new(SpamScorePolicy::new(2), [trusted]);
+        let err = PoolErrorKind::InvalidTransaction(InvalidPoolTransactionError::Overdraft);
+
+        for sender in [spammer, trusted] {
+            policy.on_rejected(
+                sender,
+                TransactionOrigin::External,
+                &PoolErrorKind::AlreadyImported,
+            );
+            policy.on_rejected(sender, TransactionOrigin::External, &err);
+            assert!(policy.check_insert(&transaction(sender), &context(0, 0)).is_ok());
+            policy.on_rejected(sender, TransactionOrigin::External, &err);
+        }
+
+        assert_eq!(
+            policy.check_insert(&transaction(spammer), &context(0, 0)),
+            Err(PolicyViolation::SpamScoreExceeded(spammer))
+        );
+        assert!(policy.evict_first(&transaction(spammer)));
+
+        assert_eq!(policy.inner.rejections(&trusted), 0);
+        assert!(policy.check_insert(&transaction(trusted), &context(0, 0)).is_ok());
+        assert!(!policy.evict_first(&transaction(trusted)));
+    }
+}
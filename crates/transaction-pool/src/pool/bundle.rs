@@ -0,0 +1,321 @@
+//! Bundles of transactions that must be included atomically.
+
+use crate::{
+    error::{InvalidBundleError, PoolError, PoolErrorKind, PoolResult},
+    traits::BestTransactions,
+    PoolTransaction, ValidPoolTransaction,
+};
+use reth_primitives::{keccak256, TxHash, B256};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt,
+    sync::Arc,
+};
+
+/// A bundle that is submitted to the pool.
+///
+/// See also [`TransactionPool::add_bundle`](crate::TransactionPool::add_bundle).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewBundle<T> {
+    /// The transactions of the bundle, in the order they must be executed.
+    pub transactions: Vec<T>,
+    /// Hashes of the bundle's transactions that are allowed to revert.
+    pub reverting_tx_hashes: Vec<TxHash>,
+    /// The block the bundle must be included in.
+    pub block_number: u64,
+}
+
+/// An ordered group of validated transactions that must all be included in the given block, or
+/// not at all.
+///
+/// The transactions of a bundle are held separately from the transactions in the subpools and are
+/// never propagated.
+#[derive(Debug)]
+pub struct PoolBundle<T: PoolTransaction> {
+    /// The hash of the bundle: keccak256 of the concatenated transaction hashes.
+    hash: B256,
+    /// The transactions of the bundle, in execution order.
+    transactions: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// Hashes of the transactions that are allowed to revert.
+    reverting_tx_hashes: HashSet<TxHash>,
+    /// The block the bundle must be included in.
+    block_number: u64,
+}
+
+impl<T: PoolTransaction> PoolBundle<T> {
+    /// Creates a new bundle from validated transactions.
+    pub(crate) fn new(
+        transactions: Vec<Arc<ValidPoolTransaction<T>>>,
+        reverting_tx_hashes: impl IntoIterator<Item = TxHash>,
+        block_number: u64,
+    ) -> Self {
+        let hashes = transactions.iter().flat_map(|tx| tx.hash().0).collect::<Vec<u8>>();
+        Self {
+            hash: keccak256(hashes),
+            transactions,
+            reverting_tx_hashes: reverting_tx_hashes.into_iter().collect(),
+            block_number,
+        }
+    }
+
+    /// Returns the hash of the bundle.
+    pub const fn hash(&self) -> &B256 {
+        &self.hash
+    }
+
+    /// Returns the transactions of the bundle in execution order.
+    pub fn transactions(&self) -> &[Arc<ValidPoolTransaction<T>>] {
+        &self.transactions
+    }
+
+    /// Returns `true` if the given transaction of the bundle is allowed to revert.
+    pub fn can_revert(&self, tx_hash: &TxHash) -> bool {
+        self.reverting_tx_hashes.contains(tx_hash)
+    }
+
+    /// Returns the block the bundle must be included in.
+    pub const fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    /// Returns the sum of the gas limits of all transactions of the bundle.
+    pub fn gas_limit(&self) -> u64 {
+        self.transactions.iter().map(|tx| tx.gas_limit()).sum()
+    }
+
+    /// Returns the average priority fee per gas of the bundle, weighted by the gas limit of its
+    /// transactions.
+    ///
+    /// Returns `None` if the max fee per gas of any transaction is below the base fee.
+    pub fn effective_tip_per_gas(&self, base_fee: u64) -> Option<u128> {
+        let mut tips = 0u128;
+        for tx in &self.transactions {
+            let tip = tx.transaction.effective_tip_per_gas(base_fee)?;
+            tips = tips.saturating_add(tip.saturating_mul(tx.gas_limit() as u128));
+        }
+        Some(tips / (self.gas_limit().max(1) as u128))
+    }
+}
+
+/// Holds all bundles of the pool by the block they target.
+pub(crate) struct BundlePool<T: PoolTransaction> {
+    /// All bundles by their hash.
+    by_hash: HashMap<B256, Arc<PoolBundle<T>>>,
+    /// Hashes of all bundles by the block they target.
+    by_block: BTreeMap<u64, Vec<B256>>,
+    /// The max number of bundles in the pool.
+    max_bundles: usize,
+}
+
+impl<T: PoolTransaction> BundlePool<T> {
+    /// Creates an empty pool that holds up to `max_bundles` bundles.
+    pub(crate) fn new(max_bundles: usize) -> Self {
+        Self { by_hash: Default::default(), by_block: Default::default(), max_bundles }
+    }
+
+    /// Number of bundles in the pool.
+    pub(crate) fn len(&self) -> usize {
+        self.by_hash.len()
+    }
+
+    /// Returns the bundle with the given hash.
+    pub(crate) fn get(&self, hash: &B256) -> Option<Arc<PoolBundle<T>>> {
+        self.by_hash.get(hash).cloned()
+    }
+
+    /// Adds the bundle to the pool.
+    ///
+    /// Rejects bundles that target a block at or below `last_seen_block`, and new bundles if the
+    /// pool is full.
+    pub(crate) fn add_bundle(
+        &mut self,
+        bundle: PoolBundle<T>,
+        last_seen_block: u64,
+    ) -> PoolResult<B256> {
+        let hash = bundle.hash;
+        if bundle.block_number <= last_seen_block {
+            return Err(PoolError::new(
+                hash,
+                InvalidBundleError::Outdated {
+                    target: bundle.block_number,
+                    current: last_seen_block,
+                },
+            ))
+        }
+        if self.by_hash.contains_key(&hash) {
+            return Err(PoolError::new(hash, PoolErrorKind::AlreadyImported))
+        }
+        if self.len() >= self.max_bundles {
+            return Err(PoolError::new(hash, PoolErrorKind::DiscardedOnInsert))
+        }
+
+        self.by_block.entry(bundle.block_number).or_default().push(hash);
+        self.by_hash.insert(hash, Arc::new(bundle));
+        Ok(hash)
+    }
+
+    /// Removes the bundle with the given hash.
+    pub(crate) fn remove_bundle(&mut self, hash: &B256) -> Option<Arc<PoolBundle<T>>> {
+        let bundle = self.by_hash.remove(hash)?;
+        if let Some(hashes) = self.by_block.get_mut(&bundle.block_number) {
+            hashes.retain(|h| h != hash);
+            if hashes.is_empty() {
+                self.by_block.remove(&bundle.block_number);
+            }
+        }
+        Some(bundle)
+    }
+
+    /// Removes all bundles that target the given block or an earlier one.
+    pub(crate) fn on_new_block(&mut self, block_number: u64) -> Vec<Arc<PoolBundle<T>>> {
+        let remaining = self.by_block.split_off(&(block_number + 1));
+        let outdated = std::mem::replace(&mut self.by_block, remaining);
+        outdated.into_values().flatten().filter_map(|hash| self.by_hash.remove(&hash)).collect()
+    }
+
+    /// Returns all bundles that target the given block and can pay the given base fee, ordered
+    /// by their effective tip per gas, highest first.
+    pub(crate) fn best_bundles(&self, block_number: u64, base_fee: u64) -> Vec<Arc<PoolBundle<T>>> {
+        let mut bundles = self
+            .by_block
+            .get(&block_number)
+            .into_iter()
+            .flatten()
+            .filter_map(|hash| {
+                let bundle = self.by_hash.get(hash)?;
+                Some((bundle.effective_tip_per_gas(base_fee)?, Arc::clone(bundle)))
+            })
+            .collect::<Vec<_>>();
+        bundles.sort_by(|(a, _), (b, _)| b.cmp(a));
+        bundles.into_iter().map(|(_, bundle)| bundle).collect()
+    }
+}
+
+impl<T: PoolTransaction> fmt::Debug for BundlePool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BundlePool")
+            .field("bundles", &self.len())
+            .field("max_bundles", &self.max_bundles)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An item yielded by [`BestBundlesAndTransactions`].
+#[derive(Debug)]
+pub enum BundleOrTransaction<T: PoolTransaction> {
+    /// A bundle whose transactions must be executed in order and all succeed.
+    Bundle(Arc<PoolBundle<T>>),
+    /// A single transaction from the pending pool.
+    Transaction(Arc<ValidPoolTransaction<T>>),
+}
+
+/// An iterator that yields the bundles targeting a block first, ordered by their effective tip,
+/// followed by the best transactions of the pending pool.
+pub struct BestBundlesAndTransactions<T: PoolTransaction> {
+    /// Bundles that haven't been yielded yet.
+    bundles: VecDeque<Arc<PoolBundle<T>>>,
+    /// The best transactions of the pending pool.
+    best: Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>>>,
+}
+
+impl<T: PoolTransaction> BestBundlesAndTransactions<T> {
+    /// Creates a new iterator that yields the given bundles before the given transactions.
+    pub fn new(
+        bundles: impl IntoIterator<Item = Arc<PoolBundle<T>>>,
+        best: Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>>>,
+    ) -> Self {
+        Self { bundles: bundles.into_iter().collect(), best }
+    }
+
+    /// Creates a new iterator that only yields the given transactions.
+    pub fn transactions_only(
+        best: Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>>>,
+    ) -> Self {
+        Self::new(std::iter::empty(), best)
+    }
+
+    /// Marks the transaction as invalid, see [`BestTransactions::mark_invalid`].
+    pub fn mark_invalid(&mut self, tx: &Arc<ValidPoolTransaction<T>>) {
+        self.best.mark_invalid(tx)
+    }
+
+    /// Skips all remaining blob transactions, see [`BestTransactions::skip_blobs`].
+    pub fn skip_blobs(&mut self) {
+        self.best.skip_blobs()
+    }
+}
+
+impl<T: PoolTransaction> Iterator for BestBundlesAndTransactions<T> {
+    type Item = BundleOrTransaction<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(bundle) = self.bundles.pop_front() {
+            return Some(BundleOrTransaction::Bundle(bundle))
+        }
+        self.best.next().map(BundleOrTransaction::Transaction)
+    }
+}
+
+impl<T: PoolTransaction> fmt::Debug for BestBundlesAndTransactions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BestBundlesAndTransactions")
+            .field("bundles", &self.bundles.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{MockTransaction, MockTransactionFactory};
+
+    fn bundle(
+        f: &mut MockTransactionFactory,
+        tip: u128,
+        block_number: u64,
+    ) -> PoolBundle<MockTransaction> {
+        let tx = MockTransaction::eip1559()
+            .with_gas_limit(21_000)
+            .with_max_fee(100)
+            .with_priority_fee(tip);
+        PoolBundle::new(vec![Arc::new(f.validated(tx))], [], block_number)
+    }
+
+    #[test]
+    fn best_bundles_by_tip() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = BundlePool::new(10);
+
+        let low = pool.add_bundle(bundle(&mut f, 1, 2), 1).unwrap();
+        let high = pool.add_bundle(bundle(&mut f, 5, 2), 1).unwrap();
+        pool.add_bundle(bundle(&mut f, 10, 3), 1).unwrap();
+
+        let best = pool.best_bundles(2, 10);
+        assert_eq!(best.iter().map(|b| *b.hash()).collect::<Vec<_>>(), vec![high, low]);
+
+        // bundles that can't pay the base fee are skipped
+        assert!(pool.best_bundles(2, 101).is_empty());
+    }
+
+    #[test]
+    fn evict_outdated_bundles() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = BundlePool::new(2);
+
+        let first = pool.add_bundle(bundle(&mut f, 1, 2), 1).unwrap();
+        pool.add_bundle(bundle(&mut f, 1, 3), 1).unwrap();
+
+        let err = pool.add_bundle(bundle(&mut f, 1, 3), 1).unwrap_err();
+        assert!(matches!(err.kind, PoolErrorKind::DiscardedOnInsert));
+        let err = pool.add_bundle(bundle(&mut f, 1, 1), 1).unwrap_err();
+        assert!(matches!(
+            err.kind,
+            PoolErrorKind::InvalidBundle(InvalidBundleError::Outdated { target: 1, current: 1 })
+        ));
+
+        let removed = pool.on_new_block(2);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(*removed[0].hash(), first);
+        assert_eq!(pool.len(), 1);
+    }
+}
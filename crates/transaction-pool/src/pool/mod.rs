@@ -66,21 +66,24 @@
 //!    category (2.) and become pending.
 
 use crate::{
-    error::{PoolError, PoolErrorKind, PoolResult},
+    conditional::{
+        ConditionalError, ConditionalOutcome, TransactionConditional, MAX_KNOWN_ACCOUNTS_COST,
+    },
+    error::{InvalidBundleError, PoolError, PoolErrorKind, PoolResult},
     identifier::{SenderId, SenderIdentifiers, TransactionId},
     pool::{
+        bundle::BundlePool,
         listener::PoolEventBroadcast,
         state::SubPool,
         txpool::{SenderInfo, TxPool},
     },
     traits::{
-        AllPoolTransactions, BestTransactionsAttributes, BlockInfo, NewTransactionEvent, PoolSize,
-        PoolTransaction, PropagatedTransactions, TransactionOrigin,
+        AllPoolTransactions, BestTransactions, BestTransactionsAttributes, BlockInfo,
+        NewTransactionEvent, PoolSize, PoolTransaction, PropagatedTransactions, TransactionOrigin,
     },
     validate::{TransactionValidationOutcome, ValidPoolTransaction},
     CanonicalStateUpdate, PoolConfig, TransactionOrdering, TransactionValidator,
 };
-use best::BestTransactions;
 use parking_lot::{Mutex, RwLock, RwLockReadGuard};
 use reth_eth_wire_types::HandleMempoolData;
 use reth_execution_types::ChangedAccount;
@@ -106,6 +109,7 @@ use crate::{
 };
 pub use best::BestTransactionFilter;
 pub use blob::{blob_tx_priority, fee_delta};
+pub use bundle::{BestBundlesAndTransactions, BundleOrTransaction, NewBundle, PoolBundle};
 pub use events::{FullTransactionEvent, TransactionEvent};
 pub use listener::{AllTransactionsEvents, TransactionEvents};
 pub use parked::{BasefeeOrd, ParkedOrd, ParkedPool, QueuedOrd};
@@ -113,6 +117,7 @@ pub use pending::PendingPool;
 
 mod best;
 mod blob;
+pub mod bundle;
 mod listener;
 mod parked;
 pub(crate) mod pending;
@@ -141,6 +146,12 @@ where
     blob_store: S,
     /// The internal pool that manages all transactions.
     pool: RwLock<TxPool<T>>,
+    /// All bundles by the block they target.
+    bundles: RwLock<BundlePool<T::Transaction>>,
+    /// Conditional transactions whose conditions are not met yet.
+    ///
+    /// These are excluded from the best transactions.
+    pending_conditionals: RwLock<HashSet<TxHash>>,
     /// Pool settings.
     config: PoolConfig,
     /// Manages listeners for transaction state change events.
@@ -170,6 +181,8 @@ where
             validator,
             event_listener: Default::default(),
             pool: RwLock::new(TxPool::new(ordering, config.clone())),
+            bundles: RwLock::new(BundlePool::new(config.max_bundles)),
+            pending_conditionals: Default::default(),
             pending_transaction_listener: Default::default(),
             transaction_listener: Default::default(),
             blob_transaction_sidecar_listener: Default::default(),
@@ -193,9 +206,15 @@ where
     pub(crate) fn block_info(&self) -> BlockInfo {
         self.get_pool_data().block_info()
     }
-    /// Returns the currently tracked block
+    /// Updates the currently tracked block
     pub(crate) fn set_block_info(&self, info: BlockInfo) {
-        self.pool.write().set_block_info(info)
+        let UpdateOutcome { promoted, discarded } = self.pool.write().set_block_info(info);
+        let mut listener = self.event_listener.write();
+
+        promoted.iter().for_each(|tx| listener.pending(tx.hash(), None));
+        discarded.iter().for_each(|tx| listener.discarded(tx.hash()));
+
+        self.delete_discarded_blobs(discarded.iter());
     }
 
     /// Returns the internal [`SenderId`] for this address
@@ -390,10 +409,51 @@ where
         // This will discard outdated transactions based on the account's nonce
         self.delete_discarded_blobs(outcome.discarded.iter());
 
+        // bundles can no longer be included once their target block has been mined
+        let outdated = self.bundles.write().on_new_block(new_tip.number);
+        if !outdated.is_empty() {
+            trace!(target: "txpool", count=outdated.len(), "evicted outdated bundles");
+        }
+
+        self.update_conditionals();
+
         // notify listeners about updates
         self.notify_on_new_state(outcome);
     }
 
+    /// Checks the conditions of all conditional transactions against the next block.
+    ///
+    /// Transactions whose conditions can't be met anymore are removed from the pool, transactions
+    /// whose conditions are not met yet are excluded from the best transactions.
+    fn update_conditionals(&self) {
+        let conditionals = self
+            .get_pool_data()
+            .all()
+            .transactions_iter()
+            .filter(|tx| tx.is_conditional())
+            .collect::<Vec<_>>();
+
+        let mut pending = HashSet::new();
+        let mut invalid = Vec::new();
+        for tx in conditionals {
+            let Some(conditional) = tx.conditional.as_deref() else { continue };
+            match self.validator.validate_conditional(conditional) {
+                ConditionalOutcome::Met => {}
+                ConditionalOutcome::Pending => {
+                    pending.insert(*tx.hash());
+                }
+                ConditionalOutcome::Invalid(err) => {
+                    trace!(target: "txpool", hash=%tx.hash(), %err, "conditional transaction can no longer be included");
+                    invalid.push(*tx.hash());
+                }
+            }
+        }
+        *self.pending_conditionals.write() = pending;
+
+        let removed = self.remove_transactions(invalid);
+        self.delete_discarded_blobs(removed.iter());
+    }
+
     /// Performs account updates on the pool.
     ///
     /// This will either promote or discard transactions based on the new account state.
@@ -419,6 +479,7 @@ where
         &self,
         origin: TransactionOrigin,
         tx: TransactionValidationOutcome<T::Transaction>,
+        conditional: Option<Box<TransactionConditional>>,
     ) -> PoolResult<TxHash> {
         match tx {
             TransactionValidationOutcome::Valid {
@@ -426,9 +487,15 @@ where
                 state_nonce,
                 transaction,
                 propagate,
+                authorities,
+                simulation,
             } => {
-                let sender_id = self.get_sender_id(transaction.sender());
+                let sender = transaction.sender();
+                let sender_id = self.get_sender_id(sender);
                 let transaction_id = TransactionId::new(sender_id, transaction.nonce());
+                let authority_ids = authorities.map(|authorities| {
+                    authorities.into_iter().map(|address| self.get_sender_id(address)).collect()
+                });
 
                 // split the valid transaction and the blob sidecar if it has any
                 let (transaction, maybe_sidecar) = match transaction {
@@ -445,12 +512,23 @@ where
                 let tx = ValidPoolTransaction {
                     transaction,
                     transaction_id,
-                    propagate,
+                    // conditional transactions are only valid with their conditions, which are not
+                    // part of the transaction
+                    propagate: propagate && conditional.is_none(),
                     timestamp: Instant::now(),
                     origin,
+                    authority_ids,
+                    conditional,
+                    simulation,
                 };
 
-                let added = self.pool.write().add_transaction(tx, balance, state_nonce)?;
+                let added = match self.pool.write().add_transaction(tx, balance, state_nonce) {
+                    Ok(added) => added,
+                    Err(err) => {
+                        self.config.policy.on_rejected(sender, origin, &err.kind);
+                        return Err(err)
+                    }
+                };
                 let hash = *added.hash();
 
                 // transaction was successfully inserted into the pool
@@ -486,7 +564,9 @@ where
             TransactionValidationOutcome::Invalid(tx, err) => {
                 let mut listener = self.event_listener.write();
                 listener.discarded(tx.hash());
-                Err(PoolError::new(*tx.hash(), err))
+                let err = PoolError::new(*tx.hash(), err);
+                self.config.policy.on_rejected(tx.sender(), origin, &err.kind);
+                Err(err)
             }
             TransactionValidationOutcome::Error(tx_hash, err) => {
                 let mut listener = self.event_listener.write();
@@ -510,14 +590,73 @@ where
         Ok(listener)
     }
 
+    /// Adds a validated conditional transaction to the pool.
+    ///
+    /// The transaction is rejected if its conditions can't be met anymore. If its conditions are
+    /// not met yet, it is excluded from the best transactions until they are.
+    pub(crate) fn add_conditional_transaction(
+        &self,
+        origin: TransactionOrigin,
+        tx: TransactionValidationOutcome<T::Transaction>,
+        conditional: TransactionConditional,
+    ) -> PoolResult<TxHash> {
+        let hash = tx.tx_hash();
+        let cost = conditional.known_accounts_cost();
+        if cost > MAX_KNOWN_ACCOUNTS_COST {
+            return Err(PoolError::new(
+                hash,
+                ConditionalError::TooManyKnownAccounts { cost, max: MAX_KNOWN_ACCOUNTS_COST },
+            ))
+        }
+
+        match self.validator.validate_conditional(&conditional) {
+            ConditionalOutcome::Met => {}
+            ConditionalOutcome::Pending => {
+                // exclude the transaction before it becomes visible to best transactions
+                self.pending_conditionals.write().insert(hash);
+            }
+            ConditionalOutcome::Invalid(err) => return Err(PoolError::new(hash, err)),
+        }
+
+        let mut results = self.add_transactions_with_conditionals(
+            origin,
+            std::iter::once((tx, Some(Box::new(conditional)))),
+        );
+        let result = results.pop().expect("result length is the same as the input");
+        if result.is_err() {
+            self.pending_conditionals.write().remove(&hash);
+        }
+        result
+    }
+
     /// Adds all transactions in the iterator to the pool, returning a list of results.
     pub fn add_transactions(
         &self,
         origin: TransactionOrigin,
         transactions: impl IntoIterator<Item = TransactionValidationOutcome<T::Transaction>>,
     ) -> Vec<PoolResult<TxHash>> {
-        let mut added =
-            transactions.into_iter().map(|tx| self.add_transaction(origin, tx)).collect::<Vec<_>>();
+        self.add_transactions_with_conditionals(
+            origin,
+            transactions.into_iter().map(|tx| (tx, None)),
+        )
+    }
+
+    /// Adds all transactions in the iterator with their optional conditions to the pool,
+    /// returning a list of results.
+    fn add_transactions_with_conditionals(
+        &self,
+        origin: TransactionOrigin,
+        transactions: impl IntoIterator<
+            Item = (
+                TransactionValidationOutcome<T::Transaction>,
+                Option<Box<TransactionConditional>>,
+            ),
+        >,
+    ) -> Vec<PoolResult<TxHash>> {
+        let mut added = transactions
+            .into_iter()
+            .map(|(tx, conditional)| self.add_transaction(origin, tx, conditional))
+            .collect::<Vec<_>>();
 
         // If at least one transaction was added successfully, then we enforce the pool size limits.
         let discarded =
@@ -638,18 +777,101 @@ where
                 promoted.iter().for_each(|tx| listener.pending(tx.hash(), None));
                 discarded.iter().for_each(|tx| listener.discarded(tx.hash()));
             }
-            AddedTransaction::Parked { transaction, replaced, .. } => {
+            AddedTransaction::Parked { transaction, replaced, discarded, .. } => {
                 listener.queued(transaction.hash());
                 if let Some(replaced) = replaced {
                     listener.replaced(replaced.clone(), *transaction.hash());
                 }
+                discarded.iter().for_each(|tx| listener.discarded(tx.hash()));
             }
         }
     }
 
+    /// Adds a bundle of validated transactions to the pool.
+    ///
+    /// The bundle is rejected if any of its transactions is invalid.
+    pub(crate) fn add_bundle(
+        &self,
+        origin: TransactionOrigin,
+        transactions: Vec<TransactionValidationOutcome<T::Transaction>>,
+        reverting_tx_hashes: Vec<TxHash>,
+        block_number: u64,
+    ) -> PoolResult<B256> {
+        let mut bundle_transactions = Vec::with_capacity(transactions.len());
+        for outcome in transactions {
+            match outcome {
+                TransactionValidationOutcome::Valid {
+                    transaction: ValidTransaction::Valid(transaction),
+                    simulation,
+                    ..
+                } => {
+                    let transaction_id = TransactionId::new(
+                        self.get_sender_id(transaction.sender()),
+                        transaction.nonce(),
+                    );
+                    bundle_transactions.push(Arc::new(ValidPoolTransaction {
+                        transaction,
+                        transaction_id,
+                        // bundles are private to the builder
+                        propagate: false,
+                        timestamp: Instant::now(),
+                        origin,
+                        authority_ids: None,
+                        conditional: None,
+                        simulation,
+                    }));
+                }
+                TransactionValidationOutcome::Valid {
+                    transaction: ValidTransaction::ValidWithSidecar { transaction, .. },
+                    ..
+                } => {
+                    let hash = *transaction.hash();
+                    return Err(PoolError::new(hash, InvalidBundleError::BlobTransaction(hash)))
+                }
+                TransactionValidationOutcome::Invalid(tx, err) => {
+                    return Err(PoolError::new(*tx.hash(), err))
+                }
+                TransactionValidationOutcome::Error(tx_hash, err) => {
+                    return Err(PoolError::other(tx_hash, err))
+                }
+            }
+        }
+
+        let bundle = PoolBundle::new(bundle_transactions, reverting_tx_hashes, block_number);
+        let last_seen_block = self.block_info().last_seen_block_number;
+        self.bundles.write().add_bundle(bundle, last_seen_block)
+    }
+
+    /// Removes the bundle with the given hash from the pool.
+    pub(crate) fn remove_bundle(&self, hash: &B256) -> Option<Arc<PoolBundle<T::Transaction>>> {
+        self.bundles.write().remove_bundle(hash)
+    }
+
+    /// Returns the bundle with the given hash.
+    pub(crate) fn get_bundle(&self, hash: &B256) -> Option<Arc<PoolBundle<T::Transaction>>> {
+        self.bundles.read().get(hash)
+    }
+
+    /// Returns an iterator that yields the bundles targeting the given block first, followed by
+    /// the transactions that are ready to be included in the block.
+    pub(crate) fn best_bundles_and_transactions(
+        &self,
+        best_transactions_attributes: BestTransactionsAttributes,
+        block_number: u64,
+    ) -> BestBundlesAndTransactions<T::Transaction> {
+        let bundles =
+            self.bundles.read().best_bundles(block_number, best_transactions_attributes.basefee);
+        BestBundlesAndTransactions::new(
+            bundles,
+            self.best_transactions_with_attributes(best_transactions_attributes),
+        )
+    }
+
     /// Returns an iterator that yields transactions that are ready to be included in the block.
-    pub(crate) fn best_transactions(&self) -> BestTransactions<T> {
-        self.get_pool_data().best_transactions()
+    pub(crate) fn best_transactions(
+        &self,
+    ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T::Transaction>>>> {
+        self.exclude_pending_conditionals(Box::new(self.get_pool_data().best_transactions()))
     }
 
     /// Returns an iterator that yields transactions that are ready to be included in the block with
@@ -657,9 +879,23 @@ where
     pub(crate) fn best_transactions_with_attributes(
         &self,
         best_transactions_attributes: BestTransactionsAttributes,
-    ) -> Box<dyn crate::traits::BestTransactions<Item = Arc<ValidPoolTransaction<T::Transaction>>>>
-    {
-        self.get_pool_data().best_transactions_with_attributes(best_transactions_attributes)
+    ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T::Transaction>>>> {
+        self.exclude_pending_conditionals(
+            self.get_pool_data().best_transactions_with_attributes(best_transactions_attributes),
+        )
+    }
+
+    /// Filters conditional transactions whose conditions are not met yet, and their descendants,
+    /// from the given best transactions.
+    fn exclude_pending_conditionals(
+        &self,
+        best: Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T::Transaction>>>>,
+    ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T::Transaction>>>> {
+        let pending = self.pending_conditionals.read();
+        if pending.is_empty() {
+            return best
+        }
+        exclude_transactions::<T>(best, pending.clone())
     }
 
     /// Returns all transactions from the pending sub-pool
@@ -842,6 +1078,29 @@ where
     }
 }
 
+/// Filters the given transactions and their descendants from the best transactions.
+///
+/// This is a free function so that the filter only depends on the [`TransactionOrdering`], which
+/// is `'static`.
+fn exclude_transactions<T: TransactionOrdering>(
+    best: Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T::Transaction>>>>,
+    excluded: HashSet<TxHash>,
+) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T::Transaction>>>> {
+    // best transactions are yielded in nonce order per sender, so all transactions of a sender
+    // after an excluded one are its descendants
+    let mut excluded_senders = HashSet::new();
+    Box::new(BestTransactions::filter(
+        best,
+        move |tx: &Arc<ValidPoolTransaction<T::Transaction>>| {
+            if excluded.contains(tx.hash()) || excluded_senders.contains(&tx.sender_id()) {
+                excluded_senders.insert(tx.sender_id());
+                return false
+            }
+            true
+        },
+    ))
+}
+
 impl<V, T: TransactionOrdering, S> fmt::Debug for PoolInner<V, T, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("PoolInner").field("config", &self.config).finish_non_exhaustive()
@@ -1026,6 +1285,9 @@ pub enum AddedTransaction<T: PoolTransaction> {
         replaced: Option<Arc<ValidPoolTransaction<T>>>,
         /// The subpool it was moved to.
         subpool: SubPool,
+        /// Transactions of EIP-7702 authorities that were discarded while inserting the
+        /// transaction.
+        discarded: Vec<Arc<ValidPoolTransaction<T>>>,
     },
 }
 
@@ -1050,7 +1312,7 @@ impl<T: PoolTransaction> AddedTransaction<T> {
     pub(crate) fn discarded_transactions(&self) -> Option<&[Arc<ValidPoolTransaction<T>>]> {
         match self {
             Self::Pending(tx) => Some(&tx.discarded),
-            Self::Parked { .. } => None,
+            Self::Parked { discarded, .. } => Some(discarded),
         }
     }
 
@@ -1143,11 +1405,13 @@ impl<T: PoolTransaction> OnNewCanonicalStateOutcome<T> {
 mod tests {
     use crate::{
         blobstore::{BlobStore, InMemoryBlobStore},
+        conditional::{KnownAccountState, TransactionConditional, MAX_KNOWN_ACCOUNTS_COST},
+        error::PoolErrorKind,
         test_utils::{MockTransaction, TestPoolBuilder},
         validate::ValidTransaction,
         BlockInfo, PoolConfig, SubPoolLimit, TransactionOrigin, TransactionValidationOutcome, U256,
     };
-    use reth_primitives::{kzg::Blob, transaction::generate_blob_sidecar};
+    use reth_primitives::{kzg::Blob, transaction::generate_blob_sidecar, Address, B256};
     use std::{fs, path::PathBuf};
 
     #[test]
@@ -1212,7 +1476,10 @@ mod tests {
                             sidecar: sidecar.clone(),
                         },
                         propagate: true,
+                        authorities: None,
+                        simulation: None,
                     },
+                    None,
                 )
                 .unwrap();
 
@@ -1229,4 +1496,53 @@ mod tests {
         // Assert that the pool's blob store matches the expected blob store.
         assert_eq!(*test_pool.blob_store(), blob_store);
     }
+
+    #[test]
+    fn test_pending_conditional_excluded_from_best() {
+        let test_pool = &TestPoolBuilder::default().pool;
+        let valid = |tx: MockTransaction| TransactionValidationOutcome::Valid {
+            balance: U256::from(1_000),
+            state_nonce: 0,
+            transaction: ValidTransaction::Valid(tx),
+            propagate: true,
+            authorities: None,
+            simulation: None,
+        };
+
+        // conditionals that check too many storage slots are rejected
+        let slots = (0..=MAX_KNOWN_ACCOUNTS_COST as u64)
+            .map(|slot| (B256::from(U256::from(slot)), B256::ZERO))
+            .collect();
+        let mut conditional = TransactionConditional::default();
+        conditional.known_accounts.insert(Address::ZERO, KnownAccountState::Slots(slots));
+        let err = test_pool
+            .add_conditional_transaction(
+                TransactionOrigin::Local,
+                valid(MockTransaction::eip1559()),
+                conditional,
+            )
+            .unwrap_err();
+        assert!(matches!(err.kind, PoolErrorKind::ConditionalNotMet(_)));
+
+        let tx = MockTransaction::eip1559();
+        let hash = tx.get_hash();
+        let descendant = tx.next();
+        test_pool
+            .add_conditional_transaction(
+                TransactionOrigin::Local,
+                valid(tx),
+                TransactionConditional::default(),
+            )
+            .unwrap();
+        let added = test_pool.get(&hash).unwrap();
+        assert!(added.is_conditional());
+        assert!(!added.propagate);
+        let results = test_pool.add_transactions(TransactionOrigin::Local, [valid(descendant)]);
+        assert!(results[0].is_ok());
+        assert_eq!(test_pool.best_transactions().count(), 2);
+
+        // the descendant can't be included without the pending conditional transaction
+        test_pool.pending_conditionals.write().insert(hash);
+        assert_eq!(test_pool.best_transactions().count(), 0);
+    }
 }
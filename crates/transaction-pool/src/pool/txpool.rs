@@ -2,9 +2,13 @@
 
 use crate::{
     config::{LocalTransactionConfig, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER},
-    error::{Eip4844PoolTransactionError, InvalidPoolTransactionError, PoolError, PoolErrorKind},
+    error::{
+        Eip4844PoolTransactionError, Eip7702PoolTransactionError, InvalidPoolTransactionError,
+        PoolError, PoolErrorKind,
+    },
     identifier::{SenderId, TransactionId},
     metrics::{AllTransactionsMetrics, TxPoolMetrics},
+    policy::{InsertContext, NoopPoolPolicy, PolicyTransaction, PolicyViolation, PoolPolicy},
     pool::{
         best::BestTransactions,
         blob::BlobTransactions,
@@ -71,6 +75,9 @@ pub struct TxPool<T: TransactionOrdering> {
     blob_pool: BlobTransactions<T::Transaction>,
     /// All transactions in the pool.
     all_transactions: AllTransactions<T::Transaction>,
+    /// Updates of authorities whose EIP-7702 transaction was moved to the pending pool that still
+    /// need to be applied.
+    authority_updates: Vec<PoolUpdate>,
     /// Transaction pool metrics
     metrics: TxPoolMetrics,
 }
@@ -87,6 +94,7 @@ impl<T: TransactionOrdering> TxPool<T> {
             basefee_pool: Default::default(),
             blob_pool: Default::default(),
             all_transactions: AllTransactions::new(&config),
+            authority_updates: Default::default(),
             config,
             metrics: Default::default(),
         }
@@ -236,7 +244,7 @@ impl<T: TransactionOrdering> TxPool<T> {
     /// Sets the current block info for the pool.
     ///
     /// This will also apply updates to the pool based on the new base fee
-    pub fn set_block_info(&mut self, info: BlockInfo) {
+    pub(crate) fn set_block_info(&mut self, info: BlockInfo) -> UpdateOutcome<T::Transaction> {
         let BlockInfo {
             last_seen_block_hash,
             last_seen_block_number,
@@ -250,6 +258,9 @@ impl<T: TransactionOrdering> TxPool<T> {
         if let Some(blob_fee) = pending_blob_fee {
             self.update_blob_fee(blob_fee, basefee_ordering)
         }
+
+        // apply the updates of authorities of promoted EIP-7702 transactions
+        self.process_updates(Vec::new())
     }
 
     /// Returns an iterator that yields transactions that are ready to be included in the block with
@@ -506,7 +517,7 @@ impl<T: TransactionOrdering> TxPool<T> {
                         replaced,
                     })
                 } else {
-                    AddedTransaction::Parked { transaction, subpool: move_to, replaced }
+                    AddedTransaction::Parked { transaction, subpool: move_to, replaced, discarded }
                 };
 
                 // Update size metrics after adding and potentially moving transactions.
@@ -564,6 +575,15 @@ impl<T: TransactionOrdering> TxPool<T> {
                             transaction.tx_type(),
                         ),
                     )),
+                    InsertErr::PolicyViolation { transaction, violation } => {
+                        Err(PoolError::new(*transaction.hash(), violation))
+                    }
+                    InsertErr::AuthorityReserved { transaction } => Err(PoolError::new(
+                        *transaction.hash(),
+                        PoolErrorKind::InvalidTransaction(
+                            Eip7702PoolTransactionError::AuthorityReserved.into(),
+                        ),
+                    )),
                 }
             }
         }
@@ -572,28 +592,34 @@ impl<T: TransactionOrdering> TxPool<T> {
     /// Maintenance task to apply a series of updates.
     ///
     /// This will move/discard the given transaction according to the `PoolUpdate`
-    fn process_updates(&mut self, updates: Vec<PoolUpdate>) -> UpdateOutcome<T::Transaction> {
+    fn process_updates(&mut self, mut updates: Vec<PoolUpdate>) -> UpdateOutcome<T::Transaction> {
         let mut outcome = UpdateOutcome::default();
-        for PoolUpdate { id, hash, current, destination } in updates {
-            match destination {
-                Destination::Discard => {
-                    // remove the transaction from the pool and subpool
-                    if let Some(tx) = self.prune_transaction_by_hash(&hash) {
-                        outcome.discarded.push(tx);
+        // moving an EIP-7702 transaction to the pending pool reserves its authorities, which
+        // results in additional updates
+        updates.append(&mut self.authority_updates);
+        while !updates.is_empty() {
+            for PoolUpdate { id, hash, current, destination } in updates {
+                match destination {
+                    Destination::Discard => {
+                        // remove the transaction from the pool and subpool
+                        if let Some(tx) = self.prune_transaction_by_hash(&hash) {
+                            outcome.discarded.push(tx);
+                        }
+                        self.metrics.removed_transactions.increment(1);
                     }
-                    self.metrics.removed_transactions.increment(1);
-                }
-                Destination::Pool(move_to) => {
-                    debug_assert_ne!(&move_to, &current, "destination must be different");
-                    let moved = self.move_transaction(current, move_to, &id);
-                    if matches!(move_to, SubPool::Pending) {
-                        if let Some(tx) = moved {
-                            trace!(target: "txpool", hash=%tx.transaction.hash(), "Promoted transaction to pending");
-                            outcome.promoted.push(tx);
+                    Destination::Pool(move_to) => {
+                        debug_assert_ne!(&move_to, &current, "destination must be different");
+                        let moved = self.move_transaction(current, move_to, &id);
+                        if matches!(move_to, SubPool::Pending) {
+                            if let Some(tx) = moved {
+                                trace!(target: "txpool", hash=%tx.transaction.hash(), "Promoted transaction to pending");
+                                outcome.promoted.push(tx);
+                            }
                         }
                     }
                 }
             }
+            updates = std::mem::take(&mut self.authority_updates);
         }
         outcome
     }
@@ -746,6 +772,13 @@ impl<T: TransactionOrdering> TxPool<T> {
         // generic and it would not be possible to distinguish whether a transaction is being
         // added to the `BaseFee` pool, or the `Queued` pool.
         trace!(target: "txpool", hash=%tx.transaction.hash(), ?pool, "Adding transaction to a subpool");
+        // the authorities of an EIP-7702 transaction are only reserved while it is executable
+        if pool.is_pending() {
+            let updates = self.all_transactions.reserve_authorities(&tx);
+            self.authority_updates.extend(updates);
+        } else {
+            self.all_transactions.release_authorities(&tx);
+        }
         match pool {
             SubPool::Queued => self.queued_pool.add_transaction(tx),
             SubPool::Pending => {
@@ -783,8 +816,13 @@ impl<T: TransactionOrdering> TxPool<T> {
 
         // Helper macro that discards the worst transactions for the pools
         macro_rules! discard_worst {
-            ($this:ident, $removed:ident, [$($limit:ident => $pool:ident),* $(,)*]) => {
+            ($this:ident, $removed:ident, [$($limit:ident => ($pool:ident, $subpool:expr)),* $(,)*]) => {
                 $ (
+                // 0. first evict the transactions the policy wants evicted first
+                if $this.$pool.exceeds(&$this.config.$limit) {
+                    $this.discard_by_policy($subpool, &mut $removed);
+                }
+
                 while $this.$pool.exceeds(&$this.config.$limit)
                     {
                         trace!(
@@ -829,16 +867,61 @@ impl<T: TransactionOrdering> TxPool<T> {
 
         discard_worst!(
             self, removed, [
-                pending_limit => pending_pool,
-                basefee_limit => basefee_pool,
-                blob_limit    => blob_pool,
-                queued_limit  => queued_pool,
+                pending_limit => (pending_pool, SubPool::Pending),
+                basefee_limit => (basefee_pool, SubPool::BaseFee),
+                blob_limit    => (blob_pool, SubPool::Blob),
+                queued_limit  => (queued_pool, SubPool::Queued),
             ]
         );
 
         removed
     }
 
+    /// Evicts the transactions of the given subpool the configured [`PoolPolicy`] wants evicted
+    /// first, until the subpool is within its limit.
+    ///
+    /// Transactions are evicted from the highest nonce downwards, and all descendants of an evicted
+    /// transaction are removed from the entire pool.
+    fn discard_by_policy(
+        &mut self,
+        subpool: SubPool,
+        removed: &mut Vec<Arc<ValidPoolTransaction<T::Transaction>>>,
+    ) {
+        let policy = Arc::clone(&self.config.policy);
+        let candidates = self
+            .all_transactions
+            .txs
+            .iter()
+            .rev()
+            .filter(|(_, tx)| {
+                tx.subpool == subpool &&
+                    policy.evict_first(&PolicyTransaction::from(tx.transaction.as_ref()))
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        for id in candidates {
+            if !self.subpool_exceeds_limit(subpool) {
+                break
+            }
+            if let Some(tx) = self.remove_transaction(&id) {
+                trace!(target: "txpool", hash=%tx.hash(), ?subpool, "Discarding transaction by policy");
+                removed.push(tx);
+                self.remove_descendants(&id, removed);
+            }
+        }
+    }
+
+    /// Returns true if the given subpool exceeds its configured limit.
+    fn subpool_exceeds_limit(&self, subpool: SubPool) -> bool {
+        match subpool {
+            SubPool::Queued => self.queued_pool.exceeds(&self.config.queued_limit),
+            SubPool::Pending => self.pending_pool.exceeds(&self.config.pending_limit),
+            SubPool::BaseFee => self.basefee_pool.exceeds(&self.config.basefee_limit),
+            SubPool::Blob => self.blob_pool.exceeds(&self.config.blob_limit),
+        }
+    }
+
     /// Number of transactions in the entire pool
     pub(crate) fn len(&self) -> usize {
         self.all_transactions.len()
@@ -926,6 +1009,10 @@ pub(crate) struct AllTransactions<T: PoolTransaction> {
     txs: BTreeMap<TransactionId, PoolInternalTransaction<T>>,
     /// Tracks the number of transactions by sender that are currently in the pool.
     tx_counter: FxHashMap<SenderId, usize>,
+    /// Tracks the number of pending EIP-7702 transactions that bump the nonce of an authority.
+    reserved_authorities: FxHashMap<SenderId, usize>,
+    /// The pending EIP-7702 transactions that reserved their authorities.
+    reserving_transactions: HashSet<TxHash>,
     /// The current block number the pool keeps track of.
     last_seen_block_number: u64,
     /// The current block hash the pool keeps track of.
@@ -936,6 +1023,8 @@ pub(crate) struct AllTransactions<T: PoolTransaction> {
     price_bumps: PriceBumpConfig,
     /// How to handle [`TransactionOrigin::Local`](crate::TransactionOrigin) transactions.
     local_transactions_config: LocalTransactionConfig,
+    /// Additional rules for inserting and replacing transactions.
+    policy: Arc<dyn PoolPolicy>,
     /// All Transactions metrics
     metrics: AllTransactionsMetrics,
 }
@@ -947,6 +1036,7 @@ impl<T: PoolTransaction> AllTransactions<T> {
             max_account_slots: config.max_account_slots,
             price_bumps: config.price_bumps,
             local_transactions_config: config.local_transactions_config.clone(),
+            policy: Arc::clone(&config.policy),
             ..Default::default()
         }
     }
@@ -995,6 +1085,60 @@ impl<T: PoolTransaction> AllTransactions<T> {
         }
     }
 
+    /// Reserves the authorities of the given EIP-7702 transaction once it is pending.
+    ///
+    /// The nonce of an authority is bumped once the delegation is executed, which invalidates
+    /// the authority's next transaction. That transaction is discarded and the authority's
+    /// remaining transactions are parked until the new nonce of the authority is known.
+    fn reserve_authorities(&mut self, transaction: &ValidPoolTransaction<T>) -> Vec<PoolUpdate> {
+        let mut updates = Vec::new();
+        if transaction.authority_ids().is_empty() ||
+            !self.reserving_transactions.insert(*transaction.hash())
+        {
+            return updates
+        }
+        for authority in transaction.authority_ids() {
+            *self.reserved_authorities.entry(*authority).or_default() += 1;
+
+            let mut txs = self.txs_iter_mut(*authority);
+            let Some((id, next)) = txs.next() else { continue };
+            if next.state.has_nonce_gap() {
+                // the nonce that is bumped is not used by the pool
+                continue
+            }
+            updates.push(PoolUpdate {
+                id: *id,
+                hash: *next.transaction.hash(),
+                current: next.subpool,
+                destination: Destination::Discard,
+            });
+            for (_, tx) in txs {
+                tx.state.remove(TxState::NO_NONCE_GAPS);
+                tx.state.remove(TxState::NO_PARKED_ANCESTORS);
+                Self::record_subpool_update(&mut updates, tx);
+            }
+        }
+        updates
+    }
+
+    /// Releases the authorities of the given EIP-7702 transaction after it was removed or moved
+    /// out of the pending pool.
+    fn release_authorities(&mut self, transaction: &ValidPoolTransaction<T>) {
+        if !self.reserving_transactions.remove(transaction.hash()) {
+            return
+        }
+        for authority in transaction.authority_ids() {
+            if let hash_map::Entry::Occupied(mut entry) =
+                self.reserved_authorities.entry(*authority)
+            {
+                *entry.get_mut() -= 1;
+                if *entry.get() == 0 {
+                    entry.remove();
+                }
+            }
+        }
+    }
+
     /// Updates the block specific info
     fn set_block_info(&mut self, block_info: BlockInfo) {
         let BlockInfo {
@@ -1218,8 +1362,6 @@ impl<T: PoolTransaction> AllTransactions<T> {
 
     /// Returns a mutable iterator over all transactions for the given sender, starting with the
     /// lowest nonce
-    #[cfg(test)]
-    #[allow(dead_code)]
     pub(crate) fn txs_iter_mut(
         &mut self,
         sender: SenderId,
@@ -1270,6 +1412,7 @@ impl<T: PoolTransaction> AllTransactions<T> {
         let internal = self.txs.remove(&tx.transaction_id)?;
         // decrement the counter for the sender.
         self.tx_decr(tx.sender_id());
+        self.release_authorities(&tx);
         self.update_size_metrics();
         Some((tx, internal.subpool))
     }
@@ -1287,6 +1430,7 @@ impl<T: PoolTransaction> AllTransactions<T> {
 
         // decrement the counter for the sender.
         self.tx_decr(internal.transaction.sender_id());
+        self.release_authorities(&internal.transaction);
 
         let result =
             self.by_hash.remove(internal.transaction.hash()).map(|tx| (tx, internal.subpool));
@@ -1319,18 +1463,22 @@ impl<T: PoolTransaction> AllTransactions<T> {
     ///   - Gas limit: reject transactions if they exceed a block's maximum gas.
     ///   - Ensures transaction types are not conflicting for the sender: blob vs normal
     ///     transactions are mutually exclusive for the same sender.
+    ///   - Policy: new transactions that don't replace an existing transaction must be admitted by
+    ///     the configured [`PoolPolicy`].
+    ///   - Authorities: reject new transactions from a sender whose nonce will be bumped by a
+    ///     pending EIP-7702 transaction.
     fn ensure_valid(
         &self,
         transaction: ValidPoolTransaction<T>,
     ) -> Result<ValidPoolTransaction<T>, InsertErr<T>> {
-        if !self.local_transactions_config.is_local(transaction.origin, transaction.sender()) {
-            let current_txs =
-                self.tx_counter.get(&transaction.sender_id()).copied().unwrap_or_default();
-            if current_txs >= self.max_account_slots {
-                return Err(InsertErr::ExceededSenderTransactionsCapacity {
-                    transaction: Arc::new(transaction),
-                })
-            }
+        let is_local =
+            self.local_transactions_config.is_local(transaction.origin, transaction.sender());
+        let current_txs =
+            self.tx_counter.get(&transaction.sender_id()).copied().unwrap_or_default();
+        if !is_local && current_txs >= self.max_account_slots {
+            return Err(InsertErr::ExceededSenderTransactionsCapacity {
+                transaction: Arc::new(transaction),
+            })
         }
         if transaction.gas_limit() > self.block_gas_limit {
             return Err(InsertErr::TxGasLimitMoreThanAvailableBlockGas {
@@ -1345,6 +1493,27 @@ impl<T: PoolTransaction> AllTransactions<T> {
             return Err(InsertErr::TxTypeConflict { transaction: Arc::new(transaction) })
         }
 
+        if self.reserved_authorities.contains_key(&transaction.sender_id()) {
+            // the nonce of the sender is going to change
+            return Err(InsertErr::AuthorityReserved { transaction: Arc::new(transaction) })
+        }
+
+        if !self.txs.contains_key(transaction.id()) {
+            let context = InsertContext {
+                is_local,
+                sender_transactions: current_txs,
+                pool_transactions: self.len(),
+            };
+            if let Err(violation) =
+                self.policy.check_insert(&PolicyTransaction::from(&transaction), &context)
+            {
+                return Err(InsertErr::PolicyViolation {
+                    transaction: Arc::new(transaction),
+                    violation,
+                })
+            }
+        }
+
         Ok(transaction)
     }
 
@@ -1575,6 +1744,16 @@ impl<T: PoolTransaction> AllTransactions<T> {
                         existing: *entry.get().transaction.hash(),
                     })
                 }
+                // Ensure the policy admits the replacement
+                if let Err(violation) = self.policy.check_replacement(
+                    &PolicyTransaction::from(existing_transaction),
+                    &PolicyTransaction::from(maybe_replacement),
+                ) {
+                    return Err(InsertErr::PolicyViolation {
+                        transaction: pool_tx.transaction,
+                        violation,
+                    })
+                }
                 let new_hash = *pool_tx.transaction.hash();
                 let new_transaction = pool_tx.transaction.clone();
                 let replaced = entry.insert(pool_tx);
@@ -1665,6 +1844,11 @@ impl<T: PoolTransaction> AllTransactions<T> {
             }
         }
 
+        // The new transaction reserves its authorities once it is added to the pending pool.
+        if let Some((replaced, _)) = &replaced_tx {
+            self.release_authorities(replaced);
+        }
+
         // If this wasn't a replacement transaction we need to update the counter.
         if replaced_tx.is_none() {
             self.tx_inc(inserted_tx_id.sender);
@@ -1711,11 +1895,14 @@ impl<T: PoolTransaction> Default for AllTransactions<T> {
             by_hash: Default::default(),
             txs: Default::default(),
             tx_counter: Default::default(),
+            reserved_authorities: Default::default(),
+            reserving_transactions: Default::default(),
             last_seen_block_number: Default::default(),
             last_seen_block_hash: Default::default(),
             pending_fees: Default::default(),
             price_bumps: Default::default(),
             local_transactions_config: Default::default(),
+            policy: Arc::new(NoopPoolPolicy),
             metrics: Default::default(),
         }
     }
@@ -1769,6 +1956,10 @@ pub(crate) enum InsertErr<T: PoolTransaction> {
     },
     /// Thrown if the mutual exclusivity constraint (blob vs normal transaction) is violated.
     TxTypeConflict { transaction: Arc<ValidPoolTransaction<T>> },
+    /// The configured [`PoolPolicy`] rejected the transaction or replacement.
+    PolicyViolation { transaction: Arc<ValidPoolTransaction<T>>, violation: PolicyViolation },
+    /// The sender is an authority of a pending EIP-7702 transaction.
+    AuthorityReserved { transaction: Arc<ValidPoolTransaction<T>> },
 }
 
 /// Transaction was successfully inserted into the pool
@@ -1852,6 +2043,7 @@ mod tests {
 
     use super::*;
     use crate::{
+        policy::SenderQuotaPolicy,
         test_utils::{MockOrdering, MockTransaction, MockTransactionFactory, MockTransactionSet},
         traits::TransactionOrigin,
         SubPoolLimit,
@@ -2707,6 +2899,102 @@ mod tests {
         assert_eq!(pool.pending_pool.len(), 1);
     }
 
+    #[test]
+    fn delegation_requeues_authority() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = TxPool::new(MockOrdering::default(), Default::default());
+
+        // two pending transactions of the authority
+        let authority_tx = MockTransaction::eip1559().inc_price_by(10);
+        let validated = f.validated(authority_tx.clone());
+        let authority = validated.sender_id();
+        let first = *validated.hash();
+        pool.add_transaction(validated, U256::from(1_000), 0).unwrap();
+        let next = authority_tx.next();
+        let validated = f.validated(next.clone());
+        let next_id = *validated.id();
+        pool.add_transaction(validated, U256::from(1_000), 0).unwrap();
+        assert_eq!(pool.pending_pool.len(), 2);
+
+        // the delegation bumps the nonce of the authority
+        let mut delegation = f.validated(MockTransaction::eip1559().inc_price_by(10));
+        delegation.authority_ids = Some(vec![authority]);
+        let delegation_hash = *delegation.hash();
+        let added = pool.add_transaction(delegation, U256::from(1_000), 0).unwrap();
+        assert_eq!(added.discarded_transactions().unwrap()[0].hash(), &first);
+        assert!(!pool.contains(&first));
+        assert!(pool.queued_pool.contains(&next_id));
+
+        // the authority can't send new transactions until the delegation is executed
+        let err = pool.add_transaction(f.validated(next.next()), U256::from(1_000), 0).unwrap_err();
+        assert!(matches!(
+            err.kind,
+            PoolErrorKind::InvalidTransaction(InvalidPoolTransactionError::Eip7702(
+                Eip7702PoolTransactionError::AuthorityReserved
+            ))
+        ));
+
+        // the delegation is mined and the authority's next transaction is promoted
+        let mut changed_senders = HashMap::new();
+        changed_senders.insert(
+            authority,
+            SenderInfo { state_nonce: next.get_nonce(), balance: U256::from(1_000) },
+        );
+        let outcome = pool.on_canonical_state_change(
+            Default::default(),
+            vec![delegation_hash],
+            changed_senders,
+        );
+        assert_eq!(outcome.promoted.len(), 1);
+        assert!(pool.pending_pool.contains(&next_id));
+        assert!(pool.all_transactions.reserved_authorities.is_empty());
+    }
+
+    #[test]
+    fn parked_delegation_does_not_reserve_authority() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = TxPool::new(MockOrdering::default(), Default::default());
+        let mut block_info = pool.block_info();
+        block_info.pending_basefee = 50;
+        pool.set_block_info(block_info);
+
+        // the delegation can't pay the base fee
+        let mut delegation = f.validated(MockTransaction::eip1559().with_max_fee(10));
+        let authority_tx = MockTransaction::eip1559().inc_price_by(100);
+        let validated = f.validated(authority_tx.clone());
+        let authority = validated.sender_id();
+        let first = *validated.hash();
+        delegation.authority_ids = Some(vec![authority]);
+        let delegation_id = *delegation.id();
+        pool.add_transaction(delegation, U256::from(1_000), 0).unwrap();
+        assert!(pool.basefee_pool.contains(&delegation_id));
+
+        // the authority can send transactions while the delegation is parked
+        pool.add_transaction(validated, U256::from(1_000), 0).unwrap();
+        let next = f.validated(authority_tx.next());
+        let next_id = *next.id();
+        pool.add_transaction(next, U256::from(1_000), 0).unwrap();
+        assert_eq!(pool.pending_pool.len(), 2);
+
+        // the delegation becomes pending and reserves the authority
+        block_info.pending_basefee = 5;
+        let outcome = pool.set_block_info(block_info);
+        assert!(pool.pending_pool.contains(&delegation_id));
+        assert_eq!(outcome.discarded.len(), 1);
+        assert_eq!(outcome.discarded[0].hash(), &first);
+        assert!(pool.queued_pool.contains(&next_id));
+        assert!(pool.all_transactions.reserved_authorities.contains_key(&authority));
+
+        // the delegation is parked again and releases the authority
+        block_info.pending_basefee = 50;
+        pool.set_block_info(block_info);
+        assert!(pool.basefee_pool.contains(&delegation_id));
+        assert!(pool.all_transactions.reserved_authorities.is_empty());
+        pool.add_transaction(f.validated(authority_tx), U256::from(1_000), 0).unwrap();
+        assert!(pool.pending_pool.contains(&next_id));
+        pool.assert_invariants();
+    }
+
     #[test]
     fn discard_with_large_blob_txs() {
         // init tracing
@@ -2817,6 +3105,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn policy_rejects_insert() {
+        let mut f = MockTransactionFactory::default();
+        let config = PoolConfig::default().with_policy(SenderQuotaPolicy::new(2, 0));
+        let mut pool = TxPool::new(MockOrdering::default(), config);
+
+        let tx = MockTransaction::eip1559();
+        pool.add_transaction(f.validated(tx.clone()), U256::from(1_000), 0).unwrap();
+        pool.add_transaction(f.validated(tx.next()), U256::from(1_000), 0).unwrap();
+
+        let err =
+            pool.add_transaction(f.validated(tx.next().next()), U256::from(1_000), 0).unwrap_err();
+        assert!(matches!(
+            err.kind,
+            PoolErrorKind::PolicyViolation(PolicyViolation::SenderQuotaExceeded(sender)) if sender == tx.get_sender()
+        ));
+
+        // replacements don't take up another slot
+        let replacement = f.validated(tx.next().inc_price_by(100));
+        assert!(pool.add_transaction(replacement, U256::from(1_000), 0).is_ok());
+    }
+
+    #[test]
+    fn discard_by_policy_first() {
+        #[derive(Debug)]
+        struct EvictSender(Address);
+
+        impl PoolPolicy for EvictSender {
+            fn evict_first(&self, transaction: &PolicyTransaction) -> bool {
+                transaction.sender == self.0
+            }
+        }
+
+        let mut f = MockTransactionFactory::default();
+        let queued_limit = SubPoolLimit::new(2, usize::MAX);
+        let spam = MockTransaction::eip1559().inc_nonce();
+        let config = PoolConfig { queued_limit, ..Default::default() }
+            .with_policy(EvictSender(spam.get_sender()));
+        let mut pool = TxPool::new(MockOrdering::default(), config);
+
+        let spam = f.validated(spam);
+        let spam_hash = *spam.hash();
+        pool.add_transaction(spam, U256::from(1_000), 0).unwrap();
+        for _ in 0..queued_limit.max_txs {
+            let tx = MockTransaction::eip1559().inc_nonce();
+            pool.add_transaction(f.validated(tx), U256::from(1_000), 0).unwrap();
+        }
+
+        let removed = pool.discard_worst();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(*removed[0].hash(), spam_hash);
+        assert_eq!(pool.size().queued, queued_limit.max_txs);
+        pool.assert_invariants();
+    }
+
     #[test]
     fn discard_blobs_at_capacity() {
         let mut f = MockTransactionFactory::default();
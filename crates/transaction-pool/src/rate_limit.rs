@@ -0,0 +1,159 @@
+//! Token bucket rate limiting for transaction submissions.
+//!
+//! Used to limit how many transactions a single source, for example a peer or an RPC client, can
+//! submit to the pool.
+
+use parking_lot::Mutex;
+use schnellru::{ByLength, LruMap};
+use std::{fmt, hash::Hash, time::Instant};
+
+/// The default maximum number of sources a [`KeyedRateLimiter`] keeps track of.
+pub const DEFAULT_MAX_TRACKED_SOURCES: u32 = 4096;
+
+/// Configuration of a token bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RateLimitConfig {
+    /// How many tokens are added to the bucket per second.
+    pub per_second: u32,
+    /// The maximum number of tokens in the bucket, the number of transactions that can be
+    /// submitted at once.
+    pub burst: u32,
+}
+
+impl RateLimitConfig {
+    /// Creates a new config that allows `per_second` transactions per second, with a burst of one
+    /// second's worth of transactions.
+    pub const fn per_second(per_second: u32) -> Self {
+        Self { per_second, burst: per_second }
+    }
+
+    /// Sets the maximum number of transactions that can be submitted at once.
+    pub const fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+}
+
+/// A token bucket rate limiter with one bucket per key.
+///
+/// Only the most recently used [`DEFAULT_MAX_TRACKED_SOURCES`] keys are tracked, a key that was
+/// evicted starts with a full bucket again.
+pub struct KeyedRateLimiter<K: Hash + Eq> {
+    config: RateLimitConfig,
+    buckets: Mutex<LruMap<K, TokenBucket, ByLength>>,
+}
+
+impl<K: Hash + Eq> KeyedRateLimiter<K> {
+    /// Creates a new rate limiter with the given config.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self::with_max_tracked_sources(config, DEFAULT_MAX_TRACKED_SOURCES)
+    }
+
+    /// Creates a new rate limiter that keeps track of at most `max_sources` keys.
+    pub fn with_max_tracked_sources(config: RateLimitConfig, max_sources: u32) -> Self {
+        Self { config, buckets: Mutex::new(LruMap::new(ByLength::new(max_sources))) }
+    }
+
+    /// Returns the config of the rate limiter.
+    pub const fn config(&self) -> RateLimitConfig {
+        self.config
+    }
+
+    /// Takes a token from the bucket of the given key.
+    ///
+    /// Returns `false` if the bucket is empty and the submission should be rejected.
+    pub fn try_acquire(&self, key: K) -> bool {
+        self.try_acquire_at(key, Instant::now())
+    }
+
+    fn try_acquire_at(&self, key: K, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock();
+        let Some(bucket) = buckets.get_or_insert(key, || TokenBucket::full(self.config, now))
+        else {
+            // the limiter can't track any keys
+            return true
+        };
+        bucket.try_acquire(self.config, now)
+    }
+}
+
+impl<K: Hash + Eq> fmt::Debug for KeyedRateLimiter<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyedRateLimiter")
+            .field("config", &self.config)
+            .field("tracked_sources", &self.buckets.try_lock().map(|buckets| buckets.len()))
+            .finish()
+    }
+}
+
+/// The state of a single token bucket.
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    /// The number of available tokens.
+    tokens: f64,
+    /// When tokens were last added.
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    const fn full(config: RateLimitConfig, now: Instant) -> Self {
+        Self { tokens: config.burst as f64, last_refill: now }
+    }
+
+    fn try_acquire(&mut self, config: RateLimitConfig, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens =
+            elapsed.mul_add(config.per_second as f64, self.tokens).min(config.burst as f64);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn refills_over_time() {
+        let limiter = KeyedRateLimiter::new(RateLimitConfig::per_second(2));
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire_at(1, now));
+        assert!(limiter.try_acquire_at(1, now));
+        assert!(!limiter.try_acquire_at(1, now));
+
+        // other keys have their own bucket
+        assert!(limiter.try_acquire_at(2, now));
+
+        let later = now + Duration::from_millis(500);
+        assert!(limiter.try_acquire_at(1, later));
+        assert!(!limiter.try_acquire_at(1, later));
+
+        // the bucket never holds more than the burst
+        let much_later = now + Duration::from_secs(60);
+        assert!(limiter.try_acquire_at(1, much_later));
+        assert!(limiter.try_acquire_at(1, much_later));
+        assert!(!limiter.try_acquire_at(1, much_later));
+    }
+
+    #[test]
+    fn evicted_sources_start_full() {
+        let limiter = KeyedRateLimiter::with_max_tracked_sources(
+            RateLimitConfig::per_second(1).with_burst(1),
+            1,
+        );
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire_at(1, now));
+        assert!(!limiter.try_acquire_at(1, now));
+        assert!(limiter.try_acquire_at(2, now));
+        assert!(limiter.try_acquire_at(1, now));
+    }
+}
@@ -0,0 +1,529 @@
+//! Recording of the inputs of the transaction pool for offline replay.
+//!
+//! The recorder writes every transaction that enters the pool, together with the state of its
+//! sender at that time, and every canonical state update to a file of [`PoolRecord`]s. The
+//! recording can be replayed into a fresh pool with a different configuration to compare orderings
+//! and pool limits, see [`replay_records`](crate::replay::replay_records).
+
+use crate::{
+    traits::TransactionListenerKind, EthPoolTransaction, NewTransactionEvent, PoolTransaction,
+    TransactionOrigin, TransactionPool,
+};
+use alloy_rlp::{Buf, BufMut, Decodable, Encodable, Header};
+use futures_util::{stream, Stream, StreamExt};
+use reth_chain_state::CanonStateNotification;
+use reth_chainspec::{ChainSpec, ChainSpecProvider};
+use reth_execution_types::ChangedAccount;
+use reth_fs_util::FsPathError;
+use reth_primitives::{Address, IntoRecoveredTransaction, TransactionSigned, B256, U256};
+use reth_storage_api::{errors::provider::ProviderError, StateProviderFactory};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+use tokio::sync::oneshot;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, warn};
+
+/// A record of the transaction pool's inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolRecord {
+    /// A transaction entered the pool.
+    Transaction {
+        /// Where the transaction originates from.
+        origin: TransactionOrigin,
+        /// Nonce of the sender in the latest state when the transaction was received.
+        state_nonce: u64,
+        /// Balance of the sender in the latest state when the transaction was received.
+        balance: U256,
+        /// The transaction, without blob sidecar.
+        transaction: Box<TransactionSigned>,
+    },
+    /// The canonical chain advanced.
+    CanonicalUpdate(RecordedStateUpdate),
+}
+
+impl PoolRecord {
+    const TRANSACTION: u8 = 0;
+    const CANONICAL_UPDATE: u8 = 1;
+
+    const fn encode_origin(origin: TransactionOrigin) -> u8 {
+        match origin {
+            TransactionOrigin::Local => 0,
+            TransactionOrigin::External => 1,
+            TransactionOrigin::Private => 2,
+        }
+    }
+
+    fn decode_origin(buf: &mut &[u8]) -> alloy_rlp::Result<TransactionOrigin> {
+        match u8::decode(buf)? {
+            0 => Ok(TransactionOrigin::Local),
+            1 => Ok(TransactionOrigin::External),
+            2 => Ok(TransactionOrigin::Private),
+            _ => Err(alloy_rlp::Error::Custom("unknown transaction origin")),
+        }
+    }
+
+    fn payload_length(&self) -> usize {
+        match self {
+            Self::Transaction { origin, state_nonce, balance, transaction } => {
+                Self::TRANSACTION.length() +
+                    Self::encode_origin(*origin).length() +
+                    state_nonce.length() +
+                    balance.length() +
+                    transaction.length()
+            }
+            Self::CanonicalUpdate(update) => Self::CANONICAL_UPDATE.length() + update.length(),
+        }
+    }
+}
+
+impl Encodable for PoolRecord {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header { list: true, payload_length: self.payload_length() }.encode(out);
+        match self {
+            Self::Transaction { origin, state_nonce, balance, transaction } => {
+                Self::TRANSACTION.encode(out);
+                Self::encode_origin(*origin).encode(out);
+                state_nonce.encode(out);
+                balance.encode(out);
+                transaction.encode(out);
+            }
+            Self::CanonicalUpdate(update) => {
+                Self::CANONICAL_UPDATE.encode(out);
+                update.encode(out);
+            }
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + alloy_rlp::length_of_length(payload_length)
+    }
+}
+
+impl Decodable for PoolRecord {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let (mut payload, rest) = decode_list_payload(buf)?;
+        let record = match u8::decode(&mut payload)? {
+            Self::TRANSACTION => Self::Transaction {
+                origin: Self::decode_origin(&mut payload)?,
+                state_nonce: u64::decode(&mut payload)?,
+                balance: U256::decode(&mut payload)?,
+                transaction: Box::new(TransactionSigned::decode(&mut payload)?),
+            },
+            Self::CANONICAL_UPDATE => {
+                Self::CanonicalUpdate(RecordedStateUpdate::decode(&mut payload)?)
+            }
+            _ => return Err(alloy_rlp::Error::Custom("unknown pool record")),
+        };
+        ensure_consumed(payload)?;
+        *buf = rest;
+        Ok(record)
+    }
+}
+
+/// A recorded [`CanonicalStateUpdate`](crate::CanonicalStateUpdate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedStateUpdate {
+    /// Hash of the new tip block.
+    pub block_hash: B256,
+    /// Number of the new tip block.
+    pub block_number: u64,
+    /// Timestamp of the new tip block.
+    pub timestamp: u64,
+    /// Gas limit of the new tip block.
+    pub gas_limit: u64,
+    /// EIP-1559 base fee of the _next_ (pending) block.
+    pub pending_block_base_fee: u64,
+    /// EIP-4844 blob fee of the _next_ (pending) block, if Cancun is active.
+    pub pending_block_blob_fee: Option<u128>,
+    /// Accounts that changed in the new blocks.
+    pub changed_accounts: Vec<ChangedAccount>,
+    /// Transactions that were mined in the new blocks.
+    pub mined_transactions: Vec<B256>,
+}
+
+impl RecordedStateUpdate {
+    fn changed_accounts_payload_length(&self) -> usize {
+        self.changed_accounts.iter().map(changed_account_length).sum()
+    }
+
+    fn payload_length(&self) -> usize {
+        let accounts_payload_length = self.changed_accounts_payload_length();
+        self.block_hash.length() +
+            self.block_number.length() +
+            self.timestamp.length() +
+            self.gas_limit.length() +
+            self.pending_block_base_fee.length() +
+            // the blob fee is never zero once Cancun is active, so zero encodes `None`
+            self.pending_block_blob_fee.unwrap_or_default().length() +
+            accounts_payload_length +
+            alloy_rlp::length_of_length(accounts_payload_length) +
+            self.mined_transactions.length()
+    }
+}
+
+impl Encodable for RecordedStateUpdate {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header { list: true, payload_length: self.payload_length() }.encode(out);
+        self.block_hash.encode(out);
+        self.block_number.encode(out);
+        self.timestamp.encode(out);
+        self.gas_limit.encode(out);
+        self.pending_block_base_fee.encode(out);
+        self.pending_block_blob_fee.unwrap_or_default().encode(out);
+        Header { list: true, payload_length: self.changed_accounts_payload_length() }.encode(out);
+        for account in &self.changed_accounts {
+            encode_changed_account(account, out);
+        }
+        self.mined_transactions.encode(out);
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + alloy_rlp::length_of_length(payload_length)
+    }
+}
+
+impl Decodable for RecordedStateUpdate {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let (mut payload, rest) = decode_list_payload(buf)?;
+        let block_hash = B256::decode(&mut payload)?;
+        let block_number = u64::decode(&mut payload)?;
+        let timestamp = u64::decode(&mut payload)?;
+        let gas_limit = u64::decode(&mut payload)?;
+        let pending_block_base_fee = u64::decode(&mut payload)?;
+        let pending_block_blob_fee = Some(u128::decode(&mut payload)?).filter(|fee| *fee != 0);
+
+        let (mut accounts, after_accounts) = decode_list_payload(&mut payload)?;
+        let mut changed_accounts = Vec::new();
+        while accounts.has_remaining() {
+            changed_accounts.push(decode_changed_account(&mut accounts)?);
+        }
+        payload = after_accounts;
+
+        let mined_transactions = Vec::<B256>::decode(&mut payload)?;
+        ensure_consumed(payload)?;
+        *buf = rest;
+
+        Ok(Self {
+            block_hash,
+            block_number,
+            timestamp,
+            gas_limit,
+            pending_block_base_fee,
+            pending_block_blob_fee,
+            changed_accounts,
+            mined_transactions,
+        })
+    }
+}
+
+fn changed_account_payload_length(account: &ChangedAccount) -> usize {
+    account.address.length() + account.nonce.length() + account.balance.length()
+}
+
+fn changed_account_length(account: &ChangedAccount) -> usize {
+    let payload_length = changed_account_payload_length(account);
+    payload_length + alloy_rlp::length_of_length(payload_length)
+}
+
+fn encode_changed_account(account: &ChangedAccount, out: &mut dyn BufMut) {
+    Header { list: true, payload_length: changed_account_payload_length(account) }.encode(out);
+    account.address.encode(out);
+    account.nonce.encode(out);
+    account.balance.encode(out);
+}
+
+fn decode_changed_account(buf: &mut &[u8]) -> alloy_rlp::Result<ChangedAccount> {
+    let (mut payload, rest) = decode_list_payload(buf)?;
+    let account = ChangedAccount {
+        address: Address::decode(&mut payload)?,
+        nonce: u64::decode(&mut payload)?,
+        balance: U256::decode(&mut payload)?,
+    };
+    ensure_consumed(payload)?;
+    *buf = rest;
+    Ok(account)
+}
+
+/// Decodes a list header and splits the input into the list's payload and the remaining input.
+fn decode_list_payload<'a>(buf: &mut &'a [u8]) -> alloy_rlp::Result<(&'a [u8], &'a [u8])> {
+    let header = Header::decode(buf)?;
+    if !header.list {
+        return Err(alloy_rlp::Error::UnexpectedString)
+    }
+    if buf.len() < header.payload_length {
+        return Err(alloy_rlp::Error::InputTooShort)
+    }
+    Ok(buf.split_at(header.payload_length))
+}
+
+const fn ensure_consumed(payload: &[u8]) -> alloy_rlp::Result<()> {
+    if payload.is_empty() {
+        Ok(())
+    } else {
+        Err(alloy_rlp::Error::UnexpectedLength)
+    }
+}
+
+/// Append-only file of [`PoolRecord`]s.
+#[derive(Debug)]
+pub struct PoolRecorder {
+    /// Path to the recording.
+    path: PathBuf,
+    /// Appends records to the file.
+    writer: BufWriter<File>,
+    /// Number of records written.
+    records: usize,
+}
+
+impl PoolRecorder {
+    /// Creates a new recording at the given path, replacing an existing file.
+    pub fn create(path: impl Into<PathBuf>) -> Result<Self, FsPathError> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            reth_fs_util::create_dir_all(parent)?;
+        }
+        let writer = BufWriter::new(reth_fs_util::create_file(&path)?);
+        Ok(Self { path, writer, records: 0 })
+    }
+
+    /// Reads all records of the recording at the given path.
+    ///
+    /// A truncated record at the end of the recording, e.g. due to a crash, is skipped.
+    pub fn load(path: &Path) -> Result<Vec<PoolRecord>, FsPathError> {
+        let data = reth_fs_util::read(path)?;
+        let mut buf = data.as_slice();
+
+        let mut records = Vec::new();
+        while buf.has_remaining() {
+            match PoolRecord::decode(&mut buf) {
+                Ok(record) => records.push(record),
+                Err(err) => {
+                    warn!(target: "txpool", %err, recording=?path, "Skipping corrupted tail of pool recording");
+                    break
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    /// Appends the record to the recording.
+    ///
+    /// The record is buffered until [`PoolRecorder::flush`] is called.
+    pub fn append(&mut self, record: &PoolRecord) -> Result<(), FsPathError> {
+        let mut buf = Vec::with_capacity(record.length());
+        record.encode(&mut buf);
+        self.writer.write_all(&buf).map_err(|err| FsPathError::write(err, &self.path))?;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Writes all buffered records to the file.
+    pub fn flush(&mut self) -> Result<(), FsPathError> {
+        self.writer.flush().map_err(|err| FsPathError::write(err, &self.path))
+    }
+
+    /// Returns the number of records written.
+    pub const fn len(&self) -> usize {
+        self.records
+    }
+
+    /// Returns `true` if no records were written.
+    pub const fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    /// Returns the path to the recording.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Converts a canonical state notification into the update the pool receives.
+///
+/// Reorgs are recorded as an update to the tip of the new chain, transactions of the reverted
+/// blocks are not recorded.
+fn recorded_state_update(
+    chain_spec: &ChainSpec,
+    notification: &CanonStateNotification,
+) -> RecordedStateUpdate {
+    let new = notification.committed();
+    let (blocks, state) = new.inner();
+    let tip = blocks.tip();
+
+    RecordedStateUpdate {
+        block_hash: tip.hash(),
+        block_number: tip.number,
+        timestamp: tip.timestamp,
+        gas_limit: tip.gas_limit,
+        pending_block_base_fee: tip
+            .next_block_base_fee(chain_spec.base_fee_params_at_timestamp(tip.timestamp + 12))
+            .unwrap_or_default(),
+        pending_block_blob_fee: tip.next_block_blob_fee(),
+        changed_accounts: state.changed_accounts().collect(),
+        mined_transactions: blocks.transaction_hashes().collect(),
+    }
+}
+
+/// Returns the nonce and balance of the account in the latest state.
+fn sender_state<Client>(client: &Client, sender: Address) -> Result<(u64, U256), ProviderError>
+where
+    Client: StateProviderFactory,
+{
+    let account = client.latest()?.basic_account(sender)?.unwrap_or_default();
+    Ok((account.nonce, account.balance))
+}
+
+/// Inputs of the pool observed by the recorder.
+enum RecorderEvent<T: PoolTransaction> {
+    Transaction(NewTransactionEvent<T>),
+    Canonical(CanonStateNotification),
+}
+
+/// Task which records all transactions entering the pool and all canonical state updates to the
+/// given file until the shutdown signal is received.
+pub async fn record_pool_task<Client, P, St>(
+    shutdown: reth_tasks::shutdown::GracefulShutdown,
+    client: Client,
+    pool: P,
+    events: St,
+    path: PathBuf,
+) where
+    Client: StateProviderFactory + ChainSpecProvider<ChainSpec = ChainSpec>,
+    P: TransactionPool,
+    P::Transaction: EthPoolTransaction,
+    St: Stream<Item = CanonStateNotification> + Unpin,
+{
+    let recorder = match PoolRecorder::create(&path) {
+        Ok(recorder) => recorder,
+        Err(err) => {
+            error!(target: "txpool", %err, recording=?path, "Failed to create pool recording");
+            return
+        }
+    };
+
+    // the file is written on a dedicated thread so that writes don't block the executor
+    let (records_tx, records_rx) = mpsc::channel();
+    let (written_tx, written_rx) = oneshot::channel();
+    let writer = std::thread::Builder::new().name("txpool-recorder".to_string()).spawn(move || {
+        let _ = written_tx.send(write_records(recorder, records_rx));
+    });
+    if let Err(err) = writer {
+        error!(target: "txpool", %err, recording=?path, "Failed to spawn pool recording writer");
+        return
+    }
+
+    let new_transactions =
+        ReceiverStream::new(pool.new_transactions_listener_for(TransactionListenerKind::All))
+            .map(RecorderEvent::Transaction);
+    let mut events =
+        stream::select(new_transactions, events.map(RecorderEvent::Canonical)).take_until(shutdown);
+
+    while let Some(event) = events.next().await {
+        let record = match event {
+            RecorderEvent::Transaction(event) => {
+                let tx = event.transaction;
+                let (state_nonce, balance) = match sender_state(&client, tx.sender()) {
+                    Ok(state) => state,
+                    Err(err) => {
+                        debug!(target: "txpool", %err, hash=%tx.hash(), "Failed to load sender state for recording");
+                        continue
+                    }
+                };
+                PoolRecord::Transaction {
+                    origin: tx.origin,
+                    state_nonce,
+                    balance,
+                    transaction: Box::new(tx.to_recovered_transaction().into_signed()),
+                }
+            }
+            RecorderEvent::Canonical(notification) => PoolRecord::CanonicalUpdate(
+                recorded_state_update(&client.chain_spec(), &notification),
+            ),
+        };
+
+        if records_tx.send(record).is_err() {
+            // the writer failed
+            break
+        }
+    }
+
+    // closing the channel lets the writer flush the remaining records and exit
+    drop(records_tx);
+    match written_rx.await {
+        Ok(Ok(records)) => {
+            debug!(target: "txpool", records, recording=?path, "Stopped pool recording")
+        }
+        Ok(Err(err)) => {
+            error!(target: "txpool", %err, recording=?path, "Failed to write pool recording")
+        }
+        Err(_) => error!(target: "txpool", recording=?path, "Pool recording writer panicked"),
+    }
+}
+
+/// Appends the received records to the recording until the channel is closed and returns the
+/// number of written records.
+///
+/// The recording is flushed whenever no more records are queued.
+fn write_records(
+    mut recorder: PoolRecorder,
+    records: mpsc::Receiver<PoolRecord>,
+) -> Result<usize, FsPathError> {
+    while let Ok(record) = records.recv() {
+        recorder.append(&record)?;
+        while let Ok(record) = records.try_recv() {
+            recorder.append(&record)?;
+        }
+        recorder.flush()?;
+    }
+    Ok(recorder.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{hex, PooledTransactionsElement};
+
+    #[test]
+    fn record_roundtrip() {
+        let tx_bytes = hex!("02f87201830655c2808505ef61f08482565f94388c818ca8b9251b393131c08a736a67ccb192978801049e39c4b5b1f580c001a01764ace353514e8abdfb92446de356b260e3c1225b73fc4c8876a6258d12a129a04f02294aa61ca7676061cd99f29275491218b4754b46a0248e5e42bc5091f507");
+        let tx = PooledTransactionsElement::decode_enveloped(&mut &tx_bytes[..]).unwrap();
+        let records = vec![
+            PoolRecord::Transaction {
+                origin: TransactionOrigin::Local,
+                state_nonce: 3,
+                balance: U256::from(100),
+                transaction: Box::new(tx.into_transaction()),
+            },
+            PoolRecord::CanonicalUpdate(RecordedStateUpdate {
+                block_hash: B256::random(),
+                block_number: 1,
+                timestamp: 12,
+                gas_limit: 30_000_000,
+                pending_block_base_fee: 7,
+                pending_block_blob_fee: None,
+                changed_accounts: vec![ChangedAccount {
+                    address: Address::random(),
+                    nonce: 4,
+                    balance: U256::from(5),
+                }],
+                mined_transactions: vec![B256::random()],
+            }),
+        ];
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("txpool-recording.rlp");
+        let mut recorder = PoolRecorder::create(&path).unwrap();
+        for record in &records {
+            recorder.append(record).unwrap();
+        }
+        recorder.flush().unwrap();
+
+        assert_eq!(PoolRecorder::load(&path).unwrap(), records);
+    }
+}
@@ -0,0 +1,373 @@
+//! Replay of a pool recording, see [`record`](crate::record).
+
+use crate::{
+    blobstore::InMemoryBlobStore,
+    record::{PoolRecord, RecordedStateUpdate},
+    traits::{BestTransactions, BestTransactionsAttributes, CanonicalStateUpdate},
+    validate::ValidTransaction,
+    EthPoolTransaction, EthPooledTransaction, FullTransactionEvent, Pool, PoolConfig,
+    PoolTransaction, Priority, TransactionOrdering, TransactionOrigin, TransactionPool,
+    TransactionPoolExt, TransactionValidationOutcome, TransactionValidator,
+};
+use futures_util::{FutureExt, StreamExt};
+use parking_lot::Mutex;
+use reth_primitives::{
+    constants::eip4844::MAX_BLOBS_PER_BLOCK, Address, Header, InvalidTransactionError, SealedBlock,
+    SealedHeader, U256,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// A [Pool] that replays a recording.
+pub type ReplayPool<O = ReplayOrdering> = Pool<ReplayValidator, O, InMemoryBlobStore>;
+
+/// Returns a new [`ReplayPool`] with the given ordering and configuration.
+pub fn replay_pool<O>(ordering: O, config: PoolConfig) -> ReplayPool<O>
+where
+    O: TransactionOrdering<Transaction = EthPooledTransaction>,
+{
+    Pool::new(ReplayValidator::default(), ordering, InMemoryBlobStore::default(), config)
+}
+
+/// The [`TransactionOrdering`] of a [`ReplayPool`].
+///
+/// Transactions with the same priority are ordered by their arrival in the pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplayOrdering {
+    /// Orders transactions by their coinbase tip, like the
+    /// [`CoinbaseTipOrdering`](crate::CoinbaseTipOrdering) of the node.
+    #[default]
+    CoinbaseTip,
+    /// Orders transactions by their max fee per gas, regardless of the base fee.
+    MaxFee,
+    /// Orders transactions by their arrival in the pool.
+    Fifo,
+}
+
+impl TransactionOrdering for ReplayOrdering {
+    type PriorityValue = U256;
+    type Transaction = EthPooledTransaction;
+
+    fn priority(
+        &self,
+        transaction: &Self::Transaction,
+        base_fee: u64,
+    ) -> Priority<Self::PriorityValue> {
+        match self {
+            Self::CoinbaseTip => transaction.effective_tip_per_gas(base_fee).map(U256::from).into(),
+            Self::MaxFee => Priority::Value(U256::from(transaction.max_fee_per_gas())),
+            Self::Fifo => Priority::Value(U256::ZERO),
+        }
+    }
+}
+
+/// A [`TransactionValidator`] that accepts every transaction, like the
+/// [`MockTransactionValidator`](crate::noop::MockTransactionValidator), but reports the recorded
+/// state of the sender instead of an infinite balance.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayValidator {
+    /// Nonce and balance of all known accounts.
+    accounts: Arc<Mutex<HashMap<Address, (u64, U256)>>>,
+}
+
+impl ReplayValidator {
+    /// Sets the nonce and balance of the account.
+    pub fn set_account(&self, address: Address, nonce: u64, balance: U256) {
+        self.accounts.lock().insert(address, (nonce, balance));
+    }
+}
+
+impl TransactionValidator for ReplayValidator {
+    type Transaction = EthPooledTransaction;
+
+    async fn validate_transaction(
+        &self,
+        _origin: TransactionOrigin,
+        mut transaction: Self::Transaction,
+    ) -> TransactionValidationOutcome<Self::Transaction> {
+        let (state_nonce, balance) =
+            self.accounts.lock().get(&transaction.sender()).copied().unwrap_or((0, U256::MAX));
+        if transaction.nonce() < state_nonce {
+            return TransactionValidationOutcome::Invalid(
+                transaction,
+                InvalidTransactionError::NonceNotConsistent.into(),
+            )
+        }
+        let maybe_sidecar = transaction.take_blob().maybe_sidecar().cloned();
+        TransactionValidationOutcome::Valid {
+            balance,
+            state_nonce,
+            transaction: ValidTransaction::new(transaction, maybe_sidecar),
+            // nothing is propagated during a replay
+            propagate: false,
+            authorities: None,
+            simulation: None,
+        }
+    }
+}
+
+/// The block the pool would have built before a recorded canonical update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayedBlock {
+    /// Number of the block.
+    pub number: u64,
+    /// Number of transactions in the block.
+    pub transactions: usize,
+    /// Sum of the gas limits of the transactions in the block.
+    pub gas: u64,
+    /// Sum of the priority fees of the transactions in the block, assuming every transaction
+    /// uses its entire gas limit.
+    pub value: u128,
+}
+
+/// Outcome of replaying a recording.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Number of transactions that were inserted into the pool.
+    pub inserted: usize,
+    /// Number of transactions that were rejected by the pool.
+    pub rejected: usize,
+    /// Number of transactions that were discarded because of the pool's limits.
+    pub discarded: usize,
+    /// The blocks built from the pool before every canonical update.
+    pub blocks: Vec<ReplayedBlock>,
+}
+
+impl ReplayReport {
+    /// Returns the summed value of all built blocks.
+    pub fn total_value(&self) -> u128 {
+        self.blocks.iter().map(|block| block.value).sum()
+    }
+}
+
+/// Replays the records into the pool and reports the blocks the pool would have built and the
+/// transactions it evicted.
+///
+/// Before every canonical update, a block is built from the best transactions of the pool
+/// within the recorded gas limit of the new block.
+pub async fn replay_records<O>(
+    pool: &ReplayPool<O>,
+    records: impl IntoIterator<Item = PoolRecord>,
+) -> ReplayReport
+where
+    O: TransactionOrdering<Transaction = EthPooledTransaction>,
+{
+    let mut events = pool.all_transactions_event_listener();
+    let mut report = ReplayReport::default();
+    let mut seen_update = false;
+
+    for record in records {
+        match record {
+            PoolRecord::Transaction { origin, state_nonce, balance, transaction } => {
+                let Some(transaction) = (*transaction).try_ecrecovered() else {
+                    report.rejected += 1;
+                    continue
+                };
+                let encoded_length = transaction.length_without_header();
+                let transaction = EthPooledTransaction::new(transaction, encoded_length);
+                pool.inner().validator().set_account(transaction.sender(), state_nonce, balance);
+                match pool.add_transaction(origin, transaction).await {
+                    Ok(_) => report.inserted += 1,
+                    Err(_) => report.rejected += 1,
+                }
+            }
+            PoolRecord::CanonicalUpdate(update) => {
+                // the pool only knows the fees of the pending block after the first update
+                if seen_update {
+                    report.blocks.push(build_block(pool, &update));
+                }
+                seen_update = true;
+                apply_update(pool, update);
+            }
+        }
+
+        while let Some(Some(event)) = events.next().now_or_never() {
+            if matches!(event, FullTransactionEvent::Discarded(_)) {
+                report.discarded += 1;
+            }
+        }
+    }
+
+    report
+}
+
+/// Builds a block from the best transactions of the pool within the gas limit of the update.
+fn build_block<O>(pool: &ReplayPool<O>, update: &RecordedStateUpdate) -> ReplayedBlock
+where
+    O: TransactionOrdering<Transaction = EthPooledTransaction>,
+{
+    let info = pool.block_info();
+    let mut best = pool.best_transactions_with_attributes(BestTransactionsAttributes::new(
+        info.pending_basefee,
+        info.pending_blob_fee.map(|fee| fee as u64),
+    ));
+
+    let mut block =
+        ReplayedBlock { number: update.block_number, transactions: 0, gas: 0, value: 0 };
+    let mut blobs = 0;
+    while let Some(tx) = best.next() {
+        let blob_count = tx.transaction.blob_count();
+        if block.gas + tx.gas_limit() > update.gas_limit || blobs + blob_count > MAX_BLOBS_PER_BLOCK
+        {
+            best.mark_invalid(&tx);
+            continue
+        }
+        block.gas += tx.gas_limit();
+        block.value += tx.effective_tip_per_gas(info.pending_basefee).unwrap_or_default() *
+            tx.gas_limit() as u128;
+        block.transactions += 1;
+        blobs += blob_count;
+    }
+    block
+}
+
+/// Applies the recorded update to the pool and the replay validator.
+fn apply_update<O>(pool: &ReplayPool<O>, update: RecordedStateUpdate)
+where
+    O: TransactionOrdering<Transaction = EthPooledTransaction>,
+{
+    let RecordedStateUpdate {
+        block_hash,
+        block_number,
+        timestamp,
+        gas_limit,
+        pending_block_base_fee,
+        pending_block_blob_fee,
+        changed_accounts,
+        mined_transactions,
+    } = update;
+
+    for account in &changed_accounts {
+        pool.inner().validator().set_account(account.address, account.nonce, account.balance);
+    }
+
+    let header = Header { number: block_number, timestamp, gas_limit, ..Default::default() };
+    let new_tip =
+        SealedBlock { header: SealedHeader::new(header, block_hash), ..Default::default() };
+    pool.on_canonical_state_change(CanonicalStateUpdate {
+        new_tip: &new_tip,
+        pending_block_base_fee,
+        pending_block_blob_fee,
+        changed_accounts,
+        mined_transactions,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::TransactionBuilder, SubPoolLimit};
+    use reth_primitives::B256;
+
+    fn records() -> Vec<PoolRecord> {
+        let signer = B256::random();
+        let mut records = vec![PoolRecord::CanonicalUpdate(RecordedStateUpdate {
+            block_hash: B256::random(),
+            block_number: 1,
+            timestamp: 12,
+            gas_limit: 30_000_000,
+            pending_block_base_fee: 7,
+            pending_block_blob_fee: None,
+            changed_accounts: Vec::new(),
+            mined_transactions: Vec::new(),
+        })];
+        for nonce in 0..10 {
+            let transaction = TransactionBuilder::default()
+                .signer(signer)
+                .nonce(nonce)
+                .gas_limit(21_000)
+                .max_fee_per_gas(100)
+                .max_priority_fee_per_gas(10)
+                .into_eip1559();
+            records.push(PoolRecord::Transaction {
+                origin: TransactionOrigin::External,
+                state_nonce: 0,
+                balance: U256::MAX,
+                transaction: Box::new(transaction),
+            });
+        }
+        records.push(PoolRecord::CanonicalUpdate(RecordedStateUpdate {
+            block_hash: B256::random(),
+            block_number: 2,
+            timestamp: 24,
+            gas_limit: 30_000_000,
+            pending_block_base_fee: 7,
+            pending_block_blob_fee: None,
+            changed_accounts: Vec::new(),
+            mined_transactions: Vec::new(),
+        }));
+        records
+    }
+
+    #[tokio::test]
+    async fn replay_with_different_orderings() {
+        let update = |block_number| {
+            PoolRecord::CanonicalUpdate(RecordedStateUpdate {
+                block_hash: B256::random(),
+                block_number,
+                timestamp: 12 * block_number,
+                gas_limit: 21_000,
+                pending_block_base_fee: 7,
+                pending_block_blob_fee: None,
+                changed_accounts: Vec::new(),
+                mined_transactions: Vec::new(),
+            })
+        };
+        let transaction = |max_priority_fee_per_gas, max_fee_per_gas| PoolRecord::Transaction {
+            origin: TransactionOrigin::External,
+            state_nonce: 0,
+            balance: U256::MAX,
+            transaction: Box::new(
+                TransactionBuilder::default()
+                    .signer(B256::random())
+                    .gas_limit(21_000)
+                    .max_fee_per_gas(max_fee_per_gas)
+                    .max_priority_fee_per_gas(max_priority_fee_per_gas)
+                    .into_eip1559(),
+            ),
+        };
+        let records = vec![
+            update(1),
+            transaction(1, 50),
+            transaction(10, 20),
+            transaction(2, 100),
+            update(2),
+        ];
+
+        for (ordering, value) in [
+            (ReplayOrdering::CoinbaseTip, 210_000),
+            (ReplayOrdering::MaxFee, 42_000),
+            (ReplayOrdering::Fifo, 21_000),
+        ] {
+            let pool = replay_pool(ordering, Default::default());
+            let report = replay_records(&pool, records.clone()).await;
+            assert_eq!(report.inserted, 3);
+            assert_eq!(
+                report.blocks,
+                vec![ReplayedBlock { number: 2, transactions: 1, gas: 21_000, value }],
+                "{ordering:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_with_different_limits() {
+        let pool = replay_pool(ReplayOrdering::CoinbaseTip, Default::default());
+        let report = replay_records(&pool, records()).await;
+        assert_eq!(report.inserted, 10);
+        assert_eq!(report.discarded, 0);
+        assert_eq!(
+            report.blocks,
+            vec![ReplayedBlock { number: 2, transactions: 10, gas: 210_000, value: 2_100_000 }]
+        );
+
+        let config = PoolConfig {
+            pending_limit: SubPoolLimit { max_txs: 5, max_size: usize::MAX },
+            ..Default::default()
+        };
+        let pool = replay_pool(ReplayOrdering::CoinbaseTip, config);
+        let limited = replay_records(&pool, records()).await;
+        assert_eq!(limited.inserted + limited.rejected, 10);
+        assert!(limited.blocks[0].transactions <= 5);
+        assert!(limited.total_value() < report.total_value());
+    }
+}
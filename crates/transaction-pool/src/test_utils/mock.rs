@@ -14,6 +14,7 @@ use rand::{
 };
 use reth_primitives::{
     constants::{eip4844::DATA_GAS_PER_BLOB, MIN_PROTOCOL_BASE_FEE},
+    eip7702::SignedAuthorization,
     transaction::TryFromRecoveredTransactionError,
     AccessList, Address, BlobTransactionSidecar, BlobTransactionValidationError, Bytes, ChainId,
     PooledTransactionsElementEcRecovered, Signature, Transaction, TransactionSigned,
@@ -771,6 +772,10 @@ impl EthPoolTransaction for MockTransaction {
     fn authorization_count(&self) -> usize {
         0
     }
+
+    fn authorization_list(&self) -> Option<&[SignedAuthorization]> {
+        None
+    }
 }
 
 impl TryFrom<TransactionSignedEcRecovered> for MockTransaction {
@@ -1154,6 +1159,9 @@ impl MockTransactionFactory {
             transaction,
             timestamp: Instant::now(),
             origin,
+            authority_ids: None,
+            conditional: None,
+            simulation: None,
         }
     }
 
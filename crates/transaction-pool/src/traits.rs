@@ -1,9 +1,14 @@
 #![allow(deprecated)]
 
 use crate::{
-    blobstore::BlobStoreError,
+    blobstore::{BlobAndProof, BlobStoreError},
+    conditional::TransactionConditional,
     error::PoolResult,
-    pool::{state::SubPool, BestTransactionFilter, TransactionEvents},
+    pool::{
+        bundle::{BestBundlesAndTransactions, NewBundle, PoolBundle},
+        state::SubPool,
+        BestTransactionFilter, TransactionEvents,
+    },
     validate::ValidPoolTransaction,
     AllTransactionsEvents,
 };
@@ -11,10 +16,11 @@ use futures_util::{ready, Stream};
 use reth_eth_wire_types::HandleMempoolData;
 use reth_execution_types::ChangedAccount;
 use reth_primitives::{
-    kzg::KzgSettings, transaction::TryFromRecoveredTransactionError, AccessList, Address,
-    BlobTransactionSidecar, BlobTransactionValidationError, PooledTransactionsElement,
-    PooledTransactionsElementEcRecovered, SealedBlock, Transaction, TransactionSignedEcRecovered,
-    TxHash, TxKind, B256, EIP1559_TX_TYPE_ID, EIP4844_TX_TYPE_ID, EIP7702_TX_TYPE_ID, U256,
+    eip7702::SignedAuthorization, kzg::KzgSettings, transaction::TryFromRecoveredTransactionError,
+    AccessList, Address, BlobTransactionSidecar, BlobTransactionValidationError,
+    PooledTransactionsElement, PooledTransactionsElementEcRecovered, SealedBlock, Transaction,
+    TransactionSignedEcRecovered, TxHash, TxKind, B256, EIP1559_TX_TYPE_ID, EIP4844_TX_TYPE_ID,
+    EIP7702_TX_TYPE_ID, U256,
 };
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -111,6 +117,44 @@ pub trait TransactionPool: Send + Sync + Clone {
         transactions: Vec<Self::Transaction>,
     ) -> impl Future<Output = Vec<PoolResult<TxHash>>> + Send;
 
+    /// Adds an _unvalidated_ transaction with the given [`TransactionConditional`] to the pool.
+    ///
+    /// The transaction is rejected if its conditions can't be met anymore. Transactions whose
+    /// conditions are not met yet are kept in the pool but are excluded from the best
+    /// transactions. Conditional transactions are never propagated.
+    ///
+    /// Consumer: RPC
+    fn add_conditional_transaction(
+        &self,
+        origin: TransactionOrigin,
+        transaction: Self::Transaction,
+        conditional: TransactionConditional,
+    ) -> impl Future<Output = PoolResult<TxHash>> + Send;
+
+    /// Adds the given bundle of _unvalidated_ transactions to the pool.
+    ///
+    /// All transactions of the bundle are validated, and the bundle is rejected if any of them is
+    /// invalid. The transactions are held separately from the subpools and are never propagated.
+    ///
+    /// Returns the hash of the bundle.
+    ///
+    /// Consumer: RPC
+    fn add_bundle(
+        &self,
+        origin: TransactionOrigin,
+        bundle: NewBundle<Self::Transaction>,
+    ) -> impl Future<Output = PoolResult<B256>> + Send;
+
+    /// Removes the bundle with the given hash from the pool.
+    ///
+    /// Consumer: RPC
+    fn remove_bundle(&self, hash: B256) -> Option<Arc<PoolBundle<Self::Transaction>>>;
+
+    /// Returns the bundle with the given hash, if it is in the pool.
+    ///
+    /// Consumer: RPC
+    fn get_bundle(&self, hash: B256) -> Option<Arc<PoolBundle<Self::Transaction>>>;
+
     /// Returns a new transaction change event stream for the given transaction.
     ///
     /// Returns `None` if the transaction is not in the pool.
@@ -266,6 +310,17 @@ pub trait TransactionPool: Send + Sync + Clone {
         best_transactions_attributes: BestTransactionsAttributes,
     ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<Self::Transaction>>>>;
 
+    /// Returns an iterator that first yields the bundles targeting the given block, ordered by
+    /// their effective tip, and then the transactions that are ready for block production with
+    /// the given attributes.
+    ///
+    /// Consumer: Block production
+    fn best_bundles_and_transactions(
+        &self,
+        best_transactions_attributes: BestTransactionsAttributes,
+        block_number: u64,
+    ) -> BestBundlesAndTransactions<Self::Transaction>;
+
     /// Returns all transactions that can be included in the next block.
     ///
     /// This is primarily used for the `txpool_` RPC namespace:
@@ -405,6 +460,12 @@ pub trait TransactionPool: Send + Sync + Clone {
         tx_hashes: Vec<TxHash>,
     ) -> Result<Vec<(TxHash, BlobTransactionSidecar)>, BlobStoreError>;
 
+    /// Returns the transaction hashes of all [`BlobTransactionSidecar`]s in the blob store.
+    ///
+    /// This includes the blobs of transactions that are no longer in the pool but were not
+    /// deleted.
+    fn get_all_blob_hashes(&self) -> Result<Vec<TxHash>, BlobStoreError>;
+
     /// Returns the exact [BlobTransactionSidecar] for the given transaction hashes in the order
     /// they were requested.
     ///
@@ -413,6 +474,15 @@ pub trait TransactionPool: Send + Sync + Clone {
         &self,
         tx_hashes: Vec<TxHash>,
     ) -> Result<Vec<BlobTransactionSidecar>, BlobStoreError>;
+
+    /// Returns the blobs and proofs for the given versioned hashes, in the order they were
+    /// requested.
+    ///
+    /// Blobs that are not in the blob store are returned as `None`.
+    fn get_blobs_for_versioned_hashes(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProof>>, BlobStoreError>;
 }
 
 /// Extension for [TransactionPool] trait that allows to set the current block info.
@@ -578,7 +648,7 @@ pub struct NewBlobSidecar {
 ///
 /// Depending on where the transaction was picked up, it affects how the transaction is handled
 /// internally, e.g. limits for simultaneous transaction of one sender.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
 pub enum TransactionOrigin {
     /// Transaction is coming from a local source.
     #[default]
@@ -732,6 +802,24 @@ pub trait BestTransactions: Iterator + Send {
     }
 }
 
+impl<I: BestTransactions + ?Sized> BestTransactions for Box<I> {
+    fn mark_invalid(&mut self, transaction: &Self::Item) {
+        (**self).mark_invalid(transaction)
+    }
+
+    fn no_updates(&mut self) {
+        (**self).no_updates()
+    }
+
+    fn skip_blobs(&mut self) {
+        (**self).skip_blobs()
+    }
+
+    fn set_skip_blobs(&mut self, skip_blobs: bool) {
+        (**self).set_skip_blobs(skip_blobs)
+    }
+}
+
 /// A no-op implementation that yields no transactions.
 impl<T> BestTransactions for std::iter::Empty<T> {
     fn mark_invalid(&mut self, _tx: &T) {}
@@ -909,6 +997,9 @@ pub trait EthPoolTransaction:
 
     /// Returns the number of authorizations this transaction has.
     fn authorization_count(&self) -> usize;
+
+    /// Returns the authorization list of an EIP-7702 transaction.
+    fn authorization_list(&self) -> Option<&[SignedAuthorization]>;
 }
 
 /// The default [`PoolTransaction`] for the [Pool](crate::Pool) for Ethereum.
@@ -1193,6 +1284,10 @@ impl EthPoolTransaction for EthPooledTransaction {
             _ => 0,
         }
     }
+
+    fn authorization_list(&self) -> Option<&[SignedAuthorization]> {
+        self.transaction.transaction.authorization_list()
+    }
 }
 
 impl TryFrom<TransactionSignedEcRecovered> for EthPooledTransaction {
@@ -3,19 +3,28 @@
 use super::constants::DEFAULT_MAX_TX_INPUT_BYTES;
 use crate::{
     blobstore::BlobStore,
-    error::{Eip4844PoolTransactionError, InvalidPoolTransactionError},
+    conditional::{ConditionalOutcome, TransactionConditional},
+    error::{
+        Eip4844PoolTransactionError, Eip7702PoolTransactionError, InvalidPoolTransactionError,
+    },
     traits::TransactionOrigin,
-    validate::{ValidTransaction, ValidationTask, MAX_INIT_CODE_BYTE_SIZE},
+    validate::{
+        simulation::TransactionSimulator, SimulationConfig, SimulationResults, ValidTransaction,
+        ValidationTask, MAX_INIT_CODE_BYTE_SIZE,
+    },
     EthBlobTransactionSidecar, EthPoolTransaction, LocalTransactionConfig, PoolTransaction,
     TransactionValidationOutcome, TransactionValidationTaskExecutor, TransactionValidator,
 };
 use reth_chainspec::{ChainSpec, EthereumHardforks};
 use reth_primitives::{
-    constants::eip4844::MAX_BLOBS_PER_BLOCK, GotExpected, InvalidTransactionError, SealedBlock,
-    EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, EIP4844_TX_TYPE_ID, EIP7702_TX_TYPE_ID,
-    LEGACY_TX_TYPE_ID,
+    constants::eip4844::MAX_BLOBS_PER_BLOCK, eip7702::delegated_address, Address, GotExpected,
+    InvalidTransactionError, SealedBlock, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID,
+    EIP4844_TX_TYPE_ID, EIP7702_TX_TYPE_ID, LEGACY_TX_TYPE_ID,
+};
+use reth_storage_api::{
+    errors::provider::ProviderResult, AccountReader, BlockReaderIdExt, StateProvider,
+    StateProviderFactory,
 };
-use reth_storage_api::{AccountReader, BlockReaderIdExt, StateProviderFactory};
 use reth_tasks::TaskSpawner;
 use revm::{
     interpreter::gas::validate_initial_tx_gas,
@@ -26,6 +35,7 @@ use std::{
     sync::{atomic::AtomicBool, Arc},
 };
 use tokio::sync::Mutex;
+use tracing::debug;
 
 /// Validator for Ethereum transactions.
 #[derive(Debug, Clone)]
@@ -44,6 +54,11 @@ impl<Client, Tx> EthTransactionValidator<Client, Tx> {
     pub fn client(&self) -> &Client {
         &self.inner.client
     }
+
+    /// Returns the results of the simulation stage, if simulation is enabled.
+    pub fn simulation_results(&self) -> Option<&SimulationResults> {
+        self.inner.simulator.as_ref().map(|simulator| simulator.results())
+    }
 }
 
 impl<Client, Tx> EthTransactionValidator<Client, Tx>
@@ -100,6 +115,10 @@ where
     fn on_new_head_block(&self, new_tip_block: &SealedBlock) {
         self.inner.on_new_head_block(new_tip_block)
     }
+
+    fn validate_conditional(&self, conditional: &TransactionConditional) -> ConditionalOutcome {
+        self.inner.validate_conditional(conditional)
+    }
 }
 
 /// A [`TransactionValidator`] implementation that validates ethereum transaction.
@@ -131,6 +150,8 @@ pub(crate) struct EthTransactionValidatorInner<Client, T> {
     local_transactions_config: LocalTransactionConfig,
     /// Maximum size in bytes a single transaction can have in order to be accepted into the pool.
     max_tx_input_bytes: usize,
+    /// Optional stage that executes new pending transactions against the latest state.
+    simulator: Option<TransactionSimulator>,
     /// Marker for the transaction type
     _marker: PhantomData<T>,
 }
@@ -274,6 +295,13 @@ where
                     InvalidTransactionError::TxTypeNotSupported.into(),
                 )
             }
+
+            if transaction.authorization_count() == 0 {
+                return TransactionValidationOutcome::Invalid(
+                    transaction,
+                    Eip7702PoolTransactionError::MissingEip7702AuthorizationList.into(),
+                )
+            }
         }
 
         if let Err(err) = ensure_intrinsic_gas(&transaction, &self.fork_tracker) {
@@ -315,11 +343,13 @@ where
             }
         }
 
-        let account = match self
-            .client
-            .latest()
-            .and_then(|state| state.basic_account(transaction.sender()))
-        {
+        let state = match self.client.latest() {
+            Ok(state) => state,
+            Err(err) => {
+                return TransactionValidationOutcome::Error(*transaction.hash(), Box::new(err))
+            }
+        };
+        let account = match state.basic_account(transaction.sender()) {
             Ok(account) => account.unwrap_or_default(),
             Err(err) => {
                 return TransactionValidationOutcome::Error(*transaction.hash(), Box::new(err))
@@ -327,8 +357,19 @@ where
         };
 
         // Signer account shouldn't have bytecode. Presence of bytecode means this is a
-        // smartcontract.
-        if account.has_bytecode() {
+        // smartcontract, unless the code is an EIP-7702 delegation.
+        let is_delegated = match account.bytecode_hash {
+            Some(code_hash) if account.has_bytecode() => match state.bytecode_by_hash(code_hash) {
+                Ok(code) => {
+                    code.is_some_and(|code| delegated_address(code.original_byte_slice()).is_some())
+                }
+                Err(err) => {
+                    return TransactionValidationOutcome::Error(*transaction.hash(), Box::new(err))
+                }
+            },
+            _ => false,
+        };
+        if account.has_bytecode() && !is_delegated {
             return TransactionValidationOutcome::Invalid(
                 transaction,
                 InvalidTransactionError::SignerAccountHasBytecode.into(),
@@ -343,6 +384,23 @@ where
             )
         }
 
+        // The delegated code can spend the balance of the account at any time, so the balance
+        // of the account can only be relied on for its next transaction.
+        if is_delegated && transaction.nonce() != account.nonce {
+            return TransactionValidationOutcome::Invalid(
+                transaction,
+                Eip7702PoolTransactionError::OutOfOrderTxFromDelegated.into(),
+            )
+        }
+
+        let authorities = match self.authorities(&transaction, &state) {
+            Ok(Ok(authorities)) => authorities,
+            Ok(Err(err)) => return TransactionValidationOutcome::Invalid(transaction, err.into()),
+            Err(err) => {
+                return TransactionValidationOutcome::Error(*transaction.hash(), Box::new(err))
+            }
+        };
+
         let cost = transaction.cost();
 
         // Checks for max cost
@@ -401,6 +459,16 @@ where
             }
         }
 
+        // Simulate transactions that are executable on top of the latest state. The outcome is
+        // only recorded and never makes the transaction invalid.
+        let simulation =
+            self.simulator.as_ref().filter(|_| transaction.nonce() == account.nonce).and_then(
+                |simulator| {
+                    let header = self.client.latest_header().ok().flatten()?;
+                    simulator.simulate(&self.chain_spec, &state, &header, &transaction)
+                },
+            );
+
         // Return the valid transaction
         TransactionValidationOutcome::Valid {
             balance: account.balance,
@@ -414,7 +482,57 @@ where
                 }
                 TransactionOrigin::Private => false,
             },
+            authorities,
+            simulation,
+        }
+    }
+
+    /// Validates the authorizations of an EIP-7702 transaction and returns the authorities whose
+    /// nonce will be bumped if the transaction is executed on top of the given state.
+    ///
+    /// Authorizations for another chain or with an invalid signature make the transaction
+    /// invalid. Authorizations that would be skipped during execution, because of a nonce
+    /// mismatch or because the authority has code other than a delegation, are ignored, as is
+    /// the sender itself.
+    fn authorities(
+        &self,
+        transaction: &Tx,
+        state: &dyn StateProvider,
+    ) -> ProviderResult<Result<Option<Vec<Address>>, Eip7702PoolTransactionError>> {
+        let Some(authorization_list) = transaction.authorization_list() else {
+            return Ok(Ok(None))
+        };
+
+        let mut authorities = Vec::with_capacity(authorization_list.len());
+        for authorization in authorization_list {
+            let chain_id = authorization.chain_id();
+            if chain_id != 0 && chain_id != self.chain_id() {
+                return Ok(Err(Eip7702PoolTransactionError::AuthorizationChainIdMismatch(chain_id)))
+            }
+            let Ok(authority) = authorization.recover_authority() else {
+                return Ok(Err(Eip7702PoolTransactionError::InvalidAuthorizationSignature))
+            };
+            if authority == transaction.sender() || authorities.contains(&authority) {
+                continue
+            }
+
+            let account = state.basic_account(authority)?.unwrap_or_default();
+            if authorization.nonce().is_some_and(|nonce| nonce != account.nonce) {
+                continue
+            }
+            // an authority that is already delegated can be delegated again
+            if let Some(code_hash) = account.bytecode_hash.filter(|_| account.has_bytecode()) {
+                let is_delegated = state
+                    .bytecode_by_hash(code_hash)?
+                    .is_some_and(|code| delegated_address(code.original_byte_slice()).is_some());
+                if !is_delegated {
+                    continue
+                }
+            }
+            authorities.push(authority);
         }
+
+        Ok(Ok(Some(authorities)))
     }
 
     fn on_new_head_block(&self, new_tip_block: &SealedBlock) {
@@ -430,6 +548,40 @@ where
         if self.chain_spec.is_prague_active_at_timestamp(new_tip_block.timestamp) {
             self.fork_tracker.prague.store(true, std::sync::atomic::Ordering::Relaxed);
         }
+
+        if let Some(simulator) = &self.simulator {
+            simulator.on_new_head_block();
+        }
+    }
+
+    /// Checks the conditions against the block following the latest block and the latest state.
+    ///
+    /// If the latest block or state can't be loaded, the conditions are considered pending so
+    /// they're checked again on the next block.
+    fn validate_conditional(&self, conditional: &TransactionConditional) -> ConditionalOutcome {
+        let header = match self.client.latest_header() {
+            Ok(Some(header)) => header,
+            Ok(None) => return ConditionalOutcome::Pending,
+            Err(err) => {
+                debug!(target: "txpool", %err, "failed to load latest header for conditional");
+                return ConditionalOutcome::Pending
+            }
+        };
+
+        let outcome = conditional.check_next_block(header.number, header.timestamp);
+        if matches!(outcome, ConditionalOutcome::Invalid(_)) ||
+            conditional.known_accounts.is_empty()
+        {
+            return outcome
+        }
+
+        match self.client.latest().and_then(|state| conditional.check_known_accounts(&state)) {
+            Ok(accounts) => outcome.and(accounts),
+            Err(err) => {
+                debug!(target: "txpool", %err, "failed to check known accounts of conditional");
+                ConditionalOutcome::Pending
+            }
+        }
     }
 }
 
@@ -459,6 +611,12 @@ pub struct EthTransactionValidatorBuilder {
     ///
     /// Default is 1
     additional_tasks: usize,
+    /// Determines how many tasks to spawn for a separate queue of
+    /// [`TransactionOrigin::External`](TransactionOrigin) transactions, if `0` they share the
+    /// queue of local transactions.
+    ///
+    /// Default is 0
+    external_tasks: usize,
 
     /// Stores the setup and parameters needed for validating KZG proofs.
     kzg_settings: EnvKzgSettings,
@@ -466,6 +624,8 @@ pub struct EthTransactionValidatorBuilder {
     local_transactions_config: LocalTransactionConfig,
     /// Max size in bytes of a single transaction allowed
     max_tx_input_bytes: usize,
+    /// Configuration of the simulation stage, disabled if `None`.
+    simulation: Option<SimulationConfig>,
 }
 
 impl EthTransactionValidatorBuilder {
@@ -483,9 +643,11 @@ impl EthTransactionValidatorBuilder {
             chain_spec,
             minimum_priority_fee: None,
             additional_tasks: 1,
+            external_tasks: 0,
             kzg_settings: EnvKzgSettings::Default,
             local_transactions_config: Default::default(),
             max_tx_input_bytes: DEFAULT_MAX_TX_INPUT_BYTES,
+            simulation: None,
 
             // by default all transaction types are allowed
             eip2718: true,
@@ -597,6 +759,16 @@ impl EthTransactionValidatorBuilder {
         self
     }
 
+    /// Sets the number of tasks that validate external transactions in their own queue.
+    ///
+    /// This keeps a flood of transactions from the network from delaying the validation of local
+    /// transactions. If set to `0`, the default, external transactions share the queue of local
+    /// transactions.
+    pub const fn with_external_tasks(mut self, external_tasks: usize) -> Self {
+        self.external_tasks = external_tasks;
+        self
+    }
+
     /// Configures validation rules based on the head block's timestamp.
     ///
     /// For example, whether the Shanghai and Cancun hardfork is activated at launch.
@@ -612,6 +784,14 @@ impl EthTransactionValidatorBuilder {
         self
     }
 
+    /// Enables simulating new pending transactions against the latest state.
+    ///
+    /// See [`EthTransactionValidator::simulation_results`].
+    pub const fn with_simulation(mut self, simulation: SimulationConfig) -> Self {
+        self.simulation = Some(simulation);
+        self
+    }
+
     /// Sets the block gas limit
     ///
     /// Transactions with a gas limit greater than this will be rejected.
@@ -643,6 +823,7 @@ impl EthTransactionValidatorBuilder {
             kzg_settings,
             local_transactions_config,
             max_tx_input_bytes,
+            simulation,
             ..
         } = self;
 
@@ -666,6 +847,9 @@ impl EthTransactionValidatorBuilder {
             kzg_settings,
             local_transactions_config,
             max_tx_input_bytes,
+            simulator: simulation.map(|config| {
+                TransactionSimulator::new(config, SimulationResults::new(config.max_results))
+            }),
             _marker: Default::default(),
         };
 
@@ -675,9 +859,10 @@ impl EthTransactionValidatorBuilder {
     /// Builds a the [`EthTransactionValidator`] and spawns validation tasks via the
     /// [`TransactionValidationTaskExecutor`]
     ///
-    /// The validator will spawn `additional_tasks` additional tasks for validation.
+    /// The validator will spawn `additional_tasks` additional tasks for validation, and
+    /// `external_tasks` tasks for the separate queue of external transactions.
     ///
-    /// By default this will spawn 1 additional task.
+    /// By default this will spawn 1 additional task and no external tasks.
     pub fn build_with_tasks<Client, Tx, T, S>(
         self,
         client: Client,
@@ -689,6 +874,7 @@ impl EthTransactionValidatorBuilder {
         S: BlobStore,
     {
         let additional_tasks = self.additional_tasks;
+        let external_tasks = self.external_tasks;
         let validator = self.build(client, blob_store);
 
         let (tx, task) = ValidationTask::new();
@@ -712,7 +898,28 @@ impl EthTransactionValidatorBuilder {
 
         let to_validation_task = Arc::new(Mutex::new(tx));
 
-        TransactionValidationTaskExecutor { validator, to_validation_task }
+        let to_external_validation_task = (external_tasks > 0).then(|| {
+            let (tx, task) = ValidationTask::new();
+            for _ in 1..external_tasks {
+                let task = task.clone();
+                tasks.spawn_blocking(Box::pin(async move {
+                    task.run().await;
+                }));
+            }
+            tasks.spawn_critical_blocking(
+                "transaction-validation-service-external",
+                Box::pin(async move {
+                    task.run().await;
+                }),
+            );
+            Arc::new(Mutex::new(tx))
+        });
+
+        TransactionValidationTaskExecutor {
+            validator,
+            to_validation_task,
+            to_external_validation_task,
+        }
     }
 }
 
@@ -798,7 +1005,10 @@ mod tests {
         EthPooledTransaction, Pool, TransactionPool,
     };
     use reth_chainspec::MAINNET;
-    use reth_primitives::{hex, PooledTransactionsElement, U256};
+    use reth_primitives::{
+        hex, sign_message, Block, Bytes, Header, PooledTransactionsElement, Transaction,
+        TransactionSigned, TxEip7702, TxKind, B256, U256,
+    };
     use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
 
     fn get_transaction() -> EthPooledTransaction {
@@ -880,4 +1090,157 @@ mod tests {
         let tx = pool.get(transaction.hash());
         assert!(tx.is_none());
     }
+
+    #[test]
+    fn delegated_sender() {
+        let transaction = get_transaction();
+        let mut code = reth_primitives::eip7702::DELEGATION_DESIGNATOR.to_vec();
+        code.extend_from_slice(Address::random().as_slice());
+
+        let provider = MockEthProvider::default();
+        provider.add_account(
+            transaction.sender(),
+            ExtendedAccount::new(transaction.nonce(), U256::MAX).with_bytecode(code.clone().into()),
+        );
+        let validator = EthTransactionValidatorBuilder::new(MAINNET.clone())
+            .build(provider.clone(), InMemoryBlobStore::default());
+        let outcome = validator.validate_one(TransactionOrigin::External, transaction.clone());
+        assert!(outcome.is_valid());
+
+        // only the next transaction of a delegated account is accepted
+        provider.add_account(
+            transaction.sender(),
+            ExtendedAccount::new(transaction.nonce() - 1, U256::MAX).with_bytecode(code.into()),
+        );
+        let outcome = validator.validate_one(TransactionOrigin::External, transaction.clone());
+        assert!(matches!(
+            outcome,
+            TransactionValidationOutcome::Invalid(
+                _,
+                InvalidPoolTransactionError::Eip7702(
+                    Eip7702PoolTransactionError::OutOfOrderTxFromDelegated
+                )
+            )
+        ));
+
+        // any other code is still rejected
+        provider.add_account(
+            transaction.sender(),
+            ExtendedAccount::new(transaction.nonce(), U256::MAX)
+                .with_bytecode(Bytes::from_static(&[0x60, 0x00])),
+        );
+        let outcome = validator.validate_one(TransactionOrigin::External, transaction);
+        assert!(matches!(
+            outcome,
+            TransactionValidationOutcome::Invalid(
+                _,
+                InvalidPoolTransactionError::Consensus(
+                    InvalidTransactionError::SignerAccountHasBytecode
+                )
+            )
+        ));
+    }
+
+    #[test]
+    fn delegated_authority() {
+        let authorization = reth_primitives::eip7702::Authorization {
+            chain_id: 1,
+            address: Address::random(),
+            nonce: Some(0).into(),
+        };
+        let signature =
+            sign_message(B256::with_last_byte(1), authorization.signature_hash()).unwrap();
+        let authorization = authorization.into_signed(
+            reth_primitives::alloy_primitives::Signature::from_rs_and_parity(
+                signature.r,
+                signature.s,
+                signature.odd_y_parity,
+            )
+            .unwrap(),
+        );
+        let authority = authorization.recover_authority().unwrap();
+
+        let sender = Address::random();
+        let transaction = Transaction::Eip7702(TxEip7702 {
+            chain_id: 1,
+            gas_limit: 100_000,
+            max_fee_per_gas: 1_000_000_000,
+            to: TxKind::Call(Address::random()),
+            authorization_list: vec![authorization],
+            ..Default::default()
+        });
+        let transaction =
+            TransactionSigned::from_transaction_and_signature(transaction, Default::default())
+                .with_signer(sender);
+        let encoded_length = transaction.length_without_header();
+        let transaction = EthPooledTransaction::new(transaction, encoded_length);
+
+        let mut code = reth_primitives::eip7702::DELEGATION_DESIGNATOR.to_vec();
+        code.extend_from_slice(Address::random().as_slice());
+
+        let provider = MockEthProvider::default();
+        provider.add_account(sender, ExtendedAccount::new(0, U256::MAX));
+        provider.add_account(authority, ExtendedAccount::new(0, U256::ZERO));
+        let validator = EthTransactionValidatorBuilder::new(MAINNET.clone())
+            .set_prague(true)
+            .build(provider.clone(), InMemoryBlobStore::default());
+        let authorities = |outcome| match outcome {
+            TransactionValidationOutcome::Valid { authorities, .. } => authorities.unwrap(),
+            _ => panic!("transaction is invalid"),
+        };
+
+        let outcome = validator.validate_one(TransactionOrigin::External, transaction.clone());
+        assert_eq!(authorities(outcome), vec![authority]);
+
+        // an authority that already delegated its code can be delegated again
+        provider
+            .add_account(authority, ExtendedAccount::new(0, U256::ZERO).with_bytecode(code.into()));
+        let outcome = validator.validate_one(TransactionOrigin::External, transaction.clone());
+        assert_eq!(authorities(outcome), vec![authority]);
+
+        // the authorization of an authority with any other code is skipped
+        provider.add_account(
+            authority,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(Bytes::from_static(&[0x60, 0x00])),
+        );
+        let outcome = validator.validate_one(TransactionOrigin::External, transaction);
+        assert!(authorities(outcome).is_empty());
+    }
+
+    #[test]
+    fn simulate_pending_transaction() {
+        let transaction = get_transaction();
+
+        let provider = MockEthProvider::default();
+        provider.add_account(
+            transaction.sender(),
+            ExtendedAccount::new(transaction.nonce(), U256::MAX),
+        );
+        let header = Header {
+            number: 1,
+            gas_limit: 30_000_000,
+            excess_blob_gas: Some(0),
+            blob_gas_used: Some(0),
+            ..Default::default()
+        };
+        provider.add_block(B256::random(), Block { header, ..Default::default() });
+
+        let validator = EthTransactionValidatorBuilder::new(MAINNET.clone())
+            .with_simulation(SimulationConfig::default())
+            .build(provider, InMemoryBlobStore::default());
+
+        let outcome = validator.validate_one(TransactionOrigin::External, transaction.clone());
+        let TransactionValidationOutcome::Valid { simulation: Some(outcome_simulation), .. } =
+            outcome
+        else {
+            panic!("expected simulated valid transaction")
+        };
+
+        let simulation = validator.simulation_results().unwrap().get(transaction.hash()).unwrap();
+        assert_eq!(outcome_simulation, simulation);
+        assert_eq!(simulation.block_number, 1);
+        assert_eq!(simulation.gas_limit, transaction.gas_limit());
+        assert!(!simulation.is_revert());
+        assert!(!simulation.should_deprioritize());
+    }
 }
@@ -1,6 +1,7 @@
 //! Transaction validation abstractions.
 
 use crate::{
+    conditional::{ConditionalOutcome, TransactionConditional},
     error::InvalidPoolTransactionError,
     identifier::{SenderId, TransactionId},
     traits::{PoolTransaction, TransactionOrigin},
@@ -15,11 +16,18 @@ use std::{fmt, future::Future, time::Instant};
 
 mod constants;
 mod eth;
+mod simulation;
 mod task;
 
 /// A `TransactionValidator` implementation that validates ethereum transaction.
 pub use eth::*;
 
+/// Optional simulation of new pending transactions.
+pub use simulation::{
+    SimulationConfig, SimulationResults, SimulationStatus, TransactionSimulation,
+    DEFAULT_LOW_GAS_USAGE_PERCENT, DEFAULT_MAX_SIMULATION_RESULTS, DEFAULT_SIMULATION_GAS_BUDGET,
+};
+
 /// A spawnable task that performs transaction validation.
 pub use task::{TransactionValidationTaskExecutor, ValidationTask};
 
@@ -46,6 +54,12 @@ pub enum TransactionValidationOutcome<T: PoolTransaction> {
         transaction: ValidTransaction<T>,
         /// Whether to propagate the transaction to the network.
         propagate: bool,
+        /// The authorities of an EIP-7702 transaction whose nonce will be bumped by one of its
+        /// authorizations, excluding the sender.
+        authorities: Option<Vec<Address>>,
+        /// The outcome of executing the transaction against the latest state, if the validator
+        /// simulated it.
+        simulation: Option<TransactionSimulation>,
     },
     /// The transaction is considered invalid indefinitely: It violates constraints that prevent
     /// this transaction from ever becoming valid.
@@ -211,6 +225,17 @@ pub trait TransactionValidator: Send + Sync {
     ///
     /// This can be used to update fork specific values (timestamp).
     fn on_new_head_block(&self, _new_tip_block: &SealedBlock) {}
+
+    /// Checks the [`TransactionConditional`] of a conditional transaction against the block
+    /// following the latest block and the latest state.
+    ///
+    /// This is invoked when a conditional transaction is added to the pool and on every canonical
+    /// state change for all conditional transactions in the pool.
+    ///
+    /// By default, the conditions are not checked and considered met.
+    fn validate_conditional(&self, _conditional: &TransactionConditional) -> ConditionalOutcome {
+        ConditionalOutcome::Met
+    }
 }
 
 impl<A, B> TransactionValidator for Either<A, B>
@@ -247,6 +272,13 @@ where
             Self::Right(v) => v.on_new_head_block(new_tip_block),
         }
     }
+
+    fn validate_conditional(&self, conditional: &TransactionConditional) -> ConditionalOutcome {
+        match self {
+            Self::Left(v) => v.validate_conditional(conditional),
+            Self::Right(v) => v.validate_conditional(conditional),
+        }
+    }
 }
 
 /// A valid transaction in the pool.
@@ -266,6 +298,15 @@ pub struct ValidPoolTransaction<T: PoolTransaction> {
     pub timestamp: Instant,
     /// Where this transaction originated from.
     pub origin: TransactionOrigin,
+    /// The identifiers of the authorities whose nonce will be bumped by this EIP-7702
+    /// transaction.
+    pub authority_ids: Option<Vec<SenderId>>,
+    /// The conditions under which this transaction can be included, if it was submitted as a
+    /// conditional transaction.
+    pub conditional: Option<Box<TransactionConditional>>,
+    /// The outcome of executing the transaction against the latest state when it was validated,
+    /// if the validator simulated it.
+    pub simulation: Option<TransactionSimulation>,
 }
 
 // === impl ValidPoolTransaction ===
@@ -276,6 +317,12 @@ impl<T: PoolTransaction> ValidPoolTransaction<T> {
         self.transaction.hash()
     }
 
+    /// Returns `true` if the simulation of the transaction predicts that it reverts or uses far
+    /// less gas than its limit, so block builders should try it last.
+    pub fn should_deprioritize(&self) -> bool {
+        self.simulation.is_some_and(|simulation| simulation.should_deprioritize())
+    }
+
     /// Returns the type identifier of the transaction
     pub fn tx_type(&self) -> u8 {
         self.transaction.tx_type()
@@ -301,6 +348,16 @@ impl<T: PoolTransaction> ValidPoolTransaction<T> {
         &self.transaction_id
     }
 
+    /// Returns the identifiers of the authorities whose nonce will be bumped by this transaction.
+    pub(crate) fn authority_ids(&self) -> &[SenderId] {
+        self.authority_ids.as_deref().unwrap_or_default()
+    }
+
+    /// Returns `true` if this transaction was submitted with a [`TransactionConditional`].
+    pub const fn is_conditional(&self) -> bool {
+        self.conditional.is_some()
+    }
+
     /// Returns the length of the rlp encoded transaction
     #[inline]
     pub fn encoded_length(&self) -> usize {
@@ -397,6 +454,9 @@ impl<T: PoolTransaction> Clone for ValidPoolTransaction<T> {
             propagate: self.propagate,
             timestamp: self.timestamp,
             origin: self.origin,
+            authority_ids: self.authority_ids.clone(),
+            conditional: self.conditional.clone(),
+            simulation: self.simulation,
         }
     }
 }
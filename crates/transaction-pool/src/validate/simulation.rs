@@ -0,0 +1,360 @@
+//! Optional simulation of new pending transactions against the latest state.
+//!
+//! The [`EthTransactionValidator`](crate::EthTransactionValidator) only performs static checks.
+//! If simulation is enabled, every transaction that is executable on top of the latest state (its
+//! nonce matches the sender's nonce) is additionally executed with revm and the outcome is
+//! recorded in [`SimulationResults`] and on the
+//! [`ValidPoolTransaction`](crate::ValidPoolTransaction). Transactions are never rejected because
+//! of the outcome of the simulation, the results are hints: block builders try deprioritised
+//! transactions last and RPC handlers warn about them.
+
+use crate::EthPoolTransaction;
+use parking_lot::Mutex;
+use reth_chainspec::ChainSpec;
+use reth_evm_ethereum::revm_spec;
+use reth_primitives::{transaction::FillTxEnv, Head, SealedHeader, TxHash};
+use reth_revm::database::StateProviderDatabase;
+use reth_storage_api::StateProvider;
+use revm::{
+    primitives::{
+        BlobExcessGasAndPrice, BlockEnv, CfgEnv, CfgEnvWithHandlerCfg, EnvWithHandlerCfg,
+        ExecutionResult, SpecId, TxEnv, U256,
+    },
+    Evm,
+};
+use schnellru::{ByLength, LruMap};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::trace;
+
+/// Default amount of gas that can be spent on simulations per block: 10 full mainnet blocks.
+pub const DEFAULT_SIMULATION_GAS_BUDGET: u64 = 300_000_000;
+
+/// Default threshold in percent of the gas limit below which a transaction is considered to use
+/// far less gas than it asks for.
+pub const DEFAULT_LOW_GAS_USAGE_PERCENT: u64 = 10;
+
+/// Default number of simulation results to keep.
+pub const DEFAULT_MAX_SIMULATION_RESULTS: u32 = 10_000;
+
+/// Configuration for the simulation stage of the
+/// [`EthTransactionValidator`](crate::EthTransactionValidator).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationConfig {
+    /// Total gas that can be spent on simulations until the next block.
+    ///
+    /// Transactions that don't fit into the remaining budget are not simulated.
+    pub gas_budget_per_block: u64,
+    /// Transactions that use less than this percentage of their gas limit are flagged as
+    /// [`TransactionSimulation::is_low_gas_usage`].
+    pub low_gas_usage_percent: u64,
+    /// Maximum number of results to keep, the least recently used results are dropped first.
+    pub max_results: u32,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            gas_budget_per_block: DEFAULT_SIMULATION_GAS_BUDGET,
+            low_gas_usage_percent: DEFAULT_LOW_GAS_USAGE_PERCENT,
+            max_results: DEFAULT_MAX_SIMULATION_RESULTS,
+        }
+    }
+}
+
+/// How the simulated execution of a transaction ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum SimulationStatus {
+    /// The transaction executed successfully.
+    Success,
+    /// The transaction reverted.
+    Revert,
+    /// The transaction halted, e.g. because it ran out of gas.
+    Halt,
+}
+
+/// Outcome of the simulation of a single transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct TransactionSimulation {
+    /// How the execution ended.
+    pub status: SimulationStatus,
+    /// Gas used by the transaction.
+    #[cfg_attr(feature = "serde", serde(with = "alloy_serde::quantity"))]
+    pub gas_used: u64,
+    /// Gas limit of the transaction.
+    #[cfg_attr(feature = "serde", serde(with = "alloy_serde::quantity"))]
+    pub gas_limit: u64,
+    /// Number of the block the transaction was simulated on top of.
+    #[cfg_attr(feature = "serde", serde(with = "alloy_serde::quantity"))]
+    pub block_number: u64,
+    /// Whether the transaction used less gas than the configured threshold.
+    pub low_gas_usage: bool,
+}
+
+impl TransactionSimulation {
+    /// Returns `true` if the transaction reverted or halted.
+    pub const fn is_revert(&self) -> bool {
+        matches!(self.status, SimulationStatus::Revert | SimulationStatus::Halt)
+    }
+
+    /// Returns `true` if the transaction used far less gas than its limit.
+    pub const fn is_low_gas_usage(&self) -> bool {
+        self.low_gas_usage
+    }
+
+    /// Returns `true` if the transaction should be deprioritised by block builders.
+    pub const fn should_deprioritize(&self) -> bool {
+        self.is_revert() || self.is_low_gas_usage()
+    }
+}
+
+/// Shared handle to the results of the simulation stage, keyed by transaction hash.
+///
+/// Results are hints: they reflect the state the transaction was simulated against, which can be
+/// outdated by the time the transaction is included.
+#[derive(Debug, Clone)]
+pub struct SimulationResults {
+    inner: Arc<Mutex<LruMap<TxHash, TransactionSimulation, ByLength>>>,
+}
+
+impl SimulationResults {
+    /// Creates a new empty set of results holding at most `max_results` entries.
+    pub fn new(max_results: u32) -> Self {
+        Self { inner: Arc::new(Mutex::new(LruMap::new(ByLength::new(max_results)))) }
+    }
+
+    /// Returns the simulation outcome of the given transaction, if it was simulated.
+    pub fn get(&self, hash: &TxHash) -> Option<TransactionSimulation> {
+        self.inner.lock().peek(hash).copied()
+    }
+
+    /// Returns `true` if the given transaction was simulated and should be deprioritised.
+    pub fn should_deprioritize(&self, hash: &TxHash) -> bool {
+        self.get(hash).is_some_and(|sim| sim.should_deprioritize())
+    }
+
+    /// Removes the result of the given transaction.
+    pub fn remove(&self, hash: &TxHash) -> Option<TransactionSimulation> {
+        self.inner.lock().remove(hash)
+    }
+
+    /// Returns the number of stored results.
+    pub fn len(&self) -> usize {
+        self.inner.lock().len()
+    }
+
+    /// Returns `true` if no results are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert(&self, hash: TxHash, simulation: TransactionSimulation) {
+        self.inner.lock().insert(hash, simulation);
+    }
+}
+
+impl Default for SimulationResults {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_SIMULATION_RESULTS)
+    }
+}
+
+/// Returns the environment of the block on top of the given header, which transactions are
+/// simulated in.
+///
+/// The timestamp of the next block is not known yet, so the current time is used if it is later
+/// than the header's. Fee checks are already done by the validator, so the base fee is zeroed out.
+fn next_block_env(
+    chain_spec: &ChainSpec,
+    header: &SealedHeader,
+) -> (CfgEnvWithHandlerCfg, BlockEnv) {
+    let number = header.number + 1;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let timestamp = now.max(header.timestamp + 1);
+
+    let spec_id = revm_spec(
+        chain_spec,
+        &Head {
+            number,
+            timestamp,
+            total_difficulty: chain_spec.final_paris_total_difficulty(number).unwrap_or_default(),
+            difficulty: header.difficulty,
+            ..Default::default()
+        },
+    );
+    let mut cfg = CfgEnv::default();
+    cfg.chain_id = chain_spec.chain().id();
+    let cfg = CfgEnvWithHandlerCfg::new_with_spec_id(cfg, spec_id);
+
+    let block_env = BlockEnv {
+        number: U256::from(number),
+        coinbase: header.beneficiary,
+        timestamp: U256::from(timestamp),
+        gas_limit: U256::from(header.gas_limit),
+        basefee: U256::ZERO,
+        difficulty: if spec_id >= SpecId::MERGE { U256::ZERO } else { header.difficulty },
+        prevrandao: (spec_id >= SpecId::MERGE).then_some(header.mix_hash),
+        blob_excess_gas_and_price: (spec_id >= SpecId::CANCUN).then(|| {
+            BlobExcessGasAndPrice::new(header.next_block_excess_blob_gas().unwrap_or_default())
+        }),
+    };
+    (cfg, block_env)
+}
+
+/// Executes transactions against the latest state within the configured gas budget.
+#[derive(Debug)]
+pub(crate) struct TransactionSimulator {
+    config: SimulationConfig,
+    results: SimulationResults,
+    /// Gas that can still be spent on simulations until the next block.
+    remaining_gas: AtomicU64,
+}
+
+impl TransactionSimulator {
+    pub(crate) const fn new(config: SimulationConfig, results: SimulationResults) -> Self {
+        Self { remaining_gas: AtomicU64::new(config.gas_budget_per_block), config, results }
+    }
+
+    pub(crate) const fn results(&self) -> &SimulationResults {
+        &self.results
+    }
+
+    /// Resets the gas budget.
+    pub(crate) fn on_new_head_block(&self) {
+        self.remaining_gas.store(self.config.gas_budget_per_block, Ordering::Relaxed);
+    }
+
+    /// Reserves the given amount of gas from the budget, returns `false` if it is exhausted.
+    fn reserve_gas(&self, gas: u64) -> bool {
+        self.remaining_gas
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                remaining.checked_sub(gas)
+            })
+            .is_ok()
+    }
+
+    /// Simulates the transaction on top of the given state and header and records the outcome.
+    ///
+    /// Returns `None` if the transaction was not simulated, either because the budget is
+    /// exhausted or because the transaction could not be executed.
+    pub(crate) fn simulate<Tx: EthPoolTransaction>(
+        &self,
+        chain_spec: &ChainSpec,
+        state: &dyn StateProvider,
+        header: &SealedHeader,
+        transaction: &Tx,
+    ) -> Option<TransactionSimulation> {
+        let gas_limit = transaction.gas_limit();
+        if !self.reserve_gas(gas_limit) {
+            trace!(target: "txpool", hash=%transaction.hash(), "simulation budget exhausted");
+            return None
+        }
+
+        let (cfg, block_env) = next_block_env(chain_spec, header);
+
+        let recovered = transaction.clone().into_consensus();
+        let mut tx_env = TxEnv::default();
+        recovered.fill_tx_env(&mut tx_env, recovered.signer());
+
+        let mut evm = Evm::builder()
+            .with_db(StateProviderDatabase::new(state))
+            .with_env_with_handler_cfg(EnvWithHandlerCfg::new_with_cfg_env(cfg, block_env, tx_env))
+            .build();
+        let result = match evm.transact() {
+            Ok(res) => res.result,
+            Err(err) => {
+                trace!(target: "txpool", hash=%transaction.hash(), %err, "failed to simulate transaction");
+                self.remaining_gas.fetch_add(gas_limit, Ordering::Relaxed);
+                return None
+            }
+        };
+
+        let (status, gas_used) = match result {
+            ExecutionResult::Success { gas_used, .. } => (SimulationStatus::Success, gas_used),
+            ExecutionResult::Revert { gas_used, .. } => (SimulationStatus::Revert, gas_used),
+            ExecutionResult::Halt { gas_used, .. } => (SimulationStatus::Halt, gas_used),
+        };
+        // only charge the gas that was actually used
+        self.remaining_gas.fetch_add(gas_limit.saturating_sub(gas_used), Ordering::Relaxed);
+
+        let low_gas_usage = (gas_used as u128) * 100 <
+            (gas_limit as u128) * self.config.low_gas_usage_percent as u128;
+        let simulation = TransactionSimulation {
+            status,
+            gas_used,
+            gas_limit,
+            block_number: header.number,
+            low_gas_usage,
+        };
+        self.results.insert(*transaction.hash(), simulation);
+
+        Some(simulation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_chainspec::{ChainSpecBuilder, MAINNET};
+    use reth_primitives::Header;
+
+    #[test]
+    fn gas_budget() {
+        let simulator = TransactionSimulator::new(
+            SimulationConfig { gas_budget_per_block: 100, ..Default::default() },
+            SimulationResults::default(),
+        );
+        assert!(simulator.reserve_gas(60));
+        assert!(!simulator.reserve_gas(60));
+        assert!(simulator.reserve_gas(40));
+
+        simulator.on_new_head_block();
+        assert!(simulator.reserve_gas(100));
+    }
+
+    #[test]
+    fn next_block_env_uses_spec_of_next_block() {
+        let header = SealedHeader::new(
+            Header { number: 100, timestamp: 1, difficulty: U256::from(2), ..Default::default() },
+            Default::default(),
+        );
+
+        // pre-merge blocks are not executed with the merge spec
+        let chain_spec = ChainSpecBuilder::default()
+            .chain(MAINNET.chain)
+            .genesis(MAINNET.genesis.clone())
+            .london_activated()
+            .build();
+        let (cfg, block_env) = next_block_env(&chain_spec, &header);
+        assert_eq!(cfg.handler_cfg.spec_id, SpecId::LONDON);
+        assert_eq!(block_env.number, U256::from(101));
+        assert_eq!(block_env.difficulty, U256::from(2));
+        assert_eq!(block_env.prevrandao, None);
+        assert_eq!(block_env.blob_excess_gas_and_price, None);
+
+        // the next block is not built on the timestamp of its parent
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert!(block_env.timestamp >= U256::from(now));
+
+        let chain_spec = ChainSpecBuilder::mainnet().cancun_activated().build();
+        let (cfg, block_env) = next_block_env(&chain_spec, &header);
+        assert_eq!(cfg.handler_cfg.spec_id, SpecId::CANCUN);
+        assert_eq!(block_env.difficulty, U256::ZERO);
+        assert!(block_env.blob_excess_gas_and_price.is_some());
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::{
     blobstore::BlobStore,
+    conditional::{ConditionalOutcome, TransactionConditional},
     validate::{EthTransactionValidatorBuilder, TransactionValidatorError},
     EthTransactionValidator, PoolTransaction, TransactionOrigin, TransactionValidationOutcome,
     TransactionValidator,
@@ -87,6 +88,10 @@ pub struct TransactionValidationTaskExecutor<V> {
     pub validator: V,
     /// The sender half to validation tasks that perform the actual validation.
     pub to_validation_task: Arc<sync::Mutex<ValidationJobSender>>,
+    /// The sender half to the validation tasks of [`TransactionOrigin::External`] transactions.
+    ///
+    /// If `None`, external transactions are sent to [`Self::to_validation_task`].
+    pub to_external_validation_task: Option<Arc<sync::Mutex<ValidationJobSender>>>,
 }
 
 // === impl TransactionValidationTaskExecutor ===
@@ -107,6 +112,7 @@ impl<V> TransactionValidationTaskExecutor<V> {
         TransactionValidationTaskExecutor {
             validator: f(self.validator),
             to_validation_task: self.to_validation_task,
+            to_external_validation_task: self.to_external_validation_task,
         }
     }
 }
@@ -163,7 +169,24 @@ impl<V> TransactionValidationTaskExecutor<V> {
     /// validation tasks.
     pub fn new(validator: V) -> Self {
         let (tx, _) = ValidationTask::new();
-        Self { validator, to_validation_task: Arc::new(sync::Mutex::new(tx)) }
+        Self {
+            validator,
+            to_validation_task: Arc::new(sync::Mutex::new(tx)),
+            to_external_validation_task: None,
+        }
+    }
+
+    /// Returns the sender to the validation tasks for transactions of the given origin.
+    const fn validation_task_for(
+        &self,
+        origin: TransactionOrigin,
+    ) -> &Arc<sync::Mutex<ValidationJobSender>> {
+        match &self.to_external_validation_task {
+            Some(to_external_validation_task) if origin.is_external() => {
+                to_external_validation_task
+            }
+            _ => &self.to_validation_task,
+        }
     }
 }
 
@@ -182,7 +205,7 @@ where
         let (tx, rx) = oneshot::channel();
         {
             let res = {
-                let to_validation_task = self.to_validation_task.clone();
+                let to_validation_task = self.validation_task_for(origin).clone();
                 let to_validation_task = to_validation_task.lock().await;
                 let validator = self.validator.clone();
                 to_validation_task
@@ -212,4 +235,41 @@ where
     fn on_new_head_block(&self, new_tip_block: &SealedBlock) {
         self.validator.on_new_head_block(new_tip_block)
     }
+
+    fn validate_conditional(&self, conditional: &TransactionConditional) -> ConditionalOutcome {
+        self.validator.validate_conditional(conditional)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{noop::MockTransactionValidator, test_utils::MockTransaction};
+
+    #[tokio::test]
+    async fn external_transactions_use_separate_queue() {
+        let (tx, task) = ValidationTask::new();
+        tokio::spawn(task.run());
+        // nothing serves the external queue
+        let (external_tx, _external_task) = ValidationTask::new();
+        let executor = TransactionValidationTaskExecutor {
+            validator: MockTransactionValidator::<MockTransaction>::default(),
+            to_validation_task: Arc::new(sync::Mutex::new(tx)),
+            to_external_validation_task: Some(Arc::new(sync::Mutex::new(external_tx))),
+        };
+
+        for _ in 0..3 {
+            let executor = executor.clone();
+            tokio::spawn(async move {
+                executor
+                    .validate_transaction(TransactionOrigin::External, MockTransaction::eip1559())
+                    .await
+            });
+        }
+
+        let outcome = executor
+            .validate_transaction(TransactionOrigin::Local, MockTransaction::eip1559())
+            .await;
+        assert!(outcome.is_valid());
+    }
 }
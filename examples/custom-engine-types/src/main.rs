@@ -293,6 +293,9 @@ where
             extra_data,
             attributes,
             chain_spec,
+            inclusion_lists,
+            simulation_cache,
+            state_root,
         } = config;
 
         // This reuses the default EthereumPayloadBuilder to build the payload
@@ -308,6 +311,9 @@ where
                 extra_data,
                 attributes: attributes.0,
                 chain_spec,
+                inclusion_lists,
+                simulation_cache,
+                state_root,
             },
             cancel,
             best_payload,
@@ -326,9 +332,12 @@ where
             extra_data,
             attributes,
             chain_spec,
+            inclusion_lists,
+            simulation_cache,
+            state_root,
         } = config;
         <reth_ethereum_payload_builder::EthereumPayloadBuilder as PayloadBuilder<Pool, Client>>::build_empty_payload(&reth_ethereum_payload_builder::EthereumPayloadBuilder::default(),client,
-                                                                                                                     PayloadConfig { initialized_block_env, initialized_cfg, parent_block, extra_data, attributes: attributes.0, chain_spec })
+                                                                                                                     PayloadConfig { initialized_block_env, initialized_cfg, parent_block, extra_data, attributes: attributes.0, chain_spec, inclusion_lists, simulation_cache, state_root })
     }
 }
 
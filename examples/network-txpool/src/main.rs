@@ -86,6 +86,8 @@ impl TransactionValidator for OkValidator {
             state_nonce: transaction.nonce(),
             transaction: ValidTransaction::Valid(transaction),
             propagate: false,
+            authorities: None,
+            simulation: None,
         }
     }
 }